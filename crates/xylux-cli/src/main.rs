@@ -1,4 +1,7 @@
 use clap::{Parser, Subcommand};
+use glam::{Vec3, Vec4};
+use xylux_ecs::{Transform, World};
+use xylux_render::{Material, Mesh, MeshRenderer, NullBackend, Renderer};
 
 #[derive(Parser)]
 #[command(name = "xylux", about = "CLI for Xylux Engine")]
@@ -9,16 +12,81 @@ struct Cli {
 
 #[derive(Subcommand)]
 enum Commands {
-    New { name: String },
-    Run,
-    Build { #[arg(long)] target: Option<String> },
+    New {
+        name: String,
+    },
+    Run {
+        /// Render without a window or GPU using the null backend.
+        #[arg(long)]
+        headless: bool,
+        /// Number of frames to render in headless mode.
+        #[arg(long, default_value_t = 1)]
+        frames: u32,
+    },
+    Build {
+        #[arg(long)]
+        target: Option<String>,
+    },
+}
+
+fn run_headless(frames: u32) {
+    let mut renderer = Renderer::headless(800, 600);
+    let mut world = World::new(1000);
+    world.register_component::<Transform>();
+    world.register_component::<MeshRenderer>();
+
+    let mesh = renderer
+        .add_mesh(Mesh::new(
+            vec![
+                Vec3::new(0.0, -0.5, 0.0),
+                Vec3::new(0.5, 0.5, 0.0),
+                Vec3::new(-0.5, 0.5, 0.0),
+            ],
+            vec![0, 2, 1],
+        ))
+        .expect("triangle mesh is valid");
+    let material = renderer.add_material(Material::new(Vec4::new(0.0, 1.0, 0.0, 1.0)));
+    let triangle = world.spawn_entity();
+    world.insert(triangle, Transform::default());
+    world.insert(triangle, MeshRenderer::new(mesh, material));
+
+    for _ in 0..frames {
+        renderer.render(&mut world);
+    }
+
+    let backend = renderer
+        .backend_as::<NullBackend>()
+        .expect("headless renderer uses the null backend");
+    let commands: usize = backend
+        .frames()
+        .iter()
+        .map(|frame| frame.commands.len())
+        .sum();
+    let draws: usize = backend
+        .frames()
+        .iter()
+        .map(|frame| frame.draw_count())
+        .sum();
+    println!(
+        "Rendered {} headless frames ({} commands, {} draws)",
+        backend.frames().len(),
+        commands,
+        draws
+    );
+    renderer.cleanup();
 }
 
 fn main() {
     let cli = Cli::parse();
     match cli.command {
         Commands::New { name } => println!("Creating project: {}", name),
-        Commands::Run => println!("Running Xylux project"),
+        Commands::Run {
+            headless: true,
+            frames,
+        } => run_headless(frames),
+        Commands::Run {
+            headless: false, ..
+        } => println!("Running Xylux project"),
         Commands::Build { target } => println!("Building for target: {:?}", target),
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,22 @@
+//! Define los componentes `Bounds` y `Static`, usados por el índice espacial.
+
+use crate::component::Component;
+use crate::spatial::Aabb;
+use serde::{Deserialize, Serialize};
+
+/// Caja envolvente de una entidad en espacio local.
+///
+/// El `SpatialIndex` la transforma con el `Transform` de la entidad para obtener
+/// su caja en espacio de mundo.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Bounds(pub Aabb);
+
+impl Component for Bounds {}
+
+/// Marca una entidad como estática: el `SpatialIndex` la guarda en el BVH en lugar
+/// del octree dinámico.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Static;
+
+impl Component for Static {}
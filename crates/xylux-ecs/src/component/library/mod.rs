@@ -4,8 +4,12 @@
 //! Esta separación mantiene el núcleo del ECS agnóstico a los tipos
 //! de componentes específicos del juego o motor.
 
+pub mod bounds;
+pub mod name;
 pub mod transform;
 pub mod velocity;
 
+pub use bounds::{Bounds, Static};
+pub use name::Name;
 pub use transform::Transform;
 pub use velocity::Velocity;
\ No newline at end of file
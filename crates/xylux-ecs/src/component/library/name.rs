@@ -0,0 +1,56 @@
+//! Define el componente `Name`, un nombre legible para identificar entidades.
+
+use crate::component::Component;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Nombre legible de una entidad (e.g. `"Player"`).
+///
+/// El `World` mantiene un índice de nombres para `find_by_name` y
+/// `find_all_by_prefix`. Para que el índice se mantenga correcto, `Name` no admite
+/// acceso mutable: se asigna o cambia con `World::set_name` o `World::insert`.
+///
+/// ```compile_fail
+/// # use xylux_ecs::{Name, World};
+/// let mut world = World::new(1);
+/// let entity = world.spawn_entity();
+/// world.set_name(entity, "Player");
+/// *world.get_mut::<Name>(entity).unwrap() = Name::new("Enemy");
+/// ```
+#[derive(Clone, Default, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Name(String);
+
+impl Name {
+    /// Crea un nuevo nombre.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(name.into())
+    }
+
+    /// Devuelve el nombre como `&str`.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Component for Name {
+    const MUTABLE: bool = false;
+}
+
+impl From<&str> for Name {
+    fn from(name: &str) -> Self {
+        Self::new(name)
+    }
+}
+
+impl From<String> for Name {
+    fn from(name: String) -> Self {
+        Self(name)
+    }
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
@@ -3,9 +3,10 @@
 
 use crate::component::Component;
 use glam::{Quat, Vec3};
+use serde::{Deserialize, Serialize};
 
 /// Componente de Transformación 3D de una entidad.
-#[derive(Clone, Copy, Default, Debug, PartialEq)]
+#[derive(Clone, Copy, Default, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Transform {
     pub position: Vec3,
     pub rotation: Quat,
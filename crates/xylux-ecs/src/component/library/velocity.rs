@@ -3,9 +3,10 @@
 
 use crate::component::Component;
 use glam::Vec3;
+use serde::{Deserialize, Serialize};
 
 /// Componente de Velocidad de una entidad.
-#[derive(Clone, Copy, Default, Debug, PartialEq)]
+#[derive(Clone, Copy, Default, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Velocity(pub Vec3);
 
 impl Component for Velocity {}
\ No newline at end of file
@@ -14,14 +14,28 @@ pub mod library;
 pub mod storage;
 
 pub use id::ComponentId;
-pub use library::{Transform, Velocity};
+pub use library::{Bounds, Name, Static, Transform, Velocity};
 pub use storage::ComponentStorage;
 
 /// Trait que deben implementar todos los componentes ECS.
 ///
 /// El bound `Default` es esencial para inicializar el almacenamiento de
-/// componentes de manera eficiente.
-pub trait Component: 'static + Default {
+/// componentes de manera eficiente. `Send + Sync` permite construir un `World`
+/// en un hilo y moverlo a otro (ver `World::merge`).
+///
+/// # Cambio incompatible
+/// `Send + Sync` se exige desde que existen `World::merge` y
+/// `World::move_entity_to`; antes bastaba con `'static + Default`. Un componente
+/// con datos que no son `Send` o `Sync` (e.g. `Rc` o `RefCell`) tiene que pasar a
+/// sus equivalentes `Arc` o `Mutex`, o guardar fuera del `World` esos datos y
+/// dejar en el componente un índice o un handle. Lo mismo vale para los recursos
+/// (`World::insert_resource`).
+pub trait Component: 'static + Default + Send + Sync {
+    /// `false` para componentes que solo se cambian sustituyéndolos con
+    /// `World::insert` (e.g. `Name`, que el `World` indexa). `World::get_mut` y las
+    /// queries con `&mut T` no compilan para ellos.
+    const MUTABLE: bool = true;
+
     /// Retorna el identificador único del tipo de componente.
     fn component_id() -> ComponentId where Self: Sized {
         ComponentId::of::<Self>()
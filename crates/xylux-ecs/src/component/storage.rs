@@ -8,9 +8,25 @@ use std::any::Any;
 ///
 /// Mantiene un `Vec<T>` para los datos y un `BitVec` para rastrear
 /// qué entidades poseen el componente, permitiendo iteraciones rápidas.
+///
+/// Además guarda, por entidad, el *tick* del `World` en el que su componente se
+/// insertó, se accedió mutablemente o se eliminó por última vez (detección de cambios),
+/// y un registro de esos cambios en orden de tick para consultarlos sin recorrer
+/// todos los huecos.
 pub struct ComponentStorage {
-    data: Box<dyn Any>,
+    data: Box<dyn Any + Send + Sync>,
     pub(crate) bitmask: BitVec,
+    ticks: Vec<u32>,
+    /// `(tick, entidad)` en orden de tick. Una entidad puede tener entradas
+    /// antiguas; la vigente es la que coincide con `ticks`.
+    changes: Vec<(u32, usize)>,
+    type_name: &'static str,
+    item_size: usize,
+    slots: fn(&ComponentStorage) -> usize,
+    /// Crea un almacenamiento vacío del mismo tipo (e.g. en otro `World`).
+    pub(crate) empty: fn(usize) -> ComponentStorage,
+    /// Mueve el componente de una entidad a otro almacenamiento del mismo tipo.
+    pub(crate) transfer: fn(&mut ComponentStorage, usize, &mut ComponentStorage, usize),
 }
 
 impl ComponentStorage {
@@ -22,9 +38,35 @@ impl ComponentStorage {
         Self {
             data: Box::new(vec),
             bitmask: bitvec![0; capacity],
+            ticks: vec![0; capacity],
+            changes: Vec::new(),
+            type_name: std::any::type_name::<T>(),
+            item_size: std::mem::size_of::<T>(),
+            slots: |storage| {
+                storage
+                    .data
+                    .downcast_ref::<Vec<T>>()
+                    .expect("Tipo incorrecto en ComponentStorage::capacity")
+                    .capacity()
+            },
+            empty: Self::new::<T>,
+            transfer: Self::transfer_typed::<T>,
         }
     }
 
+    fn transfer_typed<T: Component>(
+        &mut self,
+        entity: usize,
+        target: &mut ComponentStorage,
+        target_entity: usize,
+    ) {
+        let Some(component) = self.get_mut::<T>(entity).map(std::mem::take) else {
+            return;
+        };
+        self.remove(entity);
+        target.insert(target_entity, component);
+    }
+
     /// Inserta un componente `T` en la entidad indicada.
     pub fn insert<T: Component>(&mut self, entity: usize, component: T) {
         let vec = self
@@ -78,4 +120,69 @@ impl ComponentStorage {
     pub fn has(&self, entity: usize) -> bool {
         entity < self.bitmask.len() && self.bitmask[entity]
     }
-}
\ No newline at end of file
+
+    /// Marca el componente de la entidad como cambiado en `tick`.
+    pub(crate) fn mark_changed(&mut self, entity: usize, tick: u32) {
+        if entity >= self.ticks.len() {
+            self.ticks.resize(entity + 1, 0);
+        }
+        if self.ticks[entity] == tick {
+            return; // Ya registrado en este tick.
+        }
+        self.ticks[entity] = tick;
+        self.changes.push((tick, entity));
+        // Sin entradas antiguas queda como mucho una por entidad, así que compactar
+        // cuando el registro dobla los huecos cuesta O(1) amortizado por cambio.
+        if self.changes.len() > 2 * self.ticks.len().max(32) {
+            let ticks = &self.ticks;
+            self.changes.retain(|&(tick, entity)| ticks[entity] == tick);
+        }
+    }
+
+    /// Tick del último cambio del componente de la entidad (0 si nunca cambió).
+    pub fn changed_tick(&self, entity: usize) -> u32 {
+        self.ticks.get(entity).copied().unwrap_or(0)
+    }
+
+    /// Bytes reservados para los ticks de cambio y su registro.
+    pub(crate) fn tick_bytes(&self) -> usize {
+        self.ticks.capacity() * std::mem::size_of::<u32>()
+            + self.changes.capacity() * std::mem::size_of::<(u32, usize)>()
+    }
+
+    /// IDs de entidades cuyo componente cambió (o se eliminó) en un tick `>= since`,
+    /// en orden de cambio. El coste depende de los cambios desde `since`, no de la
+    /// capacidad.
+    pub fn changed_since(&self, since: u32) -> impl Iterator<Item = usize> + '_ {
+        let start = self.changes.partition_point(|&(tick, _)| tick < since);
+        self.changes[start..]
+            .iter()
+            .filter(|&&(tick, entity)| self.ticks[entity] == tick)
+            .map(|&(_, entity)| entity)
+    }
+
+    /// Nombre del tipo de componente almacenado.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Tamaño en bytes de un componente (sin contar memoria en el heap que posea).
+    pub fn item_size(&self) -> usize {
+        self.item_size
+    }
+
+    /// Número de huecos reservados (uno por entidad posible).
+    pub fn capacity(&self) -> usize {
+        (self.slots)(self)
+    }
+
+    /// Número de entidades que tienen el componente.
+    pub fn len(&self) -> usize {
+        self.bitmask.count_ones()
+    }
+
+    /// `true` si ninguna entidad tiene el componente.
+    pub fn is_empty(&self) -> bool {
+        self.bitmask.not_any()
+    }
+}
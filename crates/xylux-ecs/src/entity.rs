@@ -7,4 +7,13 @@
 pub struct Entity {
     pub id: usize,
     pub version: u32,
-}
\ No newline at end of file
+}
+
+impl Entity {
+    /// Entidad inválida usada como valor por defecto (e.g. en `Relation::default`).
+    /// Nunca está viva en ningún `World`.
+    pub const PLACEHOLDER: Entity = Entity {
+        id: usize::MAX,
+        version: u32::MAX,
+    };
+}
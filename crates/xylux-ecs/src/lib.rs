@@ -21,15 +21,29 @@
 
 pub mod component;
 pub mod entity;
+pub mod pool;
+pub mod prefab;
+pub mod profiling;
 pub mod query;
+pub mod relation;
+pub mod scene;
+pub mod spatial;
+pub mod stats;
 pub mod system;
 pub mod world;
 
 // --- REEXPORTS ---
-pub use component::{Component, ComponentId, Transform, Velocity};
+pub use component::{Bounds, Component, ComponentId, Name, Static, Transform, Velocity};
 pub use entity::Entity;
-pub use query::Query;
-pub use system::{System, TaskGraph};
+pub use pool::{EntityPool, Handle, Pool, PoolStats};
+pub use prefab::{Prefab, PrefabInstance, PrefabNode, PrefabOverrides, PrefabWatcher};
+pub use profiling::{ScheduleStats, SystemStats};
+pub use query::{Query, QueryAccess, QuerySingleError};
+pub use relation::{ChildOf, Related, Relation, RelationKind};
+pub use scene::{Scene, SceneEntity, SceneError};
+pub use spatial::{Aabb, Ray, SpatialIndex, update_spatial_index};
+pub use stats::{ComponentStats, StorageStrategy, WorldStats};
+pub use system::{ScheduleError, System, TaskGraph};
 pub use world::World;
 
 /// --- TEST BÁSICO ---
@@ -94,9 +108,9 @@ mod tests {
         assert!(!world.is_alive(e1));
 
         // La query ya no debería encontrar e1
-        let results: Vec<_> = Query::<(&Tag,)>::new(&mut world).iter().collect();
+        let results: Vec<_> = Query::<(&Tag,)>::new(&mut world).into_iter().collect();
         assert_eq!(results.len(), 1);
-        assert_eq!(results[0].0 .0, 2);
+        assert_eq!(results[0].0.0, 2);
 
         // Al crear una nueva entidad, debería reciclar el ID de e1
         let e3 = world.spawn_entity();
@@ -125,4 +139,184 @@ mod tests {
         assert_eq!(results[0].0, e1);
         assert_eq!(results[1].0, e2);
     }
+
+    #[test]
+    fn test_name_index() {
+        let mut world = World::new(10);
+        let player = world.spawn_entity();
+        world.set_name(player, "Player");
+        let enemy_a = world.spawn_entity();
+        world.set_name(enemy_a, "Enemy_A");
+        let enemy_b = world.spawn_entity();
+        world.insert(enemy_b, Name::new("Enemy_B"));
+
+        assert_eq!(world.find_by_name("Player"), Some(player));
+        assert_eq!(world.find_all_by_prefix("Enemy_"), vec![enemy_a, enemy_b]);
+        assert_eq!(
+            world.entity_label(player),
+            format!("Player ({}v0)", player.id)
+        );
+        assert!(format!("{:?}", world).contains("Enemy_B"));
+
+        // Renombrar actualiza el índice.
+        world.set_name(enemy_b, "Boss");
+        assert_eq!(world.find_all_by_prefix("Enemy_"), vec![enemy_a]);
+        assert_eq!(world.find_by_name("Boss"), Some(enemy_b));
+    }
+
+    #[test]
+    fn test_name_index_survives_despawn_and_recycle() {
+        let mut world = World::new(10);
+        let player = world.spawn_entity();
+        world.set_name(player, "Player");
+
+        world.despawn_entity(player);
+        assert_eq!(world.find_by_name("Player"), None);
+
+        // El ID reciclado no hereda el nombre de la entidad anterior.
+        let recycled = world.spawn_entity();
+        assert_eq!(recycled.id, player.id);
+        assert_eq!(world.find_by_name("Player"), None);
+        assert_eq!(world.name(recycled), None);
+
+        world.set_name(recycled, "Player");
+        assert_eq!(world.find_by_name("Player"), Some(recycled));
+    }
+
+    #[test]
+    fn test_move_entity_between_worlds() {
+        let mut loading = World::new(10);
+        loading.register_component::<Tag>();
+        let mut gameplay = World::new(10);
+        let other = gameplay.spawn_entity();
+
+        let entity = loading.spawn_entity();
+        loading.insert(entity, Tag(7));
+        loading.set_name(entity, "Door");
+
+        let moved = loading.move_entity_to(&mut gameplay, entity);
+        assert_ne!(moved, other);
+        assert!(!loading.is_alive(entity));
+        assert_eq!(loading.find_by_name("Door"), None);
+        assert_eq!(gameplay.get::<Tag>(moved), Some(&Tag(7)));
+        assert_eq!(gameplay.find_by_name("Door"), Some(moved));
+        assert_eq!(Query::<(&Tag,)>::new(&mut loading).iter().count(), 0);
+    }
+
+    #[test]
+    fn test_move_entities_checks_everything_before_spawning() {
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+
+        let mut loading = World::new(10);
+        let mut gameplay = World::new(2);
+        let entities: Vec<_> = (0..3).map(|_| loading.spawn_entity()).collect();
+
+        // Una entidad muerta al final no deja entidades vacías en el destino.
+        let dead = loading.spawn_entity();
+        loading.despawn_entity(dead);
+        let group = [entities[0], dead];
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            loading.move_entities_to(&mut gameplay, &group)
+        }));
+        assert!(result.is_err());
+        assert_eq!(gameplay.stats().alive, 0);
+
+        // Tampoco si no caben todas.
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            loading.move_entities_to(&mut gameplay, &entities)
+        }));
+        assert!(result.is_err());
+        assert_eq!(gameplay.stats().alive, 0);
+        assert!(entities.iter().all(|&entity| loading.is_alive(entity)));
+    }
+
+    #[test]
+    fn test_merge_world_built_in_thread() {
+        let chunk = std::thread::spawn(|| {
+            let mut chunk = World::new(10);
+            chunk.register_component::<Tag>();
+            chunk.register_relation::<ChildOf>();
+            let root = chunk.spawn_entity();
+            let child = chunk.spawn_entity();
+            chunk.insert(root, Tag(1));
+            chunk.insert(child, Tag(2));
+            chunk.relate::<ChildOf>(child, root);
+            let removed = chunk.spawn_entity();
+            chunk.despawn_entity(removed);
+            chunk.insert_resource(5u32);
+            chunk
+        })
+        .join()
+        .unwrap();
+
+        let mut world = World::new(10);
+        world.register_component::<Tag>();
+        let existing = world.spawn_entity();
+        world.insert(existing, Tag(0));
+
+        let remap = world.merge(chunk);
+        assert_eq!(remap.len(), 2);
+        let root = remap[&Entity { id: 0, version: 0 }];
+        let child = remap[&Entity { id: 1, version: 0 }];
+        assert_eq!(world.get::<Tag>(root), Some(&Tag(1)));
+        assert_eq!(world.relation_target::<ChildOf>(child), Some(root));
+        assert_eq!(world.relation_sources::<ChildOf>(root), vec![child]);
+        assert_eq!(world.resource::<u32>(), Some(&5));
+        assert_eq!(Query::<(&Tag,)>::new(&mut world).iter().count(), 3);
+    }
+
+    #[test]
+    fn test_query_get_and_single() {
+        let mut world = World::new(10);
+        world.register_component::<Tag>();
+        world.register_component::<Transform>();
+
+        let camera = world.spawn_entity();
+        world.insert(camera, Tag(1));
+        world.insert(camera, Transform::default());
+        let other = world.spawn_entity();
+        world.insert(other, Tag(2));
+
+        let mut query = Query::<(&Tag, &Transform)>::new(&mut world);
+        assert_eq!(query.get(camera).unwrap().0, &Tag(1));
+        assert!(query.get(other).is_none());
+        assert_eq!(query.single().0, &Tag(1));
+
+        assert!(matches!(
+            Query::<(&Tag,)>::new(&mut world).get_single(),
+            Err(QuerySingleError::MultipleEntities(_))
+        ));
+        world.set_active(camera, false);
+        assert!(matches!(
+            Query::<(&Tag, &Transform)>::new(&mut world).get_single(),
+            Err(QuerySingleError::NoEntities(_))
+        ));
+    }
+
+    #[test]
+    fn test_sorted_query_and_stable_order() {
+        let mut world = World::new(10);
+        world.set_stable_order(true);
+        world.register_component::<Tag>();
+
+        let entities: Vec<Entity> = (0..5).map(|_| world.spawn_entity()).collect();
+        for (&entity, tag) in entities.iter().zip([3, 1, 3, 0, 2]) {
+            world.insert(entity, Tag(tag));
+        }
+        // Sin modo estable se reciclaría el 3 (LIFO); en modo estable, el más bajo.
+        world.despawn_entity(entities[1]);
+        world.despawn_entity(entities[3]);
+        assert_eq!(world.spawn_entity().id, 1);
+        assert_eq!(world.spawn_entity().id, 3);
+
+        let sorted: Vec<(Entity, u32)> = Query::<(Entity, &Tag)>::new(&mut world)
+            .iter_sorted_by_key(|(_, tag)| tag.0)
+            .map(|(entity, tag)| (entity, tag.0))
+            .collect();
+        // Los empates conservan el orden por ID.
+        assert_eq!(
+            sorted,
+            vec![(entities[4], 2), (entities[0], 3), (entities[2], 3)]
+        );
+    }
 }
@@ -0,0 +1,449 @@
+//! # Módulo de Pooling
+//!
+//! Reutilización de objetos para evitar asignaciones en caliente (e.g. balas).
+//!
+//! - `Pool<T>`: slab genérico con handles estables (`Handle<T>`) y comprobación de
+//!   generación, de modo que un handle a un hueco reutilizado no accede al nuevo valor.
+//! - `EntityPool`: pool de entidades del ECS. En vez de destruir una entidad, `release`
+//!   la marca como *inactiva* (las queries la ignoran) y `acquire` la reactiva con sus
+//!   componentes ya creados.
+//!
+//! Ambos llevan estadísticas de aciertos/fallos en `PoolStats`.
+
+use crate::component::Component;
+use crate::entity::Entity;
+use crate::world::World;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+/// Estadísticas de uso de un pool.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PoolStats {
+    /// Peticiones servidas reutilizando un hueco/entidad libre.
+    pub hits: u64,
+    /// Peticiones que necesitaron crear un hueco/entidad nuevo.
+    pub misses: u64,
+    /// Objetos devueltos al pool.
+    pub releases: u64,
+}
+
+impl PoolStats {
+    /// Proporción de peticiones servidas sin crear nada nuevo (0.0..=1.0).
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+impl fmt::Display for PoolStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "hits: {}, misses: {}, releases: {}, hit rate: {:.1}%",
+            self.hits,
+            self.misses,
+            self.releases,
+            self.hit_rate() * 100.0
+        )
+    }
+}
+
+/// Handle estable a un valor de un `Pool<T>`.
+///
+/// `(index, generation)` funciona como `Entity`: al liberar un hueco su generación
+/// se incrementa y los handles antiguos dejan de ser válidos.
+pub struct Handle<T> {
+    index: u32,
+    generation: u32,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    /// Handle que nunca es válido; valor por defecto.
+    pub const INVALID: Self = Self {
+        index: u32::MAX,
+        generation: u32::MAX,
+        _marker: PhantomData,
+    };
+
+    /// Índice del hueco dentro del pool.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Generación del hueco cuando se creó el handle.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+// Implementaciones manuales para no exigir `T: Clone`, `T: Eq`, etc.
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index && self.generation == other.generation
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> PartialOrd for Handle<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Handle<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.index, self.generation).cmp(&(other.index, other.generation))
+    }
+}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+        self.generation.hash(state);
+    }
+}
+
+impl<T> Default for Handle<T> {
+    fn default() -> Self {
+        Self::INVALID
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Handle<{}>({}v{})",
+            std::any::type_name::<T>(),
+            self.index,
+            self.generation
+        )
+    }
+}
+
+/// Un `Handle<T>` puede guardarse en entidades (e.g. `Handle<Mesh>`).
+impl<T: 'static> Component for Handle<T> {}
+
+struct Slot<T> {
+    generation: u32,
+    value: Option<T>,
+}
+
+/// Slab genérico con handles generacionales.
+pub struct Pool<T> {
+    slots: Vec<Slot<T>>,
+    free: Vec<u32>,
+    len: usize,
+    stats: PoolStats,
+}
+
+impl<T> Default for Pool<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Pool<T> {
+    /// Crea un pool vacío.
+    pub fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+            stats: PoolStats::default(),
+        }
+    }
+
+    /// Crea un pool con espacio reservado para `capacity` valores.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            slots: Vec::with_capacity(capacity),
+            ..Self::new()
+        }
+    }
+
+    /// Inserta un valor, reutilizando un hueco libre si lo hay.
+    pub fn insert(&mut self, value: T) -> Handle<T> {
+        if let Some(index) = self.free.pop() {
+            let slot = &mut self.slots[index as usize];
+            slot.value = Some(value);
+            self.len += 1;
+            self.stats.hits += 1;
+            return Handle {
+                index,
+                generation: slot.generation,
+                _marker: PhantomData,
+            };
+        }
+
+        let index = u32::try_from(self.slots.len()).expect("Pool lleno");
+        self.slots.push(Slot {
+            generation: 0,
+            value: Some(value),
+        });
+        self.len += 1;
+        self.stats.misses += 1;
+        Handle {
+            index,
+            generation: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Elimina el valor de un handle y lo devuelve. Los handles a ese hueco quedan invalidados.
+    pub fn remove(&mut self, handle: Handle<T>) -> Option<T> {
+        let slot = self.slots.get_mut(handle.index as usize)?;
+        if slot.generation != handle.generation {
+            return None;
+        }
+        let value = slot.value.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(handle.index);
+        self.len -= 1;
+        self.stats.releases += 1;
+        Some(value)
+    }
+
+    /// Referencia inmutable al valor de un handle válido.
+    pub fn get(&self, handle: Handle<T>) -> Option<&T> {
+        self.slots
+            .get(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation)
+            .and_then(|slot| slot.value.as_ref())
+    }
+
+    /// Referencia mutable al valor de un handle válido.
+    pub fn get_mut(&mut self, handle: Handle<T>) -> Option<&mut T> {
+        self.slots
+            .get_mut(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation)
+            .and_then(|slot| slot.value.as_mut())
+    }
+
+    /// Comprueba si el handle sigue siendo válido.
+    pub fn contains(&self, handle: Handle<T>) -> bool {
+        self.get(handle).is_some()
+    }
+
+    /// Número de valores vivos.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// `true` si no hay valores vivos.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Número de huecos creados (vivos + libres).
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Estadísticas de aciertos/fallos.
+    pub fn stats(&self) -> PoolStats {
+        self.stats
+    }
+
+    /// Itera sobre los valores vivos y sus handles.
+    pub fn iter(&self) -> impl Iterator<Item = (Handle<T>, &T)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            let handle = Handle {
+                index: index as u32,
+                generation: slot.generation,
+                _marker: PhantomData,
+            };
+            slot.value.as_ref().map(|value| (handle, value))
+        })
+    }
+
+    /// Itera mutablemente sobre los valores vivos y sus handles.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Handle<T>, &mut T)> {
+        self.slots
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, slot)| {
+                let handle = Handle {
+                    index: index as u32,
+                    generation: slot.generation,
+                    _marker: PhantomData,
+                };
+                slot.value.as_mut().map(|value| (handle, value))
+            })
+    }
+}
+
+/// Inicializador de las entidades de un `EntityPool`.
+type EntityInit = Box<dyn Fn(&mut World, Entity) + Send + Sync>;
+
+/// Pool de entidades con componentes preconfigurados.
+///
+/// `init` se ejecuta al crear una entidad nueva y al reutilizar una inactiva, de
+/// modo que cada `acquire` devuelve la entidad con sus valores iniciales sin tener
+/// que crear ni destruir almacenamiento.
+pub struct EntityPool {
+    init: EntityInit,
+    inactive: Vec<Entity>,
+    stats: PoolStats,
+}
+
+impl EntityPool {
+    /// Crea un pool cuyas entidades se inicializan con `init`.
+    pub fn new<F: Fn(&mut World, Entity) + Send + Sync + 'static>(init: F) -> Self {
+        Self {
+            init: Box::new(init),
+            inactive: Vec::new(),
+            stats: PoolStats::default(),
+        }
+    }
+
+    /// Crea `count` entidades inactivas por adelantado.
+    pub fn prewarm(&mut self, world: &mut World, count: usize) {
+        for _ in 0..count {
+            let entity = world.spawn_entity();
+            (self.init)(world, entity);
+            world.set_active(entity, false);
+            self.inactive.push(entity);
+        }
+    }
+
+    /// Obtiene una entidad activa e inicializada, reutilizando una inactiva si es posible.
+    pub fn acquire(&mut self, world: &mut World) -> Entity {
+        while let Some(entity) = self.inactive.pop() {
+            // Pudo haberse destruido por fuera del pool mientras estaba inactiva.
+            if world.is_alive(entity) {
+                world.set_active(entity, true);
+                (self.init)(world, entity);
+                self.stats.hits += 1;
+                return entity;
+            }
+        }
+
+        let entity = world.spawn_entity();
+        (self.init)(world, entity);
+        self.stats.misses += 1;
+        entity
+    }
+
+    /// Devuelve una entidad al pool: queda inactiva en lugar de destruirse.
+    pub fn release(&mut self, world: &mut World, entity: Entity) {
+        if !world.is_alive(entity) || !world.is_active(entity) {
+            return;
+        }
+        world.set_active(entity, false);
+        self.inactive.push(entity);
+        self.stats.releases += 1;
+    }
+
+    /// Número de entidades inactivas disponibles.
+    pub fn inactive_count(&self) -> usize {
+        self.inactive.len()
+    }
+
+    /// Estadísticas de aciertos/fallos.
+    pub fn stats(&self) -> PoolStats {
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::{Transform, Velocity};
+    use crate::query::Query;
+    use glam::Vec3;
+
+    #[test]
+    fn test_pool_handles_and_generations() {
+        let mut pool = Pool::new();
+        let a = pool.insert("a");
+        let b = pool.insert("b");
+        assert_eq!(pool.remove(a), Some("a"));
+        assert_eq!(pool.get(a), None);
+
+        // El hueco se reutiliza con otra generación: el handle antiguo sigue inválido.
+        let c = pool.insert("c");
+        assert_eq!(c.index(), a.index());
+        assert_ne!(c, a);
+        assert_eq!(pool.get(a), None);
+        assert_eq!(pool.remove(a), None);
+        assert_eq!(pool.get(c), Some(&"c"));
+        assert_eq!(pool.get(b), Some(&"b"));
+        assert_eq!(pool.get(Handle::default()), None);
+
+        assert_eq!(pool.len(), 2);
+        assert_eq!(pool.capacity(), 2);
+        assert_eq!(
+            pool.stats(),
+            PoolStats {
+                hits: 1,
+                misses: 2,
+                releases: 1
+            }
+        );
+        assert_eq!(
+            pool.iter().map(|(_, v)| *v).collect::<Vec<_>>(),
+            vec!["c", "b"]
+        );
+    }
+
+    #[test]
+    fn test_entity_pool_recycles_inactive_entities() {
+        let mut world = World::new(16);
+        world.register_component::<Transform>();
+        world.register_component::<Velocity>();
+
+        let mut bullets = EntityPool::new(|world, bullet| {
+            world.insert(bullet, Transform::default());
+            world.insert(bullet, Velocity(Vec3::X));
+        });
+        bullets.prewarm(&mut world, 2);
+        assert_eq!(Query::<(&Transform,)>::new(&mut world).iter().count(), 0);
+
+        let first = bullets.acquire(&mut world);
+        let second = bullets.acquire(&mut world);
+        let third = bullets.acquire(&mut world);
+        assert_eq!(
+            bullets.stats(),
+            PoolStats {
+                hits: 2,
+                misses: 1,
+                releases: 0
+            }
+        );
+
+        world.get_mut::<Transform>(first).unwrap().position = Vec3::ONE;
+        bullets.release(&mut world, first);
+        assert!(world.is_alive(first));
+        assert!(!world.is_active(first));
+        assert_eq!(
+            Query::<(&Transform, &Velocity)>::new(&mut world)
+                .iter()
+                .count(),
+            2
+        );
+
+        // Al reutilizarla recupera sus valores iniciales.
+        let reused = bullets.acquire(&mut world);
+        assert_eq!(reused, first);
+        assert_eq!(world.get::<Transform>(reused).unwrap().position, Vec3::ZERO);
+        assert_eq!(Query::<(Entity,)>::new(&mut world).iter().count(), 3);
+        assert!(world.is_active(second) && world.is_active(third));
+        assert_eq!(bullets.stats().hit_rate(), 0.75);
+    }
+}
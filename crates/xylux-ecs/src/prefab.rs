@@ -0,0 +1,474 @@
+//! # Módulo de Prefabs
+//!
+//! Un `Prefab` es un árbol serializado de entidades con valores de componentes, al
+//! estilo de las declaraciones `entity Player { ... }` de Alux:
+//!
+//! ```ron
+//! (
+//!     root: (
+//!         name: "Player",
+//!         components: { "Transform": (position: (0.0, 1.0, 0.0), rotation: (0.0, 0.0, 0.0, 1.0)) },
+//!         children: [
+//!             (name: "Gun", components: { "Velocity": ((0.0, 0.0, 0.0)) }),
+//!         ],
+//!     ),
+//! )
+//! ```
+//!
+//! `World::spawn_prefab` instancia el árbol (los hijos se enlazan con `ChildOf`) y
+//! `World::spawn_prefab_with` permite sobrescribir valores por instancia. Los nodos se
+//! identifican por su ruta de índices: `""` es la raíz y cada hijo añade su índice,
+//! precedido de su nombre si tiene (`"Gun#0"`, `"Gun#0/1"`), así que dos hermanos con
+//! el mismo nombre no se confunden. Cada entidad
+//! creada recibe un `PrefabInstance` que recuerda su origen, de modo que un
+//! `PrefabWatcher` puede recargar el fichero y actualizar las instancias vivas sin
+//! tocar los valores sobrescritos.
+
+use crate::component::{Component, ComponentId};
+use crate::entity::Entity;
+use crate::relation::ChildOf;
+use crate::scene::{SceneError, from_ron, to_ron, to_value};
+use crate::world::World;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+
+/// Nodo de un prefab: una entidad con sus componentes y sus hijos.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct PrefabNode {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub components: BTreeMap<String, ron::Value>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<PrefabNode>,
+}
+
+/// Árbol de entidades preconfiguradas.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Prefab {
+    pub root: PrefabNode,
+    /// Identificador con el que se etiquetan las instancias: la ruta si se cargó de
+    /// un fichero y uno generado si no. Debe ser único; `update_prefab_instances`
+    /// actualiza todas las instancias con el mismo `id`.
+    #[serde(skip, default = "unique_prefab_id")]
+    pub id: String,
+}
+
+/// `id` único para prefabs que no vienen de un fichero.
+fn unique_prefab_id() -> String {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    format!("prefab#{}", NEXT.fetch_add(1, Ordering::Relaxed))
+}
+
+impl Default for Prefab {
+    fn default() -> Self {
+        Self::new(PrefabNode::default())
+    }
+}
+
+impl Prefab {
+    /// Crea un prefab con un `id` único.
+    pub fn new(root: PrefabNode) -> Self {
+        Self {
+            root,
+            id: unique_prefab_id(),
+        }
+    }
+
+    /// Parsea un prefab desde texto RON.
+    pub fn from_ron(source: &str) -> Result<Self, SceneError> {
+        from_ron(source)
+    }
+
+    /// Serializa el prefab a RON legible.
+    pub fn to_ron(&self) -> Result<String, SceneError> {
+        to_ron(self)
+    }
+
+    /// Carga un prefab desde un fichero `.ron`; su `id` es la ruta del fichero.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SceneError> {
+        let path = path.as_ref();
+        let mut prefab = Self::from_ron(&std::fs::read_to_string(path)?)?;
+        prefab.id = path.display().to_string();
+        Ok(prefab)
+    }
+
+    /// Busca un nodo por su ruta (ver `PrefabInstance::node`).
+    pub fn node(&self, path: &str) -> Option<&PrefabNode> {
+        if path.is_empty() {
+            return Some(&self.root);
+        }
+        path.split('/').try_fold(&self.root, |node, segment| {
+            let (name, index) = match segment.rsplit_once('#') {
+                Some((name, index)) => (Some(name), index),
+                None => (None, segment),
+            };
+            let child = node.children.get(index.parse::<usize>().ok()?)?;
+            (child.name.as_deref() == name).then_some(child)
+        })
+    }
+}
+
+/// Segmento de ruta de un hijo: `nombre#índice`, o solo el índice si no tiene nombre.
+fn node_segment(index: usize, node: &PrefabNode) -> String {
+    match &node.name {
+        Some(name) => format!("{}#{}", name, index),
+        None => index.to_string(),
+    }
+}
+
+fn child_path(parent: &str, index: usize, node: &PrefabNode) -> String {
+    let segment = node_segment(index, node);
+    if parent.is_empty() {
+        segment
+    } else {
+        format!("{}/{}", parent, segment)
+    }
+}
+
+/// Valores que sustituyen a los del prefab en una instancia concreta.
+///
+/// Las claves son la ruta del nodo (`""` para la raíz, `"Gun#0"`, `"Gun#0/Muzzle#2"`...)
+/// y el nombre registrado del componente.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PrefabOverrides {
+    values: BTreeMap<String, BTreeMap<String, ron::Value>>,
+}
+
+impl PrefabOverrides {
+    /// Crea un conjunto de overrides vacío.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sobrescribe el componente `component` del nodo `node` con `value`.
+    pub fn set<T: Serialize>(
+        mut self,
+        node: &str,
+        component: &str,
+        value: &T,
+    ) -> Result<Self, SceneError> {
+        let value = to_value(value).map_err(SceneError::Serialize)?;
+        self.values
+            .entry(node.to_string())
+            .or_default()
+            .insert(component.to_string(), value);
+        Ok(self)
+    }
+
+    fn get(&self, node: &str) -> Option<&BTreeMap<String, ron::Value>> {
+        self.values.get(node)
+    }
+}
+
+/// Componente que enlaza una entidad con el nodo del prefab del que procede.
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct PrefabInstance {
+    /// `Prefab::id` del prefab de origen.
+    pub prefab: String,
+    /// Ruta del nodo dentro del prefab.
+    pub node: String,
+    /// Componentes sobrescritos en esta instancia; las recargas no los modifican.
+    pub overridden: Vec<String>,
+}
+
+impl Component for PrefabInstance {}
+
+impl World {
+    /// Instancia un prefab y devuelve la entidad raíz.
+    pub fn spawn_prefab(&mut self, prefab: &Prefab) -> Result<Entity, SceneError> {
+        self.spawn_prefab_with(prefab, &PrefabOverrides::default())
+    }
+
+    /// Instancia un prefab aplicando `overrides` y devuelve la entidad raíz.
+    ///
+    /// Si algo falla, las entidades ya creadas se eliminan.
+    pub fn spawn_prefab_with(
+        &mut self,
+        prefab: &Prefab,
+        overrides: &PrefabOverrides,
+    ) -> Result<Entity, SceneError> {
+        self.register_component::<PrefabInstance>();
+        self.register_relation::<ChildOf>();
+
+        let mut spawned = Vec::new();
+        let result = self.spawn_prefab_node(
+            prefab,
+            &prefab.root,
+            String::new(),
+            None,
+            overrides,
+            &mut spawned,
+        );
+        if result.is_err() {
+            for &entity in &spawned {
+                self.despawn_entity(entity);
+            }
+        }
+        result
+    }
+
+    fn spawn_prefab_node(
+        &mut self,
+        prefab: &Prefab,
+        node: &PrefabNode,
+        path: String,
+        parent: Option<Entity>,
+        overrides: &PrefabOverrides,
+        spawned: &mut Vec<Entity>,
+    ) -> Result<Entity, SceneError> {
+        let entity = self.spawn_entity();
+        spawned.push(entity);
+        if let Some(name) = &node.name {
+            self.set_name(entity, name.as_str());
+        }
+        if let Some(parent) = parent {
+            self.relate::<ChildOf>(entity, parent);
+        }
+
+        let node_overrides = overrides.get(&path);
+        for (component, value) in &node.components {
+            let value = node_overrides
+                .and_then(|o| o.get(component))
+                .unwrap_or(value);
+            self.insert_serialized(entity, component, value)?;
+        }
+        // Overrides de componentes que el nodo no declara.
+        for (component, value) in node_overrides.into_iter().flatten() {
+            if !node.components.contains_key(component) {
+                self.insert_serialized(entity, component, value)?;
+            }
+        }
+
+        let instance = PrefabInstance {
+            prefab: prefab.id.clone(),
+            node: path.clone(),
+            overridden: node_overrides
+                .map(|o| o.keys().cloned().collect())
+                .unwrap_or_default(),
+        };
+        self.insert(entity, instance);
+
+        for (index, child) in node.children.iter().enumerate() {
+            let child_path = child_path(&path, index, child);
+            self.spawn_prefab_node(prefab, child, child_path, Some(entity), overrides, spawned)?;
+        }
+        Ok(entity)
+    }
+
+    /// Reaplica los valores de `prefab` a todas sus instancias vivas, respetando los
+    /// componentes sobrescritos. Devuelve cuántas entidades se actualizaron.
+    ///
+    /// Solo se actualizan valores de componentes: los nodos añadidos o eliminados en
+    /// el prefab no crean ni destruyen entidades.
+    pub fn update_prefab_instances(&mut self, prefab: &Prefab) -> Result<usize, SceneError> {
+        let instances: Vec<(Entity, PrefabInstance)> = self
+            .entities_with_component(ComponentId::of::<PrefabInstance>())
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|entity| {
+                self.get::<PrefabInstance>(entity)
+                    .map(|i| (entity, i.clone()))
+            })
+            .filter(|(_, instance)| instance.prefab == prefab.id)
+            .collect();
+
+        let mut updated = 0;
+        for (entity, instance) in instances {
+            let Some(node) = prefab.node(&instance.node) else {
+                continue;
+            };
+            for (component, value) in &node.components {
+                if !instance.overridden.contains(component) {
+                    self.insert_serialized(entity, component, value)?;
+                }
+            }
+            updated += 1;
+        }
+        Ok(updated)
+    }
+}
+
+/// Vigila un fichero de prefab y lo recarga cuando cambia.
+pub struct PrefabWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    prefab: Prefab,
+    /// Si es `true`, `poll` actualiza las instancias vivas tras cada recarga.
+    pub update_instances: bool,
+}
+
+impl PrefabWatcher {
+    /// Carga el prefab y empieza a vigilar su fichero.
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self, SceneError> {
+        let path = path.into();
+        let modified = std::fs::metadata(&path)?.modified().ok();
+        let prefab = Prefab::load(&path)?;
+        Ok(Self {
+            path,
+            modified,
+            prefab,
+            update_instances: true,
+        })
+    }
+
+    /// Prefab cargado actualmente.
+    pub fn prefab(&self) -> &Prefab {
+        &self.prefab
+    }
+
+    /// Comprueba si el fichero ha cambiado. Si es así lo recarga y, con
+    /// `update_instances`, actualiza las instancias de `world`.
+    ///
+    /// Devuelve `true` si hubo recarga. Si el fichero nuevo no es válido se devuelve
+    /// el error, se conserva la versión anterior y se reintenta en el siguiente `poll`;
+    /// lo mismo si falla la actualización de las instancias.
+    pub fn poll(&mut self, world: &mut World) -> Result<bool, SceneError> {
+        let modified = std::fs::metadata(&self.path)?.modified().ok();
+        if modified == self.modified {
+            return Ok(false);
+        }
+        self.prefab = Prefab::load(&self.path)?;
+        if self.update_instances {
+            world.update_prefab_instances(&self.prefab)?;
+        }
+        self.modified = modified;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::{Transform, Velocity};
+    use glam::Vec3;
+    use std::time::Duration;
+
+    const PLAYER: &str = r#"(
+        root: (
+            name: "Player",
+            components: { "Transform": (position: (0.0, 1.0, 0.0), rotation: (0.0, 0.0, 0.0, 1.0)) },
+            children: [
+                (name: "Gun", components: { "Velocity": ((1.0, 0.0, 0.0)) }),
+                (components: { "Velocity": ((0.0, 0.0, 1.0)) }),
+                (name: "Gun", components: { "Velocity": ((0.0, 1.0, 0.0)) }),
+            ],
+        ),
+    )"#;
+
+    fn world() -> World {
+        let mut world = World::new(32);
+        world.register_serializable::<Transform>("Transform");
+        world.register_serializable::<Velocity>("Velocity");
+        world
+    }
+
+    #[test]
+    fn test_spawn_prefab_tree_with_overrides() {
+        let mut world = world();
+        let prefab = Prefab::from_ron(PLAYER).unwrap();
+        assert!(prefab.node("1").is_some());
+        assert!(prefab.node("Gun").is_none());
+        assert!(prefab.node("Gun#1").is_none());
+        assert_ne!(prefab.id, Prefab::from_ron(PLAYER).unwrap().id);
+
+        let overrides = PrefabOverrides::new()
+            .set("Gun#2", "Velocity", &Velocity(Vec3::NEG_Z))
+            .unwrap();
+        let root = world.spawn_prefab_with(&prefab, &overrides).unwrap();
+
+        assert_eq!(world.name(root), Some("Player"));
+        assert_eq!(world.get::<Transform>(root).unwrap().position, Vec3::Y);
+
+        let children = world.relation_sources::<ChildOf>(root);
+        assert_eq!(children.len(), 3);
+        assert_eq!(world.find_by_name("Gun"), Some(children[0]));
+        assert_eq!(world.get::<Velocity>(children[0]), Some(&Velocity(Vec3::X)));
+        assert_eq!(world.get::<Velocity>(children[1]), Some(&Velocity(Vec3::Z)));
+        assert_eq!(world.get::<PrefabInstance>(children[1]).unwrap().node, "1");
+        // El segundo "Gun" tiene su propia ruta y su override.
+        assert_eq!(
+            world.get::<PrefabInstance>(children[2]).unwrap().node,
+            "Gun#2"
+        );
+        assert_eq!(
+            world.get::<Velocity>(children[2]),
+            Some(&Velocity(Vec3::NEG_Z))
+        );
+
+        // Otro prefab parseado del mismo texto no comparte instancias.
+        let other =
+            Prefab::from_ron(&PLAYER.replace("(0.0, 1.0, 0.0)", "(9.0, 9.0, 9.0)")).unwrap();
+        assert_eq!(world.update_prefab_instances(&other).unwrap(), 0);
+        assert_eq!(world.get::<Transform>(root).unwrap().position, Vec3::Y);
+    }
+
+    #[test]
+    fn test_failed_spawn_leaves_no_entities() {
+        let mut world = world();
+        let prefab = Prefab::from_ron(
+            r#"(root: (name: "Broken", children: [(components: { "Ghost": () })]))"#,
+        )
+        .unwrap();
+        let error = world.spawn_prefab(&prefab).unwrap_err();
+        assert!(matches!(error, SceneError::UnknownComponent { .. }));
+        assert_eq!(world.find_by_name("Broken"), None);
+    }
+
+    #[test]
+    fn test_watcher_hot_reloads_instances() {
+        let path = std::env::temp_dir().join(format!("xylux_prefab_{}.ron", std::process::id()));
+        std::fs::write(&path, PLAYER).unwrap();
+
+        let mut world = world();
+        let mut watcher = PrefabWatcher::new(&path).unwrap();
+        let plain = world.spawn_prefab(watcher.prefab()).unwrap();
+        let overrides = PrefabOverrides::new()
+            .set("", "Transform", &Transform::default())
+            .unwrap();
+        let custom = world
+            .spawn_prefab_with(watcher.prefab(), &overrides)
+            .unwrap();
+        assert!(!watcher.poll(&mut world).unwrap());
+
+        // Garantiza un mtime distinto aunque el sistema de ficheros tenga poca resolución.
+        let modified = SystemTime::now() + Duration::from_secs(5);
+        let write_at = |text: &str, modified: SystemTime| {
+            std::fs::write(&path, text).unwrap();
+            let file = std::fs::File::options().write(true).open(&path).unwrap();
+            file.set_modified(modified).unwrap();
+        };
+        let write = |text: &str| write_at(text, modified);
+
+        // Un fichero a medio escribir falla, y se reintenta aunque el mtime no cambie.
+        write("(root: (");
+        assert!(matches!(
+            watcher.poll(&mut world),
+            Err(SceneError::Parse(_))
+        ));
+        write(&PLAYER.replace("(0.0, 1.0, 0.0)", "(5.0, 1.0, 0.0)"));
+        assert!(watcher.poll(&mut world).unwrap());
+        assert_eq!(
+            world.get::<Transform>(plain).unwrap().position,
+            Vec3::new(5.0, 1.0, 0.0)
+        );
+        // El override de la segunda instancia se conserva.
+        assert_eq!(world.get::<Transform>(custom).unwrap().position, Vec3::ZERO);
+
+        // Si falla la actualización de las instancias también se reintenta.
+        let later = modified + Duration::from_secs(1);
+        write_at(&PLAYER.replace("((1.0, 0.0, 0.0))", "(\"fast\")"), later);
+        assert!(watcher.poll(&mut world).is_err());
+        write_at(&PLAYER.replace("(0.0, 1.0, 0.0)", "(6.0, 1.0, 0.0)"), later);
+        assert!(watcher.poll(&mut world).unwrap());
+        assert_eq!(
+            world.get::<Transform>(plain).unwrap().position,
+            Vec3::new(6.0, 1.0, 0.0)
+        );
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,359 @@
+//! # Módulo de Profiling
+//!
+//! Estadísticas de ejecución del `TaskGraph`. Cada llamada a `TaskGraph::run` con el
+//! profiling activo registra, por sistema, el tiempo de pared, el número de llamadas y
+//! cuántas entidades entregaron sus queries. Los datos se publican en el recurso
+//! `ScheduleStats` del `World`.
+//!
+//! Además de medias y percentiles sobre una ventana móvil de frames, los últimos
+//! frames se pueden volcar en formato *Chrome trace-event* JSON (visible en
+//! `chrome://tracing` o Perfetto).
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write as _;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Tamaño por defecto de la ventana móvil (en frames).
+pub const DEFAULT_STATS_WINDOW: usize = 120;
+
+/// Medición de una ejecución de un sistema dentro de un frame.
+#[derive(Clone, Debug)]
+pub struct SystemSample {
+    pub name: String,
+    pub stage: usize,
+    pub start: Instant,
+    pub duration: Duration,
+    pub entities: usize,
+}
+
+/// Estadísticas acumuladas de un sistema.
+#[derive(Clone, Debug)]
+pub struct SystemStats {
+    pub name: String,
+    /// Número total de ejecuciones.
+    pub calls: u64,
+    /// Tiempo total acumulado en todas las ejecuciones.
+    pub total_time: Duration,
+    /// Duración de la última ejecución.
+    pub last_time: Duration,
+    /// Entidades procesadas en la última ejecución.
+    pub last_entities: usize,
+    /// Entidades procesadas en todas las ejecuciones.
+    pub total_entities: u64,
+    samples: VecDeque<Duration>,
+}
+
+impl SystemStats {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            calls: 0,
+            total_time: Duration::ZERO,
+            last_time: Duration::ZERO,
+            last_entities: 0,
+            total_entities: 0,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Media de las duraciones dentro de la ventana móvil.
+    pub fn average(&self) -> Duration {
+        average(&self.samples)
+    }
+
+    /// Percentil `p` (0.0..=100.0) de las duraciones dentro de la ventana móvil.
+    pub fn percentile(&self, p: f64) -> Duration {
+        percentile(&self.samples, p)
+    }
+
+    /// Duración máxima dentro de la ventana móvil.
+    pub fn max(&self) -> Duration {
+        self.samples.iter().copied().max().unwrap_or_default()
+    }
+}
+
+/// Recurso con las estadísticas de ejecución del `TaskGraph`.
+#[derive(Clone, Debug)]
+pub struct ScheduleStats {
+    window: usize,
+    epoch: Instant,
+    frames: u64,
+    frame_times: VecDeque<Duration>,
+    systems: Vec<SystemStats>,
+    index: HashMap<String, usize>,
+    // Muestras completas de los últimos frames: (inicio, duración, sistemas).
+    trace: VecDeque<(Instant, Duration, Vec<SystemSample>)>,
+}
+
+impl Default for ScheduleStats {
+    fn default() -> Self {
+        Self::new(DEFAULT_STATS_WINDOW)
+    }
+}
+
+impl ScheduleStats {
+    /// Crea estadísticas vacías con una ventana móvil de `window` frames.
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            epoch: Instant::now(),
+            frames: 0,
+            frame_times: VecDeque::new(),
+            systems: Vec::new(),
+            index: HashMap::new(),
+            trace: VecDeque::new(),
+        }
+    }
+
+    /// Registra un frame completo.
+    pub fn record_frame(&mut self, start: Instant, duration: Duration, samples: Vec<SystemSample>) {
+        self.frames += 1;
+        push_bounded(&mut self.frame_times, duration, self.window);
+
+        for sample in &samples {
+            let index = match self.index.get(&sample.name) {
+                Some(&index) => index,
+                None => {
+                    self.index.insert(sample.name.clone(), self.systems.len());
+                    self.systems.push(SystemStats::new(&sample.name));
+                    self.systems.len() - 1
+                }
+            };
+            let stats = &mut self.systems[index];
+            stats.calls += 1;
+            stats.total_time += sample.duration;
+            stats.last_time = sample.duration;
+            stats.last_entities = sample.entities;
+            stats.total_entities += sample.entities as u64;
+            push_bounded(&mut stats.samples, sample.duration, self.window);
+        }
+
+        push_bounded(&mut self.trace, (start, duration, samples), self.window);
+    }
+
+    /// Número de frames registrados.
+    pub fn frame_count(&self) -> u64 {
+        self.frames
+    }
+
+    /// Duración del último frame.
+    pub fn last_frame_time(&self) -> Duration {
+        self.frame_times.back().copied().unwrap_or_default()
+    }
+
+    /// Media de la duración de los frames dentro de la ventana móvil.
+    pub fn average_frame_time(&self) -> Duration {
+        average(&self.frame_times)
+    }
+
+    /// Percentil `p` (0.0..=100.0) de la duración de los frames.
+    pub fn frame_percentile(&self, p: f64) -> Duration {
+        percentile(&self.frame_times, p)
+    }
+
+    /// Estadísticas de un sistema por nombre.
+    pub fn system(&self, name: &str) -> Option<&SystemStats> {
+        self.index.get(name).map(|&index| &self.systems[index])
+    }
+
+    /// Itera sobre las estadísticas de todos los sistemas, en orden de aparición.
+    pub fn systems(&self) -> impl Iterator<Item = &SystemStats> {
+        self.systems.iter()
+    }
+
+    /// Devuelve los `n` sistemas con mayor tiempo medio en la ventana móvil.
+    pub fn slowest(&self, n: usize) -> Vec<&SystemStats> {
+        let mut systems: Vec<&SystemStats> = self.systems.iter().collect();
+        systems.sort_by_key(|stats| std::cmp::Reverse(stats.average()));
+        systems.truncate(n);
+        systems
+    }
+
+    /// Exporta los frames de la ventana móvil en formato Chrome trace-event JSON.
+    ///
+    /// Cada frame y cada sistema se emite como un evento completo (`"ph": "X"`) con
+    /// marcas de tiempo en microsegundos relativas a la creación de las estadísticas.
+    pub fn to_chrome_trace(&self) -> String {
+        let micros =
+            |instant: Instant| instant.saturating_duration_since(self.epoch).as_secs_f64() * 1e6;
+        let mut events = Vec::new();
+
+        for (start, duration, samples) in &self.trace {
+            events.push(format!(
+                "{{\"name\":\"frame\",\"cat\":\"frame\",\"ph\":\"X\",\"ts\":{:.3},\"dur\":{:.3},\"pid\":1,\"tid\":0}}",
+                micros(*start),
+                duration.as_secs_f64() * 1e6,
+            ));
+            for sample in samples {
+                events.push(format!(
+                    "{{\"name\":\"{}\",\"cat\":\"system\",\"ph\":\"X\",\"ts\":{:.3},\"dur\":{:.3},\"pid\":1,\"tid\":1,\"args\":{{\"entities\":{},\"stage\":{}}}}}",
+                    json_escape(&sample.name),
+                    micros(sample.start),
+                    sample.duration.as_secs_f64() * 1e6,
+                    sample.entities,
+                    sample.stage,
+                ));
+            }
+        }
+
+        format!(
+            "{{\"traceEvents\":[{}],\"displayTimeUnit\":\"ms\"}}",
+            events.join(",")
+        )
+    }
+
+    /// Escribe el volcado Chrome trace-event en un fichero.
+    pub fn write_chrome_trace(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_chrome_trace())
+    }
+}
+
+fn push_bounded<T>(queue: &mut VecDeque<T>, value: T, capacity: usize) {
+    if queue.len() == capacity {
+        queue.pop_front();
+    }
+    queue.push_back(value);
+}
+
+fn average(samples: &VecDeque<Duration>) -> Duration {
+    if samples.is_empty() {
+        return Duration::ZERO;
+    }
+    samples.iter().sum::<Duration>() / samples.len() as u32
+}
+
+/// Percentil por el método *nearest-rank*.
+fn percentile(samples: &VecDeque<Duration>, p: f64) -> Duration {
+    if samples.is_empty() {
+        return Duration::ZERO;
+    }
+    let mut sorted: Vec<Duration> = samples.iter().copied().collect();
+    sorted.sort_unstable();
+    let rank = ((p.clamp(0.0, 100.0) / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.saturating_sub(1).min(sorted.len() - 1)]
+}
+
+fn json_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(escaped, "\\u{:04x}", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::{Component, Transform};
+    use crate::query::Query;
+    use crate::system::{System, TaskGraph};
+    use crate::world::World;
+
+    fn sample(name: &str, micros: u64) -> SystemSample {
+        SystemSample {
+            name: name.into(),
+            stage: 0,
+            start: Instant::now(),
+            duration: Duration::from_micros(micros),
+            entities: 0,
+        }
+    }
+
+    #[test]
+    fn test_rolling_window_and_percentiles() {
+        let mut stats = ScheduleStats::new(4);
+        for micros in [100, 10, 20, 30, 40] {
+            stats.record_frame(
+                Instant::now(),
+                Duration::from_micros(micros),
+                vec![sample("ai", micros)],
+            );
+        }
+
+        let ai = stats.system("ai").unwrap();
+        assert_eq!(ai.calls, 5);
+        assert_eq!(ai.total_time, Duration::from_micros(200));
+        // La muestra de 100us ya salió de la ventana.
+        assert_eq!(ai.average(), Duration::from_micros(25));
+        assert_eq!(ai.percentile(50.0), Duration::from_micros(20));
+        assert_eq!(ai.percentile(100.0), Duration::from_micros(40));
+        assert_eq!(stats.frame_percentile(0.0), Duration::from_micros(10));
+    }
+
+    #[test]
+    fn test_task_graph_records_stats() {
+        #[derive(Default)]
+        struct Marker;
+        impl Component for Marker {}
+
+        let mut world = World::new(16);
+        world.register_component::<Transform>();
+        world.register_component::<Marker>();
+        let mut first = None;
+        for i in 0..10 {
+            let entity = world.spawn_entity();
+            first.get_or_insert(entity);
+            world.insert(entity, Transform::default());
+            if i % 2 == 0 {
+                world.insert(entity, Marker);
+            }
+        }
+
+        let mut graph = TaskGraph::new();
+        graph
+            .add_system(
+                "count \"all\"".into(),
+                vec![],
+                System::new(|world| {
+                    Query::<(&Transform,)>::new(world).iter().count();
+                }),
+            )
+            .unwrap();
+        graph
+            .add_system(
+                "marked".into(),
+                vec!["count \"all\"".into()],
+                System::new(|world| {
+                    Query::<(&Transform, &Marker)>::new(world).iter().count();
+                }),
+            )
+            .unwrap();
+        let first = first.unwrap();
+        graph
+            .add_system(
+                "lookup".into(),
+                vec![],
+                System::new(move |world| {
+                    let mut query = Query::<(&Transform, &Marker)>::new(world);
+                    assert!(query.get(first).is_some());
+                }),
+            )
+            .unwrap();
+        graph.run(&mut world);
+        assert!(world.resource::<ScheduleStats>().is_none());
+        graph.set_profiling(true);
+        graph.run(&mut world);
+        graph.run(&mut world);
+
+        let stats = world.resource::<ScheduleStats>().unwrap();
+        assert_eq!(stats.frame_count(), 2);
+        assert_eq!(stats.system("count \"all\"").unwrap().last_entities, 10);
+        assert_eq!(stats.system("marked").unwrap().last_entities, 5);
+        assert_eq!(stats.system("marked").unwrap().calls, 2);
+        assert_eq!(stats.system("lookup").unwrap().last_entities, 1);
+
+        let trace = stats.to_chrome_trace();
+        assert!(trace.starts_with("{\"traceEvents\":["));
+        assert!(trace.contains("\"name\":\"count \\\"all\\\"\""));
+        assert!(trace.contains("\"args\":{\"entities\":5,\"stage\":1}"));
+        assert_eq!(trace.matches("\"cat\":\"frame\"").count(), 2);
+    }
+}
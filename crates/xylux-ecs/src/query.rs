@@ -2,37 +2,70 @@
 //!
 //! Infraestructura para consultas sobre entidades y componentes en un mundo ECS.
 //! Permite iterar sobre entidades que cumplen ciertos criterios de componentes.
+//!
+//! ## Orden de iteración
+//!
+//! `Query::iter` recorre las entidades en orden ascendente de ID, sin depender de
+//! tablas hash ni del orden de inserción de componentes. Con `World::set_stable_order`
+//! la asignación de IDs tampoco depende del orden de eliminación, así que dos
+//! ejecuciones con la misma secuencia de operaciones iteran igual en cualquier
+//! máquina. Para otro orden usa `Query::iter_sorted_by_key`.
 
 use crate::component::{Component, ComponentId};
 use crate::entity::Entity;
 use crate::world::World;
+use std::fmt;
 use std::marker::PhantomData;
 
 /// Trait que define qué se puede extraer de una `Query`.
 /// Implementado para tuplas de `&T` y `&mut T` donde `T: Component`.
-pub trait Queryable<'w>: Sized {
+///
+/// Los datos se entregan como `Item<'a>`, con la vida del préstamo de la query que
+/// los obtiene, no con la del tipo que se escribió en `Query<T>`.
+pub trait Queryable: Sized {
+    /// Datos extraídos de una entidad (e.g. `(&'a mut Transform, &'a Velocity)`).
+    type Item<'a>;
+
     /// Devuelve los `ComponentId` de los componentes de la query.
     fn component_ids() -> Vec<ComponentId>;
 
+    /// Componentes que la query lee y escribe.
+    fn access() -> QueryAccess;
+
     /// Extrae los componentes de una entidad del mundo.
     ///
     /// # Safety
     /// - El puntero `world` debe ser válido.
     /// - La `entity` debe estar viva y tener todos los componentes requeridos.
     ///   Esta condición la garantiza `QueryIter`.
-    unsafe fn fetch(world: *mut World, entity: Entity) -> Option<Self>;
+    unsafe fn fetch<'a>(world: *mut World, entity: Entity) -> Option<Self::Item<'a>>;
 }
 
 /// Query sobre entidades que cumplen los requisitos de `T: Queryable`.
-pub struct Query<'w, T: Queryable<'w>> {
+///
+/// Los resultados toman prestada la query (`&mut self`), así que no pueden
+/// coexistir dos resultados ni un resultado y un iterador de la misma query.
+pub struct Query<'w, T: Queryable> {
     world: *mut World,
     _lt: PhantomData<&'w mut World>,
     _marker: PhantomData<T>,
 }
 
-impl<'w, T: Queryable<'w>> Query<'w, T> {
+impl<'w, T: Queryable> Query<'w, T> {
     /// Crea una nueva query sobre el mundo.
+    ///
+    /// # Panics
+    /// Si la query accede mutablemente a un componente que también lee o escribe
+    /// por otro parámetro (e.g. `(&mut Health, Related<Targets, Health>)`), lo que
+    /// daría referencias que se solapan.
     pub fn new(world: &'w mut World) -> Self {
+        if let Some(conflict) = T::access().conflict {
+            panic!(
+                "La query {} accede a {} mutablemente y por otro parámetro",
+                std::any::type_name::<T>(),
+                conflict
+            );
+        }
         Self {
             world,
             _lt: PhantomData,
@@ -41,7 +74,21 @@ impl<'w, T: Queryable<'w>> Query<'w, T> {
     }
 
     /// Devuelve un iterador sobre los componentes solicitados.
-    pub fn iter(&mut self) -> QueryIter<'w, T> {
+    ///
+    /// # Cambio incompatible
+    /// Los items viven tanto como el préstamo de la query, no del mundo, así que no
+    /// pueden sobrevivir a ella: antes
+    /// `let items: Vec<_> = Query::<(&A,)>::new(&mut world).iter().collect();`
+    /// compilaba y ahora la query temporal se suelta demasiado pronto. En esos casos
+    /// guarda la query en una variable o consúmela con `into_iter`, cuyos items viven
+    /// tanto como el préstamo del mundo.
+    pub fn iter(&mut self) -> QueryIter<'_, T> {
+        self.matching()
+    }
+
+    /// Iterador sobre las entidades que cumplen la query. El llamador elige `'a`:
+    /// `iter` lo ata al préstamo de la query e `into_iter` consume la query.
+    fn matching<'a>(&self) -> QueryIter<'a, T> {
         let component_ids = T::component_ids();
 
         // OPTIMIZACIÓN: Intersectamos los bitmasks de los componentes para obtener
@@ -66,34 +113,198 @@ impl<'w, T: Queryable<'w>> Query<'w, T> {
                 final_mask &= &storage.bitmask;
             }
 
+            world_ref.retain_active(&mut final_mask);
+
             // Movemos el bitmask calculado al iterador para que sea lazy.
             return QueryIter::new(self.world, final_mask);
         }
 
         // Si no se piden componentes (e.g., Query<(Entity,)>), iteramos sobre todas las entidades vivas.
         let world_ref = unsafe { &*self.world };
-        let alive_mask = world_ref.alive_mask().clone();
+        let mut alive_mask = world_ref.alive_mask().clone();
+        world_ref.retain_active(&mut alive_mask);
         QueryIter::new(self.world, alive_mask)
     }
+
+    /// Devuelve los resultados ordenados por la clave `key`.
+    ///
+    /// El orden es estable: los empates conservan el orden ascendente de ID.
+    pub fn iter_sorted_by_key<'a, K: Ord>(
+        &'a mut self,
+        mut key: impl FnMut(&T::Item<'a>) -> K,
+    ) -> std::vec::IntoIter<T::Item<'a>> {
+        let mut items: Vec<T::Item<'a>> = self.iter().collect();
+        items.sort_by_key(|item| key(item));
+        items.into_iter()
+    }
+
+    /// Datos de la query para una entidad concreta, si la cumple.
+    ///
+    /// Devuelve `None` si la entidad no está viva, está inactiva o le falta algún
+    /// componente. El resultado toma prestada la query:
+    ///
+    /// ```compile_fail
+    /// # use xylux_ecs::{Query, Transform, World};
+    /// let mut world = World::new(1);
+    /// world.register_component::<Transform>();
+    /// let entity = world.spawn_entity();
+    /// world.insert(entity, Transform::default());
+    /// let mut query = Query::<(&mut Transform,)>::new(&mut world);
+    /// let (a,) = query.get(entity).unwrap();
+    /// let (b,) = query.get(entity).unwrap();
+    /// a.position = b.position;
+    /// ```
+    pub fn get(&mut self, entity: Entity) -> Option<T::Item<'_>> {
+        let world_ref = unsafe { &*self.world };
+        if !world_ref.is_active(entity) {
+            return None;
+        }
+        let matches = T::component_ids().iter().all(|id| {
+            world_ref
+                .components
+                .get(id)
+                .is_some_and(|storage| storage.has(entity.id))
+        });
+        if !matches {
+            return None;
+        }
+
+        // SAFETY: la entidad está viva y tiene todos los componentes. El item toma
+        // prestada la query durante su vida, así que no coexiste con otro item ni con
+        // un iterador de ella, y `Query::new` descarta accesos en conflicto dentro de
+        // un mismo item.
+        // El contador se toca antes del fetch, cuando aún no hay items vivos de
+        // esta llamada; se descuenta si el join no produce resultado.
+        unsafe { (*self.world).fetched_entities += 1 };
+        let item = unsafe { T::fetch(self.world, entity) };
+        if item.is_none() {
+            unsafe { (*self.world).fetched_entities -= 1 };
+        }
+        item
+    }
+
+    /// Resultado de una query que debe coincidir con exactamente una entidad.
+    ///
+    /// # Panics
+    /// Si no hay coincidencias o hay más de una.
+    pub fn single(&mut self) -> T::Item<'_> {
+        match self.get_single() {
+            Ok(item) => item,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    /// Como `single`, pero devuelve un error en lugar de hacer panic.
+    pub fn get_single(&mut self) -> Result<T::Item<'_>, QuerySingleError> {
+        let mut iter = self.iter();
+        let item = iter
+            .next()
+            .ok_or(QuerySingleError::NoEntities(std::any::type_name::<T>()))?;
+        if iter.next().is_some() {
+            return Err(QuerySingleError::MultipleEntities(
+                std::any::type_name::<T>(),
+            ));
+        }
+        Ok(item)
+    }
 }
 
+/// Componentes que lee y escribe una query.
+///
+/// Un componente escrito no puede aparecer en ningún otro parámetro de la misma
+/// query; `Query::new` rechaza las queries con conflictos.
+#[derive(Clone, Debug, Default)]
+pub struct QueryAccess {
+    reads: Vec<ComponentId>,
+    writes: Vec<ComponentId>,
+    /// Primer componente en conflicto.
+    conflict: Option<&'static str>,
+}
+
+impl QueryAccess {
+    /// Registra una lectura de `C`.
+    pub fn add_read<C: Component>(&mut self) {
+        let id = ComponentId::of::<C>();
+        if self.writes.contains(&id) {
+            self.conflict.get_or_insert(std::any::type_name::<C>());
+        }
+        self.reads.push(id);
+    }
+
+    /// Registra una escritura de `C`.
+    pub fn add_write<C: Component>(&mut self) {
+        let id = ComponentId::of::<C>();
+        if self.reads.contains(&id) || self.writes.contains(&id) {
+            self.conflict.get_or_insert(std::any::type_name::<C>());
+        }
+        self.writes.push(id);
+    }
+
+    /// Componente leído o escrito a la vez que se escribe, si lo hay.
+    pub fn conflict(&self) -> Option<&'static str> {
+        self.conflict
+    }
+}
+
+/// Consume la query; los items viven tanto como el préstamo del mundo.
+///
+/// # Cambio incompatible
+/// Sustituye a `Query::iter` donde la query era un temporal; ver `Query::iter`.
+impl<'w, T: Queryable> IntoIterator for Query<'w, T> {
+    type Item = T::Item<'w>;
+    type IntoIter = QueryIter<'w, T>;
+
+    fn into_iter(self) -> QueryIter<'w, T> {
+        self.matching()
+    }
+}
+
+/// Error de `Query::get_single`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuerySingleError {
+    /// Ninguna entidad cumple la query.
+    NoEntities(&'static str),
+    /// Más de una entidad cumple la query.
+    MultipleEntities(&'static str),
+}
+
+impl fmt::Display for QuerySingleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuerySingleError::NoEntities(query) => {
+                write!(f, "Ninguna entidad cumple la query {}", query)
+            }
+            QuerySingleError::MultipleEntities(query) => {
+                write!(f, "Más de una entidad cumple la query {}", query)
+            }
+        }
+    }
+}
+
+impl std::error::Error for QuerySingleError {}
+
 /// Iterador sobre entidades y sus componentes.
 /// Este iterador es "lazy" y no pre-asigna un vector con todas las entidades coincidentes.
-pub struct QueryIter<'w, T: Queryable<'w>> {
+///
+/// `'a` es el préstamo de la `Query` que lo creó.
+pub struct QueryIter<'a, T: Queryable> {
     world: *mut World,
     mask: bitvec::vec::BitVec,
     cursor: usize,
-    _lt: PhantomData<&'w mut World>,
+    /// Entidades entregadas; se suman al mundo al soltar el iterador.
+    fetched: usize,
+    _lt: PhantomData<&'a mut World>,
     _marker: PhantomData<T>,
 }
 
-impl<'w, T: Queryable<'w>> QueryIter<'w, T> {
+impl<T: Queryable> QueryIter<'_, T> {
     /// Crea un nuevo iterador a partir de un bitmask de entidades coincidentes.
     fn new(world: *mut World, mask: bitvec::vec::BitVec) -> Self {
         Self {
             world,
             mask,
             cursor: 0,
+            fetched: 0,
             _lt: PhantomData,
             _marker: PhantomData,
         }
@@ -105,14 +316,15 @@ impl<'w, T: Queryable<'w>> QueryIter<'w, T> {
             world,
             mask: bitvec::vec::BitVec::new(),
             cursor: 0,
+            fetched: 0,
             _lt: PhantomData,
             _marker: PhantomData,
         }
     }
 }
 
-impl<'w, T: Queryable<'w>> Iterator for QueryIter<'w, T> {
-    type Item = T;
+impl<'a, T: Queryable> Iterator for QueryIter<'a, T> {
+    type Item = T::Item<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
@@ -131,15 +343,28 @@ impl<'w, T: Queryable<'w>> Iterator for QueryIter<'w, T> {
             if unsafe { (*self.world).is_alive(entity) } {
                 // SAFETY: El iterador se construye con entidades que tienen todos los
                 // componentes necesarios. El puntero al mundo es válido durante la vida del
-                // iterador. La API de `Query` con `&'w mut World` previene la creación
-                // de múltiples iteradores mutables que podrían invalidar las referencias.
+                // iterador, que toma prestada la `Query` (`&'a mut`): no puede haber otro
+                // iterador ni otro resultado de ella mientras vivan sus items.
                 // La comprobación `is_alive` añade una capa extra de seguridad.
-                return unsafe { T::fetch(self.world, entity) };
+                // `fetch` puede devolver `None` en joins (e.g. `Related`) cuyo destino no
+                // cumple la query; en ese caso se pasa a la siguiente entidad.
+                if let Some(item) = unsafe { T::fetch(self.world, entity) } {
+                    self.fetched += 1;
+                    return Some(item);
+                }
             }
         }
     }
 }
 
+impl<T: Queryable> Drop for QueryIter<'_, T> {
+    fn drop(&mut self) {
+        // SAFETY: el puntero es válido durante `'w` y solo se toca el contador de
+        // entidades, que ningún item del iterador referencia.
+        unsafe { (*self.world).fetched_entities += self.fetched };
+    }
+}
+
 // --- Implementación de Queryable para tuplas ---
 
 /// Trait auxiliar para abstraer sobre `&T` y `&mut T` en las queries.
@@ -148,56 +373,71 @@ impl<'w, T: Queryable<'w>> Iterator for QueryIter<'w, T> {
 /// La implementación de este trait es `unsafe` porque debe garantizar que
 /// el acceso a los componentes a través de `fetch_param` es válido bajo
 /// las reglas de borrowing de Rust, aunque se use un puntero crudo.
-pub unsafe trait QueryParam<'w>: Sized {
-    type Item;
+pub unsafe trait QueryParam: Sized {
+    /// Dato entregado, con la vida `'a` del préstamo de la query.
+    type Item<'a>;
 
     /// Añade los `ComponentId` requeridos por este parámetro a la lista.
     /// No hace nada si el parámetro no es un componente (e.g., `Entity`).
     fn add_component_ids(ids: &mut Vec<ComponentId>);
 
+    /// Registra los componentes que lee o escribe este parámetro, incluidos los de
+    /// otras entidades.
+    fn add_access(access: &mut QueryAccess);
+
     /// # Safety
     /// El puntero `world` debe ser válido y la `entity` debe estar viva.
-    unsafe fn fetch_param(world: *mut World, entity: Entity) -> Option<Self::Item>;
+    unsafe fn fetch_param<'a>(world: *mut World, entity: Entity) -> Option<Self::Item<'a>>;
 }
 
-unsafe impl<'w, C: Component> QueryParam<'w> for &'w C {
-    type Item = &'w C;
+unsafe impl<C: Component> QueryParam for &C {
+    type Item<'a> = &'a C;
 
     fn add_component_ids(ids: &mut Vec<ComponentId>) {
         ids.push(ComponentId::of::<C>());
     }
 
-    unsafe fn fetch_param(world: *mut World, entity: Entity) -> Option<Self::Item> {
+    fn add_access(access: &mut QueryAccess) {
+        access.add_read::<C>();
+    }
+
+    unsafe fn fetch_param<'a>(world: *mut World, entity: Entity) -> Option<Self::Item<'a>> {
         // SAFETY: The caller of `fetch_param` guarantees that `world` is a valid
         // pointer and that `entity` is alive.
         unsafe { (*world).get(entity) }
     }
 }
 
-unsafe impl<'w, C: Component> QueryParam<'w> for &'w mut C {
-    type Item = &'w mut C;
+unsafe impl<C: Component> QueryParam for &mut C {
+    type Item<'a> = &'a mut C;
 
     fn add_component_ids(ids: &mut Vec<ComponentId>) {
         ids.push(ComponentId::of::<C>());
     }
 
-    unsafe fn fetch_param(world: *mut World, entity: Entity) -> Option<Self::Item> {
+    fn add_access(access: &mut QueryAccess) {
+        access.add_write::<C>();
+    }
+
+    unsafe fn fetch_param<'a>(world: *mut World, entity: Entity) -> Option<Self::Item<'a>> {
         // SAFETY: The caller of `fetch_param` guarantees that `world` is a valid
-        // pointer and that `entity` is alive. It also ensures aliasing rules
-        // for mutable access are not violated.
+        // pointer and that `entity` is alive. `Query::new` rejects queries that
+        // access `C` through another parameter, so this is the only reference.
         unsafe { (*world).get_mut(entity) }
     }
 }
 
 // Implementación para obtener el `Entity` mismo en una query.
-unsafe impl<'w> QueryParam<'w> for Entity {
-    type Item = Entity;
+unsafe impl QueryParam for Entity {
+    type Item<'a> = Entity;
 
     fn add_component_ids(_ids: &mut Vec<ComponentId>) {
         // Entity no es un componente, no añade IDs.
     }
 
-    unsafe fn fetch_param(_world: *mut World, entity: Entity) -> Option<Self::Item> {
+    fn add_access(_access: &mut QueryAccess) {}
+
+    unsafe fn fetch_param<'a>(_world: *mut World, entity: Entity) -> Option<Self::Item<'a>> {
         Some(entity)
     }
 }
@@ -205,17 +445,22 @@ unsafe impl<'w> QueryParam<'w> for Entity {
 macro_rules! impl_queryable_for_tuple {
     ( $($param:ident),* ) => {
         #[allow(non_snake_case)]
-        impl<'w, $($param),*> Queryable<'w> for ($($param,)*)
-        where
-            $($param: QueryParam<'w, Item = $param> + 'w),*
-        {
+        impl<$($param: QueryParam),*> Queryable for ($($param,)*) {
+            type Item<'a> = ($($param::Item<'a>,)*);
+
             fn component_ids() -> Vec<ComponentId> {
                 let mut ids = Vec::new();
                 $( $param::add_component_ids(&mut ids); )*
                 ids
             }
 
-            unsafe fn fetch(world: *mut World, entity: Entity) -> Option<Self> {
+            fn access() -> QueryAccess {
+                let mut access = QueryAccess::default();
+                $( $param::add_access(&mut access); )*
+                access
+            }
+
+            unsafe fn fetch<'a>(world: *mut World, entity: Entity) -> Option<Self::Item<'a>> {
                 // SAFETY: This function is unsafe and relies on the caller (QueryIter)
                 // to provide a valid world pointer and an entity that is alive and
                 // has all the required components. The individual `fetch_param` calls
@@ -0,0 +1,389 @@
+//! # Módulo de Relaciones
+//!
+//! Relaciones tipadas entre entidades (e.g. `Targets`, `Owns`, `ChildOf`).
+//!
+//! Una relación es una arista dirigida: la entidad *origen* guarda un componente
+//! `Relation<R>` que apunta a la entidad *destino*. Cada origen tiene como máximo una
+//! arista por tipo de relación. El `World` mantiene un índice inverso para responder
+//! "¿quién apunta a X?" y limpia las aristas cuando se elimina cualquiera de los dos
+//! extremos.
+//!
+//! Las queries pueden cruzar una relación con `Related<R, C>`, que entrega el
+//! componente `C` de la entidad destino:
+//!
+//! ```ignore
+//! for (enemy, target) in Query::<(Entity, Related<Targets, Health>)>::new(&mut world).iter() {
+//!     println!("{:?} apunta a {:?} con {} de vida", enemy, target.target, target.0);
+//! }
+//! ```
+
+use crate::component::{Component, ComponentId};
+use crate::entity::Entity;
+use crate::query::{QueryAccess, QueryParam};
+use crate::world::World;
+use std::any::Any;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+/// Marca un tipo como clase de relación.
+///
+/// ```ignore
+/// struct Targets;
+/// impl RelationKind for Targets {}
+/// ```
+pub trait RelationKind: 'static {}
+
+/// Relación jerárquica: el origen es hijo del destino.
+pub struct ChildOf;
+impl RelationKind for ChildOf {}
+
+/// Componente que representa una arista `R` desde su entidad hacia `target`.
+///
+/// Crea y elimina aristas con `World::relate` / `World::unrelate` (o `World::insert`).
+/// Es inmutable (ver `Component::MUTABLE`) para que el índice inverso no se quede
+/// desfasado.
+pub struct Relation<R: RelationKind> {
+    target: Entity,
+    _marker: PhantomData<fn() -> R>,
+}
+
+impl<R: RelationKind> Relation<R> {
+    /// Crea una arista hacia `target`.
+    pub fn new(target: Entity) -> Self {
+        Self {
+            target,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Entidad destino de la arista.
+    pub fn target(&self) -> Entity {
+        self.target
+    }
+}
+
+impl<R: RelationKind> Default for Relation<R> {
+    fn default() -> Self {
+        Self::new(Entity::PLACEHOLDER)
+    }
+}
+
+impl<R: RelationKind> Clone for Relation<R> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<R: RelationKind> Copy for Relation<R> {}
+
+impl<R: RelationKind> std::fmt::Debug for Relation<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Relation")
+            .field("kind", &std::any::type_name::<R>())
+            .field("target", &self.target)
+            .finish()
+    }
+}
+
+impl<R: RelationKind> Component for Relation<R> {
+    const MUTABLE: bool = false;
+}
+
+/// Índices de un tipo de relación registrado en el `World`.
+pub(crate) struct RelationIndex {
+    /// ID de la entidad origen -> destino.
+    pub(crate) forward: HashMap<usize, Entity>,
+    /// Destino -> orígenes que apuntan a él, en orden de creación.
+    pub(crate) reverse: HashMap<Entity, Vec<Entity>>,
+    /// Extrae el destino de un `Relation<R>` con tipo borrado.
+    pub(crate) target_of: fn(&dyn Any) -> Option<Entity>,
+    /// Registra la relación en un `World` y crea la arista `source -> target`.
+    pub(crate) relate: fn(&mut World, Entity, Entity),
+}
+
+impl RelationIndex {
+    fn new<R: RelationKind>() -> Self {
+        Self {
+            forward: HashMap::new(),
+            reverse: HashMap::new(),
+            target_of: |component| {
+                component
+                    .downcast_ref::<Relation<R>>()
+                    .map(Relation::target)
+            },
+            relate: |world, source, target| {
+                world.register_relation::<R>();
+                world.relate::<R>(source, target);
+            },
+        }
+    }
+
+    /// Registra la arista `source -> target`, reemplazando la anterior del origen.
+    pub(crate) fn link(&mut self, source: Entity, target: Entity) {
+        self.unlink(source);
+        self.forward.insert(source.id, target);
+        self.reverse.entry(target).or_default().push(source);
+    }
+
+    /// Elimina la arista que sale de `source`, devolviendo su destino.
+    pub(crate) fn unlink(&mut self, source: Entity) -> Option<Entity> {
+        let target = self.forward.remove(&source.id)?;
+        if let Some(sources) = self.reverse.get_mut(&target) {
+            sources.retain(|&s| s != source);
+            if sources.is_empty() {
+                self.reverse.remove(&target);
+            }
+        }
+        Some(target)
+    }
+}
+
+impl World {
+    /// Registra un tipo de relación (y su componente `Relation<R>`).
+    pub fn register_relation<R: RelationKind>(&mut self) {
+        self.register_component::<Relation<R>>();
+        self.relations
+            .entry(ComponentId::of::<Relation<R>>())
+            .or_insert_with(RelationIndex::new::<R>);
+    }
+
+    /// Crea la arista `source -R-> target`, reemplazando la que tuviera `source`.
+    ///
+    /// # Panics
+    /// - Si alguna de las entidades no está viva.
+    /// - Si `source == target`.
+    /// - Si la relación no ha sido registrada.
+    pub fn relate<R: RelationKind>(&mut self, source: Entity, target: Entity) {
+        self.insert(source, Relation::<R>::new(target));
+    }
+
+    /// Elimina la arista `R` que sale de `source`, devolviendo su destino.
+    pub fn unrelate<R: RelationKind>(&mut self, source: Entity) -> Option<Entity> {
+        if !self.is_alive(source) {
+            return None;
+        }
+        let id = ComponentId::of::<Relation<R>>();
+        let target = self.relations.get_mut(&id)?.unlink(source)?;
+        let tick = self.change_tick;
+        if let Some(storage) = self.components.get_mut(&id) {
+            storage.remove(source.id);
+            storage.mark_changed(source.id, tick);
+        }
+        Some(target)
+    }
+
+    /// Destino de la arista `R` que sale de `source`.
+    pub fn relation_target<R: RelationKind>(&self, source: Entity) -> Option<Entity> {
+        self.get::<Relation<R>>(source).map(Relation::target)
+    }
+
+    /// Entidades que tienen una arista `R` hacia `target` (búsqueda inversa).
+    pub fn relation_sources<R: RelationKind>(&self, target: Entity) -> Vec<Entity> {
+        self.relations
+            .get(&ComponentId::of::<Relation<R>>())
+            .and_then(|index| index.reverse.get(&target))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Actualiza los índices al insertar un componente de relación.
+    ///
+    /// # Panics
+    /// Si el destino no está vivo o es la propia entidad, sea cual sea el camino de
+    /// inserción (`relate`, `insert`, escenas o prefabs).
+    pub(crate) fn index_relation(&mut self, id: ComponentId, source: Entity, component: &dyn Any) {
+        let Some(index) = self.relations.get(&id) else {
+            return;
+        };
+        let target = (index.target_of)(component).expect("Tipo de relación incorrecto");
+        if !self.is_alive(target) {
+            panic!(
+                "Relación {} desde {} hacia entidad inválida {:?}",
+                self.components[&id].type_name(),
+                self.entity_label(source),
+                target
+            );
+        }
+        let index = self.relations.get_mut(&id).expect("Relación registrada");
+        if target == source {
+            panic!(
+                "Una entidad no puede relacionarse consigo misma ({:?})",
+                source
+            );
+        }
+        index.link(source, target);
+    }
+
+    /// Elimina todas las aristas en las que participa `entity` (como origen o destino).
+    ///
+    /// Se llama desde `despawn_entity` antes de invalidar la entidad.
+    pub(crate) fn unlink_relations(&mut self, entity: Entity) {
+        let tick = self.change_tick;
+        for (id, index) in self.relations.iter_mut() {
+            index.unlink(entity);
+            if let Some(sources) = index.reverse.remove(&entity) {
+                for source in sources {
+                    index.forward.remove(&source.id);
+                    if let Some(storage) = self.components.get_mut(id) {
+                        storage.remove(source.id);
+                        storage.mark_changed(source.id, tick);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Parámetro de query que cruza la relación `R` y entrega el componente `C` de la
+/// entidad destino. Las entidades cuyo destino no tiene `C` se omiten.
+///
+/// El acceso al destino es de solo lectura. Como el destino puede ser cualquier
+/// entidad, `Query::new` rechaza combinarlo con `&mut C` o `&mut Relation<R>`.
+pub struct Related<'w, R: RelationKind, C: Component> {
+    /// Entidad destino de la relación.
+    pub target: Entity,
+    /// Componente `C` de la entidad destino.
+    pub data: &'w C,
+    _marker: PhantomData<fn() -> R>,
+}
+
+impl<R: RelationKind, C: Component> Deref for Related<'_, R, C> {
+    type Target = C;
+
+    fn deref(&self) -> &C {
+        self.data
+    }
+}
+
+unsafe impl<R: RelationKind, C: Component> QueryParam for Related<'_, R, C> {
+    type Item<'a> = Related<'a, R, C>;
+
+    fn add_component_ids(ids: &mut Vec<ComponentId>) {
+        ids.push(ComponentId::of::<Relation<R>>());
+    }
+
+    fn add_access(access: &mut QueryAccess) {
+        access.add_read::<Relation<R>>();
+        access.add_read::<C>();
+    }
+
+    unsafe fn fetch_param<'a>(world: *mut World, entity: Entity) -> Option<Self::Item<'a>> {
+        // SAFETY: el llamador garantiza que `world` es válido y `entity` está viva.
+        // Solo se crean referencias compartidas hacia la entidad destino.
+        let world: &'a World = unsafe { &*world };
+        let target = world.get::<Relation<R>>(entity)?.target();
+        let data = world.get::<C>(target)?;
+        Some(Related {
+            target,
+            data,
+            _marker: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::Query;
+
+    struct Targets;
+    impl RelationKind for Targets {}
+
+    #[derive(Default, Debug, PartialEq)]
+    struct Health(u32);
+    impl Component for Health {}
+
+    fn setup() -> World {
+        let mut world = World::new(16);
+        world.register_relation::<Targets>();
+        world.register_relation::<ChildOf>();
+        world.register_component::<Health>();
+        world
+    }
+
+    #[test]
+    fn test_relate_and_reverse_lookup() {
+        let mut world = setup();
+        let player = world.spawn_entity();
+        let a = world.spawn_entity();
+        let b = world.spawn_entity();
+        world.relate::<Targets>(a, player);
+        world.relate::<Targets>(b, player);
+        world.relate::<ChildOf>(a, b);
+
+        assert_eq!(world.relation_target::<Targets>(a), Some(player));
+        assert_eq!(world.relation_sources::<Targets>(player), vec![a, b]);
+        assert_eq!(world.relation_sources::<ChildOf>(b), vec![a]);
+
+        // Cambiar de objetivo actualiza el índice inverso.
+        world.relate::<Targets>(b, a);
+        assert_eq!(world.relation_sources::<Targets>(player), vec![a]);
+        assert_eq!(world.relation_sources::<Targets>(a), vec![b]);
+
+        assert_eq!(world.unrelate::<Targets>(b), Some(a));
+        assert!(world.relation_sources::<Targets>(a).is_empty());
+        assert_eq!(world.relation_target::<Targets>(b), None);
+    }
+
+    #[test]
+    fn test_despawn_cleans_both_sides() {
+        let mut world = setup();
+        let player = world.spawn_entity();
+        let a = world.spawn_entity();
+        let b = world.spawn_entity();
+        world.relate::<Targets>(a, player);
+        world.relate::<Targets>(b, player);
+
+        // Eliminar el origen quita su arista del índice inverso.
+        world.despawn_entity(a);
+        assert_eq!(world.relation_sources::<Targets>(player), vec![b]);
+
+        // Eliminar el destino quita la arista de todos los orígenes.
+        world.despawn_entity(player);
+        assert_eq!(world.relation_target::<Targets>(b), None);
+
+        // El ID reciclado no hereda aristas antiguas.
+        let recycled = world.spawn_entity();
+        assert!(world.relation_sources::<Targets>(recycled).is_empty());
+    }
+
+    #[test]
+    fn test_query_join_across_relation() {
+        let mut world = setup();
+        let healthy = world.spawn_entity();
+        world.insert(healthy, Health(80));
+        let wall = world.spawn_entity();
+
+        let hunter = world.spawn_entity();
+        world.relate::<Targets>(hunter, healthy);
+        let confused = world.spawn_entity();
+        world.relate::<Targets>(confused, wall);
+        let sniper = world.spawn_entity();
+        world.relate::<Targets>(sniper, healthy);
+
+        let results: Vec<(Entity, Entity, u32)> =
+            Query::<(Entity, Related<Targets, Health>)>::new(&mut world)
+                .iter()
+                .map(|(source, target)| (source, target.target, target.0))
+                .collect();
+        assert_eq!(results, vec![(hunter, healthy, 80), (sniper, healthy, 80)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "hacia entidad inválida")]
+    fn test_insert_relation_to_dead_entity_panics() {
+        let mut world = setup();
+        let source = world.spawn_entity();
+        let dead = world.spawn_entity();
+        world.despawn_entity(dead);
+        world.insert(source, Relation::<Targets>::new(dead));
+    }
+
+    #[test]
+    #[should_panic(expected = "accede a")]
+    fn test_query_rejects_related_with_mutable_target_component() {
+        let mut world = setup();
+        Query::<(&mut Health, Related<Targets, Health>)>::new(&mut world);
+    }
+}
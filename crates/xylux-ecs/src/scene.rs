@@ -0,0 +1,424 @@
+//! # Módulo de Escenas
+//!
+//! Serialización del `World` a escenas en formato RON.
+//!
+//! Solo se guardan los componentes registrados con `World::register_serializable`,
+//! identificados por un nombre estable (e.g. `"Transform"`). El `Name` de cada entidad
+//! se guarda en el campo `name` y la jerarquía `ChildOf` en el campo `parent`
+//! (índice de la entidad padre dentro de la escena):
+//!
+//! ```ron
+//! (
+//!     entities: [
+//!         (name: "Player", components: { "Transform": (position: (0.0, 1.0, 0.0), rotation: (0.0, 0.0, 0.0, 1.0)) }),
+//!         (name: "Gun", parent: 0, components: {}),
+//!     ],
+//! )
+//! ```
+
+use crate::component::Component;
+use crate::entity::Entity;
+use crate::relation::ChildOf;
+use crate::world::World;
+use ron::extensions::Extensions;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+
+/// Errores al guardar o cargar escenas y prefabs.
+#[derive(Debug)]
+pub enum SceneError {
+    /// Error de lectura/escritura de fichero.
+    Io(std::io::Error),
+    /// El texto RON no es válido.
+    Parse(String),
+    /// Error al convertir datos a RON.
+    Serialize(String),
+    /// La escena usa un componente que no está registrado como serializable.
+    UnknownComponent { entity: String, component: String },
+    /// El valor de un componente no se corresponde con su tipo.
+    InvalidComponent {
+        entity: String,
+        component: String,
+        message: String,
+    },
+    /// El índice `parent` no apunta a una entidad de la escena.
+    InvalidParent { entity: String, parent: usize },
+    /// La cadena de padres de la entidad vuelve a pasar por ella.
+    ParentCycle { entity: String },
+    /// La escena tiene más entidades de las que caben en el mundo.
+    Capacity { needed: usize, available: usize },
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::Io(error) => write!(f, "Error de E/S en escena: {}", error),
+            SceneError::Parse(message) => write!(f, "RON inválido: {}", message),
+            SceneError::Serialize(message) => write!(f, "No se pudo serializar: {}", message),
+            SceneError::UnknownComponent { entity, component } => write!(
+                f,
+                "Componente '{}' desconocido en la entidad {}; ¿falta register_serializable?",
+                component, entity
+            ),
+            SceneError::InvalidComponent {
+                entity,
+                component,
+                message,
+            } => write!(
+                f,
+                "Valor inválido para '{}' en la entidad {}: {}",
+                component, entity, message
+            ),
+            SceneError::InvalidParent { entity, parent } => {
+                write!(
+                    f,
+                    "La entidad {} tiene un padre inexistente ({})",
+                    entity, parent
+                )
+            }
+            SceneError::ParentCycle { entity } => {
+                write!(f, "La entidad {} es su propio ancestro", entity)
+            }
+            SceneError::Capacity { needed, available } => write!(
+                f,
+                "La escena necesita {} entidades y el mundo solo admite {} más",
+                needed, available
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+impl From<std::io::Error> for SceneError {
+    fn from(error: std::io::Error) -> Self {
+        SceneError::Io(error)
+    }
+}
+
+/// Funciones de (de)serialización de un tipo de componente, con tipo borrado.
+pub(crate) struct ComponentSerializer {
+    save: fn(&World, Entity) -> Option<Result<ron::Value, String>>,
+    load: fn(&mut World, Entity, &ron::Value) -> Result<(), String>,
+}
+
+/// Entidad serializada dentro de una `Scene`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SceneEntity {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Índice del padre (`ChildOf`) dentro de `Scene::entities`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<usize>,
+    /// Valores de componentes por nombre registrado.
+    #[serde(default)]
+    pub components: BTreeMap<String, ron::Value>,
+}
+
+/// Instantánea serializable de un conjunto de entidades.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Scene {
+    pub entities: Vec<SceneEntity>,
+}
+
+impl Scene {
+    /// Parsea una escena desde texto RON.
+    pub fn from_ron(source: &str) -> Result<Self, SceneError> {
+        from_ron(source)
+    }
+
+    /// Serializa la escena a RON legible.
+    pub fn to_ron(&self) -> Result<String, SceneError> {
+        to_ron(self)
+    }
+
+    /// Carga una escena desde un fichero `.ron`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SceneError> {
+        Self::from_ron(&std::fs::read_to_string(path)?)
+    }
+
+    /// Guarda la escena en un fichero `.ron`.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SceneError> {
+        Ok(std::fs::write(path, self.to_ron()?)?)
+    }
+}
+
+/// Parsea RON aceptando `Some(..)` implícito (`name: "Player"`).
+pub(crate) fn from_ron<T: DeserializeOwned>(source: &str) -> Result<T, SceneError> {
+    ron::Options::default()
+        .with_default_extension(Extensions::IMPLICIT_SOME)
+        .from_str(source)
+        .map_err(|error| SceneError::Parse(error.to_string()))
+}
+
+pub(crate) fn to_ron<T: Serialize>(value: &T) -> Result<String, SceneError> {
+    let config = ron::ser::PrettyConfig::default().extensions(Extensions::IMPLICIT_SOME);
+    ron::ser::to_string_pretty(value, config)
+        .map_err(|error| SceneError::Serialize(error.to_string()))
+}
+
+/// Convierte un valor a `ron::Value`.
+pub(crate) fn to_value<T: Serialize>(value: &T) -> Result<ron::Value, String> {
+    let text = ron::to_string(value).map_err(|error| error.to_string())?;
+    ron::from_str(&text).map_err(|error| error.to_string())
+}
+
+impl World {
+    /// Registra un componente como serializable bajo un nombre estable.
+    ///
+    /// También registra el componente si aún no lo estaba.
+    pub fn register_serializable<T>(&mut self, name: &str)
+    where
+        T: Component + Serialize + DeserializeOwned,
+    {
+        self.register_component::<T>();
+        self.serializers.insert(
+            name.to_string(),
+            ComponentSerializer {
+                save: |world, entity| world.get::<T>(entity).map(to_value),
+                load: |world, entity, value| {
+                    let component: T = value
+                        .clone()
+                        .into_rust()
+                        .map_err(|error| error.to_string())?;
+                    world.insert(entity, component);
+                    Ok(())
+                },
+            },
+        );
+    }
+
+    /// Inserta un componente a partir de su nombre registrado y su valor RON.
+    pub(crate) fn insert_serialized(
+        &mut self,
+        entity: Entity,
+        component: &str,
+        value: &ron::Value,
+    ) -> Result<(), SceneError> {
+        let Some(load) = self
+            .serializers
+            .get(component)
+            .map(|serializer| serializer.load)
+        else {
+            return Err(SceneError::UnknownComponent {
+                entity: self.entity_label(entity),
+                component: component.to_string(),
+            });
+        };
+        load(self, entity, value).map_err(|message| SceneError::InvalidComponent {
+            entity: self.entity_label(entity),
+            component: component.to_string(),
+            message,
+        })
+    }
+
+    /// Serializa el valor actual de un componente registrado de una entidad.
+    pub(crate) fn serialize_component(
+        &self,
+        entity: Entity,
+        component: &str,
+    ) -> Option<Result<ron::Value, SceneError>> {
+        let serializer = self.serializers.get(component)?;
+        let result = (serializer.save)(self, entity)?;
+        Some(result.map_err(|message| SceneError::InvalidComponent {
+            entity: self.entity_label(entity),
+            component: component.to_string(),
+            message,
+        }))
+    }
+
+    /// Guarda todas las entidades vivas en una `Scene`, en orden de ID.
+    pub fn save_scene(&self) -> Result<Scene, SceneError> {
+        let entities: Vec<Entity> = self
+            .alive_mask()
+            .iter_ones()
+            .map(|id| Entity {
+                id,
+                version: self.entity_version(id),
+            })
+            .collect();
+        let indices: HashMap<Entity, usize> = entities
+            .iter()
+            .enumerate()
+            .map(|(index, &entity)| (entity, index))
+            .collect();
+
+        let mut scene = Scene::default();
+        for &entity in &entities {
+            let mut saved = SceneEntity {
+                name: self.name(entity).map(str::to_string),
+                parent: self
+                    .relation_target::<ChildOf>(entity)
+                    .and_then(|parent| indices.get(&parent).copied()),
+                components: BTreeMap::new(),
+            };
+            for name in self.serializers.keys() {
+                if let Some(value) = self.serialize_component(entity, name) {
+                    saved.components.insert(name.clone(), value?);
+                }
+            }
+            scene.entities.push(saved);
+        }
+        Ok(scene)
+    }
+
+    /// Instancia las entidades de una escena y devuelve las nuevas entidades, en el
+    /// mismo orden que `scene.entities`.
+    ///
+    /// Si la escena no cabe en el mundo se devuelve `SceneError::Capacity` sin crear
+    /// nada. Si falla después, no sobrevive ninguna entidad de la escena, pero el mundo
+    /// no queda idéntico: los IDs eliminados pasan a la lista libre con su versión
+    /// incrementada, cuentan en `stats()` y `Name`/`ChildOf` pueden quedar registrados.
+    pub fn load_scene(&mut self, scene: &Scene) -> Result<Vec<Entity>, SceneError> {
+        let available = self.remaining_capacity();
+        if scene.entities.len() > available {
+            return Err(SceneError::Capacity {
+                needed: scene.entities.len(),
+                available,
+            });
+        }
+        let spawned: Vec<Entity> = scene.entities.iter().map(|_| self.spawn_entity()).collect();
+        let result = self.fill_scene(scene, &spawned);
+        if result.is_err() {
+            for &entity in &spawned {
+                self.despawn_entity(entity);
+            }
+        }
+        result.map(|_| spawned)
+    }
+
+    fn fill_scene(&mut self, scene: &Scene, spawned: &[Entity]) -> Result<(), SceneError> {
+        for (saved, &entity) in scene.entities.iter().zip(spawned) {
+            if let Some(name) = &saved.name {
+                self.set_name(entity, name.as_str());
+            }
+            for (component, value) in &saved.components {
+                self.insert_serialized(entity, component, value)?;
+            }
+        }
+        let mut parents = Vec::new();
+        for (saved, &entity) in scene.entities.iter().zip(spawned) {
+            let Some(parent) = saved.parent else {
+                continue;
+            };
+            let Some(&parent_entity) = spawned.get(parent).filter(|&&p| p != entity) else {
+                return Err(SceneError::InvalidParent {
+                    entity: self.entity_label(entity),
+                    parent,
+                });
+            };
+            parents.push((entity, parent_entity));
+        }
+        // Con índices válidos, una cadena más larga que la escena tiene un ciclo.
+        for (start, &entity) in spawned.iter().enumerate() {
+            let mut current = start;
+            for _ in 0..scene.entities.len() {
+                let Some(parent) = scene.entities[current].parent else {
+                    break;
+                };
+                if parent == start {
+                    return Err(SceneError::ParentCycle {
+                        entity: self.entity_label(entity),
+                    });
+                }
+                current = parent;
+            }
+        }
+        for (entity, parent) in parents {
+            self.register_relation::<ChildOf>();
+            self.relate::<ChildOf>(entity, parent);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::{Transform, Velocity};
+    use glam::{Quat, Vec3};
+
+    fn registered_world() -> World {
+        let mut world = World::new(16);
+        world.register_serializable::<Transform>("Transform");
+        world.register_serializable::<Velocity>("Velocity");
+        world
+    }
+
+    #[test]
+    fn test_scene_round_trip() {
+        let mut world = registered_world();
+        world.register_relation::<ChildOf>();
+        let player = world.spawn_entity();
+        world.set_name(player, "Player");
+        world.insert(
+            player,
+            Transform {
+                position: Vec3::new(1.0, 2.0, 3.0),
+                rotation: Quat::IDENTITY,
+            },
+        );
+        let gun = world.spawn_entity();
+        world.set_name(gun, "Gun");
+        world.insert(gun, Velocity(Vec3::X));
+        world.relate::<ChildOf>(gun, player);
+
+        let text = world.save_scene().unwrap().to_ron().unwrap();
+        assert!(text.contains("name: \"Player\""));
+
+        let mut loaded = registered_world();
+        let entities = loaded.load_scene(&Scene::from_ron(&text).unwrap()).unwrap();
+        assert_eq!(entities.len(), 2);
+
+        let player = loaded.find_by_name("Player").unwrap();
+        let gun = loaded.find_by_name("Gun").unwrap();
+        assert_eq!(
+            loaded.get::<Transform>(player).unwrap().position,
+            Vec3::new(1.0, 2.0, 3.0)
+        );
+        assert_eq!(loaded.get::<Velocity>(gun), Some(&Velocity(Vec3::X)));
+        assert_eq!(loaded.relation_target::<ChildOf>(gun), Some(player));
+    }
+
+    #[test]
+    fn test_scene_errors_name_the_entity() {
+        let mut world = registered_world();
+        let scene =
+            Scene::from_ron(r#"(entities: [(name: "Boss", components: { "Health": (10) })])"#)
+                .unwrap();
+
+        let error = world.load_scene(&scene).unwrap_err();
+        assert!(matches!(error, SceneError::UnknownComponent { .. }));
+        assert!(error.to_string().contains("Boss"));
+        // La carga fallida no deja entidades a medias.
+        assert_eq!(world.find_by_name("Boss"), None);
+
+        let scene =
+            Scene::from_ron(r#"(entities: [(name: "Rock", components: { "Velocity": "fast" })])"#)
+                .unwrap();
+        let error = world.load_scene(&scene).unwrap_err();
+        assert!(matches!(error, SceneError::InvalidComponent { .. }));
+        assert!(error.to_string().contains("Rock"));
+
+        let scene = Scene::from_ron(
+            r#"(entities: [(name: "Root"), (name: "A", parent: 2), (name: "B", parent: 1)])"#,
+        )
+        .unwrap();
+        let error = world.load_scene(&scene).unwrap_err();
+        assert!(matches!(error, SceneError::ParentCycle { .. }));
+        assert!(error.to_string().contains('A'));
+        assert_eq!(world.find_by_name("Root"), None);
+
+        // Una escena que no cabe se rechaza antes de crear nada.
+        let spawned = world.stats().spawned;
+        let scene = Scene {
+            entities: vec![SceneEntity::default(); world.remaining_capacity() + 1],
+        };
+        let error = world.load_scene(&scene).unwrap_err();
+        assert!(matches!(error, SceneError::Capacity { .. }));
+        assert_eq!(world.stats().spawned, spawned);
+    }
+}
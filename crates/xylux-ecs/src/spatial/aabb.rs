@@ -0,0 +1,162 @@
+//! Primitivas geométricas del índice espacial: `Aabb` y `Ray`.
+
+use crate::component::Transform;
+use glam::{Mat3, Vec3};
+use serde::{Deserialize, Serialize};
+
+/// Caja alineada con los ejes (*axis-aligned bounding box*).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Default for Aabb {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+impl Aabb {
+    /// Caja vacía: neutra para `union`, no interseca con nada.
+    pub const EMPTY: Self = Self {
+        min: Vec3::INFINITY,
+        max: Vec3::NEG_INFINITY,
+    };
+
+    /// Crea una caja a partir de sus esquinas.
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    /// Crea una caja a partir de su centro y la mitad de su tamaño.
+    pub fn from_center_half_extents(center: Vec3, half_extents: Vec3) -> Self {
+        Self {
+            min: center - half_extents,
+            max: center + half_extents,
+        }
+    }
+
+    /// Caja mínima que contiene todos los puntos.
+    pub fn from_points(points: impl IntoIterator<Item = Vec3>) -> Self {
+        points.into_iter().fold(Self::EMPTY, |aabb, point| Self {
+            min: aabb.min.min(point),
+            max: aabb.max.max(point),
+        })
+    }
+
+    /// `true` si la caja no contiene ningún punto.
+    pub fn is_empty(&self) -> bool {
+        self.min.cmpgt(self.max).any()
+    }
+
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn half_extents(&self) -> Vec3 {
+        (self.max - self.min) * 0.5
+    }
+
+    pub fn size(&self) -> Vec3 {
+        self.max - self.min
+    }
+
+    /// Área de la superficie; heurística de coste del BVH.
+    pub fn surface_area(&self) -> f32 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        let size = self.size();
+        2.0 * (size.x * size.y + size.y * size.z + size.z * size.x)
+    }
+
+    /// Caja mínima que contiene a ambas.
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    /// Caja agrandada `margin` unidades en cada dirección.
+    pub fn expanded(&self, margin: f32) -> Aabb {
+        Aabb {
+            min: self.min - Vec3::splat(margin),
+            max: self.max + Vec3::splat(margin),
+        }
+    }
+
+    /// `true` si las cajas se solapan (los bordes cuentan).
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.min.cmple(other.max).all() && self.max.cmpge(other.min).all()
+    }
+
+    /// `true` si `other` está completamente dentro de esta caja.
+    pub fn contains(&self, other: &Aabb) -> bool {
+        self.min.cmple(other.min).all() && self.max.cmpge(other.max).all()
+    }
+
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        self.min.cmple(point).all() && self.max.cmpge(point).all()
+    }
+
+    /// Distancia al cuadrado desde un punto a la caja (0 si está dentro).
+    pub fn distance_squared_to_point(&self, point: Vec3) -> f32 {
+        (point.clamp(self.min, self.max) - point).length_squared()
+    }
+
+    /// `true` si la caja interseca la esfera.
+    pub fn intersects_sphere(&self, center: Vec3, radius: f32) -> bool {
+        self.distance_squared_to_point(center) <= radius * radius
+    }
+
+    /// Caja en espacio de mundo de esta caja local tras aplicar el `Transform`.
+    pub fn transformed(&self, transform: &Transform) -> Aabb {
+        let rotation = Mat3::from_quat(transform.rotation);
+        let abs = Mat3::from_cols(
+            rotation.x_axis.abs(),
+            rotation.y_axis.abs(),
+            rotation.z_axis.abs(),
+        );
+        let center = rotation * self.center() + transform.position;
+        Aabb::from_center_half_extents(center, abs * self.half_extents())
+    }
+
+    /// Distancia a lo largo del rayo hasta la entrada en la caja (*slab test*).
+    ///
+    /// Devuelve `Some(0.0)` si el origen está dentro y `None` si no hay impacto en
+    /// `[0, max_distance]`.
+    pub fn ray_intersection(&self, ray: &Ray, max_distance: f32) -> Option<f32> {
+        let t1 = (self.min - ray.origin) * ray.inv_direction;
+        let t2 = (self.max - ray.origin) * ray.inv_direction;
+        let near = t1.min(t2).max_element().max(0.0);
+        let far = t1.max(t2).min_element().min(max_distance);
+        (near <= far).then_some(near)
+    }
+}
+
+/// Rayo con dirección normalizada.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ray {
+    pub origin: Vec3,
+    pub direction: Vec3,
+    inv_direction: Vec3,
+}
+
+impl Ray {
+    /// Crea un rayo; `direction` se normaliza.
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        let direction = direction.normalize();
+        Self {
+            origin,
+            direction,
+            inv_direction: direction.recip(),
+        }
+    }
+
+    /// Punto a distancia `t` del origen.
+    pub fn at(&self, t: f32) -> Vec3 {
+        self.origin + self.direction * t
+    }
+}
@@ -0,0 +1,245 @@
+//! BVH (*bounding volume hierarchy*) para entidades estáticas.
+//!
+//! Se construye de una vez a partir de todas las cajas, dividiendo por la mediana del
+//! eje más largo de los centros. Reconstruir es `O(n log n)`, así que está pensado
+//! para geometría que cambia rara vez.
+
+use super::aabb::{Aabb, Ray};
+use crate::entity::Entity;
+use glam::Vec3;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// Máximo de elementos por hoja.
+const LEAF_SIZE: usize = 4;
+
+#[derive(Clone, Debug)]
+enum BvhNodeKind {
+    /// Rango `start..end` de `Bvh::items`.
+    Leaf { start: usize, end: usize },
+    /// Índices de los dos hijos en `Bvh::nodes`.
+    Branch { left: usize, right: usize },
+}
+
+#[derive(Clone, Debug)]
+struct BvhNode {
+    aabb: Aabb,
+    kind: BvhNodeKind,
+}
+
+/// Jerarquía de volúmenes envolventes inmutable.
+#[derive(Clone, Debug, Default)]
+pub struct Bvh {
+    nodes: Vec<BvhNode>,
+    items: Vec<(Entity, Aabb)>,
+}
+
+impl Bvh {
+    /// Construye el BVH a partir de las cajas de las entidades.
+    pub fn build(items: Vec<(Entity, Aabb)>) -> Self {
+        let mut bvh = Self {
+            nodes: Vec::new(),
+            items,
+        };
+        if !bvh.items.is_empty() {
+            bvh.build_node(0, bvh.items.len());
+        }
+        bvh
+    }
+
+    fn build_node(&mut self, start: usize, end: usize) -> usize {
+        let aabb = self.items[start..end]
+            .iter()
+            .fold(Aabb::EMPTY, |acc, (_, aabb)| acc.union(aabb));
+        let index = self.nodes.len();
+        self.nodes.push(BvhNode {
+            aabb,
+            kind: BvhNodeKind::Leaf { start, end },
+        });
+        if end - start <= LEAF_SIZE {
+            return index;
+        }
+
+        let centers =
+            Aabb::from_points(self.items[start..end].iter().map(|(_, aabb)| aabb.center()));
+        let axis = centers.size().max_position();
+        let mid = start + (end - start) / 2;
+        self.items[start..end].select_nth_unstable_by(mid - start, |(a, ab), (b, bb)| {
+            ab.center()[axis]
+                .total_cmp(&bb.center()[axis])
+                .then_with(|| a.cmp(b))
+        });
+
+        let left = self.build_node(start, mid);
+        let right = self.build_node(mid, end);
+        self.nodes[index].kind = BvhNodeKind::Branch { left, right };
+        index
+    }
+
+    /// Número de entidades.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Recorre los elementos de las hojas cuyos nodos cumplen `visit_node`.
+    fn traverse(
+        &self,
+        mut visit_node: impl FnMut(&Aabb) -> bool,
+        mut visit_item: impl FnMut(Entity, &Aabb),
+    ) {
+        if self.nodes.is_empty() {
+            return;
+        }
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !visit_node(&node.aabb) {
+                continue;
+            }
+            match node.kind {
+                BvhNodeKind::Leaf { start, end } => {
+                    for (entity, aabb) in &self.items[start..end] {
+                        visit_item(*entity, aabb);
+                    }
+                }
+                BvhNodeKind::Branch { left, right } => {
+                    stack.push(right);
+                    stack.push(left);
+                }
+            }
+        }
+    }
+
+    /// Añade a `out` las entidades cuya caja solapa con `query`.
+    pub fn query_aabb(&self, query: &Aabb, out: &mut Vec<Entity>) {
+        self.traverse(
+            |node| node.intersects(query),
+            |entity, aabb| {
+                if aabb.intersects(query) {
+                    out.push(entity);
+                }
+            },
+        );
+    }
+
+    /// Añade a `out` las entidades cuya caja interseca la esfera.
+    pub fn query_sphere(&self, center: Vec3, radius: f32, out: &mut Vec<Entity>) {
+        self.traverse(
+            |node| node.intersects_sphere(center, radius),
+            |entity, aabb| {
+                if aabb.intersects_sphere(center, radius) {
+                    out.push(entity);
+                }
+            },
+        );
+    }
+
+    /// Añade a `out` los impactos del rayo `(entidad, distancia)`.
+    pub fn raycast(&self, ray: &Ray, max_distance: f32, out: &mut Vec<(Entity, f32)>) {
+        self.traverse(
+            |node| node.ray_intersection(ray, max_distance).is_some(),
+            |entity, aabb| {
+                if let Some(t) = aabb.ray_intersection(ray, max_distance) {
+                    out.push((entity, t));
+                }
+            },
+        );
+    }
+
+    /// Añade a `out` las `k` entidades más cercanas a `point` como `(entidad, distancia²)`.
+    ///
+    /// Recorre los nodos en orden de distancia (*best-first*) y poda los que no pueden
+    /// mejorar el k-ésimo candidato.
+    pub fn k_nearest(&self, point: Vec3, k: usize, out: &mut Vec<(Entity, f32)>) {
+        if self.nodes.is_empty() || k == 0 {
+            return;
+        }
+        let mut best: BinaryHeap<Candidate> = BinaryHeap::new();
+        let mut frontier = BinaryHeap::new();
+        frontier.push(std::cmp::Reverse(NodeDistance(
+            self.nodes[0].aabb.distance_squared_to_point(point),
+            0,
+        )));
+
+        while let Some(std::cmp::Reverse(NodeDistance(distance, index))) = frontier.pop() {
+            if best.len() == k && best.peek().is_some_and(|worst| distance > worst.0) {
+                break;
+            }
+            match self.nodes[index].kind {
+                BvhNodeKind::Leaf { start, end } => {
+                    for (entity, aabb) in &self.items[start..end] {
+                        push_candidate(
+                            &mut best,
+                            k,
+                            Candidate(aabb.distance_squared_to_point(point), *entity),
+                        );
+                    }
+                }
+                BvhNodeKind::Branch { left, right } => {
+                    for child in [left, right] {
+                        let distance = self.nodes[child].aabb.distance_squared_to_point(point);
+                        frontier.push(std::cmp::Reverse(NodeDistance(distance, child)));
+                    }
+                }
+            }
+        }
+        out.extend(
+            best.into_iter()
+                .map(|Candidate(distance, entity)| (entity, distance)),
+        );
+    }
+}
+
+/// Candidato de k-nearest; ordenado por distancia y luego por entidad (max-heap).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Candidate(pub(crate) f32, pub(crate) Entity);
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .total_cmp(&other.0)
+            .then_with(|| self.1.cmp(&other.1))
+    }
+}
+
+/// Mantiene en `best` los `k` candidatos más cercanos.
+pub(crate) fn push_candidate(best: &mut BinaryHeap<Candidate>, k: usize, candidate: Candidate) {
+    if best.len() < k {
+        best.push(candidate);
+    } else if best.peek().is_some_and(|worst| candidate < *worst) {
+        best.pop();
+        best.push(candidate);
+    }
+}
+
+/// Nodo pendiente de visitar, ordenado por distancia.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct NodeDistance(pub(crate) f32, pub(crate) usize);
+
+impl Eq for NodeDistance {}
+
+impl PartialOrd for NodeDistance {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for NodeDistance {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .total_cmp(&other.0)
+            .then_with(|| self.1.cmp(&other.1))
+    }
+}
@@ -0,0 +1,363 @@
+//! # Módulo de Índice Espacial
+//!
+//! Aceleración de consultas espaciales sobre entidades con `Transform` y `Bounds`
+//! (diseño, sección 10): un BVH para las entidades marcadas con `Static` y un octree
+//! para las dinámicas.
+//!
+//! El recurso `SpatialIndex` se sincroniza con `update_spatial_index` (o
+//! `SpatialIndex::update`) usando la detección de cambios del `World`: en cada
+//! actualización solo se reprocesan las entidades cuyo `Transform`, `Bounds` o
+//! `Static` cambió desde la anterior. Las entidades inactivas o eliminadas salen del
+//! índice. El tick de cambios lo avanza `TaskGraph::run` (o quien llame a
+//! `World::increment_change_tick` cada frame), no el índice, así que puede haber
+//! varios consumidores de cambios a la vez.
+//!
+//! ```ignore
+//! world.insert_resource(SpatialIndex::new(Aabb::new(Vec3::splat(-512.0), Vec3::splat(512.0))));
+//! graph.add_system("spatial".into(), vec!["movement".into()], System::new(update_spatial_index))?;
+//!
+//! let index = world.resource::<SpatialIndex>().unwrap();
+//! let nearby = index.query_sphere(player_position, 10.0);
+//! ```
+
+pub mod aabb;
+pub mod bvh;
+pub mod octree;
+
+pub use aabb::{Aabb, Ray};
+pub use bvh::Bvh;
+pub use octree::Octree;
+
+use crate::component::{Bounds, ComponentId, Static, Transform};
+use crate::entity::Entity;
+use crate::world::World;
+use glam::Vec3;
+use std::collections::{BTreeSet, HashMap};
+
+/// Entrada del índice para un ID de entidad.
+#[derive(Clone, Copy, Debug)]
+struct IndexedEntity {
+    entity: Entity,
+    aabb: Aabb,
+    is_static: bool,
+}
+
+/// Recurso con el índice espacial de las entidades con `Transform` y `Bounds`.
+#[derive(Clone, Debug)]
+pub struct SpatialIndex {
+    entries: HashMap<usize, IndexedEntity>,
+    bvh: Bvh,
+    bvh_dirty: bool,
+    octree: Octree,
+    /// Primer tick aún no procesado.
+    next_tick: u32,
+}
+
+impl SpatialIndex {
+    /// Crea un índice vacío. `world_bounds` delimita el octree dinámico; las entidades
+    /// fuera de esos límites se siguen indexando, con menos eficiencia.
+    pub fn new(world_bounds: Aabb) -> Self {
+        Self {
+            entries: HashMap::new(),
+            bvh: Bvh::default(),
+            bvh_dirty: false,
+            octree: Octree::new(world_bounds),
+            next_tick: 0,
+        }
+    }
+
+    /// Sincroniza el índice con los cambios del `World` desde la última actualización.
+    ///
+    /// Si alguna entidad estática cambió, el BVH se reconstruye entero.
+    pub fn update(&mut self, world: &mut World) {
+        let mut changed = BTreeSet::new();
+        for id in [
+            ComponentId::of::<Transform>(),
+            ComponentId::of::<Bounds>(),
+            ComponentId::of::<Static>(),
+        ] {
+            changed.extend(world.changed_since(id, self.next_tick));
+        }
+
+        for entity in changed {
+            self.remove(entity.id);
+            if !world.is_active(entity) {
+                continue;
+            }
+            let (Some(transform), Some(bounds)) =
+                (world.get::<Transform>(entity), world.get::<Bounds>(entity))
+            else {
+                continue;
+            };
+            let aabb = bounds.0.transformed(transform);
+            let is_static = world.has_component(entity, ComponentId::of::<Static>());
+            self.entries.insert(
+                entity.id,
+                IndexedEntity {
+                    entity,
+                    aabb,
+                    is_static,
+                },
+            );
+            if is_static {
+                self.bvh_dirty = true;
+            } else {
+                self.octree.insert(entity, aabb);
+            }
+        }
+
+        if self.bvh_dirty {
+            let mut items: Vec<(Entity, Aabb)> = self
+                .entries
+                .values()
+                .filter(|entry| entry.is_static)
+                .map(|entry| (entry.entity, entry.aabb))
+                .collect();
+            items.sort_unstable_by_key(|(entity, _)| *entity);
+            self.bvh = Bvh::build(items);
+            self.bvh_dirty = false;
+        }
+
+        self.next_tick = world.change_tick();
+    }
+
+    fn remove(&mut self, id: usize) {
+        let Some(entry) = self.entries.remove(&id) else {
+            return;
+        };
+        if entry.is_static {
+            self.bvh_dirty = true;
+        } else {
+            self.octree.remove(entry.entity);
+        }
+    }
+
+    /// Número de entidades indexadas.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Caja en espacio de mundo de una entidad indexada.
+    pub fn bounds_of(&self, entity: Entity) -> Option<Aabb> {
+        self.entries
+            .get(&entity.id)
+            .filter(|entry| entry.entity == entity)
+            .map(|entry| entry.aabb)
+    }
+
+    /// Entidades cuya caja solapa con `aabb`, ordenadas por entidad.
+    pub fn query_aabb(&self, aabb: &Aabb) -> Vec<Entity> {
+        let mut out = Vec::new();
+        self.bvh.query_aabb(aabb, &mut out);
+        self.octree.query_aabb(aabb, &mut out);
+        out.sort_unstable();
+        out
+    }
+
+    /// Entidades cuya caja interseca la esfera, ordenadas por entidad.
+    pub fn query_sphere(&self, center: Vec3, radius: f32) -> Vec<Entity> {
+        let mut out = Vec::new();
+        self.bvh.query_sphere(center, radius, &mut out);
+        self.octree.query_sphere(center, radius, &mut out);
+        out.sort_unstable();
+        out
+    }
+
+    /// Las `k` entidades más cercanas a `point` (distancia a su caja), de la más
+    /// cercana a la más lejana.
+    pub fn k_nearest(&self, point: Vec3, k: usize) -> Vec<Entity> {
+        let mut candidates = Vec::new();
+        self.bvh.k_nearest(point, k, &mut candidates);
+        self.octree.k_nearest(point, k, &mut candidates);
+        sort_by_distance(&mut candidates);
+        candidates.truncate(k);
+        candidates.into_iter().map(|(entity, _)| entity).collect()
+    }
+
+    /// Entidades que atraviesa el rayo hasta `max_distance`, con la distancia de
+    /// entrada a su caja, de la más cercana a la más lejana.
+    pub fn raycast(&self, ray: &Ray, max_distance: f32) -> Vec<(Entity, f32)> {
+        let mut hits = Vec::new();
+        self.bvh.raycast(ray, max_distance, &mut hits);
+        self.octree.raycast(ray, max_distance, &mut hits);
+        sort_by_distance(&mut hits);
+        hits
+    }
+
+    /// Primera entidad que atraviesa el rayo.
+    pub fn raycast_first(&self, ray: &Ray, max_distance: f32) -> Option<(Entity, f32)> {
+        self.raycast(ray, max_distance).into_iter().next()
+    }
+}
+
+fn sort_by_distance(items: &mut [(Entity, f32)]) {
+    items.sort_unstable_by(|(a, da), (b, db)| da.total_cmp(db).then_with(|| a.cmp(b)));
+}
+
+/// Sistema que sincroniza el recurso `SpatialIndex` del mundo, si existe.
+pub fn update_spatial_index(world: &mut World) {
+    if let Some(mut index) = world.remove_resource::<SpatialIndex>() {
+        index.update(world);
+        world.insert_resource(index);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::Velocity;
+    use crate::query::Query;
+
+    fn setup() -> World {
+        let mut world = World::new(256);
+        world.register_component::<Transform>();
+        world.register_component::<Bounds>();
+        world.register_component::<Static>();
+        world.register_component::<Velocity>();
+        world.insert_resource(SpatialIndex::new(Aabb::new(
+            Vec3::splat(-64.0),
+            Vec3::splat(64.0),
+        )));
+        world
+    }
+
+    fn spawn_box(world: &mut World, position: Vec3, is_static: bool) -> Entity {
+        let entity = world.spawn_entity();
+        world.insert(
+            entity,
+            Transform {
+                position,
+                ..Default::default()
+            },
+        );
+        world.insert(
+            entity,
+            Bounds(Aabb::from_center_half_extents(Vec3::ZERO, Vec3::splat(0.5))),
+        );
+        if is_static {
+            world.insert(entity, Static);
+        }
+        entity
+    }
+
+    fn index(world: &World) -> &SpatialIndex {
+        world.resource::<SpatialIndex>().unwrap()
+    }
+
+    #[test]
+    fn test_queries_match_brute_force() {
+        let mut world = setup();
+        let mut entities = Vec::new();
+        for i in 0..100 {
+            // Posiciones pseudoaleatorias deterministas, parte fuera del octree.
+            let position = Vec3::new(
+                ((i * 37) % 101) as f32 - 50.0,
+                ((i * 53) % 89) as f32 - 44.0,
+                ((i * 71) % 151) as f32 - 75.0,
+            );
+            entities.push((spawn_box(&mut world, position, i % 3 == 0), position));
+        }
+        update_spatial_index(&mut world);
+        let index = index(&world);
+        assert_eq!(index.len(), 100);
+
+        let query = Aabb::new(Vec3::new(-20.0, -10.0, -30.0), Vec3::new(15.0, 25.0, 10.0));
+        let mut expected: Vec<Entity> = entities
+            .iter()
+            .filter(|(_, p)| {
+                Aabb::from_center_half_extents(*p, Vec3::splat(0.5)).intersects(&query)
+            })
+            .map(|(e, _)| *e)
+            .collect();
+        expected.sort();
+        assert!(!expected.is_empty());
+        assert_eq!(index.query_aabb(&query), expected);
+
+        let center = Vec3::new(5.0, 0.0, -5.0);
+        let mut by_distance: Vec<(f32, Entity)> = entities
+            .iter()
+            .map(|(e, p)| {
+                (
+                    Aabb::from_center_half_extents(*p, Vec3::splat(0.5))
+                        .distance_squared_to_point(center),
+                    *e,
+                )
+            })
+            .collect();
+        by_distance.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+        let nearest: Vec<Entity> = by_distance.iter().take(5).map(|(_, e)| *e).collect();
+        assert_eq!(index.k_nearest(center, 5), nearest);
+
+        let mut in_sphere: Vec<Entity> = by_distance
+            .iter()
+            .filter(|(d, _)| *d <= 400.0)
+            .map(|(_, e)| *e)
+            .collect();
+        in_sphere.sort();
+        assert_eq!(index.query_sphere(center, 20.0), in_sphere);
+    }
+
+    #[test]
+    fn test_incremental_updates_and_raycast() {
+        let mut world = setup();
+        let wall = spawn_box(&mut world, Vec3::new(10.0, 0.0, 0.0), true);
+        let enemy = spawn_box(&mut world, Vec3::new(5.0, 0.0, 0.0), false);
+        world.insert(enemy, Velocity(Vec3::new(0.0, 10.0, 0.0)));
+        let pooled = spawn_box(&mut world, Vec3::new(3.0, 0.0, 0.0), false);
+        update_spatial_index(&mut world);
+        world.increment_change_tick();
+
+        let ray = Ray::new(Vec3::ZERO, Vec3::X);
+        let hits: Vec<Entity> = index(&world)
+            .raycast(&ray, 100.0)
+            .iter()
+            .map(|(e, _)| *e)
+            .collect();
+        assert_eq!(hits, vec![pooled, enemy, wall]);
+        assert_eq!(
+            index(&world).raycast_first(&ray, 100.0),
+            Some((pooled, 2.5))
+        );
+
+        // El enemigo se mueve fuera del rayo y el otro se desactiva.
+        for (transform, velocity) in Query::<(&mut Transform, &Velocity)>::new(&mut world).iter() {
+            transform.position += velocity.0;
+        }
+        world.set_active(pooled, false);
+        update_spatial_index(&mut world);
+        world.increment_change_tick();
+        assert_eq!(index(&world).raycast(&ray, 100.0), vec![(wall, 9.5)]);
+        assert_eq!(
+            index(&world).bounds_of(enemy).unwrap().center(),
+            Vec3::new(5.0, 10.0, 0.0)
+        );
+
+        world.despawn_entity(wall);
+        update_spatial_index(&mut world);
+        assert!(index(&world).raycast(&ray, 100.0).is_empty());
+        assert_eq!(index(&world).len(), 1);
+
+        // Otro consumidor que lee los cambios antes no se los quita al índice.
+        world.increment_change_tick();
+        let since = world.change_tick();
+        world.get_mut::<Transform>(enemy).unwrap().position.x = 1.0;
+        let seen = world.changed_since(ComponentId::of::<Transform>(), since);
+        update_spatial_index(&mut world);
+        assert_eq!(seen, vec![enemy]);
+        assert_eq!(
+            index(&world).bounds_of(enemy).unwrap().center(),
+            Vec3::new(1.0, 10.0, 0.0)
+        );
+
+        // En el frame siguiente, sin cambios no se reprocesa nada.
+        world.increment_change_tick();
+        assert_eq!(
+            world.changed_since(ComponentId::of::<Transform>(), world.change_tick()),
+            vec![]
+        );
+    }
+}
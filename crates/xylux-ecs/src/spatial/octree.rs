@@ -0,0 +1,234 @@
+//! Octree dinámico para entidades que se mueven.
+//!
+//! Cada entidad se guarda en el nodo más profundo que contiene su caja por completo,
+//! así que moverla es `O(profundidad)`. Un nodo se divide en ocho al superar
+//! `MAX_ITEMS` elementos. Las cajas que se salen de los límites del octree se guardan
+//! en la raíz, por lo que nunca se pierden entidades, solo eficiencia.
+
+use super::aabb::{Aabb, Ray};
+use super::bvh::{Candidate, NodeDistance, push_candidate};
+use crate::entity::Entity;
+use glam::Vec3;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+/// Elementos por nodo antes de dividirlo.
+const MAX_ITEMS: usize = 8;
+/// Profundidad máxima de subdivisión.
+const MAX_DEPTH: u32 = 8;
+
+#[derive(Clone, Debug)]
+struct OctreeNode {
+    bounds: Aabb,
+    depth: u32,
+    items: Vec<(Entity, Aabb)>,
+    /// Índice del primero de los ocho hijos consecutivos en `Octree::nodes`.
+    children: Option<usize>,
+}
+
+impl OctreeNode {
+    fn new(bounds: Aabb, depth: u32) -> Self {
+        Self {
+            bounds,
+            depth,
+            items: Vec::new(),
+            children: None,
+        }
+    }
+}
+
+/// Octree con inserción, eliminación y actualización incrementales.
+#[derive(Clone, Debug)]
+pub struct Octree {
+    nodes: Vec<OctreeNode>,
+    /// Entidad -> nodo que la contiene.
+    locations: HashMap<Entity, usize>,
+}
+
+impl Octree {
+    /// Crea un octree que cubre `bounds`.
+    pub fn new(bounds: Aabb) -> Self {
+        Self {
+            nodes: vec![OctreeNode::new(bounds, 0)],
+            locations: HashMap::new(),
+        }
+    }
+
+    /// Límites de la raíz.
+    pub fn bounds(&self) -> Aabb {
+        self.nodes[0].bounds
+    }
+
+    /// Número de entidades.
+    pub fn len(&self) -> usize {
+        self.locations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.locations.is_empty()
+    }
+
+    /// Inserta una entidad, o la recoloca si ya estaba.
+    pub fn insert(&mut self, entity: Entity, aabb: Aabb) {
+        self.remove(entity);
+        let node = self.deepest_containing(0, &aabb);
+        self.nodes[node].items.push((entity, aabb));
+        self.locations.insert(entity, node);
+
+        let target = &self.nodes[node];
+        if target.children.is_none() && target.items.len() > MAX_ITEMS && target.depth < MAX_DEPTH {
+            self.split(node);
+        }
+    }
+
+    /// Elimina una entidad. Devuelve `false` si no estaba.
+    pub fn remove(&mut self, entity: Entity) -> bool {
+        let Some(node) = self.locations.remove(&entity) else {
+            return false;
+        };
+        let items = &mut self.nodes[node].items;
+        if let Some(position) = items.iter().position(|(e, _)| *e == entity) {
+            items.swap_remove(position);
+        }
+        true
+    }
+
+    fn deepest_containing(&self, mut node: usize, aabb: &Aabb) -> usize {
+        while let Some(first) = self.nodes[node].children {
+            match (first..first + 8).find(|&child| self.nodes[child].bounds.contains(aabb)) {
+                Some(child) => node = child,
+                None => break,
+            }
+        }
+        node
+    }
+
+    fn split(&mut self, node: usize) {
+        let OctreeNode { bounds, depth, .. } = self.nodes[node];
+        let center = bounds.center();
+        let first = self.nodes.len();
+        for octant in 0..8 {
+            let pick = |bit: usize, low: f32, high: f32| if octant & bit == 0 { low } else { high };
+            let min = Vec3::new(
+                pick(1, bounds.min.x, center.x),
+                pick(2, bounds.min.y, center.y),
+                pick(4, bounds.min.z, center.z),
+            );
+            let max = Vec3::new(
+                pick(1, center.x, bounds.max.x),
+                pick(2, center.y, bounds.max.y),
+                pick(4, center.z, bounds.max.z),
+            );
+            self.nodes
+                .push(OctreeNode::new(Aabb::new(min, max), depth + 1));
+        }
+        self.nodes[node].children = Some(first);
+
+        // Baja a los hijos los elementos que caben por completo en uno de ellos.
+        for (entity, aabb) in std::mem::take(&mut self.nodes[node].items) {
+            let target =
+                match (first..first + 8).find(|&child| self.nodes[child].bounds.contains(&aabb)) {
+                    Some(child) => child,
+                    None => node,
+                };
+            self.nodes[target].items.push((entity, aabb));
+            self.locations.insert(entity, target);
+        }
+    }
+
+    /// Recorre los elementos de los nodos cuyos límites cumplen `visit_node`.
+    ///
+    /// Los elementos de la raíz se visitan siempre porque pueden estar fuera de sus límites.
+    fn traverse(
+        &self,
+        mut visit_node: impl FnMut(&Aabb) -> bool,
+        mut visit_item: impl FnMut(Entity, &Aabb),
+    ) {
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            for (entity, aabb) in &node.items {
+                visit_item(*entity, aabb);
+            }
+            if let Some(first) = node.children {
+                stack.extend(
+                    (first..first + 8).filter(|&child| visit_node(&self.nodes[child].bounds)),
+                );
+            }
+        }
+    }
+
+    /// Añade a `out` las entidades cuya caja solapa con `query`.
+    pub fn query_aabb(&self, query: &Aabb, out: &mut Vec<Entity>) {
+        self.traverse(
+            |node| node.intersects(query),
+            |entity, aabb| {
+                if aabb.intersects(query) {
+                    out.push(entity);
+                }
+            },
+        );
+    }
+
+    /// Añade a `out` las entidades cuya caja interseca la esfera.
+    pub fn query_sphere(&self, center: Vec3, radius: f32, out: &mut Vec<Entity>) {
+        self.traverse(
+            |node| node.intersects_sphere(center, radius),
+            |entity, aabb| {
+                if aabb.intersects_sphere(center, radius) {
+                    out.push(entity);
+                }
+            },
+        );
+    }
+
+    /// Añade a `out` los impactos del rayo `(entidad, distancia)`.
+    pub fn raycast(&self, ray: &Ray, max_distance: f32, out: &mut Vec<(Entity, f32)>) {
+        self.traverse(
+            |node| node.ray_intersection(ray, max_distance).is_some(),
+            |entity, aabb| {
+                if let Some(t) = aabb.ray_intersection(ray, max_distance) {
+                    out.push((entity, t));
+                }
+            },
+        );
+    }
+
+    /// Añade a `out` las `k` entidades más cercanas a `point` como `(entidad, distancia²)`.
+    pub fn k_nearest(&self, point: Vec3, k: usize, out: &mut Vec<(Entity, f32)>) {
+        if k == 0 {
+            return;
+        }
+        let mut best = BinaryHeap::new();
+        // La raíz tiene distancia 0: sus elementos pueden estar fuera de sus límites.
+        let mut frontier = BinaryHeap::from([Reverse(NodeDistance(0.0, 0))]);
+
+        while let Some(Reverse(NodeDistance(distance, index))) = frontier.pop() {
+            if best.len() == k
+                && best
+                    .peek()
+                    .is_some_and(|worst: &Candidate| distance > worst.0)
+            {
+                break;
+            }
+            let node = &self.nodes[index];
+            for (entity, aabb) in &node.items {
+                push_candidate(
+                    &mut best,
+                    k,
+                    Candidate(aabb.distance_squared_to_point(point), *entity),
+                );
+            }
+            if let Some(first) = node.children {
+                for child in first..first + 8 {
+                    let distance = self.nodes[child].bounds.distance_squared_to_point(point);
+                    frontier.push(Reverse(NodeDistance(distance, child)));
+                }
+            }
+        }
+        out.extend(
+            best.into_iter()
+                .map(|Candidate(distance, entity)| (entity, distance)),
+        );
+    }
+}
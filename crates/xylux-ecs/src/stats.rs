@@ -0,0 +1,201 @@
+//! # Módulo de Estadísticas del World
+//!
+//! Ocupación y memoria del `World`, obtenidas con `World::stats()`.
+//!
+//! Cada `ComponentStorage` reserva un hueco por entidad posible al registrarse, así
+//! que un componente usado por pocas entidades desperdicia casi toda su memoria.
+//! Estas estadísticas hacen visible ese coste. Los tamaños cuentan solo la memoria en
+//! línea de los componentes (`size_of::<T>()`), no la que posean en el heap (e.g. el
+//! texto de un `Name`). El bitmask de presencia y los ticks de detección de cambios
+//! se cuentan aparte, como coste fijo del almacenamiento.
+
+use std::fmt;
+
+/// Estrategia de almacenamiento de un componente.
+///
+/// Hoy todos los componentes usan `DenseVec`; el campo fija la forma de la API para
+/// cuando haya otras.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StorageStrategy {
+    /// `Vec<T>` denso indexado por ID de entidad, con un bitmask de presencia.
+    DenseVec,
+}
+
+impl fmt::Display for StorageStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageStrategy::DenseVec => write!(f, "dense-vec"),
+        }
+    }
+}
+
+/// Ocupación de un tipo de componente.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ComponentStats {
+    /// Nombre del tipo registrado.
+    pub type_name: &'static str,
+    /// Cómo se almacena.
+    pub strategy: StorageStrategy,
+    /// Huecos reservados.
+    pub capacity: usize,
+    /// Entidades que tienen el componente.
+    pub live: usize,
+    /// Tamaño en bytes de un componente.
+    pub item_size: usize,
+    /// Bytes ocupados por componentes vivos.
+    pub bytes_used: usize,
+    /// Bytes reservados sin componente vivo.
+    pub bytes_wasted: usize,
+    /// Bytes del bitmask de presencia.
+    pub bitmask_bytes: usize,
+    /// Bytes de los ticks de detección de cambios y su registro.
+    pub tick_bytes: usize,
+}
+
+impl ComponentStats {
+    /// Bytes de las estructuras auxiliares (bitmask y ticks).
+    pub fn bytes_overhead(&self) -> usize {
+        self.bitmask_bytes + self.tick_bytes
+    }
+
+    /// Proporción de huecos ocupados (0.0..=1.0).
+    pub fn occupancy(&self) -> f64 {
+        if self.capacity == 0 {
+            0.0
+        } else {
+            self.live as f64 / self.capacity as f64
+        }
+    }
+}
+
+/// Estadísticas de memoria y ocupación de un `World`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorldStats {
+    /// Capacidad máxima de entidades.
+    pub capacity: usize,
+    /// Entidades vivas (activas e inactivas).
+    pub alive: usize,
+    /// Entidades vivas desactivadas.
+    pub inactive: usize,
+    /// IDs creados hasta ahora (`World::entity_count`, incluye huecos reciclables).
+    pub high_water_mark: usize,
+    /// IDs libres a la espera de reciclarse.
+    pub free_list: usize,
+    /// Entidades creadas desde la creación del mundo.
+    pub spawned: u64,
+    /// Entidades eliminadas desde la creación del mundo.
+    pub despawned: u64,
+    /// Creaciones que reutilizaron un ID libre.
+    pub recycled: u64,
+    /// Número de recursos.
+    pub resources: usize,
+    /// Estadísticas por componente, ordenadas por nombre de tipo.
+    pub components: Vec<ComponentStats>,
+}
+
+impl WorldStats {
+    /// Bytes ocupados por componentes vivos en todos los almacenamientos.
+    pub fn bytes_used(&self) -> usize {
+        self.components.iter().map(|c| c.bytes_used).sum()
+    }
+
+    /// Bytes reservados sin usar en todos los almacenamientos.
+    pub fn bytes_wasted(&self) -> usize {
+        self.components.iter().map(|c| c.bytes_wasted).sum()
+    }
+
+    /// Bytes de bitmasks y ticks en todos los almacenamientos.
+    pub fn bytes_overhead(&self) -> usize {
+        self.components
+            .iter()
+            .map(ComponentStats::bytes_overhead)
+            .sum()
+    }
+
+    /// Estadísticas de un componente por nombre de tipo (completo o sin ruta).
+    pub fn component(&self, type_name: &str) -> Option<&ComponentStats> {
+        self.components.iter().find(|c| {
+            c.type_name == type_name || c.type_name.rsplit("::").next() == Some(type_name)
+        })
+    }
+}
+
+impl fmt::Display for WorldStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "entities: {} alive ({} inactive) / {} capacity, high water {}, free list {}",
+            self.alive, self.inactive, self.capacity, self.high_water_mark, self.free_list
+        )?;
+        writeln!(
+            f,
+            "churn: {} spawned, {} despawned, {} recycled",
+            self.spawned, self.despawned, self.recycled
+        )?;
+        writeln!(
+            f,
+            "memory: {} bytes used, {} bytes wasted, {} bytes overhead",
+            self.bytes_used(),
+            self.bytes_wasted(),
+            self.bytes_overhead()
+        )?;
+        for c in &self.components {
+            writeln!(
+                f,
+                "  {} [{}]: {}/{} live, {} used, {} wasted, {} overhead",
+                c.type_name,
+                c.strategy,
+                c.live,
+                c.capacity,
+                c.bytes_used,
+                c.bytes_wasted,
+                c.bytes_overhead()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StorageStrategy;
+    use crate::component::{Transform, Velocity};
+    use crate::world::World;
+
+    #[test]
+    fn test_world_stats() {
+        let mut world = World::new(100);
+        world.register_component::<Transform>();
+        world.register_component::<Velocity>();
+
+        let entities: Vec<_> = (0..10).map(|_| world.spawn_entity()).collect();
+        for &entity in &entities {
+            world.insert(entity, Transform::default());
+        }
+        world.insert(entities[0], Velocity::default());
+        world.despawn_entity(entities[1]);
+        world.despawn_entity(entities[2]);
+        world.spawn_entity();
+        world.set_active(entities[3], false);
+
+        let stats = world.stats();
+        assert_eq!(stats.capacity, 100);
+        assert_eq!(stats.alive, 9);
+        assert_eq!(stats.inactive, 1);
+        assert_eq!(stats.high_water_mark, 10);
+        assert_eq!(stats.free_list, 1);
+        assert_eq!((stats.spawned, stats.despawned, stats.recycled), (11, 2, 1));
+
+        let transform = stats.component("Transform").unwrap();
+        let size = std::mem::size_of::<Transform>();
+        assert_eq!(transform.capacity, 100);
+        assert_eq!(transform.live, 8);
+        assert_eq!(transform.bytes_used, 8 * size);
+        assert_eq!(transform.bytes_wasted, 92 * size);
+        assert!(transform.bitmask_bytes >= 100 / 8);
+        assert!(transform.tick_bytes >= 100 * std::mem::size_of::<u32>());
+        assert_eq!(transform.strategy, StorageStrategy::DenseVec);
+        assert_eq!(stats.component("Velocity").unwrap().live, 1);
+        assert!(stats.to_string().contains("9 alive (1 inactive)"));
+    }
+}
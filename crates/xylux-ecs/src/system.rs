@@ -4,12 +4,16 @@
 //! Incluye:
 //! - `System`: encapsula una función que opera sobre el mundo.
 //! - `TaskGraph`: organiza sistemas con dependencias y permite ejecución segura.
+//! - `ScheduleError`: errores de validación del grafo (ciclos, dependencias, nombres).
 //! - Ejemplo: `move_system`, que actualiza posición según Velocity.
 
 use crate::component::{Transform, Velocity};
+use crate::profiling::{ScheduleStats, SystemSample};
 use crate::query::Query;
 use crate::world::World;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Instant;
 
 /// --- SYSTEM ---
 /// Representa un sistema ECS ejecutable.
@@ -20,7 +24,9 @@ pub struct System {
 impl System {
     /// Crea un nuevo sistema a partir de una función.
     pub fn new<F: FnMut(&mut World) + Send + Sync + 'static>(func: F) -> Self {
-        Self { func: Box::new(func) }
+        Self {
+            func: Box::new(func),
+        }
     }
 
     /// Ejecuta el sistema sobre el mundo.
@@ -29,77 +35,294 @@ impl System {
     }
 }
 
+/// --- SCHEDULE ERROR ---
+/// Errores detectados al construir o validar un `TaskGraph`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScheduleError {
+    /// Ya existe un sistema registrado con ese nombre.
+    DuplicateSystem(String),
+    /// Uno o más sistemas dependen de sistemas que no existen.
+    /// Cada par es `(sistema, dependencia_inexistente)`.
+    MissingDependencies(Vec<(String, String)>),
+    /// Ciclo de dependencias. El camino empieza y termina en el mismo sistema,
+    /// e.g. `["a", "b", "c", "a"]`.
+    Cycle(Vec<String>),
+}
+
+impl fmt::Display for ScheduleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScheduleError::DuplicateSystem(name) => {
+                write!(
+                    f,
+                    "El sistema '{}' ya está registrado en el TaskGraph",
+                    name
+                )
+            }
+            ScheduleError::MissingDependencies(missing) => {
+                write!(f, "Dependencias inexistentes en el TaskGraph:")?;
+                for (system, dependency) in missing {
+                    write!(f, " '{}' -> '{}';", system, dependency)?;
+                }
+                Ok(())
+            }
+            ScheduleError::Cycle(path) => {
+                write!(f, "Ciclo detectado en TaskGraph: {}", path.join(" -> "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScheduleError {}
+
+/// Nodo del grafo: un sistema con su nombre y sus dependencias.
+struct SystemNode {
+    name: String,
+    dependencies: Vec<String>,
+    system: System,
+}
+
 /// --- TASK GRAPH ---
 /// Grafo de tareas/sistemas con dependencias.
+///
+/// Los sistemas se agrupan en *etapas* (stages): una etapa contiene sistemas cuyas
+/// dependencias están todas en etapas anteriores, por lo que podrían ejecutarse en
+/// paralelo. El orden dentro de cada etapa respeta el orden de inserción, lo que hace
+/// la planificación determinista.
+///
+/// Con el profiling activo (ver `set_profiling`), cada `run()` publica tiempos y conteos por
+/// sistema en el recurso `ScheduleStats` del mundo.
+#[derive(Default)]
 pub struct TaskGraph {
-    systems: HashMap<String, (System, Vec<String>)>, // nombre -> (sistema, dependencias)
-    execution_order: Vec<String>,                    // orden topológico calculado
+    systems: Vec<SystemNode>,
+    index: HashMap<String, usize>, // nombre -> posición en `systems`
+    stages: Vec<Vec<usize>>,       // etapas calculadas por `build()`
+    dirty: bool,                   // hay cambios sin validar
+    profiling: bool,
 }
 
 impl TaskGraph {
     /// Crea un grafo vacío.
     pub fn new() -> Self {
-        Self {
-            systems: HashMap::new(),
-            execution_order: Vec::new(),
-        }
+        Self::default()
+    }
+
+    /// Activa o desactiva la recogida de `ScheduleStats` en `run()`; por defecto
+    /// está desactivada.
+    pub fn set_profiling(&mut self, enabled: bool) {
+        self.profiling = enabled;
     }
 
     /// Añade un sistema al grafo.
-    pub fn add_system(&mut self, name: String, dependencies: Vec<String>, system: System) {
-        self.systems.insert(name.clone(), (system, dependencies));
-        // Es más eficiente recalcular el orden aquí que en cada `run()`.
-        self.compute_execution_order();
+    ///
+    /// Las dependencias pueden referirse a sistemas que aún no se han añadido;
+    /// se validan en `build()`.
+    ///
+    /// # Errors
+    /// `ScheduleError::DuplicateSystem` si ya existe un sistema con ese nombre.
+    pub fn add_system(
+        &mut self,
+        name: String,
+        dependencies: Vec<String>,
+        system: System,
+    ) -> Result<(), ScheduleError> {
+        if self.index.contains_key(&name) {
+            return Err(ScheduleError::DuplicateSystem(name));
+        }
+        self.index.insert(name.clone(), self.systems.len());
+        self.systems.push(SystemNode {
+            name,
+            dependencies,
+            system,
+        });
+        self.dirty = true;
+        Ok(())
     }
 
-    /// Calcula el orden topológico de ejecución según dependencias.
-    fn compute_execution_order(&mut self) {
-        let mut visited = HashSet::new();
-        let mut temp_mark = HashSet::new();
-        self.execution_order.clear();
+    /// Valida el grafo y calcula las etapas de ejecución.
+    ///
+    /// # Errors
+    /// - `ScheduleError::MissingDependencies` si algún sistema depende de uno inexistente.
+    /// - `ScheduleError::Cycle` con el camino del primer ciclo encontrado.
+    pub fn build(&mut self) -> Result<(), ScheduleError> {
+        self.stages = self.compute_stages()?;
+        self.dirty = false;
+        Ok(())
+    }
 
+    /// Calcula las etapas sin modificar el grafo.
+    fn compute_stages(&self) -> Result<Vec<Vec<usize>>, ScheduleError> {
+        let missing: Vec<(String, String)> = self
+            .systems
+            .iter()
+            .flat_map(|node| {
+                node.dependencies
+                    .iter()
+                    .filter(|dep| !self.index.contains_key(*dep))
+                    .map(|dep| (node.name.clone(), dep.clone()))
+            })
+            .collect();
+        if !missing.is_empty() {
+            return Err(ScheduleError::MissingDependencies(missing));
+        }
+
+        // DFS con marcas temporales; `path` guarda la pila actual para reportar el ciclo.
+        // `levels[i]` es la etapa del sistema `i` una vez visitado.
         fn visit(
-            node: &str,
-            systems: &HashMap<String, (System, Vec<String>)>,
-            visited: &mut HashSet<String>,
-            temp_mark: &mut HashSet<String>,
-            order: &mut Vec<String>,
-        ) {
-            if visited.contains(node) {
-                return;
+            node: usize,
+            graph: &TaskGraph,
+            levels: &mut [Option<usize>],
+            path: &mut Vec<usize>,
+        ) -> Result<usize, ScheduleError> {
+            if let Some(level) = levels[node] {
+                return Ok(level);
             }
-            if temp_mark.contains(node) {
-                panic!("Ciclo detectado en TaskGraph en '{}'", node);
+            if let Some(start) = path.iter().position(|&n| n == node) {
+                let mut cycle: Vec<String> = path[start..]
+                    .iter()
+                    .map(|&n| graph.systems[n].name.clone())
+                    .collect();
+                cycle.push(graph.systems[node].name.clone());
+                return Err(ScheduleError::Cycle(cycle));
             }
-            temp_mark.insert(node.to_string());
+            path.push(node);
 
-            if let Some((_, deps)) = systems.get(node) {
-                for dep in deps {
-                    visit(dep, systems, visited, temp_mark, order);
-                }
+            let mut level = 0;
+            for dep in &graph.systems[node].dependencies {
+                let dep_level = visit(graph.index[dep], graph, levels, path)?;
+                level = level.max(dep_level + 1);
             }
 
-            temp_mark.remove(node);
-            visited.insert(node.to_string());
-            order.push(node.to_string());
+            path.pop();
+            levels[node] = Some(level);
+            Ok(level)
+        }
+
+        let mut levels = vec![None; self.systems.len()];
+        let mut path = Vec::new();
+        let mut stages: Vec<Vec<usize>> = Vec::new();
+        for node in 0..self.systems.len() {
+            visit(node, self, &mut levels, &mut path)?;
+        }
+        for (node, level) in levels.into_iter().enumerate() {
+            let level = level.expect("Todos los nodos han sido visitados");
+            if stages.len() <= level {
+                stages.resize_with(level + 1, Vec::new);
+            }
+            stages[level].push(node);
+        }
+        Ok(stages)
+    }
+
+    /// Devuelve los nombres de los sistemas en orden de ejecución.
+    ///
+    /// Vacío si el grafo no se ha construido con éxito desde el último cambio.
+    pub fn execution_order(&self) -> Vec<&str> {
+        self.stages().into_iter().flatten().collect()
+    }
+
+    /// Devuelve las etapas calculadas; los sistemas de una misma etapa no dependen
+    /// entre sí.
+    ///
+    /// Vacío si el grafo no se ha construido con éxito desde el último cambio.
+    pub fn stages(&self) -> Vec<Vec<&str>> {
+        if self.dirty {
+            return Vec::new();
+        }
+        self.stages
+            .iter()
+            .map(|stage| {
+                stage
+                    .iter()
+                    .map(|&i| self.systems[i].name.as_str())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Exporta el grafo en formato Graphviz DOT.
+    ///
+    /// Cada etapa se dibuja como un `cluster` y las aristas van de la dependencia al
+    /// sistema que depende de ella. Si el grafo no es válido (ciclos o dependencias
+    /// inexistentes) se exportan los nodos y aristas sin agrupar en etapas.
+    pub fn to_dot(&self) -> String {
+        let quote = |name: &str| format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""));
+        let mut dot = String::from("digraph TaskGraph {\n    rankdir=LR;\n    node [shape=box];\n");
+
+        match self.compute_stages() {
+            Ok(stages) => {
+                for (i, stage) in stages.iter().enumerate() {
+                    dot.push_str(&format!("    subgraph cluster_stage_{} {{\n", i));
+                    dot.push_str(&format!("        label=\"Stage {}\";\n", i));
+                    for &node in stage {
+                        dot.push_str(&format!("        {};\n", quote(&self.systems[node].name)));
+                    }
+                    dot.push_str("    }\n");
+                }
+            }
+            Err(_) => {
+                for node in &self.systems {
+                    dot.push_str(&format!("    {};\n", quote(&node.name)));
+                }
+            }
         }
 
-        for node in self.systems.keys() {
-            visit(node, &self.systems, &mut visited, &mut temp_mark, &mut self.execution_order);
+        for node in &self.systems {
+            for dep in &node.dependencies {
+                dot.push_str(&format!("    {} -> {};\n", quote(dep), quote(&node.name)));
+            }
         }
+        dot.push_str("}\n");
+        dot
     }
 
-    /// Ejecuta todos los sistemas en orden topológico.
+    /// Ejecuta todos los sistemas en orden topológico, tras avanzar el tick de
+    /// detección de cambios del mundo (`World::increment_change_tick`).
+    ///
+    /// # Panics
+    /// Si hay cambios sin construir y el grafo no es válido. Llama a `build()` antes
+    /// para obtener el `ScheduleError` en lugar de un pánico.
     pub fn run(&mut self, world: &mut World) {
-        // Para evitar problemas con el borrow checker al iterar y mutar `self.systems`
-        // a la vez, movemos temporalmente los sistemas fuera de la estructura.
-        let mut systems = std::mem::take(&mut self.systems);
-        for name in &self.execution_order {
-            if let Some((system, _)) = systems.get_mut(name) {
-                system.run(world);
+        if self.dirty
+            && let Err(error) = self.build()
+        {
+            panic!("{}", error);
+        }
+        // Un tick por frame: los cambios de este frame se distinguen de los anteriores.
+        world.increment_change_tick();
+        if !self.profiling {
+            for stage in &self.stages {
+                for &node in stage {
+                    self.systems[node].system.run(world);
+                }
             }
+            return;
+        }
+
+        let frame_start = Instant::now();
+        let mut samples = Vec::with_capacity(self.systems.len());
+        for (stage_index, stage) in self.stages.iter().enumerate() {
+            for &node in stage {
+                let fetched = world.fetched_entities;
+                let start = Instant::now();
+                self.systems[node].system.run(world);
+                samples.push(SystemSample {
+                    name: self.systems[node].name.clone(),
+                    stage: stage_index,
+                    start,
+                    duration: start.elapsed(),
+                    entities: world.fetched_entities - fetched,
+                });
+            }
+        }
+        let frame_time = frame_start.elapsed();
+
+        if world.resource::<ScheduleStats>().is_none() {
+            world.insert_resource(ScheduleStats::default());
+        }
+        if let Some(stats) = world.resource_mut::<ScheduleStats>() {
+            stats.record_frame(frame_start, frame_time, samples);
         }
-        self.systems = systems;
     }
 }
 
@@ -114,3 +337,143 @@ pub fn move_system() -> System {
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    fn noop() -> System {
+        System::new(|_| {})
+    }
+
+    fn deps(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_build_orders_by_dependencies() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut graph = TaskGraph::new();
+        for (name, dependencies) in [
+            ("render", vec!["physics", "ai"]),
+            ("physics", vec!["input"]),
+            ("ai", vec![]),
+            ("input", vec![]),
+        ] {
+            let log = log.clone();
+            graph
+                .add_system(
+                    name.into(),
+                    deps(&dependencies),
+                    System::new(move |_| log.lock().unwrap().push(name)),
+                )
+                .unwrap();
+        }
+        graph.build().unwrap();
+
+        assert_eq!(
+            graph.stages(),
+            vec![vec!["ai", "input"], vec!["physics"], vec!["render"]]
+        );
+
+        graph.run(&mut World::new(1));
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec!["ai", "input", "physics", "render"]
+        );
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        let mut graph = TaskGraph::new();
+        graph.add_system("a".into(), deps(&["c"]), noop()).unwrap();
+        assert_eq!(
+            graph.add_system("a".into(), vec![], noop()),
+            Err(ScheduleError::DuplicateSystem("a".into()))
+        );
+
+        graph
+            .add_system("b".into(), deps(&["a", "ghost"]), noop())
+            .unwrap();
+        assert_eq!(
+            graph.build(),
+            Err(ScheduleError::MissingDependencies(vec![
+                ("a".into(), "c".into()),
+                ("b".into(), "ghost".into()),
+            ]))
+        );
+
+        let mut graph = TaskGraph::new();
+        graph.add_system("a".into(), deps(&["c"]), noop()).unwrap();
+        graph.add_system("b".into(), deps(&["a"]), noop()).unwrap();
+        graph.add_system("c".into(), deps(&["b"]), noop()).unwrap();
+        assert_eq!(
+            graph.build(),
+            Err(ScheduleError::Cycle(deps(&["a", "c", "b", "a"])))
+        );
+        assert!(graph.execution_order().is_empty());
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let mut graph = TaskGraph::new();
+        graph.add_system("input".into(), vec![], noop()).unwrap();
+        graph
+            .add_system("move".into(), deps(&["input"]), noop())
+            .unwrap();
+
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph TaskGraph {"));
+        assert!(dot.contains(
+            "subgraph cluster_stage_0 {\n        label=\"Stage 0\";\n        \"input\";"
+        ));
+        assert!(dot.contains("subgraph cluster_stage_1"));
+        assert!(dot.contains("\"input\" -> \"move\";"));
+    }
+
+    #[test]
+    fn test_run_advances_change_tick_once_per_frame() {
+        use crate::component::ComponentId;
+        use glam::Vec3;
+
+        let mut world = World::new(64);
+        world.register_component::<Velocity>();
+        let moving = world.spawn_entity();
+        world.insert(moving, Velocity(Vec3::X));
+        let doomed = world.spawn_entity();
+        world.insert(doomed, Velocity(Vec3::Y));
+
+        // Dos consumidores de cambios en el mismo frame: ninguno se los quita al otro.
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut graph = TaskGraph::new();
+        for name in ["a", "b"] {
+            let seen = seen.clone();
+            let mut since = 0;
+            let system = System::new(move |world| {
+                let changed = world.changed_since(ComponentId::of::<Velocity>(), since);
+                let ids: Vec<usize> = changed.iter().map(|entity| entity.id).collect();
+                seen.lock().unwrap().push(ids);
+                since = world.change_tick();
+            });
+            graph.add_system(name.into(), vec![], system).unwrap();
+        }
+        graph.run(&mut world);
+        let both = vec![vec![moving.id, doomed.id]; 2];
+        assert_eq!(*seen.lock().unwrap(), both);
+
+        // Muchos cambios compactan el registro sin perder la eliminación.
+        world.despawn_entity(doomed);
+        for _ in 0..200 {
+            world.get_mut::<Velocity>(moving).unwrap().0.z += 1.0;
+            world.increment_change_tick();
+        }
+        seen.lock().unwrap().clear();
+        graph.run(&mut world);
+        assert_eq!(*seen.lock().unwrap(), both);
+
+        seen.lock().unwrap().clear();
+        graph.run(&mut world);
+        assert_eq!(*seen.lock().unwrap(), vec![Vec::<usize>::new(); 2]);
+    }
+}
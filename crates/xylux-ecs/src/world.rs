@@ -3,10 +3,16 @@
 //! Define el `World`, el contenedor principal del ECS que gestiona
 //! todas las entidades, componentes y sus ciclos de vida.
 
-use crate::component::{Component, ComponentId, ComponentStorage};
+use crate::component::{Component, ComponentId, ComponentStorage, Name};
 use crate::entity::Entity;
+use crate::relation::RelationIndex;
+use crate::scene::ComponentSerializer;
+use crate::stats::{ComponentStats, StorageStrategy, WorldStats};
 use bitvec::prelude::*;
-use std::collections::HashMap;
+use std::any::{Any, TypeId};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
 
 /// Contenedor principal del ECS.
 ///
@@ -17,7 +23,27 @@ pub struct World {
     pub(crate) components: HashMap<ComponentId, ComponentStorage>,
     entity_versions: Vec<u32>,
     free_entities: Vec<usize>,
+    /// Si es `true`, `free_entities` se mantiene ordenada y se recicla el ID más bajo.
+    stable_order: bool,
     alive_mask: BitVec,
+    /// Entidades vivas desactivadas (e.g. por un `EntityPool`); las queries las ignoran.
+    inactive_mask: BitVec,
+    inactive_count: usize,
+    /// Contadores de rotación de entidades (ver `World::stats`).
+    spawned: u64,
+    despawned: u64,
+    recycled: u64,
+    resources: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+    /// Índice nombre -> entidades con ese `Name`. Ordenado para búsquedas por prefijo.
+    names: BTreeMap<String, Vec<Entity>>,
+    /// Índices de las relaciones registradas, por `ComponentId` de `Relation<R>`.
+    pub(crate) relations: HashMap<ComponentId, RelationIndex>,
+    /// Componentes serializables por nombre registrado (ver `scene`).
+    pub(crate) serializers: BTreeMap<String, ComponentSerializer>,
+    /// Tick actual para la detección de cambios (ver `World::increment_change_tick`).
+    pub(crate) change_tick: u32,
+    /// Número total de entidades entregadas por queries; usado para perfilar sistemas.
+    pub(crate) fetched_entities: usize,
 }
 
 impl World {
@@ -29,15 +55,51 @@ impl World {
             components: HashMap::new(),
             entity_versions: vec![0; max_entities],
             free_entities: Vec::new(),
+            stable_order: false,
             alive_mask: bitvec![0; max_entities],
+            inactive_mask: bitvec![0; max_entities],
+            inactive_count: 0,
+            spawned: 0,
+            despawned: 0,
+            recycled: 0,
+            resources: HashMap::new(),
+            names: BTreeMap::new(),
+            relations: HashMap::new(),
+            serializers: BTreeMap::new(),
+            change_tick: 1,
+            fetched_entities: 0,
         }
     }
 
+    /// Activa el modo de orden estable.
+    ///
+    /// Por defecto los IDs libres se reciclan en orden LIFO, así que el ID de una
+    /// entidad nueva depende del orden en que se eliminaron las anteriores. En modo
+    /// estable siempre se recicla el ID libre más bajo, de modo que simulaciones con
+    /// las mismas operaciones producen los mismos IDs (y el mismo orden de iteración)
+    /// en cualquier ejecución. Eliminar entidades pasa a costar `O(libres)`.
+    pub fn set_stable_order(&mut self, enabled: bool) {
+        self.stable_order = enabled;
+        if enabled {
+            self.free_entities.sort_unstable_by(|a, b| b.cmp(a));
+        }
+    }
+
+    /// Indica si el modo de orden estable está activo.
+    pub fn stable_order(&self) -> bool {
+        self.stable_order
+    }
+
     /// Genera una nueva entidad.
     ///
     /// Reutiliza IDs libres si los hay, sino incrementa `entity_count`.
     pub fn spawn_entity(&mut self) -> Entity {
-        let id = self.free_entities.pop().unwrap_or_else(|| {
+        let recycled = self.free_entities.pop();
+        self.spawned += 1;
+        if recycled.is_some() {
+            self.recycled += 1;
+        }
+        let id = recycled.unwrap_or_else(|| {
             if self.entity_count >= self.max_entities {
                 panic!("Max entities reached");
             }
@@ -62,11 +124,27 @@ impl World {
             return;
         }
 
+        self.unindex_name(entity);
+        self.unlink_relations(entity);
+        self.set_active(entity, true);
         self.entity_versions[entity.id] = self.entity_versions[entity.id].wrapping_add(1);
-        self.free_entities.push(entity.id);
+        if self.stable_order {
+            // Orden descendente: `pop` devuelve el ID libre más bajo.
+            let index = self.free_entities.partition_point(|&id| id > entity.id);
+            self.free_entities.insert(index, entity.id);
+        } else {
+            self.free_entities.push(entity.id);
+        }
         self.alive_mask.set(entity.id, false);
+        self.despawned += 1;
 
-        self.components.values_mut().for_each(|storage| storage.remove(entity.id));
+        let tick = self.change_tick;
+        for storage in self.components.values_mut() {
+            if storage.has(entity.id) {
+                storage.remove(entity.id);
+                storage.mark_changed(entity.id, tick);
+            }
+        }
     }
 
     /// Registra un nuevo tipo de componente en el mundo.
@@ -86,14 +164,258 @@ impl World {
     /// - Si el componente no ha sido registrado.
     pub fn insert<T: Component>(&mut self, entity: Entity, component: T) {
         if !self.is_alive(entity) {
-            panic!("Intento de insertar componente en entidad inválida");
+            panic!(
+                "Intento de insertar {} en entidad inválida {:?}",
+                std::any::type_name::<T>(),
+                entity
+            );
         }
 
         let id = ComponentId::of::<T>();
-        self.components
+        if !self.components.contains_key(&id) {
+            panic!(
+                "Componente {} no registrado (entidad {})",
+                std::any::type_name::<T>(),
+                self.entity_label(entity)
+            );
+        }
+
+        let is_name = (&component as &dyn Any).is::<Name>();
+        if is_name {
+            self.unindex_name(entity);
+        }
+        self.index_relation(id, entity, &component);
+
+        let storage = self
+            .components
             .get_mut(&id)
-            .expect("Componente no registrado")
-            .insert(entity.id, component);
+            .expect("Componente no registrado");
+        storage.insert(entity.id, component);
+        storage.mark_changed(entity.id, self.change_tick);
+
+        if is_name {
+            self.index_name(entity);
+        }
+    }
+
+    /// Elimina el componente `T` de una entidad y lo devuelve.
+    pub fn remove<T: Component>(&mut self, entity: Entity) -> Option<T> {
+        if !self.has_component(entity, ComponentId::of::<T>()) {
+            return None;
+        }
+        let id = ComponentId::of::<T>();
+        if id == ComponentId::of::<Name>() {
+            self.unindex_name(entity);
+        }
+        if let Some(index) = self.relations.get_mut(&id) {
+            index.unlink(entity);
+        }
+
+        let tick = self.change_tick;
+        let storage = self.components.get_mut(&id)?;
+        let component = storage.get_mut::<T>(entity.id).map(std::mem::take);
+        storage.remove(entity.id);
+        storage.mark_changed(entity.id, tick);
+        component
+    }
+
+    /// Mueve una entidad con todos sus componentes a otro mundo y devuelve su nuevo ID.
+    ///
+    /// Los tipos de componente que `other` no tenga registrados se registran
+    /// automáticamente. Las relaciones no se conservan, ya que sus destinos pertenecen a
+    /// este mundo; usa `move_entities_to` para mover un grupo con sus relaciones internas.
+    ///
+    /// # Panics
+    /// - Si la entidad no está viva.
+    /// - Si `other` alcanza su capacidad máxima.
+    pub fn move_entity_to(&mut self, other: &mut World, entity: Entity) -> Entity {
+        self.move_entities_to(other, &[entity])[0]
+    }
+
+    /// Mueve un grupo de entidades a otro mundo, devolviendo los nuevos IDs en el mismo orden.
+    ///
+    /// Las relaciones entre entidades del grupo se remapean a los nuevos IDs; las que
+    /// apuntan fuera del grupo (o desde fuera hacia él) se eliminan. Los nombres se
+    /// indexan en `other` y las entidades inactivas siguen inactivas.
+    ///
+    /// # Panics
+    /// Antes de mover nada:
+    /// - Si alguna entidad no está viva.
+    /// - Si no caben todas en `other`.
+    pub fn move_entities_to(&mut self, other: &mut World, entities: &[Entity]) -> Vec<Entity> {
+        if let Some(entity) = entities.iter().find(|&&entity| !self.is_alive(entity)) {
+            panic!("Intento de mover entidad inválida {:?}", entity);
+        }
+        let unique: HashSet<Entity> = entities.iter().copied().collect();
+        if unique.len() > other.remaining_capacity() {
+            panic!(
+                "El mundo destino no tiene hueco para {} entidades ({} libres)",
+                unique.len(),
+                other.remaining_capacity()
+            );
+        }
+
+        // Vec para un orden determinista; el HashMap solo para búsquedas.
+        let mut moved = Vec::with_capacity(unique.len());
+        let mut remap = HashMap::with_capacity(unique.len());
+        for &entity in entities {
+            if let Entry::Vacant(slot) = remap.entry(entity) {
+                let new = other.spawn_entity();
+                slot.insert(new);
+                moved.push((entity, new));
+            }
+        }
+
+        // Aristas salientes dentro del grupo, antes de que `despawn_entity` las borre.
+        let mut edges = Vec::new();
+        for index in self.relations.values() {
+            for &(source, new_source) in &moved {
+                if let Some(new_target) = index
+                    .forward
+                    .get(&source.id)
+                    .and_then(|target| remap.get(target))
+                {
+                    edges.push((index.relate, new_source, *new_target));
+                }
+            }
+        }
+
+        for &(entity, new) in &moved {
+            self.unindex_name(entity);
+            for (id, storage) in self.components.iter_mut() {
+                if !storage.has(entity.id) || self.relations.contains_key(id) {
+                    continue;
+                }
+                let target = other
+                    .components
+                    .entry(*id)
+                    .or_insert_with(|| (storage.empty)(other.max_entities));
+                (storage.transfer)(storage, entity.id, target, new.id);
+                target.mark_changed(new.id, other.change_tick);
+            }
+            if !self.is_active(entity) {
+                other.set_active(new, false);
+            }
+            other.index_name(new);
+            self.despawn_entity(entity);
+        }
+
+        for (relate, source, target) in edges {
+            relate(other, source, target);
+        }
+
+        entities.iter().map(|entity| remap[entity]).collect()
+    }
+
+    /// Absorbe todas las entidades de `other`, devolviendo la correspondencia
+    /// ID antiguo -> ID nuevo.
+    ///
+    /// Pensado para *streaming*: un chunk de nivel se construye en un `World` en otro
+    /// hilo y se fusiona en el mundo de juego. Las relaciones internas se conservan y
+    /// los recursos y serializadores que este mundo no tenga se copian de `other`.
+    ///
+    /// # Panics
+    /// Si este mundo alcanza su capacidad máxima.
+    pub fn merge(&mut self, mut other: World) -> HashMap<Entity, Entity> {
+        let entities: Vec<Entity> = other
+            .alive_mask
+            .iter_ones()
+            .map(|id| Entity {
+                id,
+                version: other.entity_versions[id],
+            })
+            .collect();
+        let moved = other.move_entities_to(self, &entities);
+
+        for (id, resource) in other.resources.drain() {
+            self.resources.entry(id).or_insert(resource);
+        }
+        for (name, serializer) in std::mem::take(&mut other.serializers) {
+            self.serializers.entry(name).or_insert(serializer);
+        }
+
+        entities.into_iter().zip(moved).collect()
+    }
+
+    /// Asigna (o cambia) el `Name` de una entidad, registrando el componente si hace falta.
+    ///
+    /// # Panics
+    /// Si la entidad no está viva.
+    pub fn set_name(&mut self, entity: Entity, name: impl Into<Name>) {
+        self.register_component::<Name>();
+        self.insert(entity, name.into());
+    }
+
+    /// Devuelve el `Name` de una entidad, si tiene.
+    pub fn name(&self, entity: Entity) -> Option<&str> {
+        self.get::<Name>(entity).map(Name::as_str)
+    }
+
+    /// Busca una entidad viva con el nombre exacto `name`.
+    ///
+    /// Si varias entidades comparten nombre, devuelve la primera que lo recibió.
+    pub fn find_by_name(&self, name: &str) -> Option<Entity> {
+        self.names.get(name).and_then(|entities| {
+            entities
+                .iter()
+                .copied()
+                .find(|&e| self.name_matches(e, name))
+        })
+    }
+
+    /// Devuelve todas las entidades vivas cuyo nombre empieza por `prefix`,
+    /// ordenadas por nombre.
+    pub fn find_all_by_prefix(&self, prefix: &str) -> Vec<Entity> {
+        self.names
+            .range(prefix.to_string()..)
+            .take_while(|(name, _)| name.starts_with(prefix))
+            .flat_map(|(name, entities)| {
+                entities
+                    .iter()
+                    .copied()
+                    .filter(move |&e| self.name_matches(e, name))
+            })
+            .collect()
+    }
+
+    /// Etiqueta legible de una entidad para logs y mensajes de error,
+    /// e.g. `Player (3v0)` o `Entity (3v0)` si no tiene nombre.
+    pub fn entity_label(&self, entity: Entity) -> String {
+        match self.name(entity) {
+            Some(name) => format!("{} ({}v{})", name, entity.id, entity.version),
+            None => format!("Entity ({}v{})", entity.id, entity.version),
+        }
+    }
+
+    /// Comprueba que una entrada del índice de nombres sigue siendo válida.
+    fn name_matches(&self, entity: Entity, name: &str) -> bool {
+        self.name(entity) == Some(name)
+    }
+
+    /// Añade la entidad al índice de nombres si tiene `Name`.
+    fn index_name(&mut self, entity: Entity) {
+        if let Some(name) = self
+            .get::<Name>(entity)
+            .map(|name| name.as_str().to_string())
+        {
+            self.names.entry(name).or_default().push(entity);
+        }
+    }
+
+    /// Quita la entidad del índice de nombres (antes de renombrarla o eliminarla).
+    fn unindex_name(&mut self, entity: Entity) {
+        let Some(old) = self
+            .get::<Name>(entity)
+            .map(|name| name.as_str().to_string())
+        else {
+            return;
+        };
+        if let Some(entities) = self.names.get_mut(&old) {
+            entities.retain(|&e| e != entity);
+            if entities.is_empty() {
+                self.names.remove(&old);
+            }
+        }
     }
 
     /// Obtiene una referencia inmutable al componente `T` de una entidad.
@@ -107,13 +429,108 @@ impl World {
     }
 
     /// Obtiene una referencia mutable al componente `T` de una entidad.
+    ///
+    /// Marca el componente como cambiado en el tick actual, se modifique o no. No
+    /// compila para componentes inmutables (ver `Component::MUTABLE`).
     pub fn get_mut<T: Component>(&mut self, entity: Entity) -> Option<&mut T> {
+        const {
+            assert!(
+                T::MUTABLE,
+                "Componente inmutable: sustitúyelo con World::insert en lugar de modificarlo"
+            )
+        };
         if !self.is_alive(entity) {
             return None;
         }
-        self.components
-            .get_mut(&ComponentId::of::<T>())
-            .and_then(|storage| storage.get_mut(entity.id))
+        let tick = self.change_tick;
+        let storage = self.components.get_mut(&ComponentId::of::<T>())?;
+        if !storage.has(entity.id) {
+            return None;
+        }
+        storage.mark_changed(entity.id, tick);
+        storage.get_mut(entity.id)
+    }
+
+    /// Tick actual de detección de cambios.
+    ///
+    /// Cada inserción, eliminación o `get_mut` de un componente (incluidas las queries
+    /// con `&mut T`) registra este tick en el almacenamiento.
+    pub fn change_tick(&self) -> u32 {
+        self.change_tick
+    }
+
+    /// Avanza el tick de detección de cambios y devuelve el que termina.
+    ///
+    /// `TaskGraph::run` lo llama una vez al empezar cada frame; sin `TaskGraph`, llámalo
+    /// una vez por frame. Los consumidores no deben avanzarlo: uno que guarda
+    /// `change_tick()` tras procesar los cambios los pide en la siguiente pasada con
+    /// `changed_since` (puede volver a ver cambios de su mismo tick, pero ninguno se
+    /// pierde, aunque haya varios consumidores).
+    pub fn increment_change_tick(&mut self) -> u32 {
+        let tick = self.change_tick;
+        self.change_tick = self.change_tick.wrapping_add(1).max(1);
+        tick
+    }
+
+    /// Comprueba si el componente `T` de una entidad cambió en un tick `>= since`.
+    pub fn is_changed<T: Component>(&self, entity: Entity, since: u32) -> bool {
+        self.is_alive(entity)
+            && self
+                .components
+                .get(&ComponentId::of::<T>())
+                .is_some_and(|storage| {
+                    storage.has(entity.id) && storage.changed_tick(entity.id) >= since
+                })
+    }
+
+    /// Entidades cuyo componente cambió, se añadió o se eliminó en un tick `>= since`.
+    ///
+    /// Incluye entidades que perdieron el componente o se eliminaron (con la versión
+    /// actual de su ID); compruébalo con `has_component` / `is_alive`. Se devuelven en
+    /// orden de ID, con un coste proporcional a los cambios, no a `max_entities`.
+    pub fn changed_since(&self, component_id: ComponentId, since: u32) -> Vec<Entity> {
+        let Some(storage) = self.components.get(&component_id) else {
+            return Vec::new();
+        };
+        let mut ids: Vec<usize> = storage.changed_since(since).collect();
+        ids.sort_unstable();
+        ids.into_iter()
+            .map(|id| Entity {
+                id,
+                version: self.entity_versions[id],
+            })
+            .collect()
+    }
+
+    /// Inserta un recurso global, reemplazando el anterior del mismo tipo.
+    ///
+    /// Los recursos son datos únicos del mundo que no pertenecen a ninguna entidad
+    /// (e.g. `ScheduleStats`). Como los componentes, tienen que ser `Send + Sync`
+    /// para poder mover el mundo entre hilos (cambio incompatible, ver `Component`).
+    pub fn insert_resource<R: Send + Sync + 'static>(&mut self, resource: R) {
+        self.resources.insert(TypeId::of::<R>(), Box::new(resource));
+    }
+
+    /// Obtiene una referencia inmutable a un recurso.
+    pub fn resource<R: 'static>(&self) -> Option<&R> {
+        self.resources
+            .get(&TypeId::of::<R>())
+            .and_then(|resource| resource.downcast_ref())
+    }
+
+    /// Obtiene una referencia mutable a un recurso.
+    pub fn resource_mut<R: 'static>(&mut self) -> Option<&mut R> {
+        self.resources
+            .get_mut(&TypeId::of::<R>())
+            .and_then(|resource| resource.downcast_mut())
+    }
+
+    /// Elimina un recurso y lo devuelve.
+    pub fn remove_resource<R: 'static>(&mut self) -> Option<R> {
+        self.resources
+            .remove(&TypeId::of::<R>())
+            .and_then(|resource| resource.downcast().ok())
+            .map(|resource| *resource)
     }
 
     /// Devuelve la versión actual de una entidad por ID.
@@ -128,7 +545,7 @@ impl World {
         }
         self.components
             .get(&component_id)
-            .map_or(false, |storage| storage.has(entity.id))
+            .is_some_and(|storage| storage.has(entity.id))
     }
 
     /// Devuelve una colección de entidades que tienen un componente específico.
@@ -137,8 +554,13 @@ impl World {
     /// eficientemente todas las entidades con el componente.
     pub fn entities_with_component(&self, component_id: ComponentId) -> Option<Vec<Entity>> {
         self.components.get(&component_id).map(|storage| {
-            storage.bitmask.iter_ones()
-                .map(|id| Entity { id, version: self.entity_versions[id] })
+            storage
+                .bitmask
+                .iter_ones()
+                .map(|id| Entity {
+                    id,
+                    version: self.entity_versions[id],
+                })
                 .collect()
         })
     }
@@ -150,10 +572,90 @@ impl World {
 
     /// Comprueba si una entidad sigue viva.
     pub fn is_alive(&self, entity: Entity) -> bool {
-        self.entity_versions[entity.id] == entity.version
+        self.entity_versions.get(entity.id) == Some(&entity.version)
+    }
+
+    /// Activa o desactiva una entidad viva.
+    ///
+    /// Una entidad inactiva conserva sus componentes (accesibles con `get`/`get_mut`)
+    /// pero las queries no la entregan.
+    pub fn set_active(&mut self, entity: Entity, active: bool) {
+        if !self.is_alive(entity) || self.inactive_mask[entity.id] != active {
+            return;
+        }
+        self.inactive_mask.set(entity.id, !active);
+        // Activar o desactivar cuenta como cambio de todos sus componentes.
+        let tick = self.change_tick;
+        for storage in self.components.values_mut() {
+            if storage.has(entity.id) {
+                storage.mark_changed(entity.id, tick);
+            }
+        }
+        if active {
+            self.inactive_count -= 1;
+        } else {
+            self.inactive_count += 1;
+        }
+    }
+
+    /// Comprueba si una entidad está viva y activa.
+    pub fn is_active(&self, entity: Entity) -> bool {
+        self.is_alive(entity) && !self.inactive_mask[entity.id]
+    }
+
+    /// Quita de `mask` las entidades inactivas.
+    pub(crate) fn retain_active(&self, mask: &mut BitVec) {
+        if self.inactive_count == 0 {
+            return;
+        }
+        for id in self.inactive_mask.iter_ones() {
+            if id < mask.len() {
+                mask.set(id, false);
+            }
+        }
+    }
+
+    /// Estadísticas de memoria y ocupación de entidades y componentes.
+    pub fn stats(&self) -> WorldStats {
+        let mut components: Vec<ComponentStats> = self
+            .components
+            .values()
+            .map(|storage| {
+                let capacity = storage.capacity();
+                let live = storage.len();
+                let item_size = storage.item_size();
+                ComponentStats {
+                    type_name: storage.type_name(),
+                    strategy: StorageStrategy::DenseVec,
+                    capacity,
+                    live,
+                    item_size,
+                    bytes_used: live * item_size,
+                    bytes_wasted: capacity.saturating_sub(live) * item_size,
+                    bitmask_bytes: storage.bitmask.capacity().div_ceil(8),
+                    tick_bytes: storage.tick_bytes(),
+                }
+            })
+            .collect();
+        components.sort_by_key(|stats| stats.type_name);
+
+        WorldStats {
+            capacity: self.max_entities,
+            alive: self.alive_mask.count_ones(),
+            inactive: self.inactive_count,
+            high_water_mark: self.entity_count,
+            free_list: self.free_entities.len(),
+            spawned: self.spawned,
+            despawned: self.despawned,
+            recycled: self.recycled,
+            resources: self.resources.len(),
+            components,
+        }
     }
 
     /// Retorna la cantidad total de entidades creadas (incluye huecos reciclados).
+    ///
+    /// Para el número de entidades vivas usa `stats().alive`.
     pub fn entity_count(&self) -> usize {
         self.entity_count
     }
@@ -162,4 +664,30 @@ impl World {
     pub fn capacity(&self) -> usize {
         self.max_entities
     }
+
+    /// Retorna cuántas entidades más se pueden crear, contando los IDs libres.
+    pub fn remaining_capacity(&self) -> usize {
+        self.max_entities - self.entity_count + self.free_entities.len()
+    }
+}
+
+impl fmt::Debug for World {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entities: Vec<String> = self
+            .alive_mask
+            .iter_ones()
+            .map(|id| {
+                self.entity_label(Entity {
+                    id,
+                    version: self.entity_versions[id],
+                })
+            })
+            .collect();
+        f.debug_struct("World")
+            .field("capacity", &self.max_entities)
+            .field("components", &self.components.len())
+            .field("resources", &self.resources.len())
+            .field("entities", &entities)
+            .finish()
+    }
 }
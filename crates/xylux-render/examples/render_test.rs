@@ -14,8 +14,8 @@ fn main() {
     world.register_component::<Transform>();
 
     // Ejecutar loop principal usando la abstracción de XyluxWindow
-    xwindow.run_loop(|window| {
-        renderer.render(&mut world, window);
+    xwindow.run_loop(|_| {
+        renderer.render(&mut world);
     });
 
     // Limpiar recursos al salir
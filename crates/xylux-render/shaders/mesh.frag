@@ -0,0 +1,14 @@
+#version 450
+
+// Parámetros del material, iguales para todas las instancias de un batch.
+layout(push_constant) uniform Material {
+    vec4 baseColor;
+} material;
+
+layout(location = 0) in vec4 vertexColor;
+
+layout(location = 0) out vec4 outColor;
+
+void main() {
+    outColor = material.baseColor * vertexColor;
+}
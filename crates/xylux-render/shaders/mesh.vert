@@ -0,0 +1,27 @@
+#version 450
+
+// Vértices de la malla (binding 0) y matriz del modelo de cada instancia (binding 1).
+layout(location = 0) in vec3 inPosition;
+layout(location = 1) in vec3 inNormal;
+layout(location = 2) in vec2 inUv;
+layout(location = 3) in vec4 inTangent;
+layout(location = 4) in vec4 inColor;
+layout(location = 5) in vec4 inModel0;
+layout(location = 6) in vec4 inModel1;
+layout(location = 7) in vec4 inModel2;
+layout(location = 8) in vec4 inModel3;
+
+layout(set = 0, binding = 0) uniform CameraUniform {
+    mat4 view;
+    mat4 projection;
+    mat4 viewProjection;
+    vec4 position;
+} camera;
+
+layout(location = 0) out vec4 vertexColor;
+
+void main() {
+    mat4 model = mat4(inModel0, inModel1, inModel2, inModel3);
+    gl_Position = camera.viewProjection * model * vec4(inPosition, 1.0);
+    vertexColor = inColor;
+}
@@ -0,0 +1,89 @@
+#version 450
+
+// PBR metallic-roughness con la semántica de glTF 2.0: BRDF de Cook-Torrance
+// (GGX, Smith-Schlick y Fresnel de Schlick) con una luz direccional fija y un
+// término ambiente. Debe coincidir con `SoftwareProgram::pbr`.
+const vec3 SUN_DIRECTION = vec3(0.3713907, 0.9284767, 0.0);
+const vec3 SUN_COLOR = vec3(3.0);
+const vec3 AMBIENT_COLOR = vec3(0.03);
+const float PI = 3.14159265;
+
+layout(set = 0, binding = 0) uniform CameraUniform {
+    mat4 view;
+    mat4 projection;
+    mat4 viewProjection;
+    vec4 position;
+} camera;
+
+layout(set = 1, binding = 0) uniform MaterialUniform {
+    vec4 baseColor;
+    // rgb: emisivo.
+    vec4 emissive;
+    // metallic, roughness, normalScale, occlusionStrength.
+    vec4 factors;
+    // alphaCutoff, alphaMode (0 opaco, 1 máscara, 2 mezcla).
+    vec4 alpha;
+} material;
+layout(set = 1, binding = 1) uniform sampler materialSampler;
+layout(set = 1, binding = 2) uniform texture2D baseColorTexture;
+layout(set = 1, binding = 3) uniform texture2D metallicRoughnessTexture;
+layout(set = 1, binding = 4) uniform texture2D normalTexture;
+layout(set = 1, binding = 5) uniform texture2D occlusionTexture;
+layout(set = 1, binding = 6) uniform texture2D emissiveTexture;
+
+layout(location = 0) in vec3 worldPosition;
+layout(location = 1) in vec3 worldNormal;
+layout(location = 2) in vec4 worldTangent;
+layout(location = 3) in vec2 uv;
+layout(location = 4) in vec4 vertexColor;
+
+layout(location = 0) out vec4 outColor;
+
+vec4 sampleTexture(texture2D image) {
+    return texture(sampler2D(image, materialSampler), uv);
+}
+
+void main() {
+    vec4 baseColor = material.baseColor * vertexColor * sampleTexture(baseColorTexture);
+    if (material.alpha.y == 1.0 && baseColor.a < material.alpha.x) {
+        discard;
+    }
+    // glTF: rugosidad en el canal G y metalicidad en el B.
+    vec4 metallicRoughness = sampleTexture(metallicRoughnessTexture);
+    float metallic = clamp(material.factors.x * metallicRoughness.b, 0.0, 1.0);
+    float roughness = clamp(material.factors.y * metallicRoughness.g, 0.04, 1.0);
+
+    vec3 normal = normalize(worldNormal);
+    vec3 tangent = normalize(worldTangent.xyz - normal * dot(normal, worldTangent.xyz));
+    vec3 bitangent = cross(normal, tangent) * worldTangent.w;
+    vec3 tangentNormal = sampleTexture(normalTexture).xyz * 2.0 - 1.0;
+    tangentNormal.xy *= material.factors.z;
+    normal = normalize(mat3(tangent, bitangent, normal) * tangentNormal);
+    if (!gl_FrontFacing) {
+        normal = -normal;
+    }
+
+    vec3 view = normalize(camera.position.xyz - worldPosition);
+    vec3 halfway = normalize(view + SUN_DIRECTION);
+    float nDotL = max(dot(normal, SUN_DIRECTION), 0.0);
+    float nDotV = max(dot(normal, view), 1e-4);
+    float nDotH = max(dot(normal, halfway), 0.0);
+    float vDotH = max(dot(view, halfway), 0.0);
+
+    float a2 = roughness * roughness * roughness * roughness;
+    float d = nDotH * nDotH * (a2 - 1.0) + 1.0;
+    float distribution = a2 / (PI * d * d);
+    float k = (roughness + 1.0) * (roughness + 1.0) / 8.0;
+    float geometry = nDotV / (nDotV * (1.0 - k) + k) * nDotL / (nDotL * (1.0 - k) + k);
+    vec3 f0 = mix(vec3(0.04), baseColor.rgb, metallic);
+    vec3 fresnel = f0 + (1.0 - f0) * pow(1.0 - vDotH, 5.0);
+    vec3 specular = distribution * geometry * fresnel / (4.0 * nDotV * max(nDotL, 1e-4));
+    vec3 diffuse = (1.0 - fresnel) * (1.0 - metallic) * baseColor.rgb / PI;
+
+    float occlusion = mix(1.0, sampleTexture(occlusionTexture).r, material.factors.w);
+    vec3 color = (diffuse + specular) * SUN_COLOR * nDotL;
+    color += AMBIENT_COLOR * baseColor.rgb * occlusion;
+    color += material.emissive.rgb * sampleTexture(emissiveTexture).rgb;
+    float alpha = material.alpha.y == 2.0 ? baseColor.a : 1.0;
+    outColor = vec4(color, alpha);
+}
@@ -0,0 +1,38 @@
+#version 450
+
+// Mismas entradas que `mesh.vert`; pasa al fragment shader los datos en espacio de mundo.
+layout(location = 0) in vec3 inPosition;
+layout(location = 1) in vec3 inNormal;
+layout(location = 2) in vec2 inUv;
+layout(location = 3) in vec4 inTangent;
+layout(location = 4) in vec4 inColor;
+layout(location = 5) in vec4 inModel0;
+layout(location = 6) in vec4 inModel1;
+layout(location = 7) in vec4 inModel2;
+layout(location = 8) in vec4 inModel3;
+
+layout(set = 0, binding = 0) uniform CameraUniform {
+    mat4 view;
+    mat4 projection;
+    mat4 viewProjection;
+    vec4 position;
+} camera;
+
+layout(location = 0) out vec3 worldPosition;
+layout(location = 1) out vec3 worldNormal;
+layout(location = 2) out vec4 worldTangent;
+layout(location = 3) out vec2 uv;
+layout(location = 4) out vec4 vertexColor;
+
+void main() {
+    mat4 model = mat4(inModel0, inModel1, inModel2, inModel3);
+    vec4 position = model * vec4(inPosition, 1.0);
+    // La matriz del modelo no tiene escala: su parte 3x3 sirve para las normales.
+    mat3 rotation = mat3(model[0].xyz, model[1].xyz, model[2].xyz);
+    gl_Position = camera.viewProjection * position;
+    worldPosition = position.xyz;
+    worldNormal = rotation * inNormal;
+    worldTangent = vec4(rotation * inTangent.xyz, inTangent.w);
+    uv = inUv;
+    vertexColor = inColor;
+}
@@ -0,0 +1,126 @@
+use std::collections::{BTreeSet, HashMap};
+
+use super::SubAllocator;
+
+/// Sub-asignador buddy sobre un bloque de tamaño potencia de dos.
+///
+/// Cada petición se redondea a la potencia de dos `min_block << k` que la
+/// contiene; al liberar, un bloque se fusiona con su compañero si este también
+/// está libre. Los bloques de orden `k` quedan alineados a su propio tamaño, así
+/// que la alineación se cubre pidiendo al menos `alignment` bytes.
+#[derive(Debug)]
+pub struct BuddyAllocator {
+    size: u64,
+    min_block: u64,
+    /// `free_lists[k]`: offsets de los bloques libres de `min_block << k` bytes.
+    free_lists: Vec<BTreeSet<u64>>,
+    /// Orden de cada bloque asignado, por offset.
+    allocated: HashMap<u64, usize>,
+    used: u64,
+}
+
+impl BuddyAllocator {
+    pub fn new(size: u64, min_block: u64) -> Self {
+        assert!(
+            size.is_power_of_two() && min_block.is_power_of_two() && min_block <= size,
+            "Buddy allocator sizes must be powers of two with min_block <= size"
+        );
+        let orders = (size / min_block).trailing_zeros() as usize + 1;
+        let mut free_lists = vec![BTreeSet::new(); orders];
+        free_lists[orders - 1].insert(0);
+        Self {
+            size,
+            min_block,
+            free_lists,
+            allocated: HashMap::new(),
+            used: 0,
+        }
+    }
+
+    fn block_size(&self, order: usize) -> u64 {
+        self.min_block << order
+    }
+
+    fn order_for(&self, size: u64, alignment: u64) -> Option<usize> {
+        let needed = size
+            .max(alignment)
+            .max(self.min_block)
+            .checked_next_power_of_two()?;
+        (needed <= self.size).then(|| (needed / self.min_block).trailing_zeros() as usize)
+    }
+}
+
+impl SubAllocator for BuddyAllocator {
+    fn allocate(&mut self, size: u64, alignment: u64) -> Option<u64> {
+        let order = self.order_for(size, alignment)?;
+        let mut current =
+            (order..self.free_lists.len()).find(|&k| !self.free_lists[k].is_empty())?;
+        let offset = self.free_lists[current].pop_first()?;
+        while current > order {
+            current -= 1;
+            let buddy = offset + self.block_size(current);
+            self.free_lists[current].insert(buddy);
+        }
+        self.allocated.insert(offset, order);
+        self.used += self.block_size(order);
+        Some(offset)
+    }
+
+    fn free(&mut self, offset: u64) {
+        let mut order = self
+            .allocated
+            .remove(&offset)
+            .expect("Freeing an offset that is not allocated");
+        self.used -= self.block_size(order);
+        let mut offset = offset;
+        while order + 1 < self.free_lists.len() {
+            let buddy = offset ^ self.block_size(order);
+            if !self.free_lists[order].remove(&buddy) {
+                break;
+            }
+            offset = offset.min(buddy);
+            order += 1;
+        }
+        self.free_lists[order].insert(offset);
+    }
+
+    fn used(&self) -> u64 {
+        self.used
+    }
+
+    fn largest_free(&self) -> u64 {
+        (0..self.free_lists.len())
+            .rev()
+            .find(|&k| !self.free_lists[k].is_empty())
+            .map_or(0, |k| self.block_size(k))
+    }
+
+    fn allocations(&self) -> u32 {
+        self.allocated.len() as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buddy_splits_aligns_and_merges() {
+        let mut buddy = BuddyAllocator::new(1024, 64);
+        let a = buddy.allocate(100, 4).unwrap();
+        let b = buddy.allocate(64, 4).unwrap();
+        // 100 bytes ocupan un bloque de 128; el de 64 sale del resto del primer split.
+        assert_eq!((a, b), (0, 128));
+        assert_eq!(buddy.used(), 192);
+        let c = buddy.allocate(10, 256).unwrap();
+        assert_eq!(c % 256, 0);
+        assert_eq!(buddy.allocate(2048, 4), None);
+
+        buddy.free(a);
+        buddy.free(b);
+        buddy.free(c);
+        assert_eq!(buddy.used(), 0);
+        assert_eq!(buddy.largest_free(), 1024);
+        assert_eq!(buddy.allocate(1024, 1), Some(0));
+    }
+}
@@ -0,0 +1,59 @@
+use super::SubAllocator;
+
+/// Sub-asignador lineal: avanza un puntero por el bloque y solo recupera memoria
+/// cuando se liberan todas sus asignaciones o con `reset`. Sirve para datos que
+/// viven un frame (uploads, uniforms transitorios).
+#[derive(Debug)]
+pub struct LinearAllocator {
+    size: u64,
+    head: u64,
+    allocations: u32,
+}
+
+impl LinearAllocator {
+    pub fn new(size: u64) -> Self {
+        Self {
+            size,
+            head: 0,
+            allocations: 0,
+        }
+    }
+
+    /// Descarta todas las asignaciones a la vez.
+    pub fn reset(&mut self) {
+        self.head = 0;
+        self.allocations = 0;
+    }
+}
+
+impl SubAllocator for LinearAllocator {
+    fn allocate(&mut self, size: u64, alignment: u64) -> Option<u64> {
+        let offset = self.head.checked_next_multiple_of(alignment.max(1))?;
+        let end = offset.checked_add(size)?;
+        if end > self.size {
+            return None;
+        }
+        self.head = end;
+        self.allocations += 1;
+        Some(offset)
+    }
+
+    fn free(&mut self, _offset: u64) {
+        self.allocations = self.allocations.saturating_sub(1);
+        if self.allocations == 0 {
+            self.head = 0;
+        }
+    }
+
+    fn used(&self) -> u64 {
+        self.head
+    }
+
+    fn largest_free(&self) -> u64 {
+        self.size - self.head
+    }
+
+    fn allocations(&self) -> u32 {
+        self.allocations
+    }
+}
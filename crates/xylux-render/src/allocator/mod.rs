@@ -0,0 +1,951 @@
+//! # Módulo Allocator
+//!
+//! Sub-asignación de memoria de dispositivo para buffers e imágenes.
+//!
+//! Pedir memoria al driver es caro y el número de reservas vivas está limitado
+//! (`maxMemoryAllocationCount` suele ser 4096), así que los recursos se colocan en
+//! bloques grandes, uno o varios por tipo de memoria y clase de recurso, repartidos
+//! con un `BuddyAllocator`. Los recursos más grandes que la mitad de un bloque
+//! reciben una reserva propia. Los datos de un frame pueden ir a un pool lineal
+//! que se vacía de una vez.
+//!
+//! Los algoritmos trabajan sobre un `DeviceHeap` abstracto: el backend de Vulkan
+//! lo implementa con `vkAllocateMemory` y los tests con un heap en memoria.
+
+mod buddy;
+mod linear;
+
+pub use buddy::BuddyAllocator;
+pub use linear::LinearAllocator;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::BitOr;
+
+/// Propiedades de un tipo de memoria (el subconjunto de `VkMemoryPropertyFlags`
+/// que importa al elegir tipo).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct MemoryFlags(u32);
+
+impl MemoryFlags {
+    pub const DEVICE_LOCAL: Self = Self(1);
+    pub const HOST_VISIBLE: Self = Self(1 << 1);
+    pub const HOST_COHERENT: Self = Self(1 << 2);
+    pub const HOST_CACHED: Self = Self(1 << 3);
+
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    fn matching(self, other: Self) -> u32 {
+        (self.0 & other.0).count_ones()
+    }
+}
+
+impl BitOr for MemoryFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// Un tipo de memoria del dispositivo.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MemoryType {
+    pub flags: MemoryFlags,
+    pub heap_index: u32,
+}
+
+/// Quién lee y escribe la memoria de un recurso.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum MemoryLocation {
+    /// Solo la GPU (texturas, render targets).
+    #[default]
+    GpuOnly,
+    /// La CPU escribe y la GPU lee (uploads, uniforms, buffers dinámicos).
+    CpuToGpu,
+    /// La GPU escribe y la CPU lee (readbacks).
+    GpuToCpu,
+}
+
+impl MemoryLocation {
+    fn required(self) -> MemoryFlags {
+        match self {
+            MemoryLocation::GpuOnly => MemoryFlags::empty(),
+            MemoryLocation::CpuToGpu | MemoryLocation::GpuToCpu => {
+                MemoryFlags::HOST_VISIBLE | MemoryFlags::HOST_COHERENT
+            }
+        }
+    }
+
+    fn preferred(self) -> MemoryFlags {
+        match self {
+            MemoryLocation::GpuOnly => MemoryFlags::DEVICE_LOCAL,
+            MemoryLocation::CpuToGpu => MemoryFlags::empty(),
+            MemoryLocation::GpuToCpu => MemoryFlags::HOST_CACHED,
+        }
+    }
+
+    /// Propiedades que se evitan si hay alternativa: la memoria de la GPU visible
+    /// desde la CPU suele ser un heap pequeño que conviene no gastar.
+    fn unwanted(self) -> MemoryFlags {
+        match self {
+            MemoryLocation::GpuOnly => MemoryFlags::HOST_VISIBLE,
+            MemoryLocation::CpuToGpu => MemoryFlags::DEVICE_LOCAL | MemoryFlags::HOST_CACHED,
+            MemoryLocation::GpuToCpu => MemoryFlags::DEVICE_LOCAL,
+        }
+    }
+}
+
+/// Tipos de memoria de `types` admitidos por `type_bits` que sirven para
+/// `location`, del más adecuado al menos.
+pub fn memory_type_candidates(
+    types: &[MemoryType],
+    type_bits: u32,
+    location: MemoryLocation,
+) -> Vec<u32> {
+    let mut candidates: Vec<(i32, u32)> = (0..types.len() as u32)
+        .filter(|&index| index < 32 && type_bits & (1 << index) != 0)
+        .filter(|&index| types[index as usize].flags.contains(location.required()))
+        .map(|index| {
+            let flags = types[index as usize].flags;
+            let score = flags.matching(location.preferred()) as i32
+                - flags.matching(location.unwanted()) as i32;
+            (score, index)
+        })
+        .collect();
+    candidates.sort_by_key(|&(score, index)| (-score, index));
+    candidates.into_iter().map(|(_, index)| index).collect()
+}
+
+/// Clase de recurso. Buffers e imágenes van a bloques distintos para no tener que
+/// respetar `bufferImageGranularity` entre vecinos.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ResourceKind {
+    Buffer,
+    Image,
+}
+
+/// Petición de memoria para un recurso.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AllocationDesc {
+    pub size: u64,
+    pub alignment: u64,
+    /// Tipos de memoria admitidos (`memoryTypeBits` de Vulkan).
+    pub memory_type_bits: u32,
+    pub location: MemoryLocation,
+    pub kind: ResourceKind,
+    /// Pide una reserva propia aunque el recurso quepa en un bloque (e.g. cuando el
+    /// driver la prefiere para una imagen).
+    pub dedicated: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AllocationError {
+    /// Ningún tipo de memoria admitido por el recurso sirve para su `MemoryLocation`.
+    NoCompatibleMemoryType,
+    /// El dispositivo no tiene memoria suficiente en ningún tipo compatible.
+    OutOfMemory,
+    /// Error del dispositivo o del driver.
+    Device(String),
+}
+
+impl fmt::Display for AllocationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AllocationError::NoCompatibleMemoryType => write!(f, "No compatible memory type"),
+            AllocationError::OutOfMemory => write!(f, "Out of device memory"),
+            AllocationError::Device(message) => write!(f, "Device error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for AllocationError {}
+
+/// Memoria del dispositivo vista por el asignador.
+pub trait DeviceHeap {
+    /// Reserva del dispositivo (`vk::DeviceMemory` en Vulkan).
+    type Memory: Copy + fmt::Debug;
+
+    fn memory_types(&self) -> &[MemoryType];
+
+    fn allocate(&mut self, memory_type: u32, size: u64) -> Result<Self::Memory, AllocationError>;
+
+    fn free(&mut self, memory: Self::Memory);
+
+    /// Mapea la reserva entera; solo se llama con tipos `HOST_VISIBLE`. La memoria
+    /// sigue mapeada hasta que se libera.
+    fn map(&mut self, memory: Self::Memory) -> Result<*mut u8, AllocationError>;
+}
+
+/// Tamaños de bloque y umbrales del asignador.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AllocatorConfig {
+    /// Tamaño de los bloques de memoria que no es visible desde la CPU.
+    pub device_block_size: u64,
+    /// Tamaño de los bloques de memoria visible desde la CPU.
+    pub host_block_size: u64,
+    /// Granularidad mínima de las sub-asignaciones.
+    pub min_allocation: u64,
+    /// Los recursos de más bytes reciben una reserva propia.
+    pub dedicated_threshold: u64,
+}
+
+impl Default for AllocatorConfig {
+    fn default() -> Self {
+        Self {
+            device_block_size: 64 << 20,
+            host_block_size: 16 << 20,
+            min_allocation: 256,
+            dedicated_threshold: 32 << 20,
+        }
+    }
+}
+
+/// Pool lineal creado con `GpuAllocator::create_linear_pool`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LinearPoolId(usize);
+
+/// Los bloques se agrupan por tipo de memoria y clase de recurso.
+type PoolKey = (u32, ResourceKind);
+
+/// Bloques de un pool; los huecos son bloques devueltos con `trim`.
+type BlockPool<M> = Vec<Option<Block<M, BuddyAllocator>>>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum AllocationSource {
+    Block { pool: PoolKey, block: usize },
+    Linear(LinearPoolId),
+    Dedicated,
+}
+
+/// Memoria asignada a un recurso: `size` bytes a partir de `offset` en `memory`.
+///
+/// Se devuelve con `GpuAllocator::free`; si se descarta sin más, la memoria queda
+/// ocupada hasta `GpuAllocator::cleanup`.
+#[derive(Debug)]
+pub struct Allocation<M> {
+    memory: M,
+    offset: u64,
+    size: u64,
+    memory_type: u32,
+    mapped: Option<*mut u8>,
+    source: AllocationSource,
+}
+
+impl<M: Copy> Allocation<M> {
+    pub fn memory(&self) -> M {
+        self.memory
+    }
+
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn memory_type(&self) -> u32 {
+        self.memory_type
+    }
+
+    /// Puntero al primer byte de la asignación si la memoria es visible desde la CPU.
+    pub fn mapped_ptr(&self) -> Option<*mut u8> {
+        self.mapped
+    }
+
+    pub fn is_dedicated(&self) -> bool {
+        self.source == AllocationSource::Dedicated
+    }
+}
+
+/// Reserva del dispositivo repartida por un sub-asignador.
+#[derive(Debug)]
+struct Block<M, A> {
+    memory: M,
+    size: u64,
+    memory_type: u32,
+    mapped: Option<*mut u8>,
+    allocator: A,
+}
+
+impl<M: Copy, A: SubAllocator> Block<M, A> {
+    fn allocate(
+        &mut self,
+        size: u64,
+        alignment: u64,
+        source: AllocationSource,
+    ) -> Option<Allocation<M>> {
+        let offset = self.allocator.allocate(size, alignment)?;
+        Some(Allocation {
+            memory: self.memory,
+            offset,
+            size,
+            memory_type: self.memory_type,
+            mapped: self
+                .mapped
+                .map(|mapped| mapped.wrapping_add(offset as usize)),
+            source,
+        })
+    }
+}
+
+/// Algoritmo que reparte un bloque en rangos `[offset, offset + size)`.
+pub trait SubAllocator {
+    /// Offset de un rango libre de `size` bytes alineado a `alignment`.
+    fn allocate(&mut self, size: u64, alignment: u64) -> Option<u64>;
+
+    fn free(&mut self, offset: u64);
+
+    /// Bytes ocupados, incluido el redondeo del algoritmo.
+    fn used(&self) -> u64;
+
+    /// Mayor rango que se puede asignar ahora mismo.
+    fn largest_free(&self) -> u64;
+
+    fn allocations(&self) -> u32;
+}
+
+/// Uso de memoria de un tipo (o de todos).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryStats {
+    pub blocks: u32,
+    /// Bloques sin asignaciones, que `GpuAllocator::trim` devolvería al dispositivo.
+    pub empty_blocks: u32,
+    pub allocations: u32,
+    pub dedicated_allocations: u32,
+    /// Bytes reservados al dispositivo: bloques y reservas dedicadas.
+    pub reserved: u64,
+    /// Bytes ocupados por asignaciones.
+    pub used: u64,
+    /// Mayor hueco libre dentro de un bloque.
+    pub largest_free: u64,
+}
+
+impl MemoryStats {
+    fn add_block<M, A: SubAllocator>(&mut self, block: &Block<M, A>) {
+        let allocations = block.allocator.allocations();
+        self.blocks += 1;
+        self.empty_blocks += (allocations == 0) as u32;
+        self.allocations += allocations;
+        self.reserved += block.size;
+        self.used += block.allocator.used();
+        self.largest_free = self.largest_free.max(block.allocator.largest_free());
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.blocks += other.blocks;
+        self.empty_blocks += other.empty_blocks;
+        self.allocations += other.allocations;
+        self.dedicated_allocations += other.dedicated_allocations;
+        self.reserved += other.reserved;
+        self.used += other.used;
+        self.largest_free = self.largest_free.max(other.largest_free);
+    }
+
+    pub fn free(&self) -> u64 {
+        self.reserved - self.used
+    }
+
+    /// Fragmentación del espacio libre de los bloques, de `0` (todo en un hueco) a
+    /// casi `1` (muchos huecos pequeños). Un valor alto con mucha memoria libre
+    /// indica que compactaría desfragmentar.
+    pub fn fragmentation(&self) -> f32 {
+        match self.free() {
+            0 => 0.0,
+            free => 1.0 - self.largest_free as f32 / free as f32,
+        }
+    }
+}
+
+/// Estadísticas de `GpuAllocator::stats`, por tipo de memoria.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AllocatorStats {
+    pub memory_types: Vec<MemoryStats>,
+}
+
+impl AllocatorStats {
+    pub fn total(&self) -> MemoryStats {
+        let mut total = MemoryStats::default();
+        self.memory_types
+            .iter()
+            .for_each(|stats| total.merge(stats));
+        total
+    }
+}
+
+/// Asignador de memoria de dispositivo sobre un `DeviceHeap`.
+pub struct GpuAllocator<H: DeviceHeap> {
+    heap: H,
+    config: AllocatorConfig,
+    pools: HashMap<PoolKey, BlockPool<H::Memory>>,
+    linear_pools: Vec<Option<Block<H::Memory, LinearAllocator>>>,
+    /// Reservas dedicadas vivas por tipo de memoria: número y bytes.
+    dedicated: HashMap<u32, (u32, u64)>,
+}
+
+impl<H: DeviceHeap> GpuAllocator<H> {
+    pub fn new(heap: H, config: AllocatorConfig) -> Self {
+        assert!(
+            config.device_block_size.is_power_of_two()
+                && config.host_block_size.is_power_of_two()
+                && config.min_allocation.is_power_of_two(),
+            "Allocator block sizes must be powers of two"
+        );
+        Self {
+            heap,
+            config,
+            pools: HashMap::new(),
+            linear_pools: Vec::new(),
+            dedicated: HashMap::new(),
+        }
+    }
+
+    pub fn heap(&self) -> &H {
+        &self.heap
+    }
+
+    fn is_host_visible(&self, memory_type: u32) -> bool {
+        self.heap.memory_types()[memory_type as usize]
+            .flags
+            .contains(MemoryFlags::HOST_VISIBLE)
+    }
+
+    fn block_size(&self, memory_type: u32) -> u64 {
+        if self.is_host_visible(memory_type) {
+            self.config.host_block_size
+        } else {
+            self.config.device_block_size
+        }
+    }
+
+    /// Reserva `size` bytes del dispositivo y los mapea si son visibles desde la CPU.
+    fn reserve(
+        &mut self,
+        memory_type: u32,
+        size: u64,
+    ) -> Result<(H::Memory, Option<*mut u8>), AllocationError> {
+        let memory = self.heap.allocate(memory_type, size)?;
+        if !self.is_host_visible(memory_type) {
+            return Ok((memory, None));
+        }
+        match self.heap.map(memory) {
+            Ok(mapped) => Ok((memory, Some(mapped))),
+            Err(error) => {
+                self.heap.free(memory);
+                Err(error)
+            }
+        }
+    }
+
+    /// Asigna memoria para un recurso. Prueba los tipos compatibles en orden de
+    /// preferencia y pasa al siguiente si uno se queda sin memoria.
+    pub fn allocate(
+        &mut self,
+        desc: &AllocationDesc,
+    ) -> Result<Allocation<H::Memory>, AllocationError> {
+        let size = desc.size.max(1);
+        let candidates = memory_type_candidates(
+            self.heap.memory_types(),
+            desc.memory_type_bits,
+            desc.location,
+        );
+        if candidates.is_empty() {
+            return Err(AllocationError::NoCompatibleMemoryType);
+        }
+        for memory_type in candidates {
+            let dedicated = desc.dedicated
+                || size > self.config.dedicated_threshold
+                || size.max(desc.alignment) > self.block_size(memory_type) / 2;
+            let result = if dedicated {
+                self.allocate_dedicated(memory_type, size)
+            } else {
+                self.allocate_in_pool(memory_type, desc.kind, size, desc.alignment)
+            };
+            match result {
+                Err(AllocationError::OutOfMemory) => continue,
+                result => return result,
+            }
+        }
+        Err(AllocationError::OutOfMemory)
+    }
+
+    fn allocate_dedicated(
+        &mut self,
+        memory_type: u32,
+        size: u64,
+    ) -> Result<Allocation<H::Memory>, AllocationError> {
+        let (memory, mapped) = self.reserve(memory_type, size)?;
+        let entry = self.dedicated.entry(memory_type).or_default();
+        entry.0 += 1;
+        entry.1 += size;
+        Ok(Allocation {
+            memory,
+            offset: 0,
+            size,
+            memory_type,
+            mapped,
+            source: AllocationSource::Dedicated,
+        })
+    }
+
+    fn allocate_in_pool(
+        &mut self,
+        memory_type: u32,
+        kind: ResourceKind,
+        size: u64,
+        alignment: u64,
+    ) -> Result<Allocation<H::Memory>, AllocationError> {
+        let key = (memory_type, kind);
+        let blocks = self.pools.entry(key).or_default();
+        for (index, block) in blocks.iter_mut().enumerate() {
+            let source = AllocationSource::Block {
+                pool: key,
+                block: index,
+            };
+            if let Some(allocation) = block
+                .as_mut()
+                .and_then(|block| block.allocate(size, alignment, source))
+            {
+                return Ok(allocation);
+            }
+        }
+
+        // Sin hueco: bloque nuevo. Si el dispositivo no tiene para uno entero se
+        // prueba con la mitad mientras quepa el recurso.
+        let needed = size
+            .max(alignment)
+            .max(self.config.min_allocation)
+            .next_power_of_two();
+        let mut block_size = self.block_size(memory_type);
+        let (memory, mapped) = loop {
+            match self.reserve(memory_type, block_size) {
+                Ok(reserved) => break reserved,
+                Err(AllocationError::OutOfMemory) if block_size / 2 >= needed => block_size /= 2,
+                Err(error) => return Err(error),
+            }
+        };
+        let mut block = Block {
+            memory,
+            size: block_size,
+            memory_type,
+            mapped,
+            allocator: BuddyAllocator::new(block_size, self.config.min_allocation.min(block_size)),
+        };
+        let blocks = self.pools.entry(key).or_default();
+        let index = blocks
+            .iter()
+            .position(Option::is_none)
+            .unwrap_or(blocks.len());
+        let allocation = block
+            .allocate(
+                size,
+                alignment,
+                AllocationSource::Block {
+                    pool: key,
+                    block: index,
+                },
+            )
+            .expect("A new block fits the allocation");
+        if index == blocks.len() {
+            blocks.push(Some(block));
+        } else {
+            blocks[index] = Some(block);
+        }
+        Ok(allocation)
+    }
+
+    /// Devuelve la memoria de `allocation`. Los bloques que quedan vacíos se
+    /// conservan para las siguientes asignaciones hasta `trim`.
+    pub fn free(&mut self, allocation: Allocation<H::Memory>) {
+        match allocation.source {
+            AllocationSource::Block { pool, block } => {
+                let block = self
+                    .pools
+                    .get_mut(&pool)
+                    .and_then(|blocks| blocks.get_mut(block))
+                    .and_then(Option::as_mut)
+                    .expect("Allocation block no longer exists");
+                block.allocator.free(allocation.offset);
+            }
+            AllocationSource::Linear(pool) => {
+                if let Some(Some(block)) = self.linear_pools.get_mut(pool.0) {
+                    block.allocator.free(allocation.offset);
+                }
+            }
+            AllocationSource::Dedicated => {
+                self.heap.free(allocation.memory);
+                if let Some(entry) = self.dedicated.get_mut(&allocation.memory_type) {
+                    entry.0 -= 1;
+                    entry.1 -= allocation.size;
+                }
+            }
+        }
+    }
+
+    /// Devuelve al dispositivo los bloques vacíos. Devuelve los bytes liberados.
+    pub fn trim(&mut self) -> u64 {
+        let mut released = 0;
+        for blocks in self.pools.values_mut() {
+            for slot in blocks.iter_mut() {
+                if slot
+                    .as_ref()
+                    .is_some_and(|block| block.allocator.allocations() == 0)
+                {
+                    let block = slot.take().expect("Slot was checked above");
+                    released += block.size;
+                    self.heap.free(block.memory);
+                }
+            }
+        }
+        released
+    }
+
+    /// Crea un pool lineal de `size` bytes en un bloque propio.
+    pub fn create_linear_pool(
+        &mut self,
+        location: MemoryLocation,
+        memory_type_bits: u32,
+        size: u64,
+    ) -> Result<LinearPoolId, AllocationError> {
+        let candidates =
+            memory_type_candidates(self.heap.memory_types(), memory_type_bits, location);
+        if candidates.is_empty() {
+            return Err(AllocationError::NoCompatibleMemoryType);
+        }
+        for memory_type in candidates {
+            let (memory, mapped) = match self.reserve(memory_type, size) {
+                Err(AllocationError::OutOfMemory) => continue,
+                reserved => reserved?,
+            };
+            let block = Block {
+                memory,
+                size,
+                memory_type,
+                mapped,
+                allocator: LinearAllocator::new(size),
+            };
+            let index = self
+                .linear_pools
+                .iter()
+                .position(Option::is_none)
+                .unwrap_or(self.linear_pools.len());
+            if index == self.linear_pools.len() {
+                self.linear_pools.push(Some(block));
+            } else {
+                self.linear_pools[index] = Some(block);
+            }
+            return Ok(LinearPoolId(index));
+        }
+        Err(AllocationError::OutOfMemory)
+    }
+
+    /// Asigna `size` bytes del pool lineal; `OutOfMemory` si ya no caben.
+    pub fn allocate_linear(
+        &mut self,
+        pool: LinearPoolId,
+        size: u64,
+        alignment: u64,
+    ) -> Result<Allocation<H::Memory>, AllocationError> {
+        self.linear_pools
+            .get_mut(pool.0)
+            .and_then(Option::as_mut)
+            .expect("Unknown linear pool")
+            .allocate(size.max(1), alignment, AllocationSource::Linear(pool))
+            .ok_or(AllocationError::OutOfMemory)
+    }
+
+    /// Vacía el pool lineal. Las asignaciones anteriores dejan de ser válidas
+    /// aunque no se hayan liberado.
+    pub fn reset_linear_pool(&mut self, pool: LinearPoolId) {
+        if let Some(Some(block)) = self.linear_pools.get_mut(pool.0) {
+            block.allocator.reset();
+        }
+    }
+
+    pub fn destroy_linear_pool(&mut self, pool: LinearPoolId) {
+        if let Some(block) = self.linear_pools.get_mut(pool.0).and_then(Option::take) {
+            self.heap.free(block.memory);
+        }
+    }
+
+    pub fn stats(&self) -> AllocatorStats {
+        let mut memory_types = vec![MemoryStats::default(); self.heap.memory_types().len()];
+        for ((memory_type, _), blocks) in &self.pools {
+            for block in blocks.iter().flatten() {
+                memory_types[*memory_type as usize].add_block(block);
+            }
+        }
+        for block in self.linear_pools.iter().flatten() {
+            memory_types[block.memory_type as usize].add_block(block);
+        }
+        for (&memory_type, &(count, bytes)) in &self.dedicated {
+            let stats = &mut memory_types[memory_type as usize];
+            stats.allocations += count;
+            stats.dedicated_allocations += count;
+            stats.reserved += bytes;
+            stats.used += bytes;
+        }
+        AllocatorStats { memory_types }
+    }
+
+    /// Libera todos los bloques y pools lineales. Las reservas dedicadas que sigan
+    /// vivas son de sus `Allocation` y hay que liberarlas antes con `free`.
+    pub fn cleanup(&mut self) {
+        for (_, blocks) in self.pools.drain() {
+            for block in blocks.into_iter().flatten() {
+                self.heap.free(block.memory);
+            }
+        }
+        for block in self.linear_pools.drain(..).flatten() {
+            self.heap.free(block.memory);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr::NonNull;
+
+    /// Heap en memoria con un límite de bytes por tipo.
+    struct FakeHeap {
+        types: Vec<MemoryType>,
+        capacity: Vec<u64>,
+        live: HashMap<u32, (u32, u64)>,
+        next: u32,
+    }
+
+    impl FakeHeap {
+        /// Tipos: `0` solo GPU, `1` visible desde la CPU, `2` ambas cosas.
+        fn new(capacity: [u64; 3]) -> Self {
+            let host = MemoryFlags::HOST_VISIBLE | MemoryFlags::HOST_COHERENT;
+            let types = [
+                MemoryFlags::DEVICE_LOCAL,
+                host,
+                MemoryFlags::DEVICE_LOCAL | host,
+            ]
+            .into_iter()
+            .enumerate()
+            .map(|(index, flags)| MemoryType {
+                flags,
+                heap_index: index as u32,
+            })
+            .collect();
+            Self {
+                types,
+                capacity: capacity.to_vec(),
+                live: HashMap::new(),
+                next: 0,
+            }
+        }
+
+        fn used(&self, memory_type: u32) -> u64 {
+            self.live
+                .values()
+                .filter(|(ty, _)| *ty == memory_type)
+                .map(|(_, size)| size)
+                .sum()
+        }
+    }
+
+    impl DeviceHeap for FakeHeap {
+        type Memory = u32;
+
+        fn memory_types(&self) -> &[MemoryType] {
+            &self.types
+        }
+
+        fn allocate(&mut self, memory_type: u32, size: u64) -> Result<u32, AllocationError> {
+            if self.used(memory_type) + size > self.capacity[memory_type as usize] {
+                return Err(AllocationError::OutOfMemory);
+            }
+            self.next += 1;
+            self.live.insert(self.next, (memory_type, size));
+            Ok(self.next)
+        }
+
+        fn free(&mut self, memory: u32) {
+            self.live.remove(&memory).expect("Double free");
+        }
+
+        fn map(&mut self, _memory: u32) -> Result<*mut u8, AllocationError> {
+            Ok(NonNull::dangling().as_ptr())
+        }
+    }
+
+    const CONFIG: AllocatorConfig = AllocatorConfig {
+        device_block_size: 4096,
+        host_block_size: 1024,
+        min_allocation: 64,
+        dedicated_threshold: 2048,
+    };
+
+    fn desc(size: u64, location: MemoryLocation, kind: ResourceKind) -> AllocationDesc {
+        AllocationDesc {
+            size,
+            alignment: 16,
+            memory_type_bits: !0,
+            location,
+            kind,
+            dedicated: false,
+        }
+    }
+
+    #[test]
+    fn test_memory_type_selection() {
+        let heap = FakeHeap::new([1 << 20; 3]);
+        let types = heap.memory_types();
+        assert_eq!(
+            memory_type_candidates(types, !0, MemoryLocation::GpuOnly),
+            [0, 2, 1]
+        );
+        assert_eq!(
+            memory_type_candidates(types, !0, MemoryLocation::CpuToGpu),
+            [1, 2]
+        );
+        assert_eq!(
+            memory_type_candidates(types, 0b100, MemoryLocation::CpuToGpu),
+            [2]
+        );
+
+        let mut allocator = GpuAllocator::new(heap, CONFIG);
+        let mut request = desc(64, MemoryLocation::GpuToCpu, ResourceKind::Buffer);
+        request.memory_type_bits = 0b001;
+        assert_eq!(
+            allocator.allocate(&request).unwrap_err(),
+            AllocationError::NoCompatibleMemoryType
+        );
+    }
+
+    #[test]
+    fn test_pools_dedicated_allocations_and_trim() {
+        let mut allocator = GpuAllocator::new(FakeHeap::new([1 << 20; 3]), CONFIG);
+        let buffers: Vec<_> = (0..8)
+            .map(|_| {
+                allocator
+                    .allocate(&desc(100, MemoryLocation::GpuOnly, ResourceKind::Buffer))
+                    .unwrap()
+            })
+            .collect();
+        // Ocho buffers en un solo bloque, sin solaparse.
+        assert!(
+            buffers
+                .iter()
+                .all(|allocation| allocation.memory() == buffers[0].memory())
+        );
+        let mut offsets: Vec<u64> = buffers.iter().map(Allocation::offset).collect();
+        offsets.sort();
+        offsets.dedup();
+        assert_eq!(offsets.len(), 8);
+
+        let image = allocator
+            .allocate(&desc(100, MemoryLocation::GpuOnly, ResourceKind::Image))
+            .unwrap();
+        assert_ne!(image.memory(), buffers[0].memory());
+        let large = allocator
+            .allocate(&desc(3000, MemoryLocation::GpuOnly, ResourceKind::Image))
+            .unwrap();
+        assert!(large.is_dedicated());
+        let upload = allocator
+            .allocate(&desc(64, MemoryLocation::CpuToGpu, ResourceKind::Buffer))
+            .unwrap();
+        assert_eq!(upload.memory_type(), 1);
+        assert!(upload.mapped_ptr().is_some() && buffers[0].mapped_ptr().is_none());
+
+        let stats = allocator.stats();
+        assert_eq!(stats.memory_types[0].blocks, 2);
+        assert_eq!(stats.memory_types[0].allocations, 10);
+        assert_eq!(stats.memory_types[0].dedicated_allocations, 1);
+        assert_eq!(stats.memory_types[0].reserved, 2 * 4096 + 3000);
+        assert_eq!(stats.total().blocks, 3);
+
+        buffers
+            .into_iter()
+            .for_each(|allocation| allocator.free(allocation));
+        allocator.free(large);
+        assert_eq!(allocator.stats().memory_types[0].empty_blocks, 1);
+        assert_eq!(allocator.trim(), 4096);
+        assert_eq!(allocator.heap().used(0), 4096);
+
+        allocator.free(image);
+        allocator.free(upload);
+        allocator.cleanup();
+        assert!(allocator.heap().live.is_empty());
+    }
+
+    #[test]
+    fn test_out_of_memory_fallbacks() {
+        // El tipo 0 no tiene para un bloque entero ni para un segundo bloque.
+        let mut allocator = GpuAllocator::new(FakeHeap::new([3000, 1 << 20, 1 << 20]), CONFIG);
+        let first = allocator
+            .allocate(&desc(1024, MemoryLocation::GpuOnly, ResourceKind::Buffer))
+            .unwrap();
+        assert_eq!(
+            (
+                first.memory_type(),
+                allocator.stats().memory_types[0].reserved
+            ),
+            (0, 2048)
+        );
+        let second = allocator
+            .allocate(&desc(1024, MemoryLocation::GpuOnly, ResourceKind::Buffer))
+            .unwrap();
+        assert_eq!(second.memory_type(), 0);
+        let third = allocator
+            .allocate(&desc(1024, MemoryLocation::GpuOnly, ResourceKind::Buffer))
+            .unwrap();
+        assert_eq!(third.memory_type(), 2);
+
+        let mut request = desc(1024, MemoryLocation::GpuOnly, ResourceKind::Buffer);
+        request.memory_type_bits = 0b001;
+        assert_eq!(
+            allocator.allocate(&request).unwrap_err(),
+            AllocationError::OutOfMemory
+        );
+    }
+
+    #[test]
+    fn test_linear_pool_and_fragmentation_stats() {
+        let mut allocator = GpuAllocator::new(FakeHeap::new([1 << 20; 3]), CONFIG);
+        let pool = allocator
+            .create_linear_pool(MemoryLocation::CpuToGpu, !0, 256)
+            .unwrap();
+        let a = allocator.allocate_linear(pool, 100, 64).unwrap();
+        let b = allocator.allocate_linear(pool, 100, 64).unwrap();
+        assert_eq!((a.offset(), b.offset()), (0, 128));
+        assert_eq!(
+            allocator.allocate_linear(pool, 100, 64).unwrap_err(),
+            AllocationError::OutOfMemory
+        );
+        allocator.reset_linear_pool(pool);
+        assert_eq!(
+            allocator.allocate_linear(pool, 200, 64).unwrap().offset(),
+            0
+        );
+        allocator.destroy_linear_pool(pool);
+
+        // Liberar una asignación de cada dos deja el espacio libre en huecos.
+        let allocations: Vec<_> = (0..64)
+            .map(|_| {
+                allocator
+                    .allocate(&desc(64, MemoryLocation::GpuOnly, ResourceKind::Buffer))
+                    .unwrap()
+            })
+            .collect();
+        for (index, allocation) in allocations.into_iter().enumerate() {
+            if index % 2 == 0 {
+                allocator.free(allocation);
+            }
+        }
+        let stats = allocator.stats().memory_types[0];
+        assert_eq!((stats.free(), stats.largest_free), (2048, 64));
+        assert!(stats.fragmentation() > 0.9);
+    }
+}
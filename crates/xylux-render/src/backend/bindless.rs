@@ -0,0 +1,265 @@
+//! Tabla de texturas bindless.
+//!
+//! Un único bind group con un sampler y un `TextureArray` en el que cada textura
+//! ocupa un slot. Los shaders reciben el índice del slot (e.g. en un material) y
+//! muestrean `textures[index]`, así que cambiar de textura no cambia de bind group.
+
+use super::{
+    BackendError, BindGroupDesc, BindGroupEntry, BindGroupId, BindGroupLayout, BindingLayout,
+    BindingResource, BindingType, RenderBackend, SamplerId, TextureId,
+};
+use crate::vulkan::context::MAX_FRAMES_IN_FLIGHT;
+use std::collections::VecDeque;
+
+/// Binding del sampler compartido.
+pub const SAMPLER_BINDING: u32 = 0;
+
+/// Binding del array de texturas.
+pub const TEXTURES_BINDING: u32 = 1;
+
+/// Índice de una textura dentro de la tabla.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TextureSlot(pub u32);
+
+/// Slots de texturas sobre un bind group bindless.
+///
+/// `insert` y `remove` solo cambian la tabla en la CPU; `flush`, una vez por frame
+/// antes de grabarlo, escribe los slots cambiados en el bind group. Un slot
+/// liberado apunta a la textura de reserva y no se reutiliza hasta que terminan
+/// los frames en vuelo que podían leerlo.
+#[derive(Debug)]
+pub struct BindlessTextures {
+    group: BindGroupId,
+    capacity: u32,
+    /// Textura que leen los slots vacíos.
+    fallback: TextureId,
+    slots: Vec<Option<TextureId>>,
+    free: Vec<u32>,
+    /// Slots liberados y el número de `flush` en el que se liberaron.
+    retired: VecDeque<(u64, u32)>,
+    dirty: Vec<u32>,
+    flushes: u64,
+}
+
+impl BindlessTextures {
+    /// Layout del bind group para declararlo en los pipelines.
+    pub fn layout(capacity: u32) -> BindGroupLayout {
+        BindGroupLayout {
+            bindings: vec![
+                BindingLayout {
+                    binding: SAMPLER_BINDING,
+                    ty: BindingType::Sampler,
+                },
+                BindingLayout {
+                    binding: TEXTURES_BINDING,
+                    ty: BindingType::TextureArray { count: capacity },
+                },
+            ],
+        }
+    }
+
+    /// Crea el bind group con todos los slots apuntando a `fallback`.
+    pub fn new(
+        backend: &mut dyn RenderBackend,
+        capacity: u32,
+        sampler: SamplerId,
+        fallback: TextureId,
+    ) -> Result<Self, BackendError> {
+        let entries = std::iter::once(BindGroupEntry {
+            binding: SAMPLER_BINDING,
+            resource: BindingResource::Sampler(sampler),
+        })
+        .chain((0..capacity).map(|element| BindGroupEntry {
+            binding: TEXTURES_BINDING,
+            resource: BindingResource::ArrayTexture {
+                element,
+                texture: fallback,
+            },
+        }))
+        .collect();
+        let group = backend.create_bind_group(&BindGroupDesc {
+            layout: Self::layout(capacity),
+            entries,
+        })?;
+        Ok(Self {
+            group,
+            capacity,
+            fallback,
+            slots: Vec::new(),
+            free: Vec::new(),
+            retired: VecDeque::new(),
+            dirty: Vec::new(),
+            flushes: 0,
+        })
+    }
+
+    /// Bind group que se enlaza en los pipelines.
+    pub fn group(&self) -> BindGroupId {
+        self.group
+    }
+
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    /// Número de texturas en la tabla.
+    pub fn len(&self) -> usize {
+        self.slots.iter().flatten().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Asigna un slot a `texture`; `None` si la tabla está llena.
+    pub fn insert(&mut self, texture: TextureId) -> Option<TextureSlot> {
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None if (self.slots.len() as u32) < self.capacity => {
+                self.slots.push(None);
+                self.slots.len() as u32 - 1
+            }
+            None => return None,
+        };
+        self.slots[index as usize] = Some(texture);
+        self.dirty.push(index);
+        Some(TextureSlot(index))
+    }
+
+    /// Libera un slot y devuelve su textura.
+    pub fn remove(&mut self, slot: TextureSlot) -> Option<TextureId> {
+        let texture = self.slots.get_mut(slot.0 as usize)?.take()?;
+        self.dirty.push(slot.0);
+        self.retired.push_back((self.flushes, slot.0));
+        Some(texture)
+    }
+
+    pub fn get(&self, slot: TextureSlot) -> Option<TextureId> {
+        self.slots.get(slot.0 as usize).copied().flatten()
+    }
+
+    /// Escribe los slots cambiados desde el último `flush`.
+    pub fn flush(&mut self, backend: &mut dyn RenderBackend) -> Result<(), BackendError> {
+        self.dirty.sort_unstable();
+        self.dirty.dedup();
+        let entries: Vec<BindGroupEntry> = self
+            .dirty
+            .iter()
+            .map(|&element| BindGroupEntry {
+                binding: TEXTURES_BINDING,
+                resource: BindingResource::ArrayTexture {
+                    element,
+                    texture: self.slots[element as usize].unwrap_or(self.fallback),
+                },
+            })
+            .collect();
+        if !entries.is_empty() {
+            backend.update_bind_group(self.group, &entries)?;
+        }
+        self.dirty.clear();
+        self.flushes += 1;
+
+        // El frame que leía un slot liberado en el flush `n` se grabó antes del
+        // flush `n + 1`; pasados los frames en vuelo, ya terminó.
+        while let Some(&(removed_at, index)) = self.retired.front() {
+            if self.flushes <= removed_at + MAX_FRAMES_IN_FLIGHT as u64 {
+                break;
+            }
+            self.retired.pop_front();
+            self.free.push(index);
+        }
+        Ok(())
+    }
+
+    /// Destruye el bind group. Las texturas y el sampler siguen siendo del llamador.
+    pub fn destroy(self, backend: &mut dyn RenderBackend) {
+        backend.destroy_bind_group(self.group);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{NullBackend, SamplerDesc, TextureDesc, TextureFormat, TextureUsage};
+
+    fn texture(backend: &mut NullBackend) -> TextureId {
+        backend
+            .create_texture(&TextureDesc {
+                width: 1,
+                height: 1,
+                mip_levels: 1,
+                format: TextureFormat::Rgba8Srgb,
+                usage: TextureUsage::SAMPLED | TextureUsage::TRANSFER_DST,
+            })
+            .unwrap()
+    }
+
+    /// Textura en el elemento `element` del bind group.
+    fn bound(backend: &NullBackend, group: BindGroupId, element: u32) -> Option<TextureId> {
+        backend
+            .bind_group_desc(group)
+            .unwrap()
+            .entries
+            .iter()
+            .find_map(|entry| match entry.resource {
+                BindingResource::ArrayTexture {
+                    element: e,
+                    texture,
+                } if e == element => Some(texture),
+                _ => None,
+            })
+    }
+
+    #[test]
+    fn test_bindless_slots_are_written_on_flush_and_reused_late() {
+        let mut backend = NullBackend::new(8, 8);
+        let sampler = backend.create_sampler(&SamplerDesc::linear()).unwrap();
+        let fallback = texture(&mut backend);
+        let (a, b, c) = (
+            texture(&mut backend),
+            texture(&mut backend),
+            texture(&mut backend),
+        );
+        let mut table = BindlessTextures::new(&mut backend, 2, sampler, fallback).unwrap();
+        let group = table.group();
+        assert_eq!(bound(&backend, group, 1), Some(fallback));
+
+        let slot_a = table.insert(a).unwrap();
+        let slot_b = table.insert(b).unwrap();
+        assert_eq!((slot_a, slot_b), (TextureSlot(0), TextureSlot(1)));
+        assert_eq!(table.insert(c), None);
+        assert_eq!(bound(&backend, group, 0), Some(fallback));
+        table.flush(&mut backend).unwrap();
+        assert_eq!(bound(&backend, group, 0), Some(a));
+        assert_eq!(bound(&backend, group, 1), Some(b));
+
+        // El slot liberado lee la reserva y no se reutiliza con frames en vuelo.
+        assert_eq!(table.remove(slot_a), Some(a));
+        assert_eq!(table.remove(slot_a), None);
+        table.flush(&mut backend).unwrap();
+        assert_eq!(bound(&backend, group, 0), Some(fallback));
+        assert_eq!(table.insert(c), None);
+        for _ in 0..MAX_FRAMES_IN_FLIGHT {
+            table.flush(&mut backend).unwrap();
+        }
+        assert_eq!(table.insert(c), Some(slot_a));
+        table.flush(&mut backend).unwrap();
+        assert_eq!(bound(&backend, group, 0), Some(c));
+        assert_eq!(table.len(), 2);
+
+        // Solo los elementos del array se pueden cambiar, y dentro de su rango.
+        let out_of_range = [BindGroupEntry {
+            binding: TEXTURES_BINDING,
+            resource: BindingResource::ArrayTexture {
+                element: 2,
+                texture: a,
+            },
+        }];
+        assert!(backend.update_bind_group(group, &out_of_range).is_err());
+        let sampler_update = [BindGroupEntry {
+            binding: SAMPLER_BINDING,
+            resource: BindingResource::Sampler(sampler),
+        }];
+        assert!(backend.update_bind_group(group, &sampler_update).is_err());
+    }
+}
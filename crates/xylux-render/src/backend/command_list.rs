@@ -0,0 +1,314 @@
+//! Flujo de comandos de render independiente de la API gráfica.
+
+use super::{BindGroupId, BufferId, PipelineId, TextureId};
+use std::ops::Range;
+
+/// Destino de un render pass.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RenderTarget {
+    /// La imagen del swapchain adquirida en `begin_frame`.
+    Swapchain,
+    /// Una textura con uso `RENDER_TARGET`.
+    Texture(TextureId),
+}
+
+/// Uso que un pass hace de un recurso. Determina el layout de una imagen y los
+/// accesos que una barrera tiene que sincronizar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ResourceUsage {
+    /// Contenido indefinido, e.g. antes del primer uso de un recurso transitorio.
+    Undefined,
+    ColorAttachment,
+    DepthStencilAttachment,
+    /// Lectura desde shaders: textura muestreada o uniform/storage buffer.
+    ShaderRead,
+    /// Escritura desde shaders (storage).
+    ShaderWrite,
+    /// Lectura como vertex o index buffer.
+    VertexInput,
+    TransferSrc,
+    TransferDst,
+    /// Imagen lista para presentarse.
+    Present,
+}
+
+impl ResourceUsage {
+    pub fn is_write(self) -> bool {
+        matches!(
+            self,
+            ResourceUsage::ColorAttachment
+                | ResourceUsage::DepthStencilAttachment
+                | ResourceUsage::ShaderWrite
+                | ResourceUsage::TransferDst
+        )
+    }
+}
+
+/// Recurso al que se aplica una barrera.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BarrierResource {
+    Swapchain,
+    Texture(TextureId),
+    Buffer(BufferId),
+}
+
+/// Transición de un recurso entre dos usos: espera a los accesos de `before` y,
+/// en imágenes, cambia al layout de `after`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ResourceBarrier {
+    pub resource: BarrierResource,
+    pub before: ResourceUsage,
+    pub after: ResourceUsage,
+}
+
+/// Parámetros de inicio de un render pass. La profundidad empieza siempre a `1.0`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RenderPassDesc {
+    pub target: RenderTarget,
+    /// Color con el que se limpia el destino; `None` conserva su contenido.
+    pub clear_color: Option<[f32; 4]>,
+}
+
+impl RenderPassDesc {
+    /// Render pass sobre el swapchain que lo limpia con `clear_color`.
+    pub fn swapchain(clear_color: [f32; 4]) -> Self {
+        Self {
+            target: RenderTarget::Swapchain,
+            clear_color: Some(clear_color),
+        }
+    }
+}
+
+/// Rectángulo del destino, en píxeles, donde se dibuja.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Viewport {
+    /// Viewport que cubre un destino de `width` × `height` píxeles.
+    pub fn full(width: u32, height: u32) -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            width: width as f32,
+            height: height as f32,
+        }
+    }
+}
+
+/// Tipo de los índices de un index buffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IndexFormat {
+    U16,
+    U32,
+}
+
+/// Comando de render grabado en una `CommandList`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RenderCommand {
+    BeginRenderPass(RenderPassDesc),
+    EndRenderPass,
+    BindPipeline(PipelineId),
+    BindVertexBuffer {
+        slot: u32,
+        buffer: BufferId,
+        offset: u64,
+    },
+    BindIndexBuffer {
+        buffer: BufferId,
+        offset: u64,
+        format: IndexFormat,
+    },
+    /// Enlaza un grupo de recursos en el set `index` del pipeline enlazado.
+    BindGroup {
+        index: u32,
+        group: BindGroupId,
+    },
+    /// Limita el dibujo al rectángulo dado (viewport y scissor). Cada render pass
+    /// empieza con un viewport que cubre todo el destino.
+    SetViewport(Viewport),
+    /// Limpia el color y la profundidad del viewport actual, sin salir del render pass.
+    ClearColor([f32; 4]),
+    /// Datos de push constants para el pipeline enlazado.
+    PushConstants {
+        offset: u32,
+        data: Vec<u8>,
+    },
+    Draw {
+        vertices: Range<u32>,
+        instances: Range<u32>,
+    },
+    DrawIndexed {
+        indices: Range<u32>,
+        base_vertex: i32,
+        instances: Range<u32>,
+    },
+    /// Barreras entre usos de recursos; solo fuera de un render pass. Las
+    /// transiciones del swapchain las hace su propio render pass, así que los
+    /// backends pueden ignorarlas.
+    Barrier(Vec<ResourceBarrier>),
+}
+
+/// Lista de comandos de un frame (o de parte de él).
+///
+/// Se construye sin acceso a la GPU y se ejecuta con `RenderBackend::submit`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CommandList {
+    commands: Vec<RenderCommand>,
+}
+
+impl CommandList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Comandos grabados, en orden.
+    pub fn commands(&self) -> &[RenderCommand] {
+        &self.commands
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Añade un comando arbitrario.
+    pub fn push(&mut self, command: RenderCommand) {
+        self.commands.push(command);
+    }
+
+    pub fn begin_render_pass(&mut self, desc: RenderPassDesc) {
+        self.push(RenderCommand::BeginRenderPass(desc));
+    }
+
+    pub fn end_render_pass(&mut self) {
+        self.push(RenderCommand::EndRenderPass);
+    }
+
+    pub fn bind_pipeline(&mut self, pipeline: PipelineId) {
+        self.push(RenderCommand::BindPipeline(pipeline));
+    }
+
+    pub fn bind_vertex_buffer(&mut self, slot: u32, buffer: BufferId, offset: u64) {
+        self.push(RenderCommand::BindVertexBuffer {
+            slot,
+            buffer,
+            offset,
+        });
+    }
+
+    pub fn bind_index_buffer(&mut self, buffer: BufferId, offset: u64, format: IndexFormat) {
+        self.push(RenderCommand::BindIndexBuffer {
+            buffer,
+            offset,
+            format,
+        });
+    }
+
+    pub fn bind_group(&mut self, index: u32, group: BindGroupId) {
+        self.push(RenderCommand::BindGroup { index, group });
+    }
+
+    pub fn set_viewport(&mut self, viewport: Viewport) {
+        self.push(RenderCommand::SetViewport(viewport));
+    }
+
+    pub fn clear_color(&mut self, color: [f32; 4]) {
+        self.push(RenderCommand::ClearColor(color));
+    }
+
+    pub fn push_constants(&mut self, offset: u32, data: &[u8]) {
+        self.push(RenderCommand::PushConstants {
+            offset,
+            data: data.to_vec(),
+        });
+    }
+
+    pub fn draw(&mut self, vertices: Range<u32>, instances: Range<u32>) {
+        self.push(RenderCommand::Draw {
+            vertices,
+            instances,
+        });
+    }
+
+    pub fn draw_indexed(&mut self, indices: Range<u32>, base_vertex: i32, instances: Range<u32>) {
+        self.push(RenderCommand::DrawIndexed {
+            indices,
+            base_vertex,
+            instances,
+        });
+    }
+
+    pub fn barrier(&mut self, barriers: Vec<ResourceBarrier>) {
+        self.push(RenderCommand::Barrier(barriers));
+    }
+
+    /// Número de comandos de dibujo.
+    pub fn draw_count(&self) -> usize {
+        self.commands
+            .iter()
+            .filter(|command| {
+                matches!(
+                    command,
+                    RenderCommand::Draw { .. } | RenderCommand::DrawIndexed { .. }
+                )
+            })
+            .count()
+    }
+
+    /// Comprueba que los render passes están equilibrados, que los comandos de
+    /// dibujo tienen un pipeline enlazado dentro de un render pass y que los bind
+    /// groups, las limpiezas de viewport y las barreras ocurren donde pueden aplicarse.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut in_pass = false;
+        let mut pipeline_bound = false;
+        for (index, command) in self.commands.iter().enumerate() {
+            match command {
+                RenderCommand::BeginRenderPass(_) if in_pass => {
+                    return Err(format!("command {}: render pass begun twice", index));
+                }
+                RenderCommand::BeginRenderPass(_) => {
+                    in_pass = true;
+                    pipeline_bound = false;
+                }
+                RenderCommand::EndRenderPass if !in_pass => {
+                    return Err(format!(
+                        "command {}: render pass ended without begin",
+                        index
+                    ));
+                }
+                RenderCommand::EndRenderPass => in_pass = false,
+                RenderCommand::BindPipeline(_) => pipeline_bound = true,
+                RenderCommand::BindGroup { .. } if !pipeline_bound => {
+                    return Err(format!("command {}: bind group without pipeline", index));
+                }
+                RenderCommand::ClearColor(_) if !in_pass => {
+                    return Err(format!("command {}: clear outside a render pass", index));
+                }
+                RenderCommand::Barrier(_) if in_pass => {
+                    return Err(format!("command {}: barrier inside a render pass", index));
+                }
+                RenderCommand::Draw { .. } | RenderCommand::DrawIndexed { .. }
+                    if !in_pass || !pipeline_bound =>
+                {
+                    return Err(format!(
+                        "command {}: draw outside a render pass or without pipeline",
+                        index
+                    ));
+                }
+                _ => {}
+            }
+        }
+        if in_pass {
+            return Err("render pass was not ended".into());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,1012 @@
+//! # Backends de Render
+//!
+//! Abstracción sobre la API gráfica. El `Renderer` no habla con Vulkan directamente:
+//! crea recursos a través de un `RenderBackend`, graba cada frame en una
+//! `CommandList` independiente de la API y la envía al backend para ejecutarla y
+//! presentarla.
+//!
+//! - `VulkanBackend`: implementación real sobre `VulkanContext` y una ventana SDL3.
+//! - `NullBackend`: no dibuja nada; guarda los recursos y el flujo de comandos de
+//!   cada frame para poder comprobarlos en tests sin GPU (y para `--headless`).
+//! - `SoftwareBackend`: rasterizador en CPU que produce imágenes RGBA para
+//!   compararlas con imágenes de referencia.
+//!
+//! `BindlessTextures` reparte los slots de un array de texturas sobre cualquiera
+//! de ellos.
+
+pub mod bindless;
+pub mod command_list;
+pub mod null;
+pub mod software;
+pub mod upload;
+pub mod vulkan;
+
+pub use bindless::{BindlessTextures, TextureSlot};
+pub use command_list::{
+    BarrierResource, CommandList, IndexFormat, RenderCommand, RenderPassDesc, RenderTarget,
+    ResourceBarrier, ResourceUsage, Viewport,
+};
+pub use null::{NullBackend, RecordedFrame};
+pub use software::{RgbaImage, SoftwareBackend, SoftwareProgram};
+pub use upload::{UploadStats, UploadTicket};
+pub use vulkan::VulkanBackend;
+
+use crate::allocator::{AllocationError, MemoryLocation};
+use crate::camera::CameraUniform;
+use crate::material::Material;
+use crate::mesh::Mesh;
+use std::any::Any;
+use std::fmt;
+use std::ops::BitOr;
+
+/// Identificador de un buffer creado por un backend.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BufferId(pub(crate) u32);
+
+/// Identificador de una textura creada por un backend.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TextureId(pub(crate) u32);
+
+/// Identificador de un pipeline gráfico creado por un backend.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PipelineId(pub(crate) u32);
+
+/// Identificador de un grupo de recursos (en Vulkan, un descriptor set).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BindGroupId(pub(crate) u32);
+
+/// Identificador de un sampler creado por un backend.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SamplerId(pub(crate) u32);
+
+/// Alineación de los offsets de uniform buffers dentro de un bind group.
+///
+/// Es el máximo de `minUniformBufferOffsetAlignment` que permite Vulkan, así que
+/// vale para cualquier dispositivo.
+pub const UNIFORM_ALIGNMENT: u64 = 256;
+
+/// Genera una estructura de flags combinables con `|`.
+macro_rules! usage_flags {
+    ($(#[$meta:meta])* $name:ident { $($(#[$flag_meta:meta])* $flag:ident = $bit:expr),* $(,)? }) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+        pub struct $name(u32);
+
+        impl $name {
+            $($(#[$flag_meta])* pub const $flag: Self = Self(1 << $bit);)*
+
+            /// `true` si contiene todos los flags de `other`.
+            pub fn contains(self, other: Self) -> bool {
+                self.0 & other.0 == other.0
+            }
+        }
+
+        impl BitOr for $name {
+            type Output = Self;
+
+            fn bitor(self, rhs: Self) -> Self {
+                Self(self.0 | rhs.0)
+            }
+        }
+    };
+}
+
+usage_flags! {
+    /// Usos permitidos de un buffer.
+    BufferUsage {
+        VERTEX = 0,
+        INDEX = 1,
+        UNIFORM = 2,
+        STORAGE = 3,
+        /// Origen de copias (e.g. staging).
+        TRANSFER_SRC = 4,
+        /// Destino de copias.
+        TRANSFER_DST = 5,
+    }
+}
+
+usage_flags! {
+    /// Usos permitidos de una textura.
+    TextureUsage {
+        SAMPLED = 0,
+        RENDER_TARGET = 1,
+        DEPTH_STENCIL = 2,
+        TRANSFER_SRC = 3,
+        TRANSFER_DST = 4,
+    }
+}
+
+/// Descripción de un buffer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BufferDesc {
+    /// Tamaño en bytes.
+    pub size: u64,
+    pub usage: BufferUsage,
+    /// `GpuOnly` para datos estáticos, que se suben con `upload_buffer`; `CpuToGpu`
+    /// para los que se reescriben cada frame con `write_buffer`.
+    pub location: MemoryLocation,
+}
+
+/// Formatos de textura soportados por los backends.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TextureFormat {
+    Rgba8Unorm,
+    Rgba8Srgb,
+    Bgra8Unorm,
+    Bgra8Srgb,
+    Rgba16Float,
+    Rgba32Float,
+    Depth32Float,
+    Depth24Stencil8,
+}
+
+impl TextureFormat {
+    /// Bytes por píxel.
+    pub fn bytes_per_pixel(self) -> u32 {
+        match self {
+            TextureFormat::Rgba8Unorm
+            | TextureFormat::Rgba8Srgb
+            | TextureFormat::Bgra8Unorm
+            | TextureFormat::Bgra8Srgb
+            | TextureFormat::Depth32Float
+            | TextureFormat::Depth24Stencil8 => 4,
+            TextureFormat::Rgba16Float => 8,
+            TextureFormat::Rgba32Float => 16,
+        }
+    }
+
+    /// `true` para formatos de profundidad/stencil.
+    pub fn is_depth(self) -> bool {
+        matches!(
+            self,
+            TextureFormat::Depth32Float | TextureFormat::Depth24Stencil8
+        )
+    }
+
+    /// `true` si el formato almacena color en espacio sRGB.
+    pub fn is_srgb(self) -> bool {
+        matches!(self, TextureFormat::Rgba8Srgb | TextureFormat::Bgra8Srgb)
+    }
+}
+
+/// Descripción de una textura 2D.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextureDesc {
+    pub width: u32,
+    pub height: u32,
+    pub mip_levels: u32,
+    pub format: TextureFormat,
+    pub usage: TextureUsage,
+}
+
+/// Filtro con el que se leen los texels (o los niveles de mip).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FilterMode {
+    Nearest,
+    #[default]
+    Linear,
+}
+
+/// Qué se lee fuera del rango `[0, 1]` de coordenadas.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum AddressMode {
+    #[default]
+    Repeat,
+    MirroredRepeat,
+    ClampToEdge,
+}
+
+/// Descripción de un sampler. Por defecto es trilineal y repite la textura.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SamplerDesc {
+    pub mag_filter: FilterMode,
+    pub min_filter: FilterMode,
+    pub mipmap_filter: FilterMode,
+    /// Modo de cada coordenada: `u`, `v` y `w`.
+    pub address_mode: [AddressMode; 3],
+    /// Muestras del filtrado anisótropo; `1.0` lo desactiva. Los backends lo
+    /// recortan al máximo del dispositivo.
+    pub max_anisotropy: f32,
+    /// Niveles de mip que se pueden leer.
+    pub lod_min: f32,
+    pub lod_max: f32,
+}
+
+impl Default for SamplerDesc {
+    fn default() -> Self {
+        Self {
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            mipmap_filter: FilterMode::Linear,
+            address_mode: [AddressMode::Repeat; 3],
+            max_anisotropy: 1.0,
+            lod_min: 0.0,
+            lod_max: Self::LOD_CLAMP_NONE,
+        }
+    }
+}
+
+impl SamplerDesc {
+    /// `lod_max` que no limita los niveles de mip.
+    pub const LOD_CLAMP_NONE: f32 = 1000.0;
+
+    /// Filtrado trilineal.
+    pub fn linear() -> Self {
+        Self::default()
+    }
+
+    /// Sin filtrar: el texel y el nivel de mip más cercanos (e.g. pixel art).
+    pub fn nearest() -> Self {
+        Self {
+            mag_filter: FilterMode::Nearest,
+            min_filter: FilterMode::Nearest,
+            mipmap_filter: FilterMode::Nearest,
+            ..Self::default()
+        }
+    }
+
+    /// Usa `mode` en las tres coordenadas.
+    pub fn with_address_mode(mut self, mode: AddressMode) -> Self {
+        self.address_mode = [mode; 3];
+        self
+    }
+
+    pub fn with_anisotropy(mut self, max_anisotropy: f32) -> Self {
+        self.max_anisotropy = max_anisotropy;
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), BackendError> {
+        if !(1.0..).contains(&self.max_anisotropy) {
+            return Err(BackendError::InvalidCommands(format!(
+                "sampler anisotropy {} is below 1",
+                self.max_anisotropy
+            )));
+        }
+        if !(0.0..=self.lod_max).contains(&self.lod_min) {
+            return Err(BackendError::InvalidCommands(format!(
+                "sampler lod range {}..{} is empty",
+                self.lod_min, self.lod_max
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Formato de un atributo de vértice.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum VertexFormat {
+    Float32,
+    Float32x2,
+    Float32x3,
+    Float32x4,
+}
+
+impl VertexFormat {
+    /// Tamaño en bytes.
+    pub fn size(self) -> u32 {
+        match self {
+            VertexFormat::Float32 => 4,
+            VertexFormat::Float32x2 => 8,
+            VertexFormat::Float32x3 => 12,
+            VertexFormat::Float32x4 => 16,
+        }
+    }
+}
+
+/// Atributo de vértice leído por el shader en `location`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct VertexAttribute {
+    pub location: u32,
+    pub format: VertexFormat,
+    /// Offset en bytes dentro del elemento.
+    pub offset: u32,
+}
+
+/// Frecuencia con la que avanza un vertex buffer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum VertexStepMode {
+    #[default]
+    Vertex,
+    Instance,
+}
+
+/// Disposición del vertex buffer enlazado en un slot.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct VertexLayout {
+    /// Bytes entre elementos consecutivos.
+    pub stride: u32,
+    pub step_mode: VertexStepMode,
+    pub attributes: Vec<VertexAttribute>,
+}
+
+/// Tipo de recurso de un binding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BindingType {
+    UniformBuffer,
+    /// Textura muestreada (en Vulkan, `SAMPLED_IMAGE`).
+    Texture,
+    Sampler,
+    /// Array de `count` texturas para acceso bindless: los elementos pueden
+    /// quedar sin asignar y se cambian con `RenderBackend::update_bind_group` aunque
+    /// el grupo ya esté enlazado en un frame en vuelo.
+    TextureArray {
+        count: u32,
+    },
+}
+
+/// Binding de un `BindGroupLayout`, visible en todas las etapas gráficas.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BindingLayout {
+    pub binding: u32,
+    pub ty: BindingType,
+}
+
+/// Disposición de un grupo de recursos (en Vulkan, un descriptor set layout).
+///
+/// Un pipeline declara un layout por set y los bind groups que se enlazan en ese
+/// set deben crearse con el mismo layout.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BindGroupLayout {
+    pub bindings: Vec<BindingLayout>,
+}
+
+/// Recurso asignado a un binding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BindingResource {
+    /// Rango `offset..offset + size` de un buffer; `offset` debe ser múltiplo de
+    /// `UNIFORM_ALIGNMENT`.
+    Buffer {
+        buffer: BufferId,
+        offset: u64,
+        size: u64,
+    },
+    /// Textura con uso `SAMPLED`, en todos sus niveles de mip.
+    Texture(TextureId),
+    Sampler(SamplerId),
+    /// Elemento `element` de un binding `TextureArray`.
+    ArrayTexture {
+        element: u32,
+        texture: TextureId,
+    },
+}
+
+impl BindingResource {
+    /// Textura a la que apunta el recurso, si es una.
+    pub fn texture(&self) -> Option<TextureId> {
+        match *self {
+            BindingResource::Texture(texture) | BindingResource::ArrayTexture { texture, .. } => {
+                Some(texture)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BindGroupEntry {
+    pub binding: u32,
+    pub resource: BindingResource,
+}
+
+/// Descripción de un grupo de recursos: un recurso por cada binding del layout.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BindGroupDesc {
+    pub layout: BindGroupLayout,
+    pub entries: Vec<BindGroupEntry>,
+}
+
+impl BindGroupLayout {
+    /// `true` si algún binding es un `TextureArray`.
+    pub fn is_bindless(&self) -> bool {
+        self.bindings
+            .iter()
+            .any(|binding| matches!(binding.ty, BindingType::TextureArray { .. }))
+    }
+
+    /// Comprueba que `entry` es un recurso del tipo de su binding, con los offsets
+    /// alineados y los elementos dentro del array.
+    pub fn check_entry(&self, entry: &BindGroupEntry) -> Result<(), BackendError> {
+        let invalid = |reason: String| Err(BackendError::InvalidCommands(reason));
+        let Some(layout) = self.bindings.iter().find(|b| b.binding == entry.binding) else {
+            return invalid(format!("binding {} is not in the layout", entry.binding));
+        };
+        match (layout.ty, entry.resource) {
+            (BindingType::UniformBuffer, BindingResource::Buffer { offset, .. })
+                if !offset.is_multiple_of(UNIFORM_ALIGNMENT) =>
+            {
+                invalid(format!(
+                    "binding {} offset {} is not aligned to {}",
+                    entry.binding, offset, UNIFORM_ALIGNMENT
+                ))
+            }
+            (
+                BindingType::TextureArray { count },
+                BindingResource::ArrayTexture { element, .. },
+            ) if element >= count => invalid(format!(
+                "binding {} element {} is outside its array of {}",
+                entry.binding, element, count
+            )),
+            (BindingType::UniformBuffer, BindingResource::Buffer { .. })
+            | (BindingType::Texture, BindingResource::Texture(_))
+            | (BindingType::Sampler, BindingResource::Sampler(_))
+            | (BindingType::TextureArray { .. }, BindingResource::ArrayTexture { .. }) => Ok(()),
+            (ty, resource) => invalid(format!(
+                "binding {} of type {:?} cannot hold {:?}",
+                entry.binding, ty, resource
+            )),
+        }
+    }
+
+    /// Comprueba las entradas de `RenderBackend::update_bind_group`: solo se pueden
+    /// cambiar elementos de un `TextureArray`.
+    pub fn check_update(&self, entries: &[BindGroupEntry]) -> Result<(), BackendError> {
+        for entry in entries {
+            self.check_entry(entry)?;
+            if !matches!(entry.resource, BindingResource::ArrayTexture { .. }) {
+                return Err(BackendError::InvalidCommands(format!(
+                    "binding {} is not a texture array and cannot be updated",
+                    entry.binding
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl BindGroupDesc {
+    /// Comprueba que cada binding del layout tiene exactamente un recurso del tipo
+    /// correcto (los `TextureArray`, como mucho uno por elemento) y que los offsets
+    /// están alineados.
+    pub fn validate(&self) -> Result<(), BackendError> {
+        let invalid = |reason: String| Err(BackendError::InvalidCommands(reason));
+        for entry in &self.entries {
+            self.layout.check_entry(entry)?;
+        }
+        for layout in &self.layout.bindings {
+            let entries: Vec<&BindGroupEntry> = self
+                .entries
+                .iter()
+                .filter(|entry| entry.binding == layout.binding)
+                .collect();
+            if let BindingType::TextureArray { .. } = layout.ty {
+                let mut elements: Vec<u32> = entries
+                    .iter()
+                    .filter_map(|entry| match entry.resource {
+                        BindingResource::ArrayTexture { element, .. } => Some(element),
+                        _ => None,
+                    })
+                    .collect();
+                elements.sort_unstable();
+                if let Some(pair) = elements.windows(2).find(|pair| pair[0] == pair[1]) {
+                    return invalid(format!(
+                        "binding {} element {} has more than one texture",
+                        layout.binding, pair[0]
+                    ));
+                }
+            } else if entries.len() != 1 {
+                return invalid(format!(
+                    "binding {} has {} resources instead of one",
+                    layout.binding,
+                    entries.len()
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Aplica una actualización ya validada con `BindGroupLayout::check_update`:
+    /// cada entrada sustituye a la del mismo elemento del array.
+    pub(crate) fn update(&mut self, entries: &[BindGroupEntry]) {
+        for entry in entries {
+            let same_element = |existing: &BindGroupEntry| match (existing.resource, entry.resource)
+            {
+                (
+                    BindingResource::ArrayTexture { element: a, .. },
+                    BindingResource::ArrayTexture { element: b, .. },
+                ) => existing.binding == entry.binding && a == b,
+                _ => false,
+            };
+            match self
+                .entries
+                .iter_mut()
+                .find(|existing| same_element(existing))
+            {
+                Some(existing) => *existing = *entry,
+                None => self.entries.push(*entry),
+            }
+        }
+    }
+}
+
+/// Sentido en pantalla de los triángulos que se consideran de cara.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FrontFace {
+    #[default]
+    Clockwise,
+    /// El de las mallas (`Mesh`), cuyos triángulos son antihorarios vistos desde fuera.
+    CounterClockwise,
+}
+
+/// Cómo se ensamblan los vértices en primitivas.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PrimitiveTopology {
+    #[default]
+    TriangleList,
+    TriangleStrip,
+    LineList,
+    LineStrip,
+    PointList,
+}
+
+/// Caras que se descartan; cuál es la frontal lo decide `FrontFace`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CullMode {
+    #[default]
+    None,
+    Front,
+    Back,
+}
+
+/// Cómo se combina el color del fragmento con el del destino.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+    /// Sustituye el color del destino.
+    #[default]
+    Replace,
+    /// `src * a + dst * (1 - a)`.
+    Alpha,
+    /// `src + dst * (1 - a)`, para colores ya multiplicados por su alfa.
+    PremultipliedAlpha,
+    /// `src + dst`.
+    Additive,
+}
+
+/// Descripción de un pipeline gráfico.
+///
+/// Los shaders son SPIR-V; los backends que no ejecutan SPIR-V (e.g. el
+/// `NullBackend`) solo los guardan. El viewport y el scissor no forman parte del
+/// pipeline: se fijan al grabar con `RenderCommand::SetViewport`, así que
+/// redimensionar la ventana no obliga a recrearlo.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PipelineDesc {
+    /// Nombre para depuración.
+    pub label: String,
+    pub vertex_shader: Vec<u8>,
+    pub fragment_shader: Vec<u8>,
+    /// Disposición de los vertex buffers, indexada por slot.
+    pub vertex_layouts: Vec<VertexLayout>,
+    pub topology: PrimitiveTopology,
+    pub cull_mode: CullMode,
+    /// Sentido de las caras frontales.
+    pub front_face: FrontFace,
+    /// Descarta fragmentos cuya profundidad no sea menor que la guardada.
+    pub depth_test: bool,
+    pub depth_write: bool,
+    pub blend: BlendMode,
+    /// Bytes de push constants visibles en todas las etapas.
+    pub push_constant_size: u32,
+    /// Layout de los bind groups de cada set, indexado por set.
+    pub bind_group_layouts: Vec<BindGroupLayout>,
+}
+
+impl PipelineDesc {
+    /// Empieza a describir un pipeline llamado `label`.
+    pub fn builder(label: impl Into<String>) -> PipelineBuilder {
+        PipelineBuilder {
+            desc: PipelineDesc {
+                label: label.into(),
+                vertex_shader: Vec::new(),
+                fragment_shader: Vec::new(),
+                vertex_layouts: Vec::new(),
+                topology: PrimitiveTopology::TriangleList,
+                cull_mode: CullMode::Back,
+                front_face: FrontFace::Clockwise,
+                depth_test: false,
+                depth_write: false,
+                blend: BlendMode::Replace,
+                push_constant_size: 0,
+                bind_group_layouts: Vec::new(),
+            },
+        }
+    }
+
+    /// Pipeline del triángulo de ejemplo: genera sus vértices en el shader, sin buffers.
+    pub fn triangle() -> Self {
+        Self::builder("triangle")
+            .vertex_shader(include_bytes!("../../shaders/shader.vert.spv"))
+            .fragment_shader(include_bytes!("../../shaders/shader.frag.spv"))
+            .build()
+            .expect("triangle pipeline is valid")
+    }
+
+    /// Pipeline de mallas instanciadas: vértices intercalados de `Mesh` en el slot 0,
+    /// matriz del modelo por instancia en el slot 1, la `CameraUniform` en el set 0
+    /// y el color del material en push constants. Usa test y escritura de profundidad.
+    pub fn mesh() -> Self {
+        Self::instanced_mesh("mesh")
+            .vertex_shader(include_bytes!("../../shaders/mesh.vert.spv"))
+            .fragment_shader(include_bytes!("../../shaders/mesh.frag.spv"))
+            .push_constants(16)
+            .build()
+            .expect("mesh pipeline is valid")
+    }
+
+    /// Pipeline de los materiales PBR: las entradas de `mesh` y, en el set 1, el
+    /// uniform y las texturas del material (`Material::bind_group_layout`).
+    pub fn pbr() -> Self {
+        Self::instanced_mesh("pbr")
+            .vertex_shader(include_bytes!("../../shaders/pbr.vert.spv"))
+            .fragment_shader(include_bytes!("../../shaders/pbr.frag.spv"))
+            .bind_group_layout(Material::bind_group_layout())
+            .build()
+            .expect("pbr pipeline is valid")
+    }
+
+    /// Parte común de `mesh` y `pbr`: vértices de `Mesh`, instancias y cámara.
+    fn instanced_mesh(label: &str) -> PipelineBuilder {
+        let column = |location: u32| VertexAttribute {
+            location,
+            format: VertexFormat::Float32x4,
+            offset: (location - 5) * 16,
+        };
+        Self::builder(label)
+            .vertex_layout(Mesh::vertex_layout())
+            .vertex_layout(VertexLayout {
+                stride: 64,
+                step_mode: VertexStepMode::Instance,
+                attributes: (5..=8).map(column).collect(),
+            })
+            .front_face(FrontFace::CounterClockwise)
+            .depth(true, true)
+            .bind_group_layout(CameraUniform::bind_group_layout())
+    }
+
+    /// Comprueba que los shaders son SPIR-V y que los vertex layouts son coherentes.
+    pub fn validate(&self) -> Result<(), BackendError> {
+        let invalid = |reason: String| Err(BackendError::InvalidPipeline(reason));
+        for (stage, code) in [
+            ("vertex", &self.vertex_shader),
+            ("fragment", &self.fragment_shader),
+        ] {
+            if code.len() < 4 || !code.len().is_multiple_of(4) || code[..4] != SPIRV_MAGIC {
+                return invalid(format!(
+                    "{} shader of '{}' is not SPIR-V",
+                    stage, self.label
+                ));
+            }
+        }
+        let mut locations = Vec::new();
+        for (slot, layout) in self.vertex_layouts.iter().enumerate() {
+            for attribute in &layout.attributes {
+                if attribute.offset + attribute.format.size() > layout.stride {
+                    return invalid(format!(
+                        "attribute at location {} overflows the stride of slot {}",
+                        attribute.location, slot
+                    ));
+                }
+                if locations.contains(&attribute.location) {
+                    return invalid(format!("location {} is bound twice", attribute.location));
+                }
+                locations.push(attribute.location);
+            }
+        }
+        if !self.push_constant_size.is_multiple_of(4) {
+            return invalid(format!(
+                "push constant size {} is not a multiple of 4",
+                self.push_constant_size
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Número mágico de SPIR-V en little-endian.
+const SPIRV_MAGIC: [u8; 4] = 0x0723_0203u32.to_le_bytes();
+
+/// Constructor de `PipelineDesc`.
+///
+/// Por defecto dibuja listas de triángulos horarios, descarta las caras traseras y
+/// no usa profundidad ni blending.
+#[derive(Clone, Debug)]
+pub struct PipelineBuilder {
+    desc: PipelineDesc,
+}
+
+impl PipelineBuilder {
+    pub fn vertex_shader(mut self, spirv: &[u8]) -> Self {
+        self.desc.vertex_shader = spirv.to_vec();
+        self
+    }
+
+    pub fn fragment_shader(mut self, spirv: &[u8]) -> Self {
+        self.desc.fragment_shader = spirv.to_vec();
+        self
+    }
+
+    /// Añade el layout del siguiente slot de vertex buffer.
+    pub fn vertex_layout(mut self, layout: VertexLayout) -> Self {
+        self.desc.vertex_layouts.push(layout);
+        self
+    }
+
+    pub fn topology(mut self, topology: PrimitiveTopology) -> Self {
+        self.desc.topology = topology;
+        self
+    }
+
+    pub fn cull_mode(mut self, cull_mode: CullMode) -> Self {
+        self.desc.cull_mode = cull_mode;
+        self
+    }
+
+    pub fn front_face(mut self, front_face: FrontFace) -> Self {
+        self.desc.front_face = front_face;
+        self
+    }
+
+    pub fn depth(mut self, test: bool, write: bool) -> Self {
+        self.desc.depth_test = test;
+        self.desc.depth_write = write;
+        self
+    }
+
+    pub fn blend(mut self, blend: BlendMode) -> Self {
+        self.desc.blend = blend;
+        self
+    }
+
+    pub fn push_constants(mut self, size: u32) -> Self {
+        self.desc.push_constant_size = size;
+        self
+    }
+
+    /// Añade el layout del siguiente set.
+    pub fn bind_group_layout(mut self, layout: BindGroupLayout) -> Self {
+        self.desc.bind_group_layouts.push(layout);
+        self
+    }
+
+    /// Completa el pipeline con la reflexión de sus shaders (`ShaderReflection`):
+    /// si no tiene vertex layouts, uno intercalado con todas las entradas; si no
+    /// tiene bind group layouts ni push constants, los que usan los shaders. Lo ya
+    /// declarado se comprueba: cada entrada y cada binding de los shaders tiene
+    /// que estar con el mismo formato o tipo.
+    pub fn reflect(mut self) -> Result<Self, BackendError> {
+        crate::shader::reflect::reflect_pipeline(&mut self.desc)?;
+        Ok(self)
+    }
+
+    /// Valida y devuelve la descripción.
+    pub fn build(self) -> Result<PipelineDesc, BackendError> {
+        self.desc.validate()?;
+        Ok(self.desc)
+    }
+}
+
+impl From<PipelineDesc> for PipelineBuilder {
+    /// Constructor con todo lo de `desc`, para cambiarle algo (e.g. los shaders).
+    fn from(desc: PipelineDesc) -> Self {
+        Self { desc }
+    }
+}
+
+/// Muestras por píxel del destino del swapchain (MSAA).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SampleCount {
+    /// Sin multisampling.
+    #[default]
+    X1,
+    X2,
+    X4,
+    X8,
+}
+
+impl SampleCount {
+    pub const ALL: [Self; 4] = [Self::X1, Self::X2, Self::X4, Self::X8];
+
+    pub fn count(self) -> u32 {
+        1 << self as u32
+    }
+
+    /// El mayor de `ALL` que no supera `requested` y para el que `supported` es cierto.
+    pub fn clamp_to(self, supported: impl Fn(Self) -> bool) -> Self {
+        Self::ALL
+            .into_iter()
+            .rev()
+            .find(|&samples| samples <= self && supported(samples))
+            .unwrap_or(Self::X1)
+    }
+}
+
+/// Información del frame devuelta por `RenderBackend::begin_frame`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FrameInfo {
+    /// Número de frame desde la creación del backend.
+    pub frame: u64,
+    /// Tamaño en píxeles de la superficie de presentación.
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Errores de un backend.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BackendError {
+    /// La superficie cambió (e.g. al redimensionar la ventana); el backend ya se
+    /// reconfiguró y el frame debe descartarse.
+    OutOfDate,
+    /// Un comando o llamada usa un recurso que no existe.
+    InvalidHandle(String),
+    /// El backend no soporta la operación.
+    Unsupported(String),
+    /// La lista de comandos no es válida (e.g. render pass sin cerrar).
+    InvalidCommands(String),
+    /// La descripción de un pipeline no es válida (e.g. un shader que no es SPIR-V).
+    InvalidPipeline(String),
+    /// Error del dispositivo o del driver.
+    Device(String),
+}
+
+impl fmt::Display for BackendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BackendError::OutOfDate => write!(f, "Surface is out of date"),
+            BackendError::InvalidHandle(handle) => write!(f, "Invalid handle: {}", handle),
+            BackendError::Unsupported(what) => write!(f, "Unsupported operation: {}", what),
+            BackendError::InvalidCommands(reason) => write!(f, "Invalid command list: {}", reason),
+            BackendError::InvalidPipeline(reason) => write!(f, "Invalid pipeline: {}", reason),
+            BackendError::Device(message) => write!(f, "Device error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for BackendError {}
+
+impl From<AllocationError> for BackendError {
+    fn from(error: AllocationError) -> Self {
+        BackendError::Device(error.to_string())
+    }
+}
+
+/// Interfaz común de los backends de render.
+///
+/// Ciclo de un frame: `begin_frame` → `submit` (una o varias listas) → `present`.
+pub trait RenderBackend {
+    /// Nombre del backend (e.g. `"vulkan"`, `"null"`).
+    fn name(&self) -> &'static str;
+
+    /// Crea un buffer sin inicializar.
+    fn create_buffer(&mut self, desc: &BufferDesc) -> Result<BufferId, BackendError>;
+
+    /// Escribe `data` en el buffer a partir de `offset`.
+    fn write_buffer(
+        &mut self,
+        buffer: BufferId,
+        offset: u64,
+        data: &[u8],
+    ) -> Result<(), BackendError>;
+
+    /// Destruye el buffer. Como con el resto de `destroy_*`, el id deja de ser válido
+    /// en el acto, pero el backend puede aplazar liberarlo hasta que terminen los
+    /// frames en vuelo que lo usan, sin esperar a la GPU.
+    fn destroy_buffer(&mut self, buffer: BufferId);
+
+    /// Crea una textura sin inicializar.
+    fn create_texture(&mut self, desc: &TextureDesc) -> Result<TextureId, BackendError>;
+
+    /// Sube los píxeles del nivel de mip `mip_level`, ajustados al formato de la textura.
+    fn write_texture(
+        &mut self,
+        texture: TextureId,
+        mip_level: u32,
+        data: &[u8],
+    ) -> Result<(), BackendError>;
+
+    fn destroy_texture(&mut self, texture: TextureId);
+
+    /// Encola la escritura de `data` en el buffer sin esperar a la GPU.
+    ///
+    /// Las subidas encoladas se envían juntas con `flush_uploads` o, como tarde, al
+    /// presentar el frame actual, y el siguiente frame las ve terminadas. Por
+    /// defecto se escribe en el momento con `write_buffer`.
+    fn upload_buffer(
+        &mut self,
+        buffer: BufferId,
+        offset: u64,
+        data: &[u8],
+    ) -> Result<UploadTicket, BackendError> {
+        self.write_buffer(buffer, offset, data)?;
+        Ok(UploadTicket::COMPLETE)
+    }
+
+    /// Como `upload_buffer`, para el nivel de mip `mip_level` de una textura.
+    fn upload_texture(
+        &mut self,
+        texture: TextureId,
+        mip_level: u32,
+        data: &[u8],
+    ) -> Result<UploadTicket, BackendError> {
+        self.write_texture(texture, mip_level, data)?;
+        Ok(UploadTicket::COMPLETE)
+    }
+
+    /// Envía a la GPU las subidas encoladas.
+    fn flush_uploads(&mut self) -> Result<(), BackendError> {
+        Ok(())
+    }
+
+    /// Si la GPU ya terminó la subida de `ticket`.
+    fn upload_complete(&mut self, ticket: UploadTicket) -> bool {
+        ticket == UploadTicket::COMPLETE
+    }
+
+    fn upload_stats(&self) -> UploadStats {
+        UploadStats::default()
+    }
+
+    /// Crea un pipeline gráfico.
+    fn create_pipeline(&mut self, desc: &PipelineDesc) -> Result<PipelineId, BackendError>;
+
+    fn destroy_pipeline(&mut self, pipeline: PipelineId);
+
+    /// Sustituye un pipeline por otro creado con `desc` conservando su id (e.g. al
+    /// recompilar sus shaders). Si falla, el pipeline anterior sigue intacto.
+    fn replace_pipeline(
+        &mut self,
+        pipeline: PipelineId,
+        desc: &PipelineDesc,
+    ) -> Result<(), BackendError>;
+
+    /// Crea un grupo de recursos para enlazarlo con `RenderCommand::BindGroup`.
+    fn create_bind_group(&mut self, desc: &BindGroupDesc) -> Result<BindGroupId, BackendError>;
+
+    /// Cambia elementos de los `TextureArray` de un grupo ya creado.
+    ///
+    /// Los elementos que lee un frame en vuelo no deben cambiarse hasta que
+    /// termine; `BindlessTextures` se encarga de ello.
+    fn update_bind_group(
+        &mut self,
+        group: BindGroupId,
+        entries: &[BindGroupEntry],
+    ) -> Result<(), BackendError>;
+
+    fn destroy_bind_group(&mut self, group: BindGroupId);
+
+    /// Crea un sampler para enlazarlo en un binding `Sampler`.
+    fn create_sampler(&mut self, desc: &SamplerDesc) -> Result<SamplerId, BackendError>;
+
+    fn destroy_sampler(&mut self, sampler: SamplerId);
+
+    /// Empieza un frame (en Vulkan, adquiere la imagen del swapchain).
+    fn begin_frame(&mut self) -> Result<FrameInfo, BackendError>;
+
+    /// Ejecuta una lista de comandos dentro del frame actual.
+    fn submit(&mut self, commands: CommandList) -> Result<(), BackendError>;
+
+    /// Presenta el frame actual.
+    fn present(&mut self) -> Result<(), BackendError>;
+
+    /// Notifica un cambio de tamaño de la superficie.
+    fn resize(&mut self, width: u32, height: u32);
+
+    /// Tamaño actual de la superficie en píxeles.
+    fn surface_size(&self) -> (u32, u32);
+
+    /// Pide `requested` muestras por píxel para los render passes del swapchain y
+    /// devuelve las que se usarán: las más cercanas por debajo que admita el
+    /// dispositivo. Los pipelines ya creados se adaptan solos.
+    fn set_sample_count(&mut self, requested: SampleCount) -> SampleCount;
+
+    /// Muestras por píxel actuales.
+    fn sample_count(&self) -> SampleCount;
+
+    /// Espera a que el dispositivo termine todo el trabajo pendiente.
+    fn wait_idle(&mut self);
+
+    /// Libera todos los recursos del backend. No debe usarse después.
+    fn cleanup(&mut self);
+
+    fn as_any(&self) -> &dyn Any;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
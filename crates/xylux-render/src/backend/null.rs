@@ -0,0 +1,817 @@
+//! Backend nulo: no dibuja, pero valida y guarda todo lo que recibe.
+
+use super::upload::{
+    DEFAULT_STAGING_CAPACITY, StageError, UploadBatch, UploadScheduler, UploadStats, UploadTarget,
+    UploadTicket,
+};
+use super::{
+    BackendError, BarrierResource, BindGroupDesc, BindGroupEntry, BindGroupId, BindingResource,
+    BufferDesc, BufferId, CommandList, FrameInfo, PipelineDesc, PipelineId, RenderBackend,
+    RenderCommand, SampleCount, SamplerDesc, SamplerId, TextureDesc, TextureId, TextureUsage,
+};
+use std::any::Any;
+use std::collections::{HashMap, VecDeque};
+
+/// Comandos enviados durante un frame.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RecordedFrame {
+    pub frame: u64,
+    pub commands: Vec<RenderCommand>,
+    pub presented: bool,
+}
+
+impl RecordedFrame {
+    /// Número de comandos de dibujo del frame.
+    pub fn draw_count(&self) -> usize {
+        self.commands
+            .iter()
+            .filter(|command| {
+                matches!(
+                    command,
+                    RenderCommand::Draw { .. } | RenderCommand::DrawIndexed { .. }
+                )
+            })
+            .count()
+    }
+}
+
+/// Backend sin GPU para tests y modo headless.
+///
+/// Los buffers y texturas guardan su contenido en memoria, y cada frame guarda su
+/// flujo de comandos en `frames()`. Las subidas asíncronas pasan por un ring de
+/// staging como en Vulkan, y una "GPU" simulada termina sus batches al presentar.
+pub struct NullBackend {
+    width: u32,
+    height: u32,
+    next_id: u32,
+    buffers: HashMap<BufferId, (BufferDesc, Vec<u8>)>,
+    textures: HashMap<TextureId, (TextureDesc, Vec<Vec<u8>>)>,
+    pipelines: HashMap<PipelineId, PipelineDesc>,
+    bind_groups: HashMap<BindGroupId, BindGroupDesc>,
+    samplers: HashMap<SamplerId, SamplerDesc>,
+    frames: Vec<RecordedFrame>,
+    in_frame: bool,
+    out_of_date: bool,
+    samples: SampleCount,
+    uploads: UploadScheduler,
+    /// Memoria del ring de staging; crece hasta la capacidad a medida que se usa.
+    staging: Vec<u8>,
+    uploads_in_flight: VecDeque<UploadBatch>,
+}
+
+impl NullBackend {
+    /// Crea un backend con una superficie virtual de `width`x`height`.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            next_id: 0,
+            buffers: HashMap::new(),
+            textures: HashMap::new(),
+            pipelines: HashMap::new(),
+            bind_groups: HashMap::new(),
+            samplers: HashMap::new(),
+            frames: Vec::new(),
+            in_frame: false,
+            out_of_date: false,
+            samples: SampleCount::X1,
+            uploads: UploadScheduler::new(DEFAULT_STAGING_CAPACITY),
+            staging: Vec::new(),
+            uploads_in_flight: VecDeque::new(),
+        }
+    }
+
+    /// Cambia el tamaño del ring de staging de las subidas asíncronas.
+    pub fn with_staging_capacity(mut self, capacity: u64) -> Self {
+        self.uploads = UploadScheduler::new(capacity);
+        self
+    }
+
+    fn next_id(&mut self) -> u32 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    /// Frames grabados, del más antiguo al más reciente.
+    pub fn frames(&self) -> &[RecordedFrame] {
+        &self.frames
+    }
+
+    /// Último frame grabado.
+    pub fn last_frame(&self) -> Option<&RecordedFrame> {
+        self.frames.last()
+    }
+
+    /// Contenido actual de un buffer.
+    pub fn buffer_data(&self, buffer: BufferId) -> Option<&[u8]> {
+        self.buffers.get(&buffer).map(|(_, data)| data.as_slice())
+    }
+
+    pub fn buffer_desc(&self, buffer: BufferId) -> Option<&BufferDesc> {
+        self.buffers.get(&buffer).map(|(desc, _)| desc)
+    }
+
+    /// Contenido de un nivel de mip de una textura.
+    pub fn texture_data(&self, texture: TextureId, mip_level: u32) -> Option<&[u8]> {
+        self.textures
+            .get(&texture)
+            .and_then(|(_, mips)| mips.get(mip_level as usize))
+            .map(Vec::as_slice)
+    }
+
+    pub fn texture_desc(&self, texture: TextureId) -> Option<&TextureDesc> {
+        self.textures.get(&texture).map(|(desc, _)| desc)
+    }
+
+    pub fn pipeline_desc(&self, pipeline: PipelineId) -> Option<&PipelineDesc> {
+        self.pipelines.get(&pipeline)
+    }
+
+    pub fn bind_group_desc(&self, group: BindGroupId) -> Option<&BindGroupDesc> {
+        self.bind_groups.get(&group)
+    }
+
+    pub fn sampler_desc(&self, sampler: SamplerId) -> Option<&SamplerDesc> {
+        self.samplers.get(&sampler)
+    }
+
+    /// Número de buffers, texturas y pipelines vivos.
+    pub fn resource_counts(&self) -> (usize, usize, usize) {
+        (
+            self.buffers.len(),
+            self.textures.len(),
+            self.pipelines.len(),
+        )
+    }
+
+    /// Hace que el próximo `begin_frame` devuelva `OutOfDate`, como tras redimensionar.
+    pub fn simulate_out_of_date(&mut self) {
+        self.out_of_date = true;
+    }
+
+    /// Comprueba una subida con las mismas reglas que `write_buffer` y `write_texture`.
+    fn check_upload(&self, target: UploadTarget, len: usize) -> Result<(), BackendError> {
+        match target {
+            UploadTarget::Buffer { buffer, offset } => {
+                let (desc, _) = self
+                    .buffers
+                    .get(&buffer)
+                    .ok_or_else(|| BackendError::InvalidHandle(format!("{:?}", buffer)))?;
+                if offset + len as u64 > desc.size {
+                    return Err(BackendError::InvalidCommands(format!(
+                        "write of {} bytes at {} overflows {:?} ({} bytes)",
+                        len, offset, buffer, desc.size
+                    )));
+                }
+            }
+            UploadTarget::Texture { texture, mip_level } => {
+                let (desc, _) = self
+                    .textures
+                    .get(&texture)
+                    .ok_or_else(|| BackendError::InvalidHandle(format!("{:?}", texture)))?;
+                let expected = mip_size(desc, mip_level);
+                if len != expected || mip_level >= desc.mip_levels.max(1) {
+                    return Err(BackendError::InvalidCommands(format!(
+                        "mip {} of {:?} expects {} bytes, got {}",
+                        mip_level, texture, expected, len
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_upload(&mut self, target: UploadTarget, data: &[u8]) -> Result<(), BackendError> {
+        match target {
+            UploadTarget::Buffer { buffer, offset } => self.write_buffer(buffer, offset, data),
+            UploadTarget::Texture { texture, mip_level } => {
+                self.write_texture(texture, mip_level, data)
+            }
+        }
+    }
+
+    /// Copia `data` al ring. Si no hay hueco, "espera" a la GPU terminando el batch
+    /// más antiguo; lo que no cabe ni con el ring vacío se escribe en el momento.
+    fn stage_upload(
+        &mut self,
+        target: UploadTarget,
+        data: &[u8],
+    ) -> Result<UploadTicket, BackendError> {
+        self.check_upload(target, data.len())?;
+        if data.is_empty() {
+            return Ok(UploadTicket::COMPLETE);
+        }
+        loop {
+            match self.uploads.stage(target, data.len() as u64) {
+                Ok(offset) => {
+                    let (start, end) = (offset as usize, offset as usize + data.len());
+                    if self.staging.len() < end {
+                        self.staging.resize(end, 0);
+                    }
+                    self.staging[start..end].copy_from_slice(data);
+                    return Ok(self.uploads.pending_ticket());
+                }
+                Err(StageError::Full) => {
+                    self.flush_uploads()?;
+                    let oldest = self
+                        .uploads
+                        .oldest_in_flight()
+                        .expect("A full staging ring has batches in flight");
+                    self.complete_uploads(oldest);
+                }
+                Err(StageError::TooLarge) => {
+                    self.flush_uploads()?;
+                    self.complete_uploads(self.uploads.last_submitted());
+                    self.apply_upload(target, data)?;
+                    return Ok(UploadTicket::COMPLETE);
+                }
+            }
+        }
+    }
+
+    /// Simula que la GPU termina los batches enviados hasta `ticket`.
+    fn complete_uploads(&mut self, ticket: UploadTicket) {
+        while self
+            .uploads_in_flight
+            .front()
+            .is_some_and(|batch| batch.ticket <= ticket)
+        {
+            let batch = self.uploads_in_flight.pop_front().expect("Checked above");
+            for copy in batch.copies {
+                let start = copy.staging_offset as usize;
+                let data = self.staging[start..start + copy.size as usize].to_vec();
+                // El destino pudo destruirse después de encolar la subida.
+                self.apply_upload(copy.target, &data).ok();
+            }
+        }
+        self.uploads.retire(ticket);
+    }
+
+    /// Comprueba que el recurso de un binding existe y admite ese uso.
+    fn check_binding(&self, entry: &BindGroupEntry) -> Result<(), BackendError> {
+        match entry.resource {
+            BindingResource::Buffer {
+                buffer,
+                offset,
+                size,
+            } => {
+                let (buffer_desc, _) = self
+                    .buffers
+                    .get(&buffer)
+                    .ok_or_else(|| BackendError::InvalidHandle(format!("{:?}", buffer)))?;
+                if offset + size > buffer_desc.size {
+                    return Err(BackendError::InvalidCommands(format!(
+                        "binding {} range {}..{} overflows {:?} ({} bytes)",
+                        entry.binding,
+                        offset,
+                        offset + size,
+                        buffer,
+                        buffer_desc.size
+                    )));
+                }
+            }
+            BindingResource::Texture(texture) | BindingResource::ArrayTexture { texture, .. } => {
+                let (texture_desc, _) = self
+                    .textures
+                    .get(&texture)
+                    .ok_or_else(|| BackendError::InvalidHandle(format!("{:?}", texture)))?;
+                if !texture_desc.usage.contains(TextureUsage::SAMPLED) {
+                    return Err(BackendError::InvalidCommands(format!(
+                        "binding {}: {:?} was not created with SAMPLED usage",
+                        entry.binding, texture
+                    )));
+                }
+            }
+            BindingResource::Sampler(sampler) => {
+                if !self.samplers.contains_key(&sampler) {
+                    return Err(BackendError::InvalidHandle(format!("{:?}", sampler)));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn check_handles(&self, commands: &CommandList) -> Result<(), BackendError> {
+        let missing = |what: String| Err(BackendError::InvalidHandle(what));
+        for command in commands.commands() {
+            match command {
+                RenderCommand::BindPipeline(id) if !self.pipelines.contains_key(id) => {
+                    return missing(format!("{:?}", id));
+                }
+                RenderCommand::BindVertexBuffer { buffer, .. }
+                | RenderCommand::BindIndexBuffer { buffer, .. }
+                    if !self.buffers.contains_key(buffer) =>
+                {
+                    return missing(format!("{:?}", buffer));
+                }
+                RenderCommand::BindGroup { group, .. } if !self.bind_groups.contains_key(group) => {
+                    return missing(format!("{:?}", group));
+                }
+                RenderCommand::BeginRenderPass(desc) => {
+                    if let super::RenderTarget::Texture(id) = desc.target
+                        && !self.textures.contains_key(&id)
+                    {
+                        return missing(format!("{:?}", id));
+                    }
+                }
+                RenderCommand::Barrier(barriers) => {
+                    for barrier in barriers {
+                        match barrier.resource {
+                            BarrierResource::Texture(id) if !self.textures.contains_key(&id) => {
+                                return missing(format!("{:?}", id));
+                            }
+                            BarrierResource::Buffer(id) if !self.buffers.contains_key(&id) => {
+                                return missing(format!("{:?}", id));
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Tamaño en bytes de un nivel de mip.
+pub(crate) fn mip_size(desc: &TextureDesc, mip_level: u32) -> usize {
+    let width = (desc.width >> mip_level).max(1) as usize;
+    let height = (desc.height >> mip_level).max(1) as usize;
+    width * height * desc.format.bytes_per_pixel() as usize
+}
+
+impl RenderBackend for NullBackend {
+    fn name(&self) -> &'static str {
+        "null"
+    }
+
+    fn create_buffer(&mut self, desc: &BufferDesc) -> Result<BufferId, BackendError> {
+        let id = BufferId(self.next_id());
+        self.buffers
+            .insert(id, (desc.clone(), vec![0; desc.size as usize]));
+        Ok(id)
+    }
+
+    fn write_buffer(
+        &mut self,
+        buffer: BufferId,
+        offset: u64,
+        data: &[u8],
+    ) -> Result<(), BackendError> {
+        let (_, contents) = self
+            .buffers
+            .get_mut(&buffer)
+            .ok_or_else(|| BackendError::InvalidHandle(format!("{:?}", buffer)))?;
+        let start = offset as usize;
+        let end = start + data.len();
+        if end > contents.len() {
+            return Err(BackendError::InvalidCommands(format!(
+                "write of {} bytes at {} overflows {:?} ({} bytes)",
+                data.len(),
+                offset,
+                buffer,
+                contents.len()
+            )));
+        }
+        contents[start..end].copy_from_slice(data);
+        Ok(())
+    }
+
+    fn destroy_buffer(&mut self, buffer: BufferId) {
+        self.buffers.remove(&buffer);
+    }
+
+    fn create_texture(&mut self, desc: &TextureDesc) -> Result<TextureId, BackendError> {
+        let id = TextureId(self.next_id());
+        let mips = (0..desc.mip_levels.max(1))
+            .map(|level| vec![0; mip_size(desc, level)])
+            .collect();
+        self.textures.insert(id, (desc.clone(), mips));
+        Ok(id)
+    }
+
+    fn write_texture(
+        &mut self,
+        texture: TextureId,
+        mip_level: u32,
+        data: &[u8],
+    ) -> Result<(), BackendError> {
+        let (desc, mips) = self
+            .textures
+            .get_mut(&texture)
+            .ok_or_else(|| BackendError::InvalidHandle(format!("{:?}", texture)))?;
+        let expected = mip_size(desc, mip_level);
+        let mip = mips.get_mut(mip_level as usize).ok_or_else(|| {
+            BackendError::InvalidCommands(format!("{:?} has no mip level {}", texture, mip_level))
+        })?;
+        if data.len() != expected {
+            return Err(BackendError::InvalidCommands(format!(
+                "mip {} of {:?} expects {} bytes, got {}",
+                mip_level,
+                texture,
+                expected,
+                data.len()
+            )));
+        }
+        mip.copy_from_slice(data);
+        Ok(())
+    }
+
+    fn destroy_texture(&mut self, texture: TextureId) {
+        self.textures.remove(&texture);
+    }
+
+    fn upload_buffer(
+        &mut self,
+        buffer: BufferId,
+        offset: u64,
+        data: &[u8],
+    ) -> Result<UploadTicket, BackendError> {
+        self.stage_upload(UploadTarget::Buffer { buffer, offset }, data)
+    }
+
+    fn upload_texture(
+        &mut self,
+        texture: TextureId,
+        mip_level: u32,
+        data: &[u8],
+    ) -> Result<UploadTicket, BackendError> {
+        self.stage_upload(UploadTarget::Texture { texture, mip_level }, data)
+    }
+
+    fn flush_uploads(&mut self) -> Result<(), BackendError> {
+        if let Some(batch) = self.uploads.take_batch() {
+            self.uploads_in_flight.push_back(batch);
+        }
+        Ok(())
+    }
+
+    fn upload_complete(&mut self, ticket: UploadTicket) -> bool {
+        self.uploads.is_complete(ticket)
+    }
+
+    fn upload_stats(&self) -> UploadStats {
+        self.uploads.stats()
+    }
+
+    fn create_pipeline(&mut self, desc: &PipelineDesc) -> Result<PipelineId, BackendError> {
+        desc.validate()?;
+        let id = PipelineId(self.next_id());
+        self.pipelines.insert(id, desc.clone());
+        Ok(id)
+    }
+
+    fn destroy_pipeline(&mut self, pipeline: PipelineId) {
+        self.pipelines.remove(&pipeline);
+    }
+
+    fn replace_pipeline(
+        &mut self,
+        pipeline: PipelineId,
+        desc: &PipelineDesc,
+    ) -> Result<(), BackendError> {
+        desc.validate()?;
+        let existing = self
+            .pipelines
+            .get_mut(&pipeline)
+            .ok_or_else(|| BackendError::InvalidHandle(format!("{:?}", pipeline)))?;
+        *existing = desc.clone();
+        Ok(())
+    }
+
+    fn create_bind_group(&mut self, desc: &BindGroupDesc) -> Result<BindGroupId, BackendError> {
+        desc.validate()?;
+        for entry in &desc.entries {
+            self.check_binding(entry)?;
+        }
+        let id = BindGroupId(self.next_id());
+        self.bind_groups.insert(id, desc.clone());
+        Ok(id)
+    }
+
+    fn update_bind_group(
+        &mut self,
+        group: BindGroupId,
+        entries: &[BindGroupEntry],
+    ) -> Result<(), BackendError> {
+        let desc = self
+            .bind_groups
+            .get(&group)
+            .ok_or_else(|| BackendError::InvalidHandle(format!("{:?}", group)))?;
+        desc.layout.check_update(entries)?;
+        for entry in entries {
+            self.check_binding(entry)?;
+        }
+        self.bind_groups
+            .get_mut(&group)
+            .expect("bind group was checked")
+            .update(entries);
+        Ok(())
+    }
+
+    fn destroy_bind_group(&mut self, group: BindGroupId) {
+        self.bind_groups.remove(&group);
+    }
+
+    fn create_sampler(&mut self, desc: &SamplerDesc) -> Result<SamplerId, BackendError> {
+        desc.validate()?;
+        let id = SamplerId(self.next_id());
+        self.samplers.insert(id, *desc);
+        Ok(id)
+    }
+
+    fn destroy_sampler(&mut self, sampler: SamplerId) {
+        self.samplers.remove(&sampler);
+    }
+
+    fn begin_frame(&mut self) -> Result<FrameInfo, BackendError> {
+        if std::mem::take(&mut self.out_of_date) {
+            return Err(BackendError::OutOfDate);
+        }
+        let frame = self.frames.len() as u64;
+        self.frames.push(RecordedFrame {
+            frame,
+            ..Default::default()
+        });
+        self.in_frame = true;
+        Ok(FrameInfo {
+            frame,
+            width: self.width,
+            height: self.height,
+        })
+    }
+
+    fn submit(&mut self, commands: CommandList) -> Result<(), BackendError> {
+        if !self.in_frame {
+            return Err(BackendError::InvalidCommands(
+                "submit outside of a frame".into(),
+            ));
+        }
+        commands.validate().map_err(BackendError::InvalidCommands)?;
+        self.check_handles(&commands)?;
+        let frame = self.frames.last_mut().expect("frame in progress");
+        frame.commands.extend(commands.commands().iter().cloned());
+        Ok(())
+    }
+
+    fn present(&mut self) -> Result<(), BackendError> {
+        if !std::mem::take(&mut self.in_frame) {
+            return Err(BackendError::InvalidCommands(
+                "present outside of a frame".into(),
+            ));
+        }
+        self.frames.last_mut().expect("frame in progress").presented = true;
+        self.flush_uploads()?;
+        self.complete_uploads(self.uploads.last_submitted());
+        Ok(())
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+    }
+
+    fn surface_size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    fn set_sample_count(&mut self, requested: SampleCount) -> SampleCount {
+        self.samples = requested;
+        requested
+    }
+
+    fn sample_count(&self) -> SampleCount {
+        self.samples
+    }
+
+    fn wait_idle(&mut self) {
+        self.complete_uploads(self.uploads.last_submitted());
+    }
+
+    fn cleanup(&mut self) {
+        self.uploads_in_flight.clear();
+        self.buffers.clear();
+        self.textures.clear();
+        self.pipelines.clear();
+        self.bind_groups.clear();
+        self.samplers.clear();
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::allocator::MemoryLocation;
+    use crate::backend::{
+        BlendMode, BufferUsage, CullMode, FrontFace, PrimitiveTopology, RenderPassDesc,
+        TextureFormat, TextureUsage, VertexAttribute, VertexFormat, VertexLayout, VertexStepMode,
+    };
+    use crate::renderer::Renderer;
+    use crate::renderer::renderer::tests::spawn_hello_triangle;
+    use xylux_ecs::World;
+
+    #[test]
+    fn test_records_frames_and_validates_commands() {
+        let mut backend = NullBackend::new(320, 240);
+        let pipeline = backend.create_pipeline(&PipelineDesc::triangle()).unwrap();
+        let buffer = backend
+            .create_buffer(&BufferDesc {
+                size: 8,
+                usage: BufferUsage::VERTEX | BufferUsage::TRANSFER_DST,
+                location: MemoryLocation::GpuOnly,
+            })
+            .unwrap();
+        backend.write_buffer(buffer, 4, &[1, 2, 3, 4]).unwrap();
+        assert_eq!(
+            backend.buffer_data(buffer),
+            Some(&[0, 0, 0, 0, 1, 2, 3, 4][..])
+        );
+        assert!(backend.write_buffer(buffer, 6, &[0; 4]).is_err());
+
+        let info = backend.begin_frame().unwrap();
+        assert_eq!((info.frame, info.width, info.height), (0, 320, 240));
+
+        let mut commands = CommandList::new();
+        commands.begin_render_pass(RenderPassDesc::swapchain([0.0, 0.0, 0.0, 1.0]));
+        commands.bind_pipeline(pipeline);
+        commands.bind_vertex_buffer(0, buffer, 0);
+        commands.draw(0..3, 0..1);
+        commands.end_render_pass();
+        backend.submit(commands.clone()).unwrap();
+        backend.present().unwrap();
+
+        let frame = backend.last_frame().unwrap();
+        assert!(frame.presented);
+        assert_eq!(frame.commands, commands.commands());
+        assert_eq!(frame.draw_count(), 1);
+
+        // Draw sin render pass y handles inexistentes se rechazan.
+        backend.begin_frame().unwrap();
+        let mut invalid = CommandList::new();
+        invalid.draw(0..3, 0..1);
+        assert!(matches!(
+            backend.submit(invalid),
+            Err(BackendError::InvalidCommands(_))
+        ));
+        backend.destroy_pipeline(pipeline);
+        assert!(matches!(
+            backend.submit(commands),
+            Err(BackendError::InvalidHandle(_))
+        ));
+    }
+
+    #[test]
+    fn test_pipeline_builder_validates_desc() {
+        let mesh = PipelineDesc::mesh();
+        assert_eq!(mesh.topology, PrimitiveTopology::TriangleList);
+        assert_eq!(mesh.cull_mode, CullMode::Back);
+        assert_eq!(mesh.front_face, FrontFace::CounterClockwise);
+        assert_eq!(mesh.vertex_layouts.len(), 2);
+
+        let builder = PipelineDesc::builder("lines")
+            .vertex_shader(&mesh.vertex_shader)
+            .fragment_shader(&mesh.fragment_shader);
+        let lines = builder
+            .clone()
+            .topology(PrimitiveTopology::LineList)
+            .cull_mode(CullMode::None)
+            .depth(true, false)
+            .blend(BlendMode::Additive)
+            .build()
+            .unwrap();
+        assert_eq!((lines.depth_test, lines.depth_write), (true, false));
+
+        let attribute = |location, offset| VertexAttribute {
+            location,
+            format: VertexFormat::Float32x3,
+            offset,
+        };
+        let layout = |attributes| VertexLayout {
+            stride: 16,
+            step_mode: VertexStepMode::Vertex,
+            attributes,
+        };
+        let invalid = [
+            builder.clone().vertex_shader(b"not spir-v"),
+            builder.clone().vertex_layout(layout(vec![attribute(0, 8)])),
+            builder
+                .clone()
+                .vertex_layout(layout(vec![attribute(0, 0)]))
+                .vertex_layout(layout(vec![attribute(0, 0)])),
+            builder.clone().push_constants(6),
+        ];
+        for builder in invalid {
+            assert!(matches!(
+                builder.build(),
+                Err(BackendError::InvalidPipeline(_))
+            ));
+        }
+
+        let mut backend = NullBackend::new(8, 8);
+        let desc = PipelineDesc {
+            fragment_shader: Vec::new(),
+            ..lines
+        };
+        assert!(backend.create_pipeline(&desc).is_err());
+    }
+
+    #[test]
+    fn test_async_uploads_are_batched_until_present() {
+        let mut backend = NullBackend::new(8, 8).with_staging_capacity(64);
+        let buffer = backend
+            .create_buffer(&BufferDesc {
+                size: 128,
+                usage: BufferUsage::VERTEX,
+                location: MemoryLocation::CpuToGpu,
+            })
+            .unwrap();
+        let texture = backend
+            .create_texture(&TextureDesc {
+                width: 2,
+                height: 2,
+                mip_levels: 1,
+                format: TextureFormat::Rgba8Unorm,
+                usage: TextureUsage::SAMPLED,
+            })
+            .unwrap();
+
+        let first = backend.upload_buffer(buffer, 0, &[1; 8]).unwrap();
+        let second = backend.upload_texture(texture, 0, &[2; 16]).unwrap();
+        assert_eq!(first, second);
+        assert!(!backend.upload_complete(first));
+        assert_eq!(backend.buffer_data(buffer).unwrap()[0], 0);
+        assert!(backend.upload_buffer(buffer, 124, &[0; 8]).is_err());
+
+        backend.begin_frame().unwrap();
+        backend.present().unwrap();
+        assert!(backend.upload_complete(first));
+        assert_eq!(backend.buffer_data(buffer).unwrap()[..8], [1; 8]);
+        assert_eq!(backend.texture_data(texture, 0).unwrap(), [2; 16]);
+
+        // Sin hueco en el ring se espera al batch más antiguo; lo que no cabe ni
+        // vacío se escribe en el momento, después de todo lo anterior.
+        let third = backend.upload_buffer(buffer, 0, &[3; 48]).unwrap();
+        backend.flush_uploads().unwrap();
+        let fourth = backend.upload_buffer(buffer, 0, &[4; 32]).unwrap();
+        assert!(backend.upload_complete(third) && !backend.upload_complete(fourth));
+        assert_eq!(backend.buffer_data(buffer).unwrap()[0], 3);
+        let oversized = backend.upload_buffer(buffer, 0, &[5; 80]).unwrap();
+        assert_eq!(oversized, UploadTicket::COMPLETE);
+        assert!(backend.upload_complete(fourth));
+        assert_eq!(backend.buffer_data(buffer).unwrap()[..80], [5; 80]);
+
+        let stats = backend.upload_stats();
+        assert_eq!(
+            (stats.batches, stats.copies, stats.stalls, stats.oversized),
+            (3, 4, 1, 1)
+        );
+    }
+
+    #[test]
+    fn test_renderer_headless_records_frames() {
+        let mut renderer = Renderer::headless(640, 480);
+        let mut world = World::new(16);
+        spawn_hello_triangle(&mut renderer, &mut world);
+
+        renderer.render(&mut world);
+        renderer.render(&mut world);
+
+        let backend = renderer.backend_as::<NullBackend>().unwrap();
+        assert_eq!(backend.frames().len(), 2);
+        // Los vértices e índices del triángulo van por el ring de staging, una sola vez.
+        let uploads = backend.upload_stats();
+        assert_eq!((uploads.batches, uploads.copies), (1, 2));
+        let frame = backend.last_frame().unwrap();
+        assert!(frame.presented);
+        assert_eq!(frame.draw_count(), 1);
+        assert!(matches!(
+            frame.commands.first(),
+            Some(RenderCommand::BeginRenderPass(_))
+        ));
+        assert_eq!(frame.commands.last(), Some(&RenderCommand::EndRenderPass));
+
+        // Un frame con la superficie desactualizada se descarta sin grabar nada.
+        renderer
+            .backend_mut()
+            .as_any_mut()
+            .downcast_mut::<NullBackend>()
+            .unwrap()
+            .simulate_out_of_date();
+        renderer.render(&mut world);
+        assert_eq!(
+            renderer.backend_as::<NullBackend>().unwrap().frames().len(),
+            2
+        );
+
+        renderer.cleanup();
+    }
+}
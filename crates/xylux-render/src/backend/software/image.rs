@@ -0,0 +1,200 @@
+//! Imágenes RGBA8 en memoria, lectura/escritura PNG y comparación con imágenes de referencia.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+/// Variable de entorno que, si está definida, regenera las imágenes de referencia.
+pub const UPDATE_GOLDEN_ENV: &str = "XYLUX_UPDATE_GOLDEN";
+
+/// Imagen RGBA con 8 bits por canal, fila a fila desde arriba.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RgbaImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+/// Diferencia entre dos imágenes del mismo tamaño.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ImageDiff {
+    /// Mayor diferencia absoluta entre canales.
+    pub max_difference: u8,
+    /// Píxeles con algún canal distinto.
+    pub differing_pixels: usize,
+}
+
+impl ImageDiff {
+    /// `true` si ningún canal difiere más de `tolerance`.
+    pub fn within(&self, tolerance: u8) -> bool {
+        self.max_difference <= tolerance
+    }
+}
+
+impl fmt::Display for ImageDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} differing pixels, max channel difference {}",
+            self.differing_pixels, self.max_difference
+        )
+    }
+}
+
+impl RgbaImage {
+    /// Imagen de `width`x`height` rellena de negro transparente.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width as usize * height as usize * 4],
+        }
+    }
+
+    /// Envuelve píxeles RGBA8 existentes.
+    pub fn from_raw(width: u32, height: u32, pixels: Vec<u8>) -> Option<Self> {
+        (pixels.len() == width as usize * height as usize * 4).then_some(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let index = (y as usize * self.width as usize + x as usize) * 4;
+        self.pixels[index..index + 4].try_into().unwrap()
+    }
+
+    pub fn set_pixel(&mut self, x: u32, y: u32, color: [u8; 4]) {
+        let index = (y as usize * self.width as usize + x as usize) * 4;
+        self.pixels[index..index + 4].copy_from_slice(&color);
+    }
+
+    /// Compara con otra imagen. Devuelve `None` si los tamaños no coinciden.
+    pub fn diff(&self, other: &RgbaImage) -> Option<ImageDiff> {
+        if (self.width, self.height) != (other.width, other.height) {
+            return None;
+        }
+        let mut diff = ImageDiff::default();
+        for (a, b) in self
+            .pixels
+            .chunks_exact(4)
+            .zip(other.pixels.chunks_exact(4))
+        {
+            let max = a
+                .iter()
+                .zip(b)
+                .map(|(a, b)| a.abs_diff(*b))
+                .max()
+                .unwrap_or(0);
+            if max > 0 {
+                diff.differing_pixels += 1;
+                diff.max_difference = diff.max_difference.max(max);
+            }
+        }
+        Some(diff)
+    }
+
+    /// Codifica la imagen como PNG.
+    pub fn write_png<W: io::Write>(&self, writer: W) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(writer, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(io::Error::other)?;
+        writer
+            .write_image_data(&self.pixels)
+            .map_err(io::Error::other)
+    }
+
+    /// Decodifica un PNG de 8 bits (RGB, RGBA, gris o paleta) a RGBA8.
+    pub fn read_png<R: io::Read>(reader: R) -> io::Result<Self> {
+        let mut decoder = png::Decoder::new(reader);
+        decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+        let mut reader = decoder.read_info().map_err(io::Error::other)?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer).map_err(io::Error::other)?;
+        buffer.truncate(info.buffer_size());
+
+        let pixels = match info.color_type {
+            png::ColorType::Rgba => buffer,
+            png::ColorType::Rgb => buffer
+                .chunks_exact(3)
+                .flat_map(|p| [p[0], p[1], p[2], 255])
+                .collect(),
+            png::ColorType::GrayscaleAlpha => buffer
+                .chunks_exact(2)
+                .flat_map(|p| [p[0], p[0], p[0], p[1]])
+                .collect(),
+            png::ColorType::Grayscale => buffer.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+            png::ColorType::Indexed => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Unexpanded indexed PNG",
+                ));
+            }
+        };
+        Ok(Self {
+            width: info.width,
+            height: info.height,
+            pixels,
+        })
+    }
+
+    pub fn save_png(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.write_png(BufWriter::new(File::create(path)?))
+    }
+
+    pub fn load_png(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::read_png(BufReader::new(File::open(path)?))
+    }
+}
+
+/// Compara `image` con la imagen de referencia en `path`.
+///
+/// Solo con `XYLUX_UPDATE_GOLDEN` definida se (re)escribe la referencia con `image`;
+/// si falta, es un error. Si falta o no coincide, la imagen obtenida se guarda junto a
+/// la referencia como `<nombre>.actual.png` para poder inspeccionarla.
+pub fn check_golden(
+    image: &RgbaImage,
+    path: impl AsRef<Path>,
+    tolerance: u8,
+) -> Result<(), String> {
+    let path = path.as_ref();
+    if std::env::var_os(UPDATE_GOLDEN_ENV).is_some() {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|error| error.to_string())?;
+        }
+        return image.save_png(path).map_err(|error| error.to_string());
+    }
+
+    let actual: PathBuf = path.with_extension("actual.png");
+    if !path.exists() {
+        let _ = image.save_png(&actual);
+        return Err(format!(
+            "golden image {} is missing; output written to {} (set {} to accept it)",
+            path.display(),
+            actual.display(),
+            UPDATE_GOLDEN_ENV
+        ));
+    }
+
+    let golden =
+        RgbaImage::load_png(path).map_err(|error| format!("{}: {}", path.display(), error))?;
+    let mismatch = match image.diff(&golden) {
+        Some(diff) if diff.within(tolerance) => return Ok(()),
+        Some(diff) => diff.to_string(),
+        None => format!(
+            "size {}x{} does not match golden {}x{}",
+            image.width, image.height, golden.width, golden.height
+        ),
+    };
+
+    let _ = image.save_png(&actual);
+    Err(format!(
+        "{} does not match golden image ({}); output written to {}",
+        path.display(),
+        mismatch,
+        actual.display()
+    ))
+}
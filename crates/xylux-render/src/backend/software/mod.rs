@@ -0,0 +1,839 @@
+//! Backend de rasterización por software.
+//!
+//! Implementa `RenderBackend` en Rust puro para obtener imágenes de referencia y
+//! ejecutar tests de imagen (golden images) en máquinas sin GPU. Como no ejecuta
+//! SPIR-V, cada pipeline se resuelve por su `label` a un `SoftwareProgram`
+//! registrado con `register_program`; los programas `"triangle"`, `"mesh"` y `"pbr"`
+//! vienen registrados. El estado fijo (culling, profundidad, blending) se toma del
+//! `PipelineDesc`, y solo se admiten listas de triángulos.
+//!
+//! La imagen del swapchain es RGBA8 lineal (`Rgba8Unorm`) y se limpia en cada
+//! render pass junto con su buffer de profundidad. Las texturas `Rgba8Unorm` y
+//! `Rgba8Srgb` con uso `RENDER_TARGET` también pueden ser destino de un pass.
+
+pub mod image;
+pub mod raster;
+
+pub use image::{ImageDiff, RgbaImage, check_golden};
+pub use raster::{
+    FragmentInput, MAX_VARYINGS, RasterState, SoftwareProgram, Varyings, VertexInput, VertexOutput,
+};
+
+use super::{
+    BackendError, BindGroupDesc, BindGroupEntry, BindGroupId, BindingResource, BufferDesc,
+    BufferId, CommandList, FrameInfo, IndexFormat, PipelineDesc, PipelineId, PrimitiveTopology,
+    RenderBackend, RenderCommand, RenderTarget, SampleCount, SamplerDesc, SamplerId, TextureDesc,
+    TextureFormat, TextureId, TextureUsage, null::mip_size,
+};
+use glam::Vec4;
+use raster::Framebuffer;
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Estado de los enlaces durante la ejecución de una lista de comandos.
+#[derive(Default)]
+struct Bindings {
+    pipeline: Option<(Arc<SoftwareProgram>, RasterState)>,
+    vertex_buffers: Vec<Option<(BufferId, u64)>>,
+    index_buffer: Option<(BufferId, u64, IndexFormat)>,
+    /// Bind group enlazado en cada set.
+    groups: Vec<Option<BindGroupId>>,
+    push_constants: Vec<u8>,
+}
+
+/// Backend que rasteriza en la CPU.
+pub struct SoftwareBackend {
+    width: u32,
+    height: u32,
+    next_id: u32,
+    frame: u64,
+    in_frame: bool,
+    /// Imagen de color del swapchain (RGBA8).
+    back_buffer: Vec<u8>,
+    presented: Option<RgbaImage>,
+    programs: HashMap<String, Arc<SoftwareProgram>>,
+    pipelines: HashMap<PipelineId, (Arc<SoftwareProgram>, RasterState)>,
+    buffers: HashMap<BufferId, Vec<u8>>,
+    textures: HashMap<TextureId, (TextureDesc, Vec<Vec<u8>>)>,
+    bind_groups: HashMap<BindGroupId, BindGroupDesc>,
+    samplers: HashMap<SamplerId, SamplerDesc>,
+}
+
+impl SoftwareBackend {
+    /// Crea un backend con un swapchain virtual de `width`x`height`.
+    pub fn new(width: u32, height: u32) -> Self {
+        let mut backend = Self {
+            width,
+            height,
+            next_id: 0,
+            frame: 0,
+            in_frame: false,
+            back_buffer: vec![0; width as usize * height as usize * 4],
+            presented: None,
+            programs: HashMap::new(),
+            pipelines: HashMap::new(),
+            buffers: HashMap::new(),
+            textures: HashMap::new(),
+            bind_groups: HashMap::new(),
+            samplers: HashMap::new(),
+        };
+        backend.register_program("triangle", SoftwareProgram::triangle());
+        backend.register_program("mesh", SoftwareProgram::mesh());
+        backend.register_program("pbr", SoftwareProgram::pbr());
+        backend
+    }
+
+    fn next_id(&mut self) -> u32 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    /// Asocia un programa a los pipelines cuyo `PipelineDesc::label` sea `label`.
+    ///
+    /// Solo afecta a los pipelines creados después.
+    pub fn register_program(&mut self, label: impl Into<String>, program: SoftwareProgram) {
+        self.programs.insert(label.into(), Arc::new(program));
+    }
+
+    /// Contenido actual del swapchain (el frame en curso si no se ha presentado).
+    pub fn back_buffer(&self) -> RgbaImage {
+        RgbaImage::from_raw(self.width, self.height, self.back_buffer.clone()).unwrap()
+    }
+
+    /// Último frame presentado.
+    pub fn presented_image(&self) -> Option<&RgbaImage> {
+        self.presented.as_ref()
+    }
+
+    /// Nivel 0 de una textura RGBA8 como imagen.
+    pub fn texture_image(&self, texture: TextureId) -> Option<RgbaImage> {
+        let (desc, mips) = self.textures.get(&texture)?;
+        if desc.format.bytes_per_pixel() != 4 || desc.format.is_depth() {
+            return None;
+        }
+        RgbaImage::from_raw(desc.width, desc.height, mips[0].clone())
+    }
+
+    /// Comprueba que el recurso de un binding existe. Los programas solo leen
+    /// uniforms; las texturas y samplers se aceptan para poder usar los mismos
+    /// bind groups que en Vulkan.
+    fn check_binding(&self, entry: &BindGroupEntry) -> Result<(), BackendError> {
+        match entry.resource {
+            BindingResource::Buffer { buffer, .. } => self.buffer(buffer).map(|_| ()),
+            BindingResource::Texture(texture) | BindingResource::ArrayTexture { texture, .. } => {
+                if self.textures.contains_key(&texture) {
+                    Ok(())
+                } else {
+                    Err(BackendError::InvalidHandle(format!("{:?}", texture)))
+                }
+            }
+            BindingResource::Sampler(sampler) => {
+                if self.samplers.contains_key(&sampler) {
+                    Ok(())
+                } else {
+                    Err(BackendError::InvalidHandle(format!("{:?}", sampler)))
+                }
+            }
+        }
+    }
+
+    /// Programa y estado de un pipeline. Los shaders de `desc` no se ejecutan: el
+    /// programa es el registrado con el nombre del pipeline.
+    fn pipeline(
+        &self,
+        desc: &PipelineDesc,
+    ) -> Result<(Arc<SoftwareProgram>, RasterState), BackendError> {
+        desc.validate()?;
+        let program = self.programs.get(&desc.label).cloned().ok_or_else(|| {
+            BackendError::Unsupported(format!(
+                "no software program registered for pipeline '{}'",
+                desc.label
+            ))
+        })?;
+        if desc.topology != PrimitiveTopology::TriangleList {
+            return Err(BackendError::Unsupported(format!(
+                "{:?} topology in the software rasterizer",
+                desc.topology
+            )));
+        }
+        Ok((program, RasterState::from(desc)))
+    }
+
+    /// Saca el destino de color para escribir en él; se devuelve con `restore_target`.
+    fn take_target(&mut self, target: RenderTarget) -> Result<Framebuffer, BackendError> {
+        match target {
+            RenderTarget::Swapchain => Ok(Framebuffer::new(
+                self.width,
+                self.height,
+                std::mem::take(&mut self.back_buffer),
+                false,
+            )),
+            RenderTarget::Texture(id) => {
+                let (desc, mips) = self
+                    .textures
+                    .get_mut(&id)
+                    .ok_or_else(|| BackendError::InvalidHandle(format!("{:?}", id)))?;
+                if !desc.usage.contains(TextureUsage::RENDER_TARGET) {
+                    return Err(BackendError::InvalidCommands(format!(
+                        "{:?} is not a render target",
+                        id
+                    )));
+                }
+                if !matches!(
+                    desc.format,
+                    TextureFormat::Rgba8Unorm | TextureFormat::Rgba8Srgb
+                ) {
+                    return Err(BackendError::Unsupported(format!(
+                        "rendering into {:?}",
+                        desc.format
+                    )));
+                }
+                Ok(Framebuffer::new(
+                    desc.width,
+                    desc.height,
+                    std::mem::take(&mut mips[0]),
+                    desc.format.is_srgb(),
+                ))
+            }
+        }
+    }
+
+    fn restore_target(&mut self, target: RenderTarget, framebuffer: Framebuffer) {
+        match target {
+            RenderTarget::Swapchain => self.back_buffer = framebuffer.color,
+            RenderTarget::Texture(id) => {
+                if let Some((_, mips)) = self.textures.get_mut(&id) {
+                    mips[0] = framebuffer.color;
+                }
+            }
+        }
+    }
+
+    fn buffer(&self, buffer: BufferId) -> Result<&[u8], BackendError> {
+        self.buffers
+            .get(&buffer)
+            .map(Vec::as_slice)
+            .ok_or_else(|| BackendError::InvalidHandle(format!("{:?}", buffer)))
+    }
+
+    /// Ejecuta una lista ya validada.
+    fn execute(&mut self, commands: &CommandList) -> Result<(), BackendError> {
+        let mut bindings = Bindings::default();
+        let mut pass: Option<(RenderTarget, Framebuffer)> = None;
+
+        for command in commands.commands() {
+            if let Err(error) = self.execute_command(command, &mut bindings, &mut pass) {
+                // Devolver el destino para no perder su contenido.
+                if let Some((target, framebuffer)) = pass.take() {
+                    self.restore_target(target, framebuffer);
+                }
+                return Err(error);
+            }
+        }
+        Ok(())
+    }
+
+    fn execute_command(
+        &mut self,
+        command: &RenderCommand,
+        bindings: &mut Bindings,
+        pass: &mut Option<(RenderTarget, Framebuffer)>,
+    ) -> Result<(), BackendError> {
+        match command {
+            RenderCommand::BeginRenderPass(desc) => {
+                let mut framebuffer = self.take_target(desc.target)?;
+                if let Some(color) = desc.clear_color {
+                    framebuffer.clear(Vec4::from_array(color));
+                }
+                *pass = Some((desc.target, framebuffer));
+            }
+            RenderCommand::EndRenderPass => {
+                if let Some((target, framebuffer)) = pass.take() {
+                    self.restore_target(target, framebuffer);
+                }
+            }
+            RenderCommand::BindPipeline(id) => {
+                let program = self
+                    .pipelines
+                    .get(id)
+                    .ok_or_else(|| BackendError::InvalidHandle(format!("{:?}", id)))?;
+                bindings.pipeline = Some(program.clone());
+            }
+            RenderCommand::BindVertexBuffer {
+                slot,
+                buffer,
+                offset,
+            } => {
+                self.buffer(*buffer)?;
+                let slot = *slot as usize;
+                if bindings.vertex_buffers.len() <= slot {
+                    bindings.vertex_buffers.resize(slot + 1, None);
+                }
+                bindings.vertex_buffers[slot] = Some((*buffer, *offset));
+            }
+            RenderCommand::BindIndexBuffer {
+                buffer,
+                offset,
+                format,
+            } => {
+                self.buffer(*buffer)?;
+                bindings.index_buffer = Some((*buffer, *offset, *format));
+            }
+            RenderCommand::BindGroup { index, group } => {
+                if !self.bind_groups.contains_key(group) {
+                    return Err(BackendError::InvalidHandle(format!("{:?}", group)));
+                }
+                let index = *index as usize;
+                if bindings.groups.len() <= index {
+                    bindings.groups.resize(index + 1, None);
+                }
+                bindings.groups[index] = Some(*group);
+            }
+            RenderCommand::SetViewport(viewport) => {
+                if let Some((_, framebuffer)) = pass.as_mut() {
+                    framebuffer.viewport = *viewport;
+                }
+            }
+            RenderCommand::ClearColor(color) => {
+                let (_, framebuffer) = pass.as_mut().expect("validated command list");
+                framebuffer.clear_viewport(Vec4::from_array(*color));
+            }
+            RenderCommand::PushConstants { offset, data } => {
+                let end = *offset as usize + data.len();
+                if bindings.push_constants.len() < end {
+                    bindings.push_constants.resize(end, 0);
+                }
+                bindings.push_constants[*offset as usize..end].copy_from_slice(data);
+            }
+            RenderCommand::Draw {
+                vertices,
+                instances,
+            } => {
+                let (_, framebuffer) = pass.as_mut().expect("validated command list");
+                self.draw(framebuffer, bindings, vertices.clone(), instances.clone())?;
+            }
+            RenderCommand::DrawIndexed {
+                indices,
+                base_vertex,
+                instances,
+            } => {
+                let (buffer, offset, format) = bindings.index_buffer.ok_or_else(|| {
+                    BackendError::InvalidCommands("indexed draw without index buffer".into())
+                })?;
+                let data = &self.buffer(buffer)?[offset as usize..];
+                let index_size = match format {
+                    IndexFormat::U16 => 2,
+                    IndexFormat::U32 => 4,
+                };
+                let vertices = indices
+                    .clone()
+                    .map(|i| {
+                        let start = i as usize * index_size;
+                        let bytes = data.get(start..start + index_size).ok_or_else(|| {
+                            BackendError::InvalidCommands(format!("index {} out of bounds", i))
+                        })?;
+                        let index = match format {
+                            IndexFormat::U16 => u16::from_le_bytes([bytes[0], bytes[1]]) as i64,
+                            IndexFormat::U32 => {
+                                u32::from_le_bytes(bytes.try_into().unwrap()) as i64
+                            }
+                        };
+                        Ok((index + *base_vertex as i64) as u32)
+                    })
+                    .collect::<Result<Vec<u32>, BackendError>>()?;
+                let (_, framebuffer) = pass.as_mut().expect("validated command list");
+                self.draw(framebuffer, bindings, vertices, instances.clone())?;
+            }
+            // Los comandos se ejecutan en orden en la CPU: no hay nada que sincronizar.
+            RenderCommand::Barrier(_) => {}
+        }
+        Ok(())
+    }
+
+    /// Ejecuta el vertex shader sobre `vertices` (una lista de triángulos) y rasteriza.
+    fn draw(
+        &self,
+        framebuffer: &mut Framebuffer,
+        bindings: &Bindings,
+        vertices: impl IntoIterator<Item = u32> + Clone,
+        instances: std::ops::Range<u32>,
+    ) -> Result<(), BackendError> {
+        let (program, state) = bindings.pipeline.as_ref().expect("validated command list");
+        let vertex_buffers = bindings
+            .vertex_buffers
+            .iter()
+            .map(|binding| match binding {
+                Some((buffer, offset)) => {
+                    Ok(self.buffer(*buffer)?.get(*offset as usize..).unwrap_or(&[]))
+                }
+                None => Ok(&[][..]),
+            })
+            .collect::<Result<Vec<&[u8]>, BackendError>>()?;
+        let mut uniforms = Vec::new();
+        for (set, group) in bindings.groups.iter().enumerate() {
+            let Some(desc) = group.and_then(|group| self.bind_groups.get(&group)) else {
+                continue;
+            };
+            for entry in &desc.entries {
+                let BindingResource::Buffer {
+                    buffer,
+                    offset,
+                    size,
+                } = entry.resource
+                else {
+                    continue;
+                };
+                let data = self
+                    .buffer(buffer)?
+                    .get(offset as usize..(offset + size) as usize)
+                    .unwrap_or(&[]);
+                uniforms.push((set as u32, entry.binding, data));
+            }
+        }
+
+        for instance_index in instances {
+            let outputs: Vec<VertexOutput> = vertices
+                .clone()
+                .into_iter()
+                .map(|vertex_index| {
+                    (program.vertex)(&VertexInput {
+                        vertex_index,
+                        instance_index,
+                        vertex_buffers: &vertex_buffers,
+                        push_constants: &bindings.push_constants,
+                        uniforms: &uniforms,
+                    })
+                })
+                .collect();
+            for triangle in outputs.chunks_exact(3) {
+                raster::draw_triangle(
+                    framebuffer,
+                    program,
+                    state,
+                    [triangle[0], triangle[1], triangle[2]],
+                    &bindings.push_constants,
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+impl RenderBackend for SoftwareBackend {
+    fn name(&self) -> &'static str {
+        "software"
+    }
+
+    fn create_buffer(&mut self, desc: &BufferDesc) -> Result<BufferId, BackendError> {
+        let id = BufferId(self.next_id());
+        self.buffers.insert(id, vec![0; desc.size as usize]);
+        Ok(id)
+    }
+
+    fn write_buffer(
+        &mut self,
+        buffer: BufferId,
+        offset: u64,
+        data: &[u8],
+    ) -> Result<(), BackendError> {
+        let contents = self
+            .buffers
+            .get_mut(&buffer)
+            .ok_or_else(|| BackendError::InvalidHandle(format!("{:?}", buffer)))?;
+        let start = offset as usize;
+        let target = contents.get_mut(start..start + data.len()).ok_or_else(|| {
+            BackendError::InvalidCommands(format!(
+                "write of {} bytes at {} overflows {:?}",
+                data.len(),
+                offset,
+                buffer
+            ))
+        })?;
+        target.copy_from_slice(data);
+        Ok(())
+    }
+
+    fn destroy_buffer(&mut self, buffer: BufferId) {
+        self.buffers.remove(&buffer);
+    }
+
+    fn create_texture(&mut self, desc: &TextureDesc) -> Result<TextureId, BackendError> {
+        let id = TextureId(self.next_id());
+        let mips = (0..desc.mip_levels.max(1))
+            .map(|level| vec![0; mip_size(desc, level)])
+            .collect();
+        self.textures.insert(id, (desc.clone(), mips));
+        Ok(id)
+    }
+
+    fn write_texture(
+        &mut self,
+        texture: TextureId,
+        mip_level: u32,
+        data: &[u8],
+    ) -> Result<(), BackendError> {
+        let (desc, mips) = self
+            .textures
+            .get_mut(&texture)
+            .ok_or_else(|| BackendError::InvalidHandle(format!("{:?}", texture)))?;
+        let expected = mip_size(desc, mip_level);
+        match mips.get_mut(mip_level as usize) {
+            Some(mip) if data.len() == expected => {
+                mip.copy_from_slice(data);
+                Ok(())
+            }
+            _ => Err(BackendError::InvalidCommands(format!(
+                "mip {} of {:?} expects {} bytes, got {}",
+                mip_level,
+                texture,
+                expected,
+                data.len()
+            ))),
+        }
+    }
+
+    fn destroy_texture(&mut self, texture: TextureId) {
+        self.textures.remove(&texture);
+    }
+
+    fn create_pipeline(&mut self, desc: &PipelineDesc) -> Result<PipelineId, BackendError> {
+        let pipeline = self.pipeline(desc)?;
+        let id = PipelineId(self.next_id());
+        self.pipelines.insert(id, pipeline);
+        Ok(id)
+    }
+
+    fn destroy_pipeline(&mut self, pipeline: PipelineId) {
+        self.pipelines.remove(&pipeline);
+    }
+
+    fn replace_pipeline(
+        &mut self,
+        pipeline: PipelineId,
+        desc: &PipelineDesc,
+    ) -> Result<(), BackendError> {
+        if !self.pipelines.contains_key(&pipeline) {
+            return Err(BackendError::InvalidHandle(format!("{:?}", pipeline)));
+        }
+        let replacement = self.pipeline(desc)?;
+        self.pipelines.insert(pipeline, replacement);
+        Ok(())
+    }
+
+    fn create_bind_group(&mut self, desc: &BindGroupDesc) -> Result<BindGroupId, BackendError> {
+        desc.validate()?;
+        for entry in &desc.entries {
+            self.check_binding(entry)?;
+        }
+        let id = BindGroupId(self.next_id());
+        self.bind_groups.insert(id, desc.clone());
+        Ok(id)
+    }
+
+    fn update_bind_group(
+        &mut self,
+        group: BindGroupId,
+        entries: &[BindGroupEntry],
+    ) -> Result<(), BackendError> {
+        let desc = self
+            .bind_groups
+            .get(&group)
+            .ok_or_else(|| BackendError::InvalidHandle(format!("{:?}", group)))?;
+        desc.layout.check_update(entries)?;
+        for entry in entries {
+            self.check_binding(entry)?;
+        }
+        self.bind_groups
+            .get_mut(&group)
+            .expect("bind group was checked")
+            .update(entries);
+        Ok(())
+    }
+
+    fn destroy_bind_group(&mut self, group: BindGroupId) {
+        self.bind_groups.remove(&group);
+    }
+
+    fn create_sampler(&mut self, desc: &SamplerDesc) -> Result<SamplerId, BackendError> {
+        desc.validate()?;
+        let id = SamplerId(self.next_id());
+        self.samplers.insert(id, *desc);
+        Ok(id)
+    }
+
+    fn destroy_sampler(&mut self, sampler: SamplerId) {
+        self.samplers.remove(&sampler);
+    }
+
+    fn begin_frame(&mut self) -> Result<FrameInfo, BackendError> {
+        let info = FrameInfo {
+            frame: self.frame,
+            width: self.width,
+            height: self.height,
+        };
+        self.frame += 1;
+        self.in_frame = true;
+        Ok(info)
+    }
+
+    fn submit(&mut self, commands: CommandList) -> Result<(), BackendError> {
+        if !self.in_frame {
+            return Err(BackendError::InvalidCommands(
+                "submit outside of a frame".into(),
+            ));
+        }
+        commands.validate().map_err(BackendError::InvalidCommands)?;
+        self.execute(&commands)
+    }
+
+    fn present(&mut self) -> Result<(), BackendError> {
+        if !std::mem::take(&mut self.in_frame) {
+            return Err(BackendError::InvalidCommands(
+                "present outside of a frame".into(),
+            ));
+        }
+        self.presented = Some(self.back_buffer());
+        Ok(())
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        self.back_buffer = vec![0; width as usize * height as usize * 4];
+    }
+
+    fn surface_size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// El rasterizador no hace multisampling.
+    fn set_sample_count(&mut self, _requested: SampleCount) -> SampleCount {
+        SampleCount::X1
+    }
+
+    fn sample_count(&self) -> SampleCount {
+        SampleCount::X1
+    }
+
+    fn wait_idle(&mut self) {}
+
+    fn cleanup(&mut self) {
+        self.buffers.clear();
+        self.textures.clear();
+        self.pipelines.clear();
+        self.bind_groups.clear();
+        self.samplers.clear();
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::allocator::MemoryLocation;
+    use crate::backend::{
+        BufferUsage, CullMode, RenderPassDesc, VertexAttribute, VertexFormat, VertexLayout,
+        VertexStepMode,
+    };
+    use crate::material::Material;
+    use crate::mesh::{Mesh, MeshRenderer};
+    use crate::renderer::Renderer;
+    use glam::Vec3;
+    use std::path::PathBuf;
+    use xylux_ecs::Transform;
+    use xylux_ecs::World;
+
+    fn golden(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/golden")
+            .join(name)
+    }
+
+    /// Programa que lee `[x, y, z, w, r, g, b]` por vértice del slot 0.
+    fn colored_program() -> SoftwareProgram {
+        SoftwareProgram::new(
+            |input| {
+                let offset = input.vertex_index as usize * 28;
+                let [x, y, z, w] = input.read_f32s::<4>(0, offset);
+                VertexOutput::new(Vec4::new(x, y, z, w))
+                    .with_varyings(&input.read_f32s::<3>(0, offset + 16))
+            },
+            |fragment| {
+                let [r, g, b, ..] = fragment.varyings;
+                Some(Vec4::new(r, g, b, 1.0))
+            },
+        )
+    }
+
+    fn render(backend: &mut SoftwareBackend, vertices: &[[f32; 7]]) -> RgbaImage {
+        backend.register_program("colored", colored_program());
+        let pipeline = backend
+            .create_pipeline(
+                &PipelineDesc::builder("colored")
+                    .vertex_shader(&PipelineDesc::triangle().vertex_shader)
+                    .fragment_shader(&PipelineDesc::triangle().fragment_shader)
+                    .vertex_layout(VertexLayout {
+                        stride: 28,
+                        step_mode: VertexStepMode::Vertex,
+                        attributes: vec![
+                            VertexAttribute {
+                                location: 0,
+                                format: VertexFormat::Float32x4,
+                                offset: 0,
+                            },
+                            VertexAttribute {
+                                location: 1,
+                                format: VertexFormat::Float32x3,
+                                offset: 16,
+                            },
+                        ],
+                    })
+                    .cull_mode(CullMode::None)
+                    .depth(true, true)
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap();
+        let bytes: Vec<u8> = vertices
+            .iter()
+            .flatten()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        let buffer = backend
+            .create_buffer(&BufferDesc {
+                size: bytes.len() as u64,
+                usage: BufferUsage::VERTEX,
+                location: MemoryLocation::CpuToGpu,
+            })
+            .unwrap();
+        backend.write_buffer(buffer, 0, &bytes).unwrap();
+
+        backend.begin_frame().unwrap();
+        let mut commands = CommandList::new();
+        commands.begin_render_pass(RenderPassDesc::swapchain([0.0, 0.0, 0.0, 1.0]));
+        commands.bind_pipeline(pipeline);
+        commands.bind_vertex_buffer(0, buffer, 0);
+        commands.draw(0..vertices.len() as u32, 0..1);
+        commands.end_render_pass();
+        backend.submit(commands).unwrap();
+        backend.present().unwrap();
+        backend.presented_image().unwrap().clone()
+    }
+
+    #[test]
+    fn test_hello_triangle_matches_golden() {
+        let mut renderer = Renderer::with_backend(Box::new(SoftwareBackend::new(64, 48)));
+        let mut world = World::new(16);
+        crate::renderer::renderer::tests::spawn_hello_triangle(&mut renderer, &mut world);
+        renderer.render(&mut world);
+
+        let image = renderer
+            .backend_as::<SoftwareBackend>()
+            .unwrap()
+            .presented_image()
+            .unwrap();
+        assert_eq!(image.pixel(32, 24), [0, 255, 0, 255]);
+        assert_eq!(image.pixel(2, 2), [0, 0, 0, 255]);
+        check_golden(image, golden("hello_triangle.png"), 0).unwrap();
+    }
+
+    #[test]
+    fn test_depth_test_keeps_nearest_triangle() {
+        let mut backend = SoftwareBackend::new(16, 16);
+        let triangle = |z: f32, color: [f32; 3]| {
+            [[-1.0, -1.0], [3.0, -1.0], [-1.0, 3.0]]
+                .map(|[x, y]| [x, y, z, 1.0, color[0], color[1], color[2]])
+        };
+        let vertices: Vec<[f32; 7]> = [
+            triangle(0.7, [0.0, 1.0, 0.0]),
+            triangle(0.3, [1.0, 0.0, 0.0]),
+            triangle(0.5, [0.0, 0.0, 1.0]),
+        ]
+        .concat();
+        let image = render(&mut backend, &vertices);
+        assert!(
+            image
+                .pixels
+                .chunks_exact(4)
+                .all(|pixel| pixel == [255, 0, 0, 255])
+        );
+    }
+
+    #[test]
+    fn test_perspective_correct_interpolation() {
+        // Triángulo en píxeles (0.5, 0.5), (6.5, 0.5), (0.5, 6.5) de un destino de 8x8,
+        // con el primer vértice a w = 1 y los otros dos a w = 2. En el centroide,
+        // píxel (2, 2), la interpolación afín daría 1/3 y la correcta 1/2.
+        let mut backend = SoftwareBackend::new(8, 8);
+        let vertex = |x: f32, y: f32, w: f32, value: f32| [x * w, y * w, 0.0, w, value, 0.0, 0.0];
+        let image = render(
+            &mut backend,
+            &[
+                vertex(-0.875, -0.875, 1.0, 1.0),
+                vertex(0.625, -0.875, 2.0, 0.0),
+                vertex(-0.875, 0.625, 2.0, 0.0),
+            ],
+        );
+        assert_eq!(image.pixel(2, 2)[0], 128);
+        check_golden(&image, golden("perspective_interpolation.png"), 0).unwrap();
+    }
+
+    #[test]
+    fn test_missing_golden_fails_without_update() {
+        if std::env::var_os(image::UPDATE_GOLDEN_ENV).is_some() {
+            return;
+        }
+        let dir = std::env::temp_dir().join(format!("xylux_golden_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("missing.png");
+        let error = check_golden(&RgbaImage::new(2, 2), &path, 0).unwrap_err();
+        assert!(error.contains("missing"), "{}", error);
+        assert!(!path.exists());
+        assert!(dir.join("missing.actual.png").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_cube_draws_faces_towards_viewer() {
+        let mut renderer = Renderer::with_backend(Box::new(SoftwareBackend::new(32, 32)));
+        let mut world = World::new(16);
+        world.register_component::<Transform>();
+        world.register_component::<MeshRenderer>();
+
+        // Cada cara coloreada según su normal: la de -Z, hacia la cámara, sin azul.
+        let mut cube = Mesh::cube(0.5, 1);
+        cube.colors = cube
+            .normals
+            .iter()
+            .map(|n| (*n * 0.5 + 0.5).extend(1.0))
+            .collect();
+        let mesh = renderer.add_mesh(cube).unwrap();
+        let material = renderer.add_material(Material::default());
+        let entity = world.spawn_entity();
+        world.insert(
+            entity,
+            Transform {
+                position: Vec3::new(0.0, 0.0, 0.5),
+                ..Default::default()
+            },
+        );
+        world.insert(entity, MeshRenderer::new(mesh, material));
+
+        renderer.render(&mut world);
+        let image = renderer
+            .backend_as::<SoftwareBackend>()
+            .unwrap()
+            .presented_image()
+            .unwrap();
+        let [r, g, b, _] = image.pixel(16, 16);
+        assert!(r > 0 && g > 0 && b == 0, "pixel {:?}", [r, g, b]);
+    }
+}
@@ -0,0 +1,593 @@
+//! Rasterización de triángulos: recorte, setup, test de profundidad e
+//! interpolación con corrección de perspectiva.
+//!
+//! Sigue las convenciones de Vulkan: profundidad en clip space entre `0` y `w`,
+//! `y` de NDC hacia abajo y centros de píxel en `+0.5`. Los bordes compartidos se
+//! resuelven con la regla top-left, así que cada píxel se dibuja una sola vez.
+
+use crate::backend::{BlendMode, CullMode, FrontFace, PipelineDesc, Viewport};
+use crate::camera::CameraUniform;
+use crate::mesh::Mesh;
+use glam::{Mat4, Vec2, Vec3, Vec4};
+
+/// Número máximo de varyings `f32` que un vertex shader pasa al fragment shader.
+pub const MAX_VARYINGS: usize = 16;
+
+/// Valores interpolados entre vértices.
+pub type Varyings = [f32; MAX_VARYINGS];
+
+/// Datos que recibe el vertex shader.
+#[derive(Clone, Copy, Debug)]
+pub struct VertexInput<'a> {
+    pub vertex_index: u32,
+    pub instance_index: u32,
+    /// Contenido de los vertex buffers enlazados, por slot y desde su offset.
+    /// Los slots sin buffer son slices vacíos.
+    pub vertex_buffers: &'a [&'a [u8]],
+    pub push_constants: &'a [u8],
+    /// Uniform buffers de los bind groups enlazados como `(set, binding, datos)`,
+    /// con los datos ya recortados a su rango.
+    pub uniforms: &'a [(u32, u32, &'a [u8])],
+}
+
+impl VertexInput<'_> {
+    /// Lee `N` floats little-endian del buffer de `slot` a partir de `offset` bytes.
+    ///
+    /// Devuelve ceros si la lectura se sale del buffer, como un acceso robusto.
+    pub fn read_f32s<const N: usize>(&self, slot: u32, offset: usize) -> [f32; N] {
+        read_f32s(
+            self.vertex_buffers
+                .get(slot as usize)
+                .copied()
+                .unwrap_or(&[]),
+            offset,
+        )
+    }
+
+    /// Lee `N` floats del uniform buffer enlazado en `set` y `binding`; devuelve
+    /// ceros si no hay ninguno o la lectura se sale de su rango.
+    pub fn read_uniform_f32s<const N: usize>(
+        &self,
+        set: u32,
+        binding: u32,
+        offset: usize,
+    ) -> [f32; N] {
+        let bytes = self
+            .uniforms
+            .iter()
+            .find(|(s, b, _)| (*s, *b) == (set, binding))
+            .map(|(_, _, bytes)| *bytes)
+            .unwrap_or(&[]);
+        read_f32s(bytes, offset)
+    }
+}
+
+/// Resultado del vertex shader.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VertexOutput {
+    /// Posición en clip space.
+    pub position: Vec4,
+    pub varyings: Varyings,
+}
+
+impl VertexOutput {
+    pub fn new(position: Vec4) -> Self {
+        Self {
+            position,
+            varyings: [0.0; MAX_VARYINGS],
+        }
+    }
+
+    /// Copia `values` en los primeros varyings.
+    pub fn with_varyings(mut self, values: &[f32]) -> Self {
+        self.varyings[..values.len()].copy_from_slice(values);
+        self
+    }
+
+    fn lerp(&self, other: &VertexOutput, t: f32) -> VertexOutput {
+        let mut varyings = self.varyings;
+        for (value, target) in varyings.iter_mut().zip(other.varyings) {
+            *value += (target - *value) * t;
+        }
+        VertexOutput {
+            position: self.position.lerp(other.position, t),
+            varyings,
+        }
+    }
+}
+
+/// Datos que recibe el fragment shader.
+#[derive(Clone, Copy, Debug)]
+pub struct FragmentInput<'a> {
+    /// Centro del píxel en coordenadas de ventana.
+    pub position: Vec2,
+    /// Profundidad en `[0, 1]`.
+    pub depth: f32,
+    pub front_facing: bool,
+    /// Varyings interpolados con corrección de perspectiva.
+    pub varyings: Varyings,
+    pub push_constants: &'a [u8],
+}
+
+/// Estado fijo del pipeline que afecta a la rasterización, sacado de su `PipelineDesc`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RasterState {
+    pub cull: CullMode,
+    pub front_face: FrontFace,
+    pub depth_test: bool,
+    pub depth_write: bool,
+    pub blend: BlendMode,
+}
+
+impl From<&PipelineDesc> for RasterState {
+    fn from(desc: &PipelineDesc) -> Self {
+        Self {
+            cull: desc.cull_mode,
+            front_face: desc.front_face,
+            depth_test: desc.depth_test,
+            depth_write: desc.depth_write,
+            blend: desc.blend,
+        }
+    }
+}
+
+pub type VertexShader = Box<dyn Fn(&VertexInput) -> VertexOutput + Send + Sync>;
+/// Devuelve el color lineal del fragmento, o `None` para descartarlo.
+pub type FragmentShader = Box<dyn Fn(&FragmentInput) -> Option<Vec4> + Send + Sync>;
+
+/// Programa del rasterizador: equivalente por software a los shaders de un
+/// pipeline gráfico. El resto del estado (culling, profundidad, blending) viene
+/// del `PipelineDesc`.
+pub struct SoftwareProgram {
+    pub vertex: VertexShader,
+    pub fragment: FragmentShader,
+}
+
+impl SoftwareProgram {
+    pub fn new(
+        vertex: impl Fn(&VertexInput) -> VertexOutput + Send + Sync + 'static,
+        fragment: impl Fn(&FragmentInput) -> Option<Vec4> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            vertex: Box::new(vertex),
+            fragment: Box::new(fragment),
+        }
+    }
+
+    /// Equivalente a `shaders/shader.vert` y `shaders/shader.frag`: un triángulo
+    /// verde generado a partir de `vertex_index`.
+    pub fn triangle() -> Self {
+        const POSITIONS: [[f32; 2]; 3] = [[0.0, -0.5], [0.5, 0.5], [-0.5, 0.5]];
+        Self::new(
+            |input| {
+                let [x, y] = POSITIONS[input.vertex_index as usize % 3];
+                VertexOutput::new(Vec4::new(x, y, 0.0, 1.0))
+            },
+            |_| Some(Vec4::new(0.0, 1.0, 0.0, 1.0)),
+        )
+    }
+
+    /// Equivalente a `shaders/mesh.vert` y `shaders/mesh.frag` (`PipelineDesc::mesh`):
+    /// vértices de `Mesh` en el slot 0, matriz del modelo por instancia en el slot 1,
+    /// `CameraUniform` en el set 0 y color del material en las push constants,
+    /// multiplicado por el color del vértice.
+    pub fn mesh() -> Self {
+        Self::new(
+            |input| {
+                let vertex = input.vertex_index as usize * Mesh::VERTEX_STRIDE as usize;
+                let [x, y, z] = input.read_f32s::<3>(0, vertex);
+                let color = input.read_f32s::<4>(0, vertex + 48);
+                let model = Mat4::from_cols_array(
+                    &input.read_f32s::<16>(1, input.instance_index as usize * 64),
+                );
+                let view_projection = Mat4::from_cols_array(&input.read_uniform_f32s::<16>(
+                    0,
+                    0,
+                    CameraUniform::VIEW_PROJECTION_OFFSET,
+                ));
+                VertexOutput::new(view_projection * model * Vec4::new(x, y, z, 1.0))
+                    .with_varyings(&color)
+            },
+            |fragment| {
+                let base_color = Vec4::from_array(read_f32s::<4>(fragment.push_constants, 0));
+                Some(base_color * Vec4::from_slice(&fragment.varyings[..4]))
+            },
+        )
+    }
+}
+
+impl SoftwareProgram {
+    /// Equivalente a `shaders/pbr.vert` y `shaders/pbr.frag` (`PipelineDesc::pbr`)
+    /// sin texturas: solo usa los factores del uniform del material (set 1).
+    pub fn pbr() -> Self {
+        const CAMERA_POSITION_OFFSET: usize = 192;
+        Self::new(
+            |input| {
+                let vertex = input.vertex_index as usize * Mesh::VERTEX_STRIDE as usize;
+                let [x, y, z] = input.read_f32s::<3>(0, vertex);
+                let normal = Vec3::from_array(input.read_f32s::<3>(0, vertex + 12));
+                let color = Vec4::from_array(input.read_f32s::<4>(0, vertex + 48));
+                let model = Mat4::from_cols_array(
+                    &input.read_f32s::<16>(1, input.instance_index as usize * 64),
+                );
+                let view_projection = Mat4::from_cols_array(&input.read_uniform_f32s::<16>(
+                    0,
+                    0,
+                    CameraUniform::VIEW_PROJECTION_OFFSET,
+                ));
+                let camera =
+                    Vec3::from_array(input.read_uniform_f32s::<3>(0, 0, CAMERA_POSITION_OFFSET));
+                let material = input.read_uniform_f32s::<16>(1, 0, 0);
+                let position = model * Vec4::new(x, y, z, 1.0);
+                let base_color = Vec4::from_slice(&material[..4]) * color;
+                // Varyings: normal, vector hacia la cámara, color base, metallic,
+                // roughness, emisivo y el corte del alfa (0 si no hay máscara).
+                let cutoff = if material[13] == 1.0 {
+                    material[12]
+                } else {
+                    0.0
+                };
+                let mut varyings = [0.0; MAX_VARYINGS];
+                varyings[..3].copy_from_slice(&model.transform_vector3(normal).to_array());
+                varyings[3..6].copy_from_slice(&(camera - position.truncate()).to_array());
+                varyings[6..10].copy_from_slice(&base_color.to_array());
+                varyings[10..12].copy_from_slice(&material[8..10]);
+                varyings[12..15].copy_from_slice(&material[4..7]);
+                varyings[15] = cutoff;
+                VertexOutput::new(view_projection * position).with_varyings(&varyings)
+            },
+            |fragment| {
+                let varyings = &fragment.varyings;
+                let base_color = Vec4::from_slice(&varyings[6..10]);
+                if base_color.w < varyings[15] {
+                    return None;
+                }
+                let mut normal = Vec3::from_slice(&varyings[..3]).normalize_or_zero();
+                if !fragment.front_facing {
+                    normal = -normal;
+                }
+                let view = Vec3::from_slice(&varyings[3..6]).normalize_or_zero();
+                let color = shade_pbr(
+                    normal,
+                    view,
+                    base_color.truncate(),
+                    varyings[10].clamp(0.0, 1.0),
+                    varyings[11].clamp(0.04, 1.0),
+                ) + Vec3::from_slice(&varyings[12..15]);
+                Some(color.extend(base_color.w))
+            },
+        )
+    }
+}
+
+/// Luz direccional fija de `shaders/pbr.frag`, hacia la luz.
+const SUN_DIRECTION: Vec3 = Vec3::new(0.371_390_7, 0.928_476_7, 0.0);
+const SUN_COLOR: f32 = 3.0;
+const AMBIENT_COLOR: f32 = 0.03;
+
+/// BRDF de Cook-Torrance de `shaders/pbr.frag` con la luz fija y el ambiente.
+fn shade_pbr(normal: Vec3, view: Vec3, base_color: Vec3, metallic: f32, roughness: f32) -> Vec3 {
+    use std::f32::consts::PI;
+    let halfway = (view + SUN_DIRECTION).normalize_or_zero();
+    let n_dot_l = normal.dot(SUN_DIRECTION).max(0.0);
+    let n_dot_v = normal.dot(view).max(1e-4);
+    let n_dot_h = normal.dot(halfway).max(0.0);
+    let v_dot_h = view.dot(halfway).max(0.0);
+
+    let a2 = roughness.powi(4);
+    let d = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+    let distribution = a2 / (PI * d * d);
+    let k = (roughness + 1.0).powi(2) / 8.0;
+    let geometry = n_dot_v / (n_dot_v * (1.0 - k) + k) * n_dot_l / (n_dot_l * (1.0 - k) + k);
+    let f0 = Vec3::splat(0.04).lerp(base_color, metallic);
+    let fresnel = f0 + (Vec3::ONE - f0) * (1.0 - v_dot_h).powi(5);
+    let specular = distribution * geometry * fresnel / (4.0 * n_dot_v * n_dot_l.max(1e-4));
+    let diffuse = (Vec3::ONE - fresnel) * (1.0 - metallic) * base_color / PI;
+    (diffuse + specular) * SUN_COLOR * n_dot_l + AMBIENT_COLOR * base_color
+}
+
+/// Destino de color RGBA8 y profundidad de un render pass.
+pub(crate) struct Framebuffer {
+    pub width: u32,
+    pub height: u32,
+    pub color: Vec<u8>,
+    pub depth: Vec<f32>,
+    /// El color se guarda codificado en sRGB.
+    pub srgb: bool,
+    /// Rectángulo de dibujo actual; también hace de scissor.
+    pub viewport: Viewport,
+}
+
+impl Framebuffer {
+    pub fn new(width: u32, height: u32, color: Vec<u8>, srgb: bool) -> Self {
+        Self {
+            width,
+            height,
+            color,
+            depth: vec![1.0; width as usize * height as usize],
+            srgb,
+            viewport: Viewport::full(width, height),
+        }
+    }
+
+    pub fn clear(&mut self, color: Vec4) {
+        let pixel = encode(self.srgb, color);
+        for chunk in self.color.chunks_exact_mut(4) {
+            chunk.copy_from_slice(&pixel);
+        }
+    }
+
+    /// Píxeles `[min, max)` del viewport dentro del destino.
+    fn scissor(&self) -> (Vec2, Vec2) {
+        let size = Vec2::new(self.width as f32, self.height as f32);
+        let origin = Vec2::new(self.viewport.x, self.viewport.y);
+        let extent = Vec2::new(self.viewport.width, self.viewport.height);
+        (
+            origin.floor().clamp(Vec2::ZERO, size),
+            (origin + extent).ceil().clamp(Vec2::ZERO, size),
+        )
+    }
+
+    /// Limpia el color y la profundidad del viewport actual.
+    pub fn clear_viewport(&mut self, color: Vec4) {
+        let pixel = encode(self.srgb, color);
+        let (min, max) = self.scissor();
+        for y in min.y as usize..max.y as usize {
+            for x in min.x as usize..max.x as usize {
+                let index = y * self.width as usize + x;
+                self.color[index * 4..index * 4 + 4].copy_from_slice(&pixel);
+                self.depth[index] = 1.0;
+            }
+        }
+    }
+}
+
+fn encode(srgb: bool, color: Vec4) -> [u8; 4] {
+    let color = color.clamp(Vec4::ZERO, Vec4::ONE);
+    let rgb = if srgb {
+        [
+            linear_to_srgb(color.x),
+            linear_to_srgb(color.y),
+            linear_to_srgb(color.z),
+        ]
+    } else {
+        [color.x, color.y, color.z]
+    };
+    [to_u8(rgb[0]), to_u8(rgb[1]), to_u8(rgb[2]), to_u8(color.w)]
+}
+
+fn decode(srgb: bool, pixel: &[u8]) -> Vec4 {
+    let channel = |value: u8| {
+        let value = value as f32 / 255.0;
+        if srgb { srgb_to_linear(value) } else { value }
+    };
+    Vec4::new(
+        channel(pixel[0]),
+        channel(pixel[1]),
+        channel(pixel[2]),
+        pixel[3] as f32 / 255.0,
+    )
+}
+
+fn to_u8(value: f32) -> u8 {
+    (value * 255.0 + 0.5) as u8
+}
+
+pub(crate) fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+pub(crate) fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.040_45 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub(crate) fn read_f32s<const N: usize>(bytes: &[u8], offset: usize) -> [f32; N] {
+    let mut values = [0.0; N];
+    if let Some(bytes) = bytes.get(offset..offset + N * 4) {
+        for (value, chunk) in values.iter_mut().zip(bytes.chunks_exact(4)) {
+            *value = f32::from_le_bytes(chunk.try_into().unwrap());
+        }
+    }
+    values
+}
+
+/// Recorta el triángulo contra los planos `z >= 0` y `w > 0` y rasteriza el
+/// polígono resultante en abanico.
+pub(crate) fn draw_triangle(
+    target: &mut Framebuffer,
+    program: &SoftwareProgram,
+    state: &RasterState,
+    triangle: [VertexOutput; 3],
+    push_constants: &[u8],
+) {
+    const MIN_W: f32 = 1e-6;
+    let polygon = clip_polygon(triangle.to_vec(), |v| v.position.w - MIN_W);
+    let polygon = clip_polygon(polygon, |v| v.position.z);
+    for i in 1..polygon.len().saturating_sub(1) {
+        rasterize(
+            target,
+            program,
+            state,
+            [&polygon[0], &polygon[i], &polygon[i + 1]],
+            push_constants,
+        );
+    }
+}
+
+/// Sutherland-Hodgman contra el semiespacio `distance(v) >= 0`.
+fn clip_polygon(
+    polygon: Vec<VertexOutput>,
+    distance: impl Fn(&VertexOutput) -> f32,
+) -> Vec<VertexOutput> {
+    if polygon.iter().all(|v| distance(v) >= 0.0) {
+        return polygon;
+    }
+    let mut clipped = Vec::with_capacity(polygon.len() + 1);
+    for (i, current) in polygon.iter().enumerate() {
+        let next = &polygon[(i + 1) % polygon.len()];
+        let (d_current, d_next) = (distance(current), distance(next));
+        if d_current >= 0.0 {
+            clipped.push(*current);
+        }
+        if (d_current >= 0.0) != (d_next >= 0.0) {
+            clipped.push(current.lerp(next, d_current / (d_current - d_next)));
+        }
+    }
+    clipped
+}
+
+/// Vértice tras la división de perspectiva.
+struct ScreenVertex<'a> {
+    position: Vec2,
+    depth: f32,
+    inv_w: f32,
+    varyings: &'a Varyings,
+}
+
+fn edge(a: Vec2, b: Vec2, p: Vec2) -> f32 {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+/// Regla top-left para un triángulo con área positiva (horario en pantalla).
+fn is_top_left(a: Vec2, b: Vec2) -> bool {
+    (a.y == b.y && b.x > a.x) || b.y < a.y
+}
+
+fn rasterize(
+    target: &mut Framebuffer,
+    program: &SoftwareProgram,
+    state: &RasterState,
+    triangle: [&VertexOutput; 3],
+    push_constants: &[u8],
+) {
+    let origin = Vec2::new(target.viewport.x, target.viewport.y);
+    let size = Vec2::new(target.viewport.width, target.viewport.height);
+    let (scissor_min, scissor_max) = target.scissor();
+    let [v0, mut v1, mut v2] = triangle.map(|vertex| {
+        let inv_w = 1.0 / vertex.position.w;
+        let ndc = vertex.position.truncate() * inv_w;
+        ScreenVertex {
+            position: origin + (ndc.truncate() * 0.5 + 0.5) * size,
+            depth: ndc.z,
+            inv_w,
+            varyings: &vertex.varyings,
+        }
+    });
+
+    let area = edge(v0.position, v1.position, v2.position);
+    if area == 0.0 || !area.is_finite() {
+        return;
+    }
+    let clockwise = area > 0.0;
+    let front_facing = clockwise == (state.front_face == FrontFace::Clockwise);
+    match state.cull {
+        CullMode::Front if front_facing => return,
+        CullMode::Back if !front_facing => return,
+        _ => {}
+    }
+    // Orientar en sentido horario para que los pesos sean positivos dentro.
+    if !clockwise {
+        std::mem::swap(&mut v1, &mut v2);
+    }
+    let area = area.abs();
+
+    let min = v0
+        .position
+        .min(v1.position)
+        .min(v2.position)
+        .floor()
+        .max(scissor_min);
+    let max = v0
+        .position
+        .max(v1.position)
+        .max(v2.position)
+        .ceil()
+        .min(scissor_max);
+    let edges = [
+        (
+            v1.position,
+            v2.position,
+            is_top_left(v1.position, v2.position),
+        ),
+        (
+            v2.position,
+            v0.position,
+            is_top_left(v2.position, v0.position),
+        ),
+        (
+            v0.position,
+            v1.position,
+            is_top_left(v0.position, v1.position),
+        ),
+    ];
+
+    for y in min.y as u32..max.y as u32 {
+        for x in min.x as u32..max.x as u32 {
+            let p = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+            let weights = edges.map(|(a, b, top_left)| (edge(a, b, p), top_left));
+            if weights
+                .iter()
+                .any(|&(w, top_left)| w < 0.0 || (w == 0.0 && !top_left))
+            {
+                continue;
+            }
+            let [b0, b1, b2] = weights.map(|(w, _)| w / area);
+
+            let depth = b0 * v0.depth + b1 * v1.depth + b2 * v2.depth;
+            if !(0.0..=1.0).contains(&depth) {
+                continue;
+            }
+            let index = y as usize * target.width as usize + x as usize;
+            if state.depth_test && depth >= target.depth[index] {
+                continue;
+            }
+
+            // Interpolación con corrección de perspectiva: se interpola v/w y 1/w
+            // linealmente en pantalla.
+            let (p0, p1, p2) = (b0 * v0.inv_w, b1 * v1.inv_w, b2 * v2.inv_w);
+            let inv_w = p0 + p1 + p2;
+            let mut varyings = [0.0; MAX_VARYINGS];
+            for (k, value) in varyings.iter_mut().enumerate() {
+                *value = (p0 * v0.varyings[k] + p1 * v1.varyings[k] + p2 * v2.varyings[k]) / inv_w;
+            }
+
+            let input = FragmentInput {
+                position: p,
+                depth,
+                front_facing,
+                varyings,
+                push_constants,
+            };
+            let Some(color) = (program.fragment)(&input) else {
+                continue;
+            };
+
+            if state.depth_write {
+                target.depth[index] = depth;
+            }
+            let pixel = &mut target.color[index * 4..index * 4 + 4];
+            let dst = || decode(target.srgb, pixel);
+            let color = match state.blend {
+                BlendMode::Replace => color,
+                BlendMode::Alpha => {
+                    let (dst, alpha) = (dst(), color.w.clamp(0.0, 1.0));
+                    (color * alpha + dst * (1.0 - alpha)).with_w(alpha + dst.w * (1.0 - alpha))
+                }
+                BlendMode::PremultipliedAlpha => {
+                    let (dst, alpha) = (dst(), color.w.clamp(0.0, 1.0));
+                    color + dst * (1.0 - alpha)
+                }
+                BlendMode::Additive => color + dst(),
+            };
+            pixel.copy_from_slice(&encode(target.srgb, color));
+        }
+    }
+}
@@ -0,0 +1,318 @@
+//! Subidas asíncronas a buffers y texturas a través de un ring de staging.
+//!
+//! Esta es la parte de CPU, común a todos los backends: reparte el ring, agrupa
+//! las copias en batches y libera su espacio cuando el backend informa de que la
+//! GPU terminó un batch. Cada backend pone la memoria del ring y ejecuta los
+//! batches (en Vulkan, en la cola de transferencia).
+
+use super::{BufferId, TextureId};
+use std::collections::VecDeque;
+
+/// Tamaño por defecto del ring de staging.
+pub const DEFAULT_STAGING_CAPACITY: u64 = 16 << 20;
+
+/// Alineación de cada copia dentro del ring; cubre la de `vkCmdCopyBufferToImage`
+/// para todos los formatos de `TextureFormat`.
+pub const STAGING_ALIGNMENT: u64 = 16;
+
+/// Identifica el batch que lleva una subida. Los tickets crecen con cada batch, así
+/// que un ticket está completo si lo está cualquiera posterior.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UploadTicket(pub u64);
+
+impl UploadTicket {
+    /// Ticket de una subida que ya terminó al devolverse (e.g. una síncrona).
+    pub const COMPLETE: Self = Self(0);
+}
+
+/// Destino de una copia.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UploadTarget {
+    Buffer { buffer: BufferId, offset: u64 },
+    Texture { texture: TextureId, mip_level: u32 },
+}
+
+/// Copia de `size` bytes desde `staging_offset` del ring a `target`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UploadCopy {
+    pub target: UploadTarget,
+    pub staging_offset: u64,
+    pub size: u64,
+}
+
+/// Copias que se envían juntas y terminan con el mismo ticket.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UploadBatch {
+    pub ticket: UploadTicket,
+    pub copies: Vec<UploadCopy>,
+}
+
+/// Contadores acumulados del planificador.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UploadStats {
+    pub batches: u64,
+    pub copies: u64,
+    pub bytes: u64,
+    /// Veces que una subida tuvo que esperar a la GPU por falta de espacio.
+    pub stalls: u64,
+    /// Subidas más grandes que el ring, hechas de forma síncrona.
+    pub oversized: u64,
+}
+
+/// Por qué no se pudo reservar espacio en el ring.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StageError {
+    /// No hay hueco hasta que la GPU termine algún batch.
+    Full,
+    /// La copia no cabe en el ring ni vacío.
+    TooLarge,
+}
+
+/// Ring de staging: se escribe en `head` y se libera desde `tail` a medida que
+/// terminan los batches, en el mismo orden en que se enviaron.
+#[derive(Debug)]
+pub struct StagingRing {
+    capacity: u64,
+    head: u64,
+    tail: u64,
+    /// Bytes reservados desde el principio, contando el relleno por alineación y
+    /// el final que se salta al dar la vuelta.
+    allocated: u64,
+    released: u64,
+    /// Por batch cerrado: ticket, `head` y `allocated` al cerrarlo.
+    marks: VecDeque<(UploadTicket, u64, u64)>,
+}
+
+impl StagingRing {
+    pub fn new(capacity: u64) -> Self {
+        Self {
+            capacity,
+            head: 0,
+            tail: 0,
+            allocated: 0,
+            released: 0,
+            marks: VecDeque::new(),
+        }
+    }
+
+    pub fn capacity(&self) -> u64 {
+        self.capacity
+    }
+
+    /// Bytes ocupados por batches sin terminar y copias sin enviar.
+    pub fn used(&self) -> u64 {
+        self.allocated - self.released
+    }
+
+    pub fn allocate(&mut self, size: u64, alignment: u64) -> Result<u64, StageError> {
+        if size > self.capacity {
+            return Err(StageError::TooLarge);
+        }
+        if self.used() == 0 {
+            self.head = 0;
+            self.tail = 0;
+        }
+        let start = self.head.next_multiple_of(alignment.max(1));
+        let start = if self.used() == 0 || self.head > self.tail {
+            // Libre: `[head, capacity)` y, dando la vuelta, `[0, tail)`.
+            if start + size <= self.capacity {
+                start
+            } else if size <= self.tail {
+                0
+            } else {
+                return Err(StageError::Full);
+            }
+        } else if self.head < self.tail && start + size <= self.tail {
+            start
+        } else {
+            return Err(StageError::Full);
+        };
+        let consumed = if start >= self.head {
+            start + size - self.head
+        } else {
+            self.capacity - self.head + size
+        };
+        self.allocated += consumed;
+        self.head = start + size;
+        Ok(start)
+    }
+
+    /// Cierra un batch con todo lo reservado hasta ahora.
+    pub fn close(&mut self, ticket: UploadTicket) {
+        self.marks.push_back((ticket, self.head, self.allocated));
+    }
+
+    /// Libera el espacio de los batches hasta `completed` incluido.
+    pub fn release(&mut self, completed: UploadTicket) {
+        while let Some(&(ticket, head, allocated)) = self.marks.front() {
+            if ticket > completed {
+                break;
+            }
+            self.marks.pop_front();
+            self.tail = head;
+            self.released = allocated;
+        }
+    }
+
+    /// Batch cerrado más antiguo que sigue ocupando espacio.
+    pub fn oldest(&self) -> Option<UploadTicket> {
+        self.marks.front().map(|&(ticket, _, _)| ticket)
+    }
+}
+
+/// Planificador de subidas sobre un `StagingRing`.
+///
+/// El backend reserva cada copia con `stage`, escribe los datos en el offset
+/// devuelto, envía los batches de `take_batch` y llama a `retire` cuando la GPU
+/// los termina. Si `stage` devuelve `StageError::Full`, el backend envía lo
+/// pendiente, espera al batch de `oldest_in_flight` y lo vuelve a intentar.
+#[derive(Debug)]
+pub struct UploadScheduler {
+    ring: StagingRing,
+    pending: Vec<UploadCopy>,
+    /// Ticket del batch que se está llenando.
+    next_ticket: u64,
+    completed: UploadTicket,
+    stats: UploadStats,
+}
+
+impl UploadScheduler {
+    pub fn new(capacity: u64) -> Self {
+        Self {
+            ring: StagingRing::new(capacity),
+            pending: Vec::new(),
+            next_ticket: 1,
+            completed: UploadTicket::COMPLETE,
+            stats: UploadStats::default(),
+        }
+    }
+
+    pub fn capacity(&self) -> u64 {
+        self.ring.capacity()
+    }
+
+    /// Reserva `size` bytes para una copia a `target` y devuelve su offset en el ring.
+    pub fn stage(&mut self, target: UploadTarget, size: u64) -> Result<u64, StageError> {
+        match self.ring.allocate(size, STAGING_ALIGNMENT) {
+            Ok(staging_offset) => {
+                self.pending.push(UploadCopy {
+                    target,
+                    staging_offset,
+                    size,
+                });
+                self.stats.copies += 1;
+                self.stats.bytes += size;
+                Ok(staging_offset)
+            }
+            Err(error) => {
+                match error {
+                    StageError::Full => self.stats.stalls += 1,
+                    StageError::TooLarge => self.stats.oversized += 1,
+                }
+                Err(error)
+            }
+        }
+    }
+
+    /// Ticket con el que terminarán las copias pendientes.
+    pub fn pending_ticket(&self) -> UploadTicket {
+        UploadTicket(self.next_ticket)
+    }
+
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Cierra el batch pendiente para enviarlo; `None` si no hay copias.
+    pub fn take_batch(&mut self) -> Option<UploadBatch> {
+        if self.pending.is_empty() {
+            return None;
+        }
+        let ticket = self.pending_ticket();
+        self.ring.close(ticket);
+        self.next_ticket += 1;
+        self.stats.batches += 1;
+        Some(UploadBatch {
+            ticket,
+            copies: std::mem::take(&mut self.pending),
+        })
+    }
+
+    /// Último ticket enviado.
+    pub fn last_submitted(&self) -> UploadTicket {
+        UploadTicket(self.next_ticket - 1)
+    }
+
+    /// Batch enviado más antiguo sin terminar.
+    pub fn oldest_in_flight(&self) -> Option<UploadTicket> {
+        self.ring.oldest()
+    }
+
+    /// Marca como terminados los batches hasta `completed` incluido.
+    pub fn retire(&mut self, completed: UploadTicket) {
+        self.completed = self.completed.max(completed);
+        self.ring.release(self.completed);
+    }
+
+    pub fn is_complete(&self, ticket: UploadTicket) -> bool {
+        ticket <= self.completed
+    }
+
+    pub fn stats(&self) -> UploadStats {
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_staging_ring_wraps_and_releases_in_order() {
+        let mut ring = StagingRing::new(256);
+        assert_eq!(ring.allocate(100, 16), Ok(0));
+        ring.close(UploadTicket(1));
+        assert_eq!(ring.allocate(100, 16), Ok(112));
+        ring.close(UploadTicket(2));
+        // No cabe al final ni, mientras el batch 1 ocupe el principio, dando la vuelta.
+        assert_eq!(ring.allocate(64, 16), Err(StageError::Full));
+        assert_eq!(ring.allocate(300, 16), Err(StageError::TooLarge));
+
+        ring.release(UploadTicket(1));
+        assert_eq!(ring.oldest(), Some(UploadTicket(2)));
+        assert_eq!(ring.allocate(64, 16), Ok(0));
+        // El relleno y el final saltado cuentan como ocupados hasta que se libera
+        // el batch que los dejó.
+        assert_eq!(ring.used(), 112 + 44 + 64);
+        ring.close(UploadTicket(3));
+        ring.release(UploadTicket(3));
+        assert_eq!(ring.used(), 0);
+        assert_eq!(ring.allocate(256, 16), Ok(0));
+    }
+
+    #[test]
+    fn test_scheduler_batches_copies_under_one_ticket() {
+        let mut scheduler = UploadScheduler::new(1024);
+        let buffer = UploadTarget::Buffer {
+            buffer: BufferId(1),
+            offset: 0,
+        };
+        assert_eq!(scheduler.take_batch(), None);
+        assert_eq!(scheduler.stage(buffer, 10), Ok(0));
+        assert_eq!(scheduler.stage(buffer, 10), Ok(16));
+        let ticket = scheduler.pending_ticket();
+        let batch = scheduler.take_batch().unwrap();
+        assert_eq!((batch.ticket, batch.copies.len()), (ticket, 2));
+        assert_eq!(scheduler.last_submitted(), ticket);
+        assert!(!scheduler.is_complete(ticket));
+
+        assert_eq!(scheduler.stage(buffer, 2048), Err(StageError::TooLarge));
+        scheduler.retire(ticket);
+        assert!(scheduler.is_complete(ticket) && scheduler.oldest_in_flight().is_none());
+        let stats = scheduler.stats();
+        assert_eq!(
+            (stats.batches, stats.copies, stats.bytes, stats.oversized),
+            (1, 2, 20, 1)
+        );
+    }
+}
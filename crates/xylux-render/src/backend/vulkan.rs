@@ -0,0 +1,1975 @@
+//! Backend Vulkan: ejecuta las `CommandList` sobre un `VulkanContext`.
+
+use super::upload::{
+    DEFAULT_STAGING_CAPACITY, StageError, UploadScheduler, UploadStats, UploadTarget, UploadTicket,
+};
+use super::{
+    AddressMode, BackendError, BarrierResource, BindGroupDesc, BindGroupEntry, BindGroupId,
+    BindGroupLayout, BindingResource, BindingType, BufferDesc, BufferId, BufferUsage, CommandList,
+    FilterMode, FrameInfo, IndexFormat, PipelineDesc, PipelineId, RenderBackend, RenderCommand,
+    RenderTarget, ResourceBarrier, ResourceUsage, SampleCount, SamplerDesc, SamplerId, TextureDesc,
+    TextureFormat, TextureId, TextureUsage, Viewport,
+};
+use crate::allocator::{
+    Allocation, AllocationDesc, AllocatorConfig, AllocatorStats, GpuAllocator, MemoryLocation,
+    ResourceKind,
+};
+use crate::pipeline::Pipeline;
+use crate::renderer::framebuffers::{self, FrameAttachments};
+use crate::renderer::render_pass::{self, vk_sample_count};
+use crate::vulkan::context::{MAX_FRAMES_IN_FLIGHT, VulkanContext};
+use crate::vulkan::memory::VulkanHeap;
+use crate::vulkan::transfer::TransferQueue;
+use ash::khr::swapchain;
+use ash::vk;
+use std::any::Any;
+use std::collections::{HashMap, VecDeque};
+use xylux_window::XyluxWindow;
+
+struct VulkanBuffer {
+    buffer: vk::Buffer,
+    allocation: Allocation<vk::DeviceMemory>,
+    size: u64,
+    /// Nulo en memoria solo de GPU.
+    mapped: *mut u8,
+}
+
+struct VulkanTexture {
+    desc: TextureDesc,
+    image: vk::Image,
+    allocation: Allocation<vk::DeviceMemory>,
+    view: vk::ImageView,
+}
+
+/// Recurso destruido que un frame en vuelo o una subida aún pueden estar usando.
+enum Retired {
+    Buffer(VulkanBuffer),
+    Texture(VulkanTexture),
+    Pipeline(Pipeline),
+    BindGroup(vk::DescriptorPool, vk::DescriptorSet),
+    Sampler(vk::Sampler),
+}
+
+/// Crea un buffer en memoria visible desde la CPU, ya mapeada.
+fn create_host_buffer(
+    device: &ash::Device,
+    allocator: &mut GpuAllocator<VulkanHeap>,
+    size: u64,
+    usage: vk::BufferUsageFlags,
+) -> Result<VulkanBuffer, BackendError> {
+    create_buffer_in(device, allocator, size, usage, MemoryLocation::CpuToGpu)
+}
+
+/// Crea un buffer en `location`; solo se mapea la memoria `CpuToGpu`.
+fn create_buffer_in(
+    device: &ash::Device,
+    allocator: &mut GpuAllocator<VulkanHeap>,
+    size: u64,
+    usage: vk::BufferUsageFlags,
+    location: MemoryLocation,
+) -> Result<VulkanBuffer, BackendError> {
+    let buffer_info = vk::BufferCreateInfo {
+        size: size.max(1),
+        usage,
+        sharing_mode: vk::SharingMode::EXCLUSIVE,
+        ..Default::default()
+    };
+    let buffer = unsafe { device.create_buffer(&buffer_info, None) }
+        .map_err(|error| BackendError::Device(error.to_string()))?;
+    let requirements = unsafe { device.get_buffer_memory_requirements(buffer) };
+    let allocation = match allocator.allocate(&AllocationDesc {
+        size: requirements.size,
+        alignment: requirements.alignment,
+        memory_type_bits: requirements.memory_type_bits,
+        location,
+        kind: ResourceKind::Buffer,
+        dedicated: false,
+    }) {
+        Ok(allocation) => allocation,
+        Err(error) => {
+            unsafe { device.destroy_buffer(buffer, None) };
+            return Err(error.into());
+        }
+    };
+    unsafe {
+        device
+            .bind_buffer_memory(buffer, allocation.memory(), allocation.offset())
+            .expect("Failed to bind buffer memory");
+    }
+    let mapped = match location {
+        MemoryLocation::CpuToGpu => allocation
+            .mapped_ptr()
+            .expect("CPU-to-GPU memory is mapped"),
+        _ => std::ptr::null_mut(),
+    };
+    Ok(VulkanBuffer {
+        buffer,
+        allocation,
+        size,
+        mapped,
+    })
+}
+
+/// Backend sobre Vulkan con presentación en una ventana SDL3.
+///
+/// Los buffers `CpuToGpu` se escriben directamente en su memoria mapeada; los
+/// `GpuOnly` y las texturas se suben por el ring de staging.
+pub struct VulkanBackend {
+    pub context: VulkanContext,
+    pub render_pass: vk::RenderPass,
+    pub framebuffers: Vec<vk::Framebuffer>,
+    /// Profundidad y color multisampleado del render pass del swapchain.
+    attachments: FrameAttachments,
+    depth_format: vk::Format,
+    samples: SampleCount,
+    pipelines: HashMap<PipelineId, (PipelineDesc, Pipeline)>,
+    buffers: HashMap<BufferId, VulkanBuffer>,
+    textures: HashMap<TextureId, VulkanTexture>,
+    /// Memoria de buffers y texturas.
+    allocator: GpuAllocator<VulkanHeap>,
+    /// Subidas asíncronas: planificador, ring de staging y cola en la que se copian.
+    uploads: UploadScheduler,
+    upload_staging: Option<VulkanBuffer>,
+    transfer: TransferQueue,
+    /// Descriptor set layouts compartidos por pipelines y bind groups.
+    set_layouts: HashMap<BindGroupLayout, vk::DescriptorSetLayout>,
+    descriptor_pool: vk::DescriptorPool,
+    /// Pool `UPDATE_AFTER_BIND` de los grupos con `TextureArray`; nulo si el
+    /// dispositivo no admite bindless.
+    bindless_pool: vk::DescriptorPool,
+    bind_groups: HashMap<BindGroupId, (BindGroupLayout, vk::DescriptorSet)>,
+    samplers: HashMap<SamplerId, vk::Sampler>,
+    /// Recursos destruidos, con los frames empezados y la última subida enviada al
+    /// destruirlos. Se liberan cuando esos frames y esa subida han terminado.
+    retired: VecDeque<(u64, UploadTicket, Retired)>,
+    next_id: u32,
+    current_frame: usize,
+    frame: u64,
+    /// Imagen del swapchain adquirida en `begin_frame`.
+    image_index: Option<u32>,
+    /// Comandos enviados en el frame actual; se graban y ejecutan en `present`.
+    pending: Vec<RenderCommand>,
+    size: (u32, u32),
+}
+
+impl VulkanBackend {
+    pub fn new(window: &XyluxWindow) -> Self {
+        let context = VulkanContext::new(window);
+        let depth_format =
+            render_pass::select_depth_format(&context.instance, context.physical_device);
+        let samples = SampleCount::X1;
+        let render_pass = render_pass::create_render_pass(
+            &context.device,
+            context.swapchain_format(),
+            depth_format,
+            samples,
+        );
+        let attachments = FrameAttachments::new(&context, depth_format, samples);
+        let framebuffers = framebuffers::create_framebuffers(
+            &context.device,
+            render_pass,
+            context.swapchain_image_views(),
+            &attachments,
+            context.swapchain_extent(),
+        );
+
+        let descriptor_pool = create_descriptor_pool(&context.device);
+        let bindless_pool = if context.features.bindless {
+            create_bindless_pool(&context.device)
+        } else {
+            vk::DescriptorPool::null()
+        };
+        let mut allocator = GpuAllocator::new(
+            VulkanHeap::new(&context.instance, context.physical_device, &context.device),
+            AllocatorConfig::default(),
+        );
+        let upload_staging = create_host_buffer(
+            &context.device,
+            &mut allocator,
+            DEFAULT_STAGING_CAPACITY,
+            vk::BufferUsageFlags::TRANSFER_SRC,
+        )
+        .expect("Failed to create the upload staging ring");
+        let transfer = TransferQueue::new(&context);
+
+        Self {
+            context,
+            render_pass,
+            framebuffers,
+            attachments,
+            depth_format,
+            samples,
+            pipelines: HashMap::new(),
+            buffers: HashMap::new(),
+            textures: HashMap::new(),
+            allocator,
+            uploads: UploadScheduler::new(DEFAULT_STAGING_CAPACITY),
+            upload_staging: Some(upload_staging),
+            transfer,
+            set_layouts: HashMap::new(),
+            descriptor_pool,
+            bindless_pool,
+            bind_groups: HashMap::new(),
+            samplers: HashMap::new(),
+            retired: VecDeque::new(),
+            next_id: 0,
+            current_frame: 0,
+            frame: 0,
+            image_index: None,
+            pending: Vec::new(),
+            size: window.window.size(),
+        }
+    }
+
+    fn next_id(&mut self) -> u32 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    /// Uso de la memoria de buffers y texturas.
+    pub fn memory_stats(&self) -> AllocatorStats {
+        self.allocator.stats()
+    }
+
+    fn destroy_host_buffer(&mut self, buffer: VulkanBuffer) {
+        unsafe { self.context.device.destroy_buffer(buffer.buffer, None) };
+        self.allocator.free(buffer.allocation);
+    }
+
+    /// Aplaza la destrucción de `resource` hasta que ningún frame empezado ni subida
+    /// enviada pueda usarlo.
+    fn retire(&mut self, resource: Retired) {
+        self.retired
+            .push_back((self.frame, self.uploads.last_submitted(), resource));
+    }
+
+    /// Libera los recursos retirados que ya no usa la GPU, sabiendo que han terminado
+    /// los `completed_frames` primeros frames (`u64::MAX` tras esperar al dispositivo).
+    fn release_retired(&mut self, completed_frames: u64) {
+        while let Some(&(frames, ticket, _)) = self.retired.front() {
+            if frames > completed_frames || !self.uploads.is_complete(ticket) {
+                break;
+            }
+            let (_, _, resource) = self.retired.pop_front().expect("front exists");
+            let device = &self.context.device;
+            match resource {
+                Retired::Buffer(buffer) => self.destroy_host_buffer(buffer),
+                Retired::Texture(texture) => {
+                    unsafe {
+                        device.destroy_image_view(texture.view, None);
+                        device.destroy_image(texture.image, None);
+                    }
+                    self.allocator.free(texture.allocation);
+                }
+                Retired::Pipeline(pipeline) => pipeline.cleanup(device),
+                Retired::BindGroup(pool, set) => unsafe {
+                    device
+                        .free_descriptor_sets(pool, &[set])
+                        .expect("Failed to free descriptor set");
+                },
+                Retired::Sampler(sampler) => unsafe { device.destroy_sampler(sampler, None) },
+            }
+        }
+    }
+
+    /// Comprueba una subida con las mismas reglas que `write_buffer` y `write_texture`.
+    fn check_upload(&self, target: UploadTarget, len: usize) -> Result<(), BackendError> {
+        match target {
+            UploadTarget::Buffer { buffer, offset } => {
+                let size = self.buffer(buffer)?.size;
+                if offset + len as u64 > size {
+                    return Err(BackendError::InvalidCommands(format!(
+                        "write of {} bytes at {} overflows {:?} ({} bytes)",
+                        len, offset, buffer, size
+                    )));
+                }
+            }
+            UploadTarget::Texture { texture, mip_level } => {
+                let desc = &self
+                    .textures
+                    .get(&texture)
+                    .ok_or_else(|| BackendError::InvalidHandle(format!("{:?}", texture)))?
+                    .desc;
+                let expected = super::null::mip_size(desc, mip_level);
+                if len != expected || mip_level >= desc.mip_levels.max(1) {
+                    return Err(BackendError::InvalidCommands(format!(
+                        "mip {} of {:?} expects {} bytes, got {}",
+                        mip_level, texture, expected, len
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn staging(&self) -> &VulkanBuffer {
+        self.upload_staging
+            .as_ref()
+            .expect("Upload staging ring used after cleanup")
+    }
+
+    /// Copia `data` al ring de staging. Si no hay hueco espera al batch más antiguo;
+    /// lo que no cabe ni con el ring vacío se sube de forma síncrona, después de
+    /// todo lo encolado.
+    fn stage_upload(
+        &mut self,
+        target: UploadTarget,
+        data: &[u8],
+    ) -> Result<UploadTicket, BackendError> {
+        self.check_upload(target, data.len())?;
+        if data.is_empty() {
+            return Ok(UploadTicket::COMPLETE);
+        }
+        loop {
+            match self.uploads.stage(target, data.len() as u64) {
+                Ok(offset) => {
+                    // SAFETY: el ring está mapeado mientras vive el backend y el
+                    // planificador no reparte rangos fuera de su capacidad.
+                    unsafe {
+                        std::ptr::copy_nonoverlapping(
+                            data.as_ptr(),
+                            self.staging().mapped.add(offset as usize),
+                            data.len(),
+                        );
+                    }
+                    return Ok(self.uploads.pending_ticket());
+                }
+                Err(StageError::Full) => {
+                    self.flush_uploads()?;
+                    let oldest = self
+                        .uploads
+                        .oldest_in_flight()
+                        .expect("A full staging ring has batches in flight");
+                    self.transfer.wait(&self.context.device, oldest);
+                    self.retire_uploads();
+                }
+                Err(StageError::TooLarge) => {
+                    self.flush_uploads()?;
+                    self.transfer
+                        .wait(&self.context.device, self.uploads.last_submitted());
+                    self.retire_uploads();
+                    match target {
+                        UploadTarget::Buffer { buffer, offset } => {
+                            self.write_buffer_now(buffer, offset, data)?
+                        }
+                        UploadTarget::Texture { texture, mip_level } => {
+                            self.write_texture_now(texture, mip_level, data)?
+                        }
+                    }
+                    return Ok(UploadTicket::COMPLETE);
+                }
+            }
+        }
+    }
+
+    /// Devuelve al ring el espacio de los batches que la GPU ya terminó.
+    fn retire_uploads(&mut self) {
+        let completed = self.transfer.completed(&self.context.device);
+        self.uploads.retire(completed);
+        self.transfer.retire(&self.context.device, completed);
+    }
+
+    /// Escribe en un buffer solo de GPU con un staging propio en la cola gráfica y
+    /// espera a que termine. Solo para las subidas que no caben en el ring.
+    fn write_buffer_now(
+        &mut self,
+        buffer: BufferId,
+        offset: u64,
+        data: &[u8],
+    ) -> Result<(), BackendError> {
+        self.check_upload(UploadTarget::Buffer { buffer, offset }, data.len())?;
+        let target = &self.buffers[&buffer];
+        if !target.mapped.is_null() {
+            return self.write_buffer(buffer, offset, data);
+        }
+        let target = target.buffer;
+
+        let staging = create_host_buffer(
+            &self.context.device,
+            &mut self.allocator,
+            data.len() as u64,
+            vk::BufferUsageFlags::TRANSFER_SRC,
+        )?;
+        unsafe { std::ptr::copy_nonoverlapping(data.as_ptr(), staging.mapped, data.len()) };
+
+        let device = &self.context.device;
+        let staging_buffer = staging.buffer;
+        let size = data.len() as u64;
+        self.one_time_commands(|command_buffer| unsafe {
+            let region = vk::BufferCopy {
+                src_offset: 0,
+                dst_offset: offset,
+                size,
+            };
+            device.cmd_copy_buffer(command_buffer, staging_buffer, target, &[region]);
+            let to_read = vk::BufferMemoryBarrier {
+                src_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+                dst_access_mask: vk::AccessFlags::MEMORY_READ,
+                src_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+                dst_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+                buffer: target,
+                offset,
+                size,
+                ..Default::default()
+            };
+            device.cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::ALL_COMMANDS,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[to_read],
+                &[],
+            );
+        });
+
+        self.destroy_host_buffer(staging);
+        Ok(())
+    }
+
+    /// Sube un nivel de mip con un staging propio en la cola gráfica y espera a que
+    /// termine. Solo para las subidas que no caben en el ring.
+    fn write_texture_now(
+        &mut self,
+        texture: TextureId,
+        mip_level: u32,
+        data: &[u8],
+    ) -> Result<(), BackendError> {
+        self.check_upload(UploadTarget::Texture { texture, mip_level }, data.len())?;
+        let target = &self.textures[&texture];
+        let image = target.image;
+        let format = target.desc.format;
+        let width = (target.desc.width >> mip_level).max(1);
+        let height = (target.desc.height >> mip_level).max(1);
+
+        let staging = create_host_buffer(
+            &self.context.device,
+            &mut self.allocator,
+            data.len() as u64,
+            vk::BufferUsageFlags::TRANSFER_SRC,
+        )?;
+        unsafe { std::ptr::copy_nonoverlapping(data.as_ptr(), staging.mapped, data.len()) };
+
+        let device = &self.context.device;
+        let staging_buffer = staging.buffer;
+        let range = vk::ImageSubresourceRange {
+            aspect_mask: aspect_mask(format),
+            base_mip_level: mip_level,
+            level_count: 1,
+            base_array_layer: 0,
+            layer_count: 1,
+        };
+        self.one_time_commands(|command_buffer| unsafe {
+            let to_transfer = vk::ImageMemoryBarrier {
+                old_layout: vk::ImageLayout::UNDEFINED,
+                new_layout: vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                dst_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+                src_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+                dst_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+                image,
+                subresource_range: range,
+                ..Default::default()
+            };
+            device.cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::TOP_OF_PIPE,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[to_transfer],
+            );
+            let region = vk::BufferImageCopy {
+                image_subresource: vk::ImageSubresourceLayers {
+                    aspect_mask: range.aspect_mask,
+                    mip_level,
+                    base_array_layer: 0,
+                    layer_count: 1,
+                },
+                image_extent: vk::Extent3D {
+                    width,
+                    height,
+                    depth: 1,
+                },
+                ..Default::default()
+            };
+            device.cmd_copy_buffer_to_image(
+                command_buffer,
+                staging_buffer,
+                image,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                &[region],
+            );
+            let to_shader = vk::ImageMemoryBarrier {
+                old_layout: vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                new_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                src_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+                dst_access_mask: vk::AccessFlags::SHADER_READ,
+                ..to_transfer
+            };
+            device.cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::FRAGMENT_SHADER,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[to_shader],
+            );
+        });
+
+        self.destroy_host_buffer(staging);
+        Ok(())
+    }
+
+    /// Escribe los recursos de `entries` en `set`.
+    fn write_descriptors(
+        &self,
+        set: vk::DescriptorSet,
+        entries: &[BindGroupEntry],
+    ) -> Result<(), BackendError> {
+        // Primero se resuelven todos los recursos para que los punteros de las
+        // escrituras no cambien.
+        let mut buffer_infos = Vec::new();
+        let mut image_infos = Vec::new();
+        for entry in entries {
+            match entry.resource {
+                BindingResource::Buffer {
+                    buffer,
+                    offset,
+                    size,
+                } => buffer_infos.push(vk::DescriptorBufferInfo {
+                    buffer: self.buffer(buffer)?.buffer,
+                    offset,
+                    range: size,
+                }),
+                BindingResource::Texture(texture)
+                | BindingResource::ArrayTexture { texture, .. } => {
+                    let view = self
+                        .textures
+                        .get(&texture)
+                        .ok_or_else(|| BackendError::InvalidHandle(format!("{:?}", texture)))?
+                        .view;
+                    image_infos.push(vk::DescriptorImageInfo {
+                        image_view: view,
+                        image_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                        ..Default::default()
+                    });
+                }
+                BindingResource::Sampler(sampler) => {
+                    let sampler = *self
+                        .samplers
+                        .get(&sampler)
+                        .ok_or_else(|| BackendError::InvalidHandle(format!("{:?}", sampler)))?;
+                    image_infos.push(vk::DescriptorImageInfo {
+                        sampler,
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+        let (mut buffer_info, mut image_info) = (buffer_infos.iter(), image_infos.iter());
+        let writes: Vec<vk::WriteDescriptorSet> = entries
+            .iter()
+            .map(|entry| {
+                let write = vk::WriteDescriptorSet {
+                    dst_set: set,
+                    dst_binding: entry.binding,
+                    descriptor_count: 1,
+                    ..Default::default()
+                };
+                match entry.resource {
+                    BindingResource::Buffer { .. } => vk::WriteDescriptorSet {
+                        descriptor_type: vk::DescriptorType::UNIFORM_BUFFER,
+                        p_buffer_info: buffer_info.next().expect("one info per buffer"),
+                        ..write
+                    },
+                    BindingResource::Texture(_) => vk::WriteDescriptorSet {
+                        descriptor_type: vk::DescriptorType::SAMPLED_IMAGE,
+                        p_image_info: image_info.next().expect("one info per texture"),
+                        ..write
+                    },
+                    BindingResource::ArrayTexture { element, .. } => vk::WriteDescriptorSet {
+                        dst_array_element: element,
+                        descriptor_type: vk::DescriptorType::SAMPLED_IMAGE,
+                        p_image_info: image_info.next().expect("one info per texture"),
+                        ..write
+                    },
+                    BindingResource::Sampler(_) => vk::WriteDescriptorSet {
+                        descriptor_type: vk::DescriptorType::SAMPLER,
+                        p_image_info: image_info.next().expect("one info per sampler"),
+                        ..write
+                    },
+                }
+            })
+            .collect();
+        unsafe { self.context.device.update_descriptor_sets(&writes, &[]) };
+        Ok(())
+    }
+
+    /// Descriptor set layout de `layout`, creado la primera vez que se pide.
+    fn set_layout(&mut self, layout: &BindGroupLayout) -> vk::DescriptorSetLayout {
+        if let Some(&set_layout) = self.set_layouts.get(layout) {
+            return set_layout;
+        }
+        let bindings: Vec<vk::DescriptorSetLayoutBinding> = layout
+            .bindings
+            .iter()
+            .map(|binding| vk::DescriptorSetLayoutBinding {
+                binding: binding.binding,
+                descriptor_type: vk_descriptor_type(binding.ty),
+                descriptor_count: match binding.ty {
+                    BindingType::TextureArray { count } => count,
+                    _ => 1,
+                },
+                stage_flags: vk::ShaderStageFlags::ALL_GRAPHICS,
+                ..Default::default()
+            })
+            .collect();
+        // Los arrays bindless pueden tener elementos sin asignar y cambiarse con el
+        // set enlazado, siempre que el frame en vuelo no los lea.
+        let binding_flags: Vec<vk::DescriptorBindingFlags> = layout
+            .bindings
+            .iter()
+            .map(|binding| match binding.ty {
+                BindingType::TextureArray { .. } => {
+                    vk::DescriptorBindingFlags::PARTIALLY_BOUND
+                        | vk::DescriptorBindingFlags::UPDATE_AFTER_BIND
+                        | vk::DescriptorBindingFlags::UPDATE_UNUSED_WHILE_PENDING
+                }
+                _ => vk::DescriptorBindingFlags::empty(),
+            })
+            .collect();
+        let mut flags_info =
+            vk::DescriptorSetLayoutBindingFlagsCreateInfo::default().binding_flags(&binding_flags);
+        let create_info = vk::DescriptorSetLayoutCreateInfo::default()
+            .bindings(&bindings)
+            .flags(if layout.is_bindless() {
+                vk::DescriptorSetLayoutCreateFlags::UPDATE_AFTER_BIND_POOL
+            } else {
+                vk::DescriptorSetLayoutCreateFlags::empty()
+            })
+            .push_next(&mut flags_info);
+        let set_layout = unsafe {
+            self.context
+                .device
+                .create_descriptor_set_layout(&create_info, None)
+                .expect("Failed to create descriptor set layout")
+        };
+        self.set_layouts.insert(layout.clone(), set_layout);
+        set_layout
+    }
+
+    fn build_pipeline(&mut self, desc: &PipelineDesc) -> Result<Pipeline, BackendError> {
+        let set_layouts: Vec<vk::DescriptorSetLayout> = desc
+            .bind_group_layouts
+            .iter()
+            .map(|layout| self.set_layout(layout))
+            .collect();
+        Pipeline::new(
+            &self.context.device,
+            self.render_pass,
+            vk_sample_count(self.samples),
+            desc,
+            &set_layouts,
+        )
+    }
+
+    fn cleanup_swapchain(&mut self) {
+        self.cleanup_framebuffers();
+        self.context.cleanup_swapchain_resources();
+    }
+
+    fn cleanup_framebuffers(&mut self) {
+        unsafe {
+            for framebuffer in self.framebuffers.drain(..) {
+                self.context.device.destroy_framebuffer(framebuffer, None);
+            }
+        }
+        self.attachments.cleanup(&self.context.device);
+    }
+
+    /// Destruye el render pass y los pipelines construidos sobre él.
+    fn cleanup_pipelines(&mut self) {
+        for (_, pipeline) in self.pipelines.values() {
+            pipeline.cleanup(&self.context.device);
+        }
+        unsafe {
+            self.context
+                .device
+                .destroy_render_pass(self.render_pass, None);
+        }
+    }
+
+    /// Recrea el swapchain, sus framebuffers y los attachments de profundidad y MSAA.
+    ///
+    /// El viewport y el scissor son dinámicos, así que los pipelines solo se
+    /// reconstruyen si cambia el formato del swapchain (y con él el render pass).
+    pub fn recreate_swapchain(&mut self) {
+        self.wait_idle();
+        let format = self.context.swapchain_format();
+        self.cleanup_swapchain();
+
+        self.context.recreate_swapchain_resources(self.size);
+        if self.context.swapchain_format() != format {
+            self.rebuild_render_pass();
+        }
+        self.create_framebuffers();
+    }
+
+    /// Crea los attachments propios y los framebuffers del swapchain actual.
+    fn create_framebuffers(&mut self) {
+        self.attachments = FrameAttachments::new(&self.context, self.depth_format, self.samples);
+        self.framebuffers = framebuffers::create_framebuffers(
+            &self.context.device,
+            self.render_pass,
+            self.context.swapchain_image_views(),
+            &self.attachments,
+            self.context.swapchain_extent(),
+        );
+    }
+
+    /// Recrea el render pass y todos los pipelines, que dependen de su formato y
+    /// de sus muestras por píxel.
+    fn rebuild_render_pass(&mut self) {
+        self.cleanup_pipelines();
+        self.render_pass = render_pass::create_render_pass(
+            &self.context.device,
+            self.context.swapchain_format(),
+            self.depth_format,
+            self.samples,
+        );
+        let ids: Vec<PipelineId> = self.pipelines.keys().copied().collect();
+        for id in ids {
+            let desc = self.pipelines[&id].0.clone();
+            // Ya se creó una vez con el mismo desc; solo cambia el render pass.
+            let pipeline = self
+                .build_pipeline(&desc)
+                .expect("Failed to rebuild pipeline");
+            self.pipelines.get_mut(&id).unwrap().1 = pipeline;
+        }
+    }
+
+    /// Ejecuta comandos de un solo uso (e.g. copias de staging) y espera a que terminen.
+    fn one_time_commands(&self, record: impl FnOnce(vk::CommandBuffer)) {
+        let device = &self.context.device;
+        let alloc_info = vk::CommandBufferAllocateInfo {
+            command_pool: self.context.command_pool,
+            level: vk::CommandBufferLevel::PRIMARY,
+            command_buffer_count: 1,
+            ..Default::default()
+        };
+        unsafe {
+            let command_buffer = device
+                .allocate_command_buffers(&alloc_info)
+                .expect("Failed to allocate command buffer")[0];
+            let begin_info = vk::CommandBufferBeginInfo {
+                flags: vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT,
+                ..Default::default()
+            };
+            device
+                .begin_command_buffer(command_buffer, &begin_info)
+                .expect("Failed to begin command buffer");
+            record(command_buffer);
+            device
+                .end_command_buffer(command_buffer)
+                .expect("Failed to end command buffer");
+
+            let submit_info = vk::SubmitInfo {
+                command_buffer_count: 1,
+                p_command_buffers: &command_buffer,
+                ..Default::default()
+            };
+            device
+                .queue_submit(self.context.queue, &[submit_info], vk::Fence::null())
+                .expect("Failed to submit queue");
+            device
+                .queue_wait_idle(self.context.queue)
+                .expect("Failed to wait for queue");
+            device.free_command_buffers(self.context.command_pool, &[command_buffer]);
+        }
+    }
+
+    /// Graba los comandos pendientes en el command buffer del frame.
+    fn record(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        image_index: u32,
+    ) -> Result<(), BackendError> {
+        let device = &self.context.device;
+        let extent = self.context.swapchain_extent();
+        let mut bound_pipeline: Option<&Pipeline> = None;
+        let mut scissor = vk::Rect2D::default();
+
+        for command in &self.pending {
+            unsafe {
+                match command {
+                    RenderCommand::BeginRenderPass(desc) => {
+                        if let RenderTarget::Texture(texture) = desc.target {
+                            return Err(BackendError::Unsupported(format!(
+                                "render pass into {:?}",
+                                texture
+                            )));
+                        }
+                        // Uno por attachment; el de la imagen resuelta se ignora.
+                        let clear_values = [
+                            vk::ClearValue {
+                                color: vk::ClearColorValue {
+                                    float32: desc.clear_color.unwrap_or([0.0, 0.0, 0.0, 1.0]),
+                                },
+                            },
+                            vk::ClearValue {
+                                depth_stencil: vk::ClearDepthStencilValue {
+                                    depth: 1.0,
+                                    stencil: 0,
+                                },
+                            },
+                            vk::ClearValue::default(),
+                        ];
+                        let render_pass_info = vk::RenderPassBeginInfo {
+                            render_pass: self.render_pass,
+                            framebuffer: self.framebuffers[image_index as usize],
+                            render_area: vk::Rect2D {
+                                offset: vk::Offset2D { x: 0, y: 0 },
+                                extent: self.context.swapchain_extent(),
+                            },
+                            clear_value_count: clear_values.len() as u32,
+                            p_clear_values: clear_values.as_ptr(),
+                            ..Default::default()
+                        };
+                        device.cmd_begin_render_pass(
+                            command_buffer,
+                            &render_pass_info,
+                            vk::SubpassContents::INLINE,
+                        );
+                        scissor = set_viewport(
+                            device,
+                            command_buffer,
+                            Viewport::full(extent.width, extent.height),
+                            extent,
+                        );
+                    }
+                    RenderCommand::EndRenderPass => device.cmd_end_render_pass(command_buffer),
+                    RenderCommand::BindPipeline(id) => {
+                        let (_, pipeline) = self
+                            .pipelines
+                            .get(id)
+                            .ok_or_else(|| BackendError::InvalidHandle(format!("{:?}", id)))?;
+                        device.cmd_bind_pipeline(
+                            command_buffer,
+                            vk::PipelineBindPoint::GRAPHICS,
+                            pipeline.pipeline,
+                        );
+                        bound_pipeline = Some(pipeline);
+                    }
+                    RenderCommand::BindVertexBuffer {
+                        slot,
+                        buffer,
+                        offset,
+                    } => {
+                        let buffer = self.buffer(*buffer)?;
+                        device.cmd_bind_vertex_buffers(
+                            command_buffer,
+                            *slot,
+                            &[buffer.buffer],
+                            &[*offset],
+                        );
+                    }
+                    RenderCommand::BindIndexBuffer {
+                        buffer,
+                        offset,
+                        format,
+                    } => {
+                        let buffer = self.buffer(*buffer)?;
+                        let index_type = match format {
+                            IndexFormat::U16 => vk::IndexType::UINT16,
+                            IndexFormat::U32 => vk::IndexType::UINT32,
+                        };
+                        device.cmd_bind_index_buffer(
+                            command_buffer,
+                            buffer.buffer,
+                            *offset,
+                            index_type,
+                        );
+                    }
+                    RenderCommand::BindGroup { index, group } => {
+                        let pipeline = bound_pipeline.ok_or_else(|| {
+                            BackendError::InvalidCommands(
+                                "bind group without a bound pipeline".into(),
+                            )
+                        })?;
+                        let (_, set) = self
+                            .bind_groups
+                            .get(group)
+                            .ok_or_else(|| BackendError::InvalidHandle(format!("{:?}", group)))?;
+                        device.cmd_bind_descriptor_sets(
+                            command_buffer,
+                            vk::PipelineBindPoint::GRAPHICS,
+                            pipeline.pipeline_layout,
+                            *index,
+                            &[*set],
+                            &[],
+                        );
+                    }
+                    RenderCommand::SetViewport(viewport) => {
+                        scissor = set_viewport(device, command_buffer, *viewport, extent);
+                    }
+                    RenderCommand::ClearColor(color) => {
+                        let attachments = [
+                            vk::ClearAttachment {
+                                aspect_mask: vk::ImageAspectFlags::COLOR,
+                                color_attachment: 0,
+                                clear_value: vk::ClearValue {
+                                    color: vk::ClearColorValue { float32: *color },
+                                },
+                            },
+                            vk::ClearAttachment {
+                                aspect_mask: render_pass::depth_aspect(self.depth_format),
+                                color_attachment: 0,
+                                clear_value: vk::ClearValue {
+                                    depth_stencil: vk::ClearDepthStencilValue {
+                                        depth: 1.0,
+                                        stencil: 0,
+                                    },
+                                },
+                            },
+                        ];
+                        let rect = vk::ClearRect {
+                            rect: scissor,
+                            base_array_layer: 0,
+                            layer_count: 1,
+                        };
+                        if scissor.extent.width > 0 && scissor.extent.height > 0 {
+                            device.cmd_clear_attachments(command_buffer, &attachments, &[rect]);
+                        }
+                    }
+                    RenderCommand::PushConstants { offset, data } => {
+                        let pipeline = bound_pipeline.ok_or_else(|| {
+                            BackendError::InvalidCommands(
+                                "push constants without a bound pipeline".into(),
+                            )
+                        })?;
+                        device.cmd_push_constants(
+                            command_buffer,
+                            pipeline.pipeline_layout,
+                            vk::ShaderStageFlags::ALL_GRAPHICS,
+                            *offset,
+                            data,
+                        );
+                    }
+                    RenderCommand::Draw {
+                        vertices,
+                        instances,
+                    } => device.cmd_draw(
+                        command_buffer,
+                        vertices.len() as u32,
+                        instances.len() as u32,
+                        vertices.start,
+                        instances.start,
+                    ),
+                    RenderCommand::DrawIndexed {
+                        indices,
+                        base_vertex,
+                        instances,
+                    } => device.cmd_draw_indexed(
+                        command_buffer,
+                        indices.len() as u32,
+                        instances.len() as u32,
+                        indices.start,
+                        *base_vertex,
+                        instances.start,
+                    ),
+                    RenderCommand::Barrier(barriers) => {
+                        self.record_barriers(command_buffer, barriers)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Graba `barriers` en un solo `vkCmdPipelineBarrier`. Las del swapchain se
+    /// ignoran: su render pass ya hace las transiciones.
+    fn record_barriers(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        barriers: &[ResourceBarrier],
+    ) -> Result<(), BackendError> {
+        let mut src_stage = vk::PipelineStageFlags::empty();
+        let mut dst_stage = vk::PipelineStageFlags::empty();
+        let mut image_barriers = Vec::new();
+        let mut buffer_barriers = Vec::new();
+        for barrier in barriers {
+            let (old_layout, src_access, src) = vk_usage(barrier.before);
+            let (new_layout, dst_access, dst) = vk_usage(barrier.after);
+            match barrier.resource {
+                BarrierResource::Swapchain => continue,
+                BarrierResource::Texture(id) => {
+                    let texture = self
+                        .textures
+                        .get(&id)
+                        .ok_or_else(|| BackendError::InvalidHandle(format!("{:?}", id)))?;
+                    image_barriers.push(vk::ImageMemoryBarrier {
+                        src_access_mask: src_access,
+                        dst_access_mask: dst_access,
+                        old_layout,
+                        new_layout,
+                        src_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+                        dst_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+                        image: texture.image,
+                        subresource_range: vk::ImageSubresourceRange {
+                            aspect_mask: aspect_mask(texture.desc.format),
+                            base_mip_level: 0,
+                            level_count: vk::REMAINING_MIP_LEVELS,
+                            base_array_layer: 0,
+                            layer_count: vk::REMAINING_ARRAY_LAYERS,
+                        },
+                        ..Default::default()
+                    });
+                }
+                BarrierResource::Buffer(id) => {
+                    buffer_barriers.push(vk::BufferMemoryBarrier {
+                        src_access_mask: src_access,
+                        dst_access_mask: dst_access,
+                        src_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+                        dst_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+                        buffer: self.buffer(id)?.buffer,
+                        offset: 0,
+                        size: vk::WHOLE_SIZE,
+                        ..Default::default()
+                    });
+                }
+            }
+            src_stage |= src;
+            dst_stage |= dst;
+        }
+        if image_barriers.is_empty() && buffer_barriers.is_empty() {
+            return Ok(());
+        }
+        unsafe {
+            self.context.device.cmd_pipeline_barrier(
+                command_buffer,
+                src_stage,
+                dst_stage,
+                vk::DependencyFlags::empty(),
+                &[],
+                &buffer_barriers,
+                &image_barriers,
+            );
+        }
+        Ok(())
+    }
+
+    fn buffer(&self, id: BufferId) -> Result<&VulkanBuffer, BackendError> {
+        self.buffers
+            .get(&id)
+            .ok_or_else(|| BackendError::InvalidHandle(format!("{:?}", id)))
+    }
+}
+
+/// Fija viewport y scissor y devuelve el scissor, recortado al destino.
+unsafe fn set_viewport(
+    device: &ash::Device,
+    command_buffer: vk::CommandBuffer,
+    viewport: Viewport,
+    extent: vk::Extent2D,
+) -> vk::Rect2D {
+    let min_x = viewport.x.floor().clamp(0.0, extent.width as f32);
+    let min_y = viewport.y.floor().clamp(0.0, extent.height as f32);
+    let max_x = (viewport.x + viewport.width)
+        .ceil()
+        .clamp(min_x, extent.width as f32);
+    let max_y = (viewport.y + viewport.height)
+        .ceil()
+        .clamp(min_y, extent.height as f32);
+    let scissor = vk::Rect2D {
+        offset: vk::Offset2D {
+            x: min_x as i32,
+            y: min_y as i32,
+        },
+        extent: vk::Extent2D {
+            width: (max_x - min_x) as u32,
+            height: (max_y - min_y) as u32,
+        },
+    };
+    let vk_viewport = vk::Viewport {
+        x: viewport.x,
+        y: viewport.y,
+        width: viewport.width,
+        height: viewport.height,
+        min_depth: 0.0,
+        max_depth: 1.0,
+    };
+    unsafe {
+        device.cmd_set_viewport(command_buffer, 0, &[vk_viewport]);
+        device.cmd_set_scissor(command_buffer, 0, &[scissor]);
+    }
+    scissor
+}
+
+/// Pool del que salen los descriptor sets de todos los bind groups.
+fn create_descriptor_pool(device: &ash::Device) -> vk::DescriptorPool {
+    const MAX_SETS: u32 = 1024;
+    let pool_sizes = [
+        vk::DescriptorPoolSize {
+            ty: vk::DescriptorType::UNIFORM_BUFFER,
+            descriptor_count: MAX_SETS,
+        },
+        vk::DescriptorPoolSize {
+            ty: vk::DescriptorType::SAMPLED_IMAGE,
+            descriptor_count: MAX_SETS,
+        },
+        vk::DescriptorPoolSize {
+            ty: vk::DescriptorType::SAMPLER,
+            descriptor_count: MAX_SETS,
+        },
+    ];
+    let create_info = vk::DescriptorPoolCreateInfo {
+        flags: vk::DescriptorPoolCreateFlags::FREE_DESCRIPTOR_SET,
+        max_sets: MAX_SETS,
+        pool_size_count: pool_sizes.len() as u32,
+        p_pool_sizes: pool_sizes.as_ptr(),
+        ..Default::default()
+    };
+    unsafe {
+        device
+            .create_descriptor_pool(&create_info, None)
+            .expect("Failed to create descriptor pool")
+    }
+}
+
+/// Pool de los grupos bindless, con sitio para unos pocos arrays grandes.
+fn create_bindless_pool(device: &ash::Device) -> vk::DescriptorPool {
+    const MAX_SETS: u32 = 16;
+    const MAX_TEXTURES: u32 = 1 << 16;
+    let pool_sizes = [
+        vk::DescriptorPoolSize {
+            ty: vk::DescriptorType::UNIFORM_BUFFER,
+            descriptor_count: MAX_SETS,
+        },
+        vk::DescriptorPoolSize {
+            ty: vk::DescriptorType::SAMPLED_IMAGE,
+            descriptor_count: MAX_TEXTURES,
+        },
+        vk::DescriptorPoolSize {
+            ty: vk::DescriptorType::SAMPLER,
+            descriptor_count: MAX_SETS,
+        },
+    ];
+    let create_info = vk::DescriptorPoolCreateInfo {
+        flags: vk::DescriptorPoolCreateFlags::FREE_DESCRIPTOR_SET
+            | vk::DescriptorPoolCreateFlags::UPDATE_AFTER_BIND,
+        max_sets: MAX_SETS,
+        pool_size_count: pool_sizes.len() as u32,
+        p_pool_sizes: pool_sizes.as_ptr(),
+        ..Default::default()
+    };
+    unsafe {
+        device
+            .create_descriptor_pool(&create_info, None)
+            .expect("Failed to create bindless descriptor pool")
+    }
+}
+
+fn vk_descriptor_type(ty: BindingType) -> vk::DescriptorType {
+    match ty {
+        BindingType::UniformBuffer => vk::DescriptorType::UNIFORM_BUFFER,
+        BindingType::Texture | BindingType::TextureArray { .. } => {
+            vk::DescriptorType::SAMPLED_IMAGE
+        }
+        BindingType::Sampler => vk::DescriptorType::SAMPLER,
+    }
+}
+
+fn vk_filter(filter: FilterMode) -> vk::Filter {
+    match filter {
+        FilterMode::Nearest => vk::Filter::NEAREST,
+        FilterMode::Linear => vk::Filter::LINEAR,
+    }
+}
+
+fn vk_address_mode(mode: AddressMode) -> vk::SamplerAddressMode {
+    match mode {
+        AddressMode::Repeat => vk::SamplerAddressMode::REPEAT,
+        AddressMode::MirroredRepeat => vk::SamplerAddressMode::MIRRORED_REPEAT,
+        AddressMode::ClampToEdge => vk::SamplerAddressMode::CLAMP_TO_EDGE,
+    }
+}
+
+fn vk_buffer_usage(usage: BufferUsage) -> vk::BufferUsageFlags {
+    // Todos los buffers pueden recibir subidas desde el ring de staging.
+    let mut flags = vk::BufferUsageFlags::TRANSFER_DST;
+    for (usage_flag, vk_flag) in [
+        (BufferUsage::VERTEX, vk::BufferUsageFlags::VERTEX_BUFFER),
+        (BufferUsage::INDEX, vk::BufferUsageFlags::INDEX_BUFFER),
+        (BufferUsage::UNIFORM, vk::BufferUsageFlags::UNIFORM_BUFFER),
+        (BufferUsage::STORAGE, vk::BufferUsageFlags::STORAGE_BUFFER),
+        (
+            BufferUsage::TRANSFER_SRC,
+            vk::BufferUsageFlags::TRANSFER_SRC,
+        ),
+        (
+            BufferUsage::TRANSFER_DST,
+            vk::BufferUsageFlags::TRANSFER_DST,
+        ),
+    ] {
+        if usage.contains(usage_flag) {
+            flags |= vk_flag;
+        }
+    }
+    flags
+}
+
+fn vk_texture_usage(usage: TextureUsage) -> vk::ImageUsageFlags {
+    let mut flags = vk::ImageUsageFlags::empty();
+    for (usage_flag, vk_flag) in [
+        (TextureUsage::SAMPLED, vk::ImageUsageFlags::SAMPLED),
+        (
+            TextureUsage::RENDER_TARGET,
+            vk::ImageUsageFlags::COLOR_ATTACHMENT,
+        ),
+        (
+            TextureUsage::DEPTH_STENCIL,
+            vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT,
+        ),
+        (
+            TextureUsage::TRANSFER_SRC,
+            vk::ImageUsageFlags::TRANSFER_SRC,
+        ),
+        (
+            TextureUsage::TRANSFER_DST,
+            vk::ImageUsageFlags::TRANSFER_DST,
+        ),
+    ] {
+        if usage.contains(usage_flag) {
+            flags |= vk_flag;
+        }
+    }
+    flags
+}
+
+pub(crate) fn vk_format(format: TextureFormat) -> vk::Format {
+    match format {
+        TextureFormat::Rgba8Unorm => vk::Format::R8G8B8A8_UNORM,
+        TextureFormat::Rgba8Srgb => vk::Format::R8G8B8A8_SRGB,
+        TextureFormat::Bgra8Unorm => vk::Format::B8G8R8A8_UNORM,
+        TextureFormat::Bgra8Srgb => vk::Format::B8G8R8A8_SRGB,
+        TextureFormat::Rgba16Float => vk::Format::R16G16B16A16_SFLOAT,
+        TextureFormat::Rgba32Float => vk::Format::R32G32B32A32_SFLOAT,
+        TextureFormat::Depth32Float => vk::Format::D32_SFLOAT,
+        TextureFormat::Depth24Stencil8 => vk::Format::D24_UNORM_S8_UINT,
+    }
+}
+
+/// Layout, accesos y etapas de un uso de recurso.
+fn vk_usage(usage: ResourceUsage) -> (vk::ImageLayout, vk::AccessFlags, vk::PipelineStageFlags) {
+    match usage {
+        ResourceUsage::Undefined => (
+            vk::ImageLayout::UNDEFINED,
+            vk::AccessFlags::empty(),
+            vk::PipelineStageFlags::TOP_OF_PIPE,
+        ),
+        ResourceUsage::ColorAttachment => (
+            vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+            vk::AccessFlags::COLOR_ATTACHMENT_READ | vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+            vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+        ),
+        ResourceUsage::DepthStencilAttachment => (
+            vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL,
+            vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_READ
+                | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
+            vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS
+                | vk::PipelineStageFlags::LATE_FRAGMENT_TESTS,
+        ),
+        ResourceUsage::ShaderRead => (
+            vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+            vk::AccessFlags::SHADER_READ | vk::AccessFlags::UNIFORM_READ,
+            vk::PipelineStageFlags::VERTEX_SHADER
+                | vk::PipelineStageFlags::FRAGMENT_SHADER
+                | vk::PipelineStageFlags::COMPUTE_SHADER,
+        ),
+        ResourceUsage::ShaderWrite => (
+            vk::ImageLayout::GENERAL,
+            vk::AccessFlags::SHADER_READ | vk::AccessFlags::SHADER_WRITE,
+            vk::PipelineStageFlags::FRAGMENT_SHADER | vk::PipelineStageFlags::COMPUTE_SHADER,
+        ),
+        ResourceUsage::VertexInput => (
+            vk::ImageLayout::UNDEFINED,
+            vk::AccessFlags::VERTEX_ATTRIBUTE_READ | vk::AccessFlags::INDEX_READ,
+            vk::PipelineStageFlags::VERTEX_INPUT,
+        ),
+        ResourceUsage::TransferSrc => (
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            vk::AccessFlags::TRANSFER_READ,
+            vk::PipelineStageFlags::TRANSFER,
+        ),
+        ResourceUsage::TransferDst => (
+            vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            vk::AccessFlags::TRANSFER_WRITE,
+            vk::PipelineStageFlags::TRANSFER,
+        ),
+        ResourceUsage::Present => (
+            vk::ImageLayout::PRESENT_SRC_KHR,
+            vk::AccessFlags::empty(),
+            vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+        ),
+    }
+}
+
+fn aspect_mask(format: TextureFormat) -> vk::ImageAspectFlags {
+    match format {
+        TextureFormat::Depth32Float => vk::ImageAspectFlags::DEPTH,
+        TextureFormat::Depth24Stencil8 => {
+            vk::ImageAspectFlags::DEPTH | vk::ImageAspectFlags::STENCIL
+        }
+        _ => vk::ImageAspectFlags::COLOR,
+    }
+}
+
+impl RenderBackend for VulkanBackend {
+    fn name(&self) -> &'static str {
+        "vulkan"
+    }
+
+    fn create_buffer(&mut self, desc: &BufferDesc) -> Result<BufferId, BackendError> {
+        let buffer = create_buffer_in(
+            &self.context.device,
+            &mut self.allocator,
+            desc.size,
+            vk_buffer_usage(desc.usage),
+            desc.location,
+        )?;
+        let id = BufferId(self.next_id());
+        self.buffers.insert(id, buffer);
+        Ok(id)
+    }
+
+    fn write_buffer(
+        &mut self,
+        buffer: BufferId,
+        offset: u64,
+        data: &[u8],
+    ) -> Result<(), BackendError> {
+        let target = self.buffer(buffer)?;
+        if offset + data.len() as u64 > target.size {
+            return Err(BackendError::InvalidCommands(format!(
+                "write of {} bytes at {} overflows {:?} ({} bytes)",
+                data.len(),
+                offset,
+                buffer,
+                target.size
+            )));
+        }
+        if target.mapped.is_null() {
+            // Memoria solo de GPU: se copia desde el ring de staging.
+            return self.upload_buffer(buffer, offset, data).map(|_| ());
+        }
+        // SAFETY: la memoria está mapeada durante toda la vida del buffer y el rango
+        // se ha comprobado arriba.
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                data.as_ptr(),
+                target.mapped.add(offset as usize),
+                data.len(),
+            );
+        }
+        Ok(())
+    }
+
+    fn destroy_buffer(&mut self, buffer: BufferId) {
+        if let Some(buffer) = self.buffers.remove(&buffer) {
+            self.transfer.discard_buffer(buffer.buffer);
+            self.retire(Retired::Buffer(buffer));
+        }
+    }
+
+    fn create_texture(&mut self, desc: &TextureDesc) -> Result<TextureId, BackendError> {
+        let device = &self.context.device;
+        let image_info = vk::ImageCreateInfo {
+            image_type: vk::ImageType::TYPE_2D,
+            format: vk_format(desc.format),
+            extent: vk::Extent3D {
+                width: desc.width,
+                height: desc.height,
+                depth: 1,
+            },
+            mip_levels: desc.mip_levels.max(1),
+            array_layers: 1,
+            samples: vk::SampleCountFlags::TYPE_1,
+            tiling: vk::ImageTiling::OPTIMAL,
+            usage: vk_texture_usage(desc.usage),
+            sharing_mode: vk::SharingMode::EXCLUSIVE,
+            initial_layout: vk::ImageLayout::UNDEFINED,
+            ..Default::default()
+        };
+        let (image, allocation, view) = unsafe {
+            let image = device
+                .create_image(&image_info, None)
+                .map_err(|error| BackendError::Device(error.to_string()))?;
+            let requirements = device.get_image_memory_requirements(image);
+            let allocation = match self.allocator.allocate(&AllocationDesc {
+                size: requirements.size,
+                alignment: requirements.alignment,
+                memory_type_bits: requirements.memory_type_bits,
+                location: MemoryLocation::GpuOnly,
+                kind: ResourceKind::Image,
+                dedicated: false,
+            }) {
+                Ok(allocation) => allocation,
+                Err(error) => {
+                    device.destroy_image(image, None);
+                    return Err(error.into());
+                }
+            };
+            device
+                .bind_image_memory(image, allocation.memory(), allocation.offset())
+                .expect("Failed to bind image memory");
+            let view_info = vk::ImageViewCreateInfo {
+                image,
+                view_type: vk::ImageViewType::TYPE_2D,
+                format: image_info.format,
+                subresource_range: vk::ImageSubresourceRange {
+                    aspect_mask: aspect_mask(desc.format),
+                    base_mip_level: 0,
+                    level_count: image_info.mip_levels,
+                    base_array_layer: 0,
+                    layer_count: 1,
+                },
+                ..Default::default()
+            };
+            let view = device
+                .create_image_view(&view_info, None)
+                .expect("Failed to create image view");
+            (image, allocation, view)
+        };
+
+        let id = TextureId(self.next_id());
+        self.textures.insert(
+            id,
+            VulkanTexture {
+                desc: desc.clone(),
+                image,
+                allocation,
+                view,
+            },
+        );
+        Ok(id)
+    }
+
+    fn write_texture(
+        &mut self,
+        texture: TextureId,
+        mip_level: u32,
+        data: &[u8],
+    ) -> Result<(), BackendError> {
+        // Los frames esperan a las subidas, así que no hace falta esperar aquí.
+        self.upload_texture(texture, mip_level, data).map(|_| ())
+    }
+
+    fn upload_buffer(
+        &mut self,
+        buffer: BufferId,
+        offset: u64,
+        data: &[u8],
+    ) -> Result<UploadTicket, BackendError> {
+        self.stage_upload(UploadTarget::Buffer { buffer, offset }, data)
+    }
+
+    fn upload_texture(
+        &mut self,
+        texture: TextureId,
+        mip_level: u32,
+        data: &[u8],
+    ) -> Result<UploadTicket, BackendError> {
+        self.stage_upload(UploadTarget::Texture { texture, mip_level }, data)
+    }
+
+    /// Graba las copias pendientes en un command buffer de la cola de
+    /// transferencias y lo envía sin esperar.
+    fn flush_uploads(&mut self) -> Result<(), BackendError> {
+        let Some(batch) = self.uploads.take_batch() else {
+            return Ok(());
+        };
+        let device = &self.context.device;
+        let staging = self.staging().buffer;
+        let command_buffer = self.transfer.begin(device);
+        for copy in &batch.copies {
+            // Los recursos destruidos después de encolar la subida se saltan.
+            match copy.target {
+                UploadTarget::Buffer { buffer, offset } => {
+                    let Some(target) = self.buffers.get(&buffer) else {
+                        continue;
+                    };
+                    let region = vk::BufferCopy {
+                        src_offset: copy.staging_offset,
+                        dst_offset: offset,
+                        size: copy.size,
+                    };
+                    self.transfer.copy_buffer(
+                        device,
+                        command_buffer,
+                        staging,
+                        target.buffer,
+                        region,
+                    );
+                }
+                UploadTarget::Texture { texture, mip_level } => {
+                    let Some(target) = self.textures.get(&texture) else {
+                        continue;
+                    };
+                    let region = vk::BufferImageCopy {
+                        buffer_offset: copy.staging_offset,
+                        image_subresource: vk::ImageSubresourceLayers {
+                            aspect_mask: aspect_mask(target.desc.format),
+                            mip_level,
+                            base_array_layer: 0,
+                            layer_count: 1,
+                        },
+                        image_extent: vk::Extent3D {
+                            width: (target.desc.width >> mip_level).max(1),
+                            height: (target.desc.height >> mip_level).max(1),
+                            depth: 1,
+                        },
+                        ..Default::default()
+                    };
+                    self.transfer
+                        .copy_image(device, command_buffer, staging, target.image, region);
+                }
+            }
+        }
+        self.transfer.submit(device, command_buffer, batch.ticket);
+        Ok(())
+    }
+
+    fn upload_complete(&mut self, ticket: UploadTicket) -> bool {
+        self.retire_uploads();
+        self.uploads.is_complete(ticket)
+    }
+
+    fn upload_stats(&self) -> UploadStats {
+        self.uploads.stats()
+    }
+
+    fn destroy_texture(&mut self, texture: TextureId) {
+        if let Some(texture) = self.textures.remove(&texture) {
+            self.transfer.discard_image(texture.image);
+            self.retire(Retired::Texture(texture));
+        }
+    }
+
+    fn create_pipeline(&mut self, desc: &PipelineDesc) -> Result<PipelineId, BackendError> {
+        desc.validate()?;
+        let pipeline = self.build_pipeline(desc)?;
+        let id = PipelineId(self.next_id());
+        self.pipelines.insert(id, (desc.clone(), pipeline));
+        Ok(id)
+    }
+
+    fn destroy_pipeline(&mut self, pipeline: PipelineId) {
+        if let Some((_, pipeline)) = self.pipelines.remove(&pipeline) {
+            self.retire(Retired::Pipeline(pipeline));
+        }
+    }
+
+    fn replace_pipeline(
+        &mut self,
+        pipeline: PipelineId,
+        desc: &PipelineDesc,
+    ) -> Result<(), BackendError> {
+        desc.validate()?;
+        if !self.pipelines.contains_key(&pipeline) {
+            return Err(BackendError::InvalidHandle(format!("{:?}", pipeline)));
+        }
+        let replacement = self.build_pipeline(desc)?;
+        let (_, previous) = self
+            .pipelines
+            .insert(pipeline, (desc.clone(), replacement))
+            .expect("pipeline exists");
+        // Los frames en vuelo pueden estar usando el anterior.
+        self.retire(Retired::Pipeline(previous));
+        Ok(())
+    }
+
+    fn create_bind_group(&mut self, desc: &BindGroupDesc) -> Result<BindGroupId, BackendError> {
+        desc.validate()?;
+        let descriptor_pool = if desc.layout.is_bindless() {
+            if !self.context.features.bindless {
+                return Err(BackendError::Unsupported(
+                    "texture arrays need descriptor indexing".into(),
+                ));
+            }
+            self.bindless_pool
+        } else {
+            self.descriptor_pool
+        };
+        let set_layout = self.set_layout(&desc.layout);
+        let alloc_info = vk::DescriptorSetAllocateInfo {
+            descriptor_pool,
+            descriptor_set_count: 1,
+            p_set_layouts: &set_layout,
+            ..Default::default()
+        };
+        let set = unsafe { self.context.device.allocate_descriptor_sets(&alloc_info) }
+            .map_err(|error| BackendError::Device(error.to_string()))?[0];
+        if let Err(error) = self.write_descriptors(set, &desc.entries) {
+            unsafe {
+                self.context
+                    .device
+                    .free_descriptor_sets(descriptor_pool, &[set])
+                    .expect("Failed to free descriptor set");
+            }
+            return Err(error);
+        }
+
+        let id = BindGroupId(self.next_id());
+        self.bind_groups.insert(id, (desc.layout.clone(), set));
+        Ok(id)
+    }
+
+    fn update_bind_group(
+        &mut self,
+        group: BindGroupId,
+        entries: &[BindGroupEntry],
+    ) -> Result<(), BackendError> {
+        let (layout, set) = self
+            .bind_groups
+            .get(&group)
+            .ok_or_else(|| BackendError::InvalidHandle(format!("{:?}", group)))?;
+        layout.check_update(entries)?;
+        self.write_descriptors(*set, entries)
+    }
+
+    fn destroy_bind_group(&mut self, group: BindGroupId) {
+        if let Some((layout, set)) = self.bind_groups.remove(&group) {
+            let descriptor_pool = if layout.is_bindless() {
+                self.bindless_pool
+            } else {
+                self.descriptor_pool
+            };
+            self.retire(Retired::BindGroup(descriptor_pool, set));
+        }
+    }
+
+    fn create_sampler(&mut self, desc: &SamplerDesc) -> Result<SamplerId, BackendError> {
+        desc.validate()?;
+        let features = self.context.features;
+        let anisotropy = desc.max_anisotropy.min(features.max_anisotropy);
+        let [address_mode_u, address_mode_v, address_mode_w] =
+            desc.address_mode.map(vk_address_mode);
+        let create_info = vk::SamplerCreateInfo {
+            mag_filter: vk_filter(desc.mag_filter),
+            min_filter: vk_filter(desc.min_filter),
+            mipmap_mode: match desc.mipmap_filter {
+                FilterMode::Nearest => vk::SamplerMipmapMode::NEAREST,
+                FilterMode::Linear => vk::SamplerMipmapMode::LINEAR,
+            },
+            address_mode_u,
+            address_mode_v,
+            address_mode_w,
+            anisotropy_enable: (features.sampler_anisotropy && anisotropy > 1.0) as vk::Bool32,
+            max_anisotropy: anisotropy,
+            min_lod: desc.lod_min,
+            max_lod: desc.lod_max,
+            ..Default::default()
+        };
+        let sampler = unsafe { self.context.device.create_sampler(&create_info, None) }
+            .map_err(|error| BackendError::Device(error.to_string()))?;
+        let id = SamplerId(self.next_id());
+        self.samplers.insert(id, sampler);
+        Ok(id)
+    }
+
+    fn destroy_sampler(&mut self, sampler: SamplerId) {
+        if let Some(sampler) = self.samplers.remove(&sampler) {
+            self.retire(Retired::Sampler(sampler));
+        }
+    }
+
+    fn begin_frame(&mut self) -> Result<FrameInfo, BackendError> {
+        self.retire_uploads();
+        let context = &mut self.context;
+        let current_frame = self.current_frame;
+
+        unsafe {
+            // 1. Esperar a que el frame que vamos a usar esté disponible (su fence).
+            context
+                .device
+                .wait_for_fences(&[context.in_flight_fences[current_frame]], true, u64::MAX)
+                .expect("Failed to wait for fence");
+
+            // 2. Adquirir la siguiente imagen del swapchain.
+            let swapchain_loader = swapchain::Device::new(&context.instance, &context.device);
+            let result = swapchain_loader.acquire_next_image(
+                context.swapchain,
+                u64::MAX,
+                context.image_available_semaphores[current_frame], // Señalizar este semáforo cuando la imagen esté lista.
+                vk::Fence::null(),
+            );
+
+            let image_index = match result {
+                Ok((index, _)) => index,
+                Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => {
+                    self.recreate_swapchain();
+                    return Err(BackendError::OutOfDate);
+                }
+                Err(error) => panic!("Failed to acquire swapchain image: {}", error),
+            };
+
+            // Comprobar si un frame anterior está usando esta imagen y esperar si es así.
+            if context.images_in_flight[image_index as usize] != vk::Fence::null() {
+                context
+                    .device
+                    .wait_for_fences(
+                        &[context.images_in_flight[image_index as usize]],
+                        true,
+                        u64::MAX,
+                    )
+                    .unwrap();
+            }
+            // Marcar la imagen como en uso por este frame.
+            context.images_in_flight[image_index as usize] =
+                context.in_flight_fences[current_frame];
+
+            self.image_index = Some(image_index);
+        }
+
+        // Con su fence, han terminado todos los frames hasta el que usó este slot.
+        self.release_retired((self.frame + 1).saturating_sub(MAX_FRAMES_IN_FLIGHT as u64));
+
+        let extent = self.context.swapchain_extent();
+        let info = FrameInfo {
+            frame: self.frame,
+            width: extent.width,
+            height: extent.height,
+        };
+        self.frame += 1;
+        Ok(info)
+    }
+
+    fn submit(&mut self, commands: CommandList) -> Result<(), BackendError> {
+        if self.image_index.is_none() {
+            return Err(BackendError::InvalidCommands(
+                "submit outside of a frame".into(),
+            ));
+        }
+        commands.validate().map_err(BackendError::InvalidCommands)?;
+        self.pending.extend(commands.commands().iter().cloned());
+        Ok(())
+    }
+
+    fn present(&mut self) -> Result<(), BackendError> {
+        let image_index = self
+            .image_index
+            .take()
+            .ok_or_else(|| BackendError::InvalidCommands("present outside of a frame".into()))?;
+        let current_frame = self.current_frame;
+        // Las subidas encoladas durante el frame se envían antes que él.
+        let flushed = self.flush_uploads();
+
+        let context = &self.context;
+        let device = &context.device;
+        let command_buffer = context.command_buffers[current_frame];
+        assert!(
+            command_buffer != vk::CommandBuffer::null(),
+            "Command buffer is null!"
+        );
+
+        unsafe {
+            // 3. Grabar el command buffer.
+            let begin_info = vk::CommandBufferBeginInfo {
+                flags: vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT,
+                ..Default::default()
+            };
+            device
+                .begin_command_buffer(command_buffer, &begin_info)
+                .expect("Failed to begin command buffer");
+            self.transfer.record_acquires(device, command_buffer);
+            let recorded = flushed.and_then(|()| self.record(command_buffer, image_index));
+            if recorded.is_err() {
+                // La imagen adquirida se presenta igualmente, sin contenido, para
+                // consumir su semáforo; el error se devuelve después.
+                device
+                    .reset_command_buffer(command_buffer, vk::CommandBufferResetFlags::empty())
+                    .expect("Failed to reset command buffer");
+                device
+                    .begin_command_buffer(command_buffer, &begin_info)
+                    .expect("Failed to begin command buffer");
+                self.transfer.record_acquires(device, command_buffer);
+                let to_present = vk::ImageMemoryBarrier {
+                    old_layout: vk::ImageLayout::UNDEFINED,
+                    new_layout: vk::ImageLayout::PRESENT_SRC_KHR,
+                    src_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+                    dst_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+                    image: context.swapchain_images[image_index as usize],
+                    subresource_range: vk::ImageSubresourceRange {
+                        aspect_mask: vk::ImageAspectFlags::COLOR,
+                        base_mip_level: 0,
+                        level_count: 1,
+                        base_array_layer: 0,
+                        layer_count: 1,
+                    },
+                    ..Default::default()
+                };
+                device.cmd_pipeline_barrier(
+                    command_buffer,
+                    vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+                    vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+                    vk::DependencyFlags::empty(),
+                    &[],
+                    &[],
+                    &[to_present],
+                );
+            }
+            device
+                .end_command_buffer(command_buffer)
+                .expect("Failed to end command buffer");
+            self.pending.clear();
+
+            // 4. Enviar el command buffer a la GPU.
+            // El frame espera además a las subidas enviadas hasta ahora; el valor
+            // del semaphore binario de la imagen se ignora.
+            let wait_semaphores = [
+                context.image_available_semaphores[current_frame],
+                self.transfer.timeline,
+            ];
+            let wait_stages = [
+                vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+                vk::PipelineStageFlags::ALL_COMMANDS,
+            ];
+            let wait_values = [0, self.uploads.last_submitted().0];
+            let signal_semaphores = [context.render_finished_semaphores[current_frame]];
+            let mut timeline_info =
+                vk::TimelineSemaphoreSubmitInfo::default().wait_semaphore_values(&wait_values);
+
+            let submit_info = vk::SubmitInfo {
+                wait_semaphore_count: wait_semaphores.len() as u32,
+                p_wait_semaphores: wait_semaphores.as_ptr(),
+                p_wait_dst_stage_mask: wait_stages.as_ptr(),
+                command_buffer_count: 1,
+                p_command_buffers: &command_buffer,
+                signal_semaphore_count: signal_semaphores.len() as u32,
+                p_signal_semaphores: signal_semaphores.as_ptr(),
+                ..Default::default()
+            }
+            .push_next(&mut timeline_info);
+
+            device
+                .reset_fences(&[context.in_flight_fences[current_frame]])
+                .unwrap();
+            device
+                .queue_submit(
+                    context.queue,
+                    &[submit_info],
+                    context.in_flight_fences[current_frame],
+                )
+                .expect("Failed to submit queue");
+            self.transfer.clear_acquires();
+            self.current_frame = (current_frame + 1) % MAX_FRAMES_IN_FLIGHT;
+
+            // 5. Presentar la imagen en pantalla.
+            let context = &self.context;
+            let present_info = vk::PresentInfoKHR {
+                wait_semaphore_count: 1,
+                p_wait_semaphores: signal_semaphores.as_ptr(), // Esperar a que el renderizado termine.
+                swapchain_count: 1,
+                p_swapchains: &context.swapchain,
+                p_image_indices: &image_index,
+                ..Default::default()
+            };
+
+            let swapchain_loader = swapchain::Device::new(&context.instance, &context.device);
+            let presented = match swapchain_loader.queue_present(context.queue, &present_info) {
+                Ok(false) => Ok(()),
+                Ok(true) | Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => {
+                    self.recreate_swapchain();
+                    Err(BackendError::OutOfDate)
+                }
+                Err(error) => panic!("Failed to present swapchain image: {}", error),
+            };
+            recorded.and(presented)
+        }
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        self.size = (width, height);
+        self.recreate_swapchain();
+    }
+
+    fn surface_size(&self) -> (u32, u32) {
+        let extent = self.context.swapchain_extent();
+        (extent.width, extent.height)
+    }
+
+    fn set_sample_count(&mut self, requested: SampleCount) -> SampleCount {
+        let samples = render_pass::select_sample_count(
+            &self.context.instance,
+            self.context.physical_device,
+            requested,
+        );
+        if samples != self.samples {
+            self.wait_idle();
+            self.cleanup_framebuffers();
+            self.samples = samples;
+            self.rebuild_render_pass();
+            self.create_framebuffers();
+        }
+        samples
+    }
+
+    fn sample_count(&self) -> SampleCount {
+        self.samples
+    }
+
+    fn wait_idle(&mut self) {
+        unsafe {
+            self.context.device.device_wait_idle().unwrap();
+        }
+        self.retire_uploads();
+        self.release_retired(u64::MAX);
+    }
+
+    fn cleanup(&mut self) {
+        // Esperar a que la GPU termine todas las operaciones pendientes antes de limpiar.
+        self.wait_idle();
+        let buffers: Vec<BufferId> = self.buffers.keys().copied().collect();
+        buffers
+            .into_iter()
+            .for_each(|buffer| self.destroy_buffer(buffer));
+        let textures: Vec<TextureId> = self.textures.keys().copied().collect();
+        textures
+            .into_iter()
+            .for_each(|texture| self.destroy_texture(texture));
+        self.release_retired(u64::MAX);
+        if let Some(staging) = self.upload_staging.take() {
+            self.destroy_host_buffer(staging);
+        }
+        self.transfer.cleanup(&self.context.device);
+        self.allocator.cleanup();
+        self.cleanup_swapchain();
+        self.cleanup_pipelines();
+        self.pipelines.clear();
+        self.bind_groups.clear();
+        unsafe {
+            let device = &self.context.device;
+            for (_, sampler) in self.samplers.drain() {
+                device.destroy_sampler(sampler, None);
+            }
+            device.destroy_descriptor_pool(self.descriptor_pool, None);
+            if self.bindless_pool != vk::DescriptorPool::null() {
+                device.destroy_descriptor_pool(self.bindless_pool, None);
+            }
+            for (_, set_layout) in self.set_layouts.drain() {
+                device.destroy_descriptor_set_layout(set_layout, None);
+            }
+        }
+        self.context.cleanup();
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
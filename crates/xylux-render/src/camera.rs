@@ -0,0 +1,489 @@
+//! # Módulo Camera
+//!
+//! Cámaras de la escena y los datos que reciben los shaders de cada una.
+//!
+//! Una entidad con `Camera` y `Transform` dibuja la escena vista desde su
+//! `Transform` en su rectángulo de la pantalla. Con varias cámaras (pantalla
+//! dividida, minimapa) cada una se dibuja por separado, en orden de `order`. Como
+//! aún no hay jerarquía de transformaciones, el `Transform` se interpreta en
+//! coordenadas de mundo.
+
+use crate::backend::{BindGroupLayout, BindingLayout, BindingType, Viewport};
+use crate::renderer::culling::Frustum;
+use glam::{Mat4, Vec3};
+use xylux_ecs::{Component, Entity, Query, Transform, World};
+
+/// Proyección de una cámara.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Projection {
+    /// Perspectiva con campo de visión vertical `fov_y` en radianes.
+    Perspective { fov_y: f32 },
+    /// Ortográfica que abarca `height` unidades en vertical.
+    Orthographic { height: f32 },
+}
+
+/// Rectángulo normalizado de la pantalla, con el origen arriba a la izquierda.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ViewportRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl ViewportRect {
+    /// La pantalla completa.
+    pub const FULL: Self = Self::new(0.0, 0.0, 1.0, 1.0);
+
+    pub const fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Rectángulo en píxeles de un destino de `width` × `height`, redondeado para
+    /// que los rectángulos vecinos no se solapen ni dejen huecos.
+    pub fn to_pixels(&self, width: u32, height: u32) -> Viewport {
+        let (width, height) = (width as f32, height as f32);
+        let (x0, y0) = ((self.x * width).round(), (self.y * height).round());
+        let x1 = ((self.x + self.width) * width).round();
+        let y1 = ((self.y + self.height) * height).round();
+        Viewport {
+            x: x0,
+            y: y0,
+            width: x1 - x0,
+            height: y1 - y0,
+        }
+    }
+}
+
+impl Default for ViewportRect {
+    fn default() -> Self {
+        Self::FULL
+    }
+}
+
+/// Componente de cámara. Mira hacia `-Z` de su `Transform`, con `+Y` hacia arriba.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+    pub projection: Projection,
+    pub near: f32,
+    pub far: f32,
+    /// Parte de la pantalla en la que se dibuja.
+    pub viewport: ViewportRect,
+    /// Las cámaras se dibujan de menor a mayor `order`; las de mayor orden quedan
+    /// encima (e.g. un minimapa).
+    pub order: i32,
+    /// Color con el que se limpia el viewport antes de dibujar; `None` dibuja
+    /// sobre lo que haya.
+    pub clear_color: Option<[f32; 4]>,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self::perspective(60f32.to_radians(), 0.1, 1000.0)
+    }
+}
+
+impl Component for Camera {}
+
+impl Camera {
+    pub fn perspective(fov_y: f32, near: f32, far: f32) -> Self {
+        Self {
+            projection: Projection::Perspective { fov_y },
+            near,
+            far,
+            viewport: ViewportRect::FULL,
+            order: 0,
+            clear_color: Some([0.0, 0.0, 0.0, 1.0]),
+        }
+    }
+
+    pub fn orthographic(height: f32, near: f32, far: f32) -> Self {
+        Self {
+            projection: Projection::Orthographic { height },
+            ..Self::perspective(0.0, near, far)
+        }
+    }
+
+    pub fn with_viewport(mut self, viewport: ViewportRect) -> Self {
+        self.viewport = viewport;
+        self
+    }
+
+    pub fn with_order(mut self, order: i32) -> Self {
+        self.order = order;
+        self
+    }
+
+    pub fn with_clear_color(mut self, clear_color: Option<[f32; 4]>) -> Self {
+        self.clear_color = clear_color;
+        self
+    }
+
+    /// Matriz de proyección para una relación de aspecto `aspect` (ancho / alto).
+    ///
+    /// Lleva la profundidad a `[0, 1]` (de `near` a `far`) e invierte `Y`, porque
+    /// en Vulkan la `y` de clip space crece hacia abajo.
+    pub fn projection_matrix(&self, aspect: f32) -> Mat4 {
+        let projection = match self.projection {
+            Projection::Perspective { fov_y } => {
+                Mat4::perspective_rh(fov_y, aspect, self.near, self.far)
+            }
+            Projection::Orthographic { height } => {
+                let (half_width, half_height) = (height * aspect * 0.5, height * 0.5);
+                Mat4::orthographic_rh(
+                    -half_width,
+                    half_width,
+                    -half_height,
+                    half_height,
+                    self.near,
+                    self.far,
+                )
+            }
+        };
+        Mat4::from_scale(Vec3::new(1.0, -1.0, 1.0)) * projection
+    }
+}
+
+/// Matriz de vista de una cámara colocada en `transform`.
+pub fn view_matrix(transform: &Transform) -> Mat4 {
+    Mat4::from_rotation_translation(transform.rotation, transform.position).inverse()
+}
+
+/// Uniform buffer de cámara (`set = 0, binding = 0` del pipeline de mallas), con la
+/// disposición std140 de `CameraUniform` en `shaders/mesh.vert`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CameraUniform {
+    pub view: Mat4,
+    pub projection: Mat4,
+    pub view_projection: Mat4,
+    /// Posición de la cámara en el mundo.
+    pub position: Vec3,
+}
+
+impl CameraUniform {
+    /// Tamaño en bytes: tres `mat4` y un `vec4`.
+    pub const SIZE: u64 = 3 * 64 + 16;
+    /// Offset en bytes de `view_projection`.
+    pub const VIEW_PROJECTION_OFFSET: usize = 128;
+
+    /// Cámara que no transforma: las posiciones del mundo ya están en clip space.
+    pub const IDENTITY: Self = Self {
+        view: Mat4::IDENTITY,
+        projection: Mat4::IDENTITY,
+        view_projection: Mat4::IDENTITY,
+        position: Vec3::ZERO,
+    };
+
+    pub fn new(camera: &Camera, transform: &Transform, aspect: f32) -> Self {
+        let view = view_matrix(transform);
+        let projection = camera.projection_matrix(aspect);
+        Self {
+            view,
+            projection,
+            view_projection: projection * view,
+            position: transform.position,
+        }
+    }
+
+    /// Layout del bind group que contiene el uniform.
+    pub fn bind_group_layout() -> BindGroupLayout {
+        BindGroupLayout {
+            bindings: vec![BindingLayout {
+                binding: 0,
+                ty: BindingType::UniformBuffer,
+            }],
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        [self.view, self.projection, self.view_projection]
+            .iter()
+            .flat_map(|matrix| matrix.to_cols_array())
+            .chain(self.position.extend(1.0).to_array())
+            .flat_map(|value| value.to_le_bytes())
+            .collect()
+    }
+}
+
+/// Una cámara lista para dibujar en un frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CameraView {
+    /// Entidad de la cámara; `None` para la cámara implícita.
+    pub entity: Option<Entity>,
+    pub uniform: CameraUniform,
+    /// Rectángulo del destino en píxeles.
+    pub viewport: Viewport,
+    pub clear_color: Option<[f32; 4]>,
+    /// Frustum para el culling; la cámara implícita no descarta nada.
+    pub frustum: Option<Frustum>,
+}
+
+impl CameraView {
+    /// Recoge las entidades con `Camera` y `Transform` de un destino de
+    /// `width` × `height`, en orden de dibujo.
+    ///
+    /// Si no hay ninguna devuelve una cámara implícita con `CameraUniform::IDENTITY`
+    /// que cubre todo el destino, de modo que las escenas sin cámara siguen
+    /// dibujándose en clip space.
+    pub fn collect(world: &mut World, width: u32, height: u32) -> Vec<Self> {
+        let mut cameras: Vec<(i32, Self)> = Query::<(Entity, &Camera, &Transform)>::new(world)
+            .iter()
+            .map(|(entity, camera, transform)| {
+                let viewport = camera.viewport.to_pixels(width, height);
+                let aspect = if viewport.height > 0.0 {
+                    viewport.width / viewport.height
+                } else {
+                    1.0
+                };
+                let uniform = CameraUniform::new(camera, transform, aspect);
+                let view = Self {
+                    entity: Some(entity),
+                    uniform,
+                    viewport,
+                    clear_color: camera.clear_color,
+                    frustum: Some(Frustum::from_view_projection(uniform.view_projection)),
+                };
+                (camera.order, view)
+            })
+            .collect();
+        if cameras.is_empty() {
+            return vec![Self {
+                entity: None,
+                uniform: CameraUniform::IDENTITY,
+                viewport: Viewport::full(width, height),
+                clear_color: None,
+                frustum: None,
+            }];
+        }
+        cameras.sort_by_key(|(order, view)| (*order, view.entity));
+        cameras.into_iter().map(|(_, view)| view).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{BindingResource, NullBackend, RenderCommand, SoftwareBackend, Viewport};
+    use crate::material::Material;
+    use crate::mesh::{Mesh, MeshRenderer};
+    use crate::renderer::Renderer;
+    use glam::Quat;
+    use xylux_ecs::World;
+
+    fn project(matrix: Mat4, point: Vec3) -> Vec3 {
+        let clip = matrix * point.extend(1.0);
+        clip.truncate() / clip.w
+    }
+
+    #[test]
+    fn test_projection_maps_near_far_and_flips_y() {
+        let camera = Camera::perspective(90f32.to_radians(), 0.5, 10.0);
+        let projection = camera.projection_matrix(2.0);
+        assert!(project(projection, Vec3::new(0.0, 0.0, -0.5)).abs_diff_eq(Vec3::ZERO, 1e-6));
+        assert!((project(projection, Vec3::new(0.0, 0.0, -10.0)).z - 1.0).abs() < 1e-5);
+        // Arriba en el mundo es `y` negativa en clip space; el aspecto estrecha `x`.
+        let corner = project(projection, Vec3::new(2.0, 1.0, -1.0));
+        assert!(corner.abs_diff_eq(Vec3::new(1.0, -1.0, corner.z), 1e-5));
+
+        let ortho = Camera::orthographic(4.0, 0.0, 8.0).projection_matrix(0.5);
+        let corner = project(ortho, Vec3::new(1.0, 2.0, -4.0));
+        assert!(corner.abs_diff_eq(Vec3::new(1.0, -1.0, 0.5), 1e-6));
+    }
+
+    #[test]
+    fn test_uniform_from_transform() {
+        // Cámara en +X mirando hacia el origen.
+        let transform = Transform {
+            position: Vec3::new(5.0, 0.0, 0.0),
+            rotation: Quat::from_rotation_y(90f32.to_radians()),
+        };
+        let uniform = CameraUniform::new(&Camera::default(), &transform, 1.0);
+        let origin = uniform.view.transform_point3(Vec3::ZERO);
+        assert!(origin.abs_diff_eq(Vec3::new(0.0, 0.0, -5.0), 1e-5));
+        let clip = project(uniform.view_projection, Vec3::ZERO);
+        assert!(clip.truncate().abs_diff_eq(glam::Vec2::ZERO, 1e-5));
+        assert!((0.0..1.0).contains(&clip.z));
+
+        let bytes = uniform.to_bytes();
+        assert_eq!(bytes.len() as u64, CameraUniform::SIZE);
+        let offset = CameraUniform::VIEW_PROJECTION_OFFSET;
+        assert_eq!(
+            bytes[offset..offset + 4],
+            uniform.view_projection.x_axis.x.to_le_bytes()
+        );
+        assert_eq!(bytes[192..196], 5f32.to_le_bytes());
+    }
+
+    #[test]
+    fn test_viewport_rects_tile_the_screen() {
+        let left = ViewportRect::new(0.0, 0.0, 0.5, 1.0).to_pixels(101, 50);
+        let right = ViewportRect::new(0.5, 0.0, 0.5, 1.0).to_pixels(101, 50);
+        assert_eq!(left.x + left.width, right.x);
+        assert_eq!(right.x + right.width, 101.0);
+        assert_eq!(left, Viewport::full(51, 50));
+    }
+
+    /// Cubo coloreado por normales en el origen.
+    fn spawn_normal_cube(renderer: &mut Renderer, world: &mut World) {
+        let mut cube = Mesh::cube(1.0, 1);
+        cube.colors = cube
+            .normals
+            .iter()
+            .map(|n| (*n * 0.5 + 0.5).extend(1.0))
+            .collect();
+        let mesh = renderer.add_mesh(cube).unwrap();
+        let material = renderer.add_material(Material::default());
+        let entity = world.spawn_entity();
+        world.insert(entity, Transform::default());
+        world.insert(entity, MeshRenderer::new(mesh, material));
+    }
+
+    #[test]
+    fn test_split_screen_cameras_render_their_own_view() {
+        let mut renderer = Renderer::headless(64, 32);
+        let mut world = World::new(16);
+        world.register_component::<Transform>();
+        world.register_component::<MeshRenderer>();
+        world.register_component::<Camera>();
+        spawn_normal_cube(&mut renderer, &mut world);
+
+        // Minimapa encima (orden 1) y dos mitades de pantalla, insertadas desordenadas.
+        let cameras = [
+            (
+                Camera::orthographic(10.0, 0.1, 100.0)
+                    .with_viewport(ViewportRect::new(0.75, 0.0, 0.25, 0.5))
+                    .with_order(1),
+                Vec3::new(0.0, 0.0, 20.0),
+            ),
+            (
+                Camera::default().with_viewport(ViewportRect::new(0.5, 0.0, 0.5, 1.0)),
+                Vec3::new(3.0, 0.0, 5.0),
+            ),
+            (
+                Camera::default().with_viewport(ViewportRect::new(0.0, 0.0, 0.5, 1.0)),
+                Vec3::new(-3.0, 0.0, 5.0),
+            ),
+        ];
+        let mut entities = Vec::new();
+        for (camera, position) in cameras {
+            let entity = world.spawn_entity();
+            world.insert(entity, camera);
+            world.insert(
+                entity,
+                Transform {
+                    position,
+                    ..Default::default()
+                },
+            );
+            entities.push(entity);
+        }
+
+        let views = CameraView::collect(&mut world, 64, 32);
+        let order: Vec<_> = views.iter().map(|view| view.entity.unwrap()).collect();
+        assert_eq!(order, vec![entities[1], entities[2], entities[0]]);
+        assert_eq!(
+            views[2].viewport,
+            Viewport {
+                x: 48.0,
+                y: 0.0,
+                width: 16.0,
+                height: 16.0
+            }
+        );
+
+        renderer.render(&mut world);
+        let backend = renderer.backend_as::<NullBackend>().unwrap();
+        let frame = backend.last_frame().unwrap();
+        assert_eq!(frame.draw_count(), 3);
+        let viewports: Vec<_> = frame
+            .commands
+            .iter()
+            .filter_map(|command| match command {
+                RenderCommand::SetViewport(viewport) => Some(viewport.x),
+                _ => None,
+            })
+            .collect();
+        // Con el mismo orden se dibujan por entidad.
+        assert_eq!(viewports, vec![32.0, 0.0, 48.0]);
+
+        // Cada cámara enlaza su propio slot del uniform buffer con su CameraUniform.
+        let groups: Vec<_> = frame
+            .commands
+            .iter()
+            .filter_map(|command| match command {
+                RenderCommand::BindGroup { index: 0, group } => Some(*group),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(groups.len(), 3);
+        for (group, view) in groups.iter().zip(&views) {
+            let desc = backend.bind_group_desc(*group).unwrap();
+            let BindingResource::Buffer {
+                buffer,
+                offset,
+                size,
+            } = desc.entries[0].resource
+            else {
+                panic!("camera binding is a buffer");
+            };
+            let data =
+                &backend.buffer_data(buffer).unwrap()[offset as usize..(offset + size) as usize];
+            assert_eq!(data, view.uniform.to_bytes().as_slice());
+        }
+    }
+
+    #[test]
+    fn test_cameras_draw_into_their_viewport() {
+        let mut renderer = Renderer::with_backend(Box::new(SoftwareBackend::new(64, 32)));
+        let mut world = World::new(16);
+        world.register_component::<Transform>();
+        world.register_component::<MeshRenderer>();
+        world.register_component::<Camera>();
+        spawn_normal_cube(&mut renderer, &mut world);
+
+        // La cámara izquierda mira al cubo; la derecha mira hacia otro lado y limpia en gris.
+        let left = world.spawn_entity();
+        world.insert(
+            left,
+            Camera::default().with_viewport(ViewportRect::new(0.0, 0.0, 0.5, 1.0)),
+        );
+        world.insert(
+            left,
+            Transform {
+                position: Vec3::new(0.0, 0.0, 4.0),
+                ..Default::default()
+            },
+        );
+        let right = world.spawn_entity();
+        let gray = [0.5, 0.5, 0.5, 1.0];
+        world.insert(
+            right,
+            Camera::default()
+                .with_viewport(ViewportRect::new(0.5, 0.0, 0.5, 1.0))
+                .with_clear_color(Some(gray)),
+        );
+        world.insert(
+            right,
+            Transform {
+                position: Vec3::new(0.0, 0.0, 4.0),
+                rotation: glam::Quat::from_rotation_y(std::f32::consts::PI),
+            },
+        );
+
+        renderer.render(&mut world);
+        let image = renderer
+            .backend_as::<SoftwareBackend>()
+            .unwrap()
+            .presented_image()
+            .unwrap();
+        // Cara +Z del cubo en el centro de la mitad izquierda: (0.5, 0.5, 1).
+        assert_eq!(image.pixel(16, 16), [128, 128, 255, 255]);
+        assert_eq!(image.pixel(2, 2), [0, 0, 0, 255]);
+        assert_eq!(image.pixel(48, 16), [128, 128, 128, 255]);
+    }
+}
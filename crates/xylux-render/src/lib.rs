@@ -1,38 +1,42 @@
-pub mod vulkan;
+pub mod allocator;
+pub mod backend;
+pub mod camera;
+pub mod material;
+pub mod mesh;
 pub mod pipeline;
 pub mod renderer;
+pub mod shader;
+pub mod texture;
+pub mod vulkan;
 
-pub use renderer::Renderer;
+pub use allocator::{
+    Allocation, AllocationDesc, AllocationError, AllocatorConfig, AllocatorStats, DeviceHeap,
+    GpuAllocator, MemoryLocation, MemoryStats, ResourceKind,
+};
+pub use backend::{
+    AddressMode, BackendError, BarrierResource, BindGroupDesc, BindGroupEntry, BindGroupId,
+    BindGroupLayout, BindingLayout, BindingResource, BindingType, BindlessTextures, BlendMode,
+    BufferDesc, BufferId, BufferUsage, CommandList, CullMode, FilterMode, FrameInfo, FrontFace,
+    IndexFormat, NullBackend, PipelineBuilder, PipelineDesc, PipelineId, PrimitiveTopology,
+    RecordedFrame, RenderBackend, RenderCommand, RenderPassDesc, RenderTarget, ResourceBarrier,
+    ResourceUsage, RgbaImage, SampleCount, SamplerDesc, SamplerId, SoftwareBackend,
+    SoftwareProgram, TextureDesc, TextureFormat, TextureId, TextureSlot, TextureUsage, UploadStats,
+    UploadTicket, VertexAttribute, VertexFormat, VertexLayout, VertexStepMode, Viewport,
+    VulkanBackend,
+};
+pub use camera::{Camera, CameraUniform, CameraView, Projection, ViewportRect};
+pub use material::{AlphaMode, Material, MaterialPipeline, MaterialShader, MaterialTextures};
+pub use mesh::{
+    Heightmap, Lod, LodLevel, LodMetric, Mesh, MeshError, MeshRenderer, SubMesh, TerrainDesc,
+    load_gltf, load_obj, parse_gltf, parse_obj,
+};
+pub use renderer::{
+    CompiledGraph, CullingStats, DrawBatch, FrameBatches, Frustum, GraphError, InstanceData,
+    PassId, RenderGraph, Renderer, ResourceId,
+};
+pub use shader::{
+    CompiledShader, FileWatcher, ShaderCompiler, ShaderError, ShaderLanguage, ShaderPipelines,
+    ShaderReflection, ShaderStage,
+};
+pub use texture::{ColorSpace, MipFilter, Texture, TextureError, load_texture, parse_texture};
 pub use vulkan::context::VulkanContext;
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use xylux_ecs::{World, Transform};
-    use xylux_window::XyluxWindow;
-
-    #[test]
-    #[ignore] // Ignorado por CI; requiere GPU
-    fn test_renderer_init_production() {
-        println!("=== Inicializando ventana ===");
-        let xwindow = XyluxWindow::new("Render Test", 800, 600);
-
-        println!("=== Inicializando renderer ===");
-        let mut renderer = Renderer::new(&xwindow);
-
-        println!("=== Creando mundo ECS ===");
-        let mut world = World::new(1000);
-        world.register_component::<Transform>();
-
-        println!("=== Renderizando frame de prueba ===");
-        renderer.render(&mut world, &xwindow);
-
-        println!("=== Esperando a que la GPU termine ===");
-        renderer.device_wait_idle();
-
-        println!("=== Limpiando recursos del renderer ===");
-        renderer.cleanup();
-
-        println!("=== Test completado correctamente ===");
-    }
-}
\ No newline at end of file
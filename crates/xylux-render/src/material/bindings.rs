@@ -0,0 +1,334 @@
+//! Pool de uniforms y bind groups de los materiales PBR.
+//!
+//! Los uniforms van en páginas de `SLOTS_PER_PAGE` slots de `UNIFORM_ALIGNMENT`
+//! bytes, y cada material ocupa un slot con su bind group. Un slot no se
+//! reescribe mientras un frame en vuelo pueda leerlo: al cambiar o eliminar el
+//! material, su slot y su bind group se retiran y no vuelven al pool hasta
+//! pasados `MAX_FRAMES_IN_FLIGHT` frames. El material cambiado recibe un slot
+//! nuevo.
+
+use super::Material;
+use crate::allocator::MemoryLocation;
+use crate::backend::{
+    BackendError, BindGroupDesc, BindGroupEntry, BindGroupId, BindingResource, BufferDesc,
+    BufferId, BufferUsage, RenderBackend, SamplerDesc, SamplerId, TextureFormat, TextureId,
+    UNIFORM_ALIGNMENT,
+};
+use crate::texture::Texture;
+use crate::vulkan::context::MAX_FRAMES_IN_FLIGHT;
+use std::collections::{HashMap, VecDeque};
+use xylux_ecs::Handle;
+
+/// Slots de uniform de cada buffer del pool.
+pub const SLOTS_PER_PAGE: u32 = 64;
+
+/// Texturas de 1x1 que sustituyen a las que no tiene un material.
+#[derive(Clone, Copy, Debug)]
+struct DefaultTextures {
+    white: TextureId,
+    /// Normal plana en espacio tangente, `(0.5, 0.5, 1.0)`.
+    normal: TextureId,
+}
+
+#[derive(Default)]
+pub(crate) struct MaterialBindings {
+    pages: Vec<BufferId>,
+    free: Vec<u32>,
+    /// Slots retirados con el frame en el que se retiraron.
+    retired: VecDeque<(u64, u32, BindGroupId)>,
+    groups: HashMap<Handle<Material>, (u32, BindGroupId)>,
+    sampler: Option<SamplerId>,
+    defaults: Option<DefaultTextures>,
+}
+
+impl MaterialBindings {
+    /// Bind group de `handle`, creándolo si no tiene. `textures` son las texturas ya
+    /// subidas de `MaterialTextures::to_array`; las `None` usan las de por defecto.
+    pub fn bind_group(
+        &mut self,
+        backend: &mut dyn RenderBackend,
+        handle: Handle<Material>,
+        material: &Material,
+        textures: [Option<TextureId>; 5],
+    ) -> Result<BindGroupId, BackendError> {
+        if let Some(&(_, group)) = self.groups.get(&handle) {
+            return Ok(group);
+        }
+        let sampler = match self.sampler {
+            Some(sampler) => sampler,
+            None => *self
+                .sampler
+                .insert(backend.create_sampler(&SamplerDesc::default().with_anisotropy(16.0))?),
+        };
+        let defaults = match self.defaults {
+            Some(defaults) => defaults,
+            None => *self.defaults.insert(create_defaults(backend)?),
+        };
+
+        let slot = match self.free.pop() {
+            Some(slot) => slot,
+            None => {
+                let buffer = backend.create_buffer(&BufferDesc {
+                    size: SLOTS_PER_PAGE as u64 * UNIFORM_ALIGNMENT,
+                    usage: BufferUsage::UNIFORM,
+                    location: MemoryLocation::CpuToGpu,
+                })?;
+                let first = self.pages.len() as u32 * SLOTS_PER_PAGE;
+                self.pages.push(buffer);
+                // Los slots se reparten de menor a mayor.
+                self.free.extend((first + 1..first + SLOTS_PER_PAGE).rev());
+                first
+            }
+        };
+        let buffer = self.pages[(slot / SLOTS_PER_PAGE) as usize];
+        let offset = (slot % SLOTS_PER_PAGE) as u64 * UNIFORM_ALIGNMENT;
+        let result = backend
+            .write_buffer(buffer, offset, &material.uniform_bytes())
+            .and_then(|()| {
+                let mut entries = vec![
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: BindingResource::Buffer {
+                            buffer,
+                            offset,
+                            size: Material::UNIFORM_SIZE,
+                        },
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: BindingResource::Sampler(sampler),
+                    },
+                ];
+                for (index, texture) in textures.into_iter().enumerate() {
+                    let fallback = if index == 2 {
+                        defaults.normal
+                    } else {
+                        defaults.white
+                    };
+                    entries.push(BindGroupEntry {
+                        binding: 2 + index as u32,
+                        resource: BindingResource::Texture(texture.unwrap_or(fallback)),
+                    });
+                }
+                backend.create_bind_group(&BindGroupDesc {
+                    layout: Material::bind_group_layout(),
+                    entries,
+                })
+            });
+        match result {
+            Ok(group) => {
+                self.groups.insert(handle, (slot, group));
+                Ok(group)
+            }
+            Err(error) => {
+                self.free.push(slot);
+                Err(error)
+            }
+        }
+    }
+
+    /// Retira el bind group de `handle` (si tiene) en el frame `frame`; el siguiente
+    /// `bind_group` crea uno nuevo con los datos actuales del material.
+    pub fn invalidate(&mut self, handle: Handle<Material>, frame: u64) {
+        if let Some((slot, group)) = self.groups.remove(&handle) {
+            self.retired.push_back((frame, slot, group));
+        }
+    }
+
+    /// Devuelve al pool los slots que ningún frame en vuelo puede estar leyendo al
+    /// empezar el frame `frame`.
+    pub fn collect(&mut self, backend: &mut dyn RenderBackend, frame: u64) {
+        while let Some(&(retired, slot, group)) = self.retired.front() {
+            if frame < retired + MAX_FRAMES_IN_FLIGHT as u64 {
+                break;
+            }
+            self.retired.pop_front();
+            backend.destroy_bind_group(group);
+            self.free.push(slot);
+        }
+    }
+
+    /// Destruye todos los recursos del pool.
+    pub fn clear(&mut self, backend: &mut dyn RenderBackend) {
+        let groups = self.groups.drain().map(|(_, (_, group))| group);
+        for group in groups.chain(self.retired.drain(..).map(|(_, _, group)| group)) {
+            backend.destroy_bind_group(group);
+        }
+        for buffer in self.pages.drain(..) {
+            backend.destroy_buffer(buffer);
+        }
+        if let Some(sampler) = self.sampler.take() {
+            backend.destroy_sampler(sampler);
+        }
+        if let Some(defaults) = self.defaults.take() {
+            backend.destroy_texture(defaults.white);
+            backend.destroy_texture(defaults.normal);
+        }
+        self.free.clear();
+    }
+}
+
+fn create_defaults(backend: &mut dyn RenderBackend) -> Result<DefaultTextures, BackendError> {
+    let texel = |data: [u8; 4]| Texture::new(1, 1, TextureFormat::Rgba8Unorm, data.to_vec());
+    let white = texel([255; 4])
+        .expect("1x1 texture is valid")
+        .upload(backend)?;
+    let normal = texel([128, 128, 255, 255])
+        .expect("1x1 texture is valid")
+        .upload(backend)
+        .inspect_err(|_| backend.destroy_texture(white))?;
+    Ok(DefaultTextures { white, normal })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::NullBackend;
+    use crate::backend::{BindGroupId, RenderCommand, TextureFormat};
+    use crate::material::AlphaMode;
+    use crate::mesh::{Mesh, MeshRenderer};
+    use crate::renderer::Renderer;
+    use crate::texture::Texture;
+    use xylux_ecs::Pool;
+    use xylux_ecs::{Transform, World};
+
+    /// Slots en uso y disponibles (incluidos los retirados).
+    fn slot_counts(bindings: &MaterialBindings) -> (usize, usize) {
+        let total = bindings.pages.len() * SLOTS_PER_PAGE as usize;
+        (bindings.groups.len(), total - bindings.groups.len())
+    }
+
+    #[test]
+    fn test_slots_are_reused_after_frames_in_flight() {
+        let mut backend = NullBackend::new(8, 8);
+        let mut materials = Pool::new();
+        let handles: Vec<Handle<Material>> = (0..SLOTS_PER_PAGE + 1)
+            .map(|_| materials.insert(Material::pbr()))
+            .collect();
+        let mut bindings = MaterialBindings::default();
+        for &handle in &handles {
+            let material = materials.get(handle).unwrap();
+            bindings
+                .bind_group(&mut backend, handle, material, [None; 5])
+                .unwrap();
+        }
+        // Dos páginas: 65 en uso y 63 libres.
+        assert_eq!(slot_counts(&bindings), (65, 63));
+        let first = handles[0];
+        let group = bindings
+            .bind_group(&mut backend, first, &Material::pbr(), [None; 5])
+            .unwrap();
+        let normal = bindings.defaults.unwrap().normal;
+        let entries = &backend.bind_group_desc(group).unwrap().entries;
+        assert_eq!(entries[4].resource, BindingResource::Texture(normal));
+
+        // Un material cambiado recibe un slot libre; el suyo espera a los frames en vuelo.
+        bindings.invalidate(first, 10);
+        let changed = Material::pbr().with_metallic_roughness(0.0, 0.5);
+        let replacement = bindings
+            .bind_group(&mut backend, first, &changed, [None; 5])
+            .unwrap();
+        assert_ne!(replacement, group);
+        assert!(backend.bind_group_desc(group).is_some());
+        bindings.collect(&mut backend, 11);
+        assert!(backend.bind_group_desc(group).is_some());
+        bindings.collect(&mut backend, 10 + MAX_FRAMES_IN_FLIGHT as u64);
+        assert!(backend.bind_group_desc(group).is_none());
+        assert_eq!(slot_counts(&bindings), (65, 63));
+
+        bindings.clear(&mut backend);
+        assert_eq!(backend.resource_counts(), (0, 0, 0));
+    }
+
+    #[test]
+    fn test_material_textures_bind_groups_and_blend_order() {
+        let mut renderer = Renderer::headless(16, 16);
+        let mut world = World::new(16);
+        world.register_component::<Transform>();
+        world.register_component::<MeshRenderer>();
+
+        let mesh = renderer.add_mesh(Mesh::cube(1.0, 1)).unwrap();
+        let texture = renderer.add_texture(
+            Texture::new(1, 1, TextureFormat::Rgba8Srgb, vec![255, 0, 0, 255]).unwrap(),
+        );
+        let glass = renderer.add_material(Material::pbr().with_alpha_mode(AlphaMode::Blend));
+        let brick = renderer.add_material(Material::pbr().with_base_color_texture(texture));
+        let unlit = renderer.add_material(Material::default());
+        for material in [glass, brick, unlit] {
+            let entity = world.spawn_entity();
+            world.insert(entity, Transform::default());
+            world.insert(entity, MeshRenderer::new(mesh, material));
+        }
+
+        let set_one = |renderer: &Renderer| -> Vec<BindGroupId> {
+            let backend = renderer.backend_as::<NullBackend>().unwrap();
+            backend
+                .last_frame()
+                .unwrap()
+                .commands
+                .iter()
+                .filter_map(|command| match command {
+                    RenderCommand::BindGroup { index: 1, group } => Some(*group),
+                    _ => None,
+                })
+                .collect()
+        };
+        renderer.render(&mut world);
+        let backend = renderer.backend_as::<NullBackend>().unwrap();
+        let frame = backend.last_frame().unwrap();
+        let pipelines: Vec<String> = frame
+            .commands
+            .iter()
+            .filter_map(|command| match command {
+                RenderCommand::BindPipeline(pipeline) => {
+                    Some(backend.pipeline_desc(*pipeline).unwrap())
+                }
+                _ => None,
+            })
+            .map(|desc| format!("{}:{:?}", desc.label, desc.blend))
+            .collect();
+        // Opacos (PBR y unlit) primero y el transparente al final.
+        assert_eq!(pipelines, ["pbr:Replace", "mesh:Replace", "pbr:Alpha"]);
+        let groups = set_one(&renderer);
+        assert_eq!(groups.len(), 2);
+        let backend = renderer.backend_as::<NullBackend>().unwrap();
+        let entries = &backend.bind_group_desc(groups[0]).unwrap().entries;
+        let base_color = entries[2].resource.texture().unwrap();
+        assert_eq!(
+            backend.texture_data(base_color, 0),
+            Some(&[255, 0, 0, 255][..])
+        );
+
+        // Sin cambios se reutilizan; al editar un material recibe un bind group nuevo.
+        renderer.render(&mut world);
+        assert_eq!(set_one(&renderer), groups);
+        renderer.material_mut(brick).unwrap().roughness = 0.25;
+        renderer.render(&mut world);
+        let edited = set_one(&renderer);
+        assert_ne!(edited[0], groups[0]);
+        assert_eq!(edited[1], groups[1]);
+        let backend = renderer.backend_as::<NullBackend>().unwrap();
+        assert!(
+            backend.bind_group_desc(groups[0]).is_some(),
+            "un frame en vuelo aún puede leerlo"
+        );
+        renderer.render(&mut world);
+        renderer.render(&mut world);
+        let backend = renderer.backend_as::<NullBackend>().unwrap();
+        assert!(backend.bind_group_desc(groups[0]).is_none());
+
+        // Sin la textura se usa la blanca por defecto.
+        renderer.remove_texture(texture);
+        renderer.render(&mut world);
+        let backend = renderer.backend_as::<NullBackend>().unwrap();
+        let group = set_one(&renderer)[0];
+        let fallback = backend.bind_group_desc(group).unwrap().entries[2]
+            .resource
+            .texture()
+            .unwrap();
+        assert_eq!(
+            backend.texture_data(fallback, 0),
+            Some(&[255, 255, 255, 255][..])
+        );
+    }
+}
@@ -0,0 +1,125 @@
+//! Conversión de materiales glTF 2.0 a `Material`.
+
+use super::{AlphaMode, Material, MaterialShader, MaterialTextures};
+use crate::texture::Texture;
+use glam::{Vec3, Vec4};
+use xylux_ecs::Handle;
+
+impl Material {
+    /// Material PBR equivalente a `material`.
+    ///
+    /// `textures` son los handles de las texturas del documento, por índice; las
+    /// referencias a texturas que no están se ignoran. Los sets de UV distintos del
+    /// 0 y las extensiones no se admiten.
+    pub fn from_gltf(material: &gltf::Material, textures: &[Handle<Texture>]) -> Material {
+        let texture = |texture: gltf::Texture| textures.get(texture.index()).copied();
+        let pbr = material.pbr_metallic_roughness();
+        let normal = material.normal_texture();
+        let occlusion = material.occlusion_texture();
+        let alpha_mode = match material.alpha_mode() {
+            gltf::material::AlphaMode::Opaque => AlphaMode::Opaque,
+            gltf::material::AlphaMode::Mask => AlphaMode::Mask {
+                cutoff: material.alpha_cutoff().unwrap_or(0.5),
+            },
+            gltf::material::AlphaMode::Blend => AlphaMode::Blend,
+        };
+        Material {
+            shader: MaterialShader::PbrMetallicRoughness,
+            base_color: Vec4::from_array(pbr.base_color_factor()),
+            metallic: pbr.metallic_factor(),
+            roughness: pbr.roughness_factor(),
+            emissive: Vec3::from_array(material.emissive_factor()),
+            normal_scale: normal.as_ref().map_or(1.0, |normal| normal.scale()),
+            occlusion_strength: occlusion
+                .as_ref()
+                .map_or(1.0, |occlusion| occlusion.strength()),
+            textures: MaterialTextures {
+                base_color: pbr
+                    .base_color_texture()
+                    .and_then(|info| texture(info.texture())),
+                metallic_roughness: pbr
+                    .metallic_roughness_texture()
+                    .and_then(|info| texture(info.texture())),
+                normal: normal.and_then(|normal| texture(normal.texture())),
+                occlusion: occlusion.and_then(|occlusion| texture(occlusion.texture())),
+                emissive: material
+                    .emissive_texture()
+                    .and_then(|info| texture(info.texture())),
+            },
+            alpha_mode,
+            double_sided: material.double_sided(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::TextureFormat;
+    use xylux_ecs::Pool;
+
+    #[test]
+    fn test_gltf_materials_keep_factors_and_textures() {
+        let json = r#"{
+            "asset": {"version": "2.0"},
+            "images": [{"uri": "a.png"}, {"uri": "b.png"}],
+            "textures": [{"source": 0}, {"source": 1}],
+            "materials": [
+                {
+                    "pbrMetallicRoughness": {
+                        "baseColorFactor": [1.0, 0.5, 0.25, 0.5],
+                        "baseColorTexture": {"index": 0},
+                        "metallicFactor": 0.0,
+                        "roughnessFactor": 0.75,
+                        "metallicRoughnessTexture": {"index": 1}
+                    },
+                    "normalTexture": {"index": 1, "scale": 0.5},
+                    "emissiveFactor": [1.0, 1.0, 0.0],
+                    "alphaMode": "MASK",
+                    "doubleSided": true
+                },
+                {"alphaMode": "BLEND", "occlusionTexture": {"index": 1, "strength": 0.25}}
+            ]
+        }"#;
+        let document = gltf::Gltf::from_slice(json.as_bytes()).unwrap();
+        let mut pool = Pool::new();
+        let textures = [Vec4::ONE, Vec4::ZERO].map(|texel| {
+            pool.insert(Texture::from_texels(1, 1, TextureFormat::Rgba8Unorm, &[texel]).unwrap())
+        });
+        let materials: Vec<gltf::Material> = document.materials().collect();
+
+        let first = Material::from_gltf(&materials[0], &textures);
+        assert_eq!(first.shader, MaterialShader::PbrMetallicRoughness);
+        assert_eq!(first.base_color, Vec4::new(1.0, 0.5, 0.25, 0.5));
+        assert_eq!((first.metallic, first.roughness), (0.0, 0.75));
+        assert_eq!(
+            (first.emissive, first.normal_scale),
+            (Vec3::new(1.0, 1.0, 0.0), 0.5)
+        );
+        assert_eq!(first.alpha_mode, AlphaMode::Mask { cutoff: 0.5 });
+        assert!(first.double_sided);
+        assert_eq!(
+            first.textures.to_array(),
+            [
+                Some(textures[0]),
+                Some(textures[1]),
+                Some(textures[1]),
+                None,
+                None
+            ]
+        );
+
+        // Valores por defecto de glTF; sin el handle de la textura 1 no hay oclusión.
+        let second = Material::from_gltf(&materials[1], &textures[..1]);
+        let expected = Material::pbr()
+            .with_alpha_mode(AlphaMode::Blend)
+            .with_occlusion_texture(textures[0], 0.25);
+        assert_eq!(
+            second,
+            Material {
+                textures: MaterialTextures::default(),
+                ..expected
+            }
+        );
+    }
+}
@@ -0,0 +1,381 @@
+//! # Módulo Material
+//!
+//! Parámetros de superficie con los que se dibuja una malla: el shader, sus
+//! factores y sus texturas.
+//!
+//! Los materiales `Unlit` pasan el color base en push constants. Los
+//! `PbrMetallicRoughness` siguen la semántica de glTF 2.0 y llevan sus factores
+//! en un uniform y sus texturas en el set 1 (`Material::bind_group_layout`); el
+//! `Renderer` reparte esos bind groups desde un pool (`bindings`).
+//!
+//! - `bindings`: pool de uniforms y bind groups de los materiales.
+//! - `gltf`: conversión de los materiales de un documento glTF.
+//!
+//! Una entidad elige su material con el `MeshRenderer` o, si lo tiene, con un
+//! componente `Handle<Material>`, que tiene prioridad.
+
+pub(crate) mod bindings;
+pub mod gltf;
+
+use crate::backend::{
+    BindGroupLayout, BindingLayout, BindingType, BlendMode, CullMode, PipelineDesc,
+};
+use crate::texture::Texture;
+use glam::{Vec3, Vec4};
+use xylux_ecs::Handle;
+
+/// Shader con el que se dibuja un material.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum MaterialShader {
+    /// Color base por el color del vértice, sin iluminación (`PipelineDesc::mesh`).
+    #[default]
+    Unlit,
+    /// PBR metallic-roughness de glTF (`PipelineDesc::pbr`).
+    PbrMetallicRoughness,
+}
+
+/// Cómo se usa el alfa del color base, como `alphaMode` en glTF.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum AlphaMode {
+    /// El alfa se ignora.
+    #[default]
+    Opaque,
+    /// Se descartan los fragmentos con alfa menor que `cutoff`.
+    Mask { cutoff: f32 },
+    /// Mezcla con lo ya dibujado. Se dibuja después de los opacos y sin escribir
+    /// profundidad.
+    Blend,
+}
+
+/// Texturas de un material PBR. Las que faltan equivalen a una textura blanca (o
+/// a la normal plana), así que solo cuentan los factores.
+///
+/// Como en glTF, el color base y el emisivo deben cargarse en `ColorSpace::Srgb` y
+/// el resto en `ColorSpace::Linear`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MaterialTextures {
+    pub base_color: Option<Handle<Texture>>,
+    /// Rugosidad en el canal G y metalicidad en el B.
+    pub metallic_roughness: Option<Handle<Texture>>,
+    /// Normales en espacio tangente.
+    pub normal: Option<Handle<Texture>>,
+    /// Oclusión ambiental en el canal R.
+    pub occlusion: Option<Handle<Texture>>,
+    pub emissive: Option<Handle<Texture>>,
+}
+
+impl MaterialTextures {
+    /// Texturas en el orden de sus bindings (del 2 al 6 del set del material).
+    pub fn to_array(&self) -> [Option<Handle<Texture>>; 5] {
+        [
+            self.base_color,
+            self.metallic_roughness,
+            self.normal,
+            self.occlusion,
+            self.emissive,
+        ]
+    }
+
+    /// `true` si alguna textura es `texture`.
+    pub fn uses(&self, texture: Handle<Texture>) -> bool {
+        self.to_array().contains(&Some(texture))
+    }
+}
+
+/// Variante de pipeline que necesita un material.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MaterialPipeline {
+    pub shader: MaterialShader,
+    pub blend: bool,
+    pub double_sided: bool,
+}
+
+impl MaterialPipeline {
+    /// Pipeline del shader con el culling y el blending de la variante.
+    pub fn desc(&self) -> PipelineDesc {
+        let mut desc = match self.shader {
+            MaterialShader::Unlit => PipelineDesc::mesh(),
+            MaterialShader::PbrMetallicRoughness => PipelineDesc::pbr(),
+        };
+        if self.double_sided {
+            desc.cull_mode = CullMode::None;
+        }
+        if self.blend {
+            desc.blend = BlendMode::Alpha;
+            desc.depth_write = false;
+        }
+        desc
+    }
+}
+
+/// Material de una malla.
+///
+/// Los factores siguen glTF: se multiplican por sus texturas (y el color base
+/// también por el color del vértice). `Unlit` solo usa `base_color`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Material {
+    pub shader: MaterialShader,
+    /// Color lineal RGBA.
+    pub base_color: Vec4,
+    pub metallic: f32,
+    pub roughness: f32,
+    /// Color emitido, lineal.
+    pub emissive: Vec3,
+    /// Escala de X e Y de la normal de la textura.
+    pub normal_scale: f32,
+    /// Cuánto se aplica la textura de oclusión (0 = nada).
+    pub occlusion_strength: f32,
+    pub textures: MaterialTextures,
+    pub alpha_mode: AlphaMode,
+    /// Dibuja las dos caras, con la normal girada en las traseras.
+    pub double_sided: bool,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self {
+            shader: MaterialShader::Unlit,
+            base_color: Vec4::ONE,
+            metallic: 1.0,
+            roughness: 1.0,
+            emissive: Vec3::ZERO,
+            normal_scale: 1.0,
+            occlusion_strength: 1.0,
+            textures: MaterialTextures::default(),
+            alpha_mode: AlphaMode::Opaque,
+            double_sided: false,
+        }
+    }
+}
+
+impl Material {
+    /// Tamaño del uniform de `uniform_bytes`.
+    pub const UNIFORM_SIZE: u64 = 64;
+
+    /// Material `Unlit` de color sólido.
+    pub fn new(base_color: Vec4) -> Self {
+        Self {
+            base_color,
+            ..Default::default()
+        }
+    }
+
+    /// Material PBR con los valores por defecto de glTF: blanco, metálico y rugoso.
+    pub fn pbr() -> Self {
+        Self {
+            shader: MaterialShader::PbrMetallicRoughness,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_base_color(mut self, base_color: Vec4) -> Self {
+        self.base_color = base_color;
+        self
+    }
+
+    pub fn with_metallic_roughness(mut self, metallic: f32, roughness: f32) -> Self {
+        self.metallic = metallic;
+        self.roughness = roughness;
+        self
+    }
+
+    pub fn with_emissive(mut self, emissive: Vec3) -> Self {
+        self.emissive = emissive;
+        self
+    }
+
+    pub fn with_base_color_texture(mut self, texture: Handle<Texture>) -> Self {
+        self.textures.base_color = Some(texture);
+        self
+    }
+
+    pub fn with_metallic_roughness_texture(mut self, texture: Handle<Texture>) -> Self {
+        self.textures.metallic_roughness = Some(texture);
+        self
+    }
+
+    pub fn with_normal_texture(mut self, texture: Handle<Texture>, scale: f32) -> Self {
+        self.textures.normal = Some(texture);
+        self.normal_scale = scale;
+        self
+    }
+
+    pub fn with_occlusion_texture(mut self, texture: Handle<Texture>, strength: f32) -> Self {
+        self.textures.occlusion = Some(texture);
+        self.occlusion_strength = strength;
+        self
+    }
+
+    pub fn with_emissive_texture(mut self, texture: Handle<Texture>) -> Self {
+        self.textures.emissive = Some(texture);
+        self
+    }
+
+    pub fn with_alpha_mode(mut self, alpha_mode: AlphaMode) -> Self {
+        self.alpha_mode = alpha_mode;
+        self
+    }
+
+    pub fn with_double_sided(mut self, double_sided: bool) -> Self {
+        self.double_sided = double_sided;
+        self
+    }
+
+    /// Variante de pipeline con la que se dibuja.
+    pub fn pipeline(&self) -> MaterialPipeline {
+        MaterialPipeline {
+            shader: self.shader,
+            blend: self.alpha_mode == AlphaMode::Blend,
+            double_sided: self.double_sided,
+        }
+    }
+
+    /// Push constants del pipeline de mallas.
+    pub fn push_constants(&self) -> Vec<u8> {
+        self.base_color
+            .to_array()
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect()
+    }
+
+    /// Layout del set 1 de los materiales PBR: el uniform (binding 0), el sampler
+    /// (1) y las texturas de `MaterialTextures::to_array` (2 a 6).
+    pub fn bind_group_layout() -> BindGroupLayout {
+        let binding = |binding, ty| BindingLayout { binding, ty };
+        let mut bindings = vec![
+            binding(0, BindingType::UniformBuffer),
+            binding(1, BindingType::Sampler),
+        ];
+        bindings.extend((2..7).map(|index| binding(index, BindingType::Texture)));
+        BindGroupLayout { bindings }
+    }
+
+    /// Uniform de `shaders/pbr.frag`: color base, emisivo, `(metallic, roughness,
+    /// normal_scale, occlusion_strength)` y `(alpha_cutoff, alpha_mode)`.
+    pub fn uniform_bytes(&self) -> Vec<u8> {
+        let (cutoff, mode) = match self.alpha_mode {
+            AlphaMode::Opaque => (0.0, 0.0),
+            AlphaMode::Mask { cutoff } => (cutoff, 1.0),
+            AlphaMode::Blend => (0.0, 2.0),
+        };
+        let factors = [
+            self.metallic,
+            self.roughness,
+            self.normal_scale,
+            self.occlusion_strength,
+        ];
+        [
+            self.base_color.to_array(),
+            self.emissive.extend(0.0).to_array(),
+            factors,
+            [cutoff, mode, 0.0, 0.0],
+        ]
+        .iter()
+        .flatten()
+        .flat_map(|value| value.to_le_bytes())
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::SoftwareBackend;
+    use crate::camera::Camera;
+    use crate::mesh::{Mesh, MeshRenderer};
+    use crate::renderer::Renderer;
+    use xylux_ecs::{Handle, Transform, World};
+
+    #[test]
+    fn test_pbr_uniform_and_pipeline_variants() {
+        let material = Material::pbr()
+            .with_base_color(Vec4::new(0.5, 0.25, 1.0, 0.5))
+            .with_metallic_roughness(0.0, 0.5)
+            .with_emissive(Vec3::new(1.0, 2.0, 3.0))
+            .with_alpha_mode(AlphaMode::Mask { cutoff: 0.25 });
+        let bytes = material.uniform_bytes();
+        assert_eq!(bytes.len() as u64, Material::UNIFORM_SIZE);
+        let floats: Vec<f32> = bytes
+            .chunks_exact(4)
+            .map(|value| f32::from_le_bytes(value.try_into().unwrap()))
+            .collect();
+        assert_eq!(floats[..4], [0.5, 0.25, 1.0, 0.5]);
+        assert_eq!(floats[4..7], [1.0, 2.0, 3.0]);
+        assert_eq!(floats[8..], [0.0, 0.5, 1.0, 1.0, 0.25, 1.0, 0.0, 0.0]);
+
+        // La máscara no cambia el pipeline; la mezcla y las dos caras sí.
+        let opaque = material.pipeline().desc();
+        assert_eq!(
+            (opaque.label.as_str(), opaque.cull_mode),
+            ("pbr", CullMode::Back)
+        );
+        assert!(opaque.depth_write);
+        let glass = material
+            .with_alpha_mode(AlphaMode::Blend)
+            .with_double_sided(true)
+            .pipeline()
+            .desc();
+        assert_eq!(
+            (glass.blend, glass.cull_mode),
+            (BlendMode::Alpha, CullMode::None)
+        );
+        assert!(glass.depth_test && !glass.depth_write);
+        assert_eq!(glass.bind_group_layouts[1], Material::bind_group_layout());
+        assert_eq!(Material::default().pipeline().desc(), PipelineDesc::mesh());
+    }
+
+    #[test]
+    fn test_pbr_material_from_handle_component() {
+        let mut renderer = Renderer::with_backend(Box::new(SoftwareBackend::new(32, 32)));
+        let mut world = World::new(16);
+        world.register_component::<Transform>();
+        world.register_component::<MeshRenderer>();
+        world.register_component::<Handle<Material>>();
+        world.register_component::<Camera>();
+
+        // La cara frontal no recibe la luz fija: solo ambiente y emisivo.
+        let mesh = renderer.add_mesh(Mesh::cube(1.0, 1)).unwrap();
+        let red = renderer.add_material(Material::new(Vec4::new(1.0, 0.0, 0.0, 1.0)));
+        let glowing = renderer.add_material(
+            Material::pbr()
+                .with_metallic_roughness(0.0, 0.5)
+                .with_emissive(Vec3::new(0.0, 0.5, 0.0)),
+        );
+        let entity = world.spawn_entity();
+        world.insert(entity, Transform::default());
+        world.insert(entity, MeshRenderer::new(mesh, red));
+        world.insert(entity, glowing);
+        let camera = world.spawn_entity();
+        world.insert(camera, Camera::default());
+        world.insert(
+            camera,
+            Transform {
+                position: Vec3::new(0.0, 0.0, 4.0),
+                ..Default::default()
+            },
+        );
+
+        renderer.render(&mut world);
+        let image = renderer
+            .backend_as::<SoftwareBackend>()
+            .unwrap()
+            .presented_image()
+            .unwrap();
+        let [r, g, b, _] = image.pixel(16, 16);
+        assert!(
+            r == b && (5..12).contains(&r) && (130..140).contains(&g),
+            "pixel {:?}",
+            [r, g, b]
+        );
+
+        world.remove::<Handle<Material>>(entity);
+        renderer.render(&mut world);
+        let image = renderer
+            .backend_as::<SoftwareBackend>()
+            .unwrap()
+            .presented_image()
+            .unwrap();
+        assert_eq!(image.pixel(16, 16), [255, 0, 0, 255]);
+    }
+}
@@ -0,0 +1,265 @@
+//! Carga de mallas glTF 2.0.
+//!
+//! Soporta `.gltf` con buffers externos o embebidos como data URI y `.glb` con el
+//! chunk binario. Cada malla del documento produce una `Mesh` con una submalla por
+//! primitiva; solo se aceptan primitivas de triángulos. Las transformaciones de los
+//! nodos, las texturas y los materiales no se aplican aquí.
+
+use super::{Mesh, MeshError, SubMesh};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use glam::{Vec2, Vec3, Vec4};
+use std::path::Path;
+
+/// Carga todas las mallas de un archivo `.gltf` o `.glb`.
+///
+/// Los buffers externos se resuelven relativos al directorio del archivo.
+pub fn load_gltf(path: impl AsRef<Path>) -> Result<Vec<Mesh>, MeshError> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path)
+        .map_err(|error| MeshError::Io(format!("{}: {}", path.display(), error)))?;
+    parse_gltf(&bytes, path.parent())
+}
+
+/// Lee un documento glTF o GLB desde memoria.
+///
+/// `base_dir` es el directorio para los buffers externos; sin él, solo se aceptan
+/// buffers embebidos.
+pub fn parse_gltf(bytes: &[u8], base_dir: Option<&Path>) -> Result<Vec<Mesh>, MeshError> {
+    let gltf =
+        gltf::Gltf::from_slice(bytes).map_err(|error| MeshError::Parse(error.to_string()))?;
+    let buffers = gltf
+        .buffers()
+        .map(|buffer| load_buffer(&buffer, gltf.blob.as_deref(), base_dir))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    gltf.meshes()
+        .map(|mesh| read_mesh(&mesh, &buffers))
+        .collect()
+}
+
+fn load_buffer(
+    buffer: &gltf::Buffer,
+    blob: Option<&[u8]>,
+    base_dir: Option<&Path>,
+) -> Result<Vec<u8>, MeshError> {
+    let data = match buffer.source() {
+        gltf::buffer::Source::Bin => blob.map(<[u8]>::to_vec).ok_or_else(|| {
+            MeshError::Parse("buffer references a missing GLB binary chunk".into())
+        })?,
+        gltf::buffer::Source::Uri(uri) if uri.starts_with("data:") => {
+            let (_, encoded) = uri.split_once(";base64,").ok_or_else(|| {
+                MeshError::Unsupported(format!("non-base64 data URI in buffer {}", buffer.index()))
+            })?;
+            STANDARD.decode(encoded).map_err(|error| {
+                MeshError::Parse(format!("buffer {}: {}", buffer.index(), error))
+            })?
+        }
+        gltf::buffer::Source::Uri(uri) => {
+            let base_dir = base_dir.ok_or_else(|| {
+                MeshError::Io(format!(
+                    "external buffer '{}' without a base directory",
+                    uri
+                ))
+            })?;
+            let path = base_dir.join(uri);
+            std::fs::read(&path)
+                .map_err(|error| MeshError::Io(format!("{}: {}", path.display(), error)))?
+        }
+    };
+    if data.len() < buffer.length() {
+        return Err(MeshError::Parse(format!(
+            "buffer {} has {} bytes, expected {}",
+            buffer.index(),
+            data.len(),
+            buffer.length()
+        )));
+    }
+    Ok(data)
+}
+
+fn read_mesh(source: &gltf::Mesh, buffers: &[Vec<u8>]) -> Result<Mesh, MeshError> {
+    let mut mesh = Mesh::default();
+    // Un stream solo se conserva si todas las primitivas lo tienen.
+    let (mut has_normals, mut has_uvs, mut has_tangents, mut has_colors) = (true, true, true, true);
+
+    for (index, primitive) in source.primitives().enumerate() {
+        if primitive.mode() != gltf::mesh::Mode::Triangles {
+            return Err(MeshError::Unsupported(format!(
+                "primitive {} of mesh {} uses {:?}",
+                index,
+                source.index(),
+                primitive.mode()
+            )));
+        }
+        let reader = primitive.reader(|buffer| buffers.get(buffer.index()).map(Vec::as_slice));
+        let positions: Vec<Vec3> = reader
+            .read_positions()
+            .ok_or_else(|| MeshError::Parse(format!("primitive {} has no POSITION", index)))?
+            .map(Vec3::from_array)
+            .collect();
+        let base = mesh.positions.len() as u32;
+        let start = mesh.indices.len() as u32;
+        let count = positions.len();
+
+        match reader.read_indices() {
+            Some(indices) => mesh.indices.extend(indices.into_u32().map(|i| base + i)),
+            None => mesh.indices.extend(base..base + count as u32),
+        }
+
+        let normals: Vec<Vec3> = reader
+            .read_normals()
+            .into_iter()
+            .flatten()
+            .map(Vec3::from_array)
+            .collect();
+        let uvs: Vec<Vec2> = reader
+            .read_tex_coords(0)
+            .into_iter()
+            .flat_map(|uvs| uvs.into_f32())
+            .map(Vec2::from_array)
+            .collect();
+        let tangents: Vec<Vec4> = reader
+            .read_tangents()
+            .into_iter()
+            .flatten()
+            .map(Vec4::from_array)
+            .collect();
+        let colors: Vec<Vec4> = reader
+            .read_colors(0)
+            .into_iter()
+            .flat_map(|colors| colors.into_rgba_f32())
+            .map(Vec4::from_array)
+            .collect();
+        has_normals &= normals.len() == count;
+        has_uvs &= uvs.len() == count;
+        has_tangents &= tangents.len() == count;
+        has_colors &= colors.len() == count;
+
+        mesh.positions.extend(positions);
+        mesh.normals.extend(normals);
+        mesh.uvs.extend(uvs);
+        mesh.tangents.extend(tangents);
+        mesh.colors.extend(colors);
+        mesh.submeshes.push(SubMesh {
+            name: source
+                .name()
+                .map(|name| format!("{}.{}", name, index))
+                .unwrap_or_default(),
+            indices: start..mesh.indices.len() as u32,
+            material: primitive.material().index(),
+        });
+    }
+
+    if !has_normals {
+        mesh.normals.clear();
+    }
+    if !has_uvs {
+        mesh.uvs.clear();
+    }
+    if !has_tangents {
+        mesh.tangents.clear();
+    }
+    if !has_colors {
+        mesh.colors.clear();
+    }
+
+    mesh.validate()?;
+    mesh.generate_missing();
+    Ok(mesh)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Triángulo con posiciones y UVs en un único buffer de 60 bytes.
+    fn triangle_buffer() -> Vec<u8> {
+        let floats: [f32; 15] = [
+            0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0,
+        ];
+        floats.iter().flat_map(|f| f.to_le_bytes()).collect()
+    }
+
+    fn document(uri: Option<&str>) -> String {
+        let uri = uri
+            .map(|uri| format!(r#""uri": "{}", "#, uri))
+            .unwrap_or_default();
+        format!(
+            r#"{{
+  "asset": {{ "version": "2.0" }},
+  "buffers": [{{ {uri}"byteLength": 60 }}],
+  "bufferViews": [
+    {{ "buffer": 0, "byteOffset": 0, "byteLength": 36 }},
+    {{ "buffer": 0, "byteOffset": 36, "byteLength": 24 }}
+  ],
+  "accessors": [
+    {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+       "min": [0, 0, 0], "max": [1, 1, 0] }},
+    {{ "bufferView": 1, "componentType": 5126, "count": 3, "type": "VEC2" }}
+  ],
+  "meshes": [{{ "name": "tri", "primitives": [{{ "attributes": {{ "POSITION": 0, "TEXCOORD_0": 1 }} }}] }}]
+}}"#
+        )
+    }
+
+    fn glb(json: &str, bin: &[u8]) -> Vec<u8> {
+        let mut json = json.as_bytes().to_vec();
+        json.resize(json.len().next_multiple_of(4), b' ');
+        let total = 12 + 8 + json.len() + 8 + bin.len();
+        let mut out = Vec::new();
+        out.extend(b"glTF");
+        out.extend(2u32.to_le_bytes());
+        out.extend((total as u32).to_le_bytes());
+        out.extend((json.len() as u32).to_le_bytes());
+        out.extend(b"JSON");
+        out.extend(json);
+        out.extend((bin.len() as u32).to_le_bytes());
+        out.extend(b"BIN\0");
+        out.extend(bin);
+        out
+    }
+
+    #[test]
+    fn test_loads_gltf_and_glb() {
+        let buffer = triangle_buffer();
+        let embedded = document(Some(&format!(
+            "data:application/octet-stream;base64,{}",
+            STANDARD.encode(&buffer)
+        )));
+        let binary = glb(&document(None), &buffer);
+
+        let dir = std::env::temp_dir().join(format!("xylux-gltf-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("tri.bin"), &buffer).unwrap();
+        std::fs::write(dir.join("tri.gltf"), document(Some("tri.bin"))).unwrap();
+        let external = load_gltf(dir.join("tri.gltf"));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        for meshes in [
+            parse_gltf(embedded.as_bytes(), None),
+            parse_gltf(&binary, None),
+            external,
+        ] {
+            let meshes = meshes.unwrap();
+            assert_eq!(meshes.len(), 1);
+            let mesh = &meshes[0];
+            assert_eq!(mesh.indices, vec![0, 1, 2]);
+            assert_eq!(mesh.uvs[2], Vec2::new(0.0, 1.0));
+            assert_eq!(mesh.submeshes[0].name, "tri.0");
+            // Normales y tangentes generadas; la V crece hacia +Y, así que w = -1.
+            assert!(mesh.normals.iter().all(|n| n.abs_diff_eq(Vec3::Z, 1e-6)));
+            assert!(
+                mesh.tangents
+                    .iter()
+                    .all(|t| t.abs_diff_eq(Vec4::new(1.0, 0.0, 0.0, -1.0), 1e-6))
+            );
+        }
+
+        // Un buffer externo sin directorio base no se puede resolver.
+        assert!(matches!(
+            parse_gltf(document(Some("tri.bin")).as_bytes(), None),
+            Err(MeshError::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,163 @@
+//! Niveles de detalle (LOD) por entidad.
+//!
+//! Una entidad con `Lod` dibuja, en lugar de la malla de su `MeshRenderer`, la del
+//! nivel que corresponda a su tamaño en pantalla o a su distancia a la cámara. La
+//! histéresis evita que un objeto justo en un umbral cambie de nivel cada frame.
+
+use super::Mesh;
+use xylux_ecs::{Component, Handle};
+
+/// Medida con la que se elige el nivel.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LodMetric {
+    /// Fracción de la altura del viewport que ocupa la esfera que envuelve la
+    /// malla; un nivel se usa mientras la fracción sea al menos su umbral.
+    #[default]
+    ScreenSize,
+    /// Distancia de la cámara al centro de la malla; un nivel se usa mientras la
+    /// distancia no supere su umbral.
+    Distance,
+}
+
+/// Nivel de detalle: una malla y el umbral hasta el que se usa.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LodLevel {
+    pub mesh: Handle<Mesh>,
+    pub threshold: f32,
+}
+
+/// Componente de niveles de detalle, del más detallado al menos detallado.
+///
+/// El último nivel se usa cuando no se cumple ningún umbral. Con varias cámaras
+/// manda la que ve la entidad con más detalle.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Lod {
+    pub levels: Vec<LodLevel>,
+    pub metric: LodMetric,
+    /// Margen relativo alrededor de cada umbral que hay que cruzar para cambiar de
+    /// nivel (e.g. `0.1` para un 10 %).
+    pub hysteresis: f32,
+    current: usize,
+}
+
+impl Component for Lod {}
+
+impl Lod {
+    pub fn new(metric: LodMetric, levels: Vec<LodLevel>) -> Self {
+        Self {
+            levels,
+            metric,
+            hysteresis: 0.1,
+            current: 0,
+        }
+    }
+
+    pub fn with_hysteresis(mut self, hysteresis: f32) -> Self {
+        self.hysteresis = hysteresis;
+        self
+    }
+
+    /// Nivel elegido en el último `select`.
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    /// Malla del nivel actual.
+    pub fn mesh(&self) -> Option<Handle<Mesh>> {
+        self.levels
+            .get(self.current.min(self.levels.len().saturating_sub(1)))
+            .map(|level| level.mesh)
+    }
+
+    /// Elige el nivel para `value` (según `metric`) y lo guarda como actual.
+    ///
+    /// Para pasar a un nivel menos detallado el valor tiene que salir del umbral
+    /// actual por más de `hysteresis`, y para volver a uno más detallado tiene que
+    /// superar el suyo por el mismo margen.
+    pub fn select(&mut self, value: f32) -> usize {
+        let (keep, strict) = match self.metric {
+            LodMetric::ScreenSize => (1.0 - self.hysteresis, 1.0 + self.hysteresis),
+            LodMetric::Distance => (1.0 + self.hysteresis, 1.0 - self.hysteresis),
+        };
+        let current = self.current.min(self.levels.len().saturating_sub(1));
+        let candidate = self.level_for(value, 1.0);
+        self.current = if candidate > current {
+            self.level_for(value, keep).max(current)
+        } else if candidate < current {
+            self.level_for(value, strict).min(current)
+        } else {
+            current
+        };
+        self.current
+    }
+
+    /// Primer nivel cuyo umbral, multiplicado por `scale`, admite `value`.
+    fn level_for(&self, value: f32, scale: f32) -> usize {
+        self.levels
+            .iter()
+            .position(|level| match self.metric {
+                LodMetric::ScreenSize => value >= level.threshold * scale,
+                LodMetric::Distance => value <= level.threshold * scale,
+            })
+            .unwrap_or(self.levels.len().saturating_sub(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xylux_ecs::Pool;
+
+    #[test]
+    fn test_lod_switches_with_hysteresis() {
+        let mut meshes = Pool::new();
+        let [fine, medium, coarse] = [0, 1, 2].map(|_| meshes.insert(Mesh::default()));
+        let mut lod = Lod::new(
+            LodMetric::Distance,
+            vec![
+                LodLevel {
+                    mesh: fine,
+                    threshold: 10.0,
+                },
+                LodLevel {
+                    mesh: medium,
+                    threshold: 20.0,
+                },
+                LodLevel {
+                    mesh: coarse,
+                    threshold: f32::INFINITY,
+                },
+            ],
+        );
+        assert_eq!(lod.select(5.0), 0);
+        // Justo pasado el umbral no cambia; pasado el margen del 10 % sí.
+        assert_eq!(lod.select(10.5), 0);
+        assert_eq!(lod.select(11.5), 1);
+        // Para volver al nivel detallado hay que bajar de 9.
+        assert_eq!(lod.select(9.5), 1);
+        assert_eq!(lod.select(8.5), 0);
+        // Un salto grande cruza varios niveles de golpe.
+        assert_eq!(lod.select(100.0), 2);
+        assert_eq!(lod.mesh(), Some(coarse));
+
+        let mut screen = Lod::new(
+            LodMetric::ScreenSize,
+            vec![
+                LodLevel {
+                    mesh: fine,
+                    threshold: 0.5,
+                },
+                LodLevel {
+                    mesh: coarse,
+                    threshold: 0.0,
+                },
+            ],
+        )
+        .with_hysteresis(0.2);
+        assert_eq!(screen.select(0.6), 0);
+        assert_eq!(screen.select(0.45), 0);
+        assert_eq!(screen.select(0.3), 1);
+        assert_eq!(screen.select(0.55), 1);
+        assert_eq!(screen.select(0.65), 0);
+    }
+}
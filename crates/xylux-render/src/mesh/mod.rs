@@ -0,0 +1,479 @@
+//! # Módulo Mesh
+//!
+//! Mallas de triángulos y el componente `MeshRenderer`, que junto con `Transform`
+//! hace que una entidad se dibuje.
+//!
+//! Una `Mesh` guarda cada atributo en su propio stream (posición, normal, UV,
+//! tangente y color); los streams vacíos se consideran ausentes. Al subirla a la
+//! GPU se intercalan en el formato de `Mesh::vertex_layout`, rellenando los
+//! ausentes con valores por defecto.
+//!
+//! - `obj`: carga de Wavefront OBJ.
+//! - `gltf`: carga de glTF 2.0 (`.gltf` con buffers externos o embebidos y `.glb`).
+//! - `procedural`: primitivas (`Mesh::cube`, `Mesh::uv_sphere`, `Mesh::torus`...).
+//! - `terrain`: terreno con niveles de detalle a partir de un `Heightmap`.
+//! - `lod`: componente `Lod`, que cambia la malla según el tamaño en pantalla o la
+//!   distancia.
+
+pub mod gltf;
+pub mod lod;
+pub mod obj;
+pub mod procedural;
+pub mod terrain;
+
+pub use gltf::{load_gltf, parse_gltf};
+pub use lod::{Lod, LodLevel, LodMetric};
+pub use obj::{load_obj, parse_obj};
+pub use terrain::{Heightmap, TerrainDesc};
+
+use crate::backend::{VertexAttribute, VertexFormat, VertexLayout, VertexStepMode};
+use crate::material::Material;
+use glam::{Vec2, Vec3, Vec4};
+use std::fmt;
+use std::ops::Range;
+use xylux_ecs::{Aabb, Component, Handle};
+
+/// Rango de índices de una malla que se dibuja con un mismo material.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SubMesh {
+    pub name: String,
+    /// Rango dentro de `Mesh::indices`; múltiplo de tres.
+    pub indices: Range<u32>,
+    /// Índice del material en el archivo de origen, si lo tiene.
+    pub material: Option<usize>,
+}
+
+/// Errores al cargar o validar una malla.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MeshError {
+    /// No se pudo leer el archivo o uno de sus buffers.
+    Io(String),
+    /// El archivo no es válido.
+    Parse(String),
+    /// El archivo usa una característica no soportada (e.g. primitivas de líneas).
+    Unsupported(String),
+    /// Un stream no tiene un elemento por vértice.
+    StreamLength {
+        stream: &'static str,
+        len: usize,
+        expected: usize,
+    },
+    /// El número de índices no es múltiplo de tres.
+    IndexCount(usize),
+    /// Un índice apunta fuera de los vértices.
+    IndexOutOfBounds { index: u32, vertex_count: usize },
+    /// El rango de una submalla se sale de los índices o no contiene triángulos completos.
+    SubMeshRange { submesh: usize, indices: Range<u32> },
+    /// Una posición es `NaN` o infinita.
+    NonFinitePosition(usize),
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MeshError::Io(message) => write!(f, "I/O error: {}", message),
+            MeshError::Parse(message) => write!(f, "Parse error: {}", message),
+            MeshError::Unsupported(what) => write!(f, "Unsupported: {}", what),
+            MeshError::StreamLength {
+                stream,
+                len,
+                expected,
+            } => {
+                write!(
+                    f,
+                    "Stream '{}' has {} elements, expected {}",
+                    stream, len, expected
+                )
+            }
+            MeshError::IndexCount(count) => {
+                write!(f, "Index count {} is not a multiple of 3", count)
+            }
+            MeshError::IndexOutOfBounds {
+                index,
+                vertex_count,
+            } => {
+                write!(
+                    f,
+                    "Index {} out of bounds for {} vertices",
+                    index, vertex_count
+                )
+            }
+            MeshError::SubMeshRange { submesh, indices } => {
+                write!(
+                    f,
+                    "Submesh {} has invalid index range {:?}",
+                    submesh, indices
+                )
+            }
+            MeshError::NonFinitePosition(vertex) => {
+                write!(f, "Vertex {} has a non-finite position", vertex)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MeshError {}
+
+/// Malla indexada de triángulos.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Mesh {
+    pub positions: Vec<Vec3>,
+    pub normals: Vec<Vec3>,
+    pub uvs: Vec<Vec2>,
+    /// Tangente en `xyz` y signo de la bitangente en `w`. Como en glTF, la
+    /// bitangente `normal.cross(tangente) * w` apunta hacia arriba en la textura
+    /// (`v` decreciente).
+    pub tangents: Vec<Vec4>,
+    /// Color lineal RGBA por vértice.
+    pub colors: Vec<Vec4>,
+    /// Tres índices por triángulo, en sentido antihorario visto desde fuera.
+    pub indices: Vec<u32>,
+    /// Submallas; si está vacío, toda la malla es una única submalla.
+    pub submeshes: Vec<SubMesh>,
+}
+
+impl Mesh {
+    /// Bytes por vértice en el formato intercalado de `vertex_layout`.
+    pub const VERTEX_STRIDE: u32 = 64;
+
+    pub fn new(positions: Vec<Vec3>, indices: Vec<u32>) -> Self {
+        Self {
+            positions,
+            indices,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_normals(mut self, normals: Vec<Vec3>) -> Self {
+        self.normals = normals;
+        self
+    }
+
+    pub fn with_uvs(mut self, uvs: Vec<Vec2>) -> Self {
+        self.uvs = uvs;
+        self
+    }
+
+    pub fn with_tangents(mut self, tangents: Vec<Vec4>) -> Self {
+        self.tangents = tangents;
+        self
+    }
+
+    pub fn with_colors(mut self, colors: Vec<Vec4>) -> Self {
+        self.colors = colors;
+        self
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// Submallas de la malla; una sola que cubre todos los índices si no hay ninguna.
+    pub fn submeshes(&self) -> Vec<SubMesh> {
+        if self.submeshes.is_empty() {
+            vec![SubMesh {
+                indices: 0..self.indices.len() as u32,
+                ..Default::default()
+            }]
+        } else {
+            self.submeshes.clone()
+        }
+    }
+
+    /// Comprueba que los streams, índices y submallas son coherentes.
+    pub fn validate(&self) -> Result<(), MeshError> {
+        let expected = self.positions.len();
+        let streams = [
+            ("normals", self.normals.len()),
+            ("uvs", self.uvs.len()),
+            ("tangents", self.tangents.len()),
+            ("colors", self.colors.len()),
+        ];
+        for (stream, len) in streams {
+            if len != 0 && len != expected {
+                return Err(MeshError::StreamLength {
+                    stream,
+                    len,
+                    expected,
+                });
+            }
+        }
+        if let Some(vertex) = self.positions.iter().position(|p| !p.is_finite()) {
+            return Err(MeshError::NonFinitePosition(vertex));
+        }
+        if !self.indices.len().is_multiple_of(3) {
+            return Err(MeshError::IndexCount(self.indices.len()));
+        }
+        if let Some(&index) = self
+            .indices
+            .iter()
+            .find(|&&index| index as usize >= expected)
+        {
+            return Err(MeshError::IndexOutOfBounds {
+                index,
+                vertex_count: expected,
+            });
+        }
+        for (submesh, range) in self.submeshes.iter().map(|s| &s.indices).enumerate() {
+            let valid = range.start <= range.end
+                && range.end as usize <= self.indices.len()
+                && range.start % 3 == 0
+                && range.end % 3 == 0;
+            if !valid {
+                return Err(MeshError::SubMeshRange {
+                    submesh,
+                    indices: range.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Caja que contiene todas las posiciones (`Aabb::EMPTY` si no hay vértices).
+    pub fn aabb(&self) -> Aabb {
+        Aabb::from_points(self.positions.iter().copied())
+    }
+
+    /// Calcula normales suaves, ponderadas por el área de cada triángulo.
+    pub fn compute_normals(&mut self) {
+        let mut normals = vec![Vec3::ZERO; self.positions.len()];
+        for triangle in self.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| self.positions[triangle[i] as usize]);
+            // Sin normalizar: su longitud es el doble del área.
+            let normal = (b - a).cross(c - a);
+            for &index in triangle {
+                normals[index as usize] += normal;
+            }
+        }
+        self.normals = normals
+            .into_iter()
+            .map(|n| n.try_normalize().unwrap_or(Vec3::Z))
+            .collect();
+    }
+
+    /// Calcula tangentes a partir de las UVs y normales (método de Lengyel).
+    ///
+    /// Sin UVs, usa una tangente cualquiera perpendicular a la normal. Calcula las
+    /// normales antes si faltan.
+    pub fn compute_tangents(&mut self) {
+        if self.normals.is_empty() {
+            self.compute_normals();
+        }
+        let count = self.positions.len();
+        let mut tangents = vec![Vec3::ZERO; count];
+        let mut bitangents = vec![Vec3::ZERO; count];
+
+        if !self.uvs.is_empty() {
+            for triangle in self.indices.chunks_exact(3) {
+                let [i0, i1, i2] = [0, 1, 2].map(|i| triangle[i] as usize);
+                let (e1, e2) = (
+                    self.positions[i1] - self.positions[i0],
+                    self.positions[i2] - self.positions[i0],
+                );
+                let (d1, d2) = (self.uvs[i1] - self.uvs[i0], self.uvs[i2] - self.uvs[i0]);
+                let det = d1.x * d2.y - d2.x * d1.y;
+                if det.abs() <= f32::EPSILON {
+                    continue;
+                }
+                let r = 1.0 / det;
+                let tangent = (e1 * d2.y - e2 * d1.y) * r;
+                let bitangent = (e2 * d1.x - e1 * d2.x) * r;
+                for index in [i0, i1, i2] {
+                    tangents[index] += tangent;
+                    bitangents[index] += bitangent;
+                }
+            }
+        }
+
+        self.tangents = (0..count)
+            .map(|i| {
+                let normal = self.normals[i];
+                // Gram-Schmidt: quitar la componente de la normal.
+                let tangent = (tangents[i] - normal * normal.dot(tangents[i]))
+                    .try_normalize()
+                    .unwrap_or_else(|| normal.any_orthonormal_vector());
+                // `bitangents` sigue a `v` creciente, que baja en la textura.
+                let handedness = if normal.cross(tangent).dot(bitangents[i]) > 0.0 {
+                    -1.0
+                } else {
+                    1.0
+                };
+                tangent.extend(handedness)
+            })
+            .collect();
+    }
+
+    /// Genera las normales y tangentes que falten.
+    pub fn generate_missing(&mut self) {
+        if self.normals.is_empty() {
+            self.compute_normals();
+        }
+        if self.tangents.is_empty() {
+            self.compute_tangents();
+        }
+    }
+
+    /// Disposición del vertex buffer intercalado (slot 0 del pipeline de mallas):
+    /// posición, normal, UV, tangente y color en las locations 0 a 4.
+    pub fn vertex_layout() -> VertexLayout {
+        let attribute = |location, format, offset| VertexAttribute {
+            location,
+            format,
+            offset,
+        };
+        VertexLayout {
+            stride: Self::VERTEX_STRIDE,
+            step_mode: VertexStepMode::Vertex,
+            attributes: vec![
+                attribute(0, VertexFormat::Float32x3, 0),
+                attribute(1, VertexFormat::Float32x3, 12),
+                attribute(2, VertexFormat::Float32x2, 24),
+                attribute(3, VertexFormat::Float32x4, 32),
+                attribute(4, VertexFormat::Float32x4, 48),
+            ],
+        }
+    }
+
+    /// Vértices intercalados como floats little-endian según `vertex_layout`.
+    ///
+    /// Los streams ausentes se rellenan con normal `+Z`, UV `0`, tangente `+X` y
+    /// color blanco.
+    pub fn vertex_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.positions.len() * Self::VERTEX_STRIDE as usize);
+        for (i, position) in self.positions.iter().enumerate() {
+            let normal = self.normals.get(i).copied().unwrap_or(Vec3::Z);
+            let uv = self.uvs.get(i).copied().unwrap_or(Vec2::ZERO);
+            let tangent = self
+                .tangents
+                .get(i)
+                .copied()
+                .unwrap_or(Vec4::new(1.0, 0.0, 0.0, 1.0));
+            let color = self.colors.get(i).copied().unwrap_or(Vec4::ONE);
+            let floats = position
+                .to_array()
+                .into_iter()
+                .chain(normal.to_array())
+                .chain(uv.to_array())
+                .chain(tangent.to_array())
+                .chain(color.to_array());
+            bytes.extend(floats.flat_map(f32::to_le_bytes));
+        }
+        bytes
+    }
+
+    pub fn index_bytes(&self) -> Vec<u8> {
+        self.indices
+            .iter()
+            .flat_map(|index| index.to_le_bytes())
+            .collect()
+    }
+}
+
+/// Componente que dibuja una malla con un material en la posición del `Transform`
+/// de la entidad.
+///
+/// Las entidades con la misma malla y material se agrupan en un único draw instanciado.
+/// Si la entidad tiene además un componente `Handle<Material>`, ese material
+/// sustituye a `material`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct MeshRenderer {
+    pub mesh: Handle<Mesh>,
+    pub material: Handle<Material>,
+}
+
+impl MeshRenderer {
+    pub fn new(mesh: Handle<Mesh>, material: Handle<Material>) -> Self {
+        Self { mesh, material }
+    }
+}
+
+impl Component for MeshRenderer {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quad() -> Mesh {
+        Mesh::new(
+            vec![
+                Vec3::new(-1.0, -1.0, 0.0),
+                Vec3::new(1.0, -1.0, 0.0),
+                Vec3::new(1.0, 1.0, 0.0),
+                Vec3::new(-1.0, 1.0, 0.0),
+            ],
+            vec![0, 1, 2, 2, 3, 0],
+        )
+        .with_uvs(vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(0.0, 1.0),
+        ])
+    }
+
+    #[test]
+    fn test_validation_and_aabb() {
+        let mut mesh = quad();
+        assert_eq!(mesh.validate(), Ok(()));
+        assert_eq!(
+            mesh.aabb(),
+            Aabb::new(Vec3::new(-1.0, -1.0, 0.0), Vec3::new(1.0, 1.0, 0.0))
+        );
+
+        mesh.colors = vec![Vec4::ONE; 3];
+        assert!(matches!(
+            mesh.validate(),
+            Err(MeshError::StreamLength {
+                stream: "colors",
+                ..
+            })
+        ));
+        mesh.colors.clear();
+
+        mesh.indices.push(7);
+        assert_eq!(mesh.validate(), Err(MeshError::IndexCount(7)));
+        mesh.indices.extend([0, 1]);
+        assert!(matches!(
+            mesh.validate(),
+            Err(MeshError::IndexOutOfBounds { index: 7, .. })
+        ));
+        mesh.indices.truncate(6);
+
+        mesh.submeshes = vec![SubMesh {
+            indices: 3..9,
+            ..Default::default()
+        }];
+        assert!(matches!(
+            mesh.validate(),
+            Err(MeshError::SubMeshRange { submesh: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_generates_normals_and_tangents() {
+        let mut mesh = quad();
+        mesh.generate_missing();
+        assert!(mesh.normals.iter().all(|n| n.abs_diff_eq(Vec3::Z, 1e-6)));
+        // U crece en +X y V en +Y, así que arriba en la textura es -Y: w = -1.
+        assert!(
+            mesh.tangents
+                .iter()
+                .all(|t| t.abs_diff_eq(Vec4::new(1.0, 0.0, 0.0, -1.0), 1e-6))
+        );
+
+        // Con V invertida la bitangente cambia de signo.
+        let mut flipped = quad();
+        flipped.uvs.iter_mut().for_each(|uv| uv.y = 1.0 - uv.y);
+        flipped.generate_missing();
+        assert!(flipped.tangents.iter().all(|t| t.w == 1.0));
+
+        let bytes = mesh.vertex_bytes();
+        assert_eq!(bytes.len(), 4 * Mesh::VERTEX_STRIDE as usize);
+        // Color por defecto blanco en el offset 48.
+        assert_eq!(&bytes[48..52], &1.0f32.to_le_bytes());
+    }
+}
@@ -0,0 +1,142 @@
+//! Carga de mallas Wavefront OBJ.
+//!
+//! Todos los objetos y grupos del archivo se combinan en una `Mesh`, con una
+//! submalla por cada uno. Las caras se triangulan y los vértices se desduplican por
+//! combinación de posición, normal y UV. Los materiales (`.mtl`) no se cargan; solo
+//! se guarda su índice en la submalla.
+
+use super::{Mesh, MeshError, SubMesh};
+use glam::{Vec2, Vec3, Vec4};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// Carga un archivo `.obj`.
+pub fn load_obj(path: impl AsRef<Path>) -> Result<Mesh, MeshError> {
+    let path = path.as_ref();
+    let file = File::open(path)
+        .map_err(|error| MeshError::Io(format!("{}: {}", path.display(), error)))?;
+    parse_obj(&mut BufReader::new(file))
+}
+
+/// Lee un OBJ desde `reader`, genera las normales y tangentes que falten y valida el resultado.
+pub fn parse_obj(reader: &mut impl BufRead) -> Result<Mesh, MeshError> {
+    let options = tobj::LoadOptions {
+        single_index: true,
+        triangulate: true,
+        ignore_points: true,
+        ignore_lines: true,
+    };
+    let (models, _materials) =
+        tobj::load_obj_buf(reader, &options, |_| Err(tobj::LoadError::OpenFileFailed))
+            .map_err(|error| MeshError::Parse(error.to_string()))?;
+
+    let mut mesh = Mesh::default();
+    let (mut has_normals, mut has_uvs, mut has_colors) = (true, true, true);
+    for model in &models {
+        let source = &model.mesh;
+        let base = mesh.positions.len() as u32;
+        let start = mesh.indices.len() as u32;
+        let count = source.positions.len() / 3;
+
+        mesh.positions.extend(
+            source
+                .positions
+                .chunks_exact(3)
+                .map(|p| Vec3::new(p[0], p[1], p[2])),
+        );
+        has_normals &= source.normals.len() == count * 3;
+        has_uvs &= source.texcoords.len() == count * 2;
+        has_colors &= source.vertex_color.len() == count * 3;
+        mesh.normals.extend(
+            source
+                .normals
+                .chunks_exact(3)
+                .map(|n| Vec3::new(n[0], n[1], n[2])),
+        );
+        // OBJ tiene el origen de V abajo; las texturas se muestrean con el origen arriba.
+        mesh.uvs.extend(
+            source
+                .texcoords
+                .chunks_exact(2)
+                .map(|t| Vec2::new(t[0], 1.0 - t[1])),
+        );
+        mesh.colors.extend(
+            source
+                .vertex_color
+                .chunks_exact(3)
+                .map(|c| Vec4::new(c[0], c[1], c[2], 1.0)),
+        );
+        mesh.indices
+            .extend(source.indices.iter().map(|index| base + index));
+
+        mesh.submeshes.push(SubMesh {
+            name: model.name.clone(),
+            indices: start..mesh.indices.len() as u32,
+            material: source.material_id,
+        });
+    }
+
+    // Si algún objeto no tiene un stream, el stream se descarta para toda la malla.
+    if !has_normals {
+        mesh.normals.clear();
+    }
+    if !has_uvs {
+        mesh.uvs.clear();
+    }
+    if !has_colors {
+        mesh.colors.clear();
+    }
+
+    mesh.validate()?;
+    mesh.generate_missing();
+    Ok(mesh)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_parse_obj_with_groups_and_quads() {
+        let source = "\
+# Un quad y un triángulo en dos objetos
+o quad
+v -1 -1 0
+v 1 -1 0
+v 1 1 0
+v -1 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+f 1/1 2/2 3/3 4/4
+o tri
+v 0 0 1
+v 1 0 1
+v 0 1 1
+vt 0 0
+vt 1 0
+vt 0 1
+f 5/5 6/6 7/7
+";
+        let mesh = parse_obj(&mut Cursor::new(source)).unwrap();
+        assert_eq!(mesh.vertex_count(), 7);
+        assert_eq!(mesh.triangle_count(), 3);
+        let names: Vec<_> = mesh
+            .submeshes
+            .iter()
+            .map(|s| (s.name.as_str(), s.indices.clone()))
+            .collect();
+        assert_eq!(names, vec![("quad", 0..6), ("tri", 6..9)]);
+
+        // Normales generadas (caras hacia +Z) y V invertida.
+        assert!(mesh.normals.iter().all(|n| n.abs_diff_eq(Vec3::Z, 1e-6)));
+        assert_eq!(mesh.uvs[0], Vec2::new(0.0, 1.0));
+        assert_eq!(mesh.tangents.len(), 7);
+        assert_eq!(mesh.aabb().max, Vec3::new(1.0, 1.0, 1.0));
+
+        assert!(parse_obj(&mut Cursor::new("f 1 2 3\n")).is_err());
+    }
+}
@@ -0,0 +1,574 @@
+//! Generación procedural de primitivas.
+//!
+//! Todas las primitivas están centradas en el origen con `+Y` hacia arriba, tienen
+//! normales analíticas, UVs en `[0, 1]` con el origen arriba a la izquierda y
+//! tangentes calculadas a partir de ellas. Las costuras de textura duplican
+//! vértices, así que las normales y tangentes no se mezclan entre los dos lados.
+//!
+//! Las superficies de revolución (esfera UV, cilindro, cono, cápsula y toro) se
+//! generan girando un perfil alrededor de `Y`; `u` crece en sentido antihorario
+//! visto desde arriba, empezando en `+X`.
+
+use super::Mesh;
+use glam::{Vec2, Vec3};
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+/// Punto del perfil de una superficie de revolución.
+#[derive(Clone, Copy)]
+struct ProfilePoint {
+    /// Distancia al eje `Y`.
+    radius: f32,
+    y: f32,
+    /// Normal en el plano del perfil: componente radial y componente `Y`.
+    normal: Vec2,
+    v: f32,
+}
+
+impl Mesh {
+    /// Cubo de lado `size` con `subdivisions` divisiones por arista en cada cara.
+    ///
+    /// Cada cara tiene sus propios vértices y el rango completo de UVs.
+    pub fn cube(size: f32, subdivisions: u32) -> Self {
+        let half = size * 0.5;
+        // Normal, derecha y abajo de cada cara vista desde fuera.
+        let faces = [
+            (Vec3::X, Vec3::NEG_Z, Vec3::NEG_Y),
+            (Vec3::NEG_X, Vec3::Z, Vec3::NEG_Y),
+            (Vec3::Y, Vec3::X, Vec3::Z),
+            (Vec3::NEG_Y, Vec3::X, Vec3::NEG_Z),
+            (Vec3::Z, Vec3::X, Vec3::NEG_Y),
+            (Vec3::NEG_Z, Vec3::NEG_X, Vec3::NEG_Y),
+        ];
+        let mut mesh = Mesh::default();
+        for (normal, right, down) in faces {
+            let origin = (normal - right - down) * half;
+            mesh.push_grid(origin, right * size, down * size, normal, [subdivisions; 2]);
+        }
+        mesh.finish()
+    }
+
+    /// Plano en `XZ` mirando a `+Y`, de `width` × `depth` y con la rejilla de
+    /// `subdivisions` celdas en cada eje. La `v` crece hacia `+Z`.
+    pub fn plane(width: f32, depth: f32, subdivisions: [u32; 2]) -> Self {
+        let origin = Vec3::new(-width * 0.5, 0.0, -depth * 0.5);
+        let mut mesh = Mesh::default();
+        mesh.push_grid(
+            origin,
+            Vec3::X * width,
+            Vec3::Z * depth,
+            Vec3::Y,
+            subdivisions,
+        );
+        mesh.finish()
+    }
+
+    /// Esfera UV con `segments` divisiones en longitud y `rings` en latitud.
+    pub fn uv_sphere(radius: f32, segments: u32, rings: u32) -> Self {
+        let rings = rings.max(2);
+        let profile: Vec<_> = (0..=rings)
+            .map(|ring| {
+                let v = ring as f32 / rings as f32;
+                sphere_point(radius, v * PI, 0.0, v)
+            })
+            .collect();
+        let mut mesh = Mesh::default();
+        mesh.push_lathe(&profile, segments);
+        mesh.finish()
+    }
+
+    /// Icosaedro subdividido `subdivisions` veces y proyectado sobre la esfera.
+    ///
+    /// Reparte los vértices de forma más uniforme que `uv_sphere`. Las UVs son
+    /// esféricas, como las de `uv_sphere`; en los triángulos que cruzan la costura
+    /// `u` pasa de 1, así que la textura debe muestrearse en modo repetición.
+    pub fn icosphere(radius: f32, subdivisions: u32) -> Self {
+        let (points, triangles) = icosahedron(subdivisions);
+
+        let mut mesh = Mesh::default();
+        // Vértice original y si su `u` se desplaza una vuelta para cerrar la costura.
+        let mut emitted: HashMap<(u32, bool), u32> = HashMap::new();
+        for triangle in triangles {
+            let us = triangle.map(|i| sphere_u(points[i as usize]));
+            // Un triángulo que cruza la costura tiene `u` cerca de 0 y de 1 a la vez.
+            let crosses_seam = us.iter().any(|&u| u > 0.75) && us.iter().any(|&u| u < 0.25);
+            let indices = triangle.map(|i| {
+                let point = points[i as usize];
+                let wrap = crosses_seam && sphere_u(point) < 0.5;
+                let key = (i, wrap && !is_pole(point));
+                match emitted.entry(key) {
+                    Entry::Occupied(entry) => *entry.get(),
+                    Entry::Vacant(entry) => {
+                        let u = sphere_u(point) + if key.1 { 1.0 } else { 0.0 };
+                        let v = point.y.clamp(-1.0, 1.0).acos() / PI;
+                        mesh.positions.push(point * radius);
+                        mesh.normals.push(point);
+                        mesh.uvs.push(Vec2::new(u, v));
+                        *entry.insert(mesh.positions.len() as u32 - 1)
+                    }
+                }
+            });
+            mesh.indices.extend(indices);
+        }
+
+        // En los polos la `u` no está definida: cada triángulo que los toca recibe
+        // su propia copia con la `u` media de los otros dos vértices.
+        for triangle in 0..mesh.indices.len() / 3 {
+            let corners = &mut mesh.indices[triangle * 3..triangle * 3 + 3];
+            for corner in 0..3 {
+                let index = corners[corner] as usize;
+                if !is_pole(mesh.normals[index]) {
+                    continue;
+                }
+                let others = [corners[(corner + 1) % 3], corners[(corner + 2) % 3]];
+                let u = others.iter().map(|&i| mesh.uvs[i as usize].x).sum::<f32>() * 0.5;
+                mesh.positions.push(mesh.positions[index]);
+                mesh.normals.push(mesh.normals[index]);
+                mesh.uvs.push(Vec2::new(u, mesh.uvs[index].y));
+                corners[corner] = mesh.positions.len() as u32 - 1;
+            }
+        }
+        // Los vértices originales de los polos ya no se usan, pero no molestan; se
+        // quitan para que el recuento de vértices sea exacto.
+        mesh.remove_unused_vertices();
+        mesh.finish()
+    }
+
+    /// Cilindro de eje `Y` con tapas, `segments` divisiones alrededor y `stacks` a
+    /// lo largo de la altura.
+    pub fn cylinder(radius: f32, height: f32, segments: u32, stacks: u32) -> Self {
+        Self::frustum(radius, radius, height, segments, stacks)
+    }
+
+    /// Cono de eje `Y` con la base abajo y el vértice en `+Y`.
+    pub fn cone(radius: f32, height: f32, segments: u32, stacks: u32) -> Self {
+        Self::frustum(radius, 0.0, height, segments, stacks)
+    }
+
+    /// Cápsula de eje `Y`: un cilindro de altura `height` entre dos semiesferas de
+    /// radio `radius` con `rings` divisiones cada una. La altura total es
+    /// `height + 2 * radius`.
+    pub fn capsule(radius: f32, height: f32, segments: u32, rings: u32) -> Self {
+        let rings = rings.max(1);
+        let half = height * 0.5;
+        // La `v` sigue la longitud del perfil para que la textura no se estire.
+        let length = PI * radius + height;
+        let arc = |theta: f32| theta * radius / length;
+
+        let mut profile = Vec::new();
+        for ring in 0..=rings {
+            let theta = FRAC_PI_2 * ring as f32 / rings as f32;
+            profile.push(sphere_point(radius, theta, half, arc(theta)));
+        }
+        for ring in 0..=rings {
+            let theta = FRAC_PI_2 + FRAC_PI_2 * ring as f32 / rings as f32;
+            let v = arc(theta) + height / length;
+            profile.push(sphere_point(radius, theta, -half, v));
+        }
+        let mut mesh = Mesh::default();
+        mesh.push_lathe(&profile, segments);
+        mesh.finish()
+    }
+
+    /// Toro en el plano `XZ`: un tubo de radio `minor_radius` cuyo centro recorre
+    /// una circunferencia de radio `major_radius`.
+    pub fn torus(
+        major_radius: f32,
+        minor_radius: f32,
+        major_segments: u32,
+        minor_segments: u32,
+    ) -> Self {
+        let minor_segments = minor_segments.max(3);
+        // El perfil empieza por el lado exterior y sube, así que `v` recorre el tubo
+        // hacia arriba por fuera y vuelve por dentro.
+        let profile: Vec<_> = (0..=minor_segments)
+            .map(|segment| {
+                let v = segment as f32 / minor_segments as f32;
+                let (sin, cos) = (v * TAU).sin_cos();
+                ProfilePoint {
+                    radius: major_radius + minor_radius * cos,
+                    y: minor_radius * sin,
+                    normal: Vec2::new(cos, sin),
+                    v: 1.0 - v,
+                }
+            })
+            .collect();
+        let mut mesh = Mesh::default();
+        mesh.push_lathe(&profile, major_segments);
+        mesh.finish()
+    }
+
+    /// Tronco de cono con tapas; `top_radius == 0` da un cono sin tapa superior.
+    fn frustum(
+        bottom_radius: f32,
+        top_radius: f32,
+        height: f32,
+        segments: u32,
+        stacks: u32,
+    ) -> Self {
+        let stacks = stacks.max(1);
+        let half = height * 0.5;
+        // Perpendicular hacia fuera de la generatriz, que va de la base al techo.
+        let normal = Vec2::new(height, bottom_radius - top_radius).normalize_or(Vec2::X);
+        let profile: Vec<_> = (0..=stacks)
+            .map(|stack| {
+                let t = stack as f32 / stacks as f32;
+                ProfilePoint {
+                    radius: bottom_radius + (top_radius - bottom_radius) * t,
+                    y: -half + height * t,
+                    normal,
+                    v: 1.0 - t,
+                }
+            })
+            .collect();
+
+        let mut mesh = Mesh::default();
+        mesh.push_lathe(&profile, segments);
+        if top_radius > 0.0 {
+            mesh.push_disc(top_radius, half, Vec3::Y, segments);
+        }
+        if bottom_radius > 0.0 {
+            mesh.push_disc(bottom_radius, -half, Vec3::NEG_Y, segments);
+        }
+        mesh.finish()
+    }
+
+    /// Añade una rejilla rectangular: `origin + right * s + down * t` con `s, t` en
+    /// `[0, 1]` y UV `(s, t)`.
+    pub(crate) fn push_grid(
+        &mut self,
+        origin: Vec3,
+        right: Vec3,
+        down: Vec3,
+        normal: Vec3,
+        subdivisions: [u32; 2],
+    ) {
+        let [columns, rows] = subdivisions.map(|n| n.max(1));
+        let base = self.positions.len() as u32;
+        for row in 0..=rows {
+            for column in 0..=columns {
+                let uv = Vec2::new(column as f32 / columns as f32, row as f32 / rows as f32);
+                self.positions.push(origin + right * uv.x + down * uv.y);
+                self.normals.push(normal);
+                self.uvs.push(uv);
+            }
+        }
+        let stride = columns + 1;
+        for row in 0..rows {
+            for column in 0..columns {
+                let a = base + row * stride + column;
+                let [b, c, d] = [a + 1, a + stride + 1, a + stride];
+                self.push_triangle([a, b, c], normal);
+                self.push_triangle([a, c, d], normal);
+            }
+        }
+    }
+
+    /// Gira `profile` alrededor de `Y` en `segments` pasos. Las filas de radio cero
+    /// (polos y vértices de conos) no generan triángulos degenerados.
+    fn push_lathe(&mut self, profile: &[ProfilePoint], segments: u32) {
+        let segments = segments.max(3);
+        let base = self.positions.len() as u32;
+        for point in profile {
+            for segment in 0..=segments {
+                let u = segment as f32 / segments as f32;
+                let radial = lathe_direction(u);
+                self.positions
+                    .push(radial * point.radius + Vec3::Y * point.y);
+                self.normals.push(
+                    (radial * point.normal.x + Vec3::Y * point.normal.y).normalize_or(Vec3::Y),
+                );
+                self.uvs.push(Vec2::new(u, point.v));
+            }
+        }
+
+        let stride = segments + 1;
+        for (row, pair) in profile.windows(2).enumerate() {
+            for segment in 0..segments {
+                let a = base + row as u32 * stride + segment;
+                let [b, c, d] = [a + 1, a + stride + 1, a + stride];
+                if pair[0].radius != 0.0 {
+                    self.push_smooth_triangle([a, b, c]);
+                }
+                if pair[1].radius != 0.0 {
+                    self.push_smooth_triangle([a, c, d]);
+                }
+            }
+        }
+    }
+
+    /// Añade una tapa circular plana a la altura `y`.
+    fn push_disc(&mut self, radius: f32, y: f32, normal: Vec3, segments: u32) {
+        let segments = segments.max(3);
+        let center = self.positions.len() as u32;
+        // Proyección planar vista desde fuera de la tapa.
+        let uv = |direction: Vec3| {
+            Vec2::new(0.5 + direction.x * 0.5, 0.5 + direction.z * 0.5 * normal.y)
+        };
+        self.positions.push(Vec3::Y * y);
+        self.normals.push(normal);
+        self.uvs.push(Vec2::splat(0.5));
+        for segment in 0..=segments {
+            let direction = lathe_direction(segment as f32 / segments as f32);
+            self.positions.push(direction * radius + Vec3::Y * y);
+            self.normals.push(normal);
+            self.uvs.push(uv(direction));
+        }
+        for segment in 0..segments {
+            self.push_triangle([center, center + 1 + segment, center + 2 + segment], normal);
+        }
+    }
+
+    /// Añade un triángulo en sentido antihorario visto desde `facing`.
+    pub(crate) fn push_triangle(&mut self, [a, b, c]: [u32; 3], facing: Vec3) {
+        let [pa, pb, pc] = [a, b, c].map(|i| self.positions[i as usize]);
+        if (pb - pa).cross(pc - pa).dot(facing) < 0.0 {
+            self.indices.extend([a, c, b]);
+        } else {
+            self.indices.extend([a, b, c]);
+        }
+    }
+
+    /// Como `push_triangle`, orientado según las normales de sus vértices.
+    fn push_smooth_triangle(&mut self, triangle: [u32; 3]) {
+        let facing = triangle.iter().map(|&i| self.normals[i as usize]).sum();
+        self.push_triangle(triangle, facing);
+    }
+
+    /// Elimina los vértices que ningún índice usa, conservando el orden del resto.
+    fn remove_unused_vertices(&mut self) {
+        let mut remap = vec![u32::MAX; self.positions.len()];
+        for &index in &self.indices {
+            remap[index as usize] = 0;
+        }
+        for (next, slot) in remap.iter_mut().filter(|slot| **slot == 0).enumerate() {
+            *slot = next as u32;
+        }
+        retain_used(&mut self.positions, &remap);
+        retain_used(&mut self.normals, &remap);
+        retain_used(&mut self.uvs, &remap);
+        for index in &mut self.indices {
+            *index = remap[*index as usize];
+        }
+    }
+
+    fn finish(mut self) -> Self {
+        self.compute_tangents();
+        self
+    }
+}
+
+fn retain_used<T>(stream: &mut Vec<T>, remap: &[u32]) {
+    let mut used = remap.iter().map(|&slot| slot != u32::MAX);
+    stream.retain(|_| used.next().unwrap_or(false));
+}
+
+/// Dirección radial en el plano `XZ` para la coordenada `u` de una revolución.
+fn lathe_direction(u: f32) -> Vec3 {
+    let (sin, cos) = (u * TAU).sin_cos();
+    Vec3::new(cos, 0.0, -sin)
+}
+
+/// Punto de una semiesfera con latitud `theta` medida desde `+Y`, desplazada
+/// `offset` en `Y`.
+fn sphere_point(radius: f32, theta: f32, offset: f32, v: f32) -> ProfilePoint {
+    let (sin, cos) = theta.sin_cos();
+    // En los polos el radio es exactamente cero para que no haya triángulos sueltos.
+    let sin = if sin.abs() < 1e-6 { 0.0 } else { sin };
+    ProfilePoint {
+        radius: radius * sin,
+        y: radius * cos + offset,
+        normal: Vec2::new(sin, cos),
+        v,
+    }
+}
+
+/// Coordenada `u` de una dirección unitaria, con la misma convención que `lathe_direction`.
+fn sphere_u(direction: Vec3) -> f32 {
+    ((-direction.z).atan2(direction.x) / TAU).rem_euclid(1.0)
+}
+
+fn is_pole(direction: Vec3) -> bool {
+    direction.y.abs() > 1.0 - 1e-6
+}
+
+/// Vértices unitarios y triángulos de un icosaedro subdividido.
+fn icosahedron(subdivisions: u32) -> (Vec<Vec3>, Vec<[u32; 3]>) {
+    // Dos vértices en los polos y dos anillos de cinco, para que `uv` tenga polos.
+    let latitude = 0.5f32.atan();
+    let mut points = vec![Vec3::Y];
+    for ring in 0..2 {
+        let (y, offset) = if ring == 0 {
+            (latitude.sin(), 0.0)
+        } else {
+            (-latitude.sin(), 0.5)
+        };
+        for i in 0..5 {
+            let direction = lathe_direction((i as f32 + offset) / 5.0);
+            points.push(direction * latitude.cos() + Vec3::Y * y);
+        }
+    }
+    points.push(Vec3::NEG_Y);
+
+    let mut triangles = Vec::new();
+    for i in 0..5 {
+        let (upper, next_upper) = (1 + i, 1 + (i + 1) % 5);
+        let (lower, next_lower) = (6 + i, 6 + (i + 1) % 5);
+        triangles.push([0, upper, next_upper]);
+        triangles.push([upper, lower, next_upper]);
+        triangles.push([next_upper, lower, next_lower]);
+        triangles.push([lower, 11, next_lower]);
+    }
+
+    for _ in 0..subdivisions {
+        let mut midpoints: HashMap<(u32, u32), u32> = HashMap::new();
+        let mut midpoint = |a: u32, b: u32, points: &mut Vec<Vec3>| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                points.push((points[a as usize] + points[b as usize]).normalize());
+                points.len() as u32 - 1
+            })
+        };
+        triangles = triangles
+            .into_iter()
+            .flat_map(|[a, b, c]| {
+                let ab = midpoint(a, b, &mut points);
+                let bc = midpoint(b, c, &mut points);
+                let ca = midpoint(c, a, &mut points);
+                [[a, ab, ca], [ab, b, bc], [ca, bc, c], [ab, bc, ca]]
+            })
+            .collect();
+    }
+
+    // Orientar todos los triángulos hacia fuera.
+    for triangle in &mut triangles {
+        let [a, b, c] = triangle.map(|i| points[i as usize]);
+        if (b - a).cross(c - a).dot(a + b + c) < 0.0 {
+            triangle.swap(1, 2);
+        }
+    }
+    (points, triangles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Vec4;
+
+    fn shapes() -> Vec<(&'static str, Mesh)> {
+        vec![
+            ("cube", Mesh::cube(2.0, 2)),
+            ("plane", Mesh::plane(4.0, 2.0, [4, 2])),
+            ("uv_sphere", Mesh::uv_sphere(1.0, 16, 8)),
+            ("icosphere", Mesh::icosphere(1.0, 2)),
+            ("cylinder", Mesh::cylinder(0.5, 2.0, 12, 3)),
+            ("cone", Mesh::cone(0.5, 1.0, 12, 2)),
+            ("capsule", Mesh::capsule(0.5, 1.0, 12, 4)),
+            ("torus", Mesh::torus(1.0, 0.25, 24, 12)),
+        ]
+    }
+
+    #[test]
+    fn test_primitives_have_consistent_attributes() {
+        for (name, mesh) in shapes() {
+            mesh.validate()
+                .unwrap_or_else(|error| panic!("{}: {}", name, error));
+            assert_eq!(mesh.uvs.len(), mesh.vertex_count(), "{}", name);
+            assert_eq!(mesh.tangents.len(), mesh.vertex_count(), "{}", name);
+
+            for i in 0..mesh.vertex_count() {
+                let (normal, tangent, uv) = (mesh.normals[i], mesh.tangents[i], mesh.uvs[i]);
+                assert!(
+                    (normal.length() - 1.0).abs() < 1e-4,
+                    "{}: normal {}",
+                    name,
+                    normal
+                );
+                assert!(
+                    (tangent.truncate().length() - 1.0).abs() < 1e-4,
+                    "{}: tangent {}",
+                    name,
+                    tangent
+                );
+                assert!(
+                    normal.dot(tangent.truncate()).abs() < 1e-4,
+                    "{}: vertex {}",
+                    name,
+                    i
+                );
+                // La icosfera cierra la costura con `u` por encima de 1.
+                let max_u = if name == "icosphere" { 1.25 } else { 1.0 };
+                assert!(
+                    uv.cmpge(Vec2::ZERO).all() && uv.x <= max_u && uv.y <= 1.0,
+                    "{}: uv {}",
+                    name,
+                    uv
+                );
+            }
+
+            // Sentido antihorario visto desde el lado al que apuntan las normales, y
+            // ningún triángulo degenerado.
+            for triangle in mesh.indices.chunks_exact(3) {
+                let [a, b, c] = [0, 1, 2].map(|i| triangle[i] as usize);
+                let [pa, pb, pc] = [a, b, c].map(|i| mesh.positions[i]);
+                let face = (pb - pa).cross(pc - pa);
+                assert!(
+                    face.length() > 1e-8,
+                    "{}: degenerate triangle {:?}",
+                    name,
+                    triangle
+                );
+                assert!(
+                    face.dot(mesh.normals[a] + mesh.normals[b] + mesh.normals[c]) > 0.0,
+                    "{}",
+                    name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_primitive_shapes() {
+        let cube = Mesh::cube(2.0, 1);
+        assert_eq!((cube.vertex_count(), cube.triangle_count()), (24, 12));
+        assert_eq!(cube.aabb().max, Vec3::ONE);
+
+        let sphere = Mesh::uv_sphere(2.0, 16, 8);
+        assert!(
+            sphere
+                .positions
+                .iter()
+                .all(|p| (p.length() - 2.0).abs() < 1e-5)
+        );
+        // En el ecuador, sobre `+X`, la tangente sigue a `u`, hacia `-Z`.
+        let equator = sphere
+            .positions
+            .iter()
+            .position(|p| p.abs_diff_eq(Vec3::X * 2.0, 1e-5))
+            .unwrap();
+        assert!(sphere.tangents[equator].abs_diff_eq(Vec4::new(0.0, 0.0, -1.0, 1.0), 1e-4));
+
+        let icosphere = Mesh::icosphere(1.0, 0);
+        assert_eq!(icosphere.triangle_count(), 20);
+        assert!(
+            icosphere
+                .positions
+                .iter()
+                .all(|p| (p.length() - 1.0).abs() < 1e-5)
+        );
+        assert_eq!(Mesh::icosphere(1.0, 3).triangle_count(), 20 * 64);
+
+        let capsule = Mesh::capsule(0.5, 1.0, 8, 4);
+        assert!((capsule.aabb().max.y - 1.0).abs() < 1e-5);
+        assert!((capsule.aabb().min.y + 1.0).abs() < 1e-5);
+
+        let cone = Mesh::cone(1.0, 2.0, 8, 1);
+        // Lateral sin triángulos en el vértice más la base.
+        assert_eq!(cone.triangle_count(), 8 + 8);
+        assert_eq!(cone.aabb().max.y, 1.0);
+
+        let torus = Mesh::torus(1.0, 0.25, 16, 8);
+        assert!((torus.aabb().max.x - 1.25).abs() < 1e-5);
+        assert!((torus.aabb().max.y - 0.25).abs() < 1e-5);
+    }
+}
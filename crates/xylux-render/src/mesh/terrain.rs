@@ -0,0 +1,254 @@
+//! Terreno a partir de un mapa de alturas.
+//!
+//! `Mesh::terrain` genera una rejilla sobre `XZ` muestreando el `Heightmap` cada
+//! `2^lod` celdas. Los bordes se muestrean siempre, y las normales salen del mapa
+//! a resolución completa, así que los trozos vecinos coinciden en posición y
+//! sombreado aunque usen niveles distintos. Las grietas que quedan entre niveles
+//! en los bordes se tapan con faldones: tiras verticales que cuelgan del borde.
+
+use super::Mesh;
+use glam::{Vec2, Vec3};
+
+/// Alturas en una rejilla de `width` × `depth` muestras, por filas de `X`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Heightmap {
+    width: u32,
+    depth: u32,
+    heights: Vec<f32>,
+}
+
+impl Heightmap {
+    /// Crea un mapa de alturas. Entra en pánico si hay menos de 2×2 muestras o si
+    /// `heights` no tiene `width * depth` valores.
+    pub fn new(width: u32, depth: u32, heights: Vec<f32>) -> Self {
+        assert!(
+            width >= 2 && depth >= 2,
+            "Heightmap needs at least 2x2 samples"
+        );
+        assert_eq!(
+            heights.len(),
+            width as usize * depth as usize,
+            "Heightmap size does not match its sample count"
+        );
+        Self {
+            width,
+            depth,
+            heights,
+        }
+    }
+
+    /// Crea un mapa evaluando `height(x, z)` en cada muestra.
+    pub fn from_fn(width: u32, depth: u32, mut height: impl FnMut(u32, u32) -> f32) -> Self {
+        let heights = (0..depth)
+            .flat_map(|z| (0..width).map(move |x| (x, z)))
+            .map(|(x, z)| height(x, z))
+            .collect();
+        Self::new(width, depth, heights)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    /// Altura de la muestra `(x, z)`, recortando las coordenadas al borde.
+    pub fn height(&self, x: i64, z: i64) -> f32 {
+        let x = x.clamp(0, self.width as i64 - 1) as usize;
+        let z = z.clamp(0, self.depth as i64 - 1) as usize;
+        self.heights[z * self.width as usize + x]
+    }
+}
+
+/// Parámetros de `Mesh::terrain`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TerrainDesc {
+    /// Extensión del terreno en `X` y `Z`, centrado en el origen.
+    pub size: Vec2,
+    /// Factor aplicado a las alturas del mapa.
+    pub height_scale: f32,
+    /// Nivel de detalle: se toma una muestra cada `2^lod`.
+    pub lod: u32,
+    /// Cuánto bajan los faldones bajo el borde; `0` los desactiva.
+    pub skirt_depth: f32,
+}
+
+impl Default for TerrainDesc {
+    fn default() -> Self {
+        Self {
+            size: Vec2::splat(1.0),
+            height_scale: 1.0,
+            lod: 0,
+            skirt_depth: 0.0,
+        }
+    }
+}
+
+impl Mesh {
+    /// Genera la malla de terreno de `heightmap`.
+    ///
+    /// Las UVs cubren el mapa entero en `[0, 1]`, con `v` creciendo hacia `+Z`.
+    pub fn terrain(heightmap: &Heightmap, desc: &TerrainDesc) -> Self {
+        let step = 1u32 << desc.lod.min(16);
+        let samples = |count: u32| -> Vec<u32> {
+            let mut samples: Vec<u32> = (0..count - 1).step_by(step as usize).collect();
+            samples.push(count - 1);
+            samples
+        };
+        let (columns, rows) = (samples(heightmap.width), samples(heightmap.depth));
+
+        let cell =
+            desc.size / Vec2::new(heightmap.width as f32 - 1.0, heightmap.depth as f32 - 1.0);
+        let origin = -desc.size * 0.5;
+        let position = |x: u32, z: u32| {
+            let height = heightmap.height(x as i64, z as i64) * desc.height_scale;
+            Vec3::new(
+                origin.x + x as f32 * cell.x,
+                height,
+                origin.y + z as f32 * cell.y,
+            )
+        };
+        let normal = |x: u32, z: u32| {
+            let (x, z) = (x as i64, z as i64);
+            // Diferencias centrales, o laterales en el borde.
+            let dx = (heightmap.height(x + 1, z) - heightmap.height(x - 1, z)) * desc.height_scale;
+            let dz = (heightmap.height(x, z + 1) - heightmap.height(x, z - 1)) * desc.height_scale;
+            let run_x = cell.x * ((x + 1).min(heightmap.width as i64 - 1) - (x - 1).max(0)) as f32;
+            let run_z = cell.y * ((z + 1).min(heightmap.depth as i64 - 1) - (z - 1).max(0)) as f32;
+            Vec3::new(-dx / run_x, 1.0, -dz / run_z).normalize()
+        };
+        let uv = |x: u32, z: u32| {
+            Vec2::new(
+                x as f32 / (heightmap.width - 1) as f32,
+                z as f32 / (heightmap.depth - 1) as f32,
+            )
+        };
+
+        let mut mesh = Mesh::default();
+        for &z in &rows {
+            for &x in &columns {
+                mesh.positions.push(position(x, z));
+                mesh.normals.push(normal(x, z));
+                mesh.uvs.push(uv(x, z));
+            }
+        }
+        let stride = columns.len() as u32;
+        for row in 0..rows.len() as u32 - 1 {
+            for column in 0..stride - 1 {
+                let a = row * stride + column;
+                let [b, c, d] = [a + 1, a + stride + 1, a + stride];
+                mesh.push_triangle([a, b, c], Vec3::Y);
+                mesh.push_triangle([a, c, d], Vec3::Y);
+            }
+        }
+
+        if desc.skirt_depth > 0.0 {
+            let last_row = (rows.len() as u32 - 1) * stride;
+            let borders: [(Vec<u32>, Vec3); 4] = [
+                ((0..stride).collect(), Vec3::NEG_Z),
+                ((0..stride).map(|i| last_row + i).collect(), Vec3::Z),
+                (
+                    (0..rows.len() as u32).map(|i| i * stride).collect(),
+                    Vec3::NEG_X,
+                ),
+                (
+                    (0..rows.len() as u32)
+                        .map(|i| i * stride + stride - 1)
+                        .collect(),
+                    Vec3::X,
+                ),
+            ];
+            for (edge, outward) in borders {
+                mesh.push_skirt(&edge, outward, desc.skirt_depth);
+            }
+        }
+
+        mesh.compute_tangents();
+        mesh
+    }
+
+    /// Cuelga bajo los vértices `edge` una tira vertical de `depth` orientada hacia
+    /// `outward`. Los vértices del faldón copian normal y UV del borde para que no
+    /// se note el cambio de sombreado.
+    fn push_skirt(&mut self, edge: &[u32], outward: Vec3, depth: f32) {
+        let base = self.positions.len() as u32;
+        for &index in edge {
+            let index = index as usize;
+            self.positions.push(self.positions[index] - Vec3::Y * depth);
+            self.normals.push(self.normals[index]);
+            self.uvs.push(self.uvs[index]);
+        }
+        for (i, pair) in edge.windows(2).enumerate() {
+            let (top, next_top) = (pair[0], pair[1]);
+            let (bottom, next_bottom) = (base + i as u32, base + i as u32 + 1);
+            self.push_triangle([top, next_top, next_bottom], outward);
+            self.push_triangle([top, next_bottom, bottom], outward);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slope() -> Heightmap {
+        // Rampa que sube 0.5 por muestra en X.
+        Heightmap::from_fn(5, 5, |x, _| x as f32 * 0.5)
+    }
+
+    #[test]
+    fn test_terrain_lods_share_borders() {
+        let heightmap = slope();
+        let desc = TerrainDesc {
+            size: Vec2::splat(4.0),
+            ..Default::default()
+        };
+        let full = Mesh::terrain(&heightmap, &desc);
+        full.validate().unwrap();
+        assert_eq!((full.vertex_count(), full.triangle_count()), (25, 32));
+        assert!(full.positions[4].abs_diff_eq(Vec3::new(2.0, 2.0, -2.0), 1e-6));
+        // Pendiente de 0.5 por unidad en X: normal (-0.5, 1, 0) normalizada.
+        let expected = Vec3::new(-0.5, 1.0, 0.0).normalize();
+        assert!(full.normals.iter().all(|n| n.abs_diff_eq(expected, 1e-5)));
+        assert!(full.indices.chunks_exact(3).all(|t| {
+            let [a, b, c] = [0, 1, 2].map(|i| full.positions[t[i] as usize]);
+            (b - a).cross(c - a).y > 0.0
+        }));
+
+        let coarse = Mesh::terrain(&heightmap, &TerrainDesc { lod: 1, ..desc });
+        assert_eq!((coarse.vertex_count(), coarse.triangle_count()), (9, 8));
+        // Las esquinas y los bordes muestreados coinciden con el nivel completo.
+        for position in &coarse.positions {
+            assert!(full.positions.contains(position));
+        }
+        assert_eq!(coarse.normals[0], full.normals[0]);
+    }
+
+    #[test]
+    fn test_terrain_skirts_hang_below_borders() {
+        let desc = TerrainDesc {
+            size: Vec2::splat(4.0),
+            lod: 1,
+            skirt_depth: 0.25,
+            ..Default::default()
+        };
+        let mesh = Mesh::terrain(&slope(), &desc);
+        mesh.validate().unwrap();
+        // 3×3 de superficie y 4 bordes de 3 vértices con 2 quads cada uno.
+        assert_eq!(
+            (mesh.vertex_count(), mesh.triangle_count()),
+            (9 + 12, 8 + 16)
+        );
+        assert!(mesh.positions[9].abs_diff_eq(Vec3::new(-2.0, -0.25, -2.0), 1e-6));
+
+        // Cada faldón mira hacia fuera de su borde.
+        for triangle in mesh.indices[8 * 3..].chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| mesh.positions[triangle[i] as usize]);
+            let face = (b - a).cross(c - a);
+            let center = (a + b + c) / 3.0;
+            assert!(face.dot(Vec3::new(center.x, 0.0, center.z)) > 0.0);
+        }
+    }
+}
@@ -1,27 +1,34 @@
 use ash::vk;
-use std::fs;
 use std::io::Cursor;
-use std::path::Path;
 
+use crate::backend::{
+    BackendError, BlendMode, CullMode, FrontFace, PipelineDesc, PrimitiveTopology, VertexFormat,
+    VertexStepMode,
+};
+
+/// Pipeline gráfico de Vulkan creado a partir de un `PipelineDesc`.
 pub struct Pipeline {
     pub pipeline: vk::Pipeline,
     pub pipeline_layout: vk::PipelineLayout,
 }
 
 impl Pipeline {
-    pub fn new(device: &ash::Device, render_pass: vk::RenderPass, extent: vk::Extent2D) -> Self {
-        // Ruta absoluta a la carpeta de shaders
-        let shader_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("shaders");
-        let vertex_shader_path = shader_dir.join("shader.vert.spv");
-        let fragment_shader_path = shader_dir.join("shader.frag.spv");
-
-        let vertex_shader_code = fs::read(&vertex_shader_path)
-            .unwrap_or_else(|_| panic!("Failed to read vertex shader: {:?}", vertex_shader_path));
-        let fragment_shader_code = fs::read(&fragment_shader_path)
-            .unwrap_or_else(|_| panic!("Failed to read fragment shader: {:?}", fragment_shader_path));
-
-        let vertex_shader_module = create_shader_module(device, &vertex_shader_code);
-        let fragment_shader_module = create_shader_module(device, &fragment_shader_code);
+    /// Crea el pipeline. Los shaders que el driver no acepta y los errores del
+    /// dispositivo se devuelven como `BackendError` en vez de abortar.
+    pub fn new(
+        device: &ash::Device,
+        render_pass: vk::RenderPass,
+        samples: vk::SampleCountFlags,
+        desc: &PipelineDesc,
+        set_layouts: &[vk::DescriptorSetLayout],
+    ) -> Result<Self, BackendError> {
+        let vertex_shader_module = create_shader_module(device, &desc.vertex_shader)?;
+        let fragment_shader_module = create_shader_module(device, &desc.fragment_shader)
+            .inspect_err(|_| unsafe { device.destroy_shader_module(vertex_shader_module, None) })?;
+        let destroy_modules = || unsafe {
+            device.destroy_shader_module(vertex_shader_module, None);
+            device.destroy_shader_module(fragment_shader_module, None);
+        };
 
         let entry_point = std::ffi::CString::new("main").unwrap();
         let shader_stages = [
@@ -40,32 +47,63 @@ impl Pipeline {
         ];
 
         // Configuración básica de pipeline
-        let vertex_input_info = vk::PipelineVertexInputStateCreateInfo::default();
+        let bindings: Vec<vk::VertexInputBindingDescription> = desc
+            .vertex_layouts
+            .iter()
+            .enumerate()
+            .map(|(binding, layout)| vk::VertexInputBindingDescription {
+                binding: binding as u32,
+                stride: layout.stride,
+                input_rate: match layout.step_mode {
+                    VertexStepMode::Vertex => vk::VertexInputRate::VERTEX,
+                    VertexStepMode::Instance => vk::VertexInputRate::INSTANCE,
+                },
+            })
+            .collect();
+        let attributes: Vec<vk::VertexInputAttributeDescription> =
+            desc.vertex_layouts
+                .iter()
+                .enumerate()
+                .flat_map(|(binding, layout)| {
+                    layout.attributes.iter().map(move |attribute| {
+                        vk::VertexInputAttributeDescription {
+                            location: attribute.location,
+                            binding: binding as u32,
+                            format: vk_vertex_format(attribute.format),
+                            offset: attribute.offset,
+                        }
+                    })
+                })
+                .collect();
+        let vertex_input_info = vk::PipelineVertexInputStateCreateInfo {
+            vertex_binding_description_count: bindings.len() as u32,
+            p_vertex_binding_descriptions: bindings.as_ptr(),
+            vertex_attribute_description_count: attributes.len() as u32,
+            p_vertex_attribute_descriptions: attributes.as_ptr(),
+            ..Default::default()
+        };
         let input_assembly = vk::PipelineInputAssemblyStateCreateInfo {
-            topology: vk::PrimitiveTopology::TRIANGLE_LIST,
+            topology: match desc.topology {
+                PrimitiveTopology::TriangleList => vk::PrimitiveTopology::TRIANGLE_LIST,
+                PrimitiveTopology::TriangleStrip => vk::PrimitiveTopology::TRIANGLE_STRIP,
+                PrimitiveTopology::LineList => vk::PrimitiveTopology::LINE_LIST,
+                PrimitiveTopology::LineStrip => vk::PrimitiveTopology::LINE_STRIP,
+                PrimitiveTopology::PointList => vk::PrimitiveTopology::POINT_LIST,
+            },
             primitive_restart_enable: 0,
             ..Default::default()
         };
 
-        let viewport = vk::Viewport {
-            x: 0.0,
-            y: 0.0,
-            width: extent.width as f32,
-            height: extent.height as f32,
-            min_depth: 0.0,
-            max_depth: 1.0,
-        };
-
-        let scissor = vk::Rect2D {
-            offset: vk::Offset2D { x: 0, y: 0 },
-            extent,
-        };
-
+        // Viewport y scissor se fijan al grabar (`RenderCommand::SetViewport`).
         let viewport_state = vk::PipelineViewportStateCreateInfo {
             viewport_count: 1,
-            p_viewports: &viewport,
             scissor_count: 1,
-            p_scissors: &scissor,
+            ..Default::default()
+        };
+        let dynamic_states = [vk::DynamicState::VIEWPORT, vk::DynamicState::SCISSOR];
+        let dynamic_state = vk::PipelineDynamicStateCreateInfo {
+            dynamic_state_count: dynamic_states.len() as u32,
+            p_dynamic_states: dynamic_states.as_ptr(),
             ..Default::default()
         };
 
@@ -74,23 +112,64 @@ impl Pipeline {
             rasterizer_discard_enable: 0,
             polygon_mode: vk::PolygonMode::FILL,
             line_width: 1.0,
-            cull_mode: vk::CullModeFlags::BACK,
-            front_face: vk::FrontFace::CLOCKWISE,
+            cull_mode: match desc.cull_mode {
+                CullMode::None => vk::CullModeFlags::NONE,
+                CullMode::Front => vk::CullModeFlags::FRONT,
+                CullMode::Back => vk::CullModeFlags::BACK,
+            },
+            front_face: match desc.front_face {
+                FrontFace::Clockwise => vk::FrontFace::CLOCKWISE,
+                FrontFace::CounterClockwise => vk::FrontFace::COUNTER_CLOCKWISE,
+            },
             ..Default::default()
         };
 
         let multisampling = vk::PipelineMultisampleStateCreateInfo {
-            rasterization_samples: vk::SampleCountFlags::TYPE_1,
+            rasterization_samples: samples,
             ..Default::default()
         };
 
+        let depth_stencil = vk::PipelineDepthStencilStateCreateInfo {
+            depth_test_enable: desc.depth_test as u32,
+            depth_write_enable: desc.depth_write as u32,
+            depth_compare_op: vk::CompareOp::LESS,
+            ..Default::default()
+        };
+
+        // Factores (color origen, color destino, alfa origen, alfa destino).
+        let (src_color, dst_color, src_alpha, dst_alpha) = match desc.blend {
+            BlendMode::Replace => (
+                vk::BlendFactor::ONE,
+                vk::BlendFactor::ZERO,
+                vk::BlendFactor::ONE,
+                vk::BlendFactor::ZERO,
+            ),
+            BlendMode::Alpha => (
+                vk::BlendFactor::SRC_ALPHA,
+                vk::BlendFactor::ONE_MINUS_SRC_ALPHA,
+                vk::BlendFactor::ONE,
+                vk::BlendFactor::ONE_MINUS_SRC_ALPHA,
+            ),
+            BlendMode::PremultipliedAlpha => (
+                vk::BlendFactor::ONE,
+                vk::BlendFactor::ONE_MINUS_SRC_ALPHA,
+                vk::BlendFactor::ONE,
+                vk::BlendFactor::ONE_MINUS_SRC_ALPHA,
+            ),
+            BlendMode::Additive => (
+                vk::BlendFactor::ONE,
+                vk::BlendFactor::ONE,
+                vk::BlendFactor::ONE,
+                vk::BlendFactor::ONE,
+            ),
+        };
         let color_blend_attachment = vk::PipelineColorBlendAttachmentState {
-            blend_enable: 0,
-            src_color_blend_factor: vk::BlendFactor::ONE,
-            dst_color_blend_factor: vk::BlendFactor::ZERO,
+            blend_enable: (desc.blend != BlendMode::Replace) as u32,
+            src_color_blend_factor: src_color,
+            dst_color_blend_factor: dst_color,
             color_blend_op: vk::BlendOp::ADD,
-            src_alpha_blend_factor: vk::BlendFactor::ONE,
-            dst_alpha_blend_factor: vk::BlendFactor::ZERO,
+            src_alpha_blend_factor: src_alpha,
+            dst_alpha_blend_factor: dst_alpha,
             alpha_blend_op: vk::BlendOp::ADD,
             color_write_mask: vk::ColorComponentFlags::RGBA,
         };
@@ -101,10 +180,23 @@ impl Pipeline {
             ..Default::default()
         };
 
-        let pipeline_layout_info = vk::PipelineLayoutCreateInfo::default();
-        let pipeline_layout = unsafe {
-            device.create_pipeline_layout(&pipeline_layout_info, None).unwrap()
+        let push_constant_range = vk::PushConstantRange {
+            stage_flags: vk::ShaderStageFlags::ALL_GRAPHICS,
+            offset: 0,
+            size: desc.push_constant_size,
+        };
+        let pipeline_layout_info = vk::PipelineLayoutCreateInfo {
+            set_layout_count: set_layouts.len() as u32,
+            p_set_layouts: set_layouts.as_ptr(),
+            push_constant_range_count: (desc.push_constant_size > 0) as u32,
+            p_push_constant_ranges: &push_constant_range,
+            ..Default::default()
         };
+        let pipeline_layout = unsafe { device.create_pipeline_layout(&pipeline_layout_info, None) }
+            .map_err(|error| {
+                destroy_modules();
+                device_error("pipeline layout", error)
+            })?;
 
         let pipeline_info = vk::GraphicsPipelineCreateInfo {
             stage_count: shader_stages.len() as u32,
@@ -114,29 +206,31 @@ impl Pipeline {
             p_viewport_state: &viewport_state,
             p_rasterization_state: &rasterizer,
             p_multisample_state: &multisampling,
-            p_depth_stencil_state: std::ptr::null(),
+            p_depth_stencil_state: &depth_stencil,
             p_color_blend_state: &color_blending,
+            p_dynamic_state: &dynamic_state,
             layout: pipeline_layout,
             render_pass,
             subpass: 0,
             ..Default::default()
         };
 
-        let pipeline = unsafe {
-            device
-                .create_graphics_pipelines(vk::PipelineCache::null(), &[pipeline_info], None)
-                .unwrap()[0]
+        let pipelines = unsafe {
+            device.create_graphics_pipelines(vk::PipelineCache::null(), &[pipeline_info], None)
+        };
+        destroy_modules();
+        let pipeline = match pipelines {
+            Ok(pipelines) => pipelines[0],
+            Err((_, error)) => {
+                unsafe { device.destroy_pipeline_layout(pipeline_layout, None) };
+                return Err(device_error(&format!("pipeline '{}'", desc.label), error));
+            }
         };
 
-        unsafe {
-            device.destroy_shader_module(vertex_shader_module, None);
-            device.destroy_shader_module(fragment_shader_module, None);
-        }
-
-        Self {
+        Ok(Self {
             pipeline,
             pipeline_layout,
-        }
+        })
     }
 
     pub fn cleanup(&self, device: &ash::Device) {
@@ -147,15 +241,33 @@ impl Pipeline {
     }
 }
 
-fn create_shader_module(device: &ash::Device, code: &[u8]) -> vk::ShaderModule {
+fn vk_vertex_format(format: VertexFormat) -> vk::Format {
+    match format {
+        VertexFormat::Float32 => vk::Format::R32_SFLOAT,
+        VertexFormat::Float32x2 => vk::Format::R32G32_SFLOAT,
+        VertexFormat::Float32x3 => vk::Format::R32G32B32_SFLOAT,
+        VertexFormat::Float32x4 => vk::Format::R32G32B32A32_SFLOAT,
+    }
+}
+
+fn device_error(what: &str, error: vk::Result) -> BackendError {
+    BackendError::Device(format!("failed to create {}: {:?}", what, error))
+}
+
+fn create_shader_module(
+    device: &ash::Device,
+    code: &[u8],
+) -> Result<vk::ShaderModule, BackendError> {
     // El código del shader debe estar alineado para un slice de `u32`.
     // `ash::util::read_spv` se encarga de esto de forma segura.
-    let code_aligned =
-        ash::util::read_spv(&mut Cursor::new(code)).expect("Failed to read SPIR-V code");
+    let code_aligned = ash::util::read_spv(&mut Cursor::new(code)).map_err(|error| {
+        BackendError::InvalidPipeline(format!("failed to read SPIR-V code: {}", error))
+    })?;
     let create_info = vk::ShaderModuleCreateInfo {
         code_size: code_aligned.len() * std::mem::size_of::<u32>(),
         p_code: code_aligned.as_ptr(),
         ..Default::default()
     };
-    unsafe { device.create_shader_module(&create_info, None).unwrap() }
+    unsafe { device.create_shader_module(&create_info, None) }
+        .map_err(|error| device_error("shader module", error))
 }
@@ -0,0 +1,317 @@
+//! Agrupación de entidades dibujables en draws instanciados.
+
+use crate::camera::CameraView;
+use crate::material::Material;
+use crate::mesh::{Lod, LodMetric, Mesh, MeshRenderer};
+use crate::renderer::culling::CullingStats;
+use glam::Mat4;
+use std::collections::HashMap;
+use std::ops::Range;
+use xylux_ecs::{Aabb, Entity, Handle, Query, Transform, World};
+
+/// Datos por instancia del pipeline de mallas (slot 1).
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C)]
+pub struct InstanceData {
+    /// Matriz del modelo por columnas.
+    pub model: [[f32; 4]; 4],
+}
+
+impl InstanceData {
+    /// Tamaño en bytes de una instancia en el instance buffer.
+    pub const SIZE: u64 = std::mem::size_of::<InstanceData>() as u64;
+
+    pub fn from_transform(transform: &Transform) -> Self {
+        Self {
+            model: Mat4::from_rotation_translation(transform.rotation, transform.position)
+                .to_cols_array_2d(),
+        }
+    }
+
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        out.extend(
+            self.model
+                .iter()
+                .flatten()
+                .flat_map(|value| value.to_le_bytes()),
+        );
+    }
+}
+
+/// Draw instanciado de una malla con un material.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DrawBatch {
+    pub mesh: Handle<Mesh>,
+    pub material: Handle<Material>,
+    /// Rango de instancias dentro de `FrameBatches::instances`.
+    pub instances: Range<u32>,
+}
+
+/// Batches de un frame y sus instancias, contiguas por batch.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FrameBatches {
+    pub batches: Vec<DrawBatch>,
+    pub instances: Vec<InstanceData>,
+    /// Rango de `batches` que dibuja cada cámara, en el orden de las cámaras.
+    pub views: Vec<Range<usize>>,
+}
+
+impl FrameBatches {
+    /// Recoge las entidades con `Transform` y `MeshRenderer` y las agrupa por
+    /// malla y material. Un componente `Handle<Material>` sustituye al material del
+    /// `MeshRenderer`.
+    ///
+    /// Los batches quedan ordenados por `(malla, material)` y, dentro de cada uno,
+    /// las instancias siguen el orden de la query.
+    pub fn collect(world: &mut World) -> Self {
+        let mut query = Query::<(Entity, &Transform, &MeshRenderer)>::new(world);
+        let items: Vec<(Entity, MeshRenderer, InstanceData)> = query
+            .iter()
+            .map(|(entity, transform, renderer)| {
+                (entity, *renderer, InstanceData::from_transform(transform))
+            })
+            .collect();
+        let items = items
+            .into_iter()
+            .map(|(entity, renderer, instance)| (with_material(world, entity, renderer), instance))
+            .collect();
+        let mut frame = FrameBatches::default();
+        frame.push_view(items);
+        frame
+    }
+
+    /// Como `collect`, pero con un grupo de batches por cámara que solo contiene
+    /// las entidades cuya caja en espacio de mundo toca su frustum.
+    ///
+    /// Antes elige el nivel de las entidades con `Lod`, con la cámara que las vea
+    /// con más detalle. `bounds` son las cajas locales de las mallas; las mallas sin
+    /// caja no se descartan.
+    pub fn collect_visible(
+        world: &mut World,
+        bounds: &HashMap<Handle<Mesh>, Aabb>,
+        views: &[CameraView],
+        stats: &mut CullingStats,
+    ) -> Self {
+        *stats = CullingStats {
+            cameras: views.len() as u32,
+            ..Default::default()
+        };
+        let mut lod_meshes = HashMap::new();
+        let mut query = Query::<(Entity, &Transform, &mut Lod)>::new(world);
+        for (entity, transform, lod) in query.iter() {
+            let Some(aabb) = lod.levels.first().and_then(|level| bounds.get(&level.mesh)) else {
+                continue;
+            };
+            let aabb = aabb.transformed(transform);
+            let previous = lod.current();
+            lod.select(lod_value(lod.metric, &aabb, views));
+            stats.lod_changes += (lod.current() != previous) as u32;
+            if let Some(mesh) = lod.mesh() {
+                lod_meshes.insert(entity, mesh);
+            }
+        }
+
+        let mut query = Query::<(Entity, &Transform, &MeshRenderer)>::new(world);
+        let items: Vec<(Entity, MeshRenderer, Transform)> = query
+            .iter()
+            .map(|(entity, transform, renderer)| {
+                let mesh = lod_meshes.get(&entity).copied().unwrap_or(renderer.mesh);
+                (entity, MeshRenderer { mesh, ..*renderer }, *transform)
+            })
+            .collect();
+        let items: Vec<(MeshRenderer, Transform)> = items
+            .into_iter()
+            .map(|(entity, renderer, transform)| {
+                (with_material(world, entity, renderer), transform)
+            })
+            .collect();
+
+        let mut frame = FrameBatches::default();
+        for view in views {
+            let visible: Vec<(MeshRenderer, InstanceData)> = items
+                .iter()
+                .filter(|(renderer, transform)| {
+                    let (Some(frustum), Some(aabb)) = (view.frustum, bounds.get(&renderer.mesh))
+                    else {
+                        return true;
+                    };
+                    frustum.intersects_aabb(&aabb.transformed(transform))
+                })
+                .map(|(renderer, transform)| (*renderer, InstanceData::from_transform(transform)))
+                .collect();
+            stats.tested += items.len() as u32;
+            stats.visible += visible.len() as u32;
+            frame.push_view(visible);
+        }
+        stats.culled = stats.tested - stats.visible;
+        frame
+    }
+
+    /// Agrupa `items` en los batches de una nueva cámara.
+    fn push_view(&mut self, mut items: Vec<(MeshRenderer, InstanceData)>) {
+        items.sort_by_key(|(renderer, _)| (renderer.mesh, renderer.material));
+        let first = self.batches.len();
+        for (renderer, instance) in items {
+            let index = self.instances.len() as u32;
+            self.instances.push(instance);
+            match self.batches[first..].last_mut() {
+                Some(batch)
+                    if batch.mesh == renderer.mesh && batch.material == renderer.material =>
+                {
+                    batch.instances.end = index + 1;
+                }
+                _ => self.batches.push(DrawBatch {
+                    mesh: renderer.mesh,
+                    material: renderer.material,
+                    instances: index..index + 1,
+                }),
+            }
+        }
+        self.views.push(first..self.batches.len());
+    }
+
+    /// Instancias serializadas para el instance buffer.
+    pub fn instance_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.instances.len() * InstanceData::SIZE as usize);
+        for instance in &self.instances {
+            instance.write_bytes(&mut bytes);
+        }
+        bytes
+    }
+}
+
+/// `renderer` con el material del `Handle<Material>` de la entidad, si lo tiene.
+fn with_material(world: &World, entity: Entity, renderer: MeshRenderer) -> MeshRenderer {
+    match world.get::<Handle<Material>>(entity) {
+        Some(&material) => MeshRenderer {
+            material,
+            ..renderer
+        },
+        None => renderer,
+    }
+}
+
+/// Medida de `metric` para una caja en espacio de mundo: el mayor tamaño en
+/// pantalla o la menor distancia entre todas las cámaras.
+fn lod_value(metric: LodMetric, aabb: &Aabb, views: &[CameraView]) -> f32 {
+    let (center, radius) = (aabb.center(), aabb.half_extents().length());
+    let values = views.iter().map(|view| {
+        let distance = view.uniform.position.distance(center);
+        match metric {
+            LodMetric::Distance => distance,
+            LodMetric::ScreenSize => {
+                // `projection.y_axis.y` es 1 / tan(fov / 2) en perspectiva y
+                // 2 / altura en ortográfica, donde el tamaño no depende de la distancia.
+                let projection = view.uniform.projection;
+                let scale = projection.y_axis.y.abs();
+                if projection.w_axis.w == 0.0 {
+                    radius * scale / distance.max(f32::EPSILON)
+                } else {
+                    radius * scale
+                }
+            }
+        }
+    });
+    match metric {
+        LodMetric::Distance => values.fold(f32::INFINITY, f32::min),
+        LodMetric::ScreenSize => values.fold(0.0, f32::max),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::backend::{NullBackend, RenderCommand};
+    use crate::renderer::Renderer;
+    use glam::{Vec3, Vec4};
+
+    #[test]
+    fn test_entities_batched_into_instanced_draws() {
+        let mut renderer = Renderer::headless(64, 64);
+        let mut world = World::new(64);
+        world.register_component::<Transform>();
+        world.register_component::<MeshRenderer>();
+
+        let quad = renderer
+            .add_mesh(Mesh::new(vec![Vec3::ZERO; 4], vec![0, 1, 2, 2, 3, 0]))
+            .unwrap();
+        let tri = renderer
+            .add_mesh(Mesh::new(vec![Vec3::ZERO; 3], vec![0, 1, 2]))
+            .unwrap();
+        let red = renderer.add_material(Material::new(Vec4::new(1.0, 0.0, 0.0, 1.0)));
+        let blue = renderer.add_material(Material::new(Vec4::new(0.0, 0.0, 1.0, 1.0)));
+
+        let renderers = [
+            (tri, red),
+            (quad, red),
+            (tri, red),
+            (quad, blue),
+            (quad, red),
+            (tri, red),
+        ];
+        for (i, (mesh, material)) in renderers.into_iter().enumerate() {
+            let entity = world.spawn_entity();
+            let transform = Transform {
+                position: Vec3::new(i as f32, 0.0, 0.0),
+                ..Default::default()
+            };
+            world.insert(entity, transform);
+            world.insert(entity, MeshRenderer::new(mesh, material));
+        }
+        // Sin Transform no se dibuja.
+        let hidden = world.spawn_entity();
+        world.insert(hidden, MeshRenderer::new(tri, red));
+
+        let batches = FrameBatches::collect(&mut world);
+        let summary: Vec<_> = batches
+            .batches
+            .iter()
+            .map(|b| (b.mesh, b.material, b.instances.clone()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![(quad, red, 0..2), (quad, blue, 2..3), (tri, red, 3..6)]
+        );
+        // Dentro de un batch se conserva el orden de las entidades.
+        let x: Vec<f32> = batches
+            .instances
+            .iter()
+            .map(|instance| instance.model[3][0])
+            .collect();
+        assert_eq!(x, vec![1.0, 4.0, 3.0, 0.0, 2.0, 5.0]);
+
+        renderer.render(&mut world);
+        let frame = renderer
+            .backend_as::<NullBackend>()
+            .unwrap()
+            .last_frame()
+            .unwrap();
+        let draws: Vec<_> = frame
+            .commands
+            .iter()
+            .filter_map(|command| match command {
+                RenderCommand::DrawIndexed {
+                    indices, instances, ..
+                } => Some((indices.clone(), instances.clone())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(draws, vec![(0..6, 0..2), (0..6, 2..3), (0..3, 3..6)]);
+
+        // Un segundo frame reutiliza las mallas ya subidas.
+        let (buffers, _, _) = renderer
+            .backend_as::<NullBackend>()
+            .unwrap()
+            .resource_counts();
+        renderer.render(&mut world);
+        let (after, _, _) = renderer
+            .backend_as::<NullBackend>()
+            .unwrap()
+            .resource_counts();
+        assert_eq!(
+            after,
+            buffers + 2,
+            "solo se crean los buffers de instancias y cámaras del otro frame en vuelo"
+        );
+    }
+}
@@ -1,126 +1,98 @@
-use ash::vk;
-use crate::renderer::renderer::Renderer;
-use xylux_ecs::{World, Query, Transform};
-use xylux_window::XyluxWindow;
-
-// Ya no usamos 'extensions::khr::Swapchain', usamos el módulo público swapchain
-use ash::khr::swapchain;
-
-pub fn render_frame(renderer: &mut Renderer, world: &mut World, window: &XyluxWindow) {
-    let context = &mut renderer.context;
-    let device = &context.device;
-    let current_frame = renderer.current_frame;
+use crate::backend::{BindGroupId, BufferId, IndexFormat, PipelineId, Viewport};
+use crate::material::Material;
+use crate::mesh::Mesh;
+use crate::renderer::batch::FrameBatches;
+use crate::renderer::graph::RenderGraph;
+use crate::renderer::renderer::GpuMesh;
+use std::collections::HashMap;
+use xylux_ecs::Handle;
+
+/// Cámara del frame con su uniform ya subido.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FrameCamera {
+    pub viewport: Viewport,
+    pub clear_color: Option<[f32; 4]>,
+    /// Bind group con la `CameraUniform` de la cámara (set 0).
+    pub bind_group: BindGroupId,
+}
 
-    unsafe {
-        // 1. Esperar a que el frame que vamos a usar esté disponible (su fence).
-        device.wait_for_fences(&[context.in_flight_fences[current_frame]], true, u64::MAX)
-            .expect("Failed to wait for fence");
+/// Cómo se dibuja un material en el frame.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct MaterialDraw {
+    pub pipeline: PipelineId,
+    /// Bind group del set 1 (materiales PBR).
+    pub bind_group: Option<BindGroupId>,
+    /// Push constants del pipeline; vacías si no usa.
+    pub push_constants: Vec<u8>,
+    /// Se dibuja tras los opacos (`AlphaMode::Blend`).
+    pub blend: bool,
+}
 
-        // 2. Adquirir la siguiente imagen del swapchain.
-        let swapchain_loader = swapchain::Device::new(&context.instance, &context.device);
-        let result = swapchain_loader
-            .acquire_next_image(
-                context.swapchain,
-                u64::MAX,
-                context.image_available_semaphores[current_frame], // Señalizar este semáforo cuando la imagen esté lista.
-                vk::Fence::null(), // No usar un fence aquí.
-            );
+/// Lo que el pass de escena necesita del `Renderer`.
+pub(crate) struct SceneResources<'a> {
+    pub gpu_meshes: &'a HashMap<Handle<Mesh>, GpuMesh>,
+    pub materials: &'a HashMap<Handle<Material>, MaterialDraw>,
+}
 
-        let image_index = match result {
-            Ok((index, _)) => index,
-            Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => {
-                renderer.recreate_swapchain(window);
-                return;
+/// Grafo de un frame, independiente del backend: un pass que dibuja la escena en
+/// el swapchain.
+///
+/// La escena se dibuja una vez por cámara, en su viewport y con sus batches de
+/// `FrameBatches::views`. Cada batch es un draw
+/// indexado e instanciado de su malla; las instancias se leen de
+/// `instance_buffer` (slot 1) y el material va en push constants o en el set 1.
+/// Los batches con materiales transparentes se dibujan después de los opacos.
+pub(crate) fn frame_graph<'a>(
+    scene: SceneResources<'a>,
+    batches: &'a FrameBatches,
+    instance_buffer: Option<BufferId>,
+    cameras: &'a [FrameCamera],
+) -> RenderGraph<'a> {
+    let mut graph = RenderGraph::new();
+    let swapchain = graph.import_swapchain();
+    let mut pass = graph.add_pass("scene");
+    pass.color_attachment(swapchain, Some([0.0, 0.0, 0.0, 1.0])); // Negro
+    pass.record(move |context| {
+        let commands = &mut *context.commands;
+        for (camera, view) in cameras.iter().zip(&batches.views) {
+            commands.set_viewport(camera.viewport);
+            if let Some(color) = camera.clear_color {
+                commands.clear_color(color);
             }
-            Err(error) => panic!("Failed to acquire swapchain image: {}", error),
-        };
-
-        // Comprobar si un frame anterior está usando esta imagen y esperar si es así.
-        if context.images_in_flight[image_index as usize] != vk::Fence::null() {
-            device.wait_for_fences(&[context.images_in_flight[image_index as usize]], true, u64::MAX).unwrap();
-        }
-        // Marcar la imagen como en uso por este frame.
-        context.images_in_flight[image_index as usize] = context.in_flight_fences[current_frame];
-
-        let command_buffer = context.command_buffers[current_frame];
-        assert!(command_buffer != vk::CommandBuffer::null(), "Command buffer is null!");
-
-        let begin_info = vk::CommandBufferBeginInfo {
-            flags: vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT,
-            ..Default::default()
-        };
-
-        // 3. Grabar el command buffer.
-        device.begin_command_buffer(command_buffer, &begin_info)
-            .expect("Failed to begin command buffer");
-
-        let clear_color = vk::ClearValue {
-            color: vk::ClearColorValue { float32: [0.0, 0.0, 0.0, 1.0] }, // Negro
-        };
-
-        let render_pass_info = vk::RenderPassBeginInfo {
-            render_pass: renderer.render_pass,
-            framebuffer: renderer.framebuffers[image_index as usize],
-            render_area: vk::Rect2D {
-                offset: vk::Offset2D { x: 0, y: 0 },
-                extent: context.swapchain_extent(),
-            },
-            clear_value_count: 1,
-            p_clear_values: &clear_color,
-            ..Default::default()
-        };
-
-        device.cmd_begin_render_pass(command_buffer, &render_pass_info, vk::SubpassContents::INLINE);
-        device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::GRAPHICS, renderer.pipeline.pipeline);
-        device.cmd_draw(command_buffer, 3, 1, 0, 0); // Dibujar el triángulo del shader.
-
-        let mut query = Query::<(&Transform,)>::new(world);
-        for _ in query.iter() {
-            // Aquí irían las llamadas de draw
-        }
-
-        device.cmd_end_render_pass(command_buffer);
-        device.end_command_buffer(command_buffer)
-            .expect("Failed to end command buffer");
-
-        // 4. Enviar el command buffer a la GPU.
-        let wait_semaphores = [context.image_available_semaphores[current_frame]];
-        let wait_stages = [vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT];
-        let signal_semaphores = [context.render_finished_semaphores[current_frame]];
-
-        let submit_info = vk::SubmitInfo {
-            wait_semaphore_count: wait_semaphores.len() as u32,
-            p_wait_semaphores: wait_semaphores.as_ptr(),
-            p_wait_dst_stage_mask: wait_stages.as_ptr(),
-            command_buffer_count: 1,
-            p_command_buffers: &command_buffer,
-            signal_semaphore_count: signal_semaphores.len() as u32,
-            p_signal_semaphores: signal_semaphores.as_ptr(),
-            ..Default::default()
-        };
-
-        device.reset_fences(&[context.in_flight_fences[current_frame]]).unwrap();
-        device.queue_submit(context.queue, &[submit_info], context.in_flight_fences[current_frame])
-            .expect("Failed to submit queue");
-
-        // 5. Presentar la imagen en pantalla.
-        let present_info = vk::PresentInfoKHR {
-            wait_semaphore_count: 1,
-            p_wait_semaphores: signal_semaphores.as_ptr(), // Esperar a que el renderizado termine.
-            swapchain_count: 1,
-            p_swapchains: &context.swapchain,
-            p_image_indices: &image_index,
-            ..Default::default()
-        };
-
-        let result = swapchain_loader.queue_present(context.queue, &present_info);
-
-        match result {
-            Ok(false) | Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => {
-                renderer.recreate_swapchain(window);
+            let Some(instance_buffer) = instance_buffer else {
+                continue;
+            };
+            let mut bound = None;
+            for blend in [false, true] {
+                for batch in &batches.batches[view.clone()] {
+                    // Mallas o materiales eliminados: la entidad no se dibuja.
+                    let (Some(gpu), Some(material)) = (
+                        scene.gpu_meshes.get(&batch.mesh),
+                        scene.materials.get(&batch.material),
+                    ) else {
+                        continue;
+                    };
+                    if material.blend != blend {
+                        continue;
+                    }
+                    if bound != Some(material.pipeline) {
+                        bound = Some(material.pipeline);
+                        commands.bind_pipeline(material.pipeline);
+                        commands.bind_group(0, camera.bind_group);
+                        commands.bind_vertex_buffer(1, instance_buffer, 0);
+                    }
+                    if let Some(group) = material.bind_group {
+                        commands.bind_group(1, group);
+                    }
+                    commands.bind_vertex_buffer(0, gpu.vertex_buffer, 0);
+                    commands.bind_index_buffer(gpu.index_buffer, 0, IndexFormat::U32);
+                    if !material.push_constants.is_empty() {
+                        commands.push_constants(0, &material.push_constants);
+                    }
+                    commands.draw_indexed(0..gpu.index_count, 0, batch.instances.clone());
+                }
             }
-            Err(e) => panic!("Failed to present swapchain image: {}", e),
-            _ => {}
         }
-    }
+    });
+    graph
 }
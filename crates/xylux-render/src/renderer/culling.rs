@@ -0,0 +1,180 @@
+//! Frustum culling en CPU y estadísticas de visibilidad.
+
+use glam::{Mat4, Vec3, Vec4};
+use xylux_ecs::Aabb;
+
+/// Los seis planos de la pirámide de visión de una cámara, en espacio de mundo.
+///
+/// Cada plano `(n, d)` deja dentro los puntos con `n · p + d >= 0`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frustum {
+    pub planes: [Vec4; 6],
+}
+
+impl Frustum {
+    /// Extrae los planos de una matriz vista-proyección con profundidad en
+    /// `[0, 1]` (Gribb-Hartmann).
+    pub fn from_view_projection(matrix: Mat4) -> Self {
+        let [r0, r1, r2, r3] = [0, 1, 2, 3].map(|i| matrix.row(i));
+        let planes = [r3 + r0, r3 - r0, r3 + r1, r3 - r1, r2, r3 - r2].map(|plane| {
+            let length = plane.truncate().length();
+            if length > 0.0 { plane / length } else { plane }
+        });
+        Self { planes }
+    }
+
+    /// Si la caja toca el frustum. Es conservador: una caja cerca de una esquina
+    /// puede darse por visible aunque quede fuera.
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        if aabb.is_empty() {
+            return false;
+        }
+        let (center, extents) = (aabb.center(), aabb.half_extents());
+        self.planes.iter().all(|plane| {
+            let normal = plane.truncate();
+            let radius = extents.dot(normal.abs());
+            normal.dot(center) + plane.w >= -radius
+        })
+    }
+
+    /// Si la esfera toca el frustum.
+    pub fn intersects_sphere(&self, center: Vec3, radius: f32) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.truncate().dot(center) + plane.w >= -radius)
+    }
+}
+
+/// Resultado del culling de un frame, sumado sobre todas las cámaras.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CullingStats {
+    pub cameras: u32,
+    /// Pares entidad-cámara comprobados.
+    pub tested: u32,
+    pub visible: u32,
+    pub culled: u32,
+    /// Entidades con `Lod` que cambiaron de nivel en el frame.
+    pub lod_changes: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{NullBackend, RenderCommand};
+    use crate::camera::{Camera, CameraUniform};
+    use crate::material::Material;
+    use crate::mesh::{Lod, LodLevel, LodMetric, Mesh, MeshRenderer};
+    use crate::renderer::Renderer;
+    use xylux_ecs::Transform;
+    use xylux_ecs::World;
+
+    #[test]
+    fn test_frustum_culls_boxes_outside_view() {
+        let transform = Transform {
+            position: Vec3::new(0.0, 0.0, 5.0),
+            ..Default::default()
+        };
+        let camera = Camera::perspective(90f32.to_radians(), 1.0, 50.0);
+        let uniform = CameraUniform::new(&camera, &transform, 1.0);
+        let frustum = Frustum::from_view_projection(uniform.view_projection);
+
+        let unit = |center: Vec3| Aabb::from_center_half_extents(center, Vec3::splat(0.5));
+        assert!(frustum.intersects_aabb(&unit(Vec3::ZERO)));
+        // Detrás de la cámara, más allá de far y fuera por un lado.
+        assert!(!frustum.intersects_aabb(&unit(Vec3::new(0.0, 0.0, 7.0))));
+        assert!(!frustum.intersects_aabb(&unit(Vec3::new(0.0, 0.0, -50.0))));
+        assert!(!frustum.intersects_aabb(&unit(Vec3::new(10.0, 0.0, 0.0))));
+        // A 5 unidades con 90° se ve hasta |x| = 5; una caja que cruza el borde cuenta.
+        assert!(frustum.intersects_aabb(&unit(Vec3::new(5.3, 0.0, 0.0))));
+        assert!(frustum.intersects_sphere(Vec3::new(5.3, 0.0, 0.0), 0.5));
+        assert!(!frustum.intersects_aabb(&Aabb::EMPTY));
+    }
+
+    #[test]
+    fn test_culling_and_lod_without_gpu() {
+        let mut renderer = Renderer::headless(64, 64);
+        let mut world = World::new(256);
+        world.register_component::<Transform>();
+        world.register_component::<MeshRenderer>();
+        world.register_component::<Camera>();
+        world.register_component::<Lod>();
+
+        let fine = renderer.add_mesh(Mesh::uv_sphere(1.0, 16, 8)).unwrap();
+        let coarse = renderer.add_mesh(Mesh::uv_sphere(1.0, 6, 4)).unwrap();
+        let material = renderer.add_material(Material::default());
+
+        // Una fila de 20 esferas a lo largo de -Z y 20 detrás de la cámara.
+        let mut row = Vec::new();
+        for i in 0..40 {
+            let entity = world.spawn_entity();
+            let z = if i < 20 {
+                -5.0 * i as f32
+            } else {
+                5.0 * (i - 19) as f32
+            };
+            world.insert(
+                entity,
+                Transform {
+                    position: Vec3::new(0.0, 0.0, z),
+                    ..Default::default()
+                },
+            );
+            world.insert(entity, MeshRenderer::new(fine, material));
+            let levels = vec![
+                LodLevel {
+                    mesh: fine,
+                    threshold: 22.0,
+                },
+                LodLevel {
+                    mesh: coarse,
+                    threshold: f32::INFINITY,
+                },
+            ];
+            world.insert(entity, Lod::new(LodMetric::Distance, levels));
+            row.push(entity);
+        }
+        let camera = world.spawn_entity();
+        world.insert(camera, Camera::perspective(60f32.to_radians(), 0.1, 200.0));
+        world.insert(
+            camera,
+            Transform {
+                position: Vec3::new(0.0, 0.0, 2.0),
+                ..Default::default()
+            },
+        );
+
+        renderer.render(&mut world);
+        let stats = renderer.culling_stats();
+        assert_eq!(
+            (stats.cameras, stats.tested, stats.visible, stats.culled),
+            (1, 40, 20, 20)
+        );
+        // Pasan a la malla simple las que quedan más allá de 22 unidades más el 10 %
+        // de histéresis: 15 delante (z <= -25) y 15 detrás (z >= 30).
+        assert_eq!(stats.lod_changes, 15 + 15);
+        let frame = renderer
+            .backend_as::<NullBackend>()
+            .unwrap()
+            .last_frame()
+            .unwrap();
+        let draws: Vec<_> = frame
+            .commands
+            .iter()
+            .filter_map(|command| match command {
+                RenderCommand::DrawIndexed { instances, .. } => Some(instances.len()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(draws.iter().sum::<usize>(), 20);
+
+        // Al acercarse, la esfera de z = -25 vuelve al detalle solo pasado el margen.
+        let far = row[5];
+        assert_eq!(world.get::<Lod>(far).unwrap().current(), 1);
+        world.get_mut::<Transform>(camera).unwrap().position.z = -2.0;
+        renderer.render(&mut world);
+        assert_eq!(world.get::<Lod>(far).unwrap().current(), 1);
+        world.get_mut::<Transform>(camera).unwrap().position.z = -6.0;
+        renderer.render(&mut world);
+        assert_eq!(world.get::<Lod>(far).unwrap().current(), 0);
+    }
+}
@@ -1,22 +1,161 @@
 use ash::vk;
 
+use crate::backend::SampleCount;
+use crate::renderer::render_pass::{depth_aspect, vk_sample_count};
+use crate::vulkan::context::VulkanContext;
+use crate::vulkan::memory::allocate_memory;
+
+/// Imagen propia de un attachment del render pass del swapchain.
+pub struct AttachmentImage {
+    pub image: vk::Image,
+    pub memory: vk::DeviceMemory,
+    pub view: vk::ImageView,
+}
+
+impl AttachmentImage {
+    fn new(
+        context: &VulkanContext,
+        format: vk::Format,
+        samples: SampleCount,
+        usage: vk::ImageUsageFlags,
+        aspect_mask: vk::ImageAspectFlags,
+    ) -> Self {
+        let device = &context.device;
+        let extent = context.swapchain_extent();
+        let image_info = vk::ImageCreateInfo {
+            image_type: vk::ImageType::TYPE_2D,
+            format,
+            extent: vk::Extent3D {
+                width: extent.width,
+                height: extent.height,
+                depth: 1,
+            },
+            mip_levels: 1,
+            array_layers: 1,
+            samples: vk_sample_count(samples),
+            tiling: vk::ImageTiling::OPTIMAL,
+            usage: usage | vk::ImageUsageFlags::TRANSIENT_ATTACHMENT,
+            sharing_mode: vk::SharingMode::EXCLUSIVE,
+            initial_layout: vk::ImageLayout::UNDEFINED,
+            ..Default::default()
+        };
+        unsafe {
+            let image = device
+                .create_image(&image_info, None)
+                .expect("Failed to create attachment image");
+            let memory = allocate_memory(
+                &context.instance,
+                context.physical_device,
+                device,
+                device.get_image_memory_requirements(image),
+                vk::MemoryPropertyFlags::DEVICE_LOCAL,
+            );
+            device
+                .bind_image_memory(image, memory, 0)
+                .expect("Failed to bind attachment memory");
+            let view_info = vk::ImageViewCreateInfo {
+                image,
+                view_type: vk::ImageViewType::TYPE_2D,
+                format,
+                subresource_range: vk::ImageSubresourceRange {
+                    aspect_mask,
+                    base_mip_level: 0,
+                    level_count: 1,
+                    base_array_layer: 0,
+                    layer_count: 1,
+                },
+                ..Default::default()
+            };
+            let view = device
+                .create_image_view(&view_info, None)
+                .expect("Failed to create attachment view");
+            Self {
+                image,
+                memory,
+                view,
+            }
+        }
+    }
+
+    fn cleanup(&self, device: &ash::Device) {
+        unsafe {
+            device.destroy_image_view(self.view, None);
+            device.destroy_image(self.image, None);
+            device.free_memory(self.memory, None);
+        }
+    }
+}
+
+/// Attachments del render pass del swapchain que no son imágenes del swapchain:
+/// la profundidad y, con MSAA, el color multisampleado. Dependen del tamaño del
+/// swapchain, así que se recrean con él.
+pub struct FrameAttachments {
+    pub depth: AttachmentImage,
+    pub color: Option<AttachmentImage>,
+}
+
+impl FrameAttachments {
+    pub fn new(context: &VulkanContext, depth_format: vk::Format, samples: SampleCount) -> Self {
+        let depth = AttachmentImage::new(
+            context,
+            depth_format,
+            samples,
+            vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT,
+            depth_aspect(depth_format),
+        );
+        let color = (samples != SampleCount::X1).then(|| {
+            AttachmentImage::new(
+                context,
+                context.swapchain_format(),
+                samples,
+                vk::ImageUsageFlags::COLOR_ATTACHMENT,
+                vk::ImageAspectFlags::COLOR,
+            )
+        });
+        Self { depth, color }
+    }
+
+    /// Vistas en el orden de los attachments de `render_pass::create_render_pass`.
+    fn views(&self, swapchain_view: vk::ImageView) -> Vec<vk::ImageView> {
+        match &self.color {
+            Some(color) => vec![color.view, self.depth.view, swapchain_view],
+            None => vec![swapchain_view, self.depth.view],
+        }
+    }
+
+    pub fn cleanup(&self, device: &ash::Device) {
+        self.depth.cleanup(device);
+        if let Some(color) = &self.color {
+            color.cleanup(device);
+        }
+    }
+}
+
 pub fn create_framebuffers(
     device: &ash::Device,
     render_pass: vk::RenderPass,
     image_views: &[vk::ImageView],
+    attachments: &FrameAttachments,
     extent: vk::Extent2D,
 ) -> Vec<vk::Framebuffer> {
-    image_views.iter().map(|&image_view| {
-        let attachments = [image_view];
-        let create_info = vk::FramebufferCreateInfo {
-            render_pass,
-            attachment_count: attachments.len() as u32,
-            p_attachments: attachments.as_ptr(),
-            width: extent.width,
-            height: extent.height,
-            layers: 1,
-            ..Default::default()
-        };
-        unsafe { device.create_framebuffer(&create_info, None).expect("Failed to create framebuffer") }
-    }).collect()
+    image_views
+        .iter()
+        .map(|&image_view| {
+            let attachments = attachments.views(image_view);
+            let create_info = vk::FramebufferCreateInfo {
+                render_pass,
+                attachment_count: attachments.len() as u32,
+                p_attachments: attachments.as_ptr(),
+                width: extent.width,
+                height: extent.height,
+                layers: 1,
+                ..Default::default()
+            };
+            unsafe {
+                device
+                    .create_framebuffer(&create_info, None)
+                    .expect("Failed to create framebuffer")
+            }
+        })
+        .collect()
 }
@@ -0,0 +1,894 @@
+//! Grafo de render del frame.
+//!
+//! Cada pass declara las texturas y buffers que lee y escribe. Escribir un
+//! recurso devuelve una nueva versión de su handle, así que el orden de los passes
+//! sale de los datos y no del orden en que se declaran. `RenderGraph::compile` es
+//! un paso puro en CPU que:
+//!
+//! - descarta los passes cuyo resultado no llega a ninguna salida,
+//! - ordena el resto según sus dependencias,
+//! - calcula las barreras entre usos de cada recurso y
+//! - reparte las texturas transitorias en texturas físicas, reutilizando una
+//!   misma textura para recursos cuyas vidas no se solapan.
+//!
+//! `CompiledGraph::execute` graba después los passes en una `CommandList`.
+
+use crate::backend::{
+    BackendError, BarrierResource, BufferId, CommandList, RenderBackend, RenderPassDesc,
+    RenderTarget, ResourceBarrier, ResourceUsage, TextureDesc, TextureId,
+};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// Versión de un recurso del grafo. Cada escritura crea una versión nueva.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ResourceId {
+    index: u32,
+    version: u32,
+}
+
+impl ResourceId {
+    /// Si `self` y `other` son versiones del mismo recurso.
+    pub fn same_resource(self, other: ResourceId) -> bool {
+        self.index == other.index
+    }
+}
+
+/// Pass del grafo, numerado por orden de declaración.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PassId(pub u32);
+
+/// Errores al compilar un grafo.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GraphError {
+    /// Dos passes escriben sobre la misma versión de un recurso.
+    ConflictingWrite { pass: String, resource: String },
+    /// Las dependencias entre passes forman un ciclo.
+    Cycle(Vec<String>),
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::ConflictingWrite { pass, resource } => write!(
+                f,
+                "pass '{}' writes a version of '{}' that was already written",
+                pass, resource
+            ),
+            GraphError::Cycle(passes) => {
+                write!(f, "render graph has a cycle between {}", passes.join(", "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for GraphError {}
+
+#[derive(Clone, Debug)]
+enum ResourceKind {
+    /// Textura que solo vive dentro del frame; la crea el grafo.
+    Transient(TextureDesc),
+    /// Recurso externo, con el uso en el que llega y, si lo tiene, en el que debe
+    /// quedar al terminar el frame.
+    Imported {
+        resource: BarrierResource,
+        initial: ResourceUsage,
+        final_usage: Option<ResourceUsage>,
+    },
+}
+
+#[derive(Clone, Debug)]
+struct ResourceNode {
+    name: String,
+    kind: ResourceKind,
+    /// Pass que escribió cada versión; la versión `0` es el contenido inicial.
+    writers: Vec<Option<PassId>>,
+}
+
+/// Acceso de un pass a una versión de un recurso.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Access {
+    resource: ResourceId,
+    usage: ResourceUsage,
+}
+
+type RecordFn<'a> = Box<dyn FnOnce(&mut PassContext) + 'a>;
+
+struct PassNode<'a> {
+    name: String,
+    reads: Vec<Access>,
+    /// Escrituras, con la versión que consumen (la nueva es `version + 1`).
+    writes: Vec<Access>,
+    /// Destino del render pass y color de limpieza; sin él no se abre render pass.
+    color_attachment: Option<(ResourceId, Option<[f32; 4]>)>,
+    side_effects: bool,
+    record: Option<RecordFn<'a>>,
+}
+
+/// Grafo de render de un frame. `'a` es la vida de lo que capturan los passes.
+#[derive(Default)]
+pub struct RenderGraph<'a> {
+    resources: Vec<ResourceNode>,
+    passes: Vec<PassNode<'a>>,
+    outputs: Vec<ResourceId>,
+    error: Option<GraphError>,
+}
+
+impl<'a> RenderGraph<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn add_resource(&mut self, name: impl Into<String>, kind: ResourceKind) -> ResourceId {
+        self.resources.push(ResourceNode {
+            name: name.into(),
+            kind,
+            writers: vec![None],
+        });
+        ResourceId {
+            index: self.resources.len() as u32 - 1,
+            version: 0,
+        }
+    }
+
+    /// Textura transitoria: su contenido empieza indefinido y solo dura el frame.
+    pub fn create_texture(&mut self, name: impl Into<String>, desc: TextureDesc) -> ResourceId {
+        self.add_resource(name, ResourceKind::Transient(desc))
+    }
+
+    /// La imagen del swapchain; al final del frame queda lista para presentarse.
+    pub fn import_swapchain(&mut self) -> ResourceId {
+        self.add_resource(
+            "swapchain",
+            ResourceKind::Imported {
+                resource: BarrierResource::Swapchain,
+                initial: ResourceUsage::Undefined,
+                final_usage: Some(ResourceUsage::Present),
+            },
+        )
+    }
+
+    /// Textura externa que llega con el uso `current`.
+    pub fn import_texture(
+        &mut self,
+        name: impl Into<String>,
+        texture: TextureId,
+        current: ResourceUsage,
+    ) -> ResourceId {
+        self.add_resource(
+            name,
+            ResourceKind::Imported {
+                resource: BarrierResource::Texture(texture),
+                initial: current,
+                final_usage: None,
+            },
+        )
+    }
+
+    /// Buffer externo que llega con el uso `current`.
+    pub fn import_buffer(
+        &mut self,
+        name: impl Into<String>,
+        buffer: BufferId,
+        current: ResourceUsage,
+    ) -> ResourceId {
+        self.add_resource(
+            name,
+            ResourceKind::Imported {
+                resource: BarrierResource::Buffer(buffer),
+                initial: current,
+                final_usage: None,
+            },
+        )
+    }
+
+    /// Mantiene el pass que escribe `resource` aunque nadie lo lea en el grafo.
+    pub fn mark_output(&mut self, resource: ResourceId) {
+        self.outputs.push(resource);
+    }
+
+    /// Declara un pass; sus recursos se añaden con el `PassBuilder` devuelto.
+    pub fn add_pass(&mut self, name: impl Into<String>) -> PassBuilder<'_, 'a> {
+        self.passes.push(PassNode {
+            name: name.into(),
+            reads: Vec::new(),
+            writes: Vec::new(),
+            color_attachment: None,
+            side_effects: false,
+            record: None,
+        });
+        PassBuilder {
+            id: PassId(self.passes.len() as u32 - 1),
+            graph: self,
+        }
+    }
+
+    fn pass(&self, pass: PassId) -> &PassNode<'a> {
+        &self.passes[pass.0 as usize]
+    }
+
+    fn resource(&self, resource: ResourceId) -> &ResourceNode {
+        &self.resources[resource.index as usize]
+    }
+
+    /// Pass que escribió `resource`, o `None` si es el contenido inicial.
+    fn writer(&self, resource: ResourceId) -> Option<PassId> {
+        self.resource(resource).writers[resource.version as usize]
+    }
+
+    /// Passes que leen `resource`.
+    fn readers(&self, resource: ResourceId) -> impl Iterator<Item = PassId> + '_ {
+        self.passes
+            .iter()
+            .enumerate()
+            .filter_map(move |(index, pass)| {
+                pass.reads
+                    .iter()
+                    .any(|access| access.resource == resource)
+                    .then_some(PassId(index as u32))
+            })
+    }
+
+    /// Passes que tienen que ejecutarse: los que escriben una salida o tienen
+    /// efectos secundarios, y todo aquello de lo que dependen.
+    fn live_passes(&self) -> Vec<bool> {
+        let mut live = vec![false; self.passes.len()];
+        let mut stack: Vec<PassId> = Vec::new();
+        for (index, pass) in self.passes.iter().enumerate() {
+            let writes_output = pass.writes.iter().any(|access| {
+                let node = self.resource(access.resource);
+                let written = ResourceId {
+                    version: access.resource.version + 1,
+                    ..access.resource
+                };
+                let last = written.version as usize == node.writers.len() - 1;
+                let imported_output = matches!(
+                    node.kind,
+                    ResourceKind::Imported {
+                        final_usage: Some(_),
+                        ..
+                    }
+                );
+                (last && imported_output) || self.outputs.contains(&written)
+            });
+            if pass.side_effects || writes_output {
+                stack.push(PassId(index as u32));
+            }
+        }
+        while let Some(pass) = stack.pop() {
+            if std::mem::replace(&mut live[pass.0 as usize], true) {
+                continue;
+            }
+            let node = self.pass(pass);
+            // Una escritura conserva el contenido anterior salvo que lo limpie.
+            let clears = |access: &Access| matches!(node.color_attachment, Some((target, Some(_))) if target == access.resource);
+            let needed = node
+                .reads
+                .iter()
+                .chain(node.writes.iter().filter(|access| !clears(access)));
+            stack.extend(needed.filter_map(|access| self.writer(access.resource)));
+        }
+        live
+    }
+
+    /// Aristas `antes → después` entre passes vivos.
+    fn dependencies(&self, live: &[bool]) -> BTreeSet<(PassId, PassId)> {
+        let mut edges = BTreeSet::new();
+        for (index, pass) in self.passes.iter().enumerate() {
+            let pass_id = PassId(index as u32);
+            if !live[index] {
+                continue;
+            }
+            for access in &pass.reads {
+                edges.extend(self.writer(access.resource).map(|writer| (writer, pass_id)));
+            }
+            for access in &pass.writes {
+                // Tras quien escribió la versión anterior y tras quienes la leen.
+                edges.extend(self.writer(access.resource).map(|writer| (writer, pass_id)));
+                edges.extend(
+                    self.readers(access.resource)
+                        .map(|reader| (reader, pass_id)),
+                );
+            }
+        }
+        edges.retain(|&(from, to)| from != to && live[from.0 as usize] && live[to.0 as usize]);
+        edges
+    }
+
+    /// Ordena los passes vivos. Entre los que están listos a la vez va primero el
+    /// declarado antes, así que un grafo sin reordenamientos conserva su orden.
+    fn schedule(&self, live: &[bool]) -> Result<Vec<PassId>, GraphError> {
+        let edges = self.dependencies(live);
+        let mut incoming = vec![0usize; self.passes.len()];
+        for &(_, to) in &edges {
+            incoming[to.0 as usize] += 1;
+        }
+        let mut ready: BTreeSet<PassId> = (0..self.passes.len())
+            .filter(|&index| live[index] && incoming[index] == 0)
+            .map(|index| PassId(index as u32))
+            .collect();
+        let mut order = Vec::new();
+        while let Some(pass) = ready.pop_first() {
+            order.push(pass);
+            for &(_, to) in edges.range((pass, PassId(0))..(PassId(pass.0 + 1), PassId(0))) {
+                incoming[to.0 as usize] -= 1;
+                if incoming[to.0 as usize] == 0 {
+                    ready.insert(to);
+                }
+            }
+        }
+        let live_count = live.iter().filter(|&&live| live).count();
+        if order.len() < live_count {
+            let stuck = (0..self.passes.len())
+                .filter(|&index| live[index] && incoming[index] > 0)
+                .map(|index| self.passes[index].name.clone())
+                .collect();
+            return Err(GraphError::Cycle(stuck));
+        }
+        Ok(order)
+    }
+
+    /// Uso de cada recurso en `pass`; si lo lee y lo escribe manda la escritura.
+    fn pass_usages(&self, pass: PassId) -> Vec<(ResourceId, ResourceUsage)> {
+        let node = self.pass(pass);
+        let mut usages: Vec<(ResourceId, ResourceUsage)> = Vec::new();
+        for access in node.reads.iter().chain(&node.writes) {
+            match usages
+                .iter_mut()
+                .find(|(resource, _)| resource.same_resource(access.resource))
+            {
+                Some(entry) if access.usage.is_write() => *entry = (access.resource, access.usage),
+                Some(_) => {}
+                None => usages.push((access.resource, access.usage)),
+            }
+        }
+        usages
+    }
+
+    /// Compila el grafo: culling, orden, barreras y aliasing de transitorios.
+    pub fn compile(self) -> Result<CompiledGraph<'a>, GraphError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        let live = self.live_passes();
+        let order = self.schedule(&live)?;
+        let usages: Vec<Vec<(ResourceId, ResourceUsage)>> =
+            order.iter().map(|&pass| self.pass_usages(pass)).collect();
+
+        // Vida de cada transitorio: primera y última posición en `order`.
+        let mut lifetimes: Vec<Option<(usize, usize)>> = vec![None; self.resources.len()];
+        for (position, pass_usages) in usages.iter().enumerate() {
+            for (resource, _) in pass_usages {
+                let lifetime = &mut lifetimes[resource.index as usize];
+                *lifetime =
+                    Some(lifetime.map_or((position, position), |(first, _)| (first, position)));
+            }
+        }
+
+        // Aliasing: cada transitorio ocupa la primera textura física compatible que
+        // ya no se use, y hereda su último uso como punto de partida.
+        let mut physical: Vec<(TextureDesc, usize, ResourceUsage)> = Vec::new();
+        let mut slots: Vec<Option<usize>> = vec![None; self.resources.len()];
+        let mut state: Vec<ResourceUsage> = self
+            .resources
+            .iter()
+            .map(|node| match node.kind {
+                ResourceKind::Imported { initial, .. } => initial,
+                ResourceKind::Transient(_) => ResourceUsage::Undefined,
+            })
+            .collect();
+        let mut transients: Vec<usize> = (0..self.resources.len())
+            .filter(|&index| {
+                matches!(self.resources[index].kind, ResourceKind::Transient(_))
+                    && lifetimes[index].is_some()
+            })
+            .collect();
+        transients.sort_by_key(|&index| lifetimes[index]);
+        for index in transients {
+            let ResourceKind::Transient(desc) = &self.resources[index].kind else {
+                unreachable!();
+            };
+            let (first, last) = lifetimes[index].unwrap();
+            let last_usage = usages[last]
+                .iter()
+                .find(|(resource, _)| resource.index as usize == index)
+                .map(|&(_, usage)| usage)
+                .unwrap();
+            let slot = match physical
+                .iter()
+                .position(|(slot_desc, end, _)| slot_desc == desc && *end < first)
+            {
+                Some(slot) => {
+                    state[index] = physical[slot].2;
+                    physical[slot] = (desc.clone(), last, last_usage);
+                    slot
+                }
+                None => {
+                    physical.push((desc.clone(), last, last_usage));
+                    physical.len() - 1
+                }
+            };
+            slots[index] = Some(slot);
+        }
+
+        // Barreras: una por cambio de uso o por escritura tras otro acceso.
+        let mut steps = Vec::with_capacity(order.len());
+        for (&pass, pass_usages) in order.iter().zip(&usages) {
+            let mut barriers = Vec::new();
+            for &(resource, usage) in pass_usages {
+                let before = std::mem::replace(&mut state[resource.index as usize], usage);
+                if before != usage || usage.is_write() {
+                    barriers.push(GraphBarrier {
+                        resource,
+                        before,
+                        after: usage,
+                    });
+                }
+            }
+            steps.push(CompiledPass {
+                pass,
+                name: self.pass(pass).name.clone(),
+                barriers,
+            });
+        }
+        let mut final_barriers = Vec::new();
+        for (index, node) in self.resources.iter().enumerate() {
+            if let ResourceKind::Imported {
+                final_usage: Some(usage),
+                ..
+            } = node.kind
+                && state[index] != usage
+            {
+                final_barriers.push(GraphBarrier {
+                    resource: ResourceId {
+                        index: index as u32,
+                        version: node.writers.len() as u32 - 1,
+                    },
+                    before: state[index],
+                    after: usage,
+                });
+            }
+        }
+
+        let culled = (0..self.passes.len())
+            .filter(|&index| !live[index])
+            .map(|index| PassId(index as u32))
+            .collect();
+        let mut records = Vec::with_capacity(self.passes.len());
+        let mut attachments = Vec::with_capacity(self.passes.len());
+        for pass in self.passes {
+            records.push(pass.record);
+            attachments.push(pass.color_attachment);
+        }
+        Ok(CompiledGraph {
+            steps,
+            culled,
+            final_barriers,
+            transient_textures: physical.into_iter().map(|(desc, _, _)| desc).collect(),
+            slots,
+            kinds: self.resources.into_iter().map(|node| node.kind).collect(),
+            records,
+            attachments,
+        })
+    }
+}
+
+/// Declaración de los recursos de un pass.
+pub struct PassBuilder<'g, 'a> {
+    graph: &'g mut RenderGraph<'a>,
+    id: PassId,
+}
+
+impl<'a> PassBuilder<'_, 'a> {
+    pub fn id(&self) -> PassId {
+        self.id
+    }
+
+    fn node(&mut self) -> &mut PassNode<'a> {
+        &mut self.graph.passes[self.id.0 as usize]
+    }
+
+    /// Lee `resource` con el uso `usage`.
+    pub fn read(&mut self, resource: ResourceId, usage: ResourceUsage) -> &mut Self {
+        self.node().reads.push(Access { resource, usage });
+        self
+    }
+
+    /// Escribe `resource` con el uso `usage` y devuelve la nueva versión.
+    pub fn write(&mut self, resource: ResourceId, usage: ResourceUsage) -> ResourceId {
+        let node = &mut self.graph.resources[resource.index as usize];
+        if resource.version as usize + 1 != node.writers.len() {
+            let error = GraphError::ConflictingWrite {
+                pass: self.graph.passes[self.id.0 as usize].name.clone(),
+                resource: node.name.clone(),
+            };
+            self.graph.error.get_or_insert(error);
+            return resource;
+        }
+        node.writers.push(Some(self.id));
+        self.node().writes.push(Access { resource, usage });
+        ResourceId {
+            version: resource.version + 1,
+            ..resource
+        }
+    }
+
+    /// Dibuja en `resource` dentro de un render pass, limpiándolo antes con
+    /// `clear_color` si se da. Devuelve la nueva versión.
+    pub fn color_attachment(
+        &mut self,
+        resource: ResourceId,
+        clear_color: Option<[f32; 4]>,
+    ) -> ResourceId {
+        self.node().color_attachment = Some((resource, clear_color));
+        self.write(resource, ResourceUsage::ColorAttachment)
+    }
+
+    /// Mantiene el pass aunque ninguna salida dependa de él.
+    pub fn side_effects(&mut self) -> &mut Self {
+        self.node().side_effects = true;
+        self
+    }
+
+    /// Función que graba los comandos del pass.
+    pub fn record(&mut self, record: impl FnOnce(&mut PassContext) + 'a) {
+        self.node().record = Some(Box::new(record));
+    }
+}
+
+/// Barrera calculada por el grafo, sobre un recurso del grafo.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GraphBarrier {
+    pub resource: ResourceId,
+    pub before: ResourceUsage,
+    pub after: ResourceUsage,
+}
+
+/// Un pass del grafo compilado con las barreras que lo preceden.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompiledPass {
+    pub pass: PassId,
+    pub name: String,
+    pub barriers: Vec<GraphBarrier>,
+}
+
+/// Resultado de `RenderGraph::compile`.
+pub struct CompiledGraph<'a> {
+    /// Passes en orden de ejecución.
+    pub steps: Vec<CompiledPass>,
+    /// Passes descartados.
+    pub culled: Vec<PassId>,
+    /// Transiciones de los recursos importados a su uso final.
+    pub final_barriers: Vec<GraphBarrier>,
+    /// Texturas físicas que necesitan los transitorios tras el aliasing.
+    pub transient_textures: Vec<TextureDesc>,
+    slots: Vec<Option<usize>>,
+    kinds: Vec<ResourceKind>,
+    records: Vec<Option<RecordFn<'a>>>,
+    attachments: Vec<Option<(ResourceId, Option<[f32; 4]>)>>,
+}
+
+impl CompiledGraph<'_> {
+    /// Textura física (índice de `transient_textures`) de un recurso transitorio.
+    pub fn physical_texture(&self, resource: ResourceId) -> Option<usize> {
+        self.slots[resource.index as usize]
+    }
+
+    /// Graba el frame; `textures` son las texturas reales de `transient_textures`.
+    pub fn execute(mut self, textures: &[TextureId]) -> CommandList {
+        let resources: Vec<Option<BarrierResource>> = self
+            .kinds
+            .iter()
+            .zip(&self.slots)
+            .map(|(kind, slot)| match kind {
+                ResourceKind::Imported { resource, .. } => Some(*resource),
+                ResourceKind::Transient(_) => {
+                    slot.map(|slot| BarrierResource::Texture(textures[slot]))
+                }
+            })
+            .collect();
+        let physical = |barriers: &[GraphBarrier]| -> Vec<ResourceBarrier> {
+            barriers
+                .iter()
+                .filter_map(|barrier| {
+                    let resource = resources[barrier.resource.index as usize]?;
+                    // El render pass del swapchain hace sus propias transiciones.
+                    (resource != BarrierResource::Swapchain).then_some(ResourceBarrier {
+                        resource,
+                        before: barrier.before,
+                        after: barrier.after,
+                    })
+                })
+                .collect()
+        };
+
+        let mut commands = CommandList::new();
+        for step in &self.steps {
+            let barriers = physical(&step.barriers);
+            if !barriers.is_empty() {
+                commands.barrier(barriers);
+            }
+            let index = step.pass.0 as usize;
+            let target = self.attachments[index].and_then(|(resource, clear_color)| {
+                let target = match resources[resource.index as usize]? {
+                    BarrierResource::Swapchain => RenderTarget::Swapchain,
+                    BarrierResource::Texture(texture) => RenderTarget::Texture(texture),
+                    BarrierResource::Buffer(_) => return None,
+                };
+                Some(RenderPassDesc {
+                    target,
+                    clear_color,
+                })
+            });
+            if let Some(desc) = target {
+                commands.begin_render_pass(desc);
+            }
+            if let Some(record) = self.records[index].take() {
+                record(&mut PassContext {
+                    commands: &mut commands,
+                    resources: &resources,
+                });
+            }
+            if target.is_some() {
+                commands.end_render_pass();
+            }
+        }
+        let barriers = physical(&self.final_barriers);
+        if !barriers.is_empty() {
+            commands.barrier(barriers);
+        }
+        commands
+    }
+}
+
+/// Lo que recibe la función de grabación de un pass.
+pub struct PassContext<'c> {
+    pub commands: &'c mut CommandList,
+    resources: &'c [Option<BarrierResource>],
+}
+
+impl PassContext<'_> {
+    /// Textura real de un recurso del grafo.
+    pub fn texture(&self, resource: ResourceId) -> Option<TextureId> {
+        match self.resources[resource.index as usize]? {
+            BarrierResource::Texture(texture) => Some(texture),
+            _ => None,
+        }
+    }
+
+    /// Buffer real de un recurso del grafo.
+    pub fn buffer(&self, resource: ResourceId) -> Option<BufferId> {
+        match self.resources[resource.index as usize]? {
+            BarrierResource::Buffer(buffer) => Some(buffer),
+            _ => None,
+        }
+    }
+}
+
+/// Texturas transitorias de un frame en vuelo, reutilizadas mientras el grafo
+/// pida las mismas.
+#[derive(Debug, Default)]
+pub struct TransientTextures {
+    textures: Vec<(TextureDesc, TextureId)>,
+}
+
+impl TransientTextures {
+    /// Texturas para `descs`, creando o sustituyendo las que no coincidan.
+    pub fn acquire(
+        &mut self,
+        backend: &mut dyn RenderBackend,
+        descs: &[TextureDesc],
+    ) -> Result<Vec<TextureId>, BackendError> {
+        while self.textures.len() > descs.len() {
+            let (_, texture) = self.textures.pop().unwrap();
+            backend.destroy_texture(texture);
+        }
+        for (index, desc) in descs.iter().enumerate() {
+            match self.textures.get(index) {
+                Some((current, _)) if current == desc => {}
+                Some(&(_, texture)) => {
+                    backend.destroy_texture(texture);
+                    self.textures[index] = (desc.clone(), backend.create_texture(desc)?);
+                }
+                None => {
+                    let texture = backend.create_texture(desc)?;
+                    self.textures.push((desc.clone(), texture));
+                }
+            }
+        }
+        Ok(self.textures.iter().map(|&(_, texture)| texture).collect())
+    }
+
+    pub fn clear(&mut self, backend: &mut dyn RenderBackend) {
+        for (_, texture) in self.textures.drain(..) {
+            backend.destroy_texture(texture);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{NullBackend, RenderCommand, TextureFormat, TextureUsage};
+
+    fn target(width: u32) -> TextureDesc {
+        TextureDesc {
+            width,
+            height: 64,
+            mip_levels: 1,
+            format: TextureFormat::Rgba8Unorm,
+            usage: TextureUsage::RENDER_TARGET | TextureUsage::SAMPLED,
+        }
+    }
+
+    #[test]
+    fn test_graph_orders_culls_and_inserts_barriers() {
+        let mut graph = RenderGraph::new();
+        let swapchain = graph.import_swapchain();
+        let gbuffer = graph.create_texture("gbuffer", target(64));
+        let history = graph.import_texture("history", TextureId(9), ResourceUsage::ShaderRead);
+        let debug = graph.create_texture("debug", target(64));
+
+        let mut geometry = graph.add_pass("geometry");
+        let gbuffer = geometry.color_attachment(gbuffer, Some([0.0; 4]));
+        // Se declara antes que la iluminación, pero sobrescribe el historial que
+        // esta lee, así que se ejecuta después.
+        let mut store = graph.add_pass("store history");
+        store.read(gbuffer, ResourceUsage::TransferSrc);
+        let stored = store.write(history, ResourceUsage::TransferDst);
+        graph.mark_output(stored);
+        let mut lighting = graph.add_pass("lighting");
+        lighting
+            .read(gbuffer, ResourceUsage::ShaderRead)
+            .read(history, ResourceUsage::ShaderRead);
+        let lit = lighting.color_attachment(swapchain, Some([0.0; 4]));
+        graph.add_pass("unused").color_attachment(debug, None);
+
+        let compiled = graph.compile().unwrap();
+        let order: Vec<&str> = compiled
+            .steps
+            .iter()
+            .map(|step| step.name.as_str())
+            .collect();
+        assert_eq!(order, ["geometry", "lighting", "store history"]);
+        assert_eq!(compiled.culled, [PassId(3)]);
+        let barrier = |resource, before, after| GraphBarrier {
+            resource,
+            before,
+            after,
+        };
+        assert_eq!(
+            compiled.steps[1].barriers,
+            [
+                barrier(
+                    gbuffer,
+                    ResourceUsage::ColorAttachment,
+                    ResourceUsage::ShaderRead
+                ),
+                barrier(
+                    swapchain,
+                    ResourceUsage::Undefined,
+                    ResourceUsage::ColorAttachment
+                ),
+            ]
+        );
+        assert_eq!(
+            compiled.steps[2].barriers,
+            [
+                barrier(
+                    gbuffer,
+                    ResourceUsage::ShaderRead,
+                    ResourceUsage::TransferSrc
+                ),
+                barrier(
+                    history,
+                    ResourceUsage::ShaderRead,
+                    ResourceUsage::TransferDst
+                ),
+            ]
+        );
+        assert_eq!(
+            compiled.final_barriers,
+            [barrier(
+                lit,
+                ResourceUsage::ColorAttachment,
+                ResourceUsage::Present
+            )]
+        );
+        assert_eq!(compiled.transient_textures, [target(64)]);
+
+        // Al ejecutar, las barreras del swapchain quedan en manos de su render pass.
+        let commands = compiled.execute(&[TextureId(7)]);
+        assert_eq!(
+            commands.commands()[0],
+            RenderCommand::Barrier(vec![ResourceBarrier {
+                resource: BarrierResource::Texture(TextureId(7)),
+                before: ResourceUsage::Undefined,
+                after: ResourceUsage::ColorAttachment,
+            }])
+        );
+        assert_eq!(commands.len(), 7);
+        assert_eq!(commands.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_transients_alias_when_lifetimes_do_not_overlap() {
+        let mut graph = RenderGraph::new();
+        let swapchain = graph.import_swapchain();
+        let [a, b, c] = ["a", "b", "c"].map(|name| graph.create_texture(name, target(64)));
+        let half = graph.create_texture("half", target(32));
+
+        // a → b → c → half → swapchain: `a` y `c` pueden compartir textura.
+        let mut previous: Option<ResourceId> = None;
+        for (index, output) in [a, b, c, half].into_iter().enumerate() {
+            let mut pass = graph.add_pass(format!("pass {}", index));
+            if let Some(input) = previous {
+                pass.read(input, ResourceUsage::ShaderRead);
+            }
+            previous = Some(pass.color_attachment(output, None));
+        }
+        let mut present = graph.add_pass("present");
+        present.read(previous.unwrap(), ResourceUsage::ShaderRead);
+        present.color_attachment(swapchain, None);
+
+        let compiled = graph.compile().unwrap();
+        assert!(compiled.culled.is_empty());
+        assert_eq!(
+            compiled.transient_textures,
+            [target(64), target(64), target(32)]
+        );
+        assert_eq!(compiled.physical_texture(a), Some(0));
+        assert_eq!(compiled.physical_texture(b), Some(1));
+        assert_eq!(compiled.physical_texture(c), Some(0));
+        // `c` reutiliza la textura de `a` y parte de su último uso.
+        assert_eq!(
+            compiled.steps[2].barriers[1],
+            GraphBarrier {
+                resource: c,
+                before: ResourceUsage::ShaderRead,
+                after: ResourceUsage::ColorAttachment,
+            }
+        );
+
+        let mut backend = NullBackend::new(8, 8);
+        let mut pool = TransientTextures::default();
+        let textures = pool
+            .acquire(&mut backend, &compiled.transient_textures)
+            .unwrap();
+        assert_eq!(
+            pool.acquire(&mut backend, &compiled.transient_textures)
+                .unwrap(),
+            textures
+        );
+        backend.begin_frame().unwrap();
+        backend.submit(compiled.execute(&textures)).unwrap();
+        pool.clear(&mut backend);
+        assert_eq!(backend.resource_counts().1, 0);
+    }
+
+    #[test]
+    fn test_graph_errors() {
+        let mut graph = RenderGraph::new();
+        let texture = graph.create_texture("texture", target(64));
+        graph.add_pass("first").color_attachment(texture, None);
+        graph.add_pass("second").color_attachment(texture, None);
+        assert!(matches!(
+            graph.compile(),
+            Err(GraphError::ConflictingWrite { .. })
+        ));
+
+        // `b` lee la versión inicial de `x` (debe ir antes que `a`, que la
+        // reescribe) y a la vez lo que escribe `a`.
+        let mut graph = RenderGraph::new();
+        let x = graph.create_texture("x", target(64));
+        let y = graph.create_texture("y", target(64));
+        let mut a = graph.add_pass("a");
+        a.write(x, ResourceUsage::ColorAttachment);
+        let y1 = a.write(y, ResourceUsage::ColorAttachment);
+        let mut b = graph.add_pass("b");
+        b.read(x, ResourceUsage::ShaderRead)
+            .read(y1, ResourceUsage::ShaderRead)
+            .side_effects();
+        assert!(matches!(graph.compile(), Err(GraphError::Cycle(_))));
+    }
+}
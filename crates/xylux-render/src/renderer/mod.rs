@@ -1,6 +1,16 @@
-pub mod renderer;
-pub mod render_pass;
-pub mod framebuffers;
+pub mod batch;
 pub mod commands;
+pub mod culling;
+pub mod framebuffers;
+pub mod graph;
+pub mod render_pass;
+#[allow(clippy::module_inception)]
+pub mod renderer;
 
+pub use batch::{DrawBatch, FrameBatches, InstanceData};
+pub use culling::{CullingStats, Frustum};
+pub use graph::{
+    CompiledGraph, CompiledPass, GraphBarrier, GraphError, PassBuilder, PassContext, PassId,
+    RenderGraph, ResourceId, TransientTextures,
+};
 pub use renderer::Renderer;
@@ -1,10 +1,111 @@
 use ash::vk;
 
-pub fn create_render_pass(device: &ash::Device, swapchain_format: vk::Format) -> vk::RenderPass {
+use crate::backend::SampleCount;
+
+/// Formatos de profundidad/stencil por orden de preferencia; `D32_SFLOAT` sin
+/// stencil queda como último recurso.
+const DEPTH_FORMATS: [vk::Format; 3] = [
+    vk::Format::D32_SFLOAT_S8_UINT,
+    vk::Format::D24_UNORM_S8_UINT,
+    vk::Format::D32_SFLOAT,
+];
+
+/// Primer formato de `DEPTH_FORMATS` que el dispositivo admite como attachment
+/// de profundidad con tiling óptimo.
+pub fn select_depth_format(
+    instance: &ash::Instance,
+    physical_device: vk::PhysicalDevice,
+) -> vk::Format {
+    pick_depth_format(|format| unsafe {
+        instance
+            .get_physical_device_format_properties(physical_device, format)
+            .optimal_tiling_features
+            .contains(vk::FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT)
+    })
+    .expect("No supported depth format")
+}
+
+fn pick_depth_format(supported: impl Fn(vk::Format) -> bool) -> Option<vk::Format> {
+    DEPTH_FORMATS.into_iter().find(|&format| supported(format))
+}
+
+/// Aspectos de una imagen con uno de los formatos de `DEPTH_FORMATS`.
+pub fn depth_aspect(format: vk::Format) -> vk::ImageAspectFlags {
+    match format {
+        vk::Format::D32_SFLOAT_S8_UINT | vk::Format::D24_UNORM_S8_UINT => {
+            vk::ImageAspectFlags::DEPTH | vk::ImageAspectFlags::STENCIL
+        }
+        _ => vk::ImageAspectFlags::DEPTH,
+    }
+}
+
+/// Muestras que se usarán al pedir `requested`: las mayores que admitan a la vez
+/// los attachments de color y de profundidad.
+pub fn select_sample_count(
+    instance: &ash::Instance,
+    physical_device: vk::PhysicalDevice,
+    requested: SampleCount,
+) -> SampleCount {
+    let limits = unsafe {
+        instance
+            .get_physical_device_properties(physical_device)
+            .limits
+    };
+    let supported = limits.framebuffer_color_sample_counts & limits.framebuffer_depth_sample_counts;
+    requested.clamp_to(|samples| supported.contains(vk_sample_count(samples)))
+}
+
+pub fn vk_sample_count(samples: SampleCount) -> vk::SampleCountFlags {
+    vk::SampleCountFlags::from_raw(samples.count())
+}
+
+/// Render pass del swapchain.
+///
+/// Los attachments son: `0` color, `1` profundidad y, con MSAA, `2` la imagen del
+/// swapchain en la que se resuelve el color. Sin MSAA el color es directamente la
+/// imagen del swapchain.
+pub fn create_render_pass(
+    device: &ash::Device,
+    swapchain_format: vk::Format,
+    depth_format: vk::Format,
+    samples: SampleCount,
+) -> vk::RenderPass {
+    let multisampled = samples != SampleCount::X1;
     let color_attachment = vk::AttachmentDescription {
         format: swapchain_format,
-        samples: vk::SampleCountFlags::TYPE_1,
+        samples: vk_sample_count(samples),
+        load_op: vk::AttachmentLoadOp::CLEAR,
+        // Con MSAA solo se conserva la imagen resuelta.
+        store_op: if multisampled {
+            vk::AttachmentStoreOp::DONT_CARE
+        } else {
+            vk::AttachmentStoreOp::STORE
+        },
+        stencil_load_op: vk::AttachmentLoadOp::DONT_CARE,
+        stencil_store_op: vk::AttachmentStoreOp::DONT_CARE,
+        initial_layout: vk::ImageLayout::UNDEFINED,
+        final_layout: if multisampled {
+            vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL
+        } else {
+            vk::ImageLayout::PRESENT_SRC_KHR
+        },
+        ..Default::default()
+    };
+    let depth_attachment = vk::AttachmentDescription {
+        format: depth_format,
+        samples: vk_sample_count(samples),
         load_op: vk::AttachmentLoadOp::CLEAR,
+        store_op: vk::AttachmentStoreOp::DONT_CARE,
+        stencil_load_op: vk::AttachmentLoadOp::CLEAR,
+        stencil_store_op: vk::AttachmentStoreOp::DONT_CARE,
+        initial_layout: vk::ImageLayout::UNDEFINED,
+        final_layout: vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL,
+        ..Default::default()
+    };
+    let resolve_attachment = vk::AttachmentDescription {
+        format: swapchain_format,
+        samples: vk::SampleCountFlags::TYPE_1,
+        load_op: vk::AttachmentLoadOp::DONT_CARE,
         store_op: vk::AttachmentStoreOp::STORE,
         stencil_load_op: vk::AttachmentLoadOp::DONT_CARE,
         stencil_store_op: vk::AttachmentStoreOp::DONT_CARE,
@@ -12,26 +113,91 @@ pub fn create_render_pass(device: &ash::Device, swapchain_format: vk::Format) ->
         final_layout: vk::ImageLayout::PRESENT_SRC_KHR,
         ..Default::default()
     };
+    let attachments = [color_attachment, depth_attachment, resolve_attachment];
+    let attachment_count = if multisampled { 3 } else { 2 };
 
     let color_attachment_ref = vk::AttachmentReference {
         attachment: 0,
         layout: vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
     };
+    let depth_attachment_ref = vk::AttachmentReference {
+        attachment: 1,
+        layout: vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL,
+    };
+    let resolve_attachment_ref = vk::AttachmentReference {
+        attachment: 2,
+        layout: vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+    };
 
     let subpass = vk::SubpassDescription {
         pipeline_bind_point: vk::PipelineBindPoint::GRAPHICS,
         color_attachment_count: 1,
         p_color_attachments: &color_attachment_ref,
+        p_resolve_attachments: if multisampled {
+            &resolve_attachment_ref
+        } else {
+            std::ptr::null()
+        },
+        p_depth_stencil_attachment: &depth_attachment_ref,
+        ..Default::default()
+    };
+
+    // El depth buffer se comparte entre frames en vuelo: el clear de un frame
+    // tiene que esperar a los tests de profundidad del anterior.
+    let dependency = vk::SubpassDependency {
+        src_subpass: vk::SUBPASS_EXTERNAL,
+        dst_subpass: 0,
+        src_stage_mask: vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT
+            | vk::PipelineStageFlags::LATE_FRAGMENT_TESTS,
+        dst_stage_mask: vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT
+            | vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS,
+        src_access_mask: vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
+        dst_access_mask: vk::AccessFlags::COLOR_ATTACHMENT_WRITE
+            | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
         ..Default::default()
     };
 
     let render_pass_info = vk::RenderPassCreateInfo {
-        attachment_count: 1,
-        p_attachments: &color_attachment,
+        attachment_count,
+        p_attachments: attachments.as_ptr(),
         subpass_count: 1,
         p_subpasses: &subpass,
+        dependency_count: 1,
+        p_dependencies: &dependency,
         ..Default::default()
     };
 
     unsafe { device.create_render_pass(&render_pass_info, None).unwrap() }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_depth_format_and_sample_count_fallbacks() {
+        assert_eq!(
+            pick_depth_format(|_| true),
+            Some(vk::Format::D32_SFLOAT_S8_UINT)
+        );
+        assert_eq!(
+            pick_depth_format(|format| format == vk::Format::D32_SFLOAT),
+            Some(vk::Format::D32_SFLOAT)
+        );
+        assert_eq!(pick_depth_format(|_| false), None);
+        assert!(
+            depth_aspect(vk::Format::D24_UNORM_S8_UINT).contains(vk::ImageAspectFlags::STENCIL)
+        );
+
+        let supported = vk::SampleCountFlags::TYPE_1 | vk::SampleCountFlags::TYPE_4;
+        let clamp = |requested: SampleCount| {
+            requested.clamp_to(|samples| supported.contains(vk_sample_count(samples)))
+        };
+        assert_eq!(clamp(SampleCount::X8), SampleCount::X4);
+        assert_eq!(clamp(SampleCount::X2), SampleCount::X1);
+        assert_eq!(
+            vk_sample_count(SampleCount::X8),
+            vk::SampleCountFlags::TYPE_8
+        );
+    }
+}
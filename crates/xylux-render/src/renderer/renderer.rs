@@ -1,99 +1,628 @@
-use ash::vk;
-use crate::pipeline::Pipeline;
-use crate::vulkan::context::{VulkanContext, MAX_FRAMES_IN_FLIGHT};
+use crate::allocator::MemoryLocation;
+use crate::backend::{
+    BackendError, BindGroupDesc, BindGroupEntry, BindGroupId, BindingResource, BufferDesc,
+    BufferId, BufferUsage, FrameInfo, NullBackend, PipelineDesc, PipelineId, RenderBackend,
+    SampleCount, TextureId, UNIFORM_ALIGNMENT, VulkanBackend,
+};
+use crate::camera::{CameraUniform, CameraView};
+use crate::material::bindings::MaterialBindings;
+use crate::material::{Material, MaterialPipeline, MaterialShader};
+use crate::mesh::{Mesh, MeshError};
+use crate::renderer::batch::{FrameBatches, InstanceData};
+use crate::renderer::commands::{self, FrameCamera, MaterialDraw, SceneResources};
+use crate::renderer::culling::CullingStats;
+use crate::renderer::graph::TransientTextures;
+use crate::shader::{ShaderCompiler, ShaderError, ShaderPipelines};
+use crate::texture::Texture;
+use crate::vulkan::context::MAX_FRAMES_IN_FLIGHT;
 
-use xylux_ecs::World;
-use crate::renderer::{render_pass, framebuffers, commands};
-use xylux_window::XyluxWindow; // << Importar tu wrapper de ventana
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use xylux_ecs::{Aabb, Handle, Pool, World};
+use xylux_window::XyluxWindow;
 
+/// Buffers de una malla subidos al backend.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct GpuMesh {
+    pub vertex_buffer: BufferId,
+    pub index_buffer: BufferId,
+    pub index_count: u32,
+}
+
+/// Uniform buffer de cámaras de un frame en vuelo: un slot alineado a
+/// `UNIFORM_ALIGNMENT` por cámara, cada uno con su bind group.
+#[derive(Clone, Debug, Default)]
+struct CameraBuffer {
+    buffer: Option<BufferId>,
+    bind_groups: Vec<BindGroupId>,
+}
+
+/// Renderer de alto nivel: graba cada frame en una `CommandList` y la ejecuta en
+/// el `RenderBackend` que tenga asignado.
 pub struct Renderer {
-    // Hacemos el contexto mutable para actualizar el estado de sincronización
-    pub context: VulkanContext,
-    pub pipeline: Pipeline,
-    pub framebuffers: Vec<vk::Framebuffer>,
-    pub render_pass: vk::RenderPass,
-    pub(crate) current_frame: usize,
+    backend: Box<dyn RenderBackend>,
+    meshes: Pool<Mesh>,
+    materials: Pool<Material>,
+    textures: Pool<Texture>,
+    /// Texturas ya subidas; se suben la primera vez que las usa un material.
+    gpu_textures: HashMap<Handle<Texture>, TextureId>,
+    /// Pipelines de cada variante de material, creados al usarse por primera vez.
+    material_pipelines: HashMap<MaterialPipeline, PipelineId>,
+    material_bindings: MaterialBindings,
+    /// Materiales cambiados o eliminados cuyo bind group hay que retirar.
+    dirty_materials: HashSet<Handle<Material>>,
+    /// Directorio de `watch_shaders`.
+    shader_dir: Option<PathBuf>,
+    shader_pipelines: ShaderPipelines,
+    /// Errores de shaders aún no devueltos por `reload_shaders`.
+    shader_errors: Vec<(PipelineId, ShaderError)>,
+    /// Mallas ya subidas; se suben la primera vez que se dibujan.
+    pub(crate) gpu_meshes: HashMap<Handle<Mesh>, GpuMesh>,
+    /// Caja local de cada malla, para el culling.
+    mesh_bounds: HashMap<Handle<Mesh>, Aabb>,
+    culling_stats: CullingStats,
+    /// Un instance buffer por frame en vuelo, con su capacidad en bytes.
+    instance_buffers: [Option<(BufferId, u64)>; MAX_FRAMES_IN_FLIGHT],
+    /// Uniforms de cámara por frame en vuelo.
+    camera_buffers: [CameraBuffer; MAX_FRAMES_IN_FLIGHT],
+    /// Texturas transitorias del grafo por frame en vuelo.
+    transient_textures: [TransientTextures; MAX_FRAMES_IN_FLIGHT],
 }
 
 impl Renderer {
-    // ✅ Cambiar &Window por &XyluxWindow
+    /// Renderer sobre Vulkan que presenta en `window`.
     pub fn new(window: &XyluxWindow) -> Self {
-        // 1️⃣ Crear contexto Vulkan
-        let context = VulkanContext::new(window);
-
-        // 2️⃣ Crear render pass usando la información del contexto
-        let render_pass = render_pass::create_render_pass(&context.device, context.swapchain_format());
-
-        // 3️⃣ Crear framebuffers
-        let framebuffers = framebuffers::create_framebuffers(
-            &context.device,
-            render_pass,
-            context.swapchain_image_views(),
-            context.swapchain_extent(),
-        );
+        Self::with_backend(Box::new(VulkanBackend::new(window)))
+    }
 
-        // 4️⃣ Crear pipeline
-        let pipeline = Pipeline::new(&context.device, render_pass, context.swapchain_extent());
+    /// Renderer sin GPU ni ventana sobre un `NullBackend` de `width`x`height`.
+    pub fn headless(width: u32, height: u32) -> Self {
+        Self::with_backend(Box::new(NullBackend::new(width, height)))
+    }
+
+    /// Renderer sobre un backend arbitrario.
+    pub fn with_backend(mut backend: Box<dyn RenderBackend>) -> Self {
+        let mesh_pipeline = backend
+            .create_pipeline(&PipelineDesc::mesh())
+            .expect("Failed to create mesh pipeline");
 
+        let unlit = Material::default().pipeline();
         Self {
-            context,
-            pipeline,
-            framebuffers,
-            render_pass,
-            current_frame: 0,
+            backend,
+            meshes: Pool::new(),
+            materials: Pool::new(),
+            textures: Pool::new(),
+            gpu_textures: HashMap::new(),
+            material_pipelines: HashMap::from([(unlit, mesh_pipeline)]),
+            material_bindings: MaterialBindings::default(),
+            dirty_materials: HashSet::new(),
+            shader_dir: None,
+            shader_pipelines: ShaderPipelines::default(),
+            shader_errors: Vec::new(),
+            gpu_meshes: HashMap::new(),
+            mesh_bounds: HashMap::new(),
+            culling_stats: CullingStats::default(),
+            instance_buffers: [None; MAX_FRAMES_IN_FLIGHT],
+            camera_buffers: Default::default(),
+            transient_textures: Default::default(),
         }
     }
 
-    pub fn render(&mut self, world: &mut World, window: &XyluxWindow) {
-        commands::render_frame(self, world, window);
-        self.current_frame = (self.current_frame + 1) % MAX_FRAMES_IN_FLIGHT;
+    /// Valida una malla, la añade y devuelve el handle para usarla en un `MeshRenderer`.
+    pub fn add_mesh(&mut self, mesh: Mesh) -> Result<Handle<Mesh>, MeshError> {
+        mesh.validate()?;
+        let bounds = mesh.aabb();
+        let handle = self.meshes.insert(mesh);
+        self.mesh_bounds.insert(handle, bounds);
+        Ok(handle)
     }
 
-    pub fn device_wait_idle(&self) {
-        unsafe {
-            self.context.device.device_wait_idle().unwrap();
+    pub fn mesh(&self, handle: Handle<Mesh>) -> Option<&Mesh> {
+        self.meshes.get(handle)
+    }
+
+    /// Elimina una malla y libera sus buffers.
+    pub fn remove_mesh(&mut self, handle: Handle<Mesh>) -> Option<Mesh> {
+        if let Some(gpu) = self.gpu_meshes.remove(&handle) {
+            self.backend.destroy_buffer(gpu.vertex_buffer);
+            self.backend.destroy_buffer(gpu.index_buffer);
         }
+        self.mesh_bounds.remove(&handle);
+        self.meshes.remove(handle)
+    }
+
+    pub fn add_material(&mut self, material: Material) -> Handle<Material> {
+        self.materials.insert(material)
+    }
+
+    pub fn material(&self, handle: Handle<Material>) -> Option<&Material> {
+        self.materials.get(handle)
+    }
+
+    /// El material se vuelve a subir en el siguiente frame, se modifique o no.
+    pub fn material_mut(&mut self, handle: Handle<Material>) -> Option<&mut Material> {
+        self.dirty_materials.insert(handle);
+        self.materials.get_mut(handle)
+    }
+
+    /// Elimina un material; las entidades que lo usan dejan de dibujarse.
+    pub fn remove_material(&mut self, handle: Handle<Material>) -> Option<Material> {
+        self.dirty_materials.insert(handle);
+        self.materials.remove(handle)
+    }
+
+    /// Añade una textura para usarla en materiales; se sube al usarse por primera vez.
+    pub fn add_texture(&mut self, texture: Texture) -> Handle<Texture> {
+        self.textures.insert(texture)
     }
 
-    fn cleanup_swapchain(&self) {
-        unsafe {
-            for &framebuffer in &self.framebuffers {
-                self.context.device.destroy_framebuffer(framebuffer, None);
+    pub fn texture(&self, handle: Handle<Texture>) -> Option<&Texture> {
+        self.textures.get(handle)
+    }
+
+    /// Elimina una textura y la libera. Los materiales que la usan pasan a usar la
+    /// textura por defecto de su slot.
+    pub fn remove_texture(&mut self, handle: Handle<Texture>) -> Option<Texture> {
+        if let Some(texture) = self.gpu_textures.remove(&handle) {
+            self.backend.destroy_texture(texture);
+        }
+        for (material, _) in self
+            .materials
+            .iter()
+            .filter(|(_, m)| m.textures.uses(handle))
+        {
+            self.dirty_materials.insert(material);
+        }
+        self.textures.remove(handle)
+    }
+
+    /// Renderiza un frame del mundo.
+    ///
+    /// Si la superficie cambió (e.g. al redimensionar), el backend se reconfigura y
+    /// el frame se descarta.
+    pub fn render(&mut self, world: &mut World) {
+        match self.try_render(world) {
+            Ok(()) | Err(BackendError::OutOfDate) => {}
+            Err(error) => panic!("Failed to render frame: {}", error),
+        }
+    }
+
+    fn try_render(&mut self, world: &mut World) -> Result<(), BackendError> {
+        let frame = self.backend.begin_frame()?;
+        let views = CameraView::collect(world, frame.width, frame.height);
+        let batches = FrameBatches::collect_visible(
+            world,
+            &self.mesh_bounds,
+            &views,
+            &mut self.culling_stats,
+        );
+        let instance_buffer = self.prepare(&frame, &batches)?;
+        let cameras = self.prepare_cameras(&frame, &views)?;
+        let materials = self.prepare_materials(&frame, &batches)?;
+        let scene = SceneResources {
+            gpu_meshes: &self.gpu_meshes,
+            materials: &materials,
+        };
+        let graph = commands::frame_graph(scene, &batches, instance_buffer, &cameras);
+        let compiled = graph
+            .compile()
+            .map_err(|error| BackendError::InvalidCommands(error.to_string()))?;
+        let textures = self.transient_textures[frame.frame as usize % MAX_FRAMES_IN_FLIGHT]
+            .acquire(self.backend.as_mut(), &compiled.transient_textures)?;
+        self.backend.submit(compiled.execute(&textures))?;
+        self.backend.present()
+    }
+
+    /// Sube las mallas que faltan y escribe las instancias del frame.
+    fn prepare(
+        &mut self,
+        frame: &FrameInfo,
+        batches: &FrameBatches,
+    ) -> Result<Option<BufferId>, BackendError> {
+        for batch in &batches.batches {
+            if self.gpu_meshes.contains_key(&batch.mesh) {
+                continue;
             }
-            self.pipeline.cleanup(&self.context.device);
-            self.context.device.destroy_render_pass(self.render_pass, None);
-            self.context.cleanup_swapchain_resources();
+            let Some(mesh) = self.meshes.get(batch.mesh) else {
+                continue;
+            };
+            let vertices = mesh.vertex_bytes();
+            let indices = mesh.index_bytes();
+            // Geometría estática: en memoria de GPU, subida por la cola de transferencia
+            // antes de que la lea este frame.
+            let vertex_buffer = self.backend.create_buffer(&BufferDesc {
+                size: vertices.len() as u64,
+                usage: BufferUsage::VERTEX,
+                location: MemoryLocation::GpuOnly,
+            })?;
+            self.backend.upload_buffer(vertex_buffer, 0, &vertices)?;
+            let index_buffer = self.backend.create_buffer(&BufferDesc {
+                size: indices.len() as u64,
+                usage: BufferUsage::INDEX,
+                location: MemoryLocation::GpuOnly,
+            })?;
+            self.backend.upload_buffer(index_buffer, 0, &indices)?;
+            let gpu = GpuMesh {
+                vertex_buffer,
+                index_buffer,
+                index_count: mesh.indices.len() as u32,
+            };
+            self.gpu_meshes.insert(batch.mesh, gpu);
         }
+
+        if batches.instances.is_empty() {
+            return Ok(None);
+        }
+        // El buffer del frame `n` no se reutiliza hasta que la GPU termina con él.
+        let bytes = batches.instance_bytes();
+        let slot = &mut self.instance_buffers[frame.frame as usize % MAX_FRAMES_IN_FLIGHT];
+        let buffer = match *slot {
+            Some((buffer, capacity)) if capacity >= bytes.len() as u64 => buffer,
+            previous => {
+                if let Some((buffer, _)) = previous {
+                    self.backend.destroy_buffer(buffer);
+                }
+                let capacity = (bytes.len() as u64)
+                    .next_power_of_two()
+                    .max(64 * InstanceData::SIZE);
+                let buffer = self.backend.create_buffer(&BufferDesc {
+                    size: capacity,
+                    usage: BufferUsage::VERTEX,
+                    location: MemoryLocation::CpuToGpu,
+                })?;
+                *slot = Some((buffer, capacity));
+                buffer
+            }
+        };
+        self.backend.write_buffer(buffer, 0, &bytes)?;
+        Ok(Some(buffer))
     }
 
-    pub fn recreate_swapchain(&mut self, window: &XyluxWindow) {
-        self.device_wait_idle();
-        self.cleanup_swapchain();
+    /// Pipelines, texturas y bind groups de los materiales del frame.
+    ///
+    /// Antes retira los bind groups de los materiales cambiados y recupera los que
+    /// ya no usa ningún frame en vuelo.
+    fn prepare_materials(
+        &mut self,
+        frame: &FrameInfo,
+        batches: &FrameBatches,
+    ) -> Result<HashMap<Handle<Material>, MaterialDraw>, BackendError> {
+        for handle in self.dirty_materials.drain() {
+            self.material_bindings.invalidate(handle, frame.frame);
+        }
+        self.material_bindings
+            .collect(self.backend.as_mut(), frame.frame);
 
-        // Recrear swapchain y sus dependencias
-        self.context.recreate_swapchain_resources(window);
+        let mut draws = HashMap::new();
+        let mut uploaded = false;
+        let mut created = Vec::new();
+        for batch in &batches.batches {
+            if draws.contains_key(&batch.material) {
+                continue;
+            }
+            let Some(material) = self.materials.get(batch.material) else {
+                continue;
+            };
+            let key = material.pipeline();
+            let pipeline = match self.material_pipelines.get(&key) {
+                Some(&pipeline) => pipeline,
+                None => {
+                    let pipeline = self.backend.create_pipeline(&key.desc())?;
+                    self.material_pipelines.insert(key, pipeline);
+                    created.push((key, pipeline));
+                    pipeline
+                }
+            };
+            let (bind_group, push_constants) = match material.shader {
+                MaterialShader::Unlit => (None, material.push_constants()),
+                MaterialShader::PbrMetallicRoughness => {
+                    let mut textures = [None; 5];
+                    for (slot, handle) in textures.iter_mut().zip(material.textures.to_array()) {
+                        let Some(handle) = handle else {
+                            continue;
+                        };
+                        if let Some(&texture) = self.gpu_textures.get(&handle) {
+                            *slot = Some(texture);
+                        } else if let Some(texture) = self.textures.get(handle) {
+                            let texture = texture.upload(self.backend.as_mut())?;
+                            self.gpu_textures.insert(handle, texture);
+                            *slot = Some(texture);
+                            uploaded = true;
+                        }
+                    }
+                    let group = self.material_bindings.bind_group(
+                        self.backend.as_mut(),
+                        batch.material,
+                        material,
+                        textures,
+                    )?;
+                    (Some(group), Vec::new())
+                }
+            };
+            let draw = MaterialDraw {
+                pipeline,
+                bind_group,
+                push_constants,
+                blend: key.blend,
+            };
+            draws.insert(batch.material, draw);
+        }
+        // Con `watch_shaders`, los pipelines nuevos pasan a compilarse desde sus archivos.
+        for (key, pipeline) in created {
+            if let Err(error) = self.watch_material_pipeline(key, pipeline) {
+                self.shader_errors.push((pipeline, error));
+            }
+        }
+        // Las texturas nuevas tienen que estar en la GPU antes de dibujar con ellas.
+        if uploaded {
+            self.backend.flush_uploads()?;
+        }
+        Ok(draws)
+    }
 
-        self.render_pass = render_pass::create_render_pass(
-            &self.context.device,
-            self.context.swapchain_format(),
-        );
-        self.pipeline = Pipeline::new(
-            &self.context.device,
-            self.render_pass,
-            self.context.swapchain_extent(),
-        );
-        self.framebuffers = framebuffers::create_framebuffers(
-            &self.context.device,
-            self.render_pass,
-            self.context.swapchain_image_views(),
-            self.context.swapchain_extent(),
+    /// Compila los shaders de los materiales desde los archivos de `dir` en vez de
+    /// usar el SPIR-V incluido en el crate, y los vigila para `reload_shaders`.
+    ///
+    /// Cada pipeline usa `<label>.vert` y `<label>.frag` (e.g. `pbr.vert`), como en
+    /// `shaders/` del crate, que también es el directorio de los `#include <...>`.
+    /// Devuelve el primer error; los pipelines que fallen siguen con el SPIR-V
+    /// incluido hasta que se corrijan sus archivos.
+    pub fn watch_shaders(&mut self, dir: impl Into<PathBuf>) -> Result<(), ShaderError> {
+        let dir = dir.into();
+        self.shader_pipelines = ShaderPipelines::new(ShaderCompiler::new().with_include_dir(&dir));
+        self.shader_dir = Some(dir);
+        let pipelines: Vec<(MaterialPipeline, PipelineId)> = self
+            .material_pipelines
+            .iter()
+            .map(|(&key, &pipeline)| (key, pipeline))
+            .collect();
+        let mut result = Ok(());
+        for (key, pipeline) in pipelines {
+            let watched = self.watch_material_pipeline(key, pipeline);
+            if result.is_ok() {
+                result = watched;
+            }
+        }
+        result
+    }
+
+    /// Recompila los shaders vigilados cuyos archivos cambiaron y sustituye sus
+    /// pipelines. Pensado para llamarse una vez por frame.
+    ///
+    /// Devuelve los errores de compilación desde la llamada anterior, incluidos los
+    /// de pipelines de material creados mientras tanto; un pipeline con error
+    /// sigue con sus shaders anteriores.
+    pub fn reload_shaders(&mut self) -> Vec<(PipelineId, ShaderError)> {
+        let mut errors = std::mem::take(&mut self.shader_errors);
+        let reloaded = self.shader_pipelines.reload(self.backend.as_mut());
+        errors.extend(
+            reloaded
+                .into_iter()
+                .filter_map(|(pipeline, result)| Some((pipeline, result.err()?))),
         );
+        errors
+    }
+
+    fn watch_material_pipeline(
+        &mut self,
+        key: MaterialPipeline,
+        pipeline: PipelineId,
+    ) -> Result<(), ShaderError> {
+        let Some(dir) = &self.shader_dir else {
+            return Ok(());
+        };
+        let desc = key.desc();
+        let vertex = dir.join(format!("{}.vert", desc.label));
+        let fragment = dir.join(format!("{}.frag", desc.label));
+        self.shader_pipelines.watch(
+            self.backend.as_mut(),
+            pipeline,
+            desc.into(),
+            vertex,
+            fragment,
+        )
+    }
+
+    /// Estadísticas de culling del último frame.
+    pub fn culling_stats(&self) -> CullingStats {
+        self.culling_stats
+    }
+
+    /// Escribe los uniforms de las cámaras del frame, ampliando el buffer si hacen
+    /// falta más slots.
+    fn prepare_cameras(
+        &mut self,
+        frame: &FrameInfo,
+        views: &[CameraView],
+    ) -> Result<Vec<FrameCamera>, BackendError> {
+        let slot = &mut self.camera_buffers[frame.frame as usize % MAX_FRAMES_IN_FLIGHT];
+        if slot.bind_groups.len() < views.len() {
+            for group in slot.bind_groups.drain(..) {
+                self.backend.destroy_bind_group(group);
+            }
+            if let Some(buffer) = slot.buffer.take() {
+                self.backend.destroy_buffer(buffer);
+            }
+            let capacity = views.len().next_power_of_two().max(4) as u64;
+            let buffer = self.backend.create_buffer(&BufferDesc {
+                size: capacity * UNIFORM_ALIGNMENT,
+                usage: BufferUsage::UNIFORM,
+                location: MemoryLocation::CpuToGpu,
+            })?;
+            slot.buffer = Some(buffer);
+            for index in 0..capacity {
+                let group = self.backend.create_bind_group(&BindGroupDesc {
+                    layout: CameraUniform::bind_group_layout(),
+                    entries: vec![BindGroupEntry {
+                        binding: 0,
+                        resource: BindingResource::Buffer {
+                            buffer,
+                            offset: index * UNIFORM_ALIGNMENT,
+                            size: CameraUniform::SIZE,
+                        },
+                    }],
+                })?;
+                slot.bind_groups.push(group);
+            }
+        }
+
+        let buffer = slot.buffer.expect("camera buffer was created");
+        let mut cameras = Vec::with_capacity(views.len());
+        for (index, (view, &bind_group)) in views.iter().zip(&slot.bind_groups).enumerate() {
+            self.backend.write_buffer(
+                buffer,
+                index as u64 * UNIFORM_ALIGNMENT,
+                &view.uniform.to_bytes(),
+            )?;
+            cameras.push(FrameCamera {
+                viewport: view.viewport,
+                clear_color: view.clear_color,
+                bind_group,
+            });
+        }
+        Ok(cameras)
+    }
+
+    /// Notifica un cambio de tamaño de la ventana.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.backend.resize(width, height);
+    }
+
+    /// Pide antialiasing con `samples` muestras por píxel y devuelve las que admite
+    /// el backend.
+    pub fn set_msaa(&mut self, samples: SampleCount) -> SampleCount {
+        self.backend.set_sample_count(samples)
+    }
+
+    pub fn device_wait_idle(&mut self) {
+        self.backend.wait_idle();
+    }
+
+    pub fn cleanup(&mut self) {
+        self.gpu_meshes.clear();
+        self.gpu_textures.clear();
+        self.material_bindings.clear(self.backend.as_mut());
+        self.instance_buffers = [None; MAX_FRAMES_IN_FLIGHT];
+        self.camera_buffers = Default::default();
+        for textures in &mut self.transient_textures {
+            textures.clear(self.backend.as_mut());
+        }
+        self.backend.cleanup();
     }
 
-    pub fn cleanup(&self) {
-        // Esperar a que la GPU termine todas las operaciones pendientes antes de limpiar.
-        self.device_wait_idle();
-        self.cleanup_swapchain();
-        self.context.cleanup();
+    pub fn backend(&self) -> &dyn RenderBackend {
+        self.backend.as_ref()
+    }
+
+    pub fn backend_mut(&mut self) -> &mut dyn RenderBackend {
+        self.backend.as_mut()
+    }
+
+    /// Backend concreto, si es de tipo `B` (e.g. `NullBackend` en tests).
+    pub fn backend_as<B: RenderBackend + 'static>(&self) -> Option<&B> {
+        self.backend.as_any().downcast_ref::<B>()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::backend::SoftwareBackend;
+    use crate::camera::Camera;
+    use crate::mesh::MeshRenderer;
+    use glam::{Vec3, Vec4};
+    use xylux_ecs::Transform;
+
+    /// Escena de `hello_triangle`: el triángulo verde de `shaders/shader.vert` como malla.
+    pub(crate) fn spawn_hello_triangle(renderer: &mut Renderer, world: &mut World) {
+        world.register_component::<Transform>();
+        world.register_component::<MeshRenderer>();
+        let mesh = renderer
+            .add_mesh(Mesh::new(
+                vec![
+                    Vec3::new(0.0, -0.5, 0.0),
+                    Vec3::new(0.5, 0.5, 0.0),
+                    Vec3::new(-0.5, 0.5, 0.0),
+                ],
+                vec![0, 2, 1],
+            ))
+            .expect("triangle mesh is valid");
+        let material = renderer.add_material(Material::new(Vec4::new(0.0, 1.0, 0.0, 1.0)));
+        let entity = world.spawn_entity();
+        world.insert(entity, Transform::default());
+        world.insert(entity, MeshRenderer::new(mesh, material));
+    }
+
+    #[test]
+    #[ignore] // Ignorado por CI; requiere GPU
+    fn test_renderer_init_production() {
+        println!("=== Inicializando ventana ===");
+        let xwindow = XyluxWindow::new("Render Test", 800, 600);
+
+        println!("=== Inicializando renderer ===");
+        let mut renderer = Renderer::new(&xwindow);
+
+        println!("=== Creando mundo ECS ===");
+        let mut world = World::new(1000);
+        world.register_component::<Transform>();
+
+        println!("=== Renderizando frame de prueba ===");
+        renderer.render(&mut world);
+
+        println!("=== Esperando a que la GPU termine ===");
+        renderer.device_wait_idle();
+
+        println!("=== Limpiando recursos del renderer ===");
+        renderer.cleanup();
+
+        println!("=== Test completado correctamente ===");
+    }
+
+    #[test]
+    fn test_depth_buffer_resolves_overlapping_meshes() {
+        let mut renderer = Renderer::with_backend(Box::new(SoftwareBackend::new(32, 32)));
+        let mut world = World::new(16);
+        world.register_component::<Transform>();
+        world.register_component::<MeshRenderer>();
+        world.register_component::<Camera>();
+
+        // El cubo cercano se dibuja primero; sin profundidad el lejano lo taparía.
+        let mesh = renderer.add_mesh(Mesh::cube(1.0, 1)).unwrap();
+        let red = renderer.add_material(Material::new(Vec4::new(1.0, 0.0, 0.0, 1.0)));
+        let blue = renderer.add_material(Material::new(Vec4::new(0.0, 0.0, 1.0, 1.0)));
+        for (z, material) in [(1.0, red), (-1.0, blue)] {
+            let entity = world.spawn_entity();
+            world.insert(
+                entity,
+                Transform {
+                    position: Vec3::new(0.0, 0.0, z),
+                    ..Default::default()
+                },
+            );
+            world.insert(entity, MeshRenderer::new(mesh, material));
+        }
+        let camera = world.spawn_entity();
+        world.insert(camera, Camera::default());
+        world.insert(
+            camera,
+            Transform {
+                position: Vec3::new(0.0, 0.0, 4.0),
+                ..Default::default()
+            },
+        );
+
+        renderer.render(&mut world);
+        let image = renderer
+            .backend_as::<SoftwareBackend>()
+            .unwrap()
+            .presented_image()
+            .unwrap();
+        assert_eq!(image.pixel(16, 16), [255, 0, 0, 255]);
+
+        // El rasterizador no hace MSAA; el backend nulo acepta cualquier valor.
+        assert_eq!(renderer.set_msaa(SampleCount::X4), SampleCount::X1);
+        let mut headless = Renderer::headless(8, 8);
+        assert_eq!(headless.set_msaa(SampleCount::X8), SampleCount::X8);
+        assert_eq!(headless.backend().sample_count(), SampleCount::X8);
     }
 }
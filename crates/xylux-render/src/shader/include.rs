@@ -0,0 +1,204 @@
+//! Resolución de `#include` en las fuentes de los shaders.
+//!
+//! `#include "ruta"` busca primero junto al archivo que lo incluye y después en
+//! los directorios de include del compilador; `#include <ruta>`, solo en estos.
+//! Cada archivo se inserta una sola vez por shader (las inclusiones repetidas se
+//! ignoran, como con `#pragma once`) y los ciclos son un error. Se resuelve antes
+//! de compilar, así que funciona igual en WGSL que en GLSL.
+
+use super::ShaderError;
+use naga::SourceLocation;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Fuente de un shader con sus includes ya insertados.
+#[derive(Clone, Debug)]
+pub(crate) struct Source {
+    pub code: String,
+    /// Archivos leídos del disco, empezando por el principal si tiene ruta.
+    pub dependencies: Vec<PathBuf>,
+    /// Nombres de los archivos de `lines`; el 0 es el principal.
+    names: Vec<String>,
+    /// Archivo y línea original de cada línea de `code`.
+    lines: Vec<(usize, u32)>,
+}
+
+impl Source {
+    /// Inserta los includes de `code`. `path` es su ruta si viene de un archivo;
+    /// si no, `name` solo sirve para los mensajes de error.
+    pub fn resolve(
+        name: &str,
+        code: &str,
+        path: Option<&Path>,
+        include_dirs: &[PathBuf],
+    ) -> Result<Self, ShaderError> {
+        let mut resolver = Resolver {
+            include_dirs,
+            source: Source {
+                code: String::with_capacity(code.len()),
+                dependencies: path.map(Path::to_path_buf).into_iter().collect(),
+                names: vec![name.to_string()],
+                lines: Vec::new(),
+            },
+            stack: path.map(Path::to_path_buf).into_iter().collect(),
+            included: path.map(Path::to_path_buf).into_iter().collect(),
+        };
+        resolver.expand(0, code, path.and_then(Path::parent))?;
+        Ok(resolver.source)
+    }
+
+    /// `archivo:línea:columna` de una posición de `code`.
+    pub fn position(&self, location: SourceLocation) -> String {
+        match self
+            .lines
+            .get(location.line_number.saturating_sub(1) as usize)
+        {
+            Some(&(file, line)) => {
+                format!("{}:{}:{}", self.names[file], line, location.line_position)
+            }
+            None => self.names[0].clone(),
+        }
+    }
+}
+
+struct Resolver<'a> {
+    include_dirs: &'a [PathBuf],
+    source: Source,
+    /// Archivos que se están expandiendo, para detectar ciclos.
+    stack: Vec<PathBuf>,
+    included: HashSet<PathBuf>,
+}
+
+impl Resolver<'_> {
+    fn expand(&mut self, file: usize, code: &str, dir: Option<&Path>) -> Result<(), ShaderError> {
+        for (index, line) in code.lines().enumerate() {
+            let number = index as u32 + 1;
+            let at = || format!("{}:{}", self.source.names[file], number);
+            let Some(directive) = parse_include(line) else {
+                self.source.code.push_str(line);
+                self.source.code.push('\n');
+                self.source.lines.push((file, number));
+                continue;
+            };
+            let (target, quoted) = directive
+                .map_err(|()| ShaderError::Include(format!("{}: malformed #include", at())))?;
+            let path = self.find(target, quoted, dir).ok_or_else(|| {
+                ShaderError::Include(format!("{}: cannot find '{}'", at(), target))
+            })?;
+            if self.stack.contains(&path) {
+                return Err(ShaderError::Include(format!(
+                    "{}: '{}' includes itself",
+                    at(),
+                    path.display()
+                )));
+            }
+            if !self.included.insert(path.clone()) {
+                continue;
+            }
+            let included = std::fs::read_to_string(&path)
+                .map_err(|error| ShaderError::Io(format!("{}: {}", path.display(), error)))?;
+            let index = self.source.names.len();
+            self.source.names.push(path.display().to_string());
+            self.source.dependencies.push(path.clone());
+            self.stack.push(path.clone());
+            self.expand(index, &included, path.parent())?;
+            self.stack.pop();
+        }
+        Ok(())
+    }
+
+    /// Ruta canónica de un include, si existe.
+    fn find(&self, target: &str, quoted: bool, dir: Option<&Path>) -> Option<PathBuf> {
+        let local = dir.filter(|_| quoted).map(|dir| dir.join(target));
+        local
+            .into_iter()
+            .chain(self.include_dirs.iter().map(|dir| dir.join(target)))
+            .find_map(|path| std::fs::canonicalize(path).ok())
+    }
+}
+
+/// `Some` si la línea es un `#include`: la ruta y si va entre comillas, o `Err` si
+/// la ruta no está bien delimitada.
+fn parse_include(line: &str) -> Option<Result<(&str, bool), ()>> {
+    let rest = line.trim_start().strip_prefix('#')?.trim_start();
+    let target = rest.strip_prefix("include")?.trim();
+    let parsed = if let Some(quoted) = target.strip_prefix('"') {
+        quoted.strip_suffix('"').map(|path| (path, true))
+    } else if let Some(angled) = target.strip_prefix('<') {
+        angled.strip_suffix('>').map(|path| (path, false))
+    } else {
+        None
+    };
+    Some(parsed.filter(|(path, _)| !path.is_empty()).ok_or(()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_includes_are_inserted_once_and_cycles_fail() {
+        let dir = std::env::temp_dir().join(format!("xylux_include_{}", std::process::id()));
+        let common = dir.join("common");
+        std::fs::create_dir_all(&common).unwrap();
+        std::fs::write(
+            common.join("light.glsl"),
+            "#include \"math.glsl\"\nvec3 light;\n",
+        )
+        .unwrap();
+        std::fs::write(common.join("math.glsl"), "const float PI = 3.14;\n").unwrap();
+        std::fs::write(dir.join("loop.glsl"), "#include \"loop.glsl\"\n").unwrap();
+
+        let code = "#version 450\n#include <light.glsl>\n  #  include \"common/math.glsl\"\nvoid main() {}\n";
+        let main = dir.join("main.frag");
+        let source = Source::resolve(
+            "main.frag",
+            code,
+            Some(&main),
+            std::slice::from_ref(&common),
+        )
+        .unwrap();
+        assert_eq!(
+            source.code,
+            "#version 450\nconst float PI = 3.14;\nvec3 light;\nvoid main() {}\n"
+        );
+        assert_eq!(source.dependencies.len(), 3);
+        let at = |line_number| {
+            source.position(SourceLocation {
+                line_number,
+                line_position: 5,
+                offset: 0,
+                length: 0,
+            })
+        };
+        assert_eq!(at(1), "main.frag:1:5");
+        assert!(at(3).ends_with("light.glsl:2:5"));
+        assert_eq!(at(4), "main.frag:4:5");
+
+        // Entre ángulos no se busca junto al archivo.
+        let local = Source::resolve("main.frag", "#include <common/math.glsl>", Some(&main), &[]);
+        assert!(matches!(local, Err(ShaderError::Include(_))));
+        let errors = [
+            ("#include light.glsl", "main.frag:1: malformed #include"),
+            (
+                "\n#include \"none.glsl\"",
+                "main.frag:2: cannot find 'none.glsl'",
+            ),
+        ];
+        for (code, message) in errors {
+            let error = Source::resolve("main.frag", code, None, std::slice::from_ref(&common))
+                .unwrap_err();
+            assert_eq!(error, ShaderError::Include(message.into()));
+        }
+        let cycle = Source::resolve(
+            "main.frag",
+            "#include \"loop.glsl\"",
+            None,
+            std::slice::from_ref(&dir),
+        );
+        assert!(
+            matches!(cycle, Err(ShaderError::Include(message)) if message.ends_with("includes itself"))
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
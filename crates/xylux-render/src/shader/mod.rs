@@ -0,0 +1,457 @@
+//! # Módulo Shader
+//!
+//! Compilación de shaders WGSL y GLSL a SPIR-V en el propio proceso, con naga,
+//! reflexión de su interfaz y recarga en caliente.
+//!
+//! Los shaders escriben la posición en el clip space de Vulkan en los dos
+//! lenguajes (no se invierte la Y al generar SPIR-V). El punto de entrada de la
+//! etapa pedida se renombra a `main`, que es el que usan los backends, así que un
+//! archivo WGSL puede tener el vertex y el fragment shader juntos.
+//!
+//! - `include`: resolución de `#include`.
+//! - `reflect`: reflexión de SPIR-V (entradas de vértices, bind groups, push
+//!   constants) y `PipelineBuilder::reflect`.
+//! - `watch`: vigilancia de archivos por fecha de modificación.
+//! - `pipelines`: pipelines creados desde archivos que se recompilan al cambiar.
+
+pub mod include;
+pub mod pipelines;
+pub mod reflect;
+pub mod watch;
+
+pub use pipelines::ShaderPipelines;
+pub use reflect::ShaderReflection;
+pub use watch::FileWatcher;
+
+use crate::backend::BackendError;
+use include::Source;
+use naga::back::spv;
+use naga::valid::{Capabilities, ValidationFlags, Validator};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Etapa de un shader.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ShaderStage {
+    Vertex,
+    Fragment,
+}
+
+impl ShaderStage {
+    fn naga(self) -> naga::ShaderStage {
+        match self {
+            ShaderStage::Vertex => naga::ShaderStage::Vertex,
+            ShaderStage::Fragment => naga::ShaderStage::Fragment,
+        }
+    }
+}
+
+/// Lenguaje de la fuente de un shader.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ShaderLanguage {
+    Wgsl,
+    /// GLSL 450 para Vulkan.
+    Glsl,
+}
+
+impl ShaderLanguage {
+    /// Lenguaje según la extensión: `.wgsl`, o `.vert`, `.frag` y `.glsl`.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "wgsl" => Some(ShaderLanguage::Wgsl),
+            "vert" | "frag" | "glsl" => Some(ShaderLanguage::Glsl),
+            _ => None,
+        }
+    }
+}
+
+/// Errores al compilar un shader o al crear un pipeline con él.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShaderError {
+    /// No se pudo leer un archivo.
+    Io(String),
+    /// Un `#include` no se pudo resolver.
+    Include(String),
+    /// La fuente o el SPIR-V no son válidos; los mensajes de la fuente llevan
+    /// `archivo:línea:columna`.
+    Parse(String),
+    /// El shader no pasa la validación de naga.
+    Validation(String),
+    /// El shader usa algo no soportado (e.g. un storage buffer).
+    Unsupported(String),
+    /// El backend rechazó el pipeline.
+    Backend(BackendError),
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShaderError::Io(message) => write!(f, "I/O error: {}", message),
+            ShaderError::Include(message) => write!(f, "Include error: {}", message),
+            ShaderError::Parse(message) => write!(f, "Parse error: {}", message),
+            ShaderError::Validation(message) => write!(f, "Validation error: {}", message),
+            ShaderError::Unsupported(what) => write!(f, "Unsupported: {}", what),
+            ShaderError::Backend(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for ShaderError {}
+
+impl From<BackendError> for ShaderError {
+    fn from(error: BackendError) -> Self {
+        ShaderError::Backend(error)
+    }
+}
+
+/// Shader compilado a SPIR-V.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompiledShader {
+    pub stage: ShaderStage,
+    /// SPIR-V en little-endian, listo para `PipelineBuilder::vertex_shader`.
+    pub spirv: Vec<u8>,
+    /// Archivos de los que depende: el principal (si se compiló desde un archivo)
+    /// y sus includes, con rutas canónicas.
+    pub dependencies: Vec<PathBuf>,
+}
+
+impl CompiledShader {
+    pub fn reflect(&self) -> Result<ShaderReflection, ShaderError> {
+        ShaderReflection::from_spirv(&self.spirv)
+    }
+}
+
+/// Compilador de shaders.
+#[derive(Clone, Debug, Default)]
+pub struct ShaderCompiler {
+    include_dirs: Vec<PathBuf>,
+}
+
+impl ShaderCompiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Añade un directorio donde buscar los `#include`.
+    pub fn with_include_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.include_dirs.push(dir.into());
+        self
+    }
+
+    pub fn include_dirs(&self) -> &[PathBuf] {
+        &self.include_dirs
+    }
+
+    /// Compila la etapa `stage` del archivo `path`; el lenguaje sale de su extensión.
+    pub fn compile_file(
+        &self,
+        path: impl AsRef<Path>,
+        stage: ShaderStage,
+    ) -> Result<CompiledShader, ShaderError> {
+        let path = path.as_ref();
+        let io_error =
+            |error: std::io::Error| ShaderError::Io(format!("{}: {}", path.display(), error));
+        let language = ShaderLanguage::from_path(path).ok_or_else(|| {
+            ShaderError::Unsupported(format!("shader file extension of {}", path.display()))
+        })?;
+        let canonical = std::fs::canonicalize(path).map_err(io_error)?;
+        let code = std::fs::read_to_string(&canonical).map_err(io_error)?;
+        let name = path.display().to_string();
+        let source = Source::resolve(&name, &code, Some(&canonical), &self.include_dirs)?;
+        self.compile(source, language, stage)
+    }
+
+    /// Compila `code`; `name` identifica la fuente en los mensajes de error.
+    pub fn compile_source(
+        &self,
+        name: &str,
+        code: &str,
+        language: ShaderLanguage,
+        stage: ShaderStage,
+    ) -> Result<CompiledShader, ShaderError> {
+        let source = Source::resolve(name, code, None, &self.include_dirs)?;
+        self.compile(source, language, stage)
+    }
+
+    fn compile(
+        &self,
+        source: Source,
+        language: ShaderLanguage,
+        stage: ShaderStage,
+    ) -> Result<CompiledShader, ShaderError> {
+        let code = &source.code;
+        let mut module = match language {
+            ShaderLanguage::Wgsl => naga::front::wgsl::parse_str(code).map_err(|error| {
+                let at = error
+                    .location(code)
+                    .map(|location| source.position(location));
+                ShaderError::Parse(located(at, error.message()))
+            })?,
+            ShaderLanguage::Glsl => naga::front::glsl::Frontend::default()
+                .parse(&naga::front::glsl::Options::from(stage.naga()), code)
+                .map_err(|errors| {
+                    let messages: Vec<String> = errors
+                        .errors
+                        .iter()
+                        .map(|error| {
+                            let at = error
+                                .location(code)
+                                .map(|location| source.position(location));
+                            located(at, &error.kind.to_string())
+                        })
+                        .collect();
+                    ShaderError::Parse(messages.join("\n"))
+                })?,
+        };
+
+        let mut entry_points = module
+            .entry_points
+            .iter()
+            .filter(|entry_point| entry_point.stage == stage.naga());
+        let name = match (entry_points.next(), entry_points.next()) {
+            (Some(entry_point), None) => entry_point.name.clone(),
+            (None, _) => {
+                return Err(ShaderError::Parse(format!("no {:?} entry point", stage)));
+            }
+            (Some(_), Some(_)) => {
+                return Err(ShaderError::Unsupported(format!(
+                    "more than one {:?} entry point",
+                    stage
+                )));
+            }
+        };
+        module
+            .entry_points
+            .retain(|entry_point| entry_point.name == name);
+        module.entry_points[0].name = "main".into();
+
+        let info = Validator::new(ValidationFlags::all(), Capabilities::all())
+            .validate(&module)
+            .map_err(|error| {
+                let at = error
+                    .location(code)
+                    .map(|location| source.position(location));
+                ShaderError::Validation(located(at, &error_chain(error.as_inner())))
+            })?;
+        let options = spv::Options {
+            flags: spv::WriterFlags::DEBUG | spv::WriterFlags::LABEL_VARYINGS,
+            ..Default::default()
+        };
+        let pipeline_options = spv::PipelineOptions {
+            shader_stage: stage.naga(),
+            entry_point: "main".into(),
+        };
+        let words = spv::write_vec(&module, &info, &options, Some(&pipeline_options))
+            .map_err(|error| ShaderError::Unsupported(error.to_string()))?;
+        Ok(CompiledShader {
+            stage,
+            spirv: words.iter().flat_map(|word| word.to_le_bytes()).collect(),
+            dependencies: source.dependencies,
+        })
+    }
+}
+
+/// `message` precedido de su posición en la fuente, si se conoce.
+fn located(at: Option<String>, message: &str) -> String {
+    match at {
+        Some(at) => format!("{}: {}", at, message),
+        None => message.to_string(),
+    }
+}
+
+/// Mensaje de un error con los de sus causas.
+fn error_chain(error: &dyn std::error::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WGSL: &str = "
+struct Out {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+}
+
+@vertex
+fn vs_main(@location(0) position: vec2<f32>, @location(1) color: vec4<f32>) -> Out {
+    return Out(vec4<f32>(position, 0.0, 1.0), color);
+}
+
+@fragment
+fn fs_main(input: Out) -> @location(0) vec4<f32> {
+    return input.color;
+}
+";
+
+    #[test]
+    fn test_wgsl_and_glsl_compile_to_spirv_with_main() {
+        let compiler = ShaderCompiler::new();
+        for stage in [ShaderStage::Vertex, ShaderStage::Fragment] {
+            let shader = compiler
+                .compile_source("colors.wgsl", WGSL, ShaderLanguage::Wgsl, stage)
+                .unwrap();
+            assert_eq!(shader.spirv[..4], 0x0723_0203u32.to_le_bytes());
+            assert!(shader.dependencies.is_empty());
+            let options = naga::front::spv::Options::default();
+            let module = naga::front::spv::parse_u8_slice(&shader.spirv, &options).unwrap();
+            assert_eq!(module.entry_points.len(), 1);
+            assert_eq!(
+                (
+                    module.entry_points[0].name.as_str(),
+                    module.entry_points[0].stage
+                ),
+                ("main", stage.naga())
+            );
+        }
+        let vertex = compiler
+            .compile_source(
+                "colors.wgsl",
+                WGSL,
+                ShaderLanguage::Wgsl,
+                ShaderStage::Vertex,
+            )
+            .unwrap();
+        assert_eq!(
+            vertex.reflect().unwrap().vertex_inputs,
+            [
+                (0, crate::backend::VertexFormat::Float32x2),
+                (1, crate::backend::VertexFormat::Float32x4)
+            ]
+        );
+
+        let shaders = Path::new(env!("CARGO_MANIFEST_DIR")).join("shaders");
+        for (file, stage) in [
+            ("pbr.vert", ShaderStage::Vertex),
+            ("pbr.frag", ShaderStage::Fragment),
+        ] {
+            let shader = compiler.compile_file(shaders.join(file), stage).unwrap();
+            assert_eq!(
+                shader.dependencies,
+                [std::fs::canonicalize(shaders.join(file)).unwrap()]
+            );
+        }
+        assert_eq!(
+            ShaderLanguage::from_path(Path::new("a/b.wgsl")),
+            Some(ShaderLanguage::Wgsl)
+        );
+        assert!(matches!(
+            compiler.compile_file(shaders.join("pbr.vert.spv"), ShaderStage::Vertex),
+            Err(ShaderError::Unsupported(_))
+        ));
+    }
+
+    /// Los `.spv` de `shaders/` tienen que ser la compilación exacta de su fuente.
+    /// Con `XYLUX_UPDATE_SHADERS` definida se reescriben en vez de comprobarse.
+    #[test]
+    fn test_builtin_spirv_matches_its_source() {
+        let shaders = Path::new(env!("CARGO_MANIFEST_DIR")).join("shaders");
+        let update = std::env::var_os("XYLUX_UPDATE_SHADERS").is_some();
+        let compiler = ShaderCompiler::new();
+        let mut sources: Vec<PathBuf> = std::fs::read_dir(&shaders)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|ext| ext == "vert" || ext == "frag")
+            })
+            .collect();
+        sources.sort();
+        assert!(sources.len() >= 6, "{:?}", sources);
+        for source in sources {
+            let stage = match source.extension().unwrap().to_str() {
+                Some("vert") => ShaderStage::Vertex,
+                _ => ShaderStage::Fragment,
+            };
+            let spirv = compiler.compile_file(&source, stage).unwrap().spirv;
+            let binary = source.with_extension(format!(
+                "{}.spv",
+                source.extension().unwrap().to_str().unwrap()
+            ));
+            if update {
+                std::fs::write(&binary, &spirv).unwrap();
+            } else {
+                let committed = std::fs::read(&binary).unwrap();
+                assert!(
+                    committed == spirv,
+                    "{} is not compiled from its source",
+                    binary.display()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_errors_point_to_the_included_file() {
+        let dir = std::env::temp_dir().join(format!("xylux_shader_errors_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("color.wgsl"),
+            "fn tint() -> vec4<f32> {\n    return vec4<f32>(1.0) +;\n}\n",
+        )
+        .unwrap();
+        let compiler = ShaderCompiler::new().with_include_dir(&dir);
+        let code = "#include \"color.wgsl\"\n@fragment\nfn main() -> @location(0) vec4<f32> { return tint(); }\n";
+        let error = compiler
+            .compile_source(
+                "main.wgsl",
+                code,
+                ShaderLanguage::Wgsl,
+                ShaderStage::Fragment,
+            )
+            .unwrap_err();
+        let ShaderError::Parse(message) = error else {
+            panic!("unexpected error {:?}", error);
+        };
+        assert!(message.contains("color.wgsl:2:"), "{}", message);
+
+        let glsl = "#version 450\nlayout(location = 0) out vec4 color;\nvoid main() {\n    color = undefined;\n}\n";
+        let error = compiler
+            .compile_source(
+                "main.frag",
+                glsl,
+                ShaderLanguage::Glsl,
+                ShaderStage::Fragment,
+            )
+            .unwrap_err();
+        assert!(
+            matches!(&error, ShaderError::Parse(message) if message.starts_with("main.frag:4:")),
+            "{:?}",
+            error
+        );
+        let vertex_only = &WGSL[..WGSL.find("@fragment").unwrap()];
+        assert_eq!(
+            compiler.compile_source(
+                "colors.wgsl",
+                vertex_only,
+                ShaderLanguage::Wgsl,
+                ShaderStage::Fragment
+            ),
+            Err(ShaderError::Parse("no Fragment entry point".into()))
+        );
+        let two_vertex = format!(
+            "{}@vertex\nfn vs_other() -> @builtin(position) vec4<f32> {{ return vec4<f32>(0.0); }}\n",
+            vertex_only
+        );
+        assert_eq!(
+            compiler.compile_source(
+                "colors.wgsl",
+                &two_vertex,
+                ShaderLanguage::Wgsl,
+                ShaderStage::Vertex
+            ),
+            Err(ShaderError::Unsupported(
+                "more than one Vertex entry point".into()
+            ))
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
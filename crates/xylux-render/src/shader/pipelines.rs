@@ -0,0 +1,318 @@
+//! Pipelines cuyos shaders se compilan desde archivos y se recompilan al cambiar.
+
+use super::{FileWatcher, ShaderCompiler, ShaderError, ShaderStage};
+use crate::backend::{PipelineBuilder, PipelineDesc, PipelineId, RenderBackend};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Fuentes de un pipeline vigilado.
+#[derive(Clone, Debug)]
+struct ShaderPipeline {
+    /// Todo el pipeline salvo los shaders.
+    template: PipelineBuilder,
+    vertex: PathBuf,
+    fragment: PathBuf,
+}
+
+/// Registro de pipelines creados desde archivos de shaders.
+///
+/// Cada pipeline se compila con `ShaderCompiler`, se completa y comprueba con
+/// `PipelineBuilder::reflect` y se crea en el backend. `reload` recompila los
+/// que tienen algún archivo cambiado (incluidos sus `#include`) y los sustituye
+/// con `RenderBackend::replace_pipeline`, conservando su `PipelineId`; si un
+/// shader no compila, el pipeline anterior sigue en uso.
+#[derive(Debug, Default)]
+pub struct ShaderPipelines {
+    compiler: ShaderCompiler,
+    pipelines: HashMap<PipelineId, ShaderPipeline>,
+    watcher: FileWatcher<PipelineId>,
+}
+
+impl ShaderPipelines {
+    pub fn new(compiler: ShaderCompiler) -> Self {
+        Self {
+            compiler,
+            ..Default::default()
+        }
+    }
+
+    pub fn compiler(&self) -> &ShaderCompiler {
+        &self.compiler
+    }
+
+    /// Compila `vertex` y `fragment` y crea un pipeline con el resto de `template`.
+    pub fn create(
+        &mut self,
+        backend: &mut dyn RenderBackend,
+        template: PipelineBuilder,
+        vertex: impl Into<PathBuf>,
+        fragment: impl Into<PathBuf>,
+    ) -> Result<PipelineId, ShaderError> {
+        let source = ShaderPipeline {
+            template,
+            vertex: vertex.into(),
+            fragment: fragment.into(),
+        };
+        let (desc, dependencies) = self.build(&source)?;
+        let pipeline = backend.create_pipeline(&desc)?;
+        self.watcher.watch(pipeline, dependencies);
+        self.pipelines.insert(pipeline, source);
+        Ok(pipeline)
+    }
+
+    /// Pasa a vigilar un pipeline ya creado y lo sustituye por uno compilado desde
+    /// `vertex` y `fragment`. Si falla, el pipeline queda vigilado igualmente y
+    /// se recompila cuando cambien sus archivos.
+    pub fn watch(
+        &mut self,
+        backend: &mut dyn RenderBackend,
+        pipeline: PipelineId,
+        template: PipelineBuilder,
+        vertex: impl Into<PathBuf>,
+        fragment: impl Into<PathBuf>,
+    ) -> Result<(), ShaderError> {
+        let source = ShaderPipeline {
+            template,
+            vertex: vertex.into(),
+            fragment: fragment.into(),
+        };
+        self.watcher
+            .watch(pipeline, [source.vertex.clone(), source.fragment.clone()]);
+        self.pipelines.insert(pipeline, source);
+        self.rebuild(backend, pipeline)
+    }
+
+    /// Deja de vigilar `pipeline`; no lo destruye.
+    pub fn remove(&mut self, pipeline: PipelineId) {
+        self.pipelines.remove(&pipeline);
+        self.watcher.unwatch(pipeline);
+    }
+
+    /// `true` si `pipeline` está vigilado.
+    pub fn contains(&self, pipeline: PipelineId) -> bool {
+        self.pipelines.contains_key(&pipeline)
+    }
+
+    /// Recompila los pipelines con archivos cambiados desde la última llamada y
+    /// devuelve el resultado de cada uno.
+    pub fn reload(
+        &mut self,
+        backend: &mut dyn RenderBackend,
+    ) -> Vec<(PipelineId, Result<(), ShaderError>)> {
+        self.watcher
+            .poll()
+            .into_iter()
+            .map(|pipeline| (pipeline, self.rebuild(backend, pipeline)))
+            .collect()
+    }
+
+    fn rebuild(
+        &mut self,
+        backend: &mut dyn RenderBackend,
+        pipeline: PipelineId,
+    ) -> Result<(), ShaderError> {
+        let (desc, dependencies) = self.build(&self.pipelines[&pipeline])?;
+        backend.replace_pipeline(pipeline, &desc)?;
+        // Los includes pueden haber cambiado.
+        self.watcher.watch(pipeline, dependencies);
+        Ok(())
+    }
+
+    /// Compila los shaders de `source` y devuelve el pipeline y sus archivos.
+    fn build(&self, source: &ShaderPipeline) -> Result<(PipelineDesc, Vec<PathBuf>), ShaderError> {
+        let vertex = self
+            .compiler
+            .compile_file(&source.vertex, ShaderStage::Vertex)?;
+        let fragment = self
+            .compiler
+            .compile_file(&source.fragment, ShaderStage::Fragment)?;
+        let desc = source
+            .template
+            .clone()
+            .vertex_shader(&vertex.spirv)
+            .fragment_shader(&fragment.spirv)
+            .reflect()?
+            .build()?;
+        // Se vigilan las rutas tal como se dieron, además de las canónicas.
+        let dependencies = [source.vertex.clone(), source.fragment.clone()]
+            .into_iter()
+            .chain(vertex.dependencies.into_iter().skip(1))
+            .chain(fragment.dependencies.into_iter().skip(1))
+            .collect();
+        Ok((desc, dependencies))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::RenderCommand;
+    use crate::backend::{BackendError, NullBackend};
+    use crate::material::Material;
+    use crate::mesh::{Mesh, MeshRenderer};
+    use crate::renderer::Renderer;
+    use xylux_ecs::{Transform, World};
+
+    #[test]
+    fn test_changed_shaders_replace_their_pipelines() {
+        let dir =
+            std::env::temp_dir().join(format!("xylux_shader_pipelines_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let write = |name: &str, code: &str| std::fs::write(dir.join(name), code).unwrap();
+        write("color.glsl", "const vec4 COLOR = vec4(1.0);\n");
+        write(
+            "fill.vert",
+            "#version 450\nlayout(location = 0) in vec2 position;\nvoid main() { gl_Position = vec4(position, 0.0, 1.0); }\n",
+        );
+        write(
+            "fill.frag",
+            "#version 450\n#include \"color.glsl\"\nlayout(location = 0) out vec4 color;\nvoid main() { color = COLOR; }\n",
+        );
+        let mut backend = NullBackend::new(8, 8);
+        let mut pipelines = ShaderPipelines::new(ShaderCompiler::new());
+        let pipeline = pipelines
+            .create(
+                &mut backend,
+                PipelineDesc::builder("fill"),
+                dir.join("fill.vert"),
+                dir.join("fill.frag"),
+            )
+            .unwrap();
+        let original = backend.pipeline_desc(pipeline).unwrap().clone();
+        assert_eq!(original.vertex_layouts[0].stride, 8);
+        assert!(pipelines.reload(&mut backend).is_empty());
+
+        // Cambiar un include recompila el fragment shader.
+        write(
+            "color.glsl",
+            "const vec4 COLOR = vec4(0.5, 0.25, 0.125, 1.0);\n",
+        );
+        assert!(matches!(pipelines.reload(&mut backend)[..], [(id, Ok(()))] if id == pipeline));
+        let reloaded = backend.pipeline_desc(pipeline).unwrap().clone();
+        assert_ne!(reloaded.fragment_shader, original.fragment_shader);
+        assert_eq!(reloaded.vertex_shader, original.vertex_shader);
+
+        // Un error deja el pipeline anterior; al corregirlo se recarga.
+        write("color.glsl", "const vec4 COLOR = ;\n");
+        let results = pipelines.reload(&mut backend);
+        assert!(
+            matches!(results[..], [(_, Err(ShaderError::Parse(_)))]),
+            "{:?}",
+            results
+        );
+        assert_eq!(backend.pipeline_desc(pipeline), Some(&reloaded));
+        write("color.glsl", "const vec4 COLOR = vec4(1.0);\n");
+        assert!(matches!(pipelines.reload(&mut backend)[..], [(_, Ok(()))]));
+        assert_eq!(backend.pipeline_desc(pipeline), Some(&original));
+
+        // Una entrada nueva que no cubre el layout fijado no sustituye al pipeline.
+        pipelines.remove(pipeline);
+        let template =
+            PipelineDesc::builder("fill").vertex_layout(original.vertex_layouts[0].clone());
+        pipelines
+            .watch(
+                &mut backend,
+                pipeline,
+                template,
+                dir.join("fill.vert"),
+                dir.join("fill.frag"),
+            )
+            .unwrap();
+        write(
+            "fill.vert",
+            "#version 450\nlayout(location = 0) in vec2 position;\nlayout(location = 1) in float depth;\nvoid main() { gl_Position = vec4(position, depth, 1.0); }\n",
+        );
+        assert!(matches!(
+            pipelines.reload(&mut backend)[..],
+            [(
+                _,
+                Err(ShaderError::Backend(BackendError::InvalidPipeline(_)))
+            )]
+        ));
+        assert_eq!(backend.pipeline_desc(pipeline), Some(&original));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_material_shaders_hot_reload() {
+        let dir = std::env::temp_dir().join(format!("xylux_hot_reload_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let shaders = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("shaders");
+        std::fs::copy(shaders.join("mesh.vert"), dir.join("mesh.vert")).unwrap();
+        let tinted = std::fs::read_to_string(shaders.join("mesh.frag"))
+            .unwrap()
+            .replace("void main", "#include \"tint.glsl\"\n\nvoid main")
+            .replace("vertexColor;\n}", "vertexColor * TINT;\n}");
+        std::fs::write(dir.join("mesh.frag"), tinted).unwrap();
+        std::fs::write(dir.join("tint.glsl"), "const vec4 TINT = vec4(1.0);\n").unwrap();
+
+        let mut renderer = Renderer::headless(16, 16);
+        let mut world = World::new(16);
+        world.register_component::<Transform>();
+        world.register_component::<MeshRenderer>();
+        let mesh = renderer.add_mesh(Mesh::cube(1.0, 1)).unwrap();
+        let material = renderer.add_material(Material::default());
+        let entity = world.spawn_entity();
+        world.insert(entity, Transform::default());
+        world.insert(entity, MeshRenderer::new(mesh, material));
+        renderer.render(&mut world);
+
+        let fragment_shader = |renderer: &Renderer| {
+            let backend = renderer.backend_as::<NullBackend>().unwrap();
+            let pipeline = backend
+                .last_frame()
+                .unwrap()
+                .commands
+                .iter()
+                .find_map(|command| match command {
+                    RenderCommand::BindPipeline(pipeline) => Some(*pipeline),
+                    _ => None,
+                })
+                .unwrap();
+            (
+                pipeline,
+                backend
+                    .pipeline_desc(pipeline)
+                    .unwrap()
+                    .fragment_shader
+                    .clone(),
+            )
+        };
+        let (pipeline, builtin) = fragment_shader(&renderer);
+        renderer.watch_shaders(&dir).unwrap();
+        renderer.render(&mut world);
+        let (watched, original) = fragment_shader(&renderer);
+        assert_eq!(watched, pipeline, "el pipeline conserva su id");
+        assert_ne!(original, builtin);
+
+        // Un include roto se informa y el pipeline sigue con los shaders anteriores.
+        std::fs::write(dir.join("tint.glsl"), "const vec4 TINT = ;\n").unwrap();
+        let errors = renderer.reload_shaders();
+        assert!(
+            matches!(errors[..], [(id, ShaderError::Parse(_))] if id == pipeline),
+            "{:?}",
+            errors
+        );
+        assert_eq!(fragment_shader(&renderer).1, original);
+        std::fs::write(
+            dir.join("tint.glsl"),
+            "const vec4 TINT = vec4(0.5, 0.5, 0.5, 1.0);\n",
+        )
+        .unwrap();
+        assert!(renderer.reload_shaders().is_empty());
+        renderer.render(&mut world);
+        assert_ne!(fragment_shader(&renderer).1, original);
+
+        // Un pipeline de material creado después también se vigila: aquí no hay `pbr.vert`.
+        let pbr = renderer.add_material(Material::pbr());
+        world.insert(entity, MeshRenderer::new(mesh, pbr));
+        renderer.render(&mut world);
+        let errors = renderer.reload_shaders();
+        assert!(
+            matches!(errors[..], [(_, ShaderError::Io(_))]),
+            "{:?}",
+            errors
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
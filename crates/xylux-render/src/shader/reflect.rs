@@ -0,0 +1,407 @@
+//! Reflexión de SPIR-V: entradas de vértices, bindings y push constants.
+//!
+//! El SPIR-V se lee con el frontend `spv-in` de naga, así que funciona igual con
+//! los shaders compilados en tiempo de ejecución que con los `.spv` ya compilados.
+//! Solo se reconocen los tipos de `BindingType` y las entradas de `VertexFormat`;
+//! el resto (storage buffers, texturas 3D, enteros...) se rechaza.
+
+use super::ShaderError;
+use crate::backend::{
+    BackendError, BindGroupLayout, BindingLayout, BindingType, PipelineDesc, VertexAttribute,
+    VertexFormat, VertexLayout, VertexStepMode,
+};
+use naga::{
+    AddressSpace, ArraySize, Binding, ImageClass, ImageDimension, Module, ScalarKind, Type,
+    TypeInner, VectorSize,
+};
+
+/// Interfaz de un shader o de un pipeline completo.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ShaderReflection {
+    /// Entradas del vertex shader por location, en orden; vacío en otras etapas.
+    pub vertex_inputs: Vec<(u32, VertexFormat)>,
+    /// Bindings de cada set, indexado por set y ordenados por binding.
+    pub bind_group_layouts: Vec<BindGroupLayout>,
+    /// Bytes de push constants que lee el shader.
+    pub push_constant_size: u32,
+}
+
+impl ShaderReflection {
+    /// Interfaz del SPIR-V `spirv`.
+    pub fn from_spirv(spirv: &[u8]) -> Result<Self, ShaderError> {
+        let options = naga::front::spv::Options {
+            adjust_coordinate_space: false,
+            ..Default::default()
+        };
+        let module = naga::front::spv::parse_u8_slice(spirv, &options)
+            .map_err(|error| ShaderError::Parse(format!("invalid SPIR-V: {}", error)))?;
+        Self::from_module(&module)
+    }
+
+    pub(crate) fn from_module(module: &Module) -> Result<Self, ShaderError> {
+        let mut reflection = Self::default();
+        for (_, variable) in module.global_variables.iter() {
+            let inner = &module.types[variable.ty].inner;
+            match (variable.space, variable.binding) {
+                (AddressSpace::PushConstant, _) => {
+                    reflection.push_constant_size = reflection
+                        .push_constant_size
+                        .max(inner.size(module.to_ctx()));
+                }
+                (space, Some(binding)) => {
+                    let ty = binding_type(module, space, inner).ok_or_else(|| {
+                        ShaderError::Unsupported(format!(
+                            "binding {} of set {} ({:?} {})",
+                            binding.binding,
+                            binding.group,
+                            space,
+                            type_name(module, variable.ty)
+                        ))
+                    })?;
+                    let binding = (
+                        binding.group,
+                        BindingLayout {
+                            binding: binding.binding,
+                            ty,
+                        },
+                    );
+                    reflection
+                        .add_binding(binding)
+                        .map_err(ShaderError::Unsupported)?;
+                }
+                _ => {}
+            }
+        }
+
+        for entry_point in &module.entry_points {
+            if entry_point.stage != naga::ShaderStage::Vertex {
+                continue;
+            }
+            for argument in &entry_point.function.arguments {
+                let ty = &module.types[argument.ty];
+                let members = match (&argument.binding, &ty.inner) {
+                    (Some(binding), _) => vec![(binding, argument.ty)],
+                    // Las entradas de WGSL pueden ir en un struct.
+                    (None, TypeInner::Struct { members, .. }) => members
+                        .iter()
+                        .filter_map(|member| Some((member.binding.as_ref()?, member.ty)))
+                        .collect(),
+                    (None, _) => Vec::new(),
+                };
+                for (binding, ty) in members {
+                    let Binding::Location { location, .. } = *binding else {
+                        continue;
+                    };
+                    let format = vertex_format(&module.types[ty].inner).ok_or_else(|| {
+                        ShaderError::Unsupported(format!(
+                            "vertex input at location {} of type {}",
+                            location,
+                            type_name(module, ty)
+                        ))
+                    })?;
+                    reflection.vertex_inputs.push((location, format));
+                }
+            }
+        }
+        reflection
+            .vertex_inputs
+            .sort_unstable_by_key(|&(location, _)| location);
+        Ok(reflection)
+    }
+
+    /// Layout intercalado con todas las entradas por vértice, en orden de location.
+    pub fn vertex_layout(&self) -> VertexLayout {
+        let mut offset = 0;
+        let attributes = self
+            .vertex_inputs
+            .iter()
+            .map(|&(location, format)| {
+                let attribute = VertexAttribute {
+                    location,
+                    format,
+                    offset,
+                };
+                offset += format.size();
+                attribute
+            })
+            .collect();
+        VertexLayout {
+            stride: offset,
+            step_mode: VertexStepMode::Vertex,
+            attributes,
+        }
+    }
+
+    /// Añade un binding; si ya estaba, tiene que ser del mismo tipo.
+    fn add_binding(&mut self, (set, layout): (u32, BindingLayout)) -> Result<(), String> {
+        let set = set as usize;
+        if self.bind_group_layouts.len() <= set {
+            self.bind_group_layouts
+                .resize_with(set + 1, BindGroupLayout::default);
+        }
+        let bindings = &mut self.bind_group_layouts[set].bindings;
+        match bindings.iter().find(|b| b.binding == layout.binding) {
+            Some(existing) if existing.ty != layout.ty => Err(format!(
+                "binding {} of set {} is {:?} and {:?}",
+                layout.binding, set, existing.ty, layout.ty
+            )),
+            Some(_) => Ok(()),
+            None => {
+                bindings.push(layout);
+                bindings.sort_unstable_by_key(|b| b.binding);
+                Ok(())
+            }
+        }
+    }
+
+    /// Interfaz de un pipeline con `self` como vertex shader y `fragment`.
+    fn merge(mut self, fragment: Self) -> Result<Self, String> {
+        for (set, layout) in fragment.bind_group_layouts.into_iter().enumerate() {
+            for binding in layout.bindings {
+                self.add_binding((set as u32, binding))?;
+            }
+        }
+        self.push_constant_size = self.push_constant_size.max(fragment.push_constant_size);
+        Ok(self)
+    }
+}
+
+/// Completa `desc` con la interfaz de sus shaders y comprueba que lo declarado la
+/// cubre (ver `PipelineBuilder::reflect`).
+pub(crate) fn reflect_pipeline(desc: &mut PipelineDesc) -> Result<(), BackendError> {
+    let invalid =
+        |reason: String| BackendError::InvalidPipeline(format!("'{}': {}", desc.label, reason));
+    let vertex = ShaderReflection::from_spirv(&desc.vertex_shader)
+        .map_err(|error| invalid(format!("vertex shader: {}", error)))?;
+    let fragment = ShaderReflection::from_spirv(&desc.fragment_shader)
+        .map_err(|error| invalid(format!("fragment shader: {}", error)))?;
+    let reflection = vertex.merge(fragment).map_err(invalid)?;
+
+    if desc.vertex_layouts.is_empty() && !reflection.vertex_inputs.is_empty() {
+        desc.vertex_layouts.push(reflection.vertex_layout());
+    }
+    for &(location, format) in &reflection.vertex_inputs {
+        let declared = desc
+            .vertex_layouts
+            .iter()
+            .flat_map(|layout| &layout.attributes)
+            .find(|attribute| attribute.location == location);
+        match declared {
+            Some(attribute) if attribute.format == format => {}
+            Some(attribute) => {
+                return Err(invalid(format!(
+                    "location {} is {:?} in the shader and {:?} in the vertex layout",
+                    location, format, attribute.format
+                )));
+            }
+            None => {
+                return Err(invalid(format!(
+                    "no vertex layout provides location {}",
+                    location
+                )));
+            }
+        }
+    }
+
+    if desc.bind_group_layouts.is_empty() {
+        desc.bind_group_layouts = reflection.bind_group_layouts;
+    } else {
+        for (set, layout) in reflection.bind_group_layouts.iter().enumerate() {
+            for binding in &layout.bindings {
+                let declared = desc.bind_group_layouts.get(set).and_then(|declared| {
+                    declared
+                        .bindings
+                        .iter()
+                        .find(|declared| declared.binding == binding.binding)
+                });
+                if declared != Some(binding) {
+                    return Err(invalid(format!(
+                        "binding {} of set {} is {:?} in the shaders and {:?} in the layout",
+                        binding.binding,
+                        set,
+                        binding.ty,
+                        declared.map(|declared| declared.ty)
+                    )));
+                }
+            }
+        }
+    }
+
+    if desc.push_constant_size == 0 {
+        desc.push_constant_size = reflection.push_constant_size;
+    } else if desc.push_constant_size < reflection.push_constant_size {
+        return Err(invalid(format!(
+            "shaders read {} bytes of push constants but the pipeline has {}",
+            reflection.push_constant_size, desc.push_constant_size
+        )));
+    }
+    Ok(())
+}
+
+fn binding_type(module: &Module, space: AddressSpace, inner: &TypeInner) -> Option<BindingType> {
+    let is_texture = |inner: &TypeInner| {
+        matches!(
+            inner,
+            TypeInner::Image {
+                dim: ImageDimension::D2,
+                arrayed: false,
+                class: ImageClass::Sampled { multi: false, .. },
+            }
+        )
+    };
+    match (space, inner) {
+        (AddressSpace::Uniform, _) => Some(BindingType::UniformBuffer),
+        (AddressSpace::Handle, TypeInner::Sampler { comparison: false }) => {
+            Some(BindingType::Sampler)
+        }
+        (AddressSpace::Handle, inner) if is_texture(inner) => Some(BindingType::Texture),
+        (
+            AddressSpace::Handle,
+            &TypeInner::BindingArray {
+                base,
+                size: ArraySize::Constant(count),
+            },
+        ) if is_texture(&module.types[base].inner) => {
+            Some(BindingType::TextureArray { count: count.get() })
+        }
+        _ => None,
+    }
+}
+
+fn vertex_format(inner: &TypeInner) -> Option<VertexFormat> {
+    let float = |scalar: naga::Scalar| scalar.kind == ScalarKind::Float && scalar.width == 4;
+    match *inner {
+        TypeInner::Scalar(scalar) if float(scalar) => Some(VertexFormat::Float32),
+        TypeInner::Vector { size, scalar } if float(scalar) => Some(match size {
+            VectorSize::Bi => VertexFormat::Float32x2,
+            VectorSize::Tri => VertexFormat::Float32x3,
+            VectorSize::Quad => VertexFormat::Float32x4,
+        }),
+        _ => None,
+    }
+}
+
+fn type_name(module: &Module, ty: naga::Handle<Type>) -> String {
+    let ty = &module.types[ty];
+    ty.name.clone().unwrap_or_else(|| format!("{:?}", ty.inner))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shader::{ShaderCompiler, ShaderLanguage, ShaderStage};
+
+    #[test]
+    fn test_reflection_of_bindings_inputs_and_push_constants() {
+        let vertex = ShaderCompiler::new()
+            .compile_source(
+                "test",
+                "#version 450
+            layout(location = 0) in vec3 position;
+            layout(location = 2) in vec2 uv;
+            layout(location = 0) out vec2 outUv;
+            layout(set = 0, binding = 0) uniform Camera { mat4 viewProjection; } camera;
+            void main() {
+                outUv = uv;
+                gl_Position = camera.viewProjection * vec4(position, 1.0);
+            }",
+                ShaderLanguage::Glsl,
+                ShaderStage::Vertex,
+            )
+            .unwrap()
+            .spirv;
+        // GLSL no admite arrays de texturas en naga; WGSL sí.
+        let fragment = ShaderCompiler::new()
+            .compile_source(
+                "test",
+                "struct Push { tint: vec4<f32>, index: u32 }
+                var<push_constant> push: Push;
+                @group(1) @binding(0) var linear_sampler: sampler;
+                @group(1) @binding(1) var textures: binding_array<texture_2d<f32>, 8>;
+                @fragment
+                fn main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+                    return push.tint * textureSample(textures[push.index], linear_sampler, uv);
+                }",
+                ShaderLanguage::Wgsl,
+                ShaderStage::Fragment,
+            )
+            .unwrap()
+            .spirv;
+        let reflection = ShaderReflection::from_spirv(&vertex).unwrap();
+        assert_eq!(
+            reflection.vertex_inputs,
+            [(0, VertexFormat::Float32x3), (2, VertexFormat::Float32x2)]
+        );
+        assert_eq!(reflection.vertex_layout().stride, 20);
+
+        let mut desc = PipelineDesc::builder("reflected")
+            .vertex_shader(&vertex)
+            .fragment_shader(&fragment)
+            .reflect()
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(desc.vertex_layouts, [reflection.vertex_layout()]);
+        // El struct se redondea a su alineación de 16.
+        assert_eq!(desc.push_constant_size, 32);
+        let binding = |binding, ty| BindingLayout { binding, ty };
+        assert_eq!(
+            desc.bind_group_layouts,
+            [
+                BindGroupLayout {
+                    bindings: vec![binding(0, BindingType::UniformBuffer)],
+                },
+                BindGroupLayout {
+                    bindings: vec![
+                        binding(0, BindingType::Sampler),
+                        binding(1, BindingType::TextureArray { count: 8 }),
+                    ],
+                },
+            ]
+        );
+
+        // Lo ya declarado se comprueba en vez de sustituirse.
+        assert!(reflect_pipeline(&mut desc).is_ok());
+        desc.bind_group_layouts[1].bindings[1].ty = BindingType::TextureArray { count: 4 };
+        assert!(matches!(
+            reflect_pipeline(&mut desc),
+            Err(BackendError::InvalidPipeline(_))
+        ));
+        desc.bind_group_layouts.truncate(1);
+        assert!(reflect_pipeline(&mut desc).is_err());
+        let mut desc = PipelineDesc::builder("reflected")
+            .vertex_shader(&vertex)
+            .fragment_shader(&fragment)
+            .vertex_layout(VertexLayout {
+                stride: 12,
+                step_mode: VertexStepMode::Vertex,
+                attributes: vec![VertexAttribute {
+                    location: 0,
+                    format: VertexFormat::Float32x3,
+                    offset: 0,
+                }],
+            })
+            .build()
+            .unwrap();
+        let error = reflect_pipeline(&mut desc).unwrap_err();
+        assert_eq!(
+            error,
+            BackendError::InvalidPipeline(
+                "'reflected': no vertex layout provides location 2".into()
+            )
+        );
+    }
+
+    #[test]
+    fn test_builtin_pipelines_match_their_shaders() {
+        for desc in [
+            PipelineDesc::triangle(),
+            PipelineDesc::mesh(),
+            PipelineDesc::pbr(),
+        ] {
+            let mut reflected = desc.clone();
+            reflect_pipeline(&mut reflected).unwrap();
+            assert_eq!(reflected, desc);
+        }
+    }
+}
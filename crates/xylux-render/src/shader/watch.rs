@@ -0,0 +1,125 @@
+//! Vigilancia de archivos por sondeo.
+//!
+//! `FileWatcher` guarda la fecha de modificación y el tamaño de cada archivo y
+//! los compara en cada `poll`, sin hilos ni APIs del sistema. Un archivo que
+//! desaparece no cuenta como cambio hasta que vuelve a existir, para no
+//! recompilar a medias mientras un editor lo guarda.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Fecha de modificación y tamaño de un archivo.
+type FileStamp = Option<(SystemTime, u64)>;
+
+fn stamp(path: &Path) -> FileStamp {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Vigila grupos de archivos, cada uno identificado por una clave `K`.
+#[derive(Clone, Debug)]
+pub struct FileWatcher<K> {
+    watched: BTreeMap<K, Vec<(PathBuf, FileStamp)>>,
+}
+
+impl<K> Default for FileWatcher<K> {
+    fn default() -> Self {
+        Self {
+            watched: BTreeMap::new(),
+        }
+    }
+}
+
+impl<K: Copy + Ord> FileWatcher<K> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Vigila `paths` para `key`, sustituyendo los archivos que ya tuviera. Los que
+    /// siguen en la lista conservan su estado, así que un cambio aún no visto por
+    /// `poll` no se pierde.
+    pub fn watch(&mut self, key: K, paths: impl IntoIterator<Item = PathBuf>) {
+        let previous = self.watched.remove(&key).unwrap_or_default();
+        let mut files: Vec<(PathBuf, FileStamp)> = Vec::new();
+        for path in paths {
+            if files.iter().any(|(watched, _)| *watched == path) {
+                continue;
+            }
+            let stamp = previous
+                .iter()
+                .find(|(watched, _)| *watched == path)
+                .map_or_else(|| stamp(&path), |&(_, stamp)| stamp);
+            files.push((path, stamp));
+        }
+        self.watched.insert(key, files);
+    }
+
+    pub fn unwatch(&mut self, key: K) {
+        self.watched.remove(&key);
+    }
+
+    /// Archivos vigilados para `key`.
+    pub fn paths(&self, key: K) -> impl Iterator<Item = &Path> {
+        self.watched
+            .get(&key)
+            .into_iter()
+            .flatten()
+            .map(|(path, _)| path.as_path())
+    }
+
+    /// Claves con algún archivo cambiado desde el `poll` anterior, en orden.
+    pub fn poll(&mut self) -> Vec<K> {
+        let mut changed = Vec::new();
+        for (&key, files) in &mut self.watched {
+            let mut key_changed = false;
+            for (path, previous) in files.iter_mut() {
+                let current = stamp(path);
+                if current != *previous {
+                    key_changed |= current.is_some();
+                    *previous = current;
+                }
+            }
+            if key_changed {
+                changed.push(key);
+            }
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_poll_reports_changed_keys_once() {
+        let dir = std::env::temp_dir().join(format!("xylux_watch_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let [shared, only_b] = ["shared.wgsl", "b.wgsl"].map(|name| dir.join(name));
+        std::fs::write(&shared, "a").unwrap();
+        std::fs::write(&only_b, "b").unwrap();
+
+        let mut watcher = FileWatcher::new();
+        watcher.watch(1, [shared.clone()]);
+        watcher.watch(2, [shared.clone(), only_b.clone(), shared.clone()]);
+        assert_eq!(watcher.paths(2).count(), 2);
+        assert!(watcher.poll().is_empty());
+
+        // El tamaño cambia aunque la fecha no llegue a hacerlo.
+        std::fs::write(&shared, "aa").unwrap();
+        assert_eq!(watcher.poll(), [1, 2]);
+        assert!(watcher.poll().is_empty());
+
+        // Borrado y vuelto a crear, como al guardar desde algunos editores.
+        std::fs::remove_file(&only_b).unwrap();
+        assert!(watcher.poll().is_empty());
+        std::fs::write(&only_b, "bbb").unwrap();
+        assert_eq!(watcher.poll(), [2]);
+
+        watcher.unwatch(2);
+        std::fs::write(&only_b, "b").unwrap();
+        assert!(watcher.poll().is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,301 @@
+//! Conversión de texels entre los `TextureFormat` de color y valores lineales.
+
+use super::{ColorSpace, TextureError};
+use crate::backend::TextureFormat;
+use glam::Vec4;
+
+/// Decodifica un canal sRGB en `[0, 1]` a lineal.
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Codifica un canal lineal en `[0, 1]` como sRGB.
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Convierte a half float (IEEE 754 binary16) redondeando al par más cercano.
+pub fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xFF) as i32;
+    let mantissa = bits & 0x007F_FFFF;
+
+    if exponent == 0xFF {
+        // Infinito o NaN; el NaN conserva un bit de mantisa para no volverse infinito.
+        let nan = if mantissa != 0 { 0x0200 } else { 0 };
+        return sign | 0x7C00 | nan;
+    }
+    let exponent = exponent - 127 + 15;
+    if exponent >= 0x1F {
+        return sign | 0x7C00;
+    }
+    if exponent <= 0 {
+        // Subnormal en half (o cero): se desplaza la mantisa con el 1 implícito.
+        if exponent < -10 {
+            return sign;
+        }
+        let mantissa = mantissa | 0x0080_0000;
+        let shift = (14 - exponent) as u32;
+        let half = mantissa >> shift;
+        let remainder = mantissa & ((1 << shift) - 1);
+        let halfway = 1 << (shift - 1);
+        let round = remainder > halfway || (remainder == halfway && half & 1 == 1);
+        return sign | (half + round as u32) as u16;
+    }
+    let half = ((exponent as u32) << 10) | (mantissa >> 13);
+    let remainder = mantissa & 0x1FFF;
+    let round = remainder > 0x1000 || (remainder == 0x1000 && half & 1 == 1);
+    // Si el redondeo desborda la mantisa, sube el exponente (hasta infinito).
+    sign | (half + round as u32) as u16
+}
+
+/// Convierte un half float a `f32` sin pérdida.
+pub fn f16_to_f32(value: u16) -> f32 {
+    let sign = ((value & 0x8000) as u32) << 16;
+    let exponent = ((value >> 10) & 0x1F) as u32;
+    let mantissa = (value & 0x03FF) as u32;
+    let bits = match (exponent, mantissa) {
+        (0, 0) => sign,
+        (0, _) => {
+            // Subnormal: se normaliza para `f32`.
+            let shift = mantissa.leading_zeros() - 21;
+            let mantissa = (mantissa << shift) & 0x03FF;
+            sign | ((127 - 15 + 1 - shift) << 23) | (mantissa << 13)
+        }
+        (0x1F, _) => sign | 0x7F80_0000 | (mantissa << 13),
+        _ => sign | ((exponent + 127 - 15) << 23) | (mantissa << 13),
+    };
+    f32::from_bits(bits)
+}
+
+/// Variante de 8 bits de `format` en `color_space`; el resto de formatos no cambia.
+pub fn with_color_space(format: TextureFormat, color_space: ColorSpace) -> TextureFormat {
+    match (format, color_space) {
+        (TextureFormat::Rgba8Unorm | TextureFormat::Rgba8Srgb, ColorSpace::Srgb) => {
+            TextureFormat::Rgba8Srgb
+        }
+        (TextureFormat::Rgba8Unorm | TextureFormat::Rgba8Srgb, ColorSpace::Linear) => {
+            TextureFormat::Rgba8Unorm
+        }
+        (TextureFormat::Bgra8Unorm | TextureFormat::Bgra8Srgb, ColorSpace::Srgb) => {
+            TextureFormat::Bgra8Srgb
+        }
+        (TextureFormat::Bgra8Unorm | TextureFormat::Bgra8Srgb, ColorSpace::Linear) => {
+            TextureFormat::Bgra8Unorm
+        }
+        (format, _) => format,
+    }
+}
+
+fn unsupported(format: TextureFormat) -> TextureError {
+    TextureError::Unsupported(format!("{:?} texel conversion", format))
+}
+
+/// Decodifica texels de `format` a RGBA lineal.
+pub fn decode(format: TextureFormat, data: &[u8]) -> Result<Vec<Vec4>, TextureError> {
+    let bytes_per_pixel = format.bytes_per_pixel() as usize;
+    if format.is_depth() {
+        return Err(unsupported(format));
+    }
+    if !data.len().is_multiple_of(bytes_per_pixel) {
+        return Err(TextureError::Parse(format!(
+            "{} bytes is not a whole number of {:?} texels",
+            data.len(),
+            format
+        )));
+    }
+    let unorm = |value: u8| value as f32 / 255.0;
+    let srgb = |value: u8| srgb_to_linear(unorm(value));
+    let texels = data
+        .chunks_exact(bytes_per_pixel)
+        .map(|texel| match format {
+            TextureFormat::Rgba8Unorm => Vec4::new(
+                unorm(texel[0]),
+                unorm(texel[1]),
+                unorm(texel[2]),
+                unorm(texel[3]),
+            ),
+            TextureFormat::Rgba8Srgb => Vec4::new(
+                srgb(texel[0]),
+                srgb(texel[1]),
+                srgb(texel[2]),
+                unorm(texel[3]),
+            ),
+            TextureFormat::Bgra8Unorm => Vec4::new(
+                unorm(texel[2]),
+                unorm(texel[1]),
+                unorm(texel[0]),
+                unorm(texel[3]),
+            ),
+            TextureFormat::Bgra8Srgb => Vec4::new(
+                srgb(texel[2]),
+                srgb(texel[1]),
+                srgb(texel[0]),
+                unorm(texel[3]),
+            ),
+            TextureFormat::Rgba16Float => Vec4::from_array(std::array::from_fn(|i| {
+                f16_to_f32(u16::from_le_bytes([texel[2 * i], texel[2 * i + 1]]))
+            })),
+            TextureFormat::Rgba32Float => Vec4::from_array(std::array::from_fn(|i| {
+                f32::from_le_bytes([
+                    texel[4 * i],
+                    texel[4 * i + 1],
+                    texel[4 * i + 2],
+                    texel[4 * i + 3],
+                ])
+            })),
+            TextureFormat::Depth32Float | TextureFormat::Depth24Stencil8 => unreachable!(),
+        });
+    Ok(texels.collect())
+}
+
+/// Codifica texels RGBA lineales en `format`. Los formatos de 8 bits recortan a
+/// `[0, 1]`.
+pub fn encode(format: TextureFormat, texels: &[Vec4]) -> Result<Vec<u8>, TextureError> {
+    if format.is_depth() {
+        return Err(unsupported(format));
+    }
+    let unorm = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    let srgb = |value: f32| unorm(linear_to_srgb(value.clamp(0.0, 1.0)));
+    let mut data = Vec::with_capacity(texels.len() * format.bytes_per_pixel() as usize);
+    for texel in texels {
+        match format {
+            TextureFormat::Rgba8Unorm => data.extend(texel.to_array().map(unorm)),
+            TextureFormat::Rgba8Srgb => {
+                data.extend([srgb(texel.x), srgb(texel.y), srgb(texel.z), unorm(texel.w)])
+            }
+            TextureFormat::Bgra8Unorm => data.extend([
+                unorm(texel.z),
+                unorm(texel.y),
+                unorm(texel.x),
+                unorm(texel.w),
+            ]),
+            TextureFormat::Bgra8Srgb => {
+                data.extend([srgb(texel.z), srgb(texel.y), srgb(texel.x), unorm(texel.w)])
+            }
+            TextureFormat::Rgba16Float => data.extend(
+                texel
+                    .to_array()
+                    .iter()
+                    .flat_map(|&value| f32_to_f16(value).to_le_bytes()),
+            ),
+            TextureFormat::Rgba32Float => data.extend(
+                texel
+                    .to_array()
+                    .iter()
+                    .flat_map(|value| value.to_le_bytes()),
+            ),
+            TextureFormat::Depth32Float | TextureFormat::Depth24Stencil8 => unreachable!(),
+        }
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_srgb_round_trips_every_byte() {
+        assert_eq!(srgb_to_linear(0.0), 0.0);
+        assert!((srgb_to_linear(1.0) - 1.0).abs() < 1e-6);
+        assert!((srgb_to_linear(0.5) - 0.214_041).abs() < 1e-5);
+        assert!((linear_to_srgb(0.5) - 0.735_357).abs() < 1e-5);
+        let bytes: Vec<u8> = (0..=255).flat_map(|v| [v, v, v, v]).collect();
+        let texels = decode(TextureFormat::Rgba8Srgb, &bytes).unwrap();
+        // El alfa es lineal aunque el color sea sRGB.
+        assert!((texels[128].w - 128.0 / 255.0).abs() < 1e-6);
+        assert!(texels.windows(2).all(|pair| pair[0].x < pair[1].x));
+        assert_eq!(encode(TextureFormat::Rgba8Srgb, &texels).unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_half_float_conversion() {
+        assert_eq!(f32_to_f16(0.0), 0x0000);
+        assert_eq!(f32_to_f16(-0.0), 0x8000);
+        assert_eq!(f32_to_f16(1.0), 0x3C00);
+        assert_eq!(f32_to_f16(-2.0), 0xC000);
+        assert_eq!(f32_to_f16(65504.0), 0x7BFF);
+        // Fuera de rango satura a infinito; el NaN sigue siendo NaN.
+        assert_eq!(f32_to_f16(65520.0), 0x7C00);
+        assert_eq!(f32_to_f16(1e10), 0x7C00);
+        assert_eq!(f32_to_f16(f32::NEG_INFINITY), 0xFC00);
+        assert!(f16_to_f32(f32_to_f16(f32::NAN)).is_nan());
+        // Subnormales: el menor half es 2^-24 y la mitad redondea al par (cero).
+        assert_eq!(f32_to_f16(2f32.powi(-24)), 0x0001);
+        assert_eq!(f32_to_f16(2f32.powi(-25)), 0x0000);
+        assert_eq!(f32_to_f16(3.0 * 2f32.powi(-25)), 0x0002);
+        assert_eq!(f32_to_f16(2f32.powi(-14)), 0x0400);
+        // Empates en la mantisa: 1 + 2^-11 queda en 1.0 y 1 + 3 * 2^-11 sube.
+        assert_eq!(f32_to_f16(1.0 + 2f32.powi(-11)), 0x3C00);
+        assert_eq!(f32_to_f16(1.0 + 3.0 * 2f32.powi(-11)), 0x3C02);
+
+        for bits in 0..=u16::MAX {
+            let value = f16_to_f32(bits);
+            if value.is_nan() {
+                assert_eq!(bits & 0x7C00, 0x7C00);
+            } else {
+                assert_eq!(f32_to_f16(value), bits, "{:#06x} -> {}", bits, value);
+            }
+        }
+    }
+
+    #[test]
+    fn test_encode_decode_every_color_format() {
+        let texel = Vec4::new(1.0, 0.5, 0.0, 0.25);
+        let data = encode(TextureFormat::Bgra8Unorm, &[texel]).unwrap();
+        assert_eq!(data, [0, 128, 255, 64]);
+        assert_eq!(
+            encode(TextureFormat::Rgba8Unorm, &[texel]).unwrap(),
+            [255, 128, 0, 64]
+        );
+        assert_eq!(
+            encode(TextureFormat::Bgra8Srgb, &[texel]).unwrap(),
+            [0, 188, 255, 64]
+        );
+        // Los formatos de 8 bits recortan; los float conservan HDR y negativos.
+        let hdr = Vec4::new(4.5, -1.0, 0.125, 1.0);
+        assert_eq!(
+            encode(TextureFormat::Rgba8Unorm, &[hdr]).unwrap(),
+            [255, 0, 32, 255]
+        );
+        for format in [TextureFormat::Rgba16Float, TextureFormat::Rgba32Float] {
+            let data = encode(format, &[hdr, texel]).unwrap();
+            assert_eq!(data.len(), 2 * format.bytes_per_pixel() as usize);
+            assert_eq!(decode(format, &data).unwrap(), [hdr, texel]);
+        }
+        for format in [
+            TextureFormat::Rgba8Unorm,
+            TextureFormat::Rgba8Srgb,
+            TextureFormat::Bgra8Unorm,
+            TextureFormat::Bgra8Srgb,
+        ] {
+            let bytes = [10, 20, 30, 40, 250, 128, 0, 255];
+            let texels = decode(format, &bytes).unwrap();
+            assert_eq!(encode(format, &texels).unwrap(), bytes, "{:?}", format);
+        }
+        let swapped = decode(TextureFormat::Bgra8Unorm, &[0, 0, 255, 255]).unwrap();
+        assert_eq!(swapped, [Vec4::new(1.0, 0.0, 0.0, 1.0)]);
+
+        assert!(decode(TextureFormat::Rgba16Float, &[0; 7]).is_err());
+        assert!(decode(TextureFormat::Depth32Float, &[0; 4]).is_err());
+        assert!(encode(TextureFormat::Depth24Stencil8, &[texel]).is_err());
+        assert_eq!(
+            with_color_space(TextureFormat::Bgra8Unorm, ColorSpace::Srgb),
+            TextureFormat::Bgra8Srgb
+        );
+        assert_eq!(
+            with_color_space(TextureFormat::Rgba16Float, ColorSpace::Srgb),
+            TextureFormat::Rgba16Float
+        );
+    }
+}
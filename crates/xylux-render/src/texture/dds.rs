@@ -0,0 +1,272 @@
+//! Carga de archivos DDS con texturas 2D sin comprimir.
+//!
+//! Se admiten los formatos RGBA/BGRA de 32 bits por máscaras, los float de
+//! D3D9 (`A16B16G16R16F`, `A32B32G32R32F`) y la cabecera `DX10` con sus
+//! equivalentes DXGI. Los formatos por bloques (DXT/BC), cubemaps, volúmenes y
+//! arrays no se admiten. Los DDS antiguos no dicen si el color es sRGB: se cargan
+//! como `Unorm` y se pueden reinterpretar con `Texture::with_color_space`.
+
+use super::{Reader, Texture, TextureError, mip_byte_size, mip_count};
+use crate::backend::TextureFormat;
+
+const HEADER_SIZE: usize = 4 + 124;
+const DX10_HEADER_SIZE: usize = 20;
+
+const DDSD_MIPMAPCOUNT: u32 = 0x2_0000;
+const DDPF_ALPHAPIXELS: u32 = 0x1;
+const DDPF_FOURCC: u32 = 0x4;
+const DDPF_RGB: u32 = 0x40;
+const DDSCAPS2_CUBEMAP: u32 = 0x200;
+const DDSCAPS2_VOLUME: u32 = 0x20_0000;
+const D3DFMT_A16B16G16R16F: u32 = 113;
+const D3DFMT_A32B32G32R32F: u32 = 116;
+const DXGI_TEXTURE2D: u32 = 3;
+const DXGI_MISC_TEXTURECUBE: u32 = 0x4;
+
+/// `DXGI_FORMAT` de cada `TextureFormat` que se puede cargar.
+const DXGI_FORMATS: [(u32, TextureFormat); 6] = [
+    (2, TextureFormat::Rgba32Float),
+    (10, TextureFormat::Rgba16Float),
+    (28, TextureFormat::Rgba8Unorm),
+    (29, TextureFormat::Rgba8Srgb),
+    (87, TextureFormat::Bgra8Unorm),
+    (91, TextureFormat::Bgra8Srgb),
+];
+
+fn four_cc(code: &[u8; 4]) -> u32 {
+    u32::from_le_bytes(*code)
+}
+
+/// Carga un DDS desde sus bytes.
+pub fn parse_dds(bytes: &[u8]) -> Result<Texture, TextureError> {
+    if !bytes.starts_with(b"DDS ") {
+        return Err(TextureError::Parse("not a DDS file".into()));
+    }
+    let reader = Reader { bytes, what: "DDS" };
+    if reader.u32(4)? != 124 || reader.u32(76)? != 32 {
+        return Err(TextureError::Parse("DDS header has the wrong size".into()));
+    }
+    let flags = reader.u32(8)?;
+    let height = reader.u32(12)?;
+    let width = reader.u32(16)?;
+    let levels = if flags & DDSD_MIPMAPCOUNT != 0 {
+        reader.u32(28)?.clamp(1, mip_count(width, height))
+    } else {
+        1
+    };
+    let pixel_flags = reader.u32(80)?;
+    let code = reader.u32(84)?;
+    let bit_count = reader.u32(88)?;
+    let masks = [
+        reader.u32(92)?,
+        reader.u32(96)?,
+        reader.u32(100)?,
+        reader.u32(104)?,
+    ];
+    let caps2 = reader.u32(112)?;
+    if caps2 & (DDSCAPS2_CUBEMAP | DDSCAPS2_VOLUME) != 0 {
+        return Err(TextureError::Unsupported("DDS cubemaps and volumes".into()));
+    }
+
+    let mut data_offset = HEADER_SIZE;
+    // Sin canal alfa en las máscaras, se rellena opaco.
+    let mut opaque = false;
+    let format = if pixel_flags & DDPF_FOURCC != 0 {
+        match code {
+            code if code == four_cc(b"DX10") => {
+                data_offset += DX10_HEADER_SIZE;
+                let dxgi_format = reader.u32(128)?;
+                let dimension = reader.u32(132)?;
+                let misc = reader.u32(136)?;
+                let array_size = reader.u32(140)?;
+                if dimension != DXGI_TEXTURE2D
+                    || misc & DXGI_MISC_TEXTURECUBE != 0
+                    || array_size > 1
+                {
+                    return Err(TextureError::Unsupported(
+                        "DDS texture that is not a single 2D texture".into(),
+                    ));
+                }
+                DXGI_FORMATS
+                    .iter()
+                    .find(|(candidate, _)| *candidate == dxgi_format)
+                    .map(|&(_, format)| format)
+                    .ok_or_else(|| {
+                        TextureError::Unsupported(format!("DDS DXGI format {}", dxgi_format))
+                    })?
+            }
+            D3DFMT_A16B16G16R16F => TextureFormat::Rgba16Float,
+            D3DFMT_A32B32G32R32F => TextureFormat::Rgba32Float,
+            code => {
+                let name = String::from_utf8_lossy(&code.to_le_bytes()).into_owned();
+                return Err(TextureError::Unsupported(format!("DDS format {}", name)));
+            }
+        }
+    } else if pixel_flags & DDPF_RGB != 0 && bit_count == 32 {
+        opaque = pixel_flags & DDPF_ALPHAPIXELS == 0;
+        let alpha = if opaque { 0 } else { 0xFF00_0000 };
+        match masks {
+            [0xFF, 0xFF00, 0xFF_0000, a] if a == alpha => TextureFormat::Rgba8Unorm,
+            [0xFF_0000, 0xFF00, 0xFF, a] if a == alpha => TextureFormat::Bgra8Unorm,
+            _ => {
+                return Err(TextureError::Unsupported(format!(
+                    "DDS channel masks {:08x?}",
+                    masks
+                )));
+            }
+        }
+    } else {
+        return Err(TextureError::Unsupported(format!(
+            "DDS pixel format with flags {:#x} and {} bits",
+            pixel_flags, bit_count
+        )));
+    };
+
+    let mut offset = data_offset as u64;
+    let mut mips = Vec::with_capacity(levels as usize);
+    for level in 0..levels {
+        let len = mip_byte_size(width, height, format, level)? as u64;
+        let mut data = reader.slice(offset, len)?.to_vec();
+        if opaque {
+            data.chunks_exact_mut(4)
+                .for_each(|texel| texel[3] = u8::MAX);
+        }
+        mips.push(data);
+        offset += len;
+    }
+    Texture::with_mips(width, height, format, mips)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// DDS con la cabecera básica; `dxgi` añade la cabecera `DX10`.
+    fn dds(
+        width: u32,
+        height: u32,
+        pixel_format: [u32; 8],
+        dxgi: Option<u32>,
+        mips: &[&[u8]],
+    ) -> Vec<u8> {
+        let mut header = [0u32; 31];
+        header[0] = 124;
+        header[1] = 0x1007 | if mips.len() > 1 { DDSD_MIPMAPCOUNT } else { 0 };
+        header[2] = height;
+        header[3] = width;
+        header[6] = mips.len() as u32;
+        header[18..26].copy_from_slice(&pixel_format);
+        header[26] = 0x1000;
+        let mut bytes = b"DDS ".to_vec();
+        header
+            .iter()
+            .for_each(|value| bytes.extend(value.to_le_bytes()));
+        if let Some(format) = dxgi {
+            for value in [format, DXGI_TEXTURE2D, 0, 1, 0] {
+                bytes.extend(value.to_le_bytes());
+            }
+        }
+        mips.iter().for_each(|mip| bytes.extend(*mip));
+        bytes
+    }
+
+    fn four_cc_format(code: u32) -> [u32; 8] {
+        [32, DDPF_FOURCC, code, 0, 0, 0, 0, 0]
+    }
+
+    #[test]
+    fn test_dds_masks_four_cc_and_dx10() {
+        let bgra = [
+            32,
+            DDPF_RGB | DDPF_ALPHAPIXELS,
+            0,
+            32,
+            0xFF_0000,
+            0xFF00,
+            0xFF,
+            0xFF00_0000,
+        ];
+        let bytes = dds(2, 2, bgra, None, &[&[1; 16], &[2, 3, 4, 5]]);
+        let texture = parse_dds(&bytes).unwrap();
+        assert_eq!(texture.format, TextureFormat::Bgra8Unorm);
+        assert_eq!(texture.mips, [vec![1; 16], vec![2, 3, 4, 5]]);
+
+        // RGB sin alfa: se rellena opaco.
+        let rgbx = [32, DDPF_RGB, 0, 32, 0xFF, 0xFF00, 0xFF_0000, 0];
+        let texture = parse_dds(&dds(1, 1, rgbx, None, &[&[9, 8, 7, 0]])).unwrap();
+        assert_eq!(
+            (texture.format, texture.mips[0].clone()),
+            (TextureFormat::Rgba8Unorm, vec![9, 8, 7, 255])
+        );
+
+        let half = dds(
+            1,
+            1,
+            four_cc_format(D3DFMT_A16B16G16R16F),
+            None,
+            &[&[0, 0x3C, 0, 0, 0, 0xC0, 0, 0x3C]],
+        );
+        assert_eq!(
+            parse_dds(&half).unwrap().texels(0),
+            [glam::Vec4::new(1.0, 0.0, -2.0, 1.0)]
+        );
+
+        let srgb = dds(
+            1,
+            1,
+            four_cc_format(four_cc(b"DX10")),
+            Some(29),
+            &[&[1, 2, 3, 4]],
+        );
+        assert_eq!(parse_dds(&srgb).unwrap().format, TextureFormat::Rgba8Srgb);
+
+        let dxt1 = dds(4, 4, four_cc_format(four_cc(b"DXT1")), None, &[&[0; 8]]);
+        assert_eq!(
+            parse_dds(&dxt1),
+            Err(TextureError::Unsupported("DDS format DXT1".into()))
+        );
+        let bc7 = dds(
+            4,
+            4,
+            four_cc_format(four_cc(b"DX10")),
+            Some(98),
+            &[&[0; 16]],
+        );
+        assert_eq!(
+            parse_dds(&bc7),
+            Err(TextureError::Unsupported("DDS DXGI format 98".into()))
+        );
+        assert!(matches!(
+            parse_dds(&bytes[..bytes.len() - 1]),
+            Err(TextureError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_dds_rejects_malformed_headers() {
+        // Cabecera de 128 bytes con un tamaño cuyo nivel 0 no cabe en memoria.
+        let rgba = [
+            32,
+            DDPF_RGB | DDPF_ALPHAPIXELS,
+            0,
+            32,
+            0xFF,
+            0xFF00,
+            0xFF_0000,
+            0xFF00_0000,
+        ];
+        let huge = dds(u32::MAX, u32::MAX, rgba, None, &[]);
+        assert_eq!(huge.len(), HEADER_SIZE);
+        assert!(matches!(parse_dds(&huge), Err(TextureError::Parse(_))));
+        let float = four_cc_format(D3DFMT_A32B32G32R32F);
+        let huge = dds(u32::MAX, u32::MAX, float, None, &[]);
+        assert!(matches!(parse_dds(&huge), Err(TextureError::Parse(_))));
+
+        let empty = dds(0, 4, rgba, None, &[]);
+        assert!(matches!(parse_dds(&empty), Err(TextureError::Parse(_))));
+        assert!(matches!(
+            parse_dds(&huge[..100]),
+            Err(TextureError::Parse(_))
+        ));
+    }
+}
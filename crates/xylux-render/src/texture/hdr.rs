@@ -0,0 +1,237 @@
+//! Carga de imágenes Radiance HDR (`.hdr`, RGBE) como `Rgba32Float` lineal.
+
+use super::{Texture, TextureError};
+use crate::backend::TextureFormat;
+
+/// Carga un `.hdr` desde sus bytes. Solo se admite la orientación estándar
+/// (`-Y alto +X ancho`).
+pub fn parse_hdr(bytes: &[u8]) -> Result<Texture, TextureError> {
+    let parse = |message: &str| TextureError::Parse(format!("HDR: {}", message));
+
+    // Cabecera: líneas de texto hasta una vacía y después la resolución.
+    let mut lines = Vec::new();
+    let mut position = 0;
+    loop {
+        let end = bytes[position..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .ok_or_else(|| parse("header is truncated"))?;
+        let line = std::str::from_utf8(&bytes[position..position + end])
+            .map_err(|_| parse("header is not text"))?;
+        position += end + 1;
+        if line.is_empty() {
+            break;
+        }
+        lines.push(line);
+    }
+    if let Some(format) = lines.iter().find_map(|line| line.strip_prefix("FORMAT="))
+        && format != "32-bit_rle_rgbe"
+    {
+        return Err(TextureError::Unsupported(format!("HDR format {}", format)));
+    }
+    let end = bytes[position..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .ok_or_else(|| parse("resolution is missing"))?;
+    let resolution = std::str::from_utf8(&bytes[position..position + end])
+        .map_err(|_| parse("resolution is not text"))?;
+    position += end + 1;
+    let (height, width) = match resolution.split_whitespace().collect::<Vec<_>>()[..] {
+        ["-Y", height, "+X", width] => (
+            height.parse::<u32>().map_err(|_| parse("invalid height"))?,
+            width.parse::<u32>().map_err(|_| parse("invalid width"))?,
+        ),
+        _ => {
+            return Err(TextureError::Unsupported(format!(
+                "HDR orientation {}",
+                resolution
+            )));
+        }
+    };
+
+    let mut data = &bytes[position..];
+    // Antes de reservar memoria, el tamaño tiene que caber en los bytes que quedan.
+    let too_large = || parse("image is larger than its data");
+    let texels_len = (width as usize)
+        .checked_mul(height as usize)
+        .and_then(|pixels| pixels.checked_mul(16))
+        .ok_or_else(too_large)?;
+    let needed = min_scanline_len(width as usize)
+        .checked_mul(height as usize)
+        .ok_or_else(too_large)?;
+    if needed > data.len() {
+        return Err(too_large());
+    }
+    let mut texels = Vec::with_capacity(texels_len);
+    let mut scanline = vec![[0u8; 4]; width as usize];
+    for _ in 0..height {
+        data = read_scanline(data, &mut scanline)?;
+        for rgbe in &scanline {
+            for value in rgbe_to_rgb(*rgbe).into_iter().chain([1.0]) {
+                texels.extend(value.to_le_bytes());
+            }
+        }
+    }
+    Texture::new(width, height, TextureFormat::Rgba32Float, texels)
+}
+
+/// Bytes mínimos de una fila: la cabecera y un run de 2 bytes cada 127 píxeles
+/// por canal con el RLE por canales, o 4 bytes por píxel sin él.
+fn min_scanline_len(width: usize) -> usize {
+    if (8..0x8000).contains(&width) {
+        4 + 4 * 2 * width.div_ceil(127)
+    } else {
+        4 * width
+    }
+}
+
+/// Lee una fila de píxeles RGBE y devuelve el resto de los datos.
+fn read_scanline<'a>(data: &'a [u8], scanline: &mut [[u8; 4]]) -> Result<&'a [u8], TextureError> {
+    let truncated = || TextureError::Parse("HDR: pixel data is truncated".into());
+    let width = scanline.len();
+    // RLE por canales: `2 2 ancho_alto ancho_bajo` y cada canal por separado.
+    if (8..0x8000).contains(&width)
+        && data.len() >= 4
+        && data[0] == 2
+        && data[1] == 2
+        && data[2] < 128
+    {
+        if (data[2] as usize) << 8 | data[3] as usize != width {
+            return Err(TextureError::Parse("HDR: scanline width mismatch".into()));
+        }
+        let mut data = &data[4..];
+        for channel in 0..4 {
+            let mut x = 0;
+            while x < width {
+                let (&count, rest) = data.split_first().ok_or_else(truncated)?;
+                let (run, literal) = if count > 128 {
+                    ((count - 128) as usize, false)
+                } else {
+                    (count as usize, true)
+                };
+                if run == 0 || x + run > width {
+                    return Err(TextureError::Parse("HDR: invalid run length".into()));
+                }
+                if literal {
+                    let values = rest.get(..run).ok_or_else(truncated)?;
+                    for (pixel, &value) in scanline[x..x + run].iter_mut().zip(values) {
+                        pixel[channel] = value;
+                    }
+                    data = &rest[run..];
+                } else {
+                    let (&value, rest) = rest.split_first().ok_or_else(truncated)?;
+                    scanline[x..x + run]
+                        .iter_mut()
+                        .for_each(|pixel| pixel[channel] = value);
+                    data = rest;
+                }
+                x += run;
+            }
+        }
+        return Ok(data);
+    }
+
+    // Píxeles sin comprimir, con el RLE antiguo: `1 1 1 n` repite el anterior.
+    let mut data = data;
+    let mut x = 0;
+    let mut shift = 0;
+    while x < width {
+        let pixel: [u8; 4] = data.get(..4).ok_or_else(truncated)?.try_into().unwrap();
+        data = &data[4..];
+        if pixel[..3] == [1, 1, 1] && x > 0 {
+            if pixel[3] == 0 || shift >= usize::BITS {
+                return Err(TextureError::Parse("HDR: invalid run length".into()));
+            }
+            let count = (pixel[3] as usize) << shift;
+            if count > width - x {
+                return Err(TextureError::Parse("HDR: invalid run length".into()));
+            }
+            let previous = scanline[x - 1];
+            scanline[x..x + count].fill(previous);
+            x += count;
+            shift += 8;
+        } else {
+            scanline[x] = pixel;
+            x += 1;
+            shift = 0;
+        }
+    }
+    Ok(data)
+}
+
+/// Color lineal de un píxel RGBE: mantisas de 8 bits con exponente compartido.
+fn rgbe_to_rgb([r, g, b, e]: [u8; 4]) -> [f32; 3] {
+    if e == 0 {
+        return [0.0; 3];
+    }
+    let scale = 2f32.powi(e as i32 - 136);
+    [r as f32 * scale, g as f32 * scale, b as f32 * scale]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Vec4;
+
+    #[test]
+    fn test_hdr_flat_and_run_length_scanlines() {
+        // Fila 0 sin comprimir (ancho < 8 no admite RLE por canales).
+        let mut bytes = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\nEXPOSURE=1.0\n\n-Y 2 +X 2\n".to_vec();
+        bytes.extend([128, 64, 32, 129, 0, 0, 0, 0]);
+        bytes.extend([0, 0, 0, 0, 1, 1, 1, 1]);
+        let texture = parse_hdr(&bytes).unwrap();
+        assert_eq!((texture.width, texture.height), (2, 2));
+        assert_eq!(texture.format, TextureFormat::Rgba32Float);
+        let texels = texture.texels(0);
+        assert_eq!(texels[0], Vec4::new(1.0, 0.5, 0.25, 1.0));
+        assert_eq!(texels[1], Vec4::new(0.0, 0.0, 0.0, 1.0));
+        // `1 1 1 1` repite el píxel anterior.
+        assert_eq!(texels[2], texels[1]);
+        assert_eq!(texels[3], texels[1]);
+
+        // RLE por canales: R es un run, G literales, B y E runs.
+        let mut bytes = b"#?RGBE\n\n-Y 1 +X 8\n".to_vec();
+        bytes.extend([2, 2, 0, 8]);
+        bytes.extend([128 + 8, 200]);
+        bytes.extend([8, 0, 16, 32, 48, 64, 80, 96, 112]);
+        bytes.extend([128 + 8, 0]);
+        bytes.extend([128 + 8, 136]);
+        let texels = parse_hdr(&bytes).unwrap().texels(0);
+        assert_eq!(texels.len(), 8);
+        assert_eq!(texels[3], Vec4::new(200.0, 48.0, 0.0, 1.0));
+
+        assert!(matches!(
+            parse_hdr(b"#?RADIANCE\n\n+Y 1 +X 1\n\x80\x80\x80\x80"),
+            Err(TextureError::Unsupported(_))
+        ));
+        assert!(matches!(
+            parse_hdr(b"#?RADIANCE\n\n-Y 1 +X 8\n\x02\x02\x00\x08\x88"),
+            Err(TextureError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_hdr_rejects_malformed_sizes_and_runs() {
+        // 100000x100000 sin datos: se rechaza sin reservar 160 GB.
+        assert_eq!(
+            parse_hdr(b"#?RADIANCE\n\n-Y 100000 +X 100000\n"),
+            Err(TextureError::Parse(
+                "HDR: image is larger than its data".into()
+            ))
+        );
+        assert!(matches!(
+            parse_hdr(b"#?RADIANCE\n\n-Y 4294967295 +X 4294967295\n"),
+            Err(TextureError::Parse(_))
+        ));
+
+        // RLE antiguo con un run vacío y con runs encadenados que se salen de la fila.
+        let header = b"#?RADIANCE\n\n-Y 1 +X 3\n";
+        let mut zero = header.to_vec();
+        zero.extend([9, 9, 9, 9, 1, 1, 1, 0, 9, 9, 9, 9, 9, 9, 9, 9]);
+        assert!(matches!(parse_hdr(&zero), Err(TextureError::Parse(_))));
+        let mut endless = header.to_vec();
+        endless.extend([9, 9, 9, 9]);
+        (0..9).for_each(|_| endless.extend([1, 1, 1, 1]));
+        assert!(matches!(parse_hdr(&endless), Err(TextureError::Parse(_))));
+    }
+}
@@ -0,0 +1,620 @@
+//! Decodificador de JPEG baseline.
+//!
+//! Admite JPEG secuencial de 8 bits con Huffman (SOF0/SOF1), en gris o YCbCr
+//! con cualquier submuestreo, scans entrelazados o por componente y marcadores
+//! de reinicio. Los JPEG progresivos, sin pérdidas, aritméticos o CMYK se
+//! rechazan como no soportados. El croma se amplía por vecino más cercano.
+
+use super::convert::with_color_space;
+use super::{ColorSpace, Texture, TextureError};
+use crate::backend::TextureFormat;
+use std::f32::consts::PI;
+
+/// Posición natural (fila por fila) de cada coeficiente en orden zigzag.
+const ZIGZAG: [usize; 64] = [
+    0, 1, 8, 16, 9, 2, 3, 10, 17, 24, 32, 25, 18, 11, 4, 5, 12, 19, 26, 33, 40, 48, 41, 34, 27, 20,
+    13, 6, 7, 14, 21, 28, 35, 42, 49, 56, 57, 50, 43, 36, 29, 22, 15, 23, 30, 37, 44, 51, 58, 59,
+    52, 45, 38, 31, 39, 46, 53, 60, 61, 54, 47, 55, 62, 63,
+];
+
+fn parse_error(message: &str) -> TextureError {
+    TextureError::Parse(format!("JPEG: {}", message))
+}
+
+/// Tabla de Huffman canónica.
+#[derive(Clone, Default)]
+struct Huffman {
+    /// Mayor código de cada longitud (1..=16), o -1 si no hay ninguno.
+    max_code: [i32; 17],
+    /// Diferencia entre el índice del primer símbolo de cada longitud y su código.
+    offset: [i32; 17],
+    symbols: Vec<u8>,
+}
+
+impl Huffman {
+    fn new(counts: &[u8], symbols: &[u8]) -> Self {
+        let mut table = Huffman {
+            max_code: [-1; 17],
+            offset: [0; 17],
+            symbols: symbols.to_vec(),
+        };
+        let mut code = 0i32;
+        let mut index = 0i32;
+        for length in 1..=16 {
+            let count = counts[length - 1] as i32;
+            if count > 0 {
+                table.offset[length] = index - code;
+                code += count;
+                index += count;
+                table.max_code[length] = code - 1;
+            }
+            code <<= 1;
+        }
+        table
+    }
+
+    fn decode(&self, bits: &mut BitReader) -> Result<u8, TextureError> {
+        let mut code = 0i32;
+        for length in 1..=16 {
+            code = code << 1 | bits.bit()? as i32;
+            if code <= self.max_code[length] {
+                return self
+                    .symbols
+                    .get((code + self.offset[length]) as usize)
+                    .copied()
+                    .ok_or_else(|| parse_error("invalid Huffman table"));
+            }
+        }
+        Err(parse_error("invalid Huffman code"))
+    }
+}
+
+/// Lector de bits de los datos entrópicos, sin los bytes de relleno `FF 00`.
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    buffer: u32,
+    count: u32,
+}
+
+impl BitReader<'_> {
+    fn bit(&mut self) -> Result<u32, TextureError> {
+        if self.count == 0 {
+            let byte = *self
+                .data
+                .get(self.position)
+                .ok_or_else(|| parse_error("scan data is truncated"))?;
+            if byte == 0xFF {
+                match self.data.get(self.position + 1) {
+                    Some(0) => self.position += 2,
+                    // Un marcador corta los datos: se rellena con ceros sin consumirlo.
+                    Some(_) => {
+                        self.buffer = 0;
+                        self.count = 8;
+                        return self.bit();
+                    }
+                    None => return Err(parse_error("scan data is truncated")),
+                }
+            } else {
+                self.position += 1;
+            }
+            self.buffer = byte as u32;
+            self.count = 8;
+        }
+        self.count -= 1;
+        Ok(self.buffer >> self.count & 1)
+    }
+
+    fn bits(&mut self, count: u8) -> Result<u32, TextureError> {
+        (0..count).try_fold(0, |value, _| Ok(value << 1 | self.bit()?))
+    }
+
+    /// Valor con signo de `size` bits (categoría de magnitud).
+    fn signed(&mut self, size: u8) -> Result<i32, TextureError> {
+        if size == 0 {
+            return Ok(0);
+        }
+        if size > 16 {
+            return Err(parse_error("invalid coefficient size"));
+        }
+        let value = self.bits(size)? as i32;
+        Ok(if value < 1 << (size - 1) {
+            value - (1 << size) + 1
+        } else {
+            value
+        })
+    }
+
+    /// Descarta los bits pendientes y salta el marcador `RSTn` siguiente.
+    fn restart(&mut self) -> Result<(), TextureError> {
+        self.count = 0;
+        match self.data.get(self.position..self.position + 2) {
+            Some(&[0xFF, marker]) if (0xD0..=0xD7).contains(&marker) => {
+                self.position += 2;
+                Ok(())
+            }
+            _ => Err(parse_error("missing restart marker")),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Component {
+    id: u8,
+    h: usize,
+    v: usize,
+    quant: usize,
+    dc_table: usize,
+    ac_table: usize,
+    predictor: i32,
+    /// Muestras decodificadas, en bloques completos de 8x8.
+    plane: Vec<u8>,
+    stride: usize,
+}
+
+/// Estado de la imagen mientras se leen sus segmentos.
+struct Frame {
+    width: usize,
+    height: usize,
+    components: Vec<Component>,
+    h_max: usize,
+    v_max: usize,
+    mcus_x: usize,
+    mcus_y: usize,
+}
+
+/// Carga un JPEG baseline desde sus bytes como RGBA8 en `color_space`.
+pub fn parse_jpeg(bytes: &[u8], color_space: ColorSpace) -> Result<Texture, TextureError> {
+    if !bytes.starts_with(&[0xFF, 0xD8]) {
+        return Err(parse_error("missing SOI marker"));
+    }
+    let mut quant = [[0u16; 64]; 4];
+    let mut dc_tables: [Huffman; 4] = Default::default();
+    let mut ac_tables: [Huffman; 4] = Default::default();
+    let mut restart_interval = 0;
+    let mut adobe_transform = None;
+    let mut frame: Option<Frame> = None;
+    let mut position = 2;
+
+    loop {
+        // Se saltan bytes sueltos y de relleno hasta el siguiente marcador.
+        while bytes.get(position).is_some_and(|&b| b != 0xFF) {
+            position += 1;
+        }
+        while bytes.get(position) == Some(&0xFF) {
+            position += 1;
+        }
+        let marker = *bytes
+            .get(position)
+            .ok_or_else(|| parse_error("missing EOI marker"))?;
+        position += 1;
+        match marker {
+            0xD9 => break,
+            0x00 | 0xD0..=0xD7 => continue,
+            _ => {}
+        }
+        let length = bytes
+            .get(position..position + 2)
+            .map(|len| u16::from_be_bytes([len[0], len[1]]) as usize)
+            .filter(|&len| len >= 2)
+            .ok_or_else(|| parse_error("segment is truncated"))?;
+        let segment = bytes
+            .get(position + 2..position + length)
+            .ok_or_else(|| parse_error("segment is truncated"))?;
+        position += length;
+
+        match marker {
+            0xDB => {
+                let mut data = segment;
+                while let Some((&info, rest)) = data.split_first() {
+                    let (precision, table) = ((info >> 4) as usize, (info & 15) as usize);
+                    let size = 64 << precision;
+                    let values = rest
+                        .get(..size)
+                        .filter(|_| precision <= 1 && table < 4)
+                        .ok_or_else(|| parse_error("invalid quantization table"))?;
+                    for (k, value) in quant[table].iter_mut().enumerate() {
+                        *value = if precision == 0 {
+                            values[k] as u16
+                        } else {
+                            u16::from_be_bytes([values[2 * k], values[2 * k + 1]])
+                        };
+                    }
+                    data = &rest[size..];
+                }
+            }
+            0xC4 => {
+                let mut data = segment;
+                while let Some((&info, rest)) = data.split_first() {
+                    let (class, table) = (info >> 4, (info & 15) as usize);
+                    let counts = rest
+                        .get(..16)
+                        .filter(|_| class <= 1 && table < 4)
+                        .ok_or_else(|| parse_error("invalid Huffman table"))?;
+                    let total = counts.iter().map(|&count| count as usize).sum::<usize>();
+                    let symbols = rest
+                        .get(16..16 + total)
+                        .ok_or_else(|| parse_error("invalid Huffman table"))?;
+                    let tables = if class == 0 {
+                        &mut dc_tables
+                    } else {
+                        &mut ac_tables
+                    };
+                    tables[table] = Huffman::new(counts, symbols);
+                    data = &rest[16 + total..];
+                }
+            }
+            0xDD => {
+                let data = segment
+                    .get(..2)
+                    .ok_or_else(|| parse_error("invalid DRI segment"))?;
+                restart_interval = u16::from_be_bytes([data[0], data[1]]) as usize;
+            }
+            0xEE if segment.starts_with(b"Adobe") && segment.len() >= 12 => {
+                adobe_transform = Some(segment[11]);
+            }
+            0xC0 | 0xC1 => {
+                if frame.is_some() {
+                    return Err(parse_error("more than one frame"));
+                }
+                frame = Some(parse_frame(segment)?);
+            }
+            0xC2 => return Err(TextureError::Unsupported("progressive JPEG".into())),
+            0xC3 | 0xC5..=0xC7 | 0xCB | 0xCD..=0xCF => {
+                return Err(TextureError::Unsupported(
+                    "lossless or hierarchical JPEG".into(),
+                ));
+            }
+            0xC9 | 0xCA | 0xCC => {
+                return Err(TextureError::Unsupported("arithmetic-coded JPEG".into()));
+            }
+            0xDA => {
+                let frame = frame
+                    .as_mut()
+                    .ok_or_else(|| parse_error("scan before frame"))?;
+                let tables = Tables {
+                    quant: &quant,
+                    dc: &dc_tables,
+                    ac: &ac_tables,
+                };
+                position += decode_scan(
+                    frame,
+                    segment,
+                    &bytes[position..],
+                    restart_interval,
+                    &tables,
+                )?;
+            }
+            // APPn, COM y el resto de segmentos no afectan a los píxeles.
+            _ => {}
+        }
+    }
+
+    let frame = frame.ok_or_else(|| parse_error("missing frame"))?;
+    // Con tres componentes es YCbCr salvo que Adobe diga que es RGB.
+    let ycbcr = adobe_transform != Some(0);
+    let mut data = Vec::with_capacity(frame.width * frame.height * 4);
+    for y in 0..frame.height {
+        for x in 0..frame.width {
+            let mut samples = frame.components.iter().map(|component| {
+                let cx = x * component.h / frame.h_max;
+                let cy = y * component.v / frame.v_max;
+                component.plane[cy * component.stride + cx] as f32
+            });
+            let pixel = match frame.components.len() {
+                1 => [samples.next().unwrap(); 3],
+                _ => {
+                    let [a, b, c] = [(); 3].map(|_| samples.next().unwrap());
+                    if ycbcr {
+                        ycbcr_to_rgb(a, b, c)
+                    } else {
+                        [a, b, c]
+                    }
+                }
+            };
+            data.extend(pixel.map(|value| value.round().clamp(0.0, 255.0) as u8));
+            data.push(u8::MAX);
+        }
+    }
+    let format = with_color_space(TextureFormat::Rgba8Unorm, color_space);
+    Texture::new(frame.width as u32, frame.height as u32, format, data)
+}
+
+fn ycbcr_to_rgb(y: f32, cb: f32, cr: f32) -> [f32; 3] {
+    let (cb, cr) = (cb - 128.0, cr - 128.0);
+    [
+        y + 1.402 * cr,
+        y - 0.344_136 * cb - 0.714_136 * cr,
+        y + 1.772 * cb,
+    ]
+}
+
+fn parse_frame(segment: &[u8]) -> Result<Frame, TextureError> {
+    let header = segment
+        .get(..6)
+        .ok_or_else(|| parse_error("invalid SOF segment"))?;
+    if header[0] != 8 {
+        return Err(TextureError::Unsupported(format!("{}-bit JPEG", header[0])));
+    }
+    let height = u16::from_be_bytes([header[1], header[2]]) as usize;
+    let width = u16::from_be_bytes([header[3], header[4]]) as usize;
+    let count = header[5] as usize;
+    if width == 0 || height == 0 {
+        return Err(parse_error("image has no size"));
+    }
+    if count != 1 && count != 3 {
+        return Err(TextureError::Unsupported(format!(
+            "JPEG with {} components",
+            count
+        )));
+    }
+    let specs = segment
+        .get(6..6 + 3 * count)
+        .ok_or_else(|| parse_error("invalid SOF segment"))?;
+    let mut components: Vec<Component> = specs
+        .chunks_exact(3)
+        .map(|spec| {
+            let (h, v) = ((spec[1] >> 4) as usize, (spec[1] & 15) as usize);
+            if !(1..=4).contains(&h) || !(1..=4).contains(&v) || spec[2] > 3 {
+                return Err(parse_error("invalid component"));
+            }
+            Ok(Component {
+                id: spec[0],
+                h,
+                v,
+                quant: spec[2] as usize,
+                dc_table: 0,
+                ac_table: 0,
+                predictor: 0,
+                plane: Vec::new(),
+                stride: 0,
+            })
+        })
+        .collect::<Result<_, _>>()?;
+    let h_max = components
+        .iter()
+        .map(|component| component.h)
+        .max()
+        .unwrap();
+    let v_max = components
+        .iter()
+        .map(|component| component.v)
+        .max()
+        .unwrap();
+    let mcus_x = width.div_ceil(8 * h_max);
+    let mcus_y = height.div_ceil(8 * v_max);
+    for component in &mut components {
+        component.stride = mcus_x * component.h * 8;
+        component.plane = vec![0; component.stride * mcus_y * component.v * 8];
+    }
+    Ok(Frame {
+        width,
+        height,
+        components,
+        h_max,
+        v_max,
+        mcus_x,
+        mcus_y,
+    })
+}
+
+struct Tables<'a> {
+    quant: &'a [[u16; 64]; 4],
+    dc: &'a [Huffman; 4],
+    ac: &'a [Huffman; 4],
+}
+
+/// Decodifica un scan y devuelve cuántos bytes de datos entrópicos ha leído.
+fn decode_scan(
+    frame: &mut Frame,
+    header: &[u8],
+    data: &[u8],
+    restart_interval: usize,
+    tables: &Tables,
+) -> Result<usize, TextureError> {
+    let count = *header
+        .first()
+        .ok_or_else(|| parse_error("invalid SOS segment"))? as usize;
+    let specs = header
+        .get(1..1 + 2 * count)
+        .filter(|_| count > 0)
+        .ok_or_else(|| parse_error("invalid SOS segment"))?;
+    let mut scan = Vec::with_capacity(count);
+    for spec in specs.chunks_exact(2) {
+        let index = frame
+            .components
+            .iter()
+            .position(|component| component.id == spec[0])
+            .ok_or_else(|| parse_error("scan references an unknown component"))?;
+        let component = &mut frame.components[index];
+        component.dc_table = (spec[1] >> 4) as usize;
+        component.ac_table = (spec[1] & 15) as usize;
+        if component.dc_table > 3 || component.ac_table > 3 {
+            return Err(parse_error("invalid Huffman table selector"));
+        }
+        component.predictor = 0;
+        scan.push(index);
+    }
+
+    // Con un solo componente, el scan recorre sus bloques y no los MCU del frame.
+    let (mcus_x, mcus_y) = if count == 1 {
+        let component = &frame.components[scan[0]];
+        let width = (frame.width * component.h).div_ceil(frame.h_max);
+        let height = (frame.height * component.v).div_ceil(frame.v_max);
+        (width.div_ceil(8), height.div_ceil(8))
+    } else {
+        (frame.mcus_x, frame.mcus_y)
+    };
+    let mut bits = BitReader {
+        data,
+        position: 0,
+        buffer: 0,
+        count: 0,
+    };
+    let mut coefficients = [0f32; 64];
+    for mcu in 0..mcus_x * mcus_y {
+        if restart_interval > 0 && mcu > 0 && mcu % restart_interval == 0 {
+            bits.restart()?;
+            scan.iter()
+                .for_each(|&index| frame.components[index].predictor = 0);
+        }
+        let (mcu_x, mcu_y) = (mcu % mcus_x, mcu / mcus_x);
+        for &index in &scan {
+            let component = &mut frame.components[index];
+            let (h, v) = if count == 1 {
+                (1, 1)
+            } else {
+                (component.h, component.v)
+            };
+            for block in 0..h * v {
+                decode_block(component, &mut bits, tables, &mut coefficients)?;
+                let x = (mcu_x * h + block % h) * 8;
+                let y = (mcu_y * v + block / h) * 8;
+                idct(
+                    &coefficients,
+                    &mut component.plane[y * component.stride + x..],
+                    component.stride,
+                );
+            }
+        }
+    }
+    Ok(bits.position)
+}
+
+/// Decodifica y descuantiza un bloque en orden natural.
+fn decode_block(
+    component: &mut Component,
+    bits: &mut BitReader,
+    tables: &Tables,
+    coefficients: &mut [f32; 64],
+) -> Result<(), TextureError> {
+    let quant = &tables.quant[component.quant];
+    coefficients.fill(0.0);
+    let size = tables.dc[component.dc_table].decode(bits)?;
+    component.predictor += bits.signed(size)?;
+    coefficients[0] = (component.predictor * quant[0] as i32) as f32;
+
+    let ac = &tables.ac[component.ac_table];
+    let mut k = 1;
+    while k < 64 {
+        let symbol = ac.decode(bits)?;
+        let (run, size) = ((symbol >> 4) as usize, symbol & 15);
+        if size == 0 {
+            if run != 15 {
+                break;
+            }
+            k += 16;
+            continue;
+        }
+        k += run;
+        if k > 63 {
+            return Err(parse_error("coefficient index out of range"));
+        }
+        coefficients[ZIGZAG[k]] = (bits.signed(size)? * quant[k] as i32) as f32;
+        k += 1;
+    }
+    Ok(())
+}
+
+/// IDCT separable de un bloque; escribe las muestras con el offset de 128.
+fn idct(coefficients: &[f32; 64], output: &mut [u8], stride: usize) {
+    // `basis[x][u] = C(u) / 2 * cos((2x + 1) u pi / 16)`.
+    let basis: [[f32; 8]; 8] = std::array::from_fn(|x| {
+        std::array::from_fn(|u| {
+            let scale = if u == 0 { 0.5 / 2f32.sqrt() } else { 0.5 };
+            scale * ((2 * x + 1) as f32 * u as f32 * PI / 16.0).cos()
+        })
+    });
+    let mut rows = [0f32; 64];
+    for v in 0..8 {
+        for x in 0..8 {
+            rows[v * 8 + x] = (0..8).map(|u| basis[x][u] * coefficients[v * 8 + u]).sum();
+        }
+    }
+    for y in 0..8 {
+        for x in 0..8 {
+            let value: f32 = (0..8).map(|v| basis[y][v] * rows[v * 8 + x]).sum();
+            output[y * stride + x] = (value + 128.0).round().clamp(0.0, 255.0) as u8;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> Vec<u8> {
+        let path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/textures")
+            .join(name);
+        std::fs::read(path).unwrap()
+    }
+
+    #[test]
+    fn test_jpeg_subsampled_color_with_restarts() {
+        let palette = [
+            [255, 0, 0],
+            [0, 255, 0],
+            [0, 0, 255],
+            [255, 255, 255],
+            [255, 255, 0],
+            [40, 80, 160],
+        ];
+        let texture = parse_jpeg(&fixture("blocks_420.jpg"), ColorSpace::Srgb).unwrap();
+        assert_eq!((texture.width, texture.height), (20, 12));
+        assert_eq!(texture.format, TextureFormat::Rgba8Srgb);
+        for (i, pixel) in texture.mips[0].chunks_exact(4).enumerate() {
+            let (x, y) = (i % 20, i / 20);
+            // Los bordes entre colores se ven afectados por el croma submuestreado.
+            if x % 8 == 7 || y % 8 == 7 || x % 8 == 0 && x > 0 || y % 8 == 0 && y > 0 {
+                continue;
+            }
+            let expected = palette[x / 8 + 3 * (y / 8)];
+            assert!(
+                pixel[..3]
+                    .iter()
+                    .zip(expected)
+                    .all(|(&a, b)| a.abs_diff(b) <= 4)
+                    && pixel[3] == 255,
+                "pixel ({}, {}) is {:?}, expected {:?}",
+                x,
+                y,
+                pixel,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_jpeg_grayscale_and_errors() {
+        let bytes = fixture("gradient_gray.jpg");
+        let texture = parse_jpeg(&bytes, ColorSpace::Linear).unwrap();
+        assert_eq!(texture.format, TextureFormat::Rgba8Unorm);
+        for (i, pixel) in texture.mips[0].chunks_exact(4).enumerate() {
+            let expected = ((i % 16) * 16 + (i / 16) * 2).min(255) as u8;
+            assert!(
+                pixel[0].abs_diff(expected) <= 2,
+                "pixel {} is {:?}",
+                i,
+                pixel
+            );
+            assert!(pixel[0] == pixel[1] && pixel[1] == pixel[2] && pixel[3] == 255);
+        }
+
+        let sof = bytes
+            .windows(2)
+            .position(|marker| marker == [0xFF, 0xC0])
+            .unwrap();
+        let mut progressive = bytes.clone();
+        progressive[sof + 1] = 0xC2;
+        assert_eq!(
+            parse_jpeg(&progressive, ColorSpace::Srgb),
+            Err(TextureError::Unsupported("progressive JPEG".into()))
+        );
+        let truncated = &bytes[..bytes.len() - 20];
+        assert!(matches!(
+            parse_jpeg(truncated, ColorSpace::Srgb),
+            Err(TextureError::Parse(_))
+        ));
+    }
+}
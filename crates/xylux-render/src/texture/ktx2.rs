@@ -0,0 +1,182 @@
+//! Carga de contenedores KTX2 con texturas 2D sin comprimir.
+//!
+//! El formato sale del `vkFormat` del archivo, así que ya dice si es sRGB. Los
+//! niveles se guardan del más pequeño al más grande, pero el índice los lista
+//! desde el 0. No se admiten supercompresión (Basis, Zstandard), formatos por
+//! bloques, arrays, cubemaps ni texturas 3D.
+
+use super::{Reader, Texture, TextureError, mip_byte_size, mip_count};
+use crate::backend::TextureFormat;
+use ash::vk;
+
+/// Firma de los archivos KTX2: `«KTX 20»\r\n\x1A\n`.
+pub const IDENTIFIER: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+
+/// `vkFormat` de cada `TextureFormat` que se puede cargar.
+const FORMATS: [(vk::Format, TextureFormat); 6] = [
+    (vk::Format::R8G8B8A8_UNORM, TextureFormat::Rgba8Unorm),
+    (vk::Format::R8G8B8A8_SRGB, TextureFormat::Rgba8Srgb),
+    (vk::Format::B8G8R8A8_UNORM, TextureFormat::Bgra8Unorm),
+    (vk::Format::B8G8R8A8_SRGB, TextureFormat::Bgra8Srgb),
+    (vk::Format::R16G16B16A16_SFLOAT, TextureFormat::Rgba16Float),
+    (vk::Format::R32G32B32A32_SFLOAT, TextureFormat::Rgba32Float),
+];
+
+/// Tamaño de la cabecera y su índice, antes del índice de niveles.
+const HEADER_SIZE: usize = 80;
+
+/// Carga un KTX2 desde sus bytes.
+pub fn parse_ktx2(bytes: &[u8]) -> Result<Texture, TextureError> {
+    if !bytes.starts_with(&IDENTIFIER) {
+        return Err(TextureError::Parse("not a KTX2 file".into()));
+    }
+    let reader = Reader {
+        bytes,
+        what: "KTX2",
+    };
+    let vk_format = vk::Format::from_raw(reader.u32(12)? as i32);
+    let width = reader.u32(20)?;
+    let height = reader.u32(24)?;
+    let depth = reader.u32(28)?;
+    let layers = reader.u32(32)?;
+    let faces = reader.u32(36)?;
+    // Sin niveles en el archivo, el cargador puede generarlos: solo viene el 0.
+    let levels = reader.u32(40)?.max(1);
+    let supercompression = reader.u32(44)?;
+
+    let format = FORMATS
+        .iter()
+        .find(|(candidate, _)| *candidate == vk_format)
+        .map(|&(_, format)| format)
+        .ok_or_else(|| TextureError::Unsupported(format!("KTX2 format {:?}", vk_format)))?;
+    if supercompression != 0 {
+        return Err(TextureError::Unsupported(format!(
+            "KTX2 supercompression scheme {}",
+            supercompression
+        )));
+    }
+    if height == 0 || depth > 1 || layers > 1 || faces != 1 {
+        return Err(TextureError::Unsupported(format!(
+            "KTX2 texture that is not 2D ({}x{}x{}, {} layers, {} faces)",
+            width, height, depth, layers, faces
+        )));
+    }
+    if levels > mip_count(width, height) {
+        return Err(TextureError::Parse(format!(
+            "KTX2 has {} mip levels for a {}x{} texture",
+            levels, width, height
+        )));
+    }
+
+    let mips = (0..levels as usize)
+        .map(|level| {
+            mip_byte_size(width, height, format, level as u32)?;
+            let entry = HEADER_SIZE + level * 24;
+            let offset = reader.u64(entry)?;
+            let length = reader.u64(entry + 8)?;
+            reader.slice(offset, length).map(<[u8]>::to_vec)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Texture::with_mips(width, height, format, mips)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// KTX2 mínimo con los niveles de `mips` uno tras otro, del 0 en adelante.
+    fn ktx2(format: vk::Format, width: u32, height: u32, mips: &[&[u8]]) -> Vec<u8> {
+        let mut bytes = IDENTIFIER.to_vec();
+        for value in [
+            format.as_raw() as u32,
+            1,
+            width,
+            height,
+            0,
+            0,
+            1,
+            mips.len() as u32,
+            0,
+        ] {
+            bytes.extend(value.to_le_bytes());
+        }
+        bytes.extend([0u8; 32]);
+        let mut offset = (HEADER_SIZE + mips.len() * 24) as u64;
+        for mip in mips {
+            for value in [offset, mip.len() as u64, mip.len() as u64] {
+                bytes.extend(value.to_le_bytes());
+            }
+            offset += mip.len() as u64;
+        }
+        mips.iter().for_each(|mip| bytes.extend(*mip));
+        bytes
+    }
+
+    #[test]
+    fn test_ktx2_levels_and_formats() {
+        let bytes = ktx2(
+            vk::Format::R8G8B8A8_SRGB,
+            2,
+            1,
+            &[&[1, 2, 3, 4, 5, 6, 7, 8], &[9, 9, 9, 9]],
+        );
+        let texture = parse_ktx2(&bytes).unwrap();
+        assert_eq!((texture.width, texture.height), (2, 1));
+        assert_eq!(texture.format, TextureFormat::Rgba8Srgb);
+        assert_eq!(
+            texture.mips,
+            [vec![1, 2, 3, 4, 5, 6, 7, 8], vec![9, 9, 9, 9]]
+        );
+
+        let half = ktx2(
+            vk::Format::R16G16B16A16_SFLOAT,
+            1,
+            1,
+            &[&[0, 0x3C, 0, 0x3C, 0, 0x3C, 0, 0x3C]],
+        );
+        assert_eq!(parse_ktx2(&half).unwrap().texels(0), [glam::Vec4::ONE]);
+
+        let bc7 = ktx2(vk::Format::BC7_SRGB_BLOCK, 4, 4, &[&[0; 16]]);
+        assert_eq!(
+            parse_ktx2(&bc7),
+            Err(TextureError::Unsupported(
+                "KTX2 format BC7_SRGB_BLOCK".into()
+            ))
+        );
+        // Un nivel que no cabe en el archivo o con el tamaño equivocado.
+        let truncated = &bytes[..bytes.len() - 1];
+        assert!(matches!(parse_ktx2(truncated), Err(TextureError::Parse(_))));
+        let short = ktx2(vk::Format::R8G8B8A8_UNORM, 2, 2, &[&[0; 12]]);
+        assert!(matches!(
+            parse_ktx2(&short),
+            Err(TextureError::DataLength { .. })
+        ));
+    }
+
+    #[test]
+    fn test_ktx2_rejects_malformed_headers() {
+        // Un tamaño cuyo nivel 0 no cabe en memoria, aunque el índice diga otra cosa.
+        let huge = ktx2(
+            vk::Format::R32G32B32A32_SFLOAT,
+            u32::MAX,
+            u32::MAX,
+            &[&[0; 16]],
+        );
+        assert!(matches!(parse_ktx2(&huge), Err(TextureError::Parse(_))));
+
+        // Más niveles de los que admite el tamaño.
+        let mut deep = ktx2(vk::Format::R8G8B8A8_UNORM, 1, 1, &[&[0; 4]]);
+        deep[40..44].copy_from_slice(&33u32.to_le_bytes());
+        assert!(matches!(parse_ktx2(&deep), Err(TextureError::Parse(_))));
+
+        let empty = ktx2(vk::Format::R8G8B8A8_UNORM, 0, 1, &[&[]]);
+        assert!(matches!(parse_ktx2(&empty), Err(TextureError::Parse(_))));
+        let header = ktx2(vk::Format::R8G8B8A8_UNORM, 1, 1, &[&[0; 4]]);
+        assert!(matches!(
+            parse_ktx2(&header[..HEADER_SIZE]),
+            Err(TextureError::Parse(_))
+        ));
+    }
+}
@@ -0,0 +1,222 @@
+//! Generación de mips en la CPU.
+//!
+//! Cada nivel se obtiene del anterior reduciendo a la mitad con un filtro
+//! separable (primero filas, luego columnas). Los bordes se extienden repitiendo
+//! el último texel, y los lados impares reparten su texel central entre los dos
+//! vecinos.
+
+use glam::Vec4;
+use std::f32::consts::PI;
+
+/// Filtro de reducción.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum MipFilter {
+    /// Promedio de cada bloque de 2x2. Rápido, pero algo borroso y con aliasing
+    /// en detalles finos.
+    #[default]
+    Box,
+    /// Sinc con ventana de Kaiser (radio de 3 texels del nivel destino,
+    /// `alpha = 4`). Conserva más detalle; puede producir un ligero ringing que los
+    /// formatos de 8 bits recortan.
+    Kaiser,
+}
+
+const KAISER_RADIUS: f32 = 3.0;
+const KAISER_ALPHA: f32 = 4.0;
+
+impl MipFilter {
+    /// Radio del filtro en texels del nivel destino.
+    fn radius(self) -> f32 {
+        match self {
+            MipFilter::Box => 0.5,
+            MipFilter::Kaiser => KAISER_RADIUS,
+        }
+    }
+
+    /// Peso a distancia `x`, en texels del nivel destino.
+    fn weight(self, x: f32) -> f32 {
+        match self {
+            MipFilter::Box => {
+                if x.abs() <= 0.5 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            MipFilter::Kaiser => {
+                let t = x / KAISER_RADIUS;
+                if t.abs() >= 1.0 {
+                    return 0.0;
+                }
+                sinc(x) * bessel_i0(KAISER_ALPHA * (1.0 - t * t).sqrt()) / bessel_i0(KAISER_ALPHA)
+            }
+        }
+    }
+}
+
+fn sinc(x: f32) -> f32 {
+    if x.abs() < 1e-6 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+/// Función de Bessel modificada de orden cero, por su serie de potencias.
+fn bessel_i0(x: f32) -> f32 {
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let quarter = x * x / 4.0;
+    for k in 1..32 {
+        term *= quarter / (k * k) as f32;
+        sum += term;
+        if term < sum * 1e-8 {
+            break;
+        }
+    }
+    sum
+}
+
+/// Pesos normalizados de cada texel destino al reducir un eje de `src` a `dst`
+/// texels: `(índice de origen, peso)`.
+fn kernel(src: u32, dst: u32, filter: MipFilter) -> Vec<Vec<(usize, f32)>> {
+    let scale = src as f32 / dst as f32;
+    let radius = filter.radius() * scale;
+    (0..dst)
+        .map(|i| {
+            let center = (i as f32 + 0.5) * scale - 0.5;
+            let first = (center - radius).floor() as i64;
+            let last = (center + radius).ceil() as i64;
+            let mut taps: Vec<(usize, f32)> = Vec::new();
+            for j in first..=last {
+                let weight = filter.weight((j as f32 - center) / scale);
+                if weight == 0.0 {
+                    continue;
+                }
+                let index = j.clamp(0, src as i64 - 1) as usize;
+                match taps.iter_mut().find(|(tap, _)| *tap == index) {
+                    Some((_, total)) => *total += weight,
+                    None => taps.push((index, weight)),
+                }
+            }
+            let sum: f32 = taps.iter().map(|(_, weight)| weight).sum();
+            taps.iter_mut().for_each(|(_, weight)| *weight /= sum);
+            taps
+        })
+        .collect()
+}
+
+/// Reduce una imagen de `width`x`height` texels lineales al siguiente nivel de
+/// mip y devuelve sus texels y su tamaño.
+pub fn downsample(
+    texels: &[Vec4],
+    width: u32,
+    height: u32,
+    filter: MipFilter,
+) -> (Vec<Vec4>, u32, u32) {
+    assert_eq!(texels.len(), width as usize * height as usize);
+    let (dst_width, dst_height) = ((width / 2).max(1), (height / 2).max(1));
+
+    let columns = kernel(width, dst_width, filter);
+    let mut rows = Vec::with_capacity(dst_width as usize * height as usize);
+    for row in texels.chunks_exact(width as usize) {
+        rows.extend(columns.iter().map(|taps| {
+            taps.iter()
+                .map(|&(x, weight)| row[x] * weight)
+                .sum::<Vec4>()
+        }));
+    }
+
+    let lines = kernel(height, dst_height, filter);
+    let mut result = Vec::with_capacity(dst_width as usize * dst_height as usize);
+    for taps in &lines {
+        for x in 0..dst_width as usize {
+            result.push(
+                taps.iter()
+                    .map(|&(y, weight)| rows[y * dst_width as usize + x] * weight)
+                    .sum::<Vec4>(),
+            );
+        }
+    }
+    (result, dst_width, dst_height)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Vec4, b: Vec4) -> bool {
+        (a - b).abs().max_element() < 1e-5
+    }
+
+    #[test]
+    fn test_box_filter_averages_blocks() {
+        let texels: Vec<Vec4> = (0..16).map(|i| Vec4::splat(i as f32)).collect();
+        let (mip, width, height) = downsample(&texels, 4, 4, MipFilter::Box);
+        assert_eq!((width, height), (2, 2));
+        let expected = [2.5, 4.5, 10.5, 12.5].map(Vec4::splat);
+        assert!(
+            mip.iter().zip(&expected).all(|(a, b)| close(*a, *b)),
+            "{:?}",
+            mip
+        );
+
+        // Un lado de 1 texel se copia; el otro se reduce.
+        let column: Vec<Vec4> = (0..4).map(|i| Vec4::splat(i as f32)).collect();
+        let (mip, width, height) = downsample(&column, 1, 4, MipFilter::Box);
+        assert_eq!((width, height), (1, 2));
+        assert!(close(mip[0], Vec4::splat(0.5)) && close(mip[1], Vec4::splat(2.5)));
+
+        // Lado impar: el texel central pesa en los dos destinos.
+        let row: Vec<Vec4> = [0.0, 0.0, 3.0, 6.0, 6.0].map(Vec4::splat).to_vec();
+        let (mip, width, _) = downsample(&row, 5, 1, MipFilter::Box);
+        assert_eq!(width, 2);
+        assert!(
+            close(mip[0], Vec4::splat(1.0)) && close(mip[1], Vec4::splat(5.0)),
+            "{:?}",
+            mip
+        );
+    }
+
+    #[test]
+    fn test_kaiser_filter_preserves_constants_and_removes_aliasing() {
+        let constant = vec![Vec4::new(0.25, 0.5, 0.75, 1.0); 12 * 6];
+        let (mip, width, height) = downsample(&constant, 12, 6, MipFilter::Kaiser);
+        assert_eq!((width, height), (6, 3));
+        assert!(mip.iter().all(|texel| close(*texel, constant[0])));
+
+        // Una frecuencia que el nivel destino no puede representar queda en gris
+        // lejos de los bordes, donde repetir el último texel rompe el patrón.
+        let stripes: Vec<Vec4> = (0..32 * 2).map(|i| Vec4::splat((i % 2) as f32)).collect();
+        let (mip, _, _) = downsample(&stripes, 32, 2, MipFilter::Kaiser);
+        let interior = &mip[3..mip.len() - 3];
+        assert!(
+            interior.iter().all(|texel| (texel.x - 0.5).abs() < 0.01),
+            "{:?}",
+            mip
+        );
+
+        // Un escalón se conserva más nítido que con el box, con ringing acotado.
+        let step: Vec<Vec4> = (0..16)
+            .map(|i| Vec4::splat(if i < 8 { 0.0 } else { 1.0 }))
+            .collect();
+        let (kaiser, _, _) = downsample(&step, 16, 1, MipFilter::Kaiser);
+        assert!(kaiser[3].x < 0.5 && kaiser[4].x > 0.5);
+        assert!(kaiser.iter().all(|texel| (-0.1..=1.1).contains(&texel.x)));
+        assert!(close(kaiser[0], Vec4::ZERO) && close(kaiser[7], Vec4::ONE));
+    }
+
+    #[test]
+    fn test_chain_reaches_one_texel() {
+        let mut texels = vec![Vec4::ONE; 8 * 3];
+        let (mut width, mut height) = (8, 3);
+        let mut sizes = Vec::new();
+        while width > 1 || height > 1 {
+            (texels, width, height) = downsample(&texels, width, height, MipFilter::Kaiser);
+            sizes.push((width, height));
+        }
+        assert_eq!(sizes, [(4, 1), (2, 1), (1, 1)]);
+        assert!(close(texels[0], Vec4::ONE));
+        assert_eq!(super::super::mip_count(8, 3) as usize, sizes.len() + 1);
+    }
+}
@@ -0,0 +1,387 @@
+//! # Módulo Texture
+//!
+//! Texturas como asset: imágenes en memoria con su cadena de mips, listas para
+//! subirlas a un backend y muestrearlas con un sampler.
+//!
+//! Los texels se guardan ya en el `TextureFormat` de destino. Las imágenes de
+//! color de 8 bits se cargan como `Rgba8Srgb` o `Rgba8Unorm` según el
+//! `ColorSpace` pedido (color frente a normales, rugosidad...), y las HDR como
+//! `Rgba32Float`. Los mips y las conversiones de formato trabajan siempre con
+//! valores lineales.
+//!
+//! - `convert`: sRGB, half floats y conversión entre formatos.
+//! - `mip`: generación de mips en la CPU con filtro box o Kaiser.
+//! - `png`, `jpeg`, `hdr`: imágenes (`.png`, `.jpg` baseline, `.hdr` Radiance).
+//! - `ktx2`, `dds`: contenedores con mips ya generados (solo formatos sin comprimir).
+
+pub mod convert;
+pub mod dds;
+pub mod hdr;
+pub mod jpeg;
+pub mod ktx2;
+pub mod mip;
+pub mod png;
+
+pub use dds::parse_dds;
+pub use hdr::parse_hdr;
+pub use jpeg::parse_jpeg;
+pub use ktx2::parse_ktx2;
+pub use mip::MipFilter;
+pub use png::parse_png;
+
+use crate::backend::{
+    BackendError, RenderBackend, TextureDesc, TextureFormat, TextureId, TextureUsage,
+};
+use glam::Vec4;
+use std::fmt;
+use std::path::Path;
+
+/// Cómo se interpretan los valores de 8 bits de una imagen.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ColorSpace {
+    /// Color codificado en sRGB (albedo, emisivo, UI).
+    #[default]
+    Srgb,
+    /// Datos lineales (normales, rugosidad, máscaras).
+    Linear,
+}
+
+/// Errores al cargar o convertir una textura.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TextureError {
+    /// No se pudo leer el archivo.
+    Io(String),
+    /// El archivo no es válido.
+    Parse(String),
+    /// El archivo usa una característica no soportada (e.g. JPEG progresivo).
+    Unsupported(String),
+    /// Un nivel de mip no tiene los bytes que corresponden a su tamaño y formato.
+    DataLength {
+        mip_level: u32,
+        len: usize,
+        expected: usize,
+    },
+}
+
+impl fmt::Display for TextureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextureError::Io(message) => write!(f, "I/O error: {}", message),
+            TextureError::Parse(message) => write!(f, "Parse error: {}", message),
+            TextureError::Unsupported(what) => write!(f, "Unsupported: {}", what),
+            TextureError::DataLength {
+                mip_level,
+                len,
+                expected,
+            } => write!(
+                f,
+                "Mip level {} has {} bytes, expected {}",
+                mip_level, len, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TextureError {}
+
+/// Número de niveles de mip de una cadena completa hasta 1x1.
+pub fn mip_count(width: u32, height: u32) -> u32 {
+    32 - width.max(height).max(1).leading_zeros()
+}
+
+/// Tamaño del nivel de mip `mip_level`.
+pub fn mip_extent(width: u32, height: u32, mip_level: u32) -> (u32, u32) {
+    ((width >> mip_level).max(1), (height >> mip_level).max(1))
+}
+
+/// Bytes del nivel de mip `mip_level`, o un error si no caben en memoria.
+pub fn mip_byte_size(
+    width: u32,
+    height: u32,
+    format: TextureFormat,
+    mip_level: u32,
+) -> Result<usize, TextureError> {
+    let (mip_width, mip_height) = mip_extent(width, height, mip_level);
+    (mip_width as u64)
+        .checked_mul(mip_height as u64)
+        .and_then(|texels| texels.checked_mul(format.bytes_per_pixel() as u64))
+        .and_then(|len| usize::try_from(len).ok())
+        .ok_or_else(|| {
+            TextureError::Parse(format!("texture size {}x{} is too large", width, height))
+        })
+}
+
+/// Imagen 2D con su cadena de mips.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Texture {
+    pub width: u32,
+    pub height: u32,
+    pub format: TextureFormat,
+    /// Texels de cada nivel, del 0 (tamaño completo) al más pequeño.
+    pub mips: Vec<Vec<u8>>,
+}
+
+impl Texture {
+    /// Crea una textura con un solo nivel.
+    pub fn new(
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+        data: Vec<u8>,
+    ) -> Result<Self, TextureError> {
+        Self::with_mips(width, height, format, vec![data])
+    }
+
+    /// Crea una textura con los niveles de `mips`, comprobando sus tamaños.
+    pub fn with_mips(
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+        mips: Vec<Vec<u8>>,
+    ) -> Result<Self, TextureError> {
+        if width == 0 || height == 0 {
+            return Err(TextureError::Parse(format!(
+                "texture size {}x{} is empty",
+                width, height
+            )));
+        }
+        if format.is_depth() {
+            return Err(TextureError::Unsupported(format!(
+                "{:?} textures as assets",
+                format
+            )));
+        }
+        if mips.is_empty() || mips.len() as u32 > mip_count(width, height) {
+            return Err(TextureError::Parse(format!(
+                "{} mip levels for a {}x{} texture",
+                mips.len(),
+                width,
+                height
+            )));
+        }
+        for (mip_level, data) in mips.iter().enumerate() {
+            let expected = mip_byte_size(width, height, format, mip_level as u32)?;
+            if data.len() != expected {
+                return Err(TextureError::DataLength {
+                    mip_level: mip_level as u32,
+                    len: data.len(),
+                    expected,
+                });
+            }
+        }
+        Ok(Self {
+            width,
+            height,
+            format,
+            mips,
+        })
+    }
+
+    /// Crea una textura a partir de texels lineales.
+    pub fn from_texels(
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+        texels: &[Vec4],
+    ) -> Result<Self, TextureError> {
+        Self::new(width, height, format, convert::encode(format, texels)?)
+    }
+
+    pub fn mip_levels(&self) -> u32 {
+        self.mips.len() as u32
+    }
+
+    pub fn mip_extent(&self, mip_level: u32) -> (u32, u32) {
+        mip_extent(self.width, self.height, mip_level)
+    }
+
+    /// Texels lineales de un nivel.
+    pub fn texels(&self, mip_level: u32) -> Vec<Vec4> {
+        convert::decode(self.format, &self.mips[mip_level as usize])
+            .expect("texture formats are checked on creation")
+    }
+
+    /// Cambia el espacio de color de una textura de 8 bits sin tocar sus bytes,
+    /// e.g. para usar como normal map una imagen cargada como sRGB.
+    pub fn with_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.format = convert::with_color_space(self.format, color_space);
+        self
+    }
+
+    /// Convierte todos los niveles a `format`.
+    pub fn convert(&self, format: TextureFormat) -> Result<Texture, TextureError> {
+        if format == self.format {
+            return Ok(self.clone());
+        }
+        let mips = (0..self.mip_levels())
+            .map(|mip_level| convert::encode(format, &self.texels(mip_level)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Texture::with_mips(self.width, self.height, format, mips)
+    }
+
+    /// Sustituye los mips por la cadena completa generada desde el nivel 0.
+    pub fn generate_mips(&mut self, filter: MipFilter) {
+        let mut texels = self.texels(0);
+        let (mut width, mut height) = (self.width, self.height);
+        self.mips.truncate(1);
+        while width > 1 || height > 1 {
+            (texels, width, height) = mip::downsample(&texels, width, height, filter);
+            self.mips
+                .push(convert::encode(self.format, &texels).expect("texture format was checked"));
+        }
+    }
+
+    /// Descripción para crear la textura en un backend.
+    pub fn desc(&self) -> TextureDesc {
+        TextureDesc {
+            width: self.width,
+            height: self.height,
+            mip_levels: self.mip_levels(),
+            format: self.format,
+            usage: TextureUsage::SAMPLED | TextureUsage::TRANSFER_DST,
+        }
+    }
+
+    /// Crea la textura en `backend` y encola la subida de todos sus niveles.
+    pub fn upload(&self, backend: &mut dyn RenderBackend) -> Result<TextureId, BackendError> {
+        let texture = backend.create_texture(&self.desc())?;
+        for (mip_level, data) in self.mips.iter().enumerate() {
+            if let Err(error) = backend.upload_texture(texture, mip_level as u32, data) {
+                backend.destroy_texture(texture);
+                return Err(error);
+            }
+        }
+        Ok(texture)
+    }
+}
+
+/// Carga una textura de un archivo PNG, JPEG, HDR, KTX2 o DDS.
+///
+/// `color_space` decide el formato de las imágenes de color de 8 bits; los
+/// contenedores KTX2 y DDS ya declaran el suyo.
+pub fn load_texture(
+    path: impl AsRef<Path>,
+    color_space: ColorSpace,
+) -> Result<Texture, TextureError> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path)
+        .map_err(|error| TextureError::Io(format!("{}: {}", path.display(), error)))?;
+    parse_texture(&bytes, color_space)
+}
+
+/// Como `load_texture`, desde los bytes del archivo; el formato se reconoce por
+/// su firma.
+pub fn parse_texture(bytes: &[u8], color_space: ColorSpace) -> Result<Texture, TextureError> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        parse_png(bytes, color_space)
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        parse_jpeg(bytes, color_space)
+    } else if bytes.starts_with(&ktx2::IDENTIFIER) {
+        parse_ktx2(bytes)
+    } else if bytes.starts_with(b"DDS ") {
+        parse_dds(bytes)
+    } else if bytes.starts_with(b"#?RADIANCE") || bytes.starts_with(b"#?RGBE") {
+        parse_hdr(bytes)
+    } else {
+        Err(TextureError::Unsupported("unknown image format".into()))
+    }
+}
+
+/// Lector little-endian de las cabeceras de KTX2 y DDS.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    what: &'static str,
+}
+
+impl Reader<'_> {
+    fn u32(&self, offset: usize) -> Result<u32, TextureError> {
+        self.bytes
+            .get(offset..offset + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .ok_or_else(|| TextureError::Parse(format!("{} header is truncated", self.what)))
+    }
+
+    fn u64(&self, offset: usize) -> Result<u64, TextureError> {
+        Ok(self.u32(offset)? as u64 | (self.u32(offset + 4)? as u64) << 32)
+    }
+
+    fn slice(&self, offset: u64, len: u64) -> Result<&[u8], TextureError> {
+        usize::try_from(offset)
+            .ok()
+            .zip(usize::try_from(len).ok())
+            .and_then(|(start, len)| self.bytes.get(start..start.checked_add(len)?))
+            .ok_or_else(|| TextureError::Parse(format!("{} data is truncated", self.what)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::NullBackend;
+
+    #[test]
+    fn test_texture_mips_convert_and_upload() {
+        assert_eq!(mip_count(1, 1), 1);
+        assert_eq!(mip_count(256, 17), 9);
+        assert_eq!(mip_extent(8, 2, 2), (2, 1));
+
+        let white = Vec4::ONE;
+        let black = Vec4::new(0.0, 0.0, 0.0, 1.0);
+        let checker: Vec<Vec4> = (0..16)
+            .map(|i| {
+                if (i % 4 + i / 4) % 2 == 0 {
+                    white
+                } else {
+                    black
+                }
+            })
+            .collect();
+        let mut texture = Texture::from_texels(4, 4, TextureFormat::Rgba8Srgb, &checker).unwrap();
+        texture.generate_mips(MipFilter::Box);
+        assert_eq!(texture.mip_levels(), 3);
+        // El promedio se hace en lineal: gris medio lineal es 188 en sRGB, no 128.
+        assert_eq!(texture.mips[2], [188, 188, 188, 255]);
+        let linear = texture.clone().with_color_space(ColorSpace::Linear);
+        assert_eq!(linear.format, TextureFormat::Rgba8Unorm);
+        assert_eq!(linear.mips, texture.mips);
+
+        let hdr = texture.convert(TextureFormat::Rgba16Float).unwrap();
+        assert_eq!(hdr.mips.len(), 3);
+        assert_eq!(hdr.convert(TextureFormat::Rgba8Srgb).unwrap(), texture);
+        assert!(texture.convert(TextureFormat::Depth32Float).is_err());
+        assert_eq!(
+            Texture::new(2, 2, TextureFormat::Rgba8Unorm, vec![0; 15]),
+            Err(TextureError::DataLength {
+                mip_level: 0,
+                len: 15,
+                expected: 16
+            })
+        );
+
+        let mut backend = NullBackend::new(8, 8);
+        let id = texture.upload(&mut backend).unwrap();
+        backend.flush_uploads().unwrap();
+        backend.wait_idle();
+        assert_eq!(backend.texture_desc(id), Some(&texture.desc()));
+        assert_eq!(backend.texture_data(id, 2), Some(&texture.mips[2][..]));
+    }
+
+    #[test]
+    fn test_parse_texture_detects_format() {
+        let path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/textures/gradient_gray.jpg");
+        let texture = load_texture(&path, ColorSpace::Linear).unwrap();
+        assert_eq!(
+            (texture.width, texture.height, texture.format),
+            (16, 9, TextureFormat::Rgba8Unorm)
+        );
+        assert!(matches!(
+            parse_texture(b"GIF89a", ColorSpace::Srgb),
+            Err(TextureError::Unsupported(_))
+        ));
+        assert!(matches!(
+            load_texture("missing.png", ColorSpace::Srgb),
+            Err(TextureError::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,137 @@
+//! Carga de PNG.
+//!
+//! Las imágenes de 8 bits (o menos) se cargan como RGBA8 en `color_space`. Las de
+//! 16 bits se pasan a `Rgba16Float` lineal para no perder precisión (e.g. mapas
+//! de alturas), decodificando sRGB si `color_space` lo pide.
+
+use super::convert::{f32_to_f16, srgb_to_linear, with_color_space};
+use super::{ColorSpace, Texture, TextureError};
+use crate::backend::TextureFormat;
+
+/// Carga un PNG desde sus bytes.
+pub fn parse_png(bytes: &[u8], color_space: ColorSpace) -> Result<Texture, TextureError> {
+    let parse = |error: png::DecodingError| TextureError::Parse(error.to_string());
+    let mut decoder = png::Decoder::new(bytes);
+    decoder.set_transformations(png::Transformations::EXPAND);
+    let mut reader = decoder.read_info().map_err(parse)?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).map_err(parse)?;
+    buffer.truncate(info.buffer_size());
+
+    let channels = match info.color_type {
+        png::ColorType::Grayscale => 1,
+        png::ColorType::GrayscaleAlpha => 2,
+        png::ColorType::Rgb => 3,
+        png::ColorType::Rgba => 4,
+        png::ColorType::Indexed => {
+            return Err(TextureError::Parse("unexpanded indexed PNG".into()));
+        }
+    };
+    let sixteen_bit = info.bit_depth == png::BitDepth::Sixteen;
+    let samples: Vec<u16> = if sixteen_bit {
+        buffer
+            .chunks_exact(2)
+            .map(|sample| u16::from_be_bytes([sample[0], sample[1]]))
+            .collect()
+    } else {
+        buffer.iter().map(|&sample| sample as u16).collect()
+    };
+    // Gris se replica en RGB y el alfa que falta es opaco.
+    let opaque = if sixteen_bit {
+        u16::MAX
+    } else {
+        u8::MAX as u16
+    };
+    let rgba = samples.chunks_exact(channels).map(|pixel| match channels {
+        1 => [pixel[0], pixel[0], pixel[0], opaque],
+        2 => [pixel[0], pixel[0], pixel[0], pixel[1]],
+        3 => [pixel[0], pixel[1], pixel[2], opaque],
+        _ => [pixel[0], pixel[1], pixel[2], pixel[3]],
+    });
+
+    if !sixteen_bit {
+        let data = rgba.flatten().map(|sample| sample as u8).collect();
+        let format = with_color_space(TextureFormat::Rgba8Unorm, color_space);
+        return Texture::new(info.width, info.height, format, data);
+    }
+    let data = rgba
+        .flat_map(|pixel| {
+            let unorm = pixel.map(|sample| sample as f32 / u16::MAX as f32);
+            let color = |value: f32| match color_space {
+                ColorSpace::Srgb => srgb_to_linear(value),
+                ColorSpace::Linear => value,
+            };
+            [color(unorm[0]), color(unorm[1]), color(unorm[2]), unorm[3]]
+        })
+        .flat_map(|value| f32_to_f16(value).to_le_bytes())
+        .collect();
+    Texture::new(info.width, info.height, TextureFormat::Rgba16Float, data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(
+        width: u32,
+        height: u32,
+        color: png::ColorType,
+        depth: png::BitDepth,
+        data: &[u8],
+    ) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, width, height);
+        encoder.set_color(color);
+        encoder.set_depth(depth);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(data).unwrap();
+        writer.finish().unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_png_color_types_and_depths() {
+        let rgb = encode(
+            2,
+            1,
+            png::ColorType::Rgb,
+            png::BitDepth::Eight,
+            &[255, 0, 0, 0, 128, 255],
+        );
+        let texture = parse_png(&rgb, ColorSpace::Srgb).unwrap();
+        assert_eq!(texture.format, TextureFormat::Rgba8Srgb);
+        assert_eq!(texture.mips[0], [255, 0, 0, 255, 0, 128, 255, 255]);
+
+        let gray_alpha = encode(
+            1,
+            1,
+            png::ColorType::GrayscaleAlpha,
+            png::BitDepth::Eight,
+            &[7, 9],
+        );
+        let texture = parse_png(&gray_alpha, ColorSpace::Linear).unwrap();
+        assert_eq!(texture.format, TextureFormat::Rgba8Unorm);
+        assert_eq!(texture.mips[0], [7, 7, 7, 9]);
+
+        // 16 bits: lineal en half float; en sRGB se decodifica el color y no el alfa.
+        let gray16 = encode(
+            2,
+            1,
+            png::ColorType::Grayscale,
+            png::BitDepth::Sixteen,
+            &[0xFF, 0xFF, 0x80, 0x00],
+        );
+        let texture = parse_png(&gray16, ColorSpace::Linear).unwrap();
+        assert_eq!(texture.format, TextureFormat::Rgba16Float);
+        let texels = texture.texels(0);
+        assert_eq!(texels[0], glam::Vec4::ONE);
+        assert!((texels[1].x - 0.5).abs() < 1e-3 && texels[1].w == 1.0);
+        let texture = parse_png(&gray16, ColorSpace::Srgb).unwrap();
+        assert!((texture.texels(0)[1].x - 0.2141).abs() < 1e-3);
+
+        assert!(matches!(
+            parse_png(&rgb[..20], ColorSpace::Srgb),
+            Err(TextureError::Parse(_))
+        ));
+    }
+}
@@ -1,5 +1,5 @@
-use ash::vk;
 use ash::Device;
+use ash::vk;
 
 pub fn create_command_pool(device: &Device, queue_family_index: u32) -> vk::CommandPool {
     let pool_info = vk::CommandPoolCreateInfo {
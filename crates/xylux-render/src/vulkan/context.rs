@@ -1,13 +1,13 @@
-use ash::{vk, Entry, Instance, Device};
+use ash::{Device, Entry, Instance, vk};
 use ash_window::create_surface;
 use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
 use xylux_window::XyluxWindow;
 
 use super::{
+    command::{create_command_buffers, create_command_pool},
+    device::{DeviceFeatures, QueueFamilies, create_device, select_physical_device},
     instance::create_instance,
-    device::{select_physical_device, create_device},
     swapchain::create_swapchain,
-    command::{create_command_pool, create_command_buffers},
 };
 
 pub const MAX_FRAMES_IN_FLIGHT: usize = 2;
@@ -18,6 +18,12 @@ pub struct VulkanContext {
     pub device: Device,
     pub physical_device: vk::PhysicalDevice,
     pub queue: vk::Queue,
+    pub queue_families: QueueFamilies,
+    /// Cola de una familia solo de transferencias (o de cómputo), si la hay.
+    pub transfer_queue: Option<vk::Queue>,
+    /// Cola de cómputo asíncrono, si la hay.
+    pub compute_queue: Option<vk::Queue>,
+    pub features: DeviceFeatures,
     pub surface: vk::SurfaceKHR,
     swapchain_format: vk::Format,
     swapchain_extent: vk::Extent2D,
@@ -42,23 +48,43 @@ impl VulkanContext {
         let instance = create_instance(&entry, window);
 
         // 3️⃣ Crear surface multiplataforma usando tu abstracción
-        let display_handle = window.window.display_handle().expect("Failed to get display handle").into();
-        let window_handle = window.window.window_handle().expect("Failed to get window handle").into();
+        let display_handle = window
+            .window
+            .display_handle()
+            .expect("Failed to get display handle")
+            .into();
+        let window_handle = window
+            .window
+            .window_handle()
+            .expect("Failed to get window handle")
+            .into();
         let surface = unsafe {
             create_surface(&entry, &instance, display_handle, window_handle, None)
                 .expect("Failed to create Vulkan surface")
         };
 
         // 4️⃣ Elegir physical device y queue
-        let (physical_device, queue_family_index) = select_physical_device(&entry, &instance, surface);
-        let (device, queue) = create_device(&instance, physical_device, queue_family_index);
+        let (physical_device, queue_families) = select_physical_device(&entry, &instance, surface);
+        let (device, queues, features) = create_device(&instance, physical_device, &queue_families);
 
         // 5️⃣ Crear swapchain usando tu abstracción
-        let (swapchain, swapchain_format, swapchain_extent, swapchain_images, swapchain_image_views) =
-            create_swapchain(&entry, &instance, physical_device, &device, surface, window);
+        let (
+            swapchain,
+            swapchain_format,
+            swapchain_extent,
+            swapchain_images,
+            swapchain_image_views,
+        ) = create_swapchain(
+            &entry,
+            &instance,
+            physical_device,
+            &device,
+            surface,
+            window.window.size(),
+        );
 
         // 6️⃣ Crear command pool y buffers
-        let command_pool = create_command_pool(&device, queue_family_index);
+        let command_pool = create_command_pool(&device, queue_families.graphics);
         let command_buffers = create_command_buffers(&device, command_pool, MAX_FRAMES_IN_FLIGHT);
 
         // 7️⃣ Crear objetos de sincronización
@@ -85,7 +111,11 @@ impl VulkanContext {
             instance,
             device,
             physical_device,
-            queue,
+            queue: queues.graphics,
+            queue_families,
+            transfer_queue: queues.transfer,
+            compute_queue: queues.compute,
+            features,
             surface,
             swapchain,
             swapchain_format,
@@ -122,24 +152,37 @@ impl VulkanContext {
         }
     }
 
-    pub fn recreate_swapchain_resources(&mut self, window: &XyluxWindow) {
-        let (swapchain, format, extent, images, image_views) =
-            create_swapchain(&self.entry, &self.instance, self.physical_device, &self.device, self.surface, window);
+    /// Recrea el swapchain; `size` se usa si la superficie no impone un tamaño.
+    pub fn recreate_swapchain_resources(&mut self, size: (u32, u32)) {
+        let (swapchain, format, extent, images, image_views) = create_swapchain(
+            &self.entry,
+            &self.instance,
+            self.physical_device,
+            &self.device,
+            self.surface,
+            size,
+        );
 
         self.swapchain = swapchain;
         self.swapchain_format = format;
         self.swapchain_extent = extent;
         self.swapchain_images = images;
         self.swapchain_image_views = image_views;
-        self.images_in_flight = self.swapchain_images.iter().map(|_| vk::Fence::null()).collect();
+        self.images_in_flight = self
+            .swapchain_images
+            .iter()
+            .map(|_| vk::Fence::null())
+            .collect();
     }
 
     pub fn cleanup(&self) {
         unsafe {
             // 0️⃣ Destruir objetos de sincronización
             for i in 0..MAX_FRAMES_IN_FLIGHT {
-                self.device.destroy_semaphore(self.render_finished_semaphores[i], None);
-                self.device.destroy_semaphore(self.image_available_semaphores[i], None);
+                self.device
+                    .destroy_semaphore(self.render_finished_semaphores[i], None);
+                self.device
+                    .destroy_semaphore(self.image_available_semaphores[i], None);
                 self.device.destroy_fence(self.in_flight_fences[i], None);
             }
 
@@ -1,19 +1,99 @@
-use ash::{vk, Instance};
 use ash::Entry;
+use ash::{Instance, vk};
 
-pub fn select_physical_device(entry: &Entry, instance: &Instance, surface: vk::SurfaceKHR) -> (vk::PhysicalDevice, u32) {
+/// Familias de colas que usa el backend. Las de transferencia y cómputo solo se
+/// usan si son distintas de la gráfica, para que su trabajo no espere al del frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QueueFamilies {
+    pub graphics: u32,
+    pub transfer: Option<u32>,
+    pub compute: Option<u32>,
+}
+
+impl QueueFamilies {
+    /// Elige las familias de transferencia y cómputo junto a `graphics`.
+    ///
+    /// Para transferencias se prefiere una familia solo de copias (el motor DMA);
+    /// si no la hay, sirve una de cómputo, que también admite copias.
+    pub fn pick(families: &[vk::QueueFamilyProperties], graphics: u32) -> Self {
+        let candidates = || {
+            families.iter().enumerate().filter(|(index, family)| {
+                *index as u32 != graphics
+                    && family.queue_count > 0
+                    && !family.queue_flags.contains(vk::QueueFlags::GRAPHICS)
+            })
+        };
+        let transfer = candidates()
+            .find(|(_, family)| {
+                family.queue_flags.contains(vk::QueueFlags::TRANSFER)
+                    && !family.queue_flags.contains(vk::QueueFlags::COMPUTE)
+            })
+            .or_else(|| {
+                candidates()
+                    .find(|(_, family)| family.queue_flags.contains(vk::QueueFlags::COMPUTE))
+            })
+            .map(|(index, _)| index as u32);
+        let compute = candidates()
+            .find(|(_, family)| family.queue_flags.contains(vk::QueueFlags::COMPUTE))
+            .map(|(index, _)| index as u32);
+        Self {
+            graphics,
+            transfer,
+            compute,
+        }
+    }
+
+    /// Familias distintas, una cola por familia.
+    pub fn unique(&self) -> Vec<u32> {
+        let mut families = vec![self.graphics];
+        for family in [self.transfer, self.compute].into_iter().flatten() {
+            if !families.contains(&family) {
+                families.push(family);
+            }
+        }
+        families
+    }
+}
+
+/// Colas creadas con `create_device`.
+#[derive(Clone, Copy, Debug)]
+pub struct Queues {
+    pub graphics: vk::Queue,
+    pub transfer: Option<vk::Queue>,
+    pub compute: Option<vk::Queue>,
+}
+
+/// Características opcionales activadas al crear el dispositivo.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DeviceFeatures {
+    /// Filtrado anisótropo y su máximo de muestras (`1.0` si no hay).
+    pub sampler_anisotropy: bool,
+    pub max_anisotropy: f32,
+    /// Descriptor indexing para arrays de texturas bindless: elementos sin
+    /// asignar, actualizaciones con el set enlazado e índices no uniformes.
+    pub bindless: bool,
+}
+
+pub fn select_physical_device(
+    entry: &Entry,
+    instance: &Instance,
+    surface: vk::SurfaceKHR,
+) -> (vk::PhysicalDevice, QueueFamilies) {
     let devices = unsafe { instance.enumerate_physical_devices().unwrap() };
     let surface_loader = ash::khr::surface::Instance::new(entry, instance);
 
     for device in devices {
-        let queue_families = unsafe { instance.get_physical_device_queue_family_properties(device) };
+        let queue_families =
+            unsafe { instance.get_physical_device_queue_family_properties(device) };
         for (index, family) in queue_families.iter().enumerate() {
             if family.queue_flags.contains(vk::QueueFlags::GRAPHICS) {
                 let supports_surface = unsafe {
-                    surface_loader.get_physical_device_surface_support(device, index as u32, surface).unwrap()
+                    surface_loader
+                        .get_physical_device_surface_support(device, index as u32, surface)
+                        .unwrap()
                 };
                 if supports_surface {
-                    return (device, index as u32);
+                    return (device, QueueFamilies::pick(&queue_families, index as u32));
                 }
             }
         }
@@ -21,26 +101,118 @@ pub fn select_physical_device(entry: &Entry, instance: &Instance, surface: vk::S
     panic!("No suitable GPU found");
 }
 
-pub fn create_device(instance: &Instance, physical_device: vk::PhysicalDevice, queue_family_index: u32) -> (ash::Device, vk::Queue) {
-    let queue_priorities = [1.0f32];
-    let queue_info = vk::DeviceQueueCreateInfo {
-        queue_family_index,
-        queue_count: 1,
-        p_queue_priorities: queue_priorities.as_ptr(),
-        ..Default::default()
+/// Lo que el dispositivo admite de `DeviceFeatures`.
+fn supported_features(instance: &Instance, physical_device: vk::PhysicalDevice) -> DeviceFeatures {
+    let mut vulkan_12 = vk::PhysicalDeviceVulkan12Features::default();
+    let mut features = vk::PhysicalDeviceFeatures2::default().push_next(&mut vulkan_12);
+    let properties = unsafe {
+        instance.get_physical_device_features2(physical_device, &mut features);
+        instance.get_physical_device_properties(physical_device)
     };
+    let sampler_anisotropy = features.features.sampler_anisotropy == vk::TRUE;
+    DeviceFeatures {
+        sampler_anisotropy,
+        max_anisotropy: if sampler_anisotropy {
+            properties.limits.max_sampler_anisotropy
+        } else {
+            1.0
+        },
+        bindless: vulkan_12.descriptor_binding_partially_bound == vk::TRUE
+            && vulkan_12.descriptor_binding_sampled_image_update_after_bind == vk::TRUE
+            && vulkan_12.descriptor_binding_update_unused_while_pending == vk::TRUE
+            && vulkan_12.shader_sampled_image_array_non_uniform_indexing == vk::TRUE,
+    }
+}
+
+pub fn create_device(
+    instance: &Instance,
+    physical_device: vk::PhysicalDevice,
+    families: &QueueFamilies,
+) -> (ash::Device, Queues, DeviceFeatures) {
+    let queue_priorities = [1.0f32];
+    let queue_infos: Vec<vk::DeviceQueueCreateInfo> = families
+        .unique()
+        .into_iter()
+        .map(|queue_family_index| vk::DeviceQueueCreateInfo {
+            queue_family_index,
+            queue_count: 1,
+            p_queue_priorities: queue_priorities.as_ptr(),
+            ..Default::default()
+        })
+        .collect();
 
     let device_extensions = [ash::khr::swapchain::NAME.as_ptr()];
 
+    // Las subidas se sincronizan con un timeline semaphore (Vulkan 1.2).
+    let features = supported_features(instance, physical_device);
+    let mut vulkan_12_features = vk::PhysicalDeviceVulkan12Features::default()
+        .timeline_semaphore(true)
+        .descriptor_binding_partially_bound(features.bindless)
+        .descriptor_binding_sampled_image_update_after_bind(features.bindless)
+        .descriptor_binding_update_unused_while_pending(features.bindless)
+        .shader_sampled_image_array_non_uniform_indexing(features.bindless);
+    let enabled_features = vk::PhysicalDeviceFeatures {
+        sampler_anisotropy: features.sampler_anisotropy as vk::Bool32,
+        ..Default::default()
+    };
+
     let device_info = vk::DeviceCreateInfo {
-        queue_create_info_count: 1,
-        p_queue_create_infos: &queue_info,
+        queue_create_info_count: queue_infos.len() as u32,
+        p_queue_create_infos: queue_infos.as_ptr(),
         enabled_extension_count: device_extensions.len() as u32,
         pp_enabled_extension_names: device_extensions.as_ptr(),
+        p_enabled_features: &enabled_features,
         ..Default::default()
+    }
+    .push_next(&mut vulkan_12_features);
+
+    let device = unsafe {
+        instance
+            .create_device(physical_device, &device_info, None)
+            .unwrap()
+    };
+    let queue = |family: u32| unsafe { device.get_device_queue(family, 0) };
+    let queues = Queues {
+        graphics: queue(families.graphics),
+        transfer: families.transfer.map(queue),
+        compute: families.compute.map(queue),
     };
+    (device, queues, features)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn family(queue_flags: vk::QueueFlags) -> vk::QueueFamilyProperties {
+        vk::QueueFamilyProperties {
+            queue_flags,
+            queue_count: 1,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_queue_family_discovery() {
+        let all = vk::QueueFlags::GRAPHICS | vk::QueueFlags::COMPUTE | vk::QueueFlags::TRANSFER;
+        let compute = vk::QueueFlags::COMPUTE | vk::QueueFlags::TRANSFER;
+        // Típico de GPUs dedicadas: gráfica, cómputo asíncrono y DMA.
+        let families = [
+            family(all),
+            family(compute),
+            family(vk::QueueFlags::TRANSFER),
+        ];
+        let picked = QueueFamilies::pick(&families, 0);
+        assert_eq!((picked.transfer, picked.compute), (Some(2), Some(1)));
+        assert_eq!(picked.unique(), [0, 2, 1]);
 
-    let device = unsafe { instance.create_device(physical_device, &device_info, None).unwrap() };
-    let queue = unsafe { device.get_device_queue(queue_family_index, 0) };
-    (device, queue)
+        // Sin familia de copias, las transferencias van a la de cómputo.
+        let picked = QueueFamilies::pick(&families[..2], 0);
+        assert_eq!((picked.transfer, picked.compute), (Some(1), Some(1)));
+        assert_eq!(picked.unique(), [0, 1]);
+
+        // Una sola familia: todo en la gráfica.
+        let picked = QueueFamilies::pick(&[family(all), family(all)], 0);
+        assert_eq!((picked.transfer, picked.compute), (None, None));
+    }
 }
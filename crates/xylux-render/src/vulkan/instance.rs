@@ -1,6 +1,6 @@
-use ash::{vk, Entry, Instance};
+use ash::{Entry, Instance, vk};
+use std::ffi::{CStr, CString, c_char};
 use xylux_window::XyluxWindow;
-use std::ffi::{c_char, CStr, CString};
 
 /// Crea la instancia Vulkan usando XyluxWindow
 pub fn create_instance(entry: &Entry, window: &XyluxWindow) -> Instance {
@@ -77,11 +77,9 @@ pub fn create_instance(entry: &Entry, window: &XyluxWindow) -> Instance {
     };
 
     // Crear instancia Vulkan
-    let instance = unsafe {
+    unsafe {
         entry
             .create_instance(&create_info, None)
             .expect("Failed to create Vulkan instance")
-    };
-
-    instance
+    }
 }
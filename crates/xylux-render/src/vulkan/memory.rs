@@ -0,0 +1,136 @@
+use ash::{Device, Instance, vk};
+
+use crate::allocator::{AllocationError, DeviceHeap, MemoryFlags, MemoryType};
+
+/// Busca un tipo de memoria compatible con `type_bits` que tenga las propiedades `flags`.
+pub fn find_memory_type(
+    instance: &Instance,
+    physical_device: vk::PhysicalDevice,
+    type_bits: u32,
+    flags: vk::MemoryPropertyFlags,
+) -> Option<u32> {
+    let properties = unsafe { instance.get_physical_device_memory_properties(physical_device) };
+    (0..properties.memory_type_count).find(|&index| {
+        type_bits & (1 << index) != 0
+            && properties.memory_types[index as usize]
+                .property_flags
+                .contains(flags)
+    })
+}
+
+/// Reserva memoria para `requirements` con las propiedades `flags`.
+pub fn allocate_memory(
+    instance: &Instance,
+    physical_device: vk::PhysicalDevice,
+    device: &Device,
+    requirements: vk::MemoryRequirements,
+    flags: vk::MemoryPropertyFlags,
+) -> vk::DeviceMemory {
+    let memory_type_index = find_memory_type(
+        instance,
+        physical_device,
+        requirements.memory_type_bits,
+        flags,
+    )
+    .expect("Failed to find a suitable memory type");
+    let allocate_info = vk::MemoryAllocateInfo {
+        allocation_size: requirements.size,
+        memory_type_index,
+        ..Default::default()
+    };
+    unsafe {
+        device
+            .allocate_memory(&allocate_info, None)
+            .expect("Failed to allocate device memory")
+    }
+}
+
+/// `DeviceHeap` de un dispositivo Vulkan para el `GpuAllocator`.
+pub struct VulkanHeap {
+    device: Device,
+    memory_types: Vec<MemoryType>,
+}
+
+impl VulkanHeap {
+    pub fn new(instance: &Instance, physical_device: vk::PhysicalDevice, device: &Device) -> Self {
+        let properties = unsafe { instance.get_physical_device_memory_properties(physical_device) };
+        let memory_types = properties.memory_types[..properties.memory_type_count as usize]
+            .iter()
+            .map(|memory_type| MemoryType {
+                flags: memory_flags(memory_type.property_flags),
+                heap_index: memory_type.heap_index,
+            })
+            .collect();
+        Self {
+            device: device.clone(),
+            memory_types,
+        }
+    }
+}
+
+fn memory_flags(flags: vk::MemoryPropertyFlags) -> MemoryFlags {
+    [
+        (
+            vk::MemoryPropertyFlags::DEVICE_LOCAL,
+            MemoryFlags::DEVICE_LOCAL,
+        ),
+        (
+            vk::MemoryPropertyFlags::HOST_VISIBLE,
+            MemoryFlags::HOST_VISIBLE,
+        ),
+        (
+            vk::MemoryPropertyFlags::HOST_COHERENT,
+            MemoryFlags::HOST_COHERENT,
+        ),
+        (
+            vk::MemoryPropertyFlags::HOST_CACHED,
+            MemoryFlags::HOST_CACHED,
+        ),
+    ]
+    .into_iter()
+    .filter(|(vk_flag, _)| flags.contains(*vk_flag))
+    .fold(MemoryFlags::empty(), |acc, (_, flag)| acc | flag)
+}
+
+fn allocation_error(result: vk::Result) -> AllocationError {
+    match result {
+        vk::Result::ERROR_OUT_OF_DEVICE_MEMORY | vk::Result::ERROR_OUT_OF_HOST_MEMORY => {
+            AllocationError::OutOfMemory
+        }
+        error => AllocationError::Device(error.to_string()),
+    }
+}
+
+impl DeviceHeap for VulkanHeap {
+    type Memory = vk::DeviceMemory;
+
+    fn memory_types(&self) -> &[MemoryType] {
+        &self.memory_types
+    }
+
+    fn allocate(
+        &mut self,
+        memory_type: u32,
+        size: u64,
+    ) -> Result<vk::DeviceMemory, AllocationError> {
+        let allocate_info = vk::MemoryAllocateInfo {
+            allocation_size: size,
+            memory_type_index: memory_type,
+            ..Default::default()
+        };
+        unsafe { self.device.allocate_memory(&allocate_info, None) }.map_err(allocation_error)
+    }
+
+    fn free(&mut self, memory: vk::DeviceMemory) {
+        unsafe { self.device.free_memory(memory, None) };
+    }
+
+    fn map(&mut self, memory: vk::DeviceMemory) -> Result<*mut u8, AllocationError> {
+        unsafe {
+            self.device
+                .map_memory(memory, 0, vk::WHOLE_SIZE, vk::MemoryMapFlags::empty())
+                .map(|mapped| mapped as *mut u8)
+                .map_err(allocation_error)
+        }
+    }
+}
@@ -1,8 +1,10 @@
+pub mod command;
 pub mod context;
-pub mod instance;
 pub mod device;
+pub mod instance;
+pub mod memory;
 pub mod swapchain;
-pub mod command;
+pub mod transfer;
 
-pub use swapchain::*;
 pub use command::*;
+pub use swapchain::*;
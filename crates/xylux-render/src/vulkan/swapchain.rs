@@ -1,6 +1,5 @@
-use ash::{vk, Entry, Instance, Device};
 use ash::khr::{surface, swapchain};
-use xylux_window::XyluxWindow;
+use ash::{Device, Entry, Instance, vk};
 
 pub fn create_swapchain(
     entry: &Entry,
@@ -8,9 +7,14 @@ pub fn create_swapchain(
     physical_device: vk::PhysicalDevice,
     device: &Device,
     surface: vk::SurfaceKHR,
-    window: &XyluxWindow,
-) -> (vk::SwapchainKHR, vk::Format, vk::Extent2D, Vec<vk::Image>, Vec<vk::ImageView>) {
-
+    fallback_size: (u32, u32),
+) -> (
+    vk::SwapchainKHR,
+    vk::Format,
+    vk::Extent2D,
+    Vec<vk::Image>,
+    Vec<vk::ImageView>,
+) {
     let surface_loader = surface::Instance::new(entry, instance);
 
     let formats = unsafe {
@@ -20,7 +24,10 @@ pub fn create_swapchain(
     };
     let surface_format = formats
         .iter()
-        .find(|f| f.format == vk::Format::B8G8R8A8_SRGB && f.color_space == vk::ColorSpaceKHR::SRGB_NONLINEAR)
+        .find(|f| {
+            f.format == vk::Format::B8G8R8A8_SRGB
+                && f.color_space == vk::ColorSpaceKHR::SRGB_NONLINEAR
+        })
         .cloned()
         .unwrap_or(formats[0]);
 
@@ -44,16 +51,24 @@ pub fn create_swapchain(
     let extent = if capabilities.current_extent.width != u32::MAX {
         capabilities.current_extent
     } else {
-        let (width, height) = window.window.size();
+        let (width, height) = fallback_size;
         vk::Extent2D {
-            width: width.clamp(capabilities.min_image_extent.width, capabilities.max_image_extent.width),
-            height: height.clamp(capabilities.min_image_extent.height, capabilities.max_image_extent.height),
+            width: width.clamp(
+                capabilities.min_image_extent.width,
+                capabilities.max_image_extent.width,
+            ),
+            height: height.clamp(
+                capabilities.min_image_extent.height,
+                capabilities.max_image_extent.height,
+            ),
         }
     };
 
-    let image_count = (capabilities.min_image_count + 1).min(
-        if capabilities.max_image_count > 0 { capabilities.max_image_count } else { u32::MAX },
-    );
+    let image_count = (capabilities.min_image_count + 1).min(if capabilities.max_image_count > 0 {
+        capabilities.max_image_count
+    } else {
+        u32::MAX
+    });
 
     let swapchain_info = vk::SwapchainCreateInfoKHR {
         surface,
@@ -72,8 +87,16 @@ pub fn create_swapchain(
     };
 
     let swapchain_loader = swapchain::Device::new(instance, device);
-    let swapchain = unsafe { swapchain_loader.create_swapchain(&swapchain_info, None).expect("Failed to create swapchain") };
-    let swapchain_images = unsafe { swapchain_loader.get_swapchain_images(swapchain).expect("Failed to get swapchain images") };
+    let swapchain = unsafe {
+        swapchain_loader
+            .create_swapchain(&swapchain_info, None)
+            .expect("Failed to create swapchain")
+    };
+    let swapchain_images = unsafe {
+        swapchain_loader
+            .get_swapchain_images(swapchain)
+            .expect("Failed to get swapchain images")
+    };
 
     let swapchain_image_views: Vec<vk::ImageView> = swapchain_images
         .iter()
@@ -92,18 +115,24 @@ pub fn create_swapchain(
                 },
                 ..Default::default()
             };
-            unsafe { device.create_image_view(&create_info, None).expect("Failed to create image view") }
+            unsafe {
+                device
+                    .create_image_view(&create_info, None)
+                    .expect("Failed to create image view")
+            }
         })
         .collect();
 
-    (swapchain, surface_format.format, extent, swapchain_images, swapchain_image_views)
+    (
+        swapchain,
+        surface_format.format,
+        extent,
+        swapchain_images,
+        swapchain_image_views,
+    )
 }
 
-pub fn destroy_swapchain(
-    instance: &Instance,
-    device: &Device,
-    swapchain: vk::SwapchainKHR,
-) {
+pub fn destroy_swapchain(instance: &Instance, device: &Device, swapchain: vk::SwapchainKHR) {
     let swapchain_loader = swapchain::Device::new(instance, device);
     unsafe { swapchain_loader.destroy_swapchain(swapchain, None) };
 }
@@ -0,0 +1,340 @@
+use std::collections::VecDeque;
+
+use ash::{Device, vk};
+
+use super::command::create_command_pool;
+use super::context::VulkanContext;
+use crate::backend::UploadTicket;
+
+/// Etapas y accesos con los que el frame lee los recursos subidos.
+const READ_STAGES: vk::PipelineStageFlags = vk::PipelineStageFlags::from_raw(
+    vk::PipelineStageFlags::VERTEX_INPUT.as_raw()
+        | vk::PipelineStageFlags::VERTEX_SHADER.as_raw()
+        | vk::PipelineStageFlags::FRAGMENT_SHADER.as_raw(),
+);
+const BUFFER_READ_ACCESS: vk::AccessFlags = vk::AccessFlags::from_raw(
+    vk::AccessFlags::VERTEX_ATTRIBUTE_READ.as_raw()
+        | vk::AccessFlags::INDEX_READ.as_raw()
+        | vk::AccessFlags::UNIFORM_READ.as_raw()
+        | vk::AccessFlags::SHADER_READ.as_raw(),
+);
+
+/// Cola en la que se ejecutan las subidas, con un timeline semaphore cuyo valor es
+/// el último `UploadTicket` terminado.
+///
+/// Si la cola es de otra familia que la gráfica, cada recurso subido cambia de
+/// dueño: el batch graba la barrera de release y el siguiente frame la de acquire
+/// (`record_acquires`), después de esperar al semaphore.
+pub struct TransferQueue {
+    pub family: u32,
+    pub queue: vk::Queue,
+    graphics_family: u32,
+    command_pool: vk::CommandPool,
+    pub timeline: vk::Semaphore,
+    /// Command buffers de los batches enviados y sin terminar.
+    in_flight: VecDeque<(UploadTicket, vk::CommandBuffer)>,
+    acquire_buffers: Vec<vk::BufferMemoryBarrier<'static>>,
+    acquire_images: Vec<vk::ImageMemoryBarrier<'static>>,
+}
+
+impl TransferQueue {
+    /// Usa la cola de transferencias del contexto o, si no la hay, la gráfica.
+    pub fn new(context: &VulkanContext) -> Self {
+        let families = context.queue_families;
+        let (family, queue) = match (families.transfer, context.transfer_queue) {
+            (Some(family), Some(queue)) => (family, queue),
+            _ => (families.graphics, context.queue),
+        };
+        let mut type_info = vk::SemaphoreTypeCreateInfo::default()
+            .semaphore_type(vk::SemaphoreType::TIMELINE)
+            .initial_value(0);
+        let semaphore_info = vk::SemaphoreCreateInfo::default().push_next(&mut type_info);
+        let timeline = unsafe {
+            context
+                .device
+                .create_semaphore(&semaphore_info, None)
+                .expect("Failed to create upload timeline semaphore")
+        };
+        Self {
+            family,
+            queue,
+            graphics_family: families.graphics,
+            command_pool: create_command_pool(&context.device, family),
+            timeline,
+            in_flight: VecDeque::new(),
+            acquire_buffers: Vec::new(),
+            acquire_images: Vec::new(),
+        }
+    }
+
+    /// Si los recursos tienen que cambiar de familia de cola.
+    pub fn transfers_ownership(&self) -> bool {
+        self.family != self.graphics_family
+    }
+
+    /// Familias de origen y destino de las barreras de release y acquire.
+    fn families(&self) -> (u32, u32) {
+        if self.transfers_ownership() {
+            (self.family, self.graphics_family)
+        } else {
+            (vk::QUEUE_FAMILY_IGNORED, vk::QUEUE_FAMILY_IGNORED)
+        }
+    }
+
+    /// Último ticket terminado por la GPU.
+    pub fn completed(&self, device: &Device) -> UploadTicket {
+        let value = unsafe { device.get_semaphore_counter_value(self.timeline) }
+            .expect("Failed to read upload timeline semaphore");
+        UploadTicket(value)
+    }
+
+    /// Bloquea hasta que la GPU termine el batch de `ticket`.
+    pub fn wait(&self, device: &Device, ticket: UploadTicket) {
+        let semaphores = [self.timeline];
+        let values = [ticket.0];
+        let wait_info = vk::SemaphoreWaitInfo::default()
+            .semaphores(&semaphores)
+            .values(&values);
+        unsafe { device.wait_semaphores(&wait_info, u64::MAX) }
+            .expect("Failed to wait for upload timeline semaphore");
+    }
+
+    /// Libera los command buffers de los batches terminados hasta `completed`.
+    pub fn retire(&mut self, device: &Device, completed: UploadTicket) {
+        while let Some(&(ticket, command_buffer)) = self.in_flight.front() {
+            if ticket > completed {
+                break;
+            }
+            self.in_flight.pop_front();
+            unsafe { device.free_command_buffers(self.command_pool, &[command_buffer]) };
+        }
+    }
+
+    /// Empieza el command buffer de un batch.
+    pub fn begin(&self, device: &Device) -> vk::CommandBuffer {
+        let alloc_info = vk::CommandBufferAllocateInfo {
+            command_pool: self.command_pool,
+            level: vk::CommandBufferLevel::PRIMARY,
+            command_buffer_count: 1,
+            ..Default::default()
+        };
+        let begin_info = vk::CommandBufferBeginInfo {
+            flags: vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT,
+            ..Default::default()
+        };
+        unsafe {
+            let command_buffer = device
+                .allocate_command_buffers(&alloc_info)
+                .expect("Failed to allocate upload command buffer")[0];
+            device
+                .begin_command_buffer(command_buffer, &begin_info)
+                .expect("Failed to begin upload command buffer");
+            command_buffer
+        }
+    }
+
+    /// Copia `region` de `staging` a `buffer` y deja el buffer listo para el frame.
+    pub fn copy_buffer(
+        &mut self,
+        device: &Device,
+        command_buffer: vk::CommandBuffer,
+        staging: vk::Buffer,
+        buffer: vk::Buffer,
+        region: vk::BufferCopy,
+    ) {
+        let (src_queue_family_index, dst_queue_family_index) = self.families();
+        let release = vk::BufferMemoryBarrier {
+            src_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+            dst_access_mask: if self.transfers_ownership() {
+                vk::AccessFlags::empty()
+            } else {
+                BUFFER_READ_ACCESS
+            },
+            src_queue_family_index,
+            dst_queue_family_index,
+            buffer,
+            offset: region.dst_offset,
+            size: region.size,
+            ..Default::default()
+        };
+        unsafe {
+            device.cmd_copy_buffer(command_buffer, staging, buffer, &[region]);
+            device.cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::TRANSFER,
+                self.release_stages(),
+                vk::DependencyFlags::empty(),
+                &[],
+                &[release],
+                &[],
+            );
+        }
+        if self.transfers_ownership() {
+            self.acquire_buffers.push(vk::BufferMemoryBarrier {
+                src_access_mask: vk::AccessFlags::empty(),
+                dst_access_mask: BUFFER_READ_ACCESS,
+                ..release
+            });
+        }
+    }
+
+    /// Copia `region` de `staging` a un nivel de mip de `image` y lo deja en
+    /// `SHADER_READ_ONLY_OPTIMAL` para el frame.
+    pub fn copy_image(
+        &mut self,
+        device: &Device,
+        command_buffer: vk::CommandBuffer,
+        staging: vk::Buffer,
+        image: vk::Image,
+        region: vk::BufferImageCopy,
+    ) {
+        let subresource = region.image_subresource;
+        let subresource_range = vk::ImageSubresourceRange {
+            aspect_mask: subresource.aspect_mask,
+            base_mip_level: subresource.mip_level,
+            level_count: 1,
+            base_array_layer: subresource.base_array_layer,
+            layer_count: subresource.layer_count,
+        };
+        let to_transfer = vk::ImageMemoryBarrier {
+            old_layout: vk::ImageLayout::UNDEFINED,
+            new_layout: vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            dst_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+            src_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+            dst_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+            image,
+            subresource_range,
+            ..Default::default()
+        };
+        let (src_queue_family_index, dst_queue_family_index) = self.families();
+        let release = vk::ImageMemoryBarrier {
+            old_layout: vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            new_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+            src_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+            dst_access_mask: if self.transfers_ownership() {
+                vk::AccessFlags::empty()
+            } else {
+                vk::AccessFlags::SHADER_READ
+            },
+            src_queue_family_index,
+            dst_queue_family_index,
+            ..to_transfer
+        };
+        unsafe {
+            device.cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::TOP_OF_PIPE,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[to_transfer],
+            );
+            device.cmd_copy_buffer_to_image(
+                command_buffer,
+                staging,
+                image,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                &[region],
+            );
+            device.cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::TRANSFER,
+                self.release_stages(),
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[release],
+            );
+        }
+        if self.transfers_ownership() {
+            self.acquire_images.push(vk::ImageMemoryBarrier {
+                src_access_mask: vk::AccessFlags::empty(),
+                dst_access_mask: vk::AccessFlags::SHADER_READ,
+                ..release
+            });
+        }
+    }
+
+    /// Una cola solo de transferencias no admite etapas gráficas: allí el release
+    /// acaba en `BOTTOM_OF_PIPE` y el acquire del frame hace el resto.
+    fn release_stages(&self) -> vk::PipelineStageFlags {
+        if self.transfers_ownership() {
+            vk::PipelineStageFlags::BOTTOM_OF_PIPE
+        } else {
+            READ_STAGES
+        }
+    }
+
+    /// Termina y envía el batch; el timeline pasa a `ticket` cuando acaba.
+    pub fn submit(
+        &mut self,
+        device: &Device,
+        command_buffer: vk::CommandBuffer,
+        ticket: UploadTicket,
+    ) {
+        let command_buffers = [command_buffer];
+        let signal_semaphores = [self.timeline];
+        let signal_values = [ticket.0];
+        let mut timeline_info =
+            vk::TimelineSemaphoreSubmitInfo::default().signal_semaphore_values(&signal_values);
+        let submit_info = vk::SubmitInfo::default()
+            .command_buffers(&command_buffers)
+            .signal_semaphores(&signal_semaphores)
+            .push_next(&mut timeline_info);
+        unsafe {
+            device
+                .end_command_buffer(command_buffer)
+                .expect("Failed to end upload command buffer");
+            device
+                .queue_submit(self.queue, &[submit_info], vk::Fence::null())
+                .expect("Failed to submit uploads");
+        }
+        self.in_flight.push_back((ticket, command_buffer));
+    }
+
+    /// Graba en el command buffer del frame las barreras de acquire de los
+    /// recursos subidos desde el último frame. Siguen pendientes hasta
+    /// `clear_acquires`, por si el command buffer se vuelve a grabar.
+    pub fn record_acquires(&self, device: &Device, command_buffer: vk::CommandBuffer) {
+        if self.acquire_buffers.is_empty() && self.acquire_images.is_empty() {
+            return;
+        }
+        unsafe {
+            device.cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::TOP_OF_PIPE,
+                READ_STAGES,
+                vk::DependencyFlags::empty(),
+                &[],
+                &self.acquire_buffers,
+                &self.acquire_images,
+            );
+        }
+    }
+
+    /// Olvida los acquires ya grabados en un command buffer enviado.
+    pub fn clear_acquires(&mut self) {
+        self.acquire_buffers.clear();
+        self.acquire_images.clear();
+    }
+
+    /// Descarta los acquires pendientes de un buffer que se va a destruir.
+    pub fn discard_buffer(&mut self, buffer: vk::Buffer) {
+        self.acquire_buffers
+            .retain(|barrier| barrier.buffer != buffer);
+    }
+
+    /// Descarta los acquires pendientes de una imagen que se va a destruir.
+    pub fn discard_image(&mut self, image: vk::Image) {
+        self.acquire_images.retain(|barrier| barrier.image != image);
+    }
+
+    pub fn cleanup(&mut self, device: &Device) {
+        self.in_flight.clear();
+        unsafe {
+            device.destroy_command_pool(self.command_pool, None);
+            device.destroy_semaphore(self.timeline, None);
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use glam::{Quat, Vec3};
+use std::time::Instant;
+use xylux_ecs::{Transform, World};
+use xylux_render::{Camera, Material, Mesh, MeshRenderer, Renderer};
+use xylux_window::XyluxWindow;
+
+// --- MAIN ---
+
+fn main() {
+    // Crear ventana y gestionar el loop internamente
+    let mut xwindow = XyluxWindow::new("Xylux: Hello Cube", 800, 600);
+
+    // Inicializar renderer
+    let mut renderer = Renderer::new(&xwindow);
+
+    // Crear mundo ECS y registrar componentes
+    let mut world = World::new(1000);
+    world.register_component::<Transform>();
+    world.register_component::<MeshRenderer>();
+    world.register_component::<Camera>();
+
+    // Cámara en +Z mirando hacia el origen.
+    let camera = world.spawn_entity();
+    world.insert(camera, Camera::default());
+    world.insert(
+        camera,
+        Transform {
+            position: Vec3::new(0.0, 0.0, 3.0),
+            ..Default::default()
+        },
+    );
+
+    // Un cubo en el origen con cada cara coloreada según su normal.
+    let mut cube = Mesh::cube(1.0, 1);
+    cube.colors = cube
+        .normals
+        .iter()
+        .map(|n| (*n * 0.5 + 0.5).extend(1.0))
+        .collect();
+    let mesh = renderer.add_mesh(cube).expect("procedural cube is valid");
+    let material = renderer.add_material(Material::default());
+    let entity = world.spawn_entity();
+    world.insert(entity, Transform::default());
+    world.insert(entity, MeshRenderer::new(mesh, material));
+
+    // Ejecutar loop principal usando nuestra abstracción
+    let start = Instant::now();
+    let mut size = xwindow.window.size();
+    xwindow.run_loop(|window| {
+        if window.window.size() != size {
+            size = window.window.size();
+            renderer.resize(size.0, size.1);
+        }
+        let time = start.elapsed().as_secs_f32();
+        if let Some(transform) = world.get_mut::<Transform>(entity) {
+            transform.rotation = Quat::from_euler(glam::EulerRot::YXZ, time, time * 0.7, 0.0);
+        }
+        renderer.render(&mut world);
+    });
+
+    // Limpiar recursos al salir
+    renderer.cleanup();
+}
@@ -1,5 +1,6 @@
-use xylux_render::{Renderer};
-use xylux_ecs::{World, Transform};
+use glam::{Vec3, Vec4};
+use xylux_ecs::{Transform, World};
+use xylux_render::{Material, Mesh, MeshRenderer, Renderer};
 use xylux_window::XyluxWindow;
 
 // --- MAIN ---
@@ -14,10 +15,32 @@ fn main() {
     // Crear mundo ECS y registrar componentes
     let mut world = World::new(1000);
     world.register_component::<Transform>();
+    world.register_component::<MeshRenderer>();
+
+    // Un triángulo verde en el centro de la pantalla
+    let mesh = renderer
+        .add_mesh(Mesh::new(
+            vec![
+                Vec3::new(0.0, -0.5, 0.0),
+                Vec3::new(0.5, 0.5, 0.0),
+                Vec3::new(-0.5, 0.5, 0.0),
+            ],
+            vec![0, 2, 1],
+        ))
+        .expect("triangle mesh is valid");
+    let material = renderer.add_material(Material::new(Vec4::new(0.0, 1.0, 0.0, 1.0)));
+    let triangle = world.spawn_entity();
+    world.insert(triangle, Transform::default());
+    world.insert(triangle, MeshRenderer::new(mesh, material));
 
     // Ejecutar loop principal usando nuestra abstracción
+    let mut size = xwindow.window.size();
     xwindow.run_loop(|window| {
-        renderer.render(&mut world, window);
+        if window.window.size() != size {
+            size = window.window.size();
+            renderer.resize(size.0, size.1);
+        }
+        renderer.render(&mut world);
     });
 
     // Limpiar recursos al salir